@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE txos DROP COLUMN missing_required_memo;
+ALTER TABLE accounts DROP COLUMN require_sender_memo;
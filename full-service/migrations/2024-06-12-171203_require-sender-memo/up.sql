@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE accounts
+    ADD COLUMN require_sender_memo BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE txos
+    ADD COLUMN missing_required_memo BOOLEAN NOT NULL DEFAULT FALSE;
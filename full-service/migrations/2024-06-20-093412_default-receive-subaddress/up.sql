@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE accounts
+    ADD COLUMN default_receive_subaddress_index BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE transaction_logs
+    ADD COLUMN conflicting BOOLEAN NOT NULL DEFAULT FALSE;
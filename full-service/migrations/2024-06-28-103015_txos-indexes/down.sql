@@ -0,0 +1,3 @@
+DROP INDEX idx_txos__public_key;
+DROP INDEX idx_txos__account_id;
+DROP INDEX idx_txos__account_id_token_id_spent_block_index;
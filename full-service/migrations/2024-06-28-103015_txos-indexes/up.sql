@@ -0,0 +1,3 @@
+CREATE INDEX idx_txos__public_key ON txos (public_key);
+CREATE INDEX idx_txos__account_id ON txos (account_id);
+CREATE INDEX idx_txos__account_id_token_id_spent_block_index ON txos (account_id, token_id, spent_block_index);
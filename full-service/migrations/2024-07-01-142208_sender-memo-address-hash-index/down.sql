@@ -0,0 +1 @@
+DROP INDEX idx_authenticated_sender_memos__sender_address_hash;
@@ -0,0 +1 @@
+CREATE INDEX idx_authenticated_sender_memos__sender_address_hash ON authenticated_sender_memos (sender_address_hash);
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE accounts
+    ADD COLUMN import_in_progress BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE accounts
+    ADD COLUMN account_index BIGINT NOT NULL DEFAULT 0;
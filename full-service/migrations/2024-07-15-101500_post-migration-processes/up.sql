@@ -0,0 +1,5 @@
+-- Your SQL goes here
+CREATE TABLE post_migration_processes (
+    migration_version TEXT PRIMARY KEY NOT NULL,
+    has_run BOOLEAN NOT NULL DEFAULT FALSE
+);
@@ -8,17 +8,32 @@ use diesel::{connection::SimpleConnection, prelude::*, SqliteConnection};
 use dotenv::dotenv;
 use mc_attest_core::MrSigner;
 use mc_attestation_verifier::{TrustedIdentity, TrustedMrSignerIdentity};
-use mc_common::logger::{create_app_logger, log, o, Logger};
-use mc_connection::ConnectionManager;
+use mc_blockchain_types::BlockData;
+use mc_common::logger::{log, Logger};
+use mc_connection::{BlockchainConnection, ConnectionManager};
 use mc_consensus_scp::QuorumSet;
 use mc_fog_report_resolver::FogResolver;
 use mc_full_service::{
+    check_chain_id::{check_ledger_chain_id, check_peers_chain_id},
     check_host,
-    config::{APIConfig, NetworkConfig, WebhookConfig},
-    wallet::{consensus_backed_rocket, validator_backed_rocket, APIKeyState, WalletState},
-    ValidatorLedgerSyncThread, WalletDb, WalletService,
+    config::{
+        APIConfig, LedgerDbConfig, LedgerOriginError, NetworkConfig, PollInterval, WebhookConfig,
+    },
+    json_rpc::{
+        audit_log::{AuditLog, AuditLogState},
+        rate_limit::{RateLimitState, RateLimiter},
+    },
+    log_filter::{create_filtered_app_logger, LOG_FILTER},
+    service::block_info_cache::BlockInfoCache,
+    wallet::{
+        consensus_backed_rocket, validator_backed_rocket, APIKeyState, GracefulShutdown,
+        HealthCheckState, MethodFilterState, WalletState,
+    },
+    ConsensusLedgerSyncThread, ReloadableTransactionsFetcher, ValidatorLedgerSyncThread, WalletDb,
+    WalletService,
 };
-use mc_ledger_sync::{LedgerSyncServiceThread, PollingNetworkState, ReqwestTransactionsFetcher};
+use mc_ledger_db::LedgerDB;
+use mc_ledger_sync::PollingNetworkState;
 use mc_util_uri::ConnectionUri;
 use mc_validator_api::ValidatorUri;
 use mc_validator_connection::ValidatorConnection;
@@ -40,6 +55,9 @@ extern crate diesel_migrations;
 const EXIT_NO_DATABASE_CONNECTION: i32 = 2;
 const EXIT_WRONG_PASSWORD: i32 = 3;
 const EXIT_INVALID_HOST: i32 = 4;
+const EXIT_NO_AUDIT_LOG: i32 = 5;
+const EXIT_WRONG_NETWORK_LEDGER: i32 = 6;
+const EXIT_CHAIN_ID_MISMATCH: i32 = 7;
 
 #[launch]
 fn rocket() -> Rocket<Build> {
@@ -60,11 +78,10 @@ fn rocket() -> Rocket<Build> {
         exit(EXIT_INVALID_HOST);
     }
 
-    let (logger, global_logger_guard) = create_app_logger(o!());
-
-    // This is necessary to prevent the logger from being reset when it goes out of
-    // scope so that rocket can use it in its own async context
-    global_logger_guard.cancel_reset();
+    // Records are filtered by LOG_FILTER, so that log levels can be changed while
+    // the wallet is running.
+    LOG_FILTER.set_spec_from_env();
+    let logger = create_filtered_app_logger();
 
     let wallet_db = match config.wallet_db {
         Some(ref wallet_db_path_buf) => {
@@ -88,18 +105,47 @@ fn rocket() -> Rocket<Build> {
             conn.batch_execute("PRAGMA foreign_keys = ON;")
                 .expect("failed enabling foreign keys");
             WalletDb::run_proto_conversions_if_necessary(conn);
+            WalletDb::run_shared_secret_backfill(conn);
             log::info!(logger, "Connected to database.");
 
-            Some(WalletDb::new_from_url(wallet_db_path, 10).expect("Could not access wallet db"))
+            let wallet_db = WalletDb::new_from_url(wallet_db_path, config.wallet_db_pool_size)
+                .expect("Could not access wallet db");
+
+            // Pooled connections enable WAL mode once the encryption key is set, so that
+            // reads are not blocked by the sync thread's writes.
+            let journal_mode = WalletDb::journal_mode(
+                &mut wallet_db
+                    .get_pooled_conn()
+                    .expect("Could not access wallet db"),
+            )
+            .expect("failed querying journal mode");
+            if journal_mode == "wal" {
+                log::info!(logger, "Wallet db journal mode: {}", journal_mode);
+            } else {
+                log::warn!(
+                    logger,
+                    "Wallet db journal mode is {}, not wal. Reads may wait for writes.",
+                    journal_mode
+                );
+            }
+
+            Some(wallet_db)
         }
         None => None,
     };
 
-    let rocket_config = rocket::Config {
+    let mut rocket_config = rocket::Config {
         address: IpAddr::from_str(&config.listen_host).expect("failed parsing host"),
         port: config.listen_port,
+        shutdown: rocket::config::Shutdown {
+            grace: config.shutdown_grace_period.as_secs() as u32,
+            ..rocket::config::Shutdown::default()
+        },
         ..rocket::Config::default()
     };
+    if let Some(num_workers) = config.num_workers {
+        rocket_config.workers = num_workers as usize;
+    }
 
     let chain_id = config.peers_config.chain_id.clone();
     let tx_sources: Option<Vec<String>> = config.peers_config.tx_source_urls.clone();
@@ -144,7 +190,22 @@ fn rocket() -> Rocket<Build> {
     };
 
     let api_key = env::var("MC_API_KEY").unwrap_or_default();
-    rocket.manage(APIKeyState(api_key))
+    rocket
+        .manage(APIKeyState::new(api_key, config.api_keys_file.as_ref()))
+        .manage(HealthCheckState(config.health_check_sync_threshold))
+        .manage(MethodFilterState::new(
+            config.enabled_methods.clone(),
+            config.disabled_methods.clone(),
+            config.read_only,
+        ))
+        .manage(AuditLogState(config.audit_log.as_ref().map(|path| {
+            let key = config.audit_log_key.as_deref().unwrap_or_default();
+            AuditLog::open(path, key.as_bytes()).unwrap_or_else(|err| {
+                eprintln!("Cannot open audit log {path:?}: {err}");
+                exit(EXIT_NO_AUDIT_LOG);
+            })
+        })))
+        .manage(RateLimitState(config.rate_limit.map(RateLimiter::new)))
 }
 
 fn consensus_backed_full_service(
@@ -180,7 +241,7 @@ fn consensus_backed_full_service(
         logger.clone(),
     )));
 
-    let transactions_fetcher = ReqwestTransactionsFetcher::new(
+    let transactions_fetcher = ReloadableTransactionsFetcher::new(
         config
             .peers_config
             .tx_source_urls
@@ -190,27 +251,51 @@ fn consensus_backed_full_service(
     )
     .expect("Failed creating ReqwestTransactionsFetcher");
 
+    let get_origin_block_and_transactions = || {
+        transactions_fetcher
+            .get_origin_block_and_transactions()
+            .map_err(|err| err.to_string())
+    };
+    if config.ledger_db_config.verify_ledger_db {
+        verify_ledger_db_and_exit(&config.ledger_db_config, get_origin_block_and_transactions);
+    }
+
     // Create the ledger_db.
     let ledger_db = config.ledger_db_config.create_or_open_ledger_db(
-        || {
-            transactions_fetcher
-                .get_origin_block_and_transactions()
-                .map_err(|err| err.to_string())
-        },
+        get_origin_block_and_transactions,
         config.offline,
         &logger,
     );
 
+    // Without a network to fetch the origin block from, it can only be checked
+    // against a configured origin block id.
+    if !config.offline || config.ledger_db_config.origin_block_id.is_some() {
+        check_ledger_origin_block(
+            &config.ledger_db_config,
+            &ledger_db,
+            get_origin_block_and_transactions,
+            &logger,
+        );
+    }
+    check_chain_id(
+        config,
+        &ledger_db,
+        (!config.offline).then_some(&peer_manager),
+    );
+
     // Start ledger sync thread unless running in offline mode.
+    let poll_interval = PollInterval::new(config.poll_interval);
+    let block_info_cache = BlockInfoCache::default();
     let ledger_sync_service_thread = if config.offline {
         None
     } else {
-        Some(LedgerSyncServiceThread::new(
+        Some(ConsensusLedgerSyncThread::new(
             ledger_db.clone(),
             peer_manager.clone(),
             network_state.clone(),
             transactions_fetcher.clone(),
-            config.poll_interval,
+            poll_interval.clone(),
+            block_info_cache.clone(),
             logger.clone(),
         ))
     };
@@ -223,7 +308,7 @@ fn consensus_backed_full_service(
             log::info!(logger, "Opening watcher db at {:?}.", watcher_db_path);
             let watcher_db = create_or_open_rw_watcher_db(
                 watcher_db_path,
-                &transactions_fetcher.source_urls,
+                &transactions_fetcher.source_urls(),
                 logger.clone(),
             )
             .expect("Could not create or open WatcherDB");
@@ -256,14 +341,23 @@ fn consensus_backed_full_service(
         peer_manager,
         network_config,
         network_state,
+        poll_interval,
+        block_info_cache,
+        (!config.offline).then(|| transactions_fetcher.clone()),
         config.get_fog_resolver_factory(logger.clone()),
         config.offline,
+        config.deterministic_output_order,
+        config.min_confirmations,
+        config.sender_memo_filter(),
+        config.max_accounts,
+        config.token_metadata.clone().unwrap_or_default(),
         config.t3_sync_config.clone(),
         webhook_config,
         logger,
     );
 
     consensus_backed_rocket(rocket_config, config.allowed_origin.clone())
+        .attach(GracefulShutdown::new(&service))
         .manage(WalletState { service })
         .manage(ledger_sync_service_thread)
         .manage(watcher_sync_thread)
@@ -278,33 +372,41 @@ fn validator_backed_full_service(
     webhook_config: Option<WebhookConfig>,
     logger: Logger,
 ) -> Rocket<Build> {
-    if config.watcher_db.is_some() {
-        panic!("Watcher syncing is not yet supported in a validator configuration");
-    }
-
     let validator_conn = ValidatorConnection::new(
         validator_uri,
         config.peers_config.chain_id.clone(),
         logger.clone(),
     );
 
+    let get_origin_block_and_transactions = || {
+        // Get the origin block.
+        let blocks_data = validator_conn
+            .get_blocks_data(0, 1)
+            .map_err(|err| err.to_string())?;
+        assert_eq!(blocks_data.len(), 1);
+
+        Ok(blocks_data[0].clone())
+    };
+    if config.ledger_db_config.verify_ledger_db {
+        verify_ledger_db_and_exit(&config.ledger_db_config, get_origin_block_and_transactions);
+    }
+
     // Create the ledger_db.
     let ledger_db = config.ledger_db_config.create_or_open_ledger_db(
-        || {
-            // Get the origin block.
-            let blocks_data = validator_conn
-                .get_blocks_data(0, 1)
-                .map_err(|err| err.to_string())?;
-            assert_eq!(blocks_data.len(), 1);
-
-            Ok(blocks_data[0].clone())
-        },
+        get_origin_block_and_transactions,
         false,
         &logger,
     );
+    check_ledger_origin_block(
+        &config.ledger_db_config,
+        &ledger_db,
+        get_origin_block_and_transactions,
+        &logger,
+    );
 
     // Create connections manager.
     let conn_manager = ConnectionManager::new(vec![validator_conn.clone()], logger.clone());
+    check_chain_id(config, &ledger_db, Some(&conn_manager));
 
     // Create network state
     // Note: There's onlu one node but we still need a quorum set.
@@ -317,12 +419,30 @@ fn validator_backed_full_service(
         logger.clone(),
     )));
 
+    // Optionally open the watcher db. The ledger sync thread records the block
+    // signatures it fetches from the validator in it, with the validator as its
+    // only source.
+    let watcher_db = config.watcher_db.as_ref().map(|watcher_db_path| {
+        log::info!(logger, "Opening watcher db at {:?}.", watcher_db_path);
+        create_or_open_rw_watcher_db(
+            watcher_db_path,
+            &[validator_uri.url().clone()],
+            logger.clone(),
+        )
+        .expect("Could not create or open WatcherDB")
+    });
+
     // Create the ledger sync thread.
+    let poll_interval = PollInterval::new(config.poll_interval);
+    let block_info_cache = BlockInfoCache::default();
     let ledger_sync_thread = ValidatorLedgerSyncThread::new(
         validator_uri,
         config.peers_config.chain_id.clone(),
-        config.poll_interval,
+        poll_interval.clone(),
+        config.validator_blocks_chunk_size,
+        block_info_cache.clone(),
         ledger_db.clone(),
+        watcher_db.clone(),
         network_state.clone(),
         logger.clone(),
     );
@@ -332,10 +452,13 @@ fn validator_backed_full_service(
     let service = WalletService::new(
         wallet_db,
         ledger_db,
-        None,
+        watcher_db,
         conn_manager,
         network_config,
         network_state,
+        poll_interval,
+        block_info_cache,
+        None,
         Arc::new(move |fog_uris| -> Result<FogResolver, String> {
             if fog_uris.is_empty() {
                 Ok(Default::default())
@@ -357,12 +480,90 @@ fn validator_backed_full_service(
             }
         }),
         false,
+        config.deterministic_output_order,
+        config.min_confirmations,
+        config.sender_memo_filter(),
+        config.max_accounts,
+        config.token_metadata.clone().unwrap_or_default(),
         config.t3_sync_config.clone(),
         webhook_config,
         logger,
     );
 
     validator_backed_rocket(rocket_config, config.allowed_origin.clone())
+        .attach(GracefulShutdown::new(&service))
         .manage(WalletState { service })
         .manage(ledger_sync_thread)
 }
+
+/// Exit if any of the peers reject the configured chain id, or if the ledger
+/// db's origin block is not the origin block of the configured chain.
+fn check_chain_id<BC: BlockchainConnection + 'static>(
+    config: &APIConfig,
+    ledger_db: &LedgerDB,
+    peer_manager: Option<&ConnectionManager<BC>>,
+) {
+    let chain_id = &config.peers_config.chain_id;
+    let peer_manager = match peer_manager {
+        Some(peer_manager) => peer_manager,
+        None => return,
+    };
+    let result = check_peers_chain_id(peer_manager, chain_id).and_then(|()| {
+        check_ledger_chain_id(&config.ledger_db_config, ledger_db, peer_manager, chain_id)
+    });
+    if let Err(err) = result {
+        eprintln!("{err}");
+        exit(EXIT_CHAIN_ID_MISMATCH);
+    }
+}
+
+/// Exit if the ledger db is from another network. If the network's origin block
+/// can not be fetched, the check is skipped so that startup does not depend on
+/// the network being reachable.
+fn check_ledger_origin_block(
+    ledger_db_config: &LedgerDbConfig,
+    ledger_db: &LedgerDB,
+    get_origin_block_and_transactions: impl Fn() -> Result<BlockData, String>,
+    logger: &Logger,
+) {
+    match ledger_db_config.verify_origin_block(ledger_db, get_origin_block_and_transactions) {
+        Ok(()) => {}
+        Err(LedgerOriginError::Network(err)) => {
+            log::warn!(
+                logger,
+                "Could not check the ledger db's origin block against the network's: {}",
+                err
+            );
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            exit(EXIT_WRONG_NETWORK_LEDGER);
+        }
+    }
+}
+
+/// Check the existing ledger db's origin block against the network's for
+/// `--verify-ledger-db`, and exit with the result.
+fn verify_ledger_db_and_exit(
+    ledger_db_config: &LedgerDbConfig,
+    get_origin_block_and_transactions: impl Fn() -> Result<BlockData, String>,
+) -> ! {
+    let result = ledger_db_config
+        .open_ledger_db_for_verification()
+        .and_then(|ledger_db| {
+            ledger_db_config.verify_origin_block(&ledger_db, get_origin_block_and_transactions)
+        });
+    match result {
+        Ok(()) => {
+            println!(
+                "Ledger DB {:?} matches the network's origin block",
+                ledger_db_config.ledger_db
+            );
+            exit(0);
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            exit(EXIT_WRONG_NETWORK_LEDGER);
+        }
+    }
+}
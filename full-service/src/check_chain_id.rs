@@ -0,0 +1,150 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! Startup checks that the configured chain id matches the network and the
+//! ledger db.
+//!
+//! Every request to a peer carries the configured chain id in the
+//! `chain-id` header, and peers on another network reject it. Requesting the
+//! block info from each peer at startup surfaces that rejection before any
+//! syncing is done. The peers which accept the chain id then serve that
+//! chain's origin block, and the ledger db's origin block is checked against
+//! it, so that a ledger synced from another network is caught as well.
+
+use crate::config::{LedgerDbConfig, LedgerOriginError};
+use mc_connection::{
+    _retry::delay::Fibonacci, BlockchainConnection, ConnectionManager, Error as ConnectionError,
+    RetryError, RetryableBlockchainConnection,
+};
+use mc_ledger_db::LedgerDB;
+use mc_util_grpc::CHAIN_ID_MISMATCH_ERR_MSG;
+
+use displaydoc::Display;
+
+/// The Errors that may occur when checking the chain id
+#[derive(Display, Debug)]
+pub enum CheckChainIdError {
+    /// Peer {0} rejected the configured chain id {1}. It is on another network.
+    PeerMismatch(String, String),
+
+    /**
+     * Ledger DB {0} has origin block {1}, but the origin block of chain id
+     * {2} is {3}
+     */
+    LedgerMismatch(String, String, String, String),
+
+    /// Error checking the ledger db's origin block: {0}
+    LedgerOrigin(LedgerOriginError),
+}
+
+impl From<LedgerOriginError> for CheckChainIdError {
+    fn from(e: LedgerOriginError) -> Self {
+        Self::LedgerOrigin(e)
+    }
+}
+
+/// Whether a peer rejected a request because of its chain id.
+fn is_chain_id_mismatch(err: &RetryError<ConnectionError>) -> bool {
+    match &err.error {
+        ConnectionError::Grpc(grpcio::Error::RpcFailure(status)) => {
+            status.message().contains(CHAIN_ID_MISMATCH_ERR_MSG)
+        }
+        _ => false,
+    }
+}
+
+/// Check that every peer accepts the configured chain id. Peers which can not
+/// be reached for another reason are skipped, as they are while syncing.
+pub fn check_peers_chain_id<BC: BlockchainConnection + 'static>(
+    peer_manager: &ConnectionManager<BC>,
+    chain_id: &str,
+) -> Result<(), CheckChainIdError> {
+    if chain_id.is_empty() {
+        return Ok(());
+    }
+    for conn in peer_manager.conns() {
+        if let Err(err) = conn.fetch_block_info(Fibonacci::from_millis(10).take(5)) {
+            if is_chain_id_mismatch(&err) {
+                return Err(CheckChainIdError::PeerMismatch(
+                    conn.to_string(),
+                    chain_id.to_string(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that the ledger db's origin block is the origin block of the
+/// configured chain, as served by the peers. This should be called after
+/// `check_peers_chain_id`, so that the peers are known to be on the configured
+/// chain. If no peer can be reached, there is nothing to check against.
+pub fn check_ledger_chain_id<BC: BlockchainConnection + 'static>(
+    ledger_db_config: &LedgerDbConfig,
+    ledger_db: &LedgerDB,
+    peer_manager: &ConnectionManager<BC>,
+    chain_id: &str,
+) -> Result<(), CheckChainIdError> {
+    if chain_id.is_empty() {
+        return Ok(());
+    }
+    let chain_origin_block_id = peer_manager.conns().iter().find_map(|conn| {
+        conn.fetch_blocks(0..1, Fibonacci::from_millis(10).take(5))
+            .ok()?
+            .first()
+            .map(|block| hex::encode(block.id))
+    });
+    let chain_origin_block_id = match chain_origin_block_id {
+        Some(chain_origin_block_id) => chain_origin_block_id,
+        None => return Ok(()),
+    };
+
+    match ledger_db_config.verify_origin_block_id(ledger_db, &chain_origin_block_id) {
+        Ok(()) => Ok(()),
+        Err(LedgerOriginError::Mismatch(ledger_db_path, ledger_origin_block_id, _)) => {
+            Err(CheckChainIdError::LedgerMismatch(
+                ledger_db_path.display().to_string(),
+                ledger_origin_block_id,
+                chain_id.to_string(),
+                chain_origin_block_id,
+            ))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{get_test_ledger, setup_peer_manager_and_network_state};
+    use mc_common::logger::{test_with_logger, Logger};
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::{assert_matches::assert_matches, path::PathBuf};
+
+    #[test_with_logger]
+    fn test_check_ledger_chain_id(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let network_ledger_db = get_test_ledger(5, &[], 3, &mut rng);
+        let other_ledger_db = get_test_ledger(5, &[], 3, &mut rng);
+        let (peer_manager, _network_state) =
+            setup_peer_manager_and_network_state(network_ledger_db.clone(), logger, false);
+        let ledger_db_config = LedgerDbConfig {
+            ledger_db: PathBuf::from("ledger_db"),
+            ledger_db_bootstrap: None,
+            origin_block_id: None,
+            verify_ledger_db: false,
+        };
+
+        // A ledger with the peers' origin block is on the configured chain.
+        check_ledger_chain_id(&ledger_db_config, &network_ledger_db, &peer_manager, "test")
+            .unwrap();
+
+        // A ledger from another network is not.
+        assert_matches!(
+            check_ledger_chain_id(&ledger_db_config, &other_ledger_db, &peer_manager, "test"),
+            Err(CheckChainIdError::LedgerMismatch(_, _, chain_id, _)) if chain_id == "test"
+        );
+
+        // Without a configured chain id there is nothing to check.
+        check_ledger_chain_id(&ledger_db_config, &other_ledger_db, &peer_manager, "").unwrap();
+    }
+}
@@ -20,17 +20,30 @@ use mc_util_uri::{ConnectionUri, ConsensusClientUri, FogUri};
 use mc_validator_api::ValidatorUri;
 
 use clap::Parser;
+use displaydoc::Display;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     convert::TryFrom,
-    fs,
+    fmt, fs,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::Duration,
 };
-
-use crate::service::t3_sync::T3Config;
+use strum::IntoEnumIterator;
+
+use crate::{
+    db::{
+        authenticated_sender_memo::{SenderMemoFilter, SenderMemoType},
+        WalletDbError,
+    },
+    json_rpc::{v1, v2::api::request::api_schema},
+    service::t3_sync::T3Config,
+};
 
 /// Command line config for the Wallet API
 #[derive(Clone, Debug, Parser)]
@@ -48,10 +61,20 @@ pub struct APIConfig {
     #[clap(long, default_value = "9090", env = "MC_LISTEN_PORT")]
     pub listen_port: u16,
 
+    /// Override the number of workers used for the http server. This controls
+    /// how many concurrent requests the server can process.
+    #[clap(long, env = "MC_NUM_WORKERS")]
+    pub num_workers: Option<u16>,
+
     /// Path to WalletDb.
     #[clap(long, value_parser, env = "MC_WALLET_DB")]
     pub wallet_db: Option<PathBuf>,
 
+    /// The maximum number of open connections to the WalletDb. SQLite allows
+    /// one writer at a time, so additional connections serve concurrent reads.
+    #[clap(long, default_value = "10", value_parser = clap::value_parser!(u32).range(1..), env = "MC_WALLET_DB_POOL_SIZE")]
+    pub wallet_db_pool_size: u32,
+
     #[clap(flatten)]
     pub ledger_db_config: LedgerDbConfig,
 
@@ -62,10 +85,58 @@ pub struct APIConfig {
     #[clap(long, default_value = "5", value_parser = parse_duration_in_seconds, env = "MC_POLL_INTERVAL")]
     pub poll_interval: Duration,
 
+    /// How many seconds to wait on shutdown for in-flight requests and
+    /// transaction submissions to finish, and for the account sync thread to
+    /// write its current pass. New requests are refused meanwhile.
+    #[clap(long, default_value = "30", value_parser = parse_duration_in_seconds, env = "MC_SHUTDOWN_GRACE_PERIOD")]
+    pub shutdown_grace_period: Duration,
+
     /// Offline mode.
     #[clap(long, env = "MC_OFFLINE")]
     pub offline: bool,
 
+    /// Add the outputs of built transactions in a deterministic order, sorted
+    /// by recipient address, token id and value, so that identical requests
+    /// produce comparable proposals.
+    #[clap(long, env = "MC_DETERMINISTIC_OUTPUT_ORDER")]
+    pub deterministic_output_order: bool,
+
+    /// The number of blocks which must be appended after the block a Txo was
+    /// received in before it is selected as an input. MobileCoin blocks are
+    /// final once published, so this is only a precaution.
+    #[clap(long, default_value = "0", env = "MC_MIN_CONFIRMATIONS")]
+    pub min_confirmations: u64,
+
+    /// Authenticated sender memo types to store for received Txos, e.g.
+    /// authenticated_sender_with_payment_intent_id. When provided, the memos
+    /// of Txos received with any other sender memo type are not stored, and
+    /// report as unused. The types are authenticated_sender,
+    /// authenticated_sender_with_payment_request_id and
+    /// authenticated_sender_with_payment_intent_id.
+    #[clap(
+        long = "stored-sender-memo-type",
+        use_value_delimiter = true,
+        value_parser = parse_sender_memo_type,
+        conflicts_with = "skipped_sender_memo_types",
+        env = "MC_STORED_SENDER_MEMO_TYPE"
+    )]
+    pub stored_sender_memo_types: Option<Vec<SenderMemoType>>,
+
+    /// Authenticated sender memo types not to store for received Txos. The
+    /// memos of Txos received with these types report as unused.
+    #[clap(
+        long = "skipped-sender-memo-type",
+        use_value_delimiter = true,
+        value_parser = parse_sender_memo_type,
+        env = "MC_SKIPPED_SENDER_MEMO_TYPE"
+    )]
+    pub skipped_sender_memo_types: Option<Vec<SenderMemoType>>,
+
+    /// The most accounts the wallet may hold. Creating or importing accounts
+    /// beyond the limit fails. If not provided, there is no limit.
+    #[clap(long, env = "MC_MAX_ACCOUNTS")]
+    pub max_accounts: Option<u64>,
+
     /// Fog ingest enclave CSS file (needed in order to enable sending
     /// transactions to fog recipients).
     #[clap(long, value_parser = load_css_file, env = "MC_FOG_INGEST_ENCLAVE_CSS")]
@@ -76,16 +147,89 @@ pub struct APIConfig {
     #[clap(long, env = "MC_VALIDATOR")]
     pub validator: Option<ValidatorUri>,
 
+    /// The maximum number of blocks to request from the validator at once.
+    /// Wider ranges of blocks are fetched in chunks of this size.
+    #[clap(long, default_value = "1000", value_parser = clap::value_parser!(u32).range(1..), env = "MC_VALIDATOR_BLOCKS_CHUNK_SIZE")]
+    pub validator_blocks_chunk_size: u32,
+
     /// Path to watcher db (lmdb). When provided, watcher syncing will take
     /// place.
     #[clap(long, value_parser, env = "MC_WATCHER_DB")]
     pub watcher_db: Option<PathBuf>,
 
-    /// Allowed CORS origin. When provided, the http server will add CORS
-    /// headers for the provided origin. If not provided, the http server
-    /// will not add any CORS headers
-    #[clap(long, env = "MC_ALLOWED_ORIGIN")]
-    pub allowed_origin: Option<String>,
+    /// Allowed CORS origins, e.g. https://app.example.com,https://*.example.org.
+    /// When provided, the http server will add CORS headers for requests from
+    /// these origins, and reject requests from any other origin. A `*.` prefix
+    /// matches every subdomain of a domain, and `*` allows all origins. If not
+    /// provided, the http server will not add any CORS headers
+    #[clap(long, use_value_delimiter = true, env = "MC_ALLOWED_ORIGIN")]
+    pub allowed_origin: Vec<String>,
+
+    /// The number of blocks the local ledger may trail the network tip by
+    /// while the /ready endpoint still reports the service as ready.
+    #[clap(long, default_value = "5", env = "MC_HEALTH_CHECK_SYNC_THRESHOLD")]
+    pub health_check_sync_threshold: u64,
+
+    /// JSON-RPC methods to expose. When provided, every other method returns a
+    /// "method disabled" error.
+    #[clap(
+        long = "enabled-method",
+        use_value_delimiter = true,
+        conflicts_with = "disabled_methods",
+        value_parser = parse_method_name,
+        env = "MC_ENABLED_METHOD"
+    )]
+    pub enabled_methods: Option<Vec<String>>,
+
+    /// JSON-RPC methods to disable, e.g. create_account,export_account_secrets.
+    /// Disabled methods return a "method disabled" error.
+    #[clap(
+        long = "disabled-method",
+        use_value_delimiter = true,
+        value_parser = parse_method_name,
+        env = "MC_DISABLED_METHOD"
+    )]
+    pub disabled_methods: Option<Vec<String>>,
+
+    /// Read-only mode. Methods which build, sign or submit transactions,
+    /// export or import account secrets, or change the log level, poll
+    /// interval or tx source urls, return a "method disabled" error.
+    #[clap(long, env = "MC_READ_ONLY")]
+    pub read_only: bool,
+
+    /// Path to an audit log. If set, a line is appended to it for each request
+    /// which builds, signs or submits a transaction, or exports account
+    /// secrets, with the secrets in the request left out.
+    #[clap(long, value_parser, env = "MC_AUDIT_LOG", requires = "audit_log_key")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Secret key for the chain of hashes linking the lines of the audit log,
+    /// so that the chain can not be recomputed after editing the log without
+    /// it. Keep it somewhere other than the host the audit log is on.
+    #[clap(
+        long,
+        env = "MC_AUDIT_LOG_KEY",
+        hide_env_values = true,
+        requires = "audit_log"
+    )]
+    pub audit_log_key: Option<String>,
+
+    /// The number of requests per second which may be made to the wallet API
+    /// with each API key. Requests beyond the limit return 429 Too Many
+    /// Requests. If not provided, requests are not rate limited.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..), env = "MC_RATE_LIMIT")]
+    pub rate_limit: Option<u32>,
+
+    /// Path to a JSON file of API keys, each with the methods it may invoke,
+    /// for example:
+    /// [{"key":"admin"},{"key":"reader","methods":["get_account_status"]}]
+    ///
+    /// Keys without a list of methods may invoke every method. The key in
+    /// `MC_API_KEY`, if set, is also accepted and may invoke every method.
+    /// When provided, requests without an API key are rejected unless the
+    /// file includes an empty key.
+    #[clap(long, value_parser = load_api_keys_file, env = "MC_API_KEYS_FILE")]
+    pub api_keys_file: Option<ApiKeysConfig>,
 
     /// T3 Server to connect to and the api key to use for authorization.
     #[clap(flatten)]
@@ -113,6 +257,14 @@ pub struct APIConfig {
     /// on subsequent deposits.
     #[clap(long, value_parser = Url::parse, env = "MC_DEPOSITS_WEBHOOK_URL")]
     pub deposits_webhook_url: Option<Url>,
+
+    /// Path to a JSON file of display metadata for token ids, for example:
+    /// {"0":{"symbol":"MOB","decimals":12},"1":{"symbol":"eUSD","decimals":6}}
+    ///
+    /// When provided, balances and txos for the listed tokens include the
+    /// token symbol and the value formatted as a decimal string.
+    #[clap(long, value_parser = load_token_metadata_file, env = "MC_TOKEN_METADATA")]
+    pub token_metadata: Option<TokenMetadataMap>,
 }
 
 fn parse_quorum_set_from_json(src: &str) -> Result<QuorumSet<ResponderId>, String> {
@@ -134,7 +286,50 @@ fn load_css_file(filename: &str) -> Result<Signature, String> {
     Ok(signature)
 }
 
+fn load_token_metadata_file(filename: &str) -> Result<TokenMetadataMap, String> {
+    let contents = fs::read_to_string(filename)
+        .map_err(|err| format!("Failed reading file '{filename}': {err}"))?;
+    let token_metadata = serde_json::from_str(&contents)
+        .map_err(|err| format!("Failed parsing token metadata file '{filename}': {err}"))?;
+    Ok(token_metadata)
+}
+
+fn parse_sender_memo_type(src: &str) -> Result<SenderMemoType, String> {
+    src.parse().map_err(|err: WalletDbError| err.to_string())
+}
+
+/// Accept only method names of the v2 API, or of the deprecated v1 API which is
+/// filtered by the same options.
+fn parse_method_name(src: &str) -> Result<String, String> {
+    let is_v1_method = v1::api::request::JsonCommandRequest::iter().any(|request| {
+        serde_json::to_value(&request)
+            .map_or(false, |request| request["method"].as_str() == Some(src))
+    });
+    if is_v1_method || api_schema().iter().any(|method| method.method == src) {
+        Ok(src.to_string())
+    } else {
+        Err(format!("Unknown JSON-RPC method '{src}'"))
+    }
+}
+
+fn load_api_keys_file(filename: &str) -> Result<ApiKeysConfig, String> {
+    let contents = fs::read_to_string(filename)
+        .map_err(|err| format!("Failed reading file '{filename}': {err}"))?;
+    let api_keys = serde_json::from_str(&contents)
+        .map_err(|err| format!("Failed parsing API keys file '{filename}': {err}"))?;
+    Ok(api_keys)
+}
+
 impl APIConfig {
+    /// Get the filter for the authenticated sender memo types stored when
+    /// syncing.
+    pub fn sender_memo_filter(&self) -> SenderMemoFilter {
+        SenderMemoFilter::new(
+            self.stored_sender_memo_types.clone(),
+            self.skipped_sender_memo_types.clone(),
+        )
+    }
+
     /// Get the attestation verifier used to verify fog reports when sending to
     /// fog recipients.
     pub fn get_fog_ingest_identity(&self) -> Option<TrustedIdentity> {
@@ -312,9 +507,112 @@ pub struct LedgerDbConfig {
     /// initializing new ledger dbs.
     #[clap(long, env = "MC_LEDGER_DB_BOOTSTRAP")]
     pub ledger_db_bootstrap: Option<String>,
+
+    /// The hex-encoded id of the network's origin block. The ledger db's origin
+    /// block is checked against it on startup. If not provided, the origin
+    /// block is fetched from the network instead.
+    #[clap(long, value_parser = parse_block_id, env = "MC_ORIGIN_BLOCK_ID")]
+    pub origin_block_id: Option<String>,
+
+    /// Open the existing ledger db without migrating or bootstrapping it, check
+    /// that its origin block matches the network's, then exit.
+    #[clap(long, env = "MC_VERIFY_LEDGER_DB")]
+    pub verify_ledger_db: bool,
+}
+
+fn parse_block_id(src: &str) -> Result<String, String> {
+    let bytes = hex::decode(src).map_err(|err| format!("Invalid block id {src}: {err}"))?;
+    if bytes.len() != 32 {
+        return Err(format!("Invalid block id {src}: expected 32 bytes"));
+    }
+    Ok(hex::encode(bytes))
+}
+
+/// Errors checking that a ledger db belongs to the configured network.
+#[derive(Display, Debug)]
+pub enum LedgerOriginError {
+    /// Ledger DB {0:?} does not exist
+    Missing(PathBuf),
+
+    /// Error reading the ledger db: {0}
+    LedgerDb(mc_ledger_db::Error),
+
+    /// Could not get the network's origin block: {0}
+    Network(String),
+
+    /**
+     * Ledger DB {0:?} has origin block {1}, but the network's origin block
+     * is {2}. The ledger db is from another network, and must be moved
+     * aside or replaced before syncing.
+     */
+    Mismatch(PathBuf, String, String),
+}
+
+impl From<mc_ledger_db::Error> for LedgerOriginError {
+    fn from(src: mc_ledger_db::Error) -> Self {
+        Self::LedgerDb(src)
+    }
 }
 
 impl LedgerDbConfig {
+    /// Open an existing ledger db to verify it, without migrating, creating or
+    /// bootstrapping it.
+    pub fn open_ledger_db_for_verification(&self) -> Result<LedgerDB, LedgerOriginError> {
+        if !Path::new(&self.ledger_db).join("data.mdb").exists() {
+            return Err(LedgerOriginError::Missing(self.ledger_db.clone()));
+        }
+        Ok(LedgerDB::open(&self.ledger_db)?)
+    }
+
+    /// Check that the origin block of the ledger db is the network's origin
+    /// block, so that blocks from another network are never synced into it.
+    /// The network's origin block id is `origin_block_id` if configured, and
+    /// is otherwise fetched with `get_origin_block_and_transactions`. An empty
+    /// ledger db has nothing to check.
+    pub fn verify_origin_block(
+        &self,
+        ledger_db: &LedgerDB,
+        get_origin_block_and_transactions: impl Fn() -> Result<BlockData, String>,
+    ) -> Result<(), LedgerOriginError> {
+        if ledger_db.num_blocks()? == 0 {
+            return Ok(());
+        }
+
+        let network_origin_block_id = match &self.origin_block_id {
+            Some(origin_block_id) => origin_block_id.clone(),
+            None => hex::encode(
+                get_origin_block_and_transactions()
+                    .map_err(LedgerOriginError::Network)?
+                    .block()
+                    .id
+                    .clone(),
+            ),
+        };
+        self.verify_origin_block_id(ledger_db, &network_origin_block_id)
+    }
+
+    /// Check that the origin block of the ledger db has the given hex-encoded
+    /// id. An empty ledger db has nothing to check.
+    pub fn verify_origin_block_id(
+        &self,
+        ledger_db: &LedgerDB,
+        network_origin_block_id: &str,
+    ) -> Result<(), LedgerOriginError> {
+        if ledger_db.num_blocks()? == 0 {
+            return Ok(());
+        }
+        let ledger_origin_block_id = hex::encode(ledger_db.get_block(0)?.id);
+
+        if ledger_origin_block_id != network_origin_block_id {
+            return Err(LedgerOriginError::Mismatch(
+                self.ledger_db.clone(),
+                ledger_origin_block_id,
+                network_origin_block_id.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     pub fn create_or_open_ledger_db(
         &self,
         get_origin_block_and_transactions: impl Fn() -> Result<BlockData, String>,
@@ -423,3 +721,102 @@ pub struct WebhookConfig {
     pub url: Url,
     pub poll_interval: Duration,
 }
+
+/// Display metadata for a token.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TokenMetadata {
+    /// The token's symbol, e.g. MOB.
+    pub symbol: String,
+
+    /// The number of decimal places in a whole unit of the token, e.g. 12 for
+    /// MOB, whose values are denominated in picoMOB.
+    pub decimals: u32,
+}
+
+impl TokenMetadata {
+    /// Format a value in the token's smallest unit as a decimal string, e.g.
+    /// 1500000000000 picoMOB as "1.500000000000".
+    pub fn format_value(&self, value: u128) -> String {
+        let decimals = self.decimals as usize;
+        if decimals == 0 {
+            return value.to_string();
+        }
+        let digits = format!("{value:0>width$}", width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        format!("{whole}.{fraction}")
+    }
+}
+
+/// Display metadata for each configured token id. Tokens without metadata are
+/// identified only by their id.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TokenMetadataMap(pub BTreeMap<u64, TokenMetadata>);
+
+impl TokenMetadataMap {
+    pub fn get(&self, token_id: u64) -> Option<&TokenMetadata> {
+        self.0.get(&token_id)
+    }
+}
+
+/// An API key accepted by the wallet API.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ApiKeyConfig {
+    pub key: String,
+
+    /// The methods the key may invoke, or None if it may invoke every method.
+    pub methods: Option<Vec<String>>,
+}
+
+impl fmt::Debug for ApiKeyConfig {
+    /// Leave the key itself out, so that it is not logged with the config.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ApiKeyConfig")
+            .field("key", &"<redacted>")
+            .field("methods", &self.methods)
+            .finish()
+    }
+}
+
+/// The API keys configured with `--api-keys-file`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ApiKeysConfig(pub Vec<ApiKeyConfig>);
+
+/// The smallest poll interval that can be configured at runtime. Anything
+/// lower risks hammering the peers and archive sources we sync from.
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The poll interval used by the ledger sync threads, shared so that it can be
+/// adjusted at runtime. Changes take effect on the next loop iteration.
+#[derive(Clone, Debug)]
+pub struct PollInterval(Arc<AtomicU64>);
+
+impl PollInterval {
+    pub fn new(poll_interval: Duration) -> Self {
+        Self(Arc::new(AtomicU64::new(poll_interval.as_millis() as u64)))
+    }
+
+    /// Get the current poll interval.
+    pub fn get(&self) -> Duration {
+        Duration::from_millis(self.0.load(Ordering::SeqCst))
+    }
+
+    /// Set a new poll interval, which must be at least `MIN_POLL_INTERVAL`.
+    pub fn set(&self, poll_interval: Duration) -> Result<(), String> {
+        if poll_interval < MIN_POLL_INTERVAL {
+            return Err(format!(
+                "Poll interval must be at least {}ms, got {}ms",
+                MIN_POLL_INTERVAL.as_millis(),
+                poll_interval.as_millis()
+            ));
+        }
+        self.0
+            .store(poll_interval.as_millis() as u64, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+impl Default for PollInterval {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(5))
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright (c) 2018-2023 MobileCoin, Inc.
+
+//! Ledger syncing via the consensus network and archive transaction sources.
+//!
+//! This mirrors `mc_ledger_sync::LedgerSyncServiceThread`, but reads its poll
+//! interval from a shared `PollInterval` so it can be adjusted at runtime, and
+//! keeps the wallet's `BlockInfoCache` fresh as it polls the network.
+
+use crate::{config::PollInterval, service::block_info_cache::BlockInfoCache};
+use mc_blockchain_types::{Block, BlockData};
+use mc_common::{
+    logger::{log, Logger},
+    ResponderId,
+};
+use mc_connection::{BlockchainConnection, ConnectionManager};
+use mc_ledger_db::LedgerDB;
+use mc_ledger_sync::{
+    LedgerSync, LedgerSyncService, PollingNetworkState, ReqwestTransactionsFetcher,
+    ReqwestTransactionsFetcherError, TransactionsFetcher,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    thread,
+};
+use url::Url;
+
+/// The maximum number of blocks to try and retrieve in each iteration
+pub const MAX_BLOCKS_PER_SYNC_ITERATION: u32 = 1000;
+
+/// URL schemes `ReqwestTransactionsFetcher` can fetch transaction data from.
+const TX_SOURCE_URL_SCHEMES: &[&str] = &["http", "https", "file"];
+
+/// A `ReqwestTransactionsFetcher` whose source URLs can be replaced at
+/// runtime. Clones share the same fetcher, so replacing the source URLs
+/// through the wallet service's clone takes effect in the sync thread.
+#[derive(Clone)]
+pub struct ReloadableTransactionsFetcher {
+    inner: Arc<RwLock<ReqwestTransactionsFetcher>>,
+    logger: Logger,
+}
+
+impl ReloadableTransactionsFetcher {
+    pub fn new(
+        source_urls: Vec<String>,
+        logger: Logger,
+    ) -> Result<Self, ReqwestTransactionsFetcherError> {
+        let inner = ReqwestTransactionsFetcher::new(source_urls, logger.clone())?;
+        Ok(Self {
+            inner: Arc::new(RwLock::new(inner)),
+            logger,
+        })
+    }
+
+    /// The URLs transaction data is currently fetched from.
+    pub fn source_urls(&self) -> Vec<Url> {
+        self.inner
+            .read()
+            .expect("lock poisoned")
+            .source_urls
+            .clone()
+    }
+
+    /// Replace the URLs transaction data is fetched from.
+    ///
+    /// Every URL is validated before any are used, so an invalid list leaves
+    /// the current sources in place.
+    pub fn set_source_urls(&self, source_urls: Vec<String>) -> Result<(), String> {
+        if source_urls.is_empty() {
+            return Err("At least one transaction source URL is required".to_string());
+        }
+        for source_url in &source_urls {
+            let url =
+                Url::parse(source_url).map_err(|err| format!("Invalid URL {source_url}: {err}"))?;
+            if !TX_SOURCE_URL_SCHEMES.contains(&url.scheme()) {
+                return Err(format!(
+                    "Unsupported scheme for URL {source_url}, expected one of {TX_SOURCE_URL_SCHEMES:?}"
+                ));
+            }
+        }
+
+        let fetcher = ReqwestTransactionsFetcher::new(source_urls, self.logger.clone())
+            .map_err(|err| format!("Could not create transactions fetcher: {err:?}"))?;
+        log::info!(
+            self.logger,
+            "Transaction source URLs set to {:?}",
+            fetcher.source_urls
+        );
+        *self.inner.write().expect("lock poisoned") = fetcher;
+
+        Ok(())
+    }
+
+    pub fn get_origin_block_and_transactions(
+        &self,
+    ) -> Result<BlockData, ReqwestTransactionsFetcherError> {
+        self.inner
+            .read()
+            .expect("lock poisoned")
+            .get_origin_block_and_transactions()
+    }
+}
+
+impl TransactionsFetcher for ReloadableTransactionsFetcher {
+    type Error = <ReqwestTransactionsFetcher as TransactionsFetcher>::Error;
+
+    fn get_block_data(
+        &self,
+        safe_responder_ids: &[ResponderId],
+        block: &Block,
+    ) -> Result<BlockData, Self::Error> {
+        // Clone the fetcher so that replacing the source URLs does not wait on a
+        // slow fetch.
+        let fetcher = self.inner.read().expect("lock poisoned").clone();
+        fetcher.get_block_data(safe_responder_ids, block)
+    }
+}
+
+pub struct ConsensusLedgerSyncThread {
+    join_handle: Option<thread::JoinHandle<()>>,
+    stop_requested: Arc<AtomicBool>,
+}
+
+impl ConsensusLedgerSyncThread {
+    pub fn new<BC, TF>(
+        ledger_db: LedgerDB,
+        peer_manager: ConnectionManager<BC>,
+        network_state: Arc<RwLock<PollingNetworkState<BC>>>,
+        transactions_fetcher: TF,
+        poll_interval: PollInterval,
+        block_info_cache: BlockInfoCache,
+        logger: Logger,
+    ) -> Self
+    where
+        BC: BlockchainConnection + 'static,
+        TF: TransactionsFetcher + 'static,
+    {
+        let stop_requested = Arc::new(AtomicBool::new(false));
+
+        let ledger_sync_service = LedgerSyncService::new(
+            ledger_db,
+            peer_manager.clone(),
+            transactions_fetcher,
+            logger.clone(),
+        );
+
+        let thread_stop_requested = stop_requested.clone();
+        let join_handle = Some(
+            thread::Builder::new()
+                .name("ConsensusLedgerSync".into())
+                .spawn(move || {
+                    Self::thread_entrypoint(
+                        ledger_sync_service,
+                        peer_manager,
+                        poll_interval,
+                        block_info_cache,
+                        network_state,
+                        logger,
+                        thread_stop_requested,
+                    );
+                })
+                .expect("Failed spawning ConsensusLedgerSync thread"),
+        );
+
+        Self {
+            join_handle,
+            stop_requested,
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.stop_requested.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.join_handle.take() {
+            thread.join().expect("thread join failed");
+        }
+    }
+
+    fn thread_entrypoint<BC, TF>(
+        mut ledger_sync_service: LedgerSyncService<LedgerDB, BC, TF>,
+        peer_manager: ConnectionManager<BC>,
+        poll_interval: PollInterval,
+        block_info_cache: BlockInfoCache,
+        network_state: Arc<RwLock<PollingNetworkState<BC>>>,
+        logger: Logger,
+        stop_requested: Arc<AtomicBool>,
+    ) where
+        BC: BlockchainConnection + 'static,
+        TF: TransactionsFetcher + 'static,
+    {
+        log::info!(logger, "ConsensusLedgerSync thread started");
+
+        loop {
+            if stop_requested.load(Ordering::SeqCst) {
+                log::debug!(logger, "ConsensusLedgerSyncThread stop requested.");
+                break;
+            }
+
+            let is_behind = {
+                let mut network_state = network_state.write().expect("network_state lock poisoned");
+                network_state.poll();
+                ledger_sync_service.is_behind(&*network_state)
+            };
+
+            if let Err(err) =
+                block_info_cache.refresh_if_older_than(&peer_manager, poll_interval.get())
+            {
+                log::warn!(logger, "Could not refresh block info: {:?}", err);
+            }
+
+            if is_behind {
+                let network_state = network_state.read().expect("network_state lock poisoned");
+                if let Err(err) = ledger_sync_service
+                    .attempt_ledger_sync(&*network_state, MAX_BLOCKS_PER_SYNC_ITERATION)
+                {
+                    log::error!(logger, "Could not sync ledger: {:?}", err);
+                    thread::sleep(poll_interval.get());
+                }
+            } else {
+                thread::sleep(poll_interval.get());
+            }
+        }
+    }
+}
+
+impl Drop for ConsensusLedgerSyncThread {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_common::logger::test_with_logger;
+
+    #[test_with_logger]
+    fn test_set_source_urls(logger: Logger) {
+        let fetcher = ReloadableTransactionsFetcher::new(
+            vec!["https://archive.example.com/node1/".to_string()],
+            logger,
+        )
+        .unwrap();
+        let sync_thread_fetcher = fetcher.clone();
+
+        // An invalid URL anywhere in the list leaves the current sources in place.
+        for source_urls in [
+            vec![],
+            vec![
+                "https://archive.example.com/node2/".to_string(),
+                "not a url".to_string(),
+            ],
+            vec!["ftp://archive.example.com/node2/".to_string()],
+        ] {
+            assert!(fetcher.set_source_urls(source_urls).is_err());
+        }
+        assert_eq!(
+            sync_thread_fetcher.source_urls(),
+            vec![Url::parse("https://archive.example.com/node1/").unwrap()]
+        );
+
+        fetcher
+            .set_source_urls(vec![
+                "https://archive.example.com/node2/".to_string(),
+                "https://archive.example.com/node3/".to_string(),
+            ])
+            .unwrap();
+        assert_eq!(
+            sync_thread_fetcher.source_urls(),
+            vec![
+                Url::parse("https://archive.example.com/node2/").unwrap(),
+                Url::parse("https://archive.example.com/node3/").unwrap(),
+            ]
+        );
+    }
+}
@@ -23,14 +23,14 @@ use diesel::{
 };
 use mc_account_keys::{
     AccountKey, PublicAddress, RootEntropy, RootIdentity, ViewAccountKey, CHANGE_SUBADDRESS_INDEX,
-    DEFAULT_SUBADDRESS_INDEX,
+    DEFAULT_SUBADDRESS_INDEX, INVALID_SUBADDRESS_INDEX,
 };
 use mc_core::slip10::Slip10KeyGenerator;
 use mc_crypto_digestible::{Digestible, MerlinTranscript};
 use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
 use mc_transaction_core::{get_tx_out_shared_secret, TokenId};
 use serde_derive::Serialize;
-use std::fmt;
+use std::{collections::BTreeMap, fmt, str::FromStr};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
 pub struct AccountID(pub String);
@@ -68,6 +68,30 @@ impl fmt::Display for AccountID {
     }
 }
 
+/// The order in which accounts are listed. Ties are broken by account id, so
+/// that the order is stable across calls.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum AccountSortOrder {
+    /// By the index of the block the account was created or imported at.
+    #[default]
+    ImportBlockIndex,
+
+    /// By the account's name.
+    Name,
+}
+
+impl FromStr for AccountSortOrder {
+    type Err = WalletDbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "import_block_index" => Ok(AccountSortOrder::ImportBlockIndex),
+            "name" => Ok(AccountSortOrder::Name),
+            _ => Err(WalletDbError::InvalidAccountSortOrder(s.to_string())),
+        }
+    }
+}
+
 #[rustfmt::skip]
 pub trait AccountModel {
     /// Create an account from mnemonic.
@@ -84,12 +108,14 @@ pub trait AccountModel {
     ///| `fog_report_url`        | Fog Report server url.                                                  | Applicable only if user has Fog service, empty string otherwise.      |
     ///| `fog_authority_spki`    | Fog Authority Subject Public Key Info.                                  | Applicable only if user has Fog service, empty string otherwise.      |
     ///| `require_spend_subaddress` | If enabled, this mode requires all transactions to spend from a provided subaddress |                                                      |
+    ///| `account_index`         | The SLIP-0010 account index to derive the account key at.               | 0 unless the mnemonic holds several accounts.                         |
     ///
     /// # Returns:
     /// * (account_id, main_subaddress_b58)
     #[allow(clippy::too_many_arguments)]
     fn create_from_mnemonic(
         mnemonic: &Mnemonic,
+        account_index: u32,
         first_block_index: Option<u64>,
         import_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
@@ -140,6 +166,7 @@ pub trait AccountModel {
     ///| `entropy`                | Either a BIP39-encoded mnemonic phrase or a secret root entropy used to generate the account key. | Depends on the `key_derivation_version` parameter                     |
     ///| `key_derivation_version` | The version number of the key derivation path used to create a account key.                       | "2" for mnemonic phrase and "1" for root entropy                      |
     ///| `account_key`            | Contains a View keypair and a Spend keypair, used to construct and receive transactions.          | Also may contain keys to connect to the Fog ledger scanning service.  |
+    ///| `account_index`          | The SLIP-0010 account index the account key was derived at.                                       | 0 for root entropy                                                    |
     ///| `first_block_index`      | Index of the first block when this account may have received funds.                               | Defaults to 0 if not provided                                         |
     ///| `import_block_index`     | Index of the last block in local ledger database.                                                 |                                                                       |
     ///| `next_subaddress_index`  | This index represents the next subaddress to be assigned as an address.                           | This is useful information in case the account is imported elsewhere. |
@@ -155,6 +182,7 @@ pub trait AccountModel {
         entropy: &[u8],
         key_derivation_version: u8,
         account_key: &AccountKey,
+        account_index: u32,
         first_block_index: Option<u64>,
         import_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
@@ -171,6 +199,7 @@ pub trait AccountModel {
     ///| Name                    | Purpose                                                                 | Notes                                                                 |
     ///|-------------------------|-------------------------------------------------------------------------|-----------------------------------------------------------------------|
     ///| `mnemonic`              | A BIP39-encoded mnemonic phrase used to generate the account key.       |                                                                       |
+    ///| `account_index`         | The SLIP-0010 account index to derive the account key at.               | 0 unless the mnemonic holds several accounts.                         |
     ///| `name`                  | The display name for the account.                                       | A label can have duplicates, but it is not recommended.               |
     ///| `import_block_index`    | Index of the last block in local ledger database.                       |                                                                       |
     ///| `first_block_index`     | Index of the first block when this account may have received funds.     | Defaults to 0 if not provided                                         |
@@ -185,6 +214,7 @@ pub trait AccountModel {
     #[allow(clippy::too_many_arguments)]
     fn import(
         mnemonic: &Mnemonic,
+        account_index: u32,
         name: Option<String>,
         import_block_index: u64,
         first_block_index: Option<u64>,
@@ -234,12 +264,14 @@ pub trait AccountModel {
     ///|-------------------------|-------------------------------------------------------------------------|-----------------------------------------------------------------------|
     ///| `view_private_key`      | The view private key of this import candidate.                          | Grant view only permission                                            |
     ///| `spend_public_key`      | The spend public key of this import candidate.                          | Used to generate new subaddresses                                     |
+    ///| `account_index`         | The SLIP-0010 account index the keys were derived at.                   | 0 unless the mnemonic holds several accounts.                         |
     ///| `name`                  | The display name for the account.                                       | A label can have duplicates, but it is not recommended.               |
     ///| `import_block_index`    | Index of the last block in local ledger database.                       |                                                                       |
     ///| `first_block_index`     | Index of the first block when this account may have received funds.     | Defaults to 0 if not provided                                         |
     ///| `next_subaddress_index` | This index represents the next subaddress to be assigned as an address. | This is useful information in case the account is imported elsewhere. |
     ///| `managed_by_hardware_wallet` | Whether the account is managed by a hardware wallet.                 |                                                                       |
     ///| `require_spend_subaddress` | If enabled, this mode requires all transactions to spend from a provided subaddress |                                                        |
+    ///| `subaddresses`          | Subaddress indices to assign, with their comments.                      | The main and change subaddress indices cannot be included.            |
     ///| `conn`                  | An reference to the pool connection of wallet database                  |                                                                       |
     ///
     /// # Returns:
@@ -247,17 +279,20 @@ pub trait AccountModel {
     #[allow(clippy::too_many_arguments)]
     fn import_view_only(
         view_account_key: &ViewAccountKey,
+        account_index: u32,
         name: Option<String>,
         import_block_index: u64,
         first_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
         managed_by_hardware_wallet: bool,
         require_spend_subaddress: bool,
+        subaddresses: &[(u64, String)],
         conn: Conn,
     ) -> Result<Account, WalletDbError>;
 
     fn import_view_only_from_hardware_wallet_with_fog(
         view_account_key: &ViewAccountKey,
+        account_index: u32,
         name: Option<String>,
         import_block_index: u64,
         first_block_index: Option<u64>,
@@ -266,7 +301,8 @@ pub trait AccountModel {
         conn: Conn,
     ) -> Result<Account, WalletDbError>;
 
-    /// List all accounts from wallet DB.
+    /// List all accounts from wallet DB, ordered by the block index they were created or
+    /// imported at, then by account id.
     ///
     /// # Arguments
     ///
@@ -284,6 +320,38 @@ pub trait AccountModel {
         limit: Option<u64>,
     ) -> Result<Vec<Account>, WalletDbError>;
 
+    /// List all accounts from wallet DB in the given order.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                                   | Notes                    |
+    ///|--------------|-----------------------------------------------------------|--------------------------|
+    ///| `conn`       | An reference to the pool connection of wallet database    |                          |
+    ///| `sort_order` | The order to list the accounts in.                        | Ties are broken by id.   |
+    ///| `offset`     | The pagination offset. Results start at the offset index. | Optional, defaults to 0. |
+    ///| `limit`      | Limit for the number of results.                          | Optional                 |
+    ///
+    /// # Returns:
+    /// * Vector of all Accounts in the DB
+    fn list_all_sorted(
+        conn: Conn,
+        sort_order: AccountSortOrder,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<Account>, WalletDbError>;
+
+    /// Count the accounts in the wallet DB.
+    ///
+    /// # Arguments
+    ///
+    ///| Name   | Purpose                                                | Notes |
+    ///|--------|--------------------------------------------------------|-------|
+    ///| `conn` | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * number of accounts (u64)
+    fn count(conn: Conn) -> Result<u64, WalletDbError>;
+
     /// Get a specific account.
     ///
     /// # Arguments
@@ -350,6 +418,59 @@ pub trait AccountModel {
         conn: Conn,
     ) -> Result<(), WalletDbError>;
 
+    /// Update the account's require_sender_memo mode.
+    /// * Only Txos received after the update are checked for a sender memo.
+    ///
+    /// # Arguments
+    ///| Name                  | Purpose                                                        | Notes |
+    ///|-----------------------|----------------------------------------------------------------|-------|
+    ///| `require_sender_memo` | Whether Txos received without a sender memo should be flagged. |       |
+    ///| `conn`                | An reference to the pool connection of wallet database         |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn update_require_sender_memo(
+        &self,
+        require_sender_memo: bool,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
+    /// Update the subaddress the account receives at when no subaddress is given.
+    /// * The subaddress must be assigned to the account, and may not be the change subaddress.
+    ///
+    /// # Arguments
+    ///| Name               | Purpose                                                | Notes |
+    ///|--------------------|--------------------------------------------------------|-------|
+    ///| `subaddress_index` | The index of the new default receive subaddress.       |       |
+    ///| `conn`             | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn update_default_receive_subaddress_index(
+        &self,
+        subaddress_index: i64,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
+    /// Update the first block index for current account.
+    /// * The next block index is not changed; callers are responsible for
+    ///   rewinding it if earlier blocks should be scanned.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                | Purpose                                                 | Notes |
+    ///|---------------------|---------------------------------------------------------|-------|
+    ///| `first_block_index` | The first block index in the ledger for this account.   |       |
+    ///| `conn`              | An reference to the pool connection of wallet database  |       |
+    ///
+    /// # Returns:
+    /// * unit
+    fn update_first_block_index(
+        &self,
+        first_block_index: u64,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
     /// Update the next block index in current account that needs to sync.
     ///
     /// # Arguments
@@ -415,6 +536,47 @@ pub trait AccountModel {
     /// * Vector of all TokenIds
     fn get_token_ids(self, conn: Conn) -> Result<Vec<TokenId>, WalletDbError>;
 
+    /// Get the total value ever received by the current account for each token id, whether or
+    /// not it has since been spent. Change, including change received at the legacy change
+    /// subaddress, is not counted as received.
+    ///
+    /// # Arguments
+    ///
+    ///| Name               | Purpose                                                     | Notes |
+    ///|--------------------|-------------------------------------------------------------|-------|
+    ///| `conn`             | An reference to the pool connection of wallet database      |       |
+    ///
+    /// # Returns:
+    /// * Map of TokenId to total value received
+    fn lifetime_received(&self, conn: Conn) -> Result<BTreeMap<TokenId, u128>, WalletDbError>;
+
+    /// Get the total value ever sent by the current account for each token id, from the outputs
+    /// of its submitted transaction logs. Change outputs, fees, and failed transactions are not
+    /// counted.
+    ///
+    /// # Arguments
+    ///
+    ///| Name               | Purpose                                                     | Notes |
+    ///|--------------------|-------------------------------------------------------------|-------|
+    ///| `conn`             | An reference to the pool connection of wallet database      |       |
+    ///
+    /// # Returns:
+    /// * Map of TokenId to total value sent
+    fn lifetime_sent(&self, conn: Conn) -> Result<BTreeMap<TokenId, u128>, WalletDbError>;
+
+    /// Get the index of the last block in which the current account received a Txo, or had one
+    /// of its transactions submitted or finalized.
+    ///
+    /// # Arguments
+    ///
+    ///| Name               | Purpose                                                     | Notes |
+    ///|--------------------|-------------------------------------------------------------|-------|
+    ///| `conn`             | An reference to the pool connection of wallet database      |       |
+    ///
+    /// # Returns:
+    /// * The block index, or None if the account has had no activity
+    fn last_activity_block_index(&self, conn: Conn) -> Result<Option<u64>, WalletDbError>;
+
     /// Get the next sequentially unassigned subaddress index for the account
     /// * reserved addresses are not included
     ///
@@ -472,12 +634,20 @@ pub trait AccountModel {
 
     fn update_resyncing(&self, resyncing: bool, conn: Conn) -> Result<(), WalletDbError>;
 
+    /// Set whether the account was imported and has not yet caught up to the ledger tip.
+    fn update_import_in_progress(
+        &self,
+        import_in_progress: bool,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
     fn resync_in_progress(conn: Conn) -> Result<bool, WalletDbError>;
 }
 
 impl AccountModel for Account {
     fn create_from_mnemonic(
         mnemonic: &Mnemonic,
+        account_index: u32,
         first_block_index: Option<u64>,
         import_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
@@ -489,7 +659,7 @@ impl AccountModel for Account {
     ) -> Result<(AccountID, String), WalletDbError> {
         let fog_enabled = !fog_report_url.is_empty();
 
-        let slip_10_key = mnemonic.clone().derive_slip10_key(0);
+        let slip_10_key = mnemonic.clone().derive_slip10_key(account_index);
         let account_key: AccountKey = slip_10_key.into();
         let account_key_with_fog = account_key.with_fog(
             &fog_report_url,
@@ -501,6 +671,7 @@ impl AccountModel for Account {
             mnemonic.entropy(),
             MNEMONIC_KEY_DERIVATION_VERSION,
             &account_key_with_fog,
+            account_index,
             first_block_index,
             import_block_index,
             next_subaddress_index,
@@ -536,6 +707,7 @@ impl AccountModel for Account {
             &entropy.bytes,
             ROOT_ENTROPY_KEY_DERIVATION_VERSION,
             &account_key,
+            0,
             first_block_index,
             import_block_index,
             next_subaddress_index,
@@ -550,6 +722,7 @@ impl AccountModel for Account {
         entropy: &[u8],
         key_derivation_version: u8,
         account_key: &AccountKey,
+        account_index: u32,
         first_block_index: Option<u64>,
         import_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
@@ -585,6 +758,8 @@ impl AccountModel for Account {
             view_only: false,
             managed_by_hardware_wallet: false,
             require_spend_subaddress,
+            import_in_progress: false,
+            account_index: account_index as i64,
         };
 
         diesel::insert_into(accounts::table)
@@ -614,6 +789,7 @@ impl AccountModel for Account {
 
     fn import(
         mnemonic: &Mnemonic,
+        account_index: u32,
         name: Option<String>,
         import_block_index: u64,
         first_block_index: Option<u64>,
@@ -625,6 +801,7 @@ impl AccountModel for Account {
     ) -> Result<Account, WalletDbError> {
         let (account_id, _public_address_b58) = Account::create_from_mnemonic(
             mnemonic,
+            account_index,
             first_block_index,
             Some(import_block_index),
             next_subaddress_index,
@@ -634,6 +811,8 @@ impl AccountModel for Account {
             require_spend_subaddress,
             conn,
         )?;
+        let account = Account::get(&account_id, conn)?;
+        account.update_import_in_progress(true, conn)?;
         Account::get(&account_id, conn)
     }
 
@@ -659,17 +838,21 @@ impl AccountModel for Account {
             require_spend_subaddress,
             conn,
         )?;
+        let account = Account::get(&account_id, conn)?;
+        account.update_import_in_progress(true, conn)?;
         Account::get(&account_id, conn)
     }
 
     fn import_view_only(
         view_account_key: &ViewAccountKey,
+        account_index: u32,
         name: Option<String>,
         import_block_index: u64,
         first_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
         managed_by_hardware_wallet: bool,
         require_spend_subaddress: bool,
+        subaddresses: &[(u64, String)],
         conn: Conn,
     ) -> Result<Account, WalletDbError> {
         use crate::db::schema::accounts;
@@ -686,6 +869,23 @@ impl AccountModel for Account {
         let next_subaddress_index =
             next_subaddress_index.unwrap_or(DEFAULT_NEXT_SUBADDRESS_INDEX) as i64;
 
+        let mut subaddress_comments = BTreeMap::new();
+        for (subaddress_index, comment) in subaddresses {
+            if [
+                DEFAULT_SUBADDRESS_INDEX,
+                LEGACY_CHANGE_SUBADDRESS_INDEX,
+                CHANGE_SUBADDRESS_INDEX,
+                INVALID_SUBADDRESS_INDEX,
+            ]
+            .contains(subaddress_index)
+            {
+                return Err(WalletDbError::InvalidArgument(format!(
+                    "Subaddress index {subaddress_index} is reserved"
+                )));
+            }
+            subaddress_comments.insert(*subaddress_index, comment.as_str());
+        }
+
         let new_account = NewAccount {
             id: &account_id.to_string(),
             account_key: &mc_util_serial::encode(view_account_key),
@@ -699,6 +899,8 @@ impl AccountModel for Account {
             view_only: true,
             managed_by_hardware_wallet,
             require_spend_subaddress,
+            import_in_progress: true,
+            account_index: account_index as i64,
         };
 
         diesel::insert_into(accounts::table)
@@ -730,7 +932,20 @@ impl AccountModel for Account {
             AssignedSubaddress::create_for_view_only_account(
                 view_account_key,
                 subaddress_index,
-                "",
+                subaddress_comments
+                    .get(&subaddress_index)
+                    .copied()
+                    .unwrap_or_default(),
+                conn,
+            )?;
+        }
+
+        for (subaddress_index, comment) in subaddress_comments.range(next_subaddress_index as u64..)
+        {
+            AssignedSubaddress::create_for_view_only_account(
+                view_account_key,
+                *subaddress_index,
+                comment,
                 conn,
             )?;
         }
@@ -740,6 +955,7 @@ impl AccountModel for Account {
 
     fn import_view_only_from_hardware_wallet_with_fog(
         view_account_key: &ViewAccountKey,
+        account_index: u32,
         name: Option<String>,
         import_block_index: u64,
         first_block_index: Option<u64>,
@@ -771,6 +987,8 @@ impl AccountModel for Account {
             view_only: true,
             managed_by_hardware_wallet: true,
             require_spend_subaddress,
+            import_in_progress: true,
+            account_index: account_index as i64,
         };
 
         diesel::insert_into(accounts::table)
@@ -799,10 +1017,26 @@ impl AccountModel for Account {
         conn: Conn,
         offset: Option<u64>,
         limit: Option<u64>,
+    ) -> Result<Vec<Account>, WalletDbError> {
+        Account::list_all_sorted(conn, AccountSortOrder::default(), offset, limit)
+    }
+
+    fn list_all_sorted(
+        conn: Conn,
+        sort_order: AccountSortOrder,
+        offset: Option<u64>,
+        limit: Option<u64>,
     ) -> Result<Vec<Account>, WalletDbError> {
         use crate::db::schema::accounts;
 
-        let mut query = accounts::table.into_boxed();
+        let mut query = match sort_order {
+            AccountSortOrder::ImportBlockIndex => accounts::table
+                .order((accounts::import_block_index, accounts::id))
+                .into_boxed(),
+            AccountSortOrder::Name => accounts::table
+                .order((accounts::name, accounts::id))
+                .into_boxed(),
+        };
 
         if let (Some(offset), Some(limit)) = (offset, limit) {
             query = query.limit(limit as i64).offset(offset as i64);
@@ -811,6 +1045,13 @@ impl AccountModel for Account {
         Ok(query.load(conn)?)
     }
 
+    fn count(conn: Conn) -> Result<u64, WalletDbError> {
+        use crate::db::schema::accounts;
+
+        let count: i64 = accounts::table.count().get_result(conn)?;
+        Ok(count as u64)
+    }
+
     fn get(account_id: &AccountID, conn: Conn) -> Result<Account, WalletDbError> {
         use crate::db::schema::accounts;
 
@@ -862,6 +1103,51 @@ impl AccountModel for Account {
         Ok(())
     }
 
+    fn update_require_sender_memo(
+        &self,
+        require_sender_memo: bool,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::accounts;
+
+        diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
+            .set(accounts::require_sender_memo.eq(require_sender_memo))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn update_default_receive_subaddress_index(
+        &self,
+        subaddress_index: i64,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::accounts;
+
+        if subaddress_index as u64 == CHANGE_SUBADDRESS_INDEX {
+            return Err(WalletDbError::InvalidArgument(
+                "The change subaddress cannot be the default receive subaddress".to_string(),
+            ));
+        }
+        AssignedSubaddress::get_for_account_by_index(&self.id, subaddress_index, conn)?;
+
+        diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
+            .set(accounts::default_receive_subaddress_index.eq(subaddress_index))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn update_first_block_index(
+        &self,
+        first_block_index: u64,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::accounts;
+        diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
+            .set(accounts::first_block_index.eq(first_block_index as i64))
+            .execute(conn)?;
+        Ok(())
+    }
+
     fn update_next_block_index(
         &self,
         next_block_index: u64,
@@ -921,6 +1207,69 @@ impl AccountModel for Account {
         Ok(distinct_token_ids)
     }
 
+    fn lifetime_received(&self, conn: Conn) -> Result<BTreeMap<TokenId, u128>, WalletDbError> {
+        use crate::db::schema::txos;
+
+        let values = txos::table
+            .filter(txos::account_id.eq(&self.id))
+            .filter(txos::received_block_index.is_not_null())
+            .filter(
+                txos::subaddress_index
+                    .is_null()
+                    .or(txos::subaddress_index.ne_all(vec![
+                        CHANGE_SUBADDRESS_INDEX as i64,
+                        LEGACY_CHANGE_SUBADDRESS_INDEX as i64,
+                    ])),
+            )
+            .select((txos::token_id, txos::value))
+            .load::<(i64, i64)>(conn)?;
+
+        Ok(sum_values_by_token(values))
+    }
+
+    fn lifetime_sent(&self, conn: Conn) -> Result<BTreeMap<TokenId, u128>, WalletDbError> {
+        use crate::db::schema::{transaction_logs, transaction_output_txos, txos};
+
+        let values = transaction_output_txos::table
+            .inner_join(transaction_logs::table)
+            .inner_join(txos::table)
+            .filter(transaction_logs::account_id.eq(&self.id))
+            .filter(transaction_logs::failed.eq(false))
+            .filter(
+                transaction_logs::submitted_block_index
+                    .is_not_null()
+                    .or(transaction_logs::finalized_block_index.is_not_null()),
+            )
+            .filter(transaction_output_txos::is_change.eq(false))
+            .select((txos::token_id, txos::value))
+            .load::<(i64, i64)>(conn)?;
+
+        Ok(sum_values_by_token(values))
+    }
+
+    fn last_activity_block_index(&self, conn: Conn) -> Result<Option<u64>, WalletDbError> {
+        use crate::db::schema::{transaction_logs, txos};
+
+        let last_received: Option<i64> = txos::table
+            .filter(txos::account_id.eq(&self.id))
+            .select(diesel::dsl::max(txos::received_block_index))
+            .first(conn)?;
+
+        let (last_submitted, last_finalized): (Option<i64>, Option<i64>) = transaction_logs::table
+            .filter(transaction_logs::account_id.eq(&self.id))
+            .select((
+                diesel::dsl::max(transaction_logs::submitted_block_index),
+                diesel::dsl::max(transaction_logs::finalized_block_index),
+            ))
+            .first(conn)?;
+
+        Ok([last_received, last_submitted, last_finalized]
+            .into_iter()
+            .flatten()
+            .max()
+            .map(|block_index| block_index as u64))
+    }
+
     fn next_subaddress_index(self, conn: Conn) -> Result<u64, WalletDbError> {
         use crate::db::schema::assigned_subaddresses;
 
@@ -981,6 +1330,19 @@ impl AccountModel for Account {
         Ok(())
     }
 
+    fn update_import_in_progress(
+        &self,
+        import_in_progress: bool,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use crate::db::schema::accounts;
+
+        diesel::update(accounts::table.filter(accounts::id.eq(&self.id)))
+            .set(accounts::import_in_progress.eq(import_in_progress))
+            .execute(conn)?;
+        Ok(())
+    }
+
     fn resync_in_progress(conn: Conn) -> Result<bool, WalletDbError> {
         use crate::db::schema::accounts;
 
@@ -991,13 +1353,31 @@ impl AccountModel for Account {
     }
 }
 
+/// Convert per-token sums of the stored (i64) txo values to a map of TokenId to
+/// total value.
+/// Sum txo values by token. The values are summed as u128, since their sum
+/// can overflow the i64 that SQLite sums integers as.
+fn sum_values_by_token(values: Vec<(i64, i64)>) -> BTreeMap<TokenId, u128> {
+    let mut totals = BTreeMap::new();
+    for (token_id, value) in values {
+        *totals.entry(TokenId::from(token_id as u64)).or_insert(0) += (value as u64) as u128;
+    }
+    totals
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{test_utils::WalletDbTestContext, util::b58::b58_encode_public_address};
+    use crate::{
+        db::authenticated_sender_memo::SenderMemoFilter,
+        test_utils::{create_test_txo_for_recipient, WalletDbTestContext, MOB},
+        util::b58::b58_encode_public_address,
+    };
+    use bip39::Language;
     use mc_account_keys::RootIdentity;
     use mc_common::logger::{test_with_logger, Logger};
     use mc_crypto_keys::RistrettoPublic;
+    use mc_transaction_core::{tokens::Mob, Amount, Token};
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
     use std::{collections::HashSet, convert::TryFrom, iter::FromIterator, ops::DerefMut};
@@ -1055,6 +1435,10 @@ mod tests {
             managed_by_hardware_wallet: false,
             resyncing: false,
             require_spend_subaddress: false,
+            require_sender_memo: false,
+            default_receive_subaddress_index: 0,
+            import_in_progress: false,
+            account_index: 0,
         };
         assert_eq!(expected_account, acc);
 
@@ -1124,6 +1508,10 @@ mod tests {
             managed_by_hardware_wallet: false,
             resyncing: false,
             require_spend_subaddress: false,
+            require_sender_memo: false,
+            default_receive_subaddress_index: 0,
+            import_in_progress: false,
+            account_index: 0,
         };
         assert_eq!(expected_account_secondary, acc_secondary);
 
@@ -1165,6 +1553,75 @@ mod tests {
         }
     }
 
+    #[test_with_logger]
+    fn test_list_accounts_sorted(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        // The first block index does not affect the order, only the block the
+        // account was imported at.
+        let mut create_account = |import_block_index: u64, name: &str| {
+            let root_id = RootIdentity::from_random(&mut rng);
+            Account::create_from_root_entropy(
+                &root_id.root_entropy,
+                Some(100 - import_block_index),
+                Some(import_block_index),
+                None,
+                name,
+                "".to_string(),
+                "".to_string(),
+                false,
+                conn,
+            )
+            .unwrap()
+            .0
+            .to_string()
+        };
+        let carol = create_account(20, "Carol");
+        let alice = create_account(30, "Alice");
+        let bob_1 = create_account(10, "Bob");
+        let bob_2 = create_account(10, "Bob");
+
+        // Accounts imported at the same block, or with the same name, are
+        // ordered by id.
+        let (bob_1, bob_2) = if bob_1 < bob_2 {
+            (bob_1, bob_2)
+        } else {
+            (bob_2, bob_1)
+        };
+
+        let ids = |accounts: Vec<Account>| -> Vec<String> {
+            accounts.into_iter().map(|account| account.id).collect()
+        };
+        assert_eq!(
+            ids(Account::list_all(conn, None, None).unwrap()),
+            vec![bob_1.clone(), bob_2.clone(), carol.clone(), alice.clone()]
+        );
+        assert_eq!(
+            ids(Account::list_all_sorted(conn, AccountSortOrder::Name, None, None).unwrap()),
+            vec![alice.clone(), bob_1.clone(), bob_2.clone(), carol]
+        );
+        assert_eq!(
+            ids(Account::list_all_sorted(conn, AccountSortOrder::Name, Some(1), Some(2)).unwrap()),
+            vec![bob_1, bob_2]
+        );
+        assert_eq!(Account::count(conn).unwrap(), 4);
+
+        assert_eq!(
+            AccountSortOrder::from_str("import_block_index").unwrap(),
+            AccountSortOrder::ImportBlockIndex
+        );
+        assert_eq!(
+            AccountSortOrder::from_str("name").unwrap(),
+            AccountSortOrder::Name
+        );
+        assert!(AccountSortOrder::from_str("balance").is_err());
+    }
+
     // Providing entropy should succeed and derive account key.
     #[test_with_logger]
     fn test_create_account_from_entropy(logger: Logger) {
@@ -1204,6 +1661,54 @@ mod tests {
         assert_eq!(decoded_account_key, account_key);
     }
 
+    // Importing a mnemonic at another account index should derive a different
+    // account key, and remember the index.
+    #[test_with_logger]
+    fn test_import_account_at_account_index(logger: Logger) {
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+
+        let mnemonic = Mnemonic::from_entropy(&[7u8; 32], Language::English).unwrap();
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let account_0 = Account::import(
+            &mnemonic,
+            0,
+            None,
+            0,
+            None,
+            None,
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+        let account_1 = Account::import(
+            &mnemonic,
+            1,
+            None,
+            0,
+            None,
+            None,
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        assert_ne!(account_0.id, account_1.id);
+        assert_eq!(account_0.account_index, 0);
+        assert_eq!(account_1.account_index, 1);
+        assert_eq!(account_0.entropy, account_1.entropy);
+
+        let expected_key: AccountKey = mnemonic.derive_slip10_key(1).into();
+        let account_key: AccountKey = mc_util_serial::decode(&account_1.account_key).unwrap();
+        assert_eq!(account_key, expected_key);
+    }
+
     #[test_with_logger]
     fn test_create_fog_account(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -1294,6 +1799,10 @@ mod tests {
             managed_by_hardware_wallet: false,
             resyncing: false,
             require_spend_subaddress: false,
+            require_sender_memo: false,
+            default_receive_subaddress_index: 0,
+            import_in_progress: false,
+            account_index: 0,
         };
         assert_eq!(expected_account, acc);
     }
@@ -1316,12 +1825,14 @@ mod tests {
 
             Account::import_view_only(
                 &view_account_key,
+                0,
                 Some("View Only Account".to_string()),
                 12,
                 None,
                 None,
                 false,
                 false,
+                &[],
                 conn,
             )
             .unwrap()
@@ -1354,10 +1865,72 @@ mod tests {
             managed_by_hardware_wallet: false,
             resyncing: false,
             require_spend_subaddress: false,
+            require_sender_memo: false,
+            default_receive_subaddress_index: 0,
+            import_in_progress: true,
+            account_index: 0,
         };
         assert_eq!(expected_account, account);
     }
 
+    #[test_with_logger]
+    fn test_import_view_only_account_with_subaddresses(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+
+        let view_private_key = RistrettoPrivate::from_random(&mut rng);
+        let spend_public_key = RistrettoPublic::from_random(&mut rng);
+        let view_account_key = ViewAccountKey::new(view_private_key, spend_public_key);
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        // Reserved subaddress indices cannot be seeded.
+        match Account::import_view_only(
+            &view_account_key,
+            0,
+            None,
+            12,
+            None,
+            None,
+            false,
+            false,
+            &[(CHANGE_SUBADDRESS_INDEX, "Not Change".to_string())],
+            conn,
+        ) {
+            Err(WalletDbError::InvalidArgument(_)) => {}
+            other => panic!("Expected InvalidArgument, got {other:?}"),
+        }
+        assert!(Account::list_all(conn, None, None).unwrap().is_empty());
+
+        let account = Account::import_view_only(
+            &view_account_key,
+            0,
+            None,
+            12,
+            None,
+            Some(5),
+            false,
+            false,
+            &[(3, "Alice".to_string()), (100, "Bob".to_string())],
+            conn,
+        )
+        .unwrap();
+
+        // Subaddresses below the next subaddress index are assigned with
+        // their comments, and those above it are assigned in addition.
+        let alice = AssignedSubaddress::get_for_account_by_index(&account.id, 3, conn).unwrap();
+        assert_eq!(alice.comment, "Alice");
+        let unlabeled = AssignedSubaddress::get_for_account_by_index(&account.id, 4, conn).unwrap();
+        assert_eq!(unlabeled.comment, "");
+        let bob = AssignedSubaddress::get_for_account_by_index(&account.id, 100, conn).unwrap();
+        assert_eq!(bob.comment, "Bob");
+        assert!(AssignedSubaddress::get_for_account_by_index(&account.id, 5, conn).is_err());
+        assert_eq!(account.next_subaddress_index(conn).unwrap(), 101);
+    }
+
     #[test_with_logger]
     fn test_import_view_only_from_hardware_wallet_with_fog(logger: Logger) {
         // Test Setup
@@ -1382,6 +1955,7 @@ mod tests {
 
             Account::import_view_only_from_hardware_wallet_with_fog(
                 &view_account_key,
+                1,
                 Some("View Only Account".to_string()),
                 12,
                 None,
@@ -1416,6 +1990,10 @@ mod tests {
             managed_by_hardware_wallet: true,
             resyncing: false,
             require_spend_subaddress: false,
+            require_sender_memo: false,
+            default_receive_subaddress_index: 0,
+            import_in_progress: true,
+            account_index: 1,
         };
 
         // Check to make sure the account in the database is correct
@@ -1452,4 +2030,65 @@ mod tests {
 
         assert_eq!(default_subaddress, expected_default_subaddress);
     }
+
+    #[test_with_logger]
+    fn test_lifetime_received_excludes_legacy_change(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let account = Account::import_legacy(
+            &root_id.root_entropy,
+            None,
+            0,
+            None,
+            None,
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        // Legacy accounts received their change at the legacy change subaddress.
+        for (subaddress_index, value) in [
+            (DEFAULT_SUBADDRESS_INDEX, 70 * MOB),
+            (LEGACY_CHANGE_SUBADDRESS_INDEX, 20 * MOB),
+            (CHANGE_SUBADDRESS_INDEX, 10 * MOB),
+        ] {
+            let amount = Amount::new(value, Mob::ID);
+            let (txo, key_image) =
+                create_test_txo_for_recipient(&account_key, subaddress_index, amount, &mut rng);
+            Txo::create_received(
+                txo,
+                Some(subaddress_index),
+                Some(key_image),
+                amount,
+                15,
+                &account.id,
+                &SenderMemoFilter::default(),
+                conn,
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            account.lifetime_received(conn).unwrap(),
+            BTreeMap::from([(Mob::ID, 70 * MOB as u128)])
+        );
+    }
+
+    #[test]
+    fn test_sum_values_by_token() {
+        // Values are stored as i64, so the largest values read back negative.
+        let max_value = u64::MAX as i64;
+        let totals = sum_values_by_token(vec![(0, max_value), (0, max_value), (1, 5)]);
+        assert_eq!(totals[&TokenId::from(0)], 2 * u64::MAX as u128);
+        assert_eq!(totals[&TokenId::from(1)], 5);
+    }
 }
@@ -2,6 +2,7 @@
 
 use crate::db::{models::AuthenticatedSenderMemo, Conn, WalletDbError};
 use diesel::RunQueryDsl;
+use std::{collections::HashSet, str::FromStr};
 
 impl AuthenticatedSenderMemo {
     pub fn list(conn: Conn) -> Result<Vec<AuthenticatedSenderMemo>, WalletDbError> {
@@ -9,3 +10,57 @@ impl AuthenticatedSenderMemo {
         Ok(authenticated_sender_memos::table.load(conn)?)
     }
 }
+
+/// The types of authenticated sender memo which may be stored when syncing.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SenderMemoType {
+    AuthenticatedSender,
+    AuthenticatedSenderWithPaymentRequestId,
+    AuthenticatedSenderWithPaymentIntentId,
+}
+
+impl FromStr for SenderMemoType {
+    type Err = WalletDbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "authenticated_sender" => Ok(SenderMemoType::AuthenticatedSender),
+            "authenticated_sender_with_payment_request_id" => {
+                Ok(SenderMemoType::AuthenticatedSenderWithPaymentRequestId)
+            }
+            "authenticated_sender_with_payment_intent_id" => {
+                Ok(SenderMemoType::AuthenticatedSenderWithPaymentIntentId)
+            }
+            _ => Err(WalletDbError::InvalidSenderMemoType(s.to_string())),
+        }
+    }
+}
+
+/// Which types of authenticated sender memo are stored when a Txo is received.
+/// The memo of a Txo whose memo type is not stored reports as unused. By
+/// default, every type is stored.
+#[derive(Clone, Debug, Default)]
+pub struct SenderMemoFilter {
+    /// If set, only these memo types are stored.
+    stored: Option<HashSet<SenderMemoType>>,
+
+    /// Memo types which are never stored.
+    skipped: HashSet<SenderMemoType>,
+}
+
+impl SenderMemoFilter {
+    pub fn new(stored: Option<Vec<SenderMemoType>>, skipped: Option<Vec<SenderMemoType>>) -> Self {
+        Self {
+            stored: stored.map(HashSet::from_iter),
+            skipped: skipped.map(HashSet::from_iter).unwrap_or_default(),
+        }
+    }
+
+    /// Whether memos of the given type are stored.
+    pub fn stores(&self, memo_type: SenderMemoType) -> bool {
+        self.stored
+            .as_ref()
+            .map_or(true, |stored| stored.contains(&memo_type))
+            && !self.skipped.contains(&memo_type)
+    }
+}
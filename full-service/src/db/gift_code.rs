@@ -7,6 +7,7 @@ use crate::{
         models::{GiftCode, NewGiftCode},
         Conn, WalletDbError,
     },
+    error::ErrorCode,
     service::gift_code::EncodedGiftCode,
 };
 use diesel::prelude::*;
@@ -18,6 +19,14 @@ pub enum GiftCodeDbError {
     GiftCodeNotFound(String),
 }
 
+impl ErrorCode for GiftCodeDbError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::GiftCodeNotFound(..) => "gift_code_not_found",
+        }
+    }
+}
+
 #[rustfmt::skip]
 pub trait GiftCodeModel {
     /// Create a gift code.
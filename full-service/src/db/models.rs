@@ -4,8 +4,8 @@
 
 use super::schema::{
     __diesel_schema_migrations, accounts, assigned_subaddresses, authenticated_sender_memos,
-    destination_memos, gift_codes, transaction_input_txos, transaction_logs,
-    transaction_output_txos, txos,
+    destination_memos, gift_codes, post_migration_processes, transaction_input_txos,
+    transaction_logs, transaction_output_txos, txos,
 };
 use mc_crypto_keys::CompressedRistrettoPublic;
 use serde::Serialize;
@@ -42,6 +42,19 @@ pub struct Account {
     pub resyncing: bool,
     /// If true, this account is only allowed to spend from subaddresses.
     pub require_spend_subaddress: bool,
+    /// If true, Txos received by this account without a sender memo are
+    /// flagged with `missing_required_memo`.
+    pub require_sender_memo: bool,
+    /// The subaddress used for receiving when no subaddress is given, such as
+    /// for new payment requests.
+    pub default_receive_subaddress_index: i64,
+    /// If true, this account was imported and has not yet been synced up to
+    /// the tip of the ledger, so its balance may still change as past blocks
+    /// are scanned.
+    pub import_in_progress: bool,
+    /// The SLIP-0010 account index the account key was derived at from the
+    /// mnemonic. Always 0 for accounts which were not derived from a mnemonic.
+    pub account_index: i64,
 }
 
 /// A structure that can be inserted to create a new entity in the `accounts`
@@ -61,6 +74,8 @@ pub struct NewAccount<'a> {
     pub view_only: bool,
     pub managed_by_hardware_wallet: bool,
     pub require_spend_subaddress: bool,
+    pub import_in_progress: bool,
+    pub account_index: i64,
 }
 
 /// A transaction output entity that either was received to an Account in this
@@ -96,6 +111,9 @@ pub struct Txo {
     pub shared_secret: Option<Vec<u8>>,
     pub memo_type: Option<i32>,
     pub is_synced_to_t3: bool,
+    /// Whether this Txo was received without a memo by an account which
+    /// requires a sender memo.
+    pub missing_required_memo: bool,
 }
 
 impl Txo {
@@ -166,6 +184,7 @@ pub struct TransactionLog {
     pub comment: String,
     pub tx: Vec<u8>,
     pub failed: bool,
+    pub conflicting: bool,
 }
 
 /// A structure that can be inserted to create a new TransactionLog entity.
@@ -313,3 +332,10 @@ pub struct PostMigrationProcess {
     pub migration_version: String,
     pub has_run: bool,
 }
+
+#[derive(Insertable)]
+#[diesel(table_name = post_migration_processes)]
+pub struct NewPostMigrationProcess {
+    pub migration_version: String,
+    pub has_run: bool,
+}
@@ -15,6 +15,10 @@ diesel::table! {
         managed_by_hardware_wallet -> Bool,
         resyncing -> Bool,
         require_spend_subaddress -> Bool,
+        require_sender_memo -> Bool,
+        default_receive_subaddress_index -> BigInt,
+        import_in_progress -> Bool,
+        account_index -> BigInt,
     }
 }
 
@@ -57,6 +61,13 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    post_migration_processes (migration_version) {
+        migration_version -> Text,
+        has_run -> Bool,
+    }
+}
+
 diesel::table! {
     transaction_input_txos (transaction_log_id, txo_id) {
         transaction_log_id -> Text,
@@ -76,6 +87,7 @@ diesel::table! {
         comment -> Text,
         tx -> Binary,
         failed -> Bool,
+        conflicting -> Bool,
     }
 }
 
@@ -105,6 +117,7 @@ diesel::table! {
         shared_secret -> Nullable<Binary>,
         memo_type -> Nullable<Integer>,
         is_synced_to_t3 -> Bool,
+        missing_required_memo -> Bool,
     }
 }
 
@@ -131,6 +144,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     authenticated_sender_memos,
     destination_memos,
     gift_codes,
+    post_migration_processes,
     transaction_input_txos,
     transaction_logs,
     transaction_output_txos,
@@ -4,16 +4,26 @@
 
 use diesel::prelude::*;
 use hex_fmt::HexFmt;
+use mc_account_keys::CHANGE_SUBADDRESS_INDEX;
 use mc_common::HashMap;
-use mc_transaction_core::{Amount, TokenId};
+use mc_crypto_keys::RistrettoPublic;
+use mc_transaction_core::{
+    get_tx_out_shared_secret,
+    onetime_keys::recover_public_subaddress_spend_key,
+    ring_signature::KeyImage,
+    tx::{Tx, TxOut},
+    Amount, TokenId,
+};
+use mc_transaction_extra::TxOutConfirmationNumber;
 use std::{convert::TryFrom, fmt};
 
 use crate::{
     db::{
         account::{AccountID, AccountModel},
+        assigned_subaddress::AssignedSubaddressModel,
         models::{
-            Account, NewTransactionInputTxo, NewTransactionLog, TransactionInputTxo,
-            TransactionLog, TransactionOutputTxo, Txo,
+            Account, AssignedSubaddress, NewTransactionInputTxo, NewTransactionLog,
+            TransactionInputTxo, TransactionLog, TransactionOutputTxo, Txo,
         },
         schema::{
             transaction_input_txos, transaction_logs,
@@ -49,6 +59,25 @@ impl TryFrom<&UnsignedTxProposal> for TransactionId {
     }
 }
 
+impl TryFrom<&Tx> for TransactionId {
+    type Error = &'static str;
+    fn try_from(tx: &Tx) -> Result<Self, Self::Error> {
+        // The payload outputs of a raw Tx are unknown, so all of its outputs
+        // are considered.
+        Ok(Self(
+            HexFmt(
+                tx.prefix
+                    .outputs
+                    .iter()
+                    .map(|tx_out| tx_out.public_key)
+                    .min()
+                    .ok_or("no valid output")?,
+            )
+            .to_string(),
+        ))
+    }
+}
+
 impl TryFrom<Vec<OutputTxo>> for TransactionId {
     type Error = &'static str;
     fn try_from(_payload_txos: Vec<OutputTxo>) -> Result<Self, Self::Error> {
@@ -85,6 +114,9 @@ pub enum TxStatus {
     // Either consensus has rejected the tx proposal, or the tombstone block index has passed
     // without the txos in this transaction showing on the ledger
     Failed,
+    // The transaction was submitted, but one of its inputs was spent by a different transaction,
+    // so it can never succeed
+    Conflicting,
 }
 
 impl fmt::Display for TxStatus {
@@ -95,6 +127,7 @@ impl fmt::Display for TxStatus {
             TxStatus::Pending => write!(f, "pending"),
             TxStatus::Succeeded => write!(f, "succeeded"),
             TxStatus::Failed => write!(f, "failed"),
+            TxStatus::Conflicting => write!(f, "conflicting"),
         }
     }
 }
@@ -242,6 +275,62 @@ pub trait TransactionLogModel {
         conn: Conn,
     ) -> Result<Vec<(TransactionLog, AssociatedTxos, ValueMap)>, WalletDbError>;
 
+    /// List the transaction logs which spent the given txo as an input or created it as an
+    /// output.
+    ///
+    /// # Arguments
+    ///
+    ///| Name     | Purpose                                                | Notes |
+    ///|----------|--------------------------------------------------------|-------|
+    ///| `txo_id` | The txo to find transaction logs for.                  |       |
+    ///| `conn`   | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * Vec(TransactionLog)
+    fn list_for_txo(txo_id: &str, conn: Conn) -> Result<Vec<TransactionLog>, WalletDbError>;
+
+    /// Count the transaction logs that have been submitted but are not yet finalized or failed.
+    ///
+    /// # Arguments
+    ///
+    ///| Name   | Purpose                                                | Notes |
+    ///|--------|--------------------------------------------------------|-------|
+    ///| `conn` | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * number of pending transaction logs (u64)
+    fn count_pending(conn: Conn) -> Result<u64, WalletDbError>;
+
+    /// List the transaction logs for all accounts that have been submitted but are not yet
+    /// finalized or failed.
+    ///
+    /// # Arguments
+    ///
+    ///| Name   | Purpose                                                | Notes |
+    ///|--------|--------------------------------------------------------|-------|
+    ///| `conn` | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns:
+    /// * Vec(TransactionLog)
+    fn list_pending(conn: Conn) -> Result<Vec<TransactionLog>, WalletDbError>;
+
+    /// List the transaction logs for an account that have been submitted but are not yet
+    /// finalized or failed.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                                | Notes                               |
+    ///|--------------|--------------------------------------------------------|-------------------------------------|
+    ///| `account_id` | The account id to scan for transaction logs.           | Account must exist in the database. |
+    ///| `conn`       | An reference to the pool connection of wallet database |                                     |
+    ///
+    /// # Returns:
+    /// * Vec(TransactionLog)
+    fn list_pending_for_account(
+        account_id: &AccountID,
+        conn: Conn,
+    ) -> Result<Vec<TransactionLog>, WalletDbError>;
+
     /// Log a transaction that has been built but not yet signed.
     /// 
     /// # Arguments
@@ -311,6 +400,33 @@ pub trait TransactionLogModel {
         conn: Conn,
     ) -> Result<TransactionLog, WalletDbError>;
 
+    /// Log a submitted transaction which was not built by this wallet.
+    ///
+    /// The inputs are associated with the log by their key images. Only the outputs sent to the
+    /// account's own subaddresses, such as its change, can be decoded, so those are the only
+    /// outputs associated with the log. The transaction is finalized once all of its key images
+    /// appear in the same block, so one which sends nothing back to the account is tracked too.
+    ///
+    /// # Arguments
+    ///
+    ///| Name             | Purpose                                                      | Notes                               |
+    ///|------------------|--------------------------------------------------------------|-------------------------------------|
+    ///| `tx`             | The submitted transaction that will be logged.               |                                     |
+    ///| `block_index`    | The block index of where the transaction was submitted.      |                                     |
+    ///| `comment`        | The arbitrary comments of the current submitted transaction. |                                     |
+    ///| `account_id_hex` | The account to log the transaction for.                      | Account must exist in the database. |
+    ///| `conn`           | An reference to the pool connection of wallet database       |                                     |
+    ///
+    /// # Returns:
+    /// * TransactionLog
+    fn log_submitted_raw(
+        tx: &Tx,
+        block_index: u64,
+        comment: String,
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<TransactionLog, WalletDbError>;
+
     /// Remove all transaction logs for an account.
     /// 
     /// # Arguments
@@ -327,6 +443,18 @@ pub trait TransactionLogModel {
         conn: Conn
     ) -> Result<(), WalletDbError>;
 
+    /// Delete a transaction log along with its associations to its input and output txos.
+    ///
+    /// # Arguments
+    ///
+    ///| Name   | Purpose                                                | Notes |
+    ///|--------|--------------------------------------------------------|-------|
+    ///| `conn` | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns
+    /// * unit
+    fn delete(&self, conn: Conn) -> Result<(), WalletDbError>;
+
     /// Update the finalized block index to all pending transaction logs that have an output
     /// transaction corresponding to `transaction_output_txo_id_hex`.
     /// 
@@ -342,10 +470,31 @@ pub trait TransactionLogModel {
         conn: Conn,
     ) -> Result<(), WalletDbError>;
 
+    /// Update the finalized block index of an account's pending transaction logs whose key
+    /// images all appear in the same block. This finalizes logged transactions which were not
+    /// built by this wallet, and so may have no outputs associated with them.
+    ///
+    /// Note: When processing inputs and outputs from the same block, be sure to call this prior
+    /// to `update_consumed_txo_to_failed()`.
+    ///
+    /// # Arguments
+    /// * `account_id_hex` - The account whose transaction logs to update.
+    /// * `key_image_block_indices` - The key images which appeared in the ledger, with the block
+    ///   index of each.
+    /// * `conn` - A reference to the pool connection of wallet database
+    fn update_pending_with_key_images_to_succeeded(
+        account_id_hex: &str,
+        key_image_block_indices: &HashMap<KeyImage, u64>,
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
 
     /// Update all transaction logs that have an input transaction corresponding to
     /// `transaction_input_txo_id_hex` to failed.
     ///
+    /// Submitted transaction logs are also marked as conflicting, since the input was
+    /// spent by a different transaction and theirs can never land.
+    ///
     /// Note: When processing inputs and outputs from the same block, be sure to mark the
     /// appropriate transaction logs as succeeded prior to calling this method. See
     /// `update_pending_associated_with_txo_to_succeeded()`.
@@ -414,7 +563,9 @@ pub trait TransactionLogModel {
 
 impl TransactionLogModel for TransactionLog {
     fn status(&self) -> TxStatus {
-        if self.failed {
+        if self.conflicting {
+            TxStatus::Conflicting
+        } else if self.failed {
             TxStatus::Failed
         } else if self.finalized_block_index.is_some() {
             TxStatus::Succeeded
@@ -552,6 +703,55 @@ impl TransactionLogModel for TransactionLog {
         Ok(results)
     }
 
+    fn list_for_txo(txo_id: &str, conn: Conn) -> Result<Vec<TransactionLog>, WalletDbError> {
+        let input_log_ids = transaction_input_txos::table
+            .filter(transaction_input_txos::txo_id.eq(txo_id))
+            .select(transaction_input_txos::transaction_log_id);
+        let output_log_ids = transaction_output_txos::table
+            .filter(transaction_output_txos::txo_id.eq(txo_id))
+            .select(transaction_output_txos::transaction_log_id);
+
+        Ok(transaction_logs::table
+            .filter(
+                transaction_logs::id
+                    .eq_any(input_log_ids)
+                    .or(transaction_logs::id.eq_any(output_log_ids)),
+            )
+            .order(transaction_logs::submitted_block_index.desc())
+            .load(conn)?)
+    }
+
+    fn count_pending(conn: Conn) -> Result<u64, WalletDbError> {
+        let count: i64 = transaction_logs::table
+            .filter(transaction_logs::submitted_block_index.is_not_null())
+            .filter(transaction_logs::finalized_block_index.is_null())
+            .filter(transaction_logs::failed.eq(false))
+            .count()
+            .get_result(conn)?;
+
+        Ok(count as u64)
+    }
+
+    fn list_pending(conn: Conn) -> Result<Vec<TransactionLog>, WalletDbError> {
+        Ok(transaction_logs::table
+            .filter(transaction_logs::submitted_block_index.is_not_null())
+            .filter(transaction_logs::finalized_block_index.is_null())
+            .filter(transaction_logs::failed.eq(false))
+            .load(conn)?)
+    }
+
+    fn list_pending_for_account(
+        account_id: &AccountID,
+        conn: Conn,
+    ) -> Result<Vec<TransactionLog>, WalletDbError> {
+        Ok(transaction_logs::table
+            .filter(transaction_logs::account_id.eq(account_id.to_string()))
+            .filter(transaction_logs::submitted_block_index.is_not_null())
+            .filter(transaction_logs::finalized_block_index.is_null())
+            .filter(transaction_logs::failed.eq(false))
+            .load(conn)?)
+    }
+
     fn log_built(
         unsigned_tx_proposal: &UnsignedTxProposal,
         account_id: &AccountID,
@@ -749,6 +949,75 @@ impl TransactionLogModel for TransactionLog {
         TransactionLog::get(&transaction_log_id, conn)
     }
 
+    fn log_submitted_raw(
+        tx: &Tx,
+        block_index: u64,
+        comment: String,
+        account_id_hex: &str,
+        conn: Conn,
+    ) -> Result<TransactionLog, WalletDbError> {
+        let account = Account::get(&AccountID(account_id_hex.to_string()), conn)?;
+
+        let transaction_log_id = TransactionId::try_from(tx)
+            .map_err(|e| WalletDbError::InvalidArgument(e.to_string()))?;
+
+        match TransactionLog::get(&transaction_log_id, conn) {
+            Ok(transaction_log) => {
+                transaction_log.update_submitted_block_index(block_index, conn)?;
+                transaction_log.update_comment(comment, conn)?;
+            }
+
+            Err(WalletDbError::TransactionLogNotFound(_)) => {
+                let new_transaction_log = NewTransactionLog {
+                    id: &transaction_log_id.to_string(),
+                    account_id: account_id_hex,
+                    fee_value: tx.prefix.fee as i64,
+                    fee_token_id: tx.prefix.fee_token_id as i64,
+                    submitted_block_index: Some(block_index as i64),
+                    tombstone_block_index: Some(tx.prefix.tombstone_block as i64),
+                    finalized_block_index: None,
+                    comment: &comment,
+                    tx: &mc_util_serial::encode(tx),
+                    failed: false,
+                };
+
+                diesel::insert_into(transaction_logs::table)
+                    .values(&new_transaction_log)
+                    .execute(conn)?;
+
+                // The inputs are recognized by their key images.
+                let unspent_key_images =
+                    Txo::list_unspent_or_pending_key_images(account_id_hex, None, conn)?;
+                for key_image in tx.key_images() {
+                    if let Some(txo_id) = unspent_key_images.get(&key_image) {
+                        let transaction_input_txo = NewTransactionInputTxo {
+                            transaction_log_id: &transaction_log_id.to_string(),
+                            txo_id,
+                        };
+
+                        diesel::insert_into(transaction_input_txos::table)
+                            .values(&transaction_input_txo)
+                            .execute(conn)?;
+                    }
+                }
+
+                for tx_out in tx.prefix.outputs.iter() {
+                    if let Some((output_txo, is_change)) =
+                        decode_own_output(tx_out, &account, conn)?
+                    {
+                        Txo::create_new_output(&output_txo, is_change, &transaction_log_id, conn)?;
+                    }
+                }
+            }
+
+            Err(e) => {
+                return Err(e);
+            }
+        }
+
+        TransactionLog::get(&transaction_log_id, conn)
+    }
+
     fn delete_all_for_account(account_id_hex: &str, conn: Conn) -> Result<(), WalletDbError> {
         let transaction_input_txos: Vec<TransactionInputTxo> = transaction_input_txos::table
             .inner_join(transaction_logs::table)
@@ -778,6 +1047,25 @@ impl TransactionLogModel for TransactionLog {
         Ok(())
     }
 
+    fn delete(&self, conn: Conn) -> Result<(), WalletDbError> {
+        diesel::delete(
+            transaction_input_txos::table
+                .filter(transaction_input_txos::transaction_log_id.eq(&self.id)),
+        )
+        .execute(conn)?;
+
+        diesel::delete(
+            transaction_output_txos::table
+                .filter(transaction_output_txos::transaction_log_id.eq(&self.id)),
+        )
+        .execute(conn)?;
+
+        diesel::delete(transaction_logs::table.filter(transaction_logs::id.eq(&self.id)))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
     fn update_pending_associated_with_txo_to_succeeded(
         transaction_output_txo_id_hex: &str,
         finalized_block_index: u64,
@@ -803,6 +1091,40 @@ impl TransactionLogModel for TransactionLog {
         Ok(())
     }
 
+    fn update_pending_with_key_images_to_succeeded(
+        account_id_hex: &str,
+        key_image_block_indices: &HashMap<KeyImage, u64>,
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        let pending_transaction_logs: Vec<TransactionLog> = transaction_logs::table
+            .filter(transaction_logs::account_id.eq(account_id_hex))
+            .filter(transaction_logs::submitted_block_index.is_not_null())
+            .filter(transaction_logs::failed.eq(false))
+            .filter(transaction_logs::finalized_block_index.is_null())
+            .load(conn)?;
+
+        for transaction_log in pending_transaction_logs {
+            let tx: Tx = mc_util_serial::decode(&transaction_log.tx)?;
+            let mut block_indices = tx
+                .key_images()
+                .into_iter()
+                .map(|key_image| key_image_block_indices.get(&key_image).copied());
+
+            // A transaction lands in a single block, so if only some of its key
+            // images appear, they were spent by a different transaction.
+            let Some(Some(finalized_block_index)) = block_indices.next() else {
+                continue;
+            };
+            if block_indices.all(|block_index| block_index == Some(finalized_block_index)) {
+                diesel::update(&transaction_log)
+                    .set(transaction_logs::finalized_block_index.eq(finalized_block_index as i64))
+                    .execute(conn)?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn update_consumed_txo_to_failed(
         transaction_input_txo_id_hex: &str,
         conn: Conn,
@@ -815,6 +1137,18 @@ impl TransactionLogModel for TransactionLog {
             .select(transaction_logs::id)
             .load(conn)?;
 
+        // Any transaction log which has not been finalized by now lost its input to a
+        // different transaction. If it was submitted, it was competing with that
+        // transaction for the input, which clients should be able to tell apart
+        // from a generic failure.
+        diesel::update(
+            transaction_logs::table
+                .filter(transaction_logs::id.eq_any(&transaction_log_ids))
+                .filter(transaction_logs::submitted_block_index.is_not_null()),
+        )
+        .set((transaction_logs::conflicting.eq(true),))
+        .execute(conn)?;
+
         diesel::update(
             transaction_logs::table.filter(transaction_logs::id.eq_any(transaction_log_ids)),
         )
@@ -868,27 +1202,85 @@ impl TransactionLogModel for TransactionLog {
     }
 }
 
+/// Decode an output of a transaction which was not built by this wallet, if it
+/// was sent to one of the account's own subaddresses.
+///
+/// Returns the output and whether it is change.
+fn decode_own_output(
+    tx_out: &TxOut,
+    account: &Account,
+    conn: Conn,
+) -> Result<Option<(OutputTxo, bool)>, WalletDbError> {
+    let (tx_public_key, target_key) = match (
+        RistrettoPublic::try_from(&tx_out.public_key),
+        RistrettoPublic::try_from(&tx_out.target_key),
+    ) {
+        (Ok(tx_public_key), Ok(target_key)) => (tx_public_key, target_key),
+        _ => return Ok(None),
+    };
+
+    let view_private_key = account.view_private_key()?;
+    let shared_secret = get_tx_out_shared_secret(&view_private_key, &tx_public_key);
+    let amount = match tx_out
+        .get_masked_amount()
+        .ok()
+        .and_then(|masked_amount| masked_amount.get_value(&shared_secret).ok())
+    {
+        Some((amount, _)) => amount,
+        None => return Ok(None),
+    };
+
+    let subaddress_spend_public_key =
+        recover_public_subaddress_spend_key(&view_private_key, &target_key, &tx_public_key);
+    let subaddress_index = match AssignedSubaddress::find_by_subaddress_spend_public_key(
+        &subaddress_spend_public_key,
+        conn,
+    ) {
+        Ok((subaddress_index, account_id)) if account_id == account.id => subaddress_index,
+        Ok(_) | Err(WalletDbError::AssignedSubaddressNotFound(_)) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let recipient_public_address =
+        AssignedSubaddress::get_for_account_by_index(&account.id, subaddress_index, conn)?
+            .public_address()?;
+
+    let output_txo = OutputTxo {
+        tx_out: tx_out.clone(),
+        recipient_public_address,
+        confirmation_number: TxOutConfirmationNumber::from(&shared_secret),
+        amount,
+        shared_secret: Some(shared_secret),
+    };
+
+    Ok(Some((
+        output_txo,
+        subaddress_index as u64 == CHANGE_SUBADDRESS_INDEX,
+    )))
+}
+
 #[cfg(test)]
 mod tests {
-    use mc_account_keys::{AccountKey, PublicAddress, RootIdentity, CHANGE_SUBADDRESS_INDEX};
+    use mc_account_keys::{AccountKey, PublicAddress, RootIdentity};
     use mc_common::logger::{async_test_with_logger, test_with_logger, Logger};
     use mc_ledger_db::Ledger;
-    use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, tx::Tx, Token};
-    use mc_transaction_extra::TxOutConfirmationNumber;
+    use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, Token};
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
     use std::{
         assert_matches::assert_matches,
-        collections::HashMap,
+        collections::{BTreeMap, HashMap},
         ops::DerefMut,
         sync::{Arc, Mutex},
     };
 
     use super::*;
     use crate::{
-        db::{account::AccountID, transaction_log::TransactionId, txo::TxoStatus},
+        db::{
+            account::AccountID, authenticated_sender_memo::SenderMemoFilter,
+            transaction_log::TransactionId, txo::TxoStatus,
+        },
         service::{
-            sync::SyncThread, transaction::TransactionMemo,
+            sync::SyncThread, sync_progress::SyncProgress, transaction::TransactionMemo,
             transaction_builder::WalletTransactionBuilder,
         },
         test_utils::{
@@ -922,6 +1314,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -954,7 +1348,11 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let tx_proposal = unsigned_tx_proposal.clone().sign(&account).await.unwrap();
+        let tx_proposal = unsigned_tx_proposal
+            .clone()
+            .sign(&account, None)
+            .await
+            .unwrap();
 
         assert_eq!(
             TransactionId::try_from(&tx_proposal),
@@ -1042,6 +1440,21 @@ mod tests {
         // The subaddress will also be set once received.
         assert_eq!(change_details.subaddress_index, None,);
 
+        // The log can be found from any of its input, output, or change txos
+        for txo_id in [
+            &associated_txos.inputs[0].id,
+            &associated_txos.outputs[0].0.id,
+            &associated_txos.change[0].0.id,
+        ] {
+            let logs = TransactionLog::list_for_txo(
+                txo_id,
+                wallet_db.get_pooled_conn().unwrap().deref_mut(),
+            )
+            .unwrap();
+            assert_eq!(logs.len(), 1);
+            assert_eq!(logs[0].id, tx_log.id);
+        }
+
         let key_images: Vec<KeyImage> = tx_proposal
             .input_txos
             .iter()
@@ -1094,6 +1507,119 @@ mod tests {
             updated_change_details.subaddress_index,
             Some(CHANGE_SUBADDRESS_INDEX as i64)
         );
+
+        // The received change is not counted in the account's lifetime received
+        // total, and neither the change nor the fee is counted as sent.
+        let account = Account::get(&AccountID::from(&account_key), conn).unwrap();
+        assert_eq!(
+            account.lifetime_received(conn).unwrap(),
+            BTreeMap::from([(Mob::ID, 70 * MOB as u128)])
+        );
+        assert_eq!(
+            account.lifetime_sent(conn).unwrap(),
+            BTreeMap::from([(Mob::ID, 50 * MOB as u128)])
+        );
+
+        // The account was last active when the change was received and the
+        // transaction finalized, in the last block.
+        assert_eq!(account.last_activity_block_index(conn).unwrap(), Some(13));
+    }
+
+    #[async_test_with_logger]
+    async fn test_log_submitted_raw(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        // Start sync thread
+        let _sync_thread = SyncThread::start(
+            ledger_db.clone(),
+            wallet_db.clone(),
+            Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
+            logger.clone(),
+        );
+
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[70 * MOB],
+            &mut rng,
+            &logger,
+        );
+
+        // Build a transaction
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let account = Account::get(&AccountID::from(&account_key), conn).unwrap();
+
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder
+            .add_recipient(recipient.clone(), 50 * MOB, Mob::ID)
+            .unwrap();
+        builder.set_tombstone(0).unwrap();
+        builder.select_txos(conn, None).unwrap();
+        let unsigned_tx_proposal = builder
+            .build(
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                conn,
+            )
+            .unwrap();
+        let tx_proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
+
+        // Log only the Tx, as if it had been built elsewhere.
+        let tx_log = TransactionLog::log_submitted_raw(
+            &tx_proposal.tx,
+            ledger_db.num_blocks().unwrap(),
+            "".to_string(),
+            &AccountID::from(&account_key).to_string(),
+            conn,
+        )
+        .unwrap();
+
+        assert_eq!(tx_log.status(), TxStatus::Pending);
+        assert_eq!(
+            tx_log.tombstone_block_index,
+            Some(tx_proposal.tx.prefix.tombstone_block as i64)
+        );
+
+        // The input is found by its key image, and is now pending.
+        let associated_txos = tx_log.get_associated_txos(conn).unwrap();
+        assert_eq!(associated_txos.inputs.len(), 1);
+        assert_eq!(
+            associated_txos.inputs[0].id,
+            TxoID::from(&tx_proposal.input_txos[0].tx_out).to_string()
+        );
+        assert_eq!(
+            associated_txos.inputs[0].status(conn).unwrap(),
+            TxoStatus::Pending
+        );
+
+        // The payload cannot be decoded, but the change can.
+        assert!(associated_txos.outputs.is_empty());
+        assert_eq!(associated_txos.change.len(), 1);
+        assert_eq!(
+            associated_txos.change[0].0.id,
+            TxoID::from(&tx_proposal.change_txos[0].tx_out).to_string()
+        );
+        assert_eq!(
+            associated_txos.change[0].0.value as u64,
+            20 * MOB - Mob::MINIMUM_FEE
+        );
+        assert_eq!(
+            associated_txos.change[0].0.confirmation,
+            Some(mc_util_serial::encode(
+                &tx_proposal.change_txos[0].confirmation_number
+            ))
+        );
     }
 
     #[async_test_with_logger]
@@ -1110,6 +1636,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1145,7 +1673,7 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let tx_proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let tx_proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
 
         let tx_log = TransactionLog::log_submitted(
             &tx_proposal,
@@ -1203,6 +1731,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1235,7 +1765,7 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let tx_proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let tx_proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
 
         // Log submitted transaction from tx_proposal
         TransactionLog::log_submitted(
@@ -1317,6 +1847,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1349,7 +1881,7 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let tx_proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let tx_proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
 
         assert_eq!(
             tx_proposal.payload_txos[0].amount.value,
@@ -1397,6 +1929,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1432,7 +1966,7 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let tx_proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let tx_proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
 
         // Log submitted transaction from tx_proposal
         let tx_log = TransactionLog::log_submitted(
@@ -1634,6 +2168,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1684,11 +2220,16 @@ mod tests {
             comment: "".to_string(),
             tx: vec![],
             failed: false,
+            conflicting: false,
         };
 
         assert_eq!(tx_log, expected_tx_log);
 
-        let tx_proposal = unsigned_tx_proposal.clone().sign(&account).await.unwrap();
+        let tx_proposal = unsigned_tx_proposal
+            .clone()
+            .sign(&account, None)
+            .await
+            .unwrap();
         let tx_bytes = mc_util_serial::encode(&tx_proposal.tx);
 
         assert_eq!(
@@ -1717,6 +2258,7 @@ mod tests {
             comment: "".to_string(),
             tx: tx_bytes.clone(),
             failed: false,
+            conflicting: false,
         };
 
         assert_eq!(tx_log, expected_tx_log);
@@ -1744,6 +2286,7 @@ mod tests {
             comment: "".to_string(),
             tx: tx_bytes,
             failed: false,
+            conflicting: false,
         };
         assert_eq!(tx_log, expected_tx_log);
         assert_eq!(tx_log.value_for_token_id(Mob::ID, conn).unwrap(), 50 * MOB);
@@ -1876,6 +2419,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1924,11 +2469,16 @@ mod tests {
             comment: "".to_string(),
             tx: vec![],
             failed: false,
+            conflicting: false,
         };
 
         assert_eq!(tx_log, expected_tx_log);
 
-        let tx_proposal = unsigned_tx_proposal.clone().sign(&account).await.unwrap();
+        let tx_proposal = unsigned_tx_proposal
+            .clone()
+            .sign(&account, None)
+            .await
+            .unwrap();
         let tx_bytes = mc_util_serial::encode(&tx_proposal.tx);
 
         assert_eq!(
@@ -1957,6 +2507,7 @@ mod tests {
             comment: "first change".to_string(),
             tx: tx_bytes.clone(),
             failed: false,
+            conflicting: false,
         };
 
         assert_eq!(tx_log, expected_tx_log);
@@ -1984,6 +2535,7 @@ mod tests {
             comment: "second change".to_string(),
             tx: tx_bytes,
             failed: false,
+            conflicting: false,
         };
 
         assert_eq!(tx_log.tx, expected_tx_log.tx);
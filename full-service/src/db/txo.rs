@@ -6,6 +6,7 @@ use crate::{
     db::{
         account::{AccountID, AccountModel},
         assigned_subaddress::AssignedSubaddressModel,
+        authenticated_sender_memo::{SenderMemoFilter, SenderMemoType},
         models::{
             Account, AssignedSubaddress, AuthenticatedSenderMemo as AuthenticatedSenderMemoModel,
             DestinationMemo as DestinationMemoModel, NewAuthenticatedSenderMemo,
@@ -21,7 +22,7 @@ use diesel::{
     dsl::{count, exists, not},
     prelude::*,
 };
-use mc_account_keys::{AccountKey, PublicAddress};
+use mc_account_keys::{AccountKey, PublicAddress, CHANGE_SUBADDRESS_INDEX};
 use mc_common::{logger::global_log, HashMap};
 use mc_crypto_digestible::{Digestible, MerlinTranscript};
 use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
@@ -41,6 +42,11 @@ use mc_transaction_extra::{
 use mc_util_serial::Message;
 use std::{convert::TryFrom, fmt, str::FromStr};
 
+/// The maximum number of Txos updated by a single statement in
+/// [TxoModel::batch_update_key_images]. Each Txo uses five bind parameters,
+/// which keeps every statement under SQLite's default limit of 999.
+pub const KEY_IMAGE_UPDATE_BATCH_SIZE: usize = 150;
+
 #[derive(Debug, PartialEq)]
 pub enum TxoStatus {
     // The txo has been created as part of build-transaction, but its associated transaction is
@@ -110,6 +116,47 @@ impl FromStr for TxoStatus {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum TxoEventType {
+    // The txo was created as an output of a transaction built by the wallet
+    Created,
+
+    // The txo was received in a block
+    Received,
+
+    // The txo was used as an input of a transaction built by the wallet
+    UsedAsInput,
+
+    // The txo was spent in a block
+    Spent,
+}
+
+impl fmt::Display for TxoEventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxoEventType::Created => write!(f, "created"),
+            TxoEventType::Received => write!(f, "received"),
+            TxoEventType::UsedAsInput => write!(f, "used_as_input"),
+            TxoEventType::Spent => write!(f, "spent"),
+        }
+    }
+}
+
+/// An event in the lifecycle of a Txo.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TxoEvent {
+    pub event_type: TxoEventType,
+
+    /// The block index at which the event happened. For events from a
+    /// transaction, this is the block index at which the transaction was
+    /// submitted, or None if it has not been.
+    pub block_index: Option<u64>,
+
+    /// The transaction log which referenced the txo, for events from a
+    /// transaction.
+    pub transaction_log_id: Option<String>,
+}
+
 /// A unique ID derived from a TxOut in the ledger.
 #[derive(Debug)]
 pub struct TxoID(pub String);
@@ -169,10 +216,12 @@ pub trait TxoModel {
     ///| `amount`               | The value in this TxOut                                                         | Unit in picoMob |
     ///| `received_block_index` | The index of the block at which this TxOut was received.                        |                 |
     ///| `account_id_hex`       | The account ID for the account which received this TxOut                        |                 |
+    ///| `sender_memo_filter`   | The authenticated sender memo types to store                                    |                 |
     ///| `conn`                 | An reference to the pool connection of wallet database                          |                 |
     ///
     /// # Returns
     /// * txo_id_hex
+    #[allow(clippy::too_many_arguments)]
     fn create_received(
         tx_out: TxOut,
         subaddress_index: Option<u64>,
@@ -180,6 +229,7 @@ pub trait TxoModel {
         amount: Amount,
         received_block_index: u64,
         account_id_hex: &str,
+        sender_memo_filter: &SenderMemoFilter,
         conn: Conn,
     ) -> Result<String, WalletDbError>;
 
@@ -258,6 +308,20 @@ pub trait TxoModel {
         conn: Conn,
     ) -> Result<(), WalletDbError>;
 
+    /// Replace the memo stored for a received Txo with one decoded from its memo payload. All
+    /// memo types are stored, including those skipped while syncing.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name           | Purpose                                                | Notes |
+    ///|----------------|--------------------------------------------------------|-------|
+    ///| `memo_payload` | The decrypted memo payload of the TxOut                |       |
+    ///| `conn`         | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns
+    /// * unit
+    fn update_memo(&self, memo_payload: &MemoPayload, conn: Conn) -> Result<(), WalletDbError>;
+
     /// Update a Txo's key image and optionally update its status to spent
     /// 
     /// # Arguments
@@ -278,6 +342,25 @@ pub trait TxoModel {
         conn: Conn,
     ) -> Result<(), WalletDbError>;
 
+    /// Update the key images, and optionally the spent block indices, of many
+    /// Txos at once. Updates are applied with one statement per chunk of
+    /// [KEY_IMAGE_UPDATE_BATCH_SIZE] Txos, so callers should wrap this in a
+    /// transaction to apply the whole batch atomically.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name      | Purpose                                                      | Notes                                                      |
+    ///|-----------|--------------------------------------------------------------|------------------------------------------------------------|
+    ///| `updates` | Tuples of (txo_id_hex, key_image, spent_block_index)         | A `None` spent block index leaves the existing value as is |
+    ///| `conn`    | An reference to the pool connection of wallet database       |                                                            |
+    ///
+    /// # Returns
+    /// * unit
+    fn batch_update_key_images(
+        updates: &[(String, KeyImage, Option<u64>)],
+        conn: Conn,
+    ) -> Result<(), WalletDbError>;
+
 
     fn update_is_synced_to_t3(&self, is_synced: bool, conn: Conn) -> Result<(), WalletDbError>;
 
@@ -341,6 +424,48 @@ pub trait TxoModel {
         conn: Conn,
     ) -> Result<Vec<Txo>, WalletDbError>;
 
+    /// Get all Txos received at the change subaddress of an account
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name             | Purpose                                                   | Notes                               |
+    ///|------------------|-----------------------------------------------------------|-------------------------------------|
+    ///| `account_id_hex` | The account id where the list of Txos from                | Account must exist in the database. |
+    ///| `token_id`       | The id of a supported type of token to filter on          |                                     |
+    ///| `offset`         | The pagination offset. Results start at the offset index. | Optional. Defaults to 0.            |
+    ///| `limit`          | Limit for the number of results.                          | Optional.                           |
+    ///| `conn`           | An reference to the pool connection of wallet database    |                                     |
+    ///
+    /// # Returns
+    /// * Vector of TxoOut
+    fn list_change_for_account(
+        account_id_hex: &str,
+        token_id: Option<u64>,
+        offset: Option<u64>,
+        limit: Option<u64>,
+        conn: Conn,
+    ) -> Result<Vec<Txo>, WalletDbError>;
+
+    /// Get all Txos received by an account without a sender memo while the account required one
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name             | Purpose                                                   | Notes                               |
+    ///|------------------|-----------------------------------------------------------|-------------------------------------|
+    ///| `account_id_hex` | The account id where the list of Txos from                | Account must exist in the database. |
+    ///| `offset`         | The pagination offset. Results start at the offset index. | Optional. Defaults to 0.            |
+    ///| `limit`          | Limit for the number of results.                          | Optional.                           |
+    ///| `conn`           | An reference to the pool connection of wallet database    |                                     |
+    ///
+    /// # Returns
+    /// * Vector of TxoOut
+    fn list_missing_required_memo_for_account(
+        account_id_hex: &str,
+        offset: Option<u64>,
+        limit: Option<u64>,
+        conn: Conn,
+    ) -> Result<Vec<Txo>, WalletDbError>;
+
     /// Get all Txos associated with an assigned subaddress
     /// 
     /// # Arguments
@@ -388,6 +513,27 @@ pub trait TxoModel {
         conn: Conn,
     ) -> Result<HashMap<KeyImage, String>, WalletDbError>;
 
+    /// Get the key images and spent block indices of all spent Txos for this account, ordered by
+    /// spent block index.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name             | Purpose                                                   | Notes                               |
+    ///|------------------|-----------------------------------------------------------|-------------------------------------|
+    ///| `account_id_hex` | The account id where the key images and Txos from         | Account must exist in the database. |
+    ///| `offset`         | The pagination offset. Results start at the offset index. | Optional. Defaults to 0.            |
+    ///| `limit`          | Limit for the number of results.                          | Optional.                           |
+    ///| `conn`           | An reference to the pool connection of wallet database    |                                     |
+    ///
+    /// # Returns
+    /// * Vector of (TxOut id string, KeyImage, spent block index)
+    fn list_spent_key_images(
+        account_id_hex: &str,
+        offset: Option<u64>,
+        limit: Option<u64>,
+        conn: Conn,
+    ) -> Result<Vec<(String, KeyImage, u64)>, WalletDbError>;
+
     /// Get all unspent Txos associated  with an account or an assigned subaddress
     /// 
     /// # Arguments
@@ -541,6 +687,7 @@ pub trait TxoModel {
     ///| `max_spendable_value`     | The upper limit for the spendable TxOut value to filter on |                                     |
     ///| `assigned_subaddress_b58` | The subaddress at which the list of Txos from              |                                     |
     ///| `token_id`                | The id of a supported type of token to filter on           |                                     |
+    ///| `max_received_block_index`| The maximum block index to query for received txos, inclusive | Optional. Used to enforce min confirmations. |
     ///| `conn`                    | An reference to the pool connection of wallet database     |                                     |
     ///
     /// 
@@ -553,6 +700,7 @@ pub trait TxoModel {
         assigned_subaddress_b58: Option<&str>,
         token_id: u64,
         default_token_fee: u64,
+        max_received_block_index: Option<u64>,
         conn: Conn,
     ) -> Result<SpendableTxosResult, WalletDbError>;
 
@@ -637,6 +785,7 @@ pub trait TxoModel {
     ///| `assigned_subaddress_b58`  | The subaddress where the spendable Txos can be sourced from |                                      |
     ///| `token_id`            | The id of a supported type of token to filter on           |                                     |
     ///| `default_token_fee`   | The default transaction fee in Mob network                 |                                     |
+    ///| `max_received_block_index` | The maximum block index to select received txos from, inclusive | Optional. Used to enforce min confirmations. |
     ///| `conn`                | An reference to the pool connection of wallet database     |                                     |
     ///
     /// # Returns:
@@ -648,6 +797,35 @@ pub trait TxoModel {
         assigned_subaddress_b58: Option<&str>,
         token_id: u64,
         default_token_fee: u64,
+        excluded_txo_ids: &[String],
+        max_received_block_index: Option<u64>,
+        conn: Conn,
+    ) -> Result<Vec<Txo>, WalletDbError>;
+
+    /// Select the most valuable unspent Txos that can be spent together in one transaction, to
+    /// send as much as possible.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                  | Purpose                                                    | Notes                               |
+    ///|-----------------------|------------------------------------------------------------|-------------------------------------|
+    ///| `account_id_hex`      | The account id where the Txos from                         | Account must exist in the database. |
+    ///| `max_spendable_value` | The upper limit for the spendable TxOut value to filter on |                                     |
+    ///| `assigned_subaddress_b58`  | The subaddress where the spendable Txos can be sourced from |                                      |
+    ///| `token_id`            | The id of a supported type of token to filter on           |                                     |
+    ///| `excluded_txo_ids`    | Txos which must not be selected                            | Such as those reserved by other proposals. |
+    ///| `max_received_block_index` | The maximum block index to select received txos from, inclusive | Optional. Used to enforce min confirmations. |
+    ///| `conn`                | An reference to the pool connection of wallet database     |                                     |
+    ///
+    /// # Returns:
+    /// * Vector of at most MAX_INPUTS TxoOut
+    fn select_max_spendable_txos(
+        account_id_hex: &str,
+        max_spendable_value: Option<u64>,
+        assigned_subaddress_b58: Option<&str>,
+        token_id: u64,
+        excluded_txo_ids: &[String],
+        max_received_block_index: Option<u64>,
         conn: Conn,
     ) -> Result<Vec<Txo>, WalletDbError>;
 
@@ -696,6 +874,20 @@ pub trait TxoModel {
     /// * unit
     fn delete_unreferenced(conn: Conn) -> Result<(), WalletDbError>;
 
+    /// Store the shared secrets of received Txos which were synced before shared secrets were
+    /// stored, computing them with the view key of the account which received them. Txos whose
+    /// shared secrets can not be computed are logged and skipped.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name   | Purpose                                                | Notes |
+    ///|--------|--------------------------------------------------------|-------|
+    ///| `conn` | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns
+    /// * The number of Txos whose shared secrets were stored
+    fn backfill_shared_secrets(conn: Conn) -> Result<usize, WalletDbError>;
+
     /// Get status for current TxOut
     /// 
     /// # Arguments
@@ -711,6 +903,20 @@ pub trait TxoModel {
     /// Get memo for current TxOut
     fn memo(&self, conn: Conn) -> Result<TxoMemo, WalletDbError>;
 
+    /// Get the lifecycle events of the current TxOut: when it was created by or used as an input
+    /// of the wallet's transactions, and when it was received and spent. Events are ordered by
+    /// block index, with events from transactions which have not been submitted last.
+    /// 
+    /// # Arguments
+    /// 
+    ///| Name   | Purpose                                                | Notes |
+    ///|--------|--------------------------------------------------------|-------|
+    ///| `conn` | An reference to the pool connection of wallet database |       |
+    ///
+    /// # Returns
+    /// * Vector of TxoEvent
+    fn history(&self, conn: Conn) -> Result<Vec<TxoEvent>, WalletDbError>;
+
     /// Get the membership proof from ledger DB for current TxOut
     /// 
     /// # Arguments
@@ -759,6 +965,7 @@ impl TxoModel for Txo {
         amount: Amount,
         received_block_index: u64,
         account_id_hex: &str,
+        sender_memo_filter: &SenderMemoFilter,
         conn: Conn,
     ) -> Result<String, WalletDbError> {
         // Verify that the account exists.
@@ -825,7 +1032,15 @@ impl TxoModel for Txo {
             }
         };
 
-        add_memo_to_database(&txo_id.to_string(), &memo_payload, conn)?;
+        add_memo_to_database(&txo_id.to_string(), &memo_payload, sender_memo_filter, conn)?;
+
+        // The flag is based on the decrypted memo, since memos of types which are
+        // not stored still carry a memo.
+        if account.require_sender_memo && is_missing_required_memo(&memo_payload) {
+            diesel::update(crate::db::schema::txos::table.find(txo_id.to_string()))
+                .set(crate::db::schema::txos::missing_required_memo.eq(true))
+                .execute(conn)?;
+        }
 
         Ok(txo_id.to_string())
     }
@@ -896,7 +1111,12 @@ impl TxoModel for Txo {
             .execute(conn)?;
 
         if let Some(memo_payload) = memo_payload {
-            add_memo_to_database(&txo_id.to_string(), &memo_payload, conn)?;
+            add_memo_to_database(
+                &txo_id.to_string(),
+                &memo_payload,
+                &SenderMemoFilter::default(),
+                conn,
+            )?;
         }
 
         Ok(())
@@ -951,6 +1171,34 @@ impl TxoModel for Txo {
         Ok(())
     }
 
+    fn update_memo(&self, memo_payload: &MemoPayload, conn: Conn) -> Result<(), WalletDbError> {
+        use crate::db::schema::{authenticated_sender_memos, destination_memos, txos};
+
+        diesel::delete(
+            authenticated_sender_memos::table
+                .filter(authenticated_sender_memos::txo_id.eq(&self.id)),
+        )
+        .execute(conn)?;
+        diesel::delete(destination_memos::table.filter(destination_memos::txo_id.eq(&self.id)))
+            .execute(conn)?;
+
+        let memo_type = two_bytes_to_i32(*memo_payload.get_memo_type());
+        diesel::update(self)
+            .set(txos::memo_type.eq(Some(memo_type)))
+            .execute(conn)?;
+        add_memo_to_database(&self.id, memo_payload, &SenderMemoFilter::default(), conn)?;
+
+        if let Some(account) = self.account(conn)? {
+            if account.require_sender_memo {
+                diesel::update(self)
+                    .set(txos::missing_required_memo.eq(is_missing_required_memo(memo_payload)))
+                    .execute(conn)?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn update_key_image(
         txo_id_hex: &str,
         key_image: &KeyImage,
@@ -977,6 +1225,42 @@ impl TxoModel for Txo {
         Ok(())
     }
 
+    fn batch_update_key_images(
+        updates: &[(String, KeyImage, Option<u64>)],
+        conn: Conn,
+    ) -> Result<(), WalletDbError> {
+        use diesel::sql_types::{BigInt, Binary, Nullable, Text};
+
+        for chunk in updates.chunks(KEY_IMAGE_UPDATE_BATCH_SIZE) {
+            let cases = "WHEN ? THEN ? ".repeat(chunk.len());
+            let spent_cases = "WHEN ? THEN COALESCE(?, spent_block_index) ".repeat(chunk.len());
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let sql = format!(
+                "UPDATE txos SET key_image = CASE id {cases}END, \
+                 spent_block_index = CASE id {spent_cases}ELSE spent_block_index END \
+                 WHERE id IN ({placeholders})"
+            );
+
+            let mut query = diesel::sql_query(sql).into_boxed();
+            for (txo_id, key_image, _) in chunk {
+                query = query
+                    .bind::<Text, _>(txo_id.as_str())
+                    .bind::<Binary, _>(mc_util_serial::encode(key_image));
+            }
+            for (txo_id, _, spent_block_index) in chunk {
+                query = query
+                    .bind::<Text, _>(txo_id.as_str())
+                    .bind::<Nullable<BigInt>, _>(spent_block_index.map(|i| i as i64));
+            }
+            for (txo_id, _, _) in chunk {
+                query = query.bind::<Text, _>(txo_id.as_str());
+            }
+            query.execute(conn)?;
+        }
+
+        Ok(())
+    }
+
     fn update_is_synced_to_t3(&self, is_synced: bool, conn: Conn) -> Result<(), WalletDbError> {
         use crate::db::schema::txos;
 
@@ -1223,6 +1507,51 @@ impl TxoModel for Txo {
         Ok(query.order(txos::received_block_index.desc()).load(conn)?)
     }
 
+    fn list_change_for_account(
+        account_id_hex: &str,
+        token_id: Option<u64>,
+        offset: Option<u64>,
+        limit: Option<u64>,
+        conn: Conn,
+    ) -> Result<Vec<Txo>, WalletDbError> {
+        use crate::db::schema::txos;
+
+        let mut query = txos::table
+            .filter(txos::account_id.eq(account_id_hex))
+            .filter(txos::subaddress_index.eq(CHANGE_SUBADDRESS_INDEX as i64))
+            .into_boxed();
+
+        if let (Some(o), Some(l)) = (offset, limit) {
+            query = query.offset(o as i64).limit(l as i64);
+        }
+
+        if let Some(token_id) = token_id {
+            query = query.filter(txos::token_id.eq(token_id as i64));
+        }
+
+        Ok(query.order(txos::received_block_index.desc()).load(conn)?)
+    }
+
+    fn list_missing_required_memo_for_account(
+        account_id_hex: &str,
+        offset: Option<u64>,
+        limit: Option<u64>,
+        conn: Conn,
+    ) -> Result<Vec<Txo>, WalletDbError> {
+        use crate::db::schema::txos;
+
+        let mut query = txos::table
+            .filter(txos::account_id.eq(account_id_hex))
+            .filter(txos::missing_required_memo.eq(true))
+            .into_boxed();
+
+        if let (Some(o), Some(l)) = (offset, limit) {
+            query = query.offset(o as i64).limit(l as i64);
+        }
+
+        Ok(query.order(txos::received_block_index.desc()).load(conn)?)
+    }
+
     fn list_for_address(
         assigned_subaddress_b58: &str,
         status: Option<TxoStatus>,
@@ -1595,6 +1924,44 @@ impl TxoModel for Txo {
             .collect())
     }
 
+    fn list_spent_key_images(
+        account_id_hex: &str,
+        offset: Option<u64>,
+        limit: Option<u64>,
+        conn: Conn,
+    ) -> Result<Vec<(String, KeyImage, u64)>, WalletDbError> {
+        use crate::db::schema::txos;
+
+        let mut query = txos::table
+            .filter(txos::account_id.eq(account_id_hex))
+            .filter(txos::key_image.is_not_null())
+            .filter(txos::spent_block_index.is_not_null())
+            .into_boxed();
+
+        // Either may be given alone. SQLite only accepts an offset along with a
+        // limit, where a negative limit is unbounded.
+        if offset.is_some() || limit.is_some() {
+            query = query
+                .offset(offset.unwrap_or(0) as i64)
+                .limit(limit.map_or(-1, |l| l as i64));
+        }
+
+        let results: Vec<(String, Option<Vec<u8>>, Option<i64>)> = query
+            .select((txos::id, txos::key_image, txos::spent_block_index))
+            .order((txos::spent_block_index.asc(), txos::id.asc()))
+            .load(conn)?;
+
+        let mut spent_key_images = Vec::with_capacity(results.len());
+        for (txo_id_hex, key_image, spent_block_index) in results {
+            if let (Some(key_image), Some(spent_block_index)) = (key_image, spent_block_index) {
+                let key_image: KeyImage = mc_util_serial::decode(&key_image)?;
+                spent_key_images.push((txo_id_hex, key_image, spent_block_index as u64));
+            }
+        }
+
+        Ok(spent_key_images)
+    }
+
     fn list_spent(
         account_id_hex: Option<&str>,
         assigned_subaddress_b58: Option<&str>,
@@ -1793,6 +2160,7 @@ impl TxoModel for Txo {
         assigned_subaddress_b58: Option<&str>,
         token_id: u64,
         default_token_fee: u64,
+        max_received_block_index: Option<u64>,
         conn: Conn,
     ) -> Result<SpendableTxosResult, WalletDbError> {
         use crate::db::schema::{transaction_input_txos, transaction_logs, txos};
@@ -1840,6 +2208,10 @@ impl TxoModel for Txo {
             query = query.filter(txos::account_id.eq(account_id_hex));
         }
 
+        if let Some(max_received_block_index) = max_received_block_index {
+            query = query.filter(txos::received_block_index.le(max_received_block_index as i64));
+        }
+
         let mut spendable_txos = query
             .select(txos::all_columns)
             .distinct()
@@ -1881,6 +2253,8 @@ impl TxoModel for Txo {
         assigned_subaddress_b58: Option<&str>,
         token_id: u64,
         default_token_fee: u64,
+        excluded_txo_ids: &[String],
+        max_received_block_index: Option<u64>,
         conn: Conn,
     ) -> Result<Vec<Txo>, WalletDbError> {
         let SpendableTxosResult {
@@ -1892,9 +2266,14 @@ impl TxoModel for Txo {
             assigned_subaddress_b58,
             token_id,
             default_token_fee,
+            max_received_block_index,
             conn,
         )?;
 
+        // Skip txos reserved as inputs by proposals which have been built but not
+        // yet submitted.
+        spendable_txos.retain(|txo| !excluded_txo_ids.contains(&txo.id));
+
         if spendable_txos.is_empty() {
             return Err(WalletDbError::NoSpendableTxos(token_id.to_string()));
         }
@@ -1963,6 +2342,39 @@ impl TxoModel for Txo {
         Ok(selected_utxos)
     }
 
+    fn select_max_spendable_txos(
+        account_id_hex: &str,
+        max_spendable_value: Option<u64>,
+        assigned_subaddress_b58: Option<&str>,
+        token_id: u64,
+        excluded_txo_ids: &[String],
+        max_received_block_index: Option<u64>,
+        conn: Conn,
+    ) -> Result<Vec<Txo>, WalletDbError> {
+        let SpendableTxosResult {
+            mut spendable_txos, ..
+        } = Txo::list_spendable(
+            Some(account_id_hex),
+            max_spendable_value,
+            assigned_subaddress_b58,
+            token_id,
+            0,
+            max_received_block_index,
+            conn,
+        )?;
+
+        spendable_txos.retain(|txo| !excluded_txo_ids.contains(&txo.id));
+
+        if spendable_txos.is_empty() {
+            return Err(WalletDbError::NoSpendableTxos(token_id.to_string()));
+        }
+
+        // The txos are sorted by decreasing value, so the first MAX_INPUTS are the
+        // most that can be spent in one transaction.
+        spendable_txos.truncate(MAX_INPUTS as usize);
+        Ok(spendable_txos)
+    }
+
     fn validate_confirmation(
         account_id: &AccountID,
         txo_id_hex: &str,
@@ -2025,6 +2437,43 @@ impl TxoModel for Txo {
         Ok(())
     }
 
+    fn backfill_shared_secrets(conn: Conn) -> Result<usize, WalletDbError> {
+        use crate::db::schema::txos;
+
+        let txos_missing_shared_secret = txos::table
+            .filter(txos::account_id.is_not_null())
+            .filter(txos::shared_secret.is_null())
+            .load::<Txo>(conn)?;
+
+        let mut num_stored = 0;
+        for txo in &txos_missing_shared_secret {
+            // A Txo whose shared secret can not be computed is skipped, so that it
+            // does not keep the others from being backfilled.
+            let stored = (|| -> Result<bool, WalletDbError> {
+                let Some(account) = txo.account(conn)? else {
+                    return Ok(false);
+                };
+                let shared_secret =
+                    account.get_shared_secret(&RistrettoPublic::try_from(&txo.public_key()?)?)?;
+                diesel::update(txo)
+                    .set(txos::shared_secret.eq(Some(shared_secret.encode_to_vec())))
+                    .execute(conn)?;
+                Ok(true)
+            })();
+            match stored {
+                Ok(true) => num_stored += 1,
+                Ok(false) => {}
+                Err(err) => global_log::warn!(
+                    "Skipping backfilling the shared secret of txo {}: {}",
+                    txo.id,
+                    err
+                ),
+            }
+        }
+
+        Ok(num_stored)
+    }
+
     fn status(&self, conn: Conn) -> Result<TxoStatus, WalletDbError> {
         use crate::db::schema::{
             transaction_input_txos, transaction_logs, transaction_output_txos, txos,
@@ -2097,44 +2546,111 @@ impl TxoModel for Txo {
         }
     }
 
-    fn memo(&self, conn: Conn) -> Result<TxoMemo, WalletDbError> {
-        use crate::db::schema::{authenticated_sender_memos, destination_memos};
-        Ok(
-            match self.memo_type {
-                None => TxoMemo::Unused,
-                Some(mtype) => {
-                    match i32_to_two_bytes(mtype) {
-                        <AuthenticatedSenderMemo as RegisteredMemoType>::MEMO_TYPE_BYTES |
-                        <AuthenticatedSenderWithPaymentIntentIdMemo as RegisteredMemoType>::MEMO_TYPE_BYTES |
-                        <AuthenticatedSenderWithPaymentRequestIdMemo as RegisteredMemoType>::MEMO_TYPE_BYTES
-                            => {
-                                let db_memo = authenticated_sender_memos::table.filter(
-                                    authenticated_sender_memos::txo_id.eq(&self.id),
-                                    ).first::<AuthenticatedSenderMemoModel>(conn)?;
-                                TxoMemo::AuthenticatedSender(db_memo)
-                            },
-                        <DestinationMemo as RegisteredMemoType>::MEMO_TYPE_BYTES |
-                        <DestinationWithPaymentIntentIdMemo as RegisteredMemoType>::MEMO_TYPE_BYTES |
-                        <DestinationWithPaymentRequestIdMemo as RegisteredMemoType>::MEMO_TYPE_BYTES
-                            => {
-                                let db_memo = destination_memos::table.filter(
-                                    destination_memos::txo_id.eq(&self.id),
-                                    ).first::<DestinationMemoModel>(conn)?;
-                                TxoMemo::Destination(db_memo)
-                            },
-                        _ => TxoMemo::Unused,
-                    }
-                }
-            }
-        )
-    }
+    fn history(&self, conn: Conn) -> Result<Vec<TxoEvent>, WalletDbError> {
+        use crate::db::schema::{
+            transaction_input_txos, transaction_logs, transaction_output_txos,
+        };
 
-    fn membership_proof(
-        &self,
-        ledger_db: &LedgerDB,
-    ) -> Result<TxOutMembershipProof, WalletDbError> {
-        let index = ledger_db.get_tx_out_index_by_public_key(&self.public_key()?)?;
-        let membership_proof = ledger_db
+        let created_by: Vec<(String, Option<i64>)> = transaction_logs::table
+            .inner_join(transaction_output_txos::table)
+            .filter(transaction_output_txos::txo_id.eq(&self.id))
+            .select((
+                transaction_logs::id,
+                transaction_logs::submitted_block_index,
+            ))
+            .load(conn)?;
+        let used_by: Vec<(String, Option<i64>)> = transaction_logs::table
+            .inner_join(transaction_input_txos::table)
+            .filter(transaction_input_txos::txo_id.eq(&self.id))
+            .select((
+                transaction_logs::id,
+                transaction_logs::submitted_block_index,
+            ))
+            .load(conn)?;
+
+        let transaction_events = created_by
+            .into_iter()
+            .map(|log| (TxoEventType::Created, log))
+            .chain(
+                used_by
+                    .into_iter()
+                    .map(|log| (TxoEventType::UsedAsInput, log)),
+            )
+            .map(
+                |(event_type, (transaction_log_id, submitted_block_index))| TxoEvent {
+                    event_type,
+                    block_index: submitted_block_index.map(|i| i as u64),
+                    transaction_log_id: Some(transaction_log_id),
+                },
+            );
+        let block_events = [
+            (TxoEventType::Received, self.received_block_index),
+            (TxoEventType::Spent, self.spent_block_index),
+        ]
+        .into_iter()
+        .filter_map(|(event_type, block_index)| {
+            Some(TxoEvent {
+                event_type,
+                block_index: Some(block_index? as u64),
+                transaction_log_id: None,
+            })
+        });
+
+        let mut events: Vec<TxoEvent> = transaction_events.chain(block_events).collect();
+        events.sort_by_key(|event| {
+            (
+                event.block_index.is_none(),
+                event.block_index,
+                event.event_type,
+            )
+        });
+        Ok(events)
+    }
+
+    fn memo(&self, conn: Conn) -> Result<TxoMemo, WalletDbError> {
+        use crate::db::schema::{authenticated_sender_memos, destination_memos};
+
+        // Without a shared secret the memo could not have been decrypted.
+        if self.shared_secret.is_none() {
+            return Ok(TxoMemo::Unused);
+        }
+
+        Ok(match self.memo_type {
+            None => TxoMemo::Unused,
+            Some(mtype) => {
+                match i32_to_two_bytes(mtype) {
+                    <AuthenticatedSenderMemo as RegisteredMemoType>::MEMO_TYPE_BYTES |
+                    <AuthenticatedSenderWithPaymentIntentIdMemo as RegisteredMemoType>::MEMO_TYPE_BYTES |
+                    <AuthenticatedSenderWithPaymentRequestIdMemo as RegisteredMemoType>::MEMO_TYPE_BYTES
+                        => {
+                            // The memo is not stored if its type was filtered out when the
+                            // Txo was received.
+                            let db_memo = authenticated_sender_memos::table.filter(
+                                authenticated_sender_memos::txo_id.eq(&self.id),
+                                ).first::<AuthenticatedSenderMemoModel>(conn).optional()?;
+                            db_memo.map_or(TxoMemo::Unused, TxoMemo::AuthenticatedSender)
+                        },
+                    <DestinationMemo as RegisteredMemoType>::MEMO_TYPE_BYTES |
+                    <DestinationWithPaymentIntentIdMemo as RegisteredMemoType>::MEMO_TYPE_BYTES |
+                    <DestinationWithPaymentRequestIdMemo as RegisteredMemoType>::MEMO_TYPE_BYTES
+                        => {
+                            let db_memo = destination_memos::table.filter(
+                                destination_memos::txo_id.eq(&self.id),
+                                ).first::<DestinationMemoModel>(conn)?;
+                            TxoMemo::Destination(db_memo)
+                        },
+                    _ => TxoMemo::Unused,
+                }
+            }
+        })
+    }
+
+    fn membership_proof(
+        &self,
+        ledger_db: &LedgerDB,
+    ) -> Result<TxOutMembershipProof, WalletDbError> {
+        let index = ledger_db.get_tx_out_index_by_public_key(&self.public_key()?)?;
+        let membership_proof = ledger_db
             .get_tx_out_proof_of_memberships(&[index])?
             .first()
             .ok_or_else(|| WalletDbError::MissingTxoMembershipProof(self.id.clone()))?
@@ -2260,14 +2776,47 @@ fn two_bytes_to_i32(bytes: [u8; 2]) -> i32 {
     ((bytes[0] as i32) << 8) | (bytes[1] as i32)
 }
 
+/// Whether a received Txo's memo is neither a sender memo nor a destination
+/// memo, for accounts which require a sender memo. This does not depend on
+/// which sender memo types are stored.
+fn is_missing_required_memo(memo_payload: &MemoPayload) -> bool {
+    !matches!(
+        MemoType::try_from(memo_payload),
+        Ok(MemoType::AuthenticatedSender(_)
+            | MemoType::AuthenticatedSenderWithPaymentIntentId(_)
+            | MemoType::AuthenticatedSenderWithPaymentRequestId(_)
+            | MemoType::Destination(_)
+            | MemoType::DestinationWithPaymentIntentId(_)
+            | MemoType::DestinationWithPaymentRequestId(_))
+    )
+}
+
 fn add_memo_to_database(
     txo_id: &str,
     memo_payload: &MemoPayload,
+    sender_memo_filter: &SenderMemoFilter,
     conn: Conn,
 ) -> Result<(), WalletDbError> {
     // Interpret the memo payload and save it to the correct database table.
     // Check that there is no existing memo before creating a new one.
     match MemoType::try_from(memo_payload) {
+        Ok(MemoType::AuthenticatedSender(_))
+            if !sender_memo_filter.stores(SenderMemoType::AuthenticatedSender) =>
+        {
+            Ok(())
+        }
+        Ok(MemoType::AuthenticatedSenderWithPaymentIntentId(_))
+            if !sender_memo_filter
+                .stores(SenderMemoType::AuthenticatedSenderWithPaymentIntentId) =>
+        {
+            Ok(())
+        }
+        Ok(MemoType::AuthenticatedSenderWithPaymentRequestId(_))
+            if !sender_memo_filter
+                .stores(SenderMemoType::AuthenticatedSenderWithPaymentRequestId) =>
+        {
+            Ok(())
+        }
         Ok(MemoType::AuthenticatedSender(memo)) => add_authenticated_memo_to_database(
             txo_id,
             &memo.sender_address_hash().to_string(),
@@ -2593,6 +3142,7 @@ mod tests {
             shared_secret: Some(shared_secret.encode_to_vec()),
             memo_type: Some(0),
             is_synced_to_t3: false,
+            missing_required_memo: false,
         };
 
         assert_eq!(expected_txo, txos[0]);
@@ -2631,7 +3181,10 @@ mod tests {
             TxoStatus::Created,
         );
 
-        let tx_proposal = unsigned_tx_proposal.sign(&alice_account).await.unwrap();
+        let tx_proposal = unsigned_tx_proposal
+            .sign(&alice_account, None)
+            .await
+            .unwrap();
         // There should be 2 outputs, one to dest and one change
         assert_eq!(tx_proposal.tx.prefix.outputs.len(), 2);
         transaction_log = TransactionLog::log_signed(
@@ -2866,6 +3419,18 @@ mod tests {
             .collect();
         assert_eq!(change.len(), 1);
 
+        // The same change Txo is returned when listing change directly
+        let listed_change = Txo::list_change_for_account(
+            &alice_account_id.to_string(),
+            Some(0),
+            None,
+            None,
+            &mut wallet_db.get_pooled_conn().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(listed_change.len(), 1);
+        assert_eq!(listed_change[0].id, change[0].id);
+
         // Create a new account and send some MOB to it
         let bob_root_id = RootIdentity::from_random(&mut rng);
         let bob_account_key = AccountKey::from(&bob_root_id);
@@ -3026,6 +3591,7 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            None,
             conn,
         )
         .unwrap();
@@ -3111,6 +3677,7 @@ mod tests {
             Some(&alice_public_address_b58),
             0,
             Mob::MINIMUM_FEE,
+            None,
             conn,
         )
         .unwrap();
@@ -3148,6 +3715,7 @@ mod tests {
                 subaddress.clone(),
                 0,
                 Mob::MINIMUM_FEE,
+                None,
                 conn,
             )
             .unwrap();
@@ -3201,6 +3769,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -3215,6 +3785,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -3232,6 +3804,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         );
 
@@ -3250,6 +3824,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -3372,6 +3948,8 @@ mod tests {
                 Some(subaddress),
                 0,
                 Mob::MINIMUM_FEE,
+                &[],
+                None,
                 conn,
             )
             .unwrap();
@@ -3388,6 +3966,8 @@ mod tests {
             Some(&alice_public_address_b58),
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             conn,
         );
 
@@ -3441,6 +4021,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -3452,6 +4034,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         );
 
@@ -3507,6 +4091,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         );
         match res {
@@ -3666,7 +4252,10 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let proposal = unsigned_tx_proposal.sign(&sender_account).await.unwrap();
+        let proposal = unsigned_tx_proposal
+            .sign(&sender_account, None)
+            .await
+            .unwrap();
 
         // Sleep to make sure that the foreign keys exist
         std::thread::sleep(Duration::from_secs(3));
@@ -3951,6 +4540,7 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            None,
             conn,
         )
         .unwrap();
@@ -3962,6 +4552,75 @@ mod tests {
         );
     }
 
+    #[test_with_logger]
+    fn test_list_spendable_max_received_block_index(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let (account_id, _address) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        for i in 1..=10 {
+            let (_txo_id, _txo, _key_image) = create_test_received_txo(
+                &account_key,
+                i,
+                Amount::new(100 * MOB, Mob::ID),
+                i,
+                &mut rng,
+                &wallet_db,
+            );
+        }
+
+        // Txos received after the max received block index are not spendable
+        let SpendableTxosResult { spendable_txos, .. } = Txo::list_spendable(
+            Some(&account_id.to_string()),
+            None,
+            None,
+            0,
+            Mob::MINIMUM_FEE,
+            Some(7),
+            conn,
+        )
+        .unwrap();
+        assert_eq!(spendable_txos.len(), 7);
+        assert!(spendable_txos
+            .iter()
+            .all(|txo| txo.received_block_index.unwrap() <= 7));
+
+        // Nor are they selected as inputs
+        let result = Txo::select_spendable_txos_for_value(
+            &account_id.to_string(),
+            (800 * MOB) as u128,
+            None,
+            None,
+            0,
+            Mob::MINIMUM_FEE,
+            &[],
+            Some(7),
+            conn,
+        );
+        assert!(matches!(
+            result,
+            Err(WalletDbError::InsufficientFundsUnderMaxSpendable(_))
+        ));
+    }
+
     #[test_with_logger]
     fn test_list_spendable_less_than_min_fee(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -4008,6 +4667,7 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            None,
             conn,
         )
         .unwrap();
@@ -4094,6 +4754,7 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            None,
             conn,
         )
         .unwrap();
@@ -4156,6 +4817,7 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            None,
             conn,
         )
         .unwrap();
@@ -4171,6 +4833,7 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            None,
             conn,
         )
         .unwrap();
@@ -4191,6 +4854,7 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            None,
             conn,
         )
         .unwrap();
@@ -4206,6 +4870,7 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            None,
             conn,
         )
         .unwrap();
@@ -4249,6 +4914,7 @@ mod tests {
             amount,
             15,
             &account_id.to_string(),
+            &SenderMemoFilter::default(),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4274,6 +4940,7 @@ mod tests {
             amount,
             15,
             &account_id.to_string(),
+            &SenderMemoFilter::default(),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4299,6 +4966,7 @@ mod tests {
             amount,
             15,
             &account_id.to_string(),
+            &SenderMemoFilter::default(),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4419,6 +5087,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4427,6 +5097,26 @@ mod tests {
         assert_eq!(target_value, (sum - Mob::MINIMUM_FEE) as u128);
     }
 
+    #[test_with_logger]
+    fn test_select_max_spendable_txos(logger: Logger) {
+        let (account_id, wallet_db) = setup_select_unspent_txos_tests(logger, false);
+
+        // Only the 16 most valuable of the 20 txos can be spent together.
+        let result = Txo::select_max_spendable_txos(
+            &account_id.to_string(),
+            None,
+            None,
+            0,
+            &[],
+            None,
+            &mut wallet_db.get_pooled_conn().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.len(), MAX_INPUTS as usize);
+        let sum: u64 = result.iter().map(|x| x.value as u64).sum();
+        assert_eq!(sum, 200 * MOB);
+    }
+
     #[test_with_logger]
     fn test_select_unspent_txos_target_value_over_max_spendable_in_account(logger: Logger) {
         let (account_id, wallet_db) = setup_select_unspent_txos_tests(logger, false);
@@ -4438,6 +5128,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         );
 
@@ -4457,6 +5149,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4474,6 +5168,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         );
         assert!(result.is_err());
@@ -4492,6 +5188,8 @@ mod tests {
             None,
             0,
             Mob::MINIMUM_FEE,
+            &[],
+            None,
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4538,6 +5236,7 @@ mod tests {
             amount,
             15,
             &account_id.to_string(),
+            &SenderMemoFilter::default(),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4568,6 +5267,7 @@ mod tests {
             amount,
             15,
             &account_id.to_string(),
+            &SenderMemoFilter::default(),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4603,6 +5303,7 @@ mod tests {
             amount,
             15,
             &account_id.to_string(),
+            &SenderMemoFilter::default(),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4638,6 +5339,7 @@ mod tests {
             amount,
             15,
             &account_id.to_string(),
+            &SenderMemoFilter::default(),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4654,6 +5356,258 @@ mod tests {
         }
     }
 
+    #[test_with_logger]
+    fn test_list_missing_required_memo_for_account(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let (account_id, _address) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+        let amount = Amount::new(1000 * MOB, Mob::ID);
+
+        // Txos without a memo are not flagged while a memo is not required.
+        let (txo, key_image) = create_test_txo_for_recipient(&account_key, 0, amount, &mut rng);
+        Txo::create_received(
+            txo,
+            Some(0),
+            Some(key_image),
+            amount,
+            15,
+            &account_id.to_string(),
+            &SenderMemoFilter::default(),
+            conn,
+        )
+        .unwrap();
+
+        let account = Account::get(&account_id, conn).unwrap();
+        account.update_require_sender_memo(true, conn).unwrap();
+
+        let (txo, key_image) = create_test_txo_for_recipient(&account_key, 0, amount, &mut rng);
+        let missing_memo_txo_id = Txo::create_received(
+            txo,
+            Some(0),
+            Some(key_image),
+            amount,
+            16,
+            &account_id.to_string(),
+            &SenderMemoFilter::default(),
+            conn,
+        )
+        .unwrap();
+
+        let (txo, key_image) = create_test_txo_for_recipient_with_memo(
+            &account_key,
+            0,
+            amount,
+            &mut rng,
+            TransactionMemo::RTH {
+                subaddress_index: None,
+            },
+        );
+        Txo::create_received(
+            txo,
+            Some(0),
+            Some(key_image),
+            amount,
+            17,
+            &account_id.to_string(),
+            &SenderMemoFilter::default(),
+            conn,
+        )
+        .unwrap();
+
+        // A sender memo of a type which is not stored is still a sender memo.
+        let (txo, key_image) = create_test_txo_for_recipient_with_memo(
+            &account_key,
+            0,
+            amount,
+            &mut rng,
+            TransactionMemo::RTH {
+                subaddress_index: None,
+            },
+        );
+        let filtered_memo_txo_id = Txo::create_received(
+            txo,
+            Some(0),
+            Some(key_image),
+            amount,
+            18,
+            &account_id.to_string(),
+            &SenderMemoFilter::new(None, Some(vec![SenderMemoType::AuthenticatedSender])),
+            conn,
+        )
+        .unwrap();
+        assert_matches!(
+            Txo::get(&filtered_memo_txo_id, conn)
+                .unwrap()
+                .memo(conn)
+                .unwrap(),
+            TxoMemo::Unused
+        );
+
+        let txos =
+            Txo::list_missing_required_memo_for_account(&account_id.to_string(), None, None, conn)
+                .unwrap();
+        assert_eq!(txos.len(), 1);
+        assert_eq!(txos[0].id, missing_memo_txo_id);
+        assert!(txos[0].missing_required_memo);
+    }
+
+    #[test_with_logger]
+    fn test_list_spent_key_images(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let (account_id, _address) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+        let amount = Amount::new(1000 * MOB, Mob::ID);
+
+        let mut received = Vec::new();
+        for received_block_index in 15..18 {
+            let (txo, key_image) = create_test_txo_for_recipient(&account_key, 0, amount, &mut rng);
+            let txo_id = Txo::create_received(
+                txo,
+                Some(0),
+                Some(key_image),
+                amount,
+                received_block_index,
+                &account_id.to_string(),
+                &SenderMemoFilter::default(),
+                conn,
+            )
+            .unwrap();
+            received.push((txo_id, key_image));
+        }
+
+        // Spend the last txo before the first, and leave the middle one unspent.
+        Txo::update_spent_block_index(&received[2].0, 20, conn).unwrap();
+        Txo::update_spent_block_index(&received[0].0, 21, conn).unwrap();
+
+        let spent_key_images =
+            Txo::list_spent_key_images(&account_id.to_string(), None, None, conn).unwrap();
+        assert_eq!(
+            spent_key_images,
+            vec![
+                (received[2].0.clone(), received[2].1, 20),
+                (received[0].0.clone(), received[0].1, 21),
+            ]
+        );
+
+        let spent_key_images =
+            Txo::list_spent_key_images(&account_id.to_string(), Some(1), Some(1), conn).unwrap();
+        assert_eq!(
+            spent_key_images,
+            vec![(received[0].0.clone(), received[0].1, 21)]
+        );
+
+        // The offset and limit apply independently.
+        let spent_key_images =
+            Txo::list_spent_key_images(&account_id.to_string(), Some(1), None, conn).unwrap();
+        assert_eq!(
+            spent_key_images,
+            vec![(received[0].0.clone(), received[0].1, 21)]
+        );
+        let spent_key_images =
+            Txo::list_spent_key_images(&account_id.to_string(), None, Some(1), conn).unwrap();
+        assert_eq!(
+            spent_key_images,
+            vec![(received[2].0.clone(), received[2].1, 20)]
+        );
+    }
+
+    #[test_with_logger]
+    fn test_batch_update_key_images(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let (account_id, _address) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+        let amount = Amount::new(1000 * MOB, Mob::ID);
+
+        // Use more txos than fit in one statement so that chunking is exercised.
+        let mut updates = Vec::new();
+        for i in 0..(KEY_IMAGE_UPDATE_BATCH_SIZE + 2) {
+            let (txo, key_image) = create_test_txo_for_recipient(&account_key, 0, amount, &mut rng);
+            let txo_id = Txo::create_received(
+                txo,
+                Some(0),
+                None,
+                amount,
+                10,
+                &account_id.to_string(),
+                &SenderMemoFilter::default(),
+                conn,
+            )
+            .unwrap();
+            let spent_block_index = if i % 2 == 0 { Some(12) } else { None };
+            updates.push((txo_id, key_image, spent_block_index));
+        }
+
+        // A txo that was already marked spent keeps its spent block index when the
+        // update does not provide one.
+        Txo::update_spent_block_index(&updates[1].0, 11, conn).unwrap();
+
+        Txo::batch_update_key_images(&updates, conn).unwrap();
+
+        for (i, (txo_id, key_image, spent_block_index)) in updates.iter().enumerate() {
+            let txo = Txo::get(txo_id, conn).unwrap();
+            assert_eq!(txo.key_image, Some(mc_util_serial::encode(key_image)));
+            let expected_spent_block_index = match (i, spent_block_index) {
+                (1, _) => Some(11),
+                (_, index) => index.map(|index| index as i64),
+            };
+            assert_eq!(txo.spent_block_index, expected_spent_block_index);
+        }
+    }
+
     #[test_with_logger]
     fn test_get_memos_for_t3_sync_get_correct_txos(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -4693,6 +5647,7 @@ mod tests {
             amount,
             15,
             &account_id.to_string(),
+            &SenderMemoFilter::default(),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4716,6 +5671,7 @@ mod tests {
             amount,
             15,
             &account_id.to_string(),
+            &SenderMemoFilter::default(),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4740,6 +5696,7 @@ mod tests {
             amount,
             15,
             &account_id.to_string(),
+            &SenderMemoFilter::default(),
             &mut wallet_db.get_pooled_conn().unwrap(),
         )
         .unwrap();
@@ -4803,6 +5760,7 @@ mod tests {
             amount,
             15,
             &account_id_1.to_string(),
+            &SenderMemoFilter::default(),
             conn,
         )
         .unwrap();
@@ -4826,6 +5784,7 @@ mod tests {
             amount,
             15,
             &account_id_1.to_string(),
+            &SenderMemoFilter::default(),
             conn,
         )
         .unwrap();
@@ -4850,6 +5809,7 @@ mod tests {
             amount,
             15,
             &account_id_1.to_string(),
+            &SenderMemoFilter::default(),
             conn,
         )
         .unwrap();
@@ -4891,6 +5851,7 @@ mod tests {
             amount,
             15,
             &account_id_2.to_string(),
+            &SenderMemoFilter::default(),
             conn,
         )
         .unwrap();
@@ -4914,6 +5875,7 @@ mod tests {
             amount,
             15,
             &account_id_2.to_string(),
+            &SenderMemoFilter::default(),
             conn,
         )
         .unwrap();
@@ -4938,6 +5900,7 @@ mod tests {
             amount,
             15,
             &account_id_2.to_string(),
+            &SenderMemoFilter::default(),
             conn,
         )
         .unwrap();
@@ -5020,6 +5983,7 @@ mod tests {
             amount,
             15,
             &account_id_1.to_string(),
+            &SenderMemoFilter::default(),
             conn,
         )
         .unwrap();
@@ -5043,6 +6007,7 @@ mod tests {
             amount,
             15,
             &account_id_1.to_string(),
+            &SenderMemoFilter::default(),
             conn,
         )
         .unwrap();
@@ -5067,6 +6032,7 @@ mod tests {
             amount,
             15,
             &account_id_1.to_string(),
+            &SenderMemoFilter::default(),
             conn,
         )
         .unwrap();
@@ -5093,4 +6059,234 @@ mod tests {
         let recipient_public_address = txo_3.recipient_public_address(conn).unwrap();
         assert_eq!(recipient_public_address, Some(default_public_address));
     }
+
+    #[test_with_logger]
+    fn test_create_received_skips_filtered_sender_memos(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let (account_id, _address) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        // Only store memos with a payment intent id.
+        let sender_memo_filter = SenderMemoFilter::new(
+            Some(vec![SenderMemoType::AuthenticatedSenderWithPaymentIntentId]),
+            None,
+        );
+        let amount = Amount::new(1000 * MOB, Mob::ID);
+        let mut create_received = |memo| {
+            let (txo, key_image) =
+                create_test_txo_for_recipient_with_memo(&account_key, 0, amount, &mut rng, memo);
+            let txo_id = Txo::create_received(
+                txo,
+                Some(0),
+                Some(key_image),
+                amount,
+                15,
+                &account_id.to_string(),
+                &sender_memo_filter,
+                conn,
+            )
+            .unwrap();
+            Txo::get(&txo_id, conn).unwrap()
+        };
+
+        let skipped_txo = create_received(TransactionMemo::RTH {
+            subaddress_index: None,
+        });
+        let stored_txo = create_received(TransactionMemo::RTHWithPaymentIntentId {
+            subaddress_index: None,
+            payment_intent_id: 2000,
+        });
+
+        let memos = crate::db::models::AuthenticatedSenderMemo::list(conn).unwrap();
+        assert_eq!(memos.len(), 1);
+        assert_eq!(memos[0].txo_id, stored_txo.id);
+        assert_eq!(memos[0].payment_intent_id, Some(2000));
+
+        // The Txo still records its memo type, but its memo reports as unused.
+        assert!(skipped_txo.memo_type.is_some());
+        assert_matches!(skipped_txo.memo(conn).unwrap(), TxoMemo::Unused);
+        assert_matches!(
+            stored_txo.memo(conn).unwrap(),
+            TxoMemo::AuthenticatedSender(_)
+        );
+    }
+
+    #[test_with_logger]
+    fn test_update_memo(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let (account_id, _address) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        // Receive a Txo while skipping its memo.
+        let amount = Amount::new(1000 * MOB, Mob::ID);
+        let (txo, key_image) = create_test_txo_for_recipient_with_memo(
+            &account_key,
+            0,
+            amount,
+            &mut rng,
+            TransactionMemo::RTHWithPaymentRequestId {
+                subaddress_index: None,
+                payment_request_id: 3000,
+            },
+        );
+        let txo_id = Txo::create_received(
+            txo.clone(),
+            Some(0),
+            Some(key_image),
+            amount,
+            15,
+            &account_id.to_string(),
+            &SenderMemoFilter::new(Some(vec![]), None),
+            conn,
+        )
+        .unwrap();
+        let received_txo = Txo::get(&txo_id, conn).unwrap();
+        assert_matches!(received_txo.memo(conn).unwrap(), TxoMemo::Unused);
+
+        // Decoding the memo payload again stores the memo.
+        let account = Account::get(&account_id, conn).unwrap();
+        let shared_secret = account
+            .get_shared_secret(&RistrettoPublic::try_from(&txo.public_key).unwrap())
+            .unwrap();
+        let memo_payload = txo.e_memo.unwrap().decrypt(&shared_secret);
+        received_txo.update_memo(&memo_payload, conn).unwrap();
+        // Updating it again replaces the stored memo.
+        received_txo.update_memo(&memo_payload, conn).unwrap();
+
+        let memos = crate::db::models::AuthenticatedSenderMemo::list(conn).unwrap();
+        assert_eq!(memos.len(), 1);
+        assert_eq!(memos[0].txo_id, txo_id);
+        assert_eq!(memos[0].payment_request_id, Some(3000));
+        assert_matches!(
+            Txo::get(&txo_id, conn).unwrap().memo(conn).unwrap(),
+            TxoMemo::AuthenticatedSender(_)
+        );
+    }
+
+    #[test_with_logger]
+    fn test_backfill_shared_secrets(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let account_key = AccountKey::from(&root_id);
+        let (account_id, _address) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            None,
+            None,
+            "",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        let amount = Amount::new(1000 * MOB, Mob::ID);
+        let (txo, key_image) = create_test_txo_for_recipient_with_memo(
+            &account_key,
+            0,
+            amount,
+            &mut rng,
+            TransactionMemo::RTH {
+                subaddress_index: None,
+            },
+        );
+        let txo_id = Txo::create_received(
+            txo,
+            Some(0),
+            Some(key_image),
+            amount,
+            15,
+            &account_id.to_string(),
+            &SenderMemoFilter::default(),
+            conn,
+        )
+        .unwrap();
+        let shared_secret = Txo::get(&txo_id, conn).unwrap().shared_secret;
+        assert!(shared_secret.is_some());
+
+        let (corrupt_txo, corrupt_key_image) =
+            create_test_txo_for_recipient(&account_key, 0, amount, &mut rng);
+        let corrupt_txo_id = Txo::create_received(
+            corrupt_txo,
+            Some(0),
+            Some(corrupt_key_image),
+            amount,
+            16,
+            &account_id.to_string(),
+            &SenderMemoFilter::default(),
+            conn,
+        )
+        .unwrap();
+
+        // Clear the shared secrets, as for Txos received before they were stored.
+        diesel::update(crate::db::schema::txos::table)
+            .set(crate::db::schema::txos::shared_secret.eq(None::<Vec<u8>>))
+            .execute(conn)
+            .unwrap();
+        let txo = Txo::get(&txo_id, conn).unwrap();
+        assert_matches!(txo.memo(conn).unwrap(), TxoMemo::Unused);
+
+        // A Txo with a corrupt public key is skipped without failing the others.
+        diesel::update(
+            crate::db::schema::txos::table.filter(crate::db::schema::txos::id.eq(&corrupt_txo_id)),
+        )
+        .set(crate::db::schema::txos::public_key.eq(vec![1u8, 2, 3]))
+        .execute(conn)
+        .unwrap();
+
+        assert_eq!(Txo::backfill_shared_secrets(conn).unwrap(), 1);
+        let txo = Txo::get(&txo_id, conn).unwrap();
+        assert_eq!(txo.shared_secret, shared_secret);
+        assert_matches!(txo.memo(conn).unwrap(), TxoMemo::AuthenticatedSender(_));
+        assert!(Txo::get(&corrupt_txo_id, conn)
+            .unwrap()
+            .shared_secret
+            .is_none());
+
+        // There is nothing left to backfill.
+        assert_eq!(Txo::backfill_shared_secrets(conn).unwrap(), 0);
+    }
 }
@@ -1,6 +1,10 @@
 use crate::db::{
-    models::{AssignedSubaddress, Migration, NewMigration},
-    schema::{__diesel_schema_migrations, assigned_subaddresses},
+    models::{
+        AssignedSubaddress, Migration, NewMigration, NewPostMigrationProcess, PostMigrationProcess,
+        Txo,
+    },
+    schema::{__diesel_schema_migrations, assigned_subaddresses, post_migration_processes},
+    txo::TxoModel,
     WalletDbError,
 };
 use diesel::{
@@ -20,6 +24,14 @@ pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/");
 
 pub type Conn<'a> = &'a mut SqliteConnection;
 
+/// The version of the migration which added the nullable shared secret column
+/// to txos, whose shared secrets are backfilled once after it.
+const SHARED_SECRET_BACKFILL_MIGRATION_VERSION: &str = "20230323192434";
+
+/// How long a connection waits for another connection's write lock before
+/// failing with "database is locked".
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Debug)]
 pub struct ConnectionOptions {
     pub enable_wal: bool,
@@ -66,6 +78,13 @@ impl WalletDb {
         Self { pool }
     }
 
+    /// Open a pool of up to `db_connections` connections to the database.
+    ///
+    /// Connections use WAL mode, so reads proceed while another connection is
+    /// writing, but SQLite (and SQLCipher) allow only one writer at a time.
+    /// Writers wait for each other for up to `BUSY_TIMEOUT` rather than failing
+    /// immediately, so a larger pool helps concurrent reads without making
+    /// writes fail.
     pub fn new_from_url(database_url: &str, db_connections: u32) -> Result<Self, WalletDbError> {
         let manager = ConnectionManager::<SqliteConnection>::new(database_url);
         let pool = Pool::builder()
@@ -73,7 +92,7 @@ impl WalletDb {
             .connection_customizer(Box::new(ConnectionOptions {
                 enable_wal: true,
                 enable_foreign_keys: true,
-                busy_timeout: Some(Duration::from_secs(30)),
+                busy_timeout: Some(BUSY_TIMEOUT),
             }))
             .test_on_check_out(true)
             .build(manager)?;
@@ -128,6 +147,11 @@ impl WalletDb {
             .is_ok()
     }
 
+    /// The journal mode of the connection, e.g. "wal".
+    pub fn journal_mode(conn: &mut SqliteConnection) -> Result<String, diesel::result::Error> {
+        diesel::dsl::sql::<sql_types::Text>("PRAGMA journal_mode;").get_result::<String>(conn)
+    }
+
     pub fn validate_foreign_keys(conn: &mut SqliteConnection) {
         let invalid_foreign_keys = diesel::dsl::sql::<(
             sql_types::Text,
@@ -238,6 +262,50 @@ impl WalletDb {
             global_log::debug!("Assigned subaddress proto conversion done");
         }
     }
+
+    /// Txos received before shared secrets were stored have no shared secret,
+    /// so their memos could not be decoded again. This computes and stores
+    /// them.
+    ///
+    /// This is a one-time conversion, so its completion is recorded in the
+    /// post migration processes, and once it has run we do nothing. A failure
+    /// is logged rather than stopping the wallet from starting, and the
+    /// conversion is tried again on the next start.
+    pub fn run_shared_secret_backfill(conn: &mut SqliteConnection) {
+        global_log::debug!("Checking for txos missing shared secrets");
+        let result: Result<Option<usize>, WalletDbError> = exclusive_transaction(conn, |conn| {
+            let has_run = post_migration_processes::table
+                .filter(
+                    post_migration_processes::migration_version
+                        .eq(SHARED_SECRET_BACKFILL_MIGRATION_VERSION),
+                )
+                .first::<PostMigrationProcess>(conn)
+                .optional()?
+                .map_or(false, |process| process.has_run);
+            if has_run {
+                return Ok(None);
+            }
+
+            let num_txos = Txo::backfill_shared_secrets(conn)?;
+            diesel::replace_into(post_migration_processes::table)
+                .values(&NewPostMigrationProcess {
+                    migration_version: SHARED_SECRET_BACKFILL_MIGRATION_VERSION.to_string(),
+                    has_run: true,
+                })
+                .execute(conn)?;
+            Ok(Some(num_txos))
+        });
+
+        match result {
+            Ok(None) => {
+                global_log::debug!("Txo shared secret backfill already done, skipping...")
+            }
+            Ok(Some(num_txos)) => {
+                global_log::info!("Stored the shared secrets of {} txos", num_txos)
+            }
+            Err(err) => global_log::error!("Failed backfilling txo shared secrets: {}", err),
+        }
+    }
 }
 
 /// Escape a string for consumption by SQLite.
@@ -247,6 +315,89 @@ fn sql_escape_string(s: &str) -> String {
     format!("'{}'", s.replace('\'', "''"))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::WalletDbTestContext;
+    use mc_common::logger::{test_with_logger, Logger};
+
+    #[test_with_logger]
+    fn test_pooled_connections_use_wal(logger: Logger) {
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let conn = &mut wallet_db.get_pooled_conn().unwrap();
+        assert_eq!(WalletDb::journal_mode(conn).unwrap(), "wal");
+    }
+
+    #[test_with_logger]
+    fn test_queries_use_indexes(logger: Logger) {
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let conn = &mut wallet_db.get_pooled_conn().unwrap();
+
+        let mut query_plan = |query: &str| -> String {
+            diesel::dsl::sql::<(
+                sql_types::Integer,
+                sql_types::Integer,
+                sql_types::Integer,
+                sql_types::Text,
+            )>(&format!("EXPLAIN QUERY PLAN {query}"))
+            .load::<(i32, i32, i32, String)>(conn)
+            .unwrap()
+            .into_iter()
+            .map(|(_, _, _, detail)| detail)
+            .collect::<Vec<_>>()
+            .join("\n")
+        };
+
+        assert!(query_plan("SELECT * FROM txos WHERE public_key = x'00'")
+            .contains("USING INDEX idx_txos__public_key"));
+        assert!(query_plan("SELECT * FROM txos WHERE account_id = 'a'")
+            .contains("USING INDEX idx_txos__account_id"));
+        assert!(query_plan(
+            "SELECT * FROM txos WHERE account_id = 'a' AND token_id = 0 AND spent_block_index IS NULL"
+        )
+        .contains("USING INDEX idx_txos__account_id_token_id_spent_block_index"));
+
+        // txo_id is the primary key, which SQLite indexes automatically.
+        assert!(query_plan(
+            "SELECT * FROM authenticated_sender_memos WHERE sender_address_hash = 'a'"
+        )
+        .contains("USING INDEX idx_authenticated_sender_memos__sender_address_hash"));
+        assert!(
+            query_plan("SELECT * FROM authenticated_sender_memos WHERE txo_id = 'a'")
+                .contains("USING INDEX sqlite_autoindex_authenticated_sender_memos_1")
+        );
+    }
+
+    #[test_with_logger]
+    fn test_shared_secret_backfill_is_recorded(logger: Logger) {
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger);
+        let conn = &mut wallet_db.get_pooled_conn().unwrap();
+
+        let has_run = |conn: Conn| -> Option<bool> {
+            post_migration_processes::table
+                .filter(
+                    post_migration_processes::migration_version
+                        .eq(SHARED_SECRET_BACKFILL_MIGRATION_VERSION),
+                )
+                .first::<PostMigrationProcess>(conn)
+                .optional()
+                .unwrap()
+                .map(|process| process.has_run)
+        };
+        assert_eq!(has_run(conn), None);
+
+        WalletDb::run_shared_secret_backfill(conn);
+        assert_eq!(has_run(conn), Some(true));
+
+        // Running it again leaves the record as it is.
+        WalletDb::run_shared_secret_backfill(conn);
+        assert_eq!(has_run(conn), Some(true));
+    }
+}
+
 /// Create an immediate SQLite transaction with retry.
 /// Note: This function does not support nested transactions.
 pub fn exclusive_transaction<T, E, F>(conn: Conn, f: F) -> Result<T, E>
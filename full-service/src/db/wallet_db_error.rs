@@ -1,6 +1,6 @@
 // Copyright (c) 2020-2021 MobileCoin Inc.
 
-use crate::{db::gift_code::GiftCodeDbError, util::b58::B58Error};
+use crate::{db::gift_code::GiftCodeDbError, error::ErrorCode, util::b58::B58Error};
 use base64::DecodeSliceError;
 use mc_transaction_extra::MemoDecodingError;
 use reqwest;
@@ -147,6 +147,12 @@ pub enum WalletDbError {
     /// invalid txo status
     InvalidTxoStatus(String),
 
+    /// Invalid account sort order: {0}
+    InvalidAccountSortOrder(String),
+
+    /// Invalid sender memo type: {0}
+    InvalidSenderMemoType(String),
+
     /// Expected to find TxOut as an outlay
     ExpectedTxOutAsOutlay,
 
@@ -172,6 +178,75 @@ pub enum WalletDbError {
     MemoDecoding(MemoDecodingError),
 }
 
+impl ErrorCode for WalletDbError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::WalletFunctionsDisabled => "wallet_functions_disabled",
+            Self::ViewOnlyAccountAlreadyExists(..) => "view_only_account_already_exists",
+            Self::AccountAlreadyExists(..) => "account_already_exists",
+            Self::Diesel(..) => "diesel",
+            Self::RocketDB(..) => "rocket_db",
+            Self::DuplicateEntries(..) => "duplicate_entries",
+            Self::B58Encode(..) => "b58_encode",
+            Self::B58Decode => "b58_decode",
+            Self::MultipleAccountIDsInTransaction => "multiple_account_i_ds_in_transaction",
+            Self::TransactionLacksRecipient => "transaction_lacks_recipient",
+            Self::TransactionLacksAccount => "transaction_lacks_account",
+            Self::ProstDecode(..) => "prost_decode",
+            Self::UnexpectedNumberOfChangeOutputs => "unexpected_number_of_change_outputs",
+            Self::MissingKeyImage => "missing_key_image",
+            Self::NullSubaddressOnReceived => "null_subaddress_on_received",
+            Self::NoSpendableTxos(..) => "no_spendable_txos",
+            Self::InsufficientFundsFragmentedTxos => "insufficient_funds_fragmented_txos",
+            Self::InsufficientFunds(..) => "insufficient_funds",
+            Self::InsufficientFundsUnderMaxSpendable(..) => {
+                "insufficient_funds_under_max_spendable"
+            }
+            Self::InvalidArgument(..) => "invalid_argument",
+            Self::UnexpectedTransactionTxoType(..) => "unexpected_transaction_txo_type",
+            Self::UnexpectedAccountTxoStatus(..) => "unexpected_account_txo_status",
+            Self::UnexpectedNumberOfAccountsAssociatedWithTxo(..) => {
+                "unexpected_number_of_accounts_associated_with_txo"
+            }
+            Self::TransactionMismatch => "transaction_mismatch",
+            Self::AccountNotFound(..) => "account_not_found",
+            Self::AssignedSubaddressNotFound(..) => "assigned_subaddress_not_found",
+            Self::TxoNotFound(..) => "txo_not_found",
+            Self::TransactionLogNotFound(..) => "transaction_log_not_found",
+            Self::AccountTxoStatusNotFound(..) => "account_txo_status_not_found",
+            Self::TransactionValueExceedsMax => "transaction_value_exceeds_max",
+            Self::TxoExistsForAnotherAccount(..) => "txo_exists_for_another_account",
+            Self::TxoAssociatedWithTooManyAccounts(..) => "txo_associated_with_too_many_accounts",
+            Self::MalformedTxoDatabaseEntry => "malformed_txo_database_entry",
+            Self::AccountSecretsDoNotMatch => "account_secrets_do_not_match",
+            Self::InsufficientSecretsToCreateAccount => "insufficient_secrets_to_create_account",
+            Self::GiftCode(err) => err.error_code(),
+            Self::B58(err) => err.error_code(),
+            Self::LedgerDB(..) => "ledger_db",
+            Self::ProtoConversion(..) => "proto_conversion",
+            Self::Slip10Key(..) => "slip10_key",
+            Self::Base64Decode(..) => "base64_decode",
+            Self::SubaddressesNotSupportedForFOGEnabledAccounts => {
+                "subaddresses_not_supported_for_fog_enabled_accounts"
+            }
+            Self::KeyError(..) => "key_error",
+            Self::InvalidTxoStatus(..) => "invalid_txo_status",
+            Self::InvalidAccountSortOrder(..) => "invalid_account_sort_order",
+            Self::InvalidSenderMemoType(..) => "invalid_sender_memo_type",
+            Self::ExpectedTxOutAsOutlay => "expected_tx_out_as_outlay",
+            Self::MissingTxoMembershipProof(..) => "missing_txo_membership_proof",
+            Self::MissingKeyImageForInputTxo(..) => "missing_key_image_for_input_txo",
+            Self::ReqwestError(..) => "reqwest_error",
+            Self::Dalek(..) => "dalek",
+            Self::AccountKeyNotAvailableForViewOnlyAccount => {
+                "account_key_not_available_for_view_only_account"
+            }
+            Self::DecodeSlice(..) => "decode_slice",
+            Self::MemoDecoding(..) => "memo_decoding",
+        }
+    }
+}
+
 impl From<diesel::result::Error> for WalletDbError {
     fn from(src: diesel::result::Error) -> Self {
         Self::Diesel(src)
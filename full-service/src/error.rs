@@ -16,6 +16,16 @@ use crate::{
 use displaydoc::Display;
 use hex::FromHexError;
 
+/// A stable, machine-readable snake_case code naming an error variant, which
+/// JSON RPC clients can branch on instead of parsing the English message.
+///
+/// Variants wrapping another wallet error report the code of the error they
+/// wrap, so `AccountService(Database(AccountNotFound(..)))` is
+/// `account_not_found`.
+pub trait ErrorCode {
+    fn error_code(&self) -> &'static str;
+}
+
 #[derive(Display, Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum WalletServiceError {
@@ -68,6 +78,29 @@ pub enum WalletServiceError {
     PaymentRequestService(PaymentRequestServiceError),
 }
 
+impl ErrorCode for WalletServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Database(err) => err.error_code(),
+            Self::HexDecode(..) => "hex_decode",
+            Self::U64Parse => "u64_parse",
+            Self::LedgerDB(..) => "ledger_db",
+            Self::ProstDecode(..) => "prost_decode",
+            Self::SerdeJson(..) => "serde_json",
+            Self::Diesel(..) => "diesel",
+            Self::TransactionService(err) => err.error_code(),
+            Self::BalanceService(err) => err.error_code(),
+            Self::LedgerService(err) => err.error_code(),
+            Self::TxoService(err) => err.error_code(),
+            Self::ConfirmationService(err) => err.error_code(),
+            Self::TransactionLogService(err) => err.error_code(),
+            Self::GiftCodeService(err) => err.error_code(),
+            Self::AccountService(err) => err.error_code(),
+            Self::PaymentRequestService(err) => err.error_code(),
+        }
+    }
+}
+
 impl From<WalletDbError> for WalletServiceError {
     fn from(src: WalletDbError) -> Self {
         Self::Database(src)
@@ -353,6 +386,14 @@ pub enum WalletTransactionBuilderError {
     /// Transaction is missing inputs for outputs with token id {0}
     MissingInputsForTokenId(String),
 
+    /// The fee must be paid in the same token as the outputs, but the fee is in
+    /// token {0} and an output is in token {1}
+    FeeTokenMismatch(u64, u64),
+
+    /// Insufficient balance of token {0} to pay the fee of {1}, in addition to
+    /// the outputs
+    InsufficientFeeTokenBalance(u64, u64),
+
     /// Error decoding the hex string: {0}
     FromHexError(hex::FromHexError),
 
@@ -374,6 +415,45 @@ pub enum WalletTransactionBuilderError {
     ChangeLargerThanMaxValue(u128),
 }
 
+impl ErrorCode for WalletTransactionBuilderError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::InsufficientFunds(..) => "insufficient_funds",
+            Self::InsufficientInputFunds(..) => "insufficient_input_funds",
+            Self::InsufficientTxOuts => "insufficient_tx_outs",
+            Self::RingSizeMismatch => "ring_size_mismatch",
+            Self::NoRecipient => "no_recipient",
+            Self::RingsAndProofsEmpty => "rings_and_proofs_empty",
+            Self::LedgerDB(..) => "ledger_db",
+            Self::TxBuilder(..) => "tx_builder",
+            Self::InvalidArgument(..) => "invalid_argument",
+            Self::ProstDecode(..) => "prost_decode",
+            Self::WalletDb(err) => err.error_code(),
+            Self::FogError(..) => "fog_error",
+            Self::NullSubaddress(..) => "null_subaddress",
+            Self::Diesel(..) => "diesel",
+            Self::NoInputs => "no_inputs",
+            Self::OutboundValueTooLarge => "outbound_value_too_large",
+            Self::TombstoneNotSet => "tombstone_not_set",
+            Self::InsufficientFee(..) => "insufficient_fee",
+            Self::UriParse(..) => "uri_parse",
+            Self::FogPubkeyResolver(..) => "fog_pubkey_resolver",
+            Self::B58(err) => err.error_code(),
+            Self::AmountError(..) => "amount_error",
+            Self::KeyError(..) => "key_error",
+            Self::MissingInputsForTokenId(..) => "missing_inputs_for_token_id",
+            Self::FeeTokenMismatch(..) => "fee_token_mismatch",
+            Self::InsufficientFeeTokenBalance(..) => "insufficient_fee_token_balance",
+            Self::FromHexError(..) => "from_hex_error",
+            Self::InvalidBurnRedemptionMemo(..) => "invalid_burn_redemption_memo",
+            Self::TxOutConversion(..) => "tx_out_conversion",
+            Self::RTHUnavailableForViewOnlyAccounts => "rth_unavailable_for_view_only_accounts",
+            Self::CannotUseOrphanedTxoAsInput(..) => "cannot_use_orphaned_txo_as_input",
+            Self::ChangeLargerThanMaxValue(..) => "change_larger_than_max_value",
+        }
+    }
+}
+
 impl From<mc_transaction_core::AmountError> for WalletTransactionBuilderError {
     fn from(src: mc_transaction_core::AmountError) -> Self {
         Self::AmountError(src)
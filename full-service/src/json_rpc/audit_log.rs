@@ -0,0 +1,460 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! An audit log of the JSON-RPC requests which move funds.
+//!
+//! When configured with a path, one JSON line is appended to the file for each
+//! fund-moving request, separately from the application log. Only the method,
+//! account, output values, hashes of the recipient addresses and the resulting
+//! transaction log id are recorded, so request secrets such as mnemonics, keys
+//! and gift codes never reach the file.
+//!
+//! Each line includes an HMAC of the line before it, keyed with a secret from
+//! the operator's config, so that editing or removing a line can be detected
+//! with [AuditLog::verify] by anyone holding the key, and can not be covered up
+//! by recomputing the chain without it.
+//!
+//! A request is recorded before it runs, and its outcome once it completes. If
+//! the request can not be recorded, it is refused, and once any write fails
+//! every later fund-moving request is refused too, so that funds never move
+//! without a record of it.
+
+use crate::{
+    error::ErrorCode,
+    json_rpc::{
+        json_rpc_request::JsonRPCRequest, json_rpc_response::JsonRPCError,
+        wallet::FUND_MOVING_METHODS,
+    },
+};
+use displaydoc::Display;
+use hmac::{Hmac, Mac};
+use mc_common::logger::global_log;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The previous hash recorded by the first line of an audit log.
+const GENESIS_HASH: &str = "";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Display, Debug)]
+pub enum AuditLogError {
+    /// Error accessing the audit log: {0}
+    Io(std::io::Error),
+
+    /// Error serializing an audit log entry: {0}
+    Json(serde_json::Error),
+
+    /// Audit log line {0} does not follow the line before it
+    BrokenChain(usize),
+
+    /// A previous write to the audit log failed, so no more requests are
+    /// recorded
+    Failed,
+}
+
+impl ErrorCode for AuditLogError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Io(..) | Self::Json(..) | Self::Failed => "audit_log_unavailable",
+            Self::BrokenChain(..) => "audit_log_broken_chain",
+        }
+    }
+}
+
+impl From<std::io::Error> for AuditLogError {
+    fn from(src: std::io::Error) -> Self {
+        Self::Io(src)
+    }
+}
+
+impl From<serde_json::Error> for AuditLogError {
+    fn from(src: serde_json::Error) -> Self {
+        Self::Json(src)
+    }
+}
+
+/// The audit log, if one is configured, as managed by rocket.
+pub struct AuditLogState(pub Option<AuditLog>);
+
+impl AuditLogState {
+    /// Record a request before it runs, if an audit log is configured. The
+    /// request must not be run if this fails.
+    pub fn record_request(&self, request: &JsonRPCRequest) -> Result<(), AuditLogError> {
+        match &self.0 {
+            Some(audit_log) => audit_log.record(request, false, None, None),
+            None => Ok(()),
+        }
+    }
+
+    /// Record the outcome of a request, if an audit log is configured. The
+    /// request has already run, so failing to write its outcome is logged, and
+    /// later requests are refused.
+    pub fn record_outcome<R: Serialize>(
+        &self,
+        request: &JsonRPCRequest,
+        result: Option<&R>,
+        error: Option<&JsonRPCError>,
+    ) {
+        if let Some(audit_log) = &self.0 {
+            let result = result.and_then(|result| serde_json::to_value(result).ok());
+            let error = error.map(|JsonRPCError::error { message, .. }| message.clone());
+            if let Err(e) = audit_log.record(request, true, result, error) {
+                global_log::error!("Failed writing to the audit log: {}", e);
+            }
+        }
+    }
+}
+
+pub struct AuditLog {
+    /// The key the chain of hashes is computed with.
+    key: Vec<u8>,
+
+    file: Mutex<AuditLogFile>,
+}
+
+struct AuditLogFile {
+    file: File,
+
+    /// The hash of the last line of the file.
+    prev_hash: String,
+
+    /// Whether a write has failed, which may have left a partial line.
+    failed: bool,
+}
+
+/// A single line of the audit log.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct AuditLogEntry {
+    /// Seconds since the unix epoch when the request completed.
+    pub timestamp: u64,
+
+    /// The JSON-RPC method, after translating v1 requests to v2.
+    pub method: String,
+
+    /// Whether this line records the outcome of the request, rather than the
+    /// request before it runs.
+    pub completed: bool,
+
+    /// The account the request acted on, if any.
+    pub account_id: Option<String>,
+
+    /// The outputs the request asked to send.
+    pub outputs: Vec<AuditLogOutput>,
+
+    /// The id of the transaction log the request created or updated, if any.
+    pub transaction_log_id: Option<String>,
+
+    /// The error message, if the request failed.
+    pub error: Option<String>,
+
+    /// The hex-encoded HMAC-SHA-256 of the previous line of the log.
+    pub prev_hash: String,
+}
+
+/// An output a fund-moving request asked to send.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct AuditLogOutput {
+    /// The hex-encoded SHA-256 hash of the recipient's b58 address.
+    pub recipient_hash: Option<String>,
+
+    pub value: Option<String>,
+
+    pub token_id: Option<String>,
+}
+
+impl AuditLog {
+    /// Open the audit log at the given path for appending, creating it if it
+    /// does not exist. The chain is keyed with `key`, which must be the key
+    /// the existing lines were written with.
+    pub fn open(path: &Path, key: &[u8]) -> Result<Self, AuditLogError> {
+        let prev_hash = match File::open(path) {
+            Ok(file) => match BufReader::new(file).lines().last() {
+                Some(line) => hmac_line(key, &line?),
+                None => GENESIS_HASH.to_string(),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => GENESIS_HASH.to_string(),
+            Err(e) => return Err(e.into()),
+        };
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            key: key.to_vec(),
+            file: Mutex::new(AuditLogFile {
+                file,
+                prev_hash,
+                failed: false,
+            }),
+        })
+    }
+
+    /// Append an entry for a request, if it is one which moves funds. The
+    /// request must already be translated to v2, if it was made as a v1
+    /// request. The result is the serialized response, or None if the request
+    /// failed or has not run yet.
+    pub fn record(
+        &self,
+        request: &JsonRPCRequest,
+        completed: bool,
+        result: Option<Value>,
+        error: Option<String>,
+    ) -> Result<(), AuditLogError> {
+        if !FUND_MOVING_METHODS.contains(&request.method.as_str()) {
+            return Ok(());
+        }
+        let params = request.params.clone().unwrap_or_default();
+
+        let mut file = self.file.lock().expect("lock poisoned");
+        if file.failed {
+            return Err(AuditLogError::Failed);
+        }
+        let entry = AuditLogEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            method: request.method.clone(),
+            completed,
+            account_id: params["account_id"].as_str().map(str::to_string),
+            outputs: outputs(&params),
+            transaction_log_id: result.as_ref().and_then(transaction_log_id),
+            error,
+            prev_hash: file.prev_hash.clone(),
+        };
+        let line = serde_json::to_string(&entry)?;
+        if let Err(e) = file
+            .file
+            .write_all(format!("{line}\n").as_bytes())
+            .and_then(|()| file.file.flush())
+        {
+            file.failed = true;
+            return Err(e.into());
+        }
+        file.prev_hash = hmac_line(&self.key, &line);
+        Ok(())
+    }
+
+    /// Check that every line of the audit log at the given path follows the
+    /// line before it, using the key the log was written with, returning the
+    /// number of lines.
+    pub fn verify(path: &Path, key: &[u8]) -> Result<usize, AuditLogError> {
+        let mut prev_hash = GENESIS_HASH.to_string();
+        let mut num_lines = 0;
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            num_lines += 1;
+            let entry: AuditLogEntry = serde_json::from_str(&line)?;
+            if entry.prev_hash != prev_hash {
+                return Err(AuditLogError::BrokenChain(num_lines));
+            }
+            prev_hash = hmac_line(key, &line);
+        }
+        Ok(num_lines)
+    }
+}
+
+fn hmac_line(key: &[u8], line: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(line.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn hash_line(line: &str) -> String {
+    hex::encode(Sha256::digest(line.as_bytes()))
+}
+
+fn hash_address(address: &Value) -> Option<String> {
+    address.as_str().map(hash_line)
+}
+
+/// Read a value given either as a string or as a number.
+fn value_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// The outputs of a v1 or v2 fund-moving request.
+fn outputs(params: &Value) -> Vec<AuditLogOutput> {
+    let mut outputs = Vec::new();
+
+    // v2 requests give amounts as objects with a value and token id.
+    if let Some(addresses_and_amounts) = params["addresses_and_amounts"].as_array() {
+        for pair in addresses_and_amounts {
+            outputs.push(AuditLogOutput {
+                recipient_hash: hash_address(&pair[0]),
+                value: value_string(&pair[1]["value"]),
+                token_id: value_string(&pair[1]["token_id"]),
+            });
+        }
+    }
+    if !params["amount"].is_null() {
+        outputs.push(AuditLogOutput {
+            recipient_hash: hash_address(&params["recipient_public_address"]),
+            value: value_string(&params["amount"]["value"]),
+            token_id: value_string(&params["amount"]["token_id"]),
+        });
+    }
+
+    // v1 requests give values in picoMOB.
+    if let Some(addresses_and_values) = params["addresses_and_values"].as_array() {
+        for pair in addresses_and_values {
+            outputs.push(AuditLogOutput {
+                recipient_hash: hash_address(&pair[0]),
+                value: value_string(&pair[1]),
+                token_id: Some("0".to_string()),
+            });
+        }
+    }
+    if !params["value_pmob"].is_null() {
+        outputs.push(AuditLogOutput {
+            recipient_hash: hash_address(&params["recipient_public_address"]),
+            value: value_string(&params["value_pmob"]),
+            token_id: Some("0".to_string()),
+        });
+    }
+
+    outputs
+}
+
+/// The transaction log id in a v1 or v2 response, if any.
+fn transaction_log_id(result: &Value) -> Option<String> {
+    [
+        &result["transaction_log_id"],
+        &result["transaction_log"]["id"],
+        &result["transaction_log"]["transaction_log_id"],
+    ]
+    .into_iter()
+    .find_map(|id| id.as_str().map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_common::logger::{test_with_logger, Logger};
+    use serde_json::json;
+    use std::assert_matches::assert_matches;
+    use tempdir::TempDir;
+
+    const KEY: &[u8] = b"audit log key";
+
+    fn request(method: &str, params: Value) -> JsonRPCRequest {
+        JsonRPCRequest {
+            method: method.to_string(),
+            params: Some(params),
+            jsonrpc: "2.0".to_string(),
+            id: json!(1),
+            include_elapsed_ms: None,
+        }
+    }
+
+    #[test_with_logger]
+    fn test_audit_log(_logger: Logger) {
+        let dir = TempDir::new("audit_log").unwrap();
+        let path = dir.path().join("audit.log");
+
+        let audit_log = AuditLog::open(&path, KEY).unwrap();
+        audit_log
+            .record(
+                &request(
+                    "build_and_submit_transaction",
+                    json!({
+                        "account_id": "a1",
+                        "addresses_and_amounts": [["addr1", {"value": "42", "token_id": "1"}]],
+                    }),
+                ),
+                true,
+                Some(json!({"transaction_log": {"id": "log1"}})),
+                None,
+            )
+            .unwrap();
+        // Requests which do not move funds are not recorded.
+        audit_log
+            .record(
+                &request("get_account_status", json!({"account_id": "a1"})),
+                true,
+                None,
+                None,
+            )
+            .unwrap();
+        drop(audit_log);
+
+        // Reopening continues the chain.
+        let audit_log = AuditLog::open(&path, KEY).unwrap();
+        audit_log
+            .record(
+                &request(
+                    "export_account_secrets",
+                    json!({"account_id": "a1", "mnemonic": "secret words"}),
+                ),
+                true,
+                None,
+                Some("failed".to_string()),
+            )
+            .unwrap();
+        assert_eq!(AuditLog::verify(&path, KEY).unwrap(), 2);
+        // The chain can not be verified, or recomputed, without the key.
+        assert_matches!(
+            AuditLog::verify(&path, b"other key"),
+            Err(AuditLogError::BrokenChain(2))
+        );
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("addr1"));
+        assert!(!contents.contains("secret words"));
+        let entries: Vec<AuditLogEntry> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries[0].account_id.as_deref(), Some("a1"));
+        assert_eq!(
+            entries[0].outputs,
+            vec![AuditLogOutput {
+                recipient_hash: Some(hash_line("addr1")),
+                value: Some("42".to_string()),
+                token_id: Some("1".to_string()),
+            }]
+        );
+        assert_eq!(entries[0].transaction_log_id.as_deref(), Some("log1"));
+        assert_eq!(entries[1].method, "export_account_secrets");
+        assert_eq!(entries[1].error.as_deref(), Some("failed"));
+
+        // Editing a line breaks the chain after it.
+        std::fs::write(&path, contents.replace("\"42\"", "\"43\"")).unwrap();
+        assert_matches!(
+            AuditLog::verify(&path, KEY),
+            Err(AuditLogError::BrokenChain(2))
+        );
+    }
+
+    #[test_with_logger]
+    fn test_audit_log_fails_closed(_logger: Logger) {
+        let dir = TempDir::new("audit_log").unwrap();
+        let path = dir.path().join("audit.log");
+        let submit = request("submit_transaction", json!({"account_id": "a1"}));
+
+        let state = AuditLogState(Some(AuditLog::open(&path, KEY).unwrap()));
+        state.record_request(&submit).unwrap();
+
+        // Once a write fails, nothing more is recorded, so fund-moving requests
+        // are refused.
+        state.0.as_ref().unwrap().file.lock().unwrap().failed = true;
+        assert_matches!(state.record_request(&submit), Err(AuditLogError::Failed));
+        assert!(state
+            .record_request(&request("get_account_status", json!({})))
+            .is_ok());
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let entry: AuditLogEntry = serde_json::from_str(contents.trim()).unwrap();
+        assert_eq!(entry.method, "submit_transaction");
+        assert!(!entry.completed);
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright (c) 2020-2023 MobileCoin Inc.
+
+//! Gzip content-encoding support for the Wallet API.
+//!
+//! Requests may be sent with `Content-Encoding: gzip`, and responses are
+//! compressed for clients that send `Accept-Encoding: gzip`.
+
+use displaydoc::Display;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use rocket::{
+    data::{self, Data, FromData, Limits},
+    fairing::{Fairing, Info, Kind},
+    http::{Header, Status},
+    outcome::Outcome,
+    serde::json::Json,
+    Request, Response,
+};
+use serde::de::DeserializeOwned;
+use std::io::{Cursor, Read, Write};
+
+const GZIP: &str = "gzip";
+
+/// A JSON request body which may optionally be gzip-compressed.
+///
+/// Bodies without a `Content-Encoding: gzip` header are parsed exactly as
+/// Rocket's `Json` would parse them.
+pub struct GzipJson<T>(pub Json<T>);
+
+#[derive(Display, Debug)]
+pub enum GzipJsonError {
+    /// Error reading request body: {0}
+    Io(std::io::Error),
+
+    /// Request body exceeds the JSON size limit
+    TooLarge,
+
+    /// Error parsing JSON request body: {0}
+    Parse(String),
+}
+
+#[rocket::async_trait]
+impl<'r, T: DeserializeOwned> FromData<'r> for GzipJson<T> {
+    type Error = GzipJsonError;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        if req.headers().get_one("Content-Encoding") != Some(GZIP) {
+            return Json::from_data(req, data)
+                .await
+                .map(GzipJson)
+                .map_error(|(status, err)| (status, GzipJsonError::Parse(err.to_string())));
+        }
+
+        let limit = req.limits().get("json").unwrap_or(Limits::JSON);
+        let compressed = match data.open(limit).into_bytes().await {
+            Ok(bytes) if bytes.is_complete() => bytes.into_inner(),
+            Ok(_) => return Outcome::Error((Status::PayloadTooLarge, GzipJsonError::TooLarge)),
+            Err(err) => return Outcome::Error((Status::BadRequest, GzipJsonError::Io(err))),
+        };
+
+        // Bound the decompressed size by the same limit, so a small compressed
+        // body cannot expand into an arbitrarily large allocation.
+        let mut decompressed = Vec::new();
+        let max_len = limit.as_u64();
+        if let Err(err) = GzDecoder::new(&compressed[..])
+            .take(max_len + 1)
+            .read_to_end(&mut decompressed)
+        {
+            return Outcome::Error((Status::BadRequest, GzipJsonError::Io(err)));
+        }
+        if decompressed.len() as u64 > max_len {
+            return Outcome::Error((Status::PayloadTooLarge, GzipJsonError::TooLarge));
+        }
+
+        match serde_json::from_slice(&decompressed) {
+            Ok(value) => Outcome::Success(GzipJson(Json(value))),
+            Err(err) => Outcome::Error((
+                Status::UnprocessableEntity,
+                GzipJsonError::Parse(err.to_string()),
+            )),
+        }
+    }
+}
+
+/// Compress response bodies for clients which accept gzip.
+pub struct Gzip;
+
+#[rocket::async_trait]
+impl Fairing for Gzip {
+    fn info(&self) -> Info {
+        Info {
+            name: "Gzip Response Compression",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let accepts_gzip = request
+            .headers()
+            .get("Accept-Encoding")
+            .flat_map(|value| value.split(','))
+            .any(|encoding| encoding.split(';').next().unwrap_or_default().trim() == GZIP);
+        if !accepts_gzip || response.headers().contains("Content-Encoding") {
+            return;
+        }
+
+        let body = match response.body_mut().to_bytes().await {
+            Ok(body) => body,
+            Err(_) => return,
+        };
+        if body.is_empty() {
+            return;
+        }
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        let compressed = match encoder.write_all(&body).and_then(|_| encoder.finish()) {
+            Ok(compressed) => compressed,
+            Err(_) => {
+                // Fall back to the uncompressed body.
+                response.set_sized_body(body.len(), Cursor::new(body));
+                return;
+            }
+        };
+
+        response.set_header(Header::new("Content-Encoding", GZIP));
+        response.adjoin_header(Header::new("Vary", "Accept-Encoding"));
+        response.set_sized_body(compressed.len(), Cursor::new(compressed));
+    }
+}
@@ -23,4 +23,11 @@ pub struct JsonRPCRequest {
     /// JSON-RPC Notification requests are not yet supported, so this field is
     /// not optional.
     pub id: serde_json::Value,
+
+    /// Whether to report how long the request took server-side in the
+    /// response's `elapsed_ms` field.
+    ///
+    /// Optional: defaults to false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_elapsed_ms: Option<bool>,
 }
@@ -3,8 +3,24 @@
 //! JSON-RPC Responses from the Wallet API.
 //!
 //! API v2
+use crate::{
+    db::{gift_code::GiftCodeDbError, WalletDbError},
+    error::{ErrorCode, WalletServiceError, WalletTransactionBuilderError},
+    json_rpc::{audit_log::AuditLogError, wallet::MethodFilterError},
+    service::{
+        account::AccountServiceError, address::AddressServiceError, balance::BalanceServiceError,
+        confirmation_number::ConfirmationServiceError, gift_code::GiftCodeServiceError,
+        hardware_wallet::HardwareWalletServiceError, ledger::LedgerServiceError,
+        memo::MemoServiceError, payment_request::PaymentRequestServiceError,
+        receipt::ReceiptServiceError, transaction::TransactionServiceError,
+        transaction_log::TransactionLogServiceError, txo::TxoServiceError,
+        watcher::WatcherServiceError,
+    },
+    util::{b58::B58Error, encrypted_secrets::EncryptedSecretsError},
+};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::any::Any;
 use strum::Display;
 
 pub trait JsonCommandResponse {}
@@ -39,6 +55,12 @@ where
 
     /// The id of the Request object to which this response corresponds.
     pub id: serde_json::Value,
+
+    /// How long the server took to handle the request, in milliseconds.
+    ///
+    /// Optional: only returned when the request sets `include_elapsed_ms`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u64>,
 }
 
 /// A JSON RPC Error.
@@ -80,34 +102,140 @@ pub enum JsonRPCErrorCodes {
 }
 
 /// Helper method to format displaydoc errors in JSON RPC 2.0 format.
-pub fn format_error<T: std::fmt::Display + std::fmt::Debug>(e: T) -> JsonRPCError {
-    let data: serde_json::Value =
-        json!({"server_error": format!("{e:?}"), "details": e.to_string()});
+pub fn format_error<T: std::fmt::Display + std::fmt::Debug + 'static>(e: T) -> JsonRPCError {
     JsonRPCError::error {
         code: JsonRPCErrorCodes::InternalError as i32,
         message: JsonRPCErrorCodes::InternalError.to_string(),
-        data,
+        data: error_data(&e, "internal_error"),
     }
 }
 
 /// Helper method to format displaydoc invalid request errors in JSON RPC 2.0
 /// format.
-pub fn format_invalid_request_error<T: std::fmt::Display + std::fmt::Debug>(e: T) -> JsonRPCError {
-    let data: serde_json::Value =
-        json!({"server_error": format!("{e:?}"), "details": e.to_string()});
+pub fn format_invalid_request_error<T: std::fmt::Display + std::fmt::Debug + 'static>(
+    e: T,
+) -> JsonRPCError {
     JsonRPCError::error {
         code: JsonRPCErrorCodes::InvalidRequest as i32,
         message: JsonRPCErrorCodes::InvalidRequest.to_string(),
-        data,
+        data: error_data(&e, "invalid_request"),
     }
 }
 
-pub fn format_invalid_params_error<T: std::fmt::Display + std::fmt::Debug>(e: T) -> JsonRPCError {
-    let data: serde_json::Value =
-        json!({"server_error": format!("{e:?}"), "details": e.to_string()});
+pub fn format_invalid_params_error<T: std::fmt::Display + std::fmt::Debug + 'static>(
+    e: T,
+) -> JsonRPCError {
     JsonRPCError::error {
         code: JsonRPCErrorCodes::InvalidParams as i32,
         message: JsonRPCErrorCodes::InvalidParams.to_string(),
-        data,
+        data: error_data(&e, "invalid_params"),
+    }
+}
+
+/// Build the `data` field of a JSON RPC error.
+///
+/// Alongside the debug and display representations of the error, this includes
+/// an `error_code`: a stable, machine-readable string clients can branch on
+/// instead of parsing the English `details`.
+fn error_data<T: std::fmt::Display + std::fmt::Debug + 'static>(
+    e: &T,
+    default_code: &str,
+) -> serde_json::Value {
+    let error_code = error_code(e).unwrap_or(default_code);
+    json!({"server_error": format!("{e:?}"), "details": e.to_string(), "error_code": error_code})
+}
+
+/// Look up the error code of a wallet error, or None for errors which do not
+/// have one (e.g. a `String` or an error from another crate).
+fn error_code(e: &dyn Any) -> Option<&'static str> {
+    macro_rules! error_code_of {
+        ($($error:ty),* $(,)?) => {
+            $(
+                if let Some(e) = e.downcast_ref::<$error>() {
+                    return Some(e.error_code());
+                }
+            )*
+        };
+    }
+
+    error_code_of!(
+        WalletServiceError,
+        AccountServiceError,
+        AddressServiceError,
+        BalanceServiceError,
+        ConfirmationServiceError,
+        GiftCodeServiceError,
+        HardwareWalletServiceError,
+        LedgerServiceError,
+        MemoServiceError,
+        PaymentRequestServiceError,
+        ReceiptServiceError,
+        TransactionLogServiceError,
+        TransactionServiceError,
+        TxoServiceError,
+        WatcherServiceError,
+        WalletTransactionBuilderError,
+        WalletDbError,
+        GiftCodeDbError,
+        B58Error,
+        EncryptedSecretsError,
+        MethodFilterError,
+        AuditLogError,
+    );
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code_uses_innermost_variant() {
+        assert_eq!(
+            error_code(&WalletServiceError::AccountService(
+                AccountServiceError::Database(WalletDbError::AccountNotFound("a1b2".to_string()))
+            )),
+            Some("account_not_found")
+        );
+        assert_eq!(
+            error_code(&WalletServiceError::Database(
+                WalletDbError::WalletFunctionsDisabled
+            )),
+            Some("wallet_functions_disabled")
+        );
+        assert_eq!(
+            error_code(&TransactionServiceError::TransactionBuilder(
+                WalletTransactionBuilderError::InsufficientFunds("Not enough".to_string())
+            )),
+            Some("insufficient_funds")
+        );
+        assert_eq!(
+            error_code(&TransactionServiceError::Offline),
+            Some("offline")
+        );
+    }
+
+    #[test]
+    fn test_error_code_stops_at_foreign_errors() {
+        assert_eq!(
+            error_code(&WalletServiceError::HexDecode(hex::FromHexError::OddLength)),
+            Some("hex_decode")
+        );
+        assert_eq!(error_code(&hex::FromHexError::OddLength), None);
+        assert_eq!(error_code(&"Unknown method".to_string()), None);
+    }
+
+    #[test]
+    fn test_format_error_includes_error_code() {
+        let JsonRPCError::error { data, .. } = format_error("Unknown method");
+        assert_eq!(data.get("error_code").unwrap(), "internal_error");
+
+        let JsonRPCError::error { data, .. } = format_invalid_params_error("Bad param");
+        assert_eq!(data.get("error_code").unwrap(), "invalid_params");
+
+        let JsonRPCError::error { data, .. } =
+            format_error(WalletDbError::AccountNotFound("a1b2".to_string()));
+        assert_eq!(data.get("error_code").unwrap(), "account_not_found");
     }
 }
@@ -2,8 +2,11 @@
 
 //! JSON RPC 2.0 API specification for the Full Service wallet.
 
+pub mod audit_log;
+pub mod gzip;
 pub mod json_rpc_request;
 pub mod json_rpc_response;
+pub mod rate_limit;
 pub mod v1;
 pub mod v2;
 pub mod wallet;
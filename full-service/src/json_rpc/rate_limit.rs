@@ -0,0 +1,121 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! Rate limiting of wallet API requests, per API key.
+//!
+//! Each API key has a token bucket which holds up to one second's worth of
+//! requests, and refills at the configured rate. Each request takes a token
+//! from the bucket of the key it was made with, and is rejected with 429 Too
+//! Many Requests if the bucket is empty.
+
+use crate::json_rpc::wallet::API_KEY_HEADER;
+use rocket::{http::Status, outcome::Outcome, request::FromRequest, Request};
+use std::{collections::HashMap, sync::Mutex, time::Instant};
+
+/// The rate limiter, if one is configured, as managed by rocket.
+pub struct RateLimitState(pub Option<RateLimiter>);
+
+pub struct RateLimiter {
+    /// The rate at which each bucket refills, which is also its capacity.
+    requests_per_second: f64,
+
+    /// The bucket for each API key which has made a request.
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: u32) -> Self {
+        Self {
+            requests_per_second: requests_per_second as f64,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token from the bucket of an API key, returning false if the
+    /// bucket is empty.
+    pub fn try_acquire(&self, api_key: &str) -> bool {
+        self.try_acquire_at(api_key, Instant::now())
+    }
+
+    fn try_acquire_at(&self, api_key: &str, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock().expect("mutex poisoned");
+        let bucket = buckets
+            .entry(api_key.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: self.requests_per_second,
+                updated_at: now,
+            });
+
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.requests_per_second)
+            .min(self.requests_per_second);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Rejects requests made with an API key which has exceeded its rate limit.
+pub struct RateLimitGuard {}
+
+#[derive(Debug)]
+pub enum RateLimitError {
+    Exceeded,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RateLimitGuard {
+    type Error = RateLimitError;
+
+    async fn from_request(
+        req: &'r Request<'_>,
+    ) -> Outcome<Self, (rocket::http::Status, Self::Error), rocket::http::Status> {
+        if let Some(RateLimitState(Some(rate_limiter))) = req.rocket().state::<RateLimitState>() {
+            let api_key = req.headers().get_one(API_KEY_HEADER).unwrap_or_default();
+            if !rate_limiter.try_acquire(api_key) {
+                return Outcome::Error((Status::TooManyRequests, RateLimitError::Exceeded));
+            }
+        }
+        Outcome::Success(RateLimitGuard {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rate_limiter() {
+        let rate_limiter = RateLimiter::new(2);
+        let start = Instant::now();
+
+        // A bucket starts full, with one second's worth of requests.
+        assert!(rate_limiter.try_acquire_at("a", start));
+        assert!(rate_limiter.try_acquire_at("a", start));
+        assert!(!rate_limiter.try_acquire_at("a", start));
+
+        // Other keys have their own buckets.
+        assert!(rate_limiter.try_acquire_at("b", start));
+
+        // The bucket refills at the configured rate.
+        let later = start + Duration::from_millis(500);
+        assert!(rate_limiter.try_acquire_at("a", later));
+        assert!(!rate_limiter.try_acquire_at("a", later));
+
+        // But never holds more than one second's worth of requests.
+        let much_later = start + Duration::from_secs(60);
+        assert!(rate_limiter.try_acquire_at("a", much_later));
+        assert!(rate_limiter.try_acquire_at("a", much_later));
+        assert!(!rate_limiter.try_acquire_at("a", much_later));
+    }
+}
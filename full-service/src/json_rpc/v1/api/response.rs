@@ -90,6 +90,8 @@ pub enum JsonCommandResponse {
     get_account_status {
         account: Account,
         balance: Balance,
+        last_activity_block_index: Option<String>,
+        last_activity_timestamp: Option<String>,
     },
     get_address_for_account {
         address: Address,
@@ -118,6 +120,8 @@ pub enum JsonCommandResponse {
     },
     get_balance_for_account {
         balance: Balance,
+        last_activity_block_index: Option<String>,
+        last_activity_timestamp: Option<String>,
     },
     get_balance_for_address {
         balance: Balance,
@@ -1,7 +1,8 @@
 // Copyright (c) 2020-2021 MobileCoin Inc.
 
 use crate::{
-    config::NetworkConfig,
+    config::{NetworkConfig, PollInterval, TokenMetadataMap},
+    db::authenticated_sender_memo::SenderMemoFilter,
     json_rpc::{
         json_rpc_request::JsonRPCRequest,
         json_rpc_response::JsonRPCResponse,
@@ -9,7 +10,7 @@ use crate::{
             request::JsonCommandRequest, response::JsonCommandResponse, wallet::wallet_api_inner,
         },
     },
-    service::{t3_sync::T3Config, WalletService},
+    service::{block_info_cache::BlockInfoCache, t3_sync::T3Config, WalletService},
     test_utils::{
         get_resolver_factory, get_test_ledger, setup_peer_manager_and_network_state,
         WalletDbTestContext,
@@ -67,6 +68,7 @@ async fn test_wallet_api(
         error: None,
         jsonrpc: "2.0".to_string(),
         id: command.0.id,
+        elapsed_ms: None,
     };
 
     match wallet_api_inner(&state.service, JsonCommandRequest::try_from(&req)?).await {
@@ -119,8 +121,16 @@ pub fn create_test_setup(
         peer_manager,
         network_setup_config,
         network_state.clone(),
+        PollInterval::default(),
+        BlockInfoCache::default(),
+        None,
         get_resolver_factory(rng).unwrap(),
         false,
+        false,
+        0,
+        SenderMemoFilter::default(),
+        None,
+        TokenMetadataMap::default(),
         T3Config::default(),
         None,
         logger,
@@ -148,7 +158,7 @@ pub fn setup(
     let (rocket_instance, ledger_db, db_test_context, network_state) =
         create_test_setup(rng, logger);
 
-    let rocket = rocket_instance.manage(APIKeyState("".to_string()));
+    let rocket = rocket_instance.manage(APIKeyState::single("".to_string()));
     (
         Client::untracked(rocket).expect("valid rocket instance"),
         ledger_db,
@@ -170,7 +180,7 @@ pub fn setup_with_api_key(
     let (rocket_instance, ledger_db, db_test_context, network_state) =
         create_test_setup(rng, logger);
 
-    let rocket = rocket_instance.manage(APIKeyState(api_key));
+    let rocket = rocket_instance.manage(APIKeyState::single(api_key));
 
     (
         Client::untracked(rocket).expect("valid rocket instance"),
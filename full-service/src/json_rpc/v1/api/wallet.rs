@@ -1,11 +1,12 @@
 use crate::{
     db::{
-        account::AccountID,
+        account::{AccountID, AccountSortOrder},
         transaction_log::TransactionId,
         txo::{TxoID, TxoStatus},
     },
     json_rpc::{
         self,
+        audit_log::AuditLogState,
         json_rpc_request::JsonRPCRequest,
         json_rpc_response::{
             format_error, format_invalid_request_error, JsonRPCError, JsonRPCResponse,
@@ -29,7 +30,7 @@ use crate::{
             },
         },
         v2::models::amount::Amount,
-        wallet::{ApiKeyGuard, WalletState},
+        wallet::{ApiKeyGuard, MethodFilterState, WalletState},
     },
     service::{
         self,
@@ -61,7 +62,9 @@ use serde_json::Map;
 use std::{collections::HashMap, convert::TryFrom, iter::FromIterator};
 
 pub async fn generic_wallet_api<T, FPR>(
-    _api_key_guard: ApiKeyGuard,
+    api_key_guard: ApiKeyGuard,
+    method_filter: &rocket::State<MethodFilterState>,
+    audit_log: &rocket::State<AuditLogState>,
     state: &rocket::State<WalletState<T, FPR>>,
     command: Json<JsonRPCRequest>,
 ) -> Result<Json<JsonRPCResponse<JsonCommandResponse>>, String>
@@ -77,8 +80,17 @@ where
         error: None,
         jsonrpc: "2.0".to_string(),
         id: command.0.id,
+        elapsed_ms: None,
     };
 
+    if let Err(error) = method_filter
+        .check(&req.method)
+        .and_then(|()| api_key_guard.check(&req.method))
+    {
+        response.error = Some(format_invalid_request_error(error));
+        return Ok(Json(response));
+    }
+
     let request = match JsonCommandRequest::try_from(&req) {
         Ok(request) => request,
         Err(error) => {
@@ -87,6 +99,11 @@ where
         }
     };
 
+    if let Err(error) = audit_log.record_request(&req) {
+        response.error = Some(format_error(error));
+        return Ok(Json(response));
+    }
+
     match wallet_api_inner(&state.service, request).await {
         Ok(command_response) => {
             global_log::info!("Command executed successfully");
@@ -97,6 +114,7 @@ where
             response.error = Some(rpc_error);
         }
     };
+    audit_log.record_outcome(&req, response.result.as_ref(), response.error.as_ref());
 
     Ok(Json(response))
 }
@@ -182,6 +200,7 @@ where
                     },
                     None,
                     None, // Note: Not including spend_subaddress in V1 API
+                    None,
                 )
                 .await
                 .map_err(format_error)?;
@@ -301,6 +320,7 @@ where
                     },
                     None,
                     None, // Note: not including spend_subaddress in V1 API
+                    None,
                 )
                 .await
                 .map_err(format_error)?;
@@ -327,7 +347,10 @@ where
                         b58_encode_public_address(&payment_request.public_address)
                             .map_err(format_error)?;
                     b58_data.insert("public_address_b58".to_string(), public_address_b58);
-                    b58_data.insert("value".to_string(), payment_request.value.to_string());
+                    b58_data.insert(
+                        "value".to_string(),
+                        payment_request.value.unwrap_or_default().to_string(),
+                    );
                     b58_data.insert("memo".to_string(), payment_request.memo);
                 }
             }
@@ -468,8 +491,17 @@ where
                 &network_status,
             );
 
+            let (last_activity_block_index, last_activity_timestamp) = service
+                .get_account_last_activity(&account_id)
+                .map_err(format_error)?;
+
             let account = Account::new(account, next_subaddress_index).map_err(format_error)?;
-            JsonCommandResponse::get_account_status { account, balance }
+            JsonCommandResponse::get_account_status {
+                account,
+                balance,
+                last_activity_block_index: last_activity_block_index.map(|i| i.to_string()),
+                last_activity_timestamp: last_activity_timestamp.map(|t| t.to_string()),
+            }
         }
         JsonCommandRequest::get_address_for_account { account_id, index } => {
             let assigned_subaddress = service
@@ -510,7 +542,9 @@ where
             }
         }
         JsonCommandRequest::get_all_accounts => {
-            let accounts = service.list_accounts(None, None).map_err(format_error)?;
+            let accounts = service
+                .list_accounts(AccountSortOrder::default(), None, None)
+                .map_err(format_error)?;
             let json_accounts: Vec<(String, serde_json::Value)> = accounts
                 .iter()
                 .map(|a| {
@@ -698,12 +732,19 @@ where
             let balance_mob = balance_map.get(&Mob::ID).unwrap_or_default();
 
             let network_status = service.get_network_status().map_err(format_error)?;
+
+            let (last_activity_block_index, last_activity_timestamp) = service
+                .get_account_last_activity(&account_id)
+                .map_err(format_error)?;
+
             JsonCommandResponse::get_balance_for_account {
                 balance: Balance::new(
                     balance_mob,
                     account.next_block_index as u64,
                     &network_status,
                 ),
+                last_activity_block_index: last_activity_block_index.map(|i| i.to_string()),
+                last_activity_timestamp: last_activity_timestamp.map(|t| t.to_string()),
             }
         }
         JsonCommandRequest::get_balance_for_address { address } => {
@@ -1005,6 +1046,7 @@ where
             let account = service
                 .import_account(
                     mnemonic,
+                    None,
                     name,
                     fb,
                     ns,
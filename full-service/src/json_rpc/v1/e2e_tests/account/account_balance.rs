@@ -93,6 +93,9 @@ mod e2e_account {
                 .expect("Could not parse u64"),
             13
         );
+
+        // The account was last active when it received the txo, in the last block.
+        assert_eq!(result["last_activity_block_index"].as_str().unwrap(), "12");
     }
 
     #[test_with_logger]
@@ -37,6 +37,7 @@ impl From<&db::transaction_log::TxStatus> for TxStatus {
             db::transaction_log::TxStatus::Pending => TxStatus::Pending,
             db::transaction_log::TxStatus::Succeeded => TxStatus::Succeeded,
             db::transaction_log::TxStatus::Failed => TxStatus::Failed,
+            db::transaction_log::TxStatus::Conflicting => TxStatus::Failed,
         }
     }
 }
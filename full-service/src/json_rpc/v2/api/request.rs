@@ -2,16 +2,28 @@
 
 //! The JSON RPC 2.0 Requests to the Wallet API for Full Service.
 
-use crate::json_rpc::{
-    json_rpc_request::JsonRPCRequest,
-    v2::models::{
-        account_key::FogInfo, amount::Amount, receiver_receipt::ReceiverReceipt,
-        tx_proposal::TxProposal,
-    },
+use crate::{
+    json_rpc::{
+        json_rpc_request::JsonRPCRequest,
+        v2::{
+            api::v1_compat::translate_v1_request,
+            models::{
+                account::ImportAccountSpec,
+                account_key::FogInfo,
+                address::SubaddressMetadata,
+                amount::Amount,
+                api_schema::{ApiMethod, ApiParam},
+                receiver_receipt::ReceiverReceipt,
+                tx_proposal::TxProposal,
+            },
+        },
+    },
+    util::encrypted_secrets::EncryptedSecrets,
 };
 
 use mc_mobilecoind_json::data_types::JsonTxOut;
 use mc_transaction_signer::types::TxoSynced;
+use redact::{expose_secret, Secret};
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use strum::IntoEnumIterator;
@@ -25,12 +37,82 @@ pub fn help_str() -> String {
     help_str
 }
 
+/// Describe each request method and its parameters, derived from the
+/// serialized form of each request variant.
+///
+/// Parameters which serialize as null are optional. Their type is found by
+/// checking which kind of JSON value the request will accept in their place.
+pub fn api_schema() -> Vec<ApiMethod> {
+    JsonCommandRequest::iter()
+        .map(|request| {
+            let request = serde_json::to_value(&request).expect("Could not serialize request");
+            let method = request["method"].as_str().unwrap_or_default().to_string();
+
+            let params = match request.get("params").and_then(|params| params.as_object()) {
+                Some(params) => params
+                    .iter()
+                    .map(|(name, value)| match json_type(value) {
+                        Some(param_type) => ApiParam {
+                            name: name.clone(),
+                            param_type: Some(param_type.to_string()),
+                            required: true,
+                        },
+                        None => ApiParam {
+                            name: name.clone(),
+                            param_type: optional_param_type(&request, name),
+                            required: false,
+                        },
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+
+            ApiMethod { method, params }
+        })
+        .collect()
+}
+
+fn json_type(value: &serde_json::Value) -> Option<&'static str> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::Bool(_) => Some("boolean"),
+        serde_json::Value::Number(_) => Some("number"),
+        serde_json::Value::String(_) => Some("string"),
+        serde_json::Value::Array(_) => Some("array"),
+        serde_json::Value::Object(_) => Some("object"),
+    }
+}
+
+fn optional_param_type(request: &serde_json::Value, name: &str) -> Option<String> {
+    let candidates = [
+        serde_json::json!(""),
+        serde_json::json!(0),
+        serde_json::json!(false),
+        serde_json::json!([]),
+        serde_json::json!({}),
+    ];
+    candidates.into_iter().find_map(|candidate| {
+        let param_type = json_type(&candidate)?;
+        let mut request = request.clone();
+        request["params"][name] = candidate;
+        serde_json::from_value::<JsonCommandRequest>(request)
+            .ok()
+            .map(|_| param_type.to_string())
+    })
+}
+
 impl TryFrom<&JsonRPCRequest> for JsonCommandRequest {
     type Error = String;
 
     fn try_from(src: &JsonRPCRequest) -> Result<JsonCommandRequest, String> {
         let src_json: serde_json::Value = serde_json::json!(src);
-        serde_json::from_value(src_json).map_err(|e| format!("Could not get value {e:?}"))
+        serde_json::from_value(src_json).or_else(|e| {
+            // Fall back to translating v1 requests which have a v2 equivalent.
+            match translate_v1_request(src) {
+                Some(request) => request,
+                None => Err(format!("Could not get value {e:?}")),
+            }
+        })
     }
 }
 
@@ -39,10 +121,17 @@ impl TryFrom<&JsonRPCRequest> for JsonCommandRequest {
 #[serde(tag = "method", content = "params")]
 #[allow(non_camel_case_types)]
 pub enum JsonCommandRequest {
+    abandon_transaction {
+        tx_proposal: Option<TxProposal>,
+        reservation_id: Option<String>,
+    },
     assign_address_for_account {
         account_id: String,
         metadata: Option<String>,
     },
+    backfill_memos {
+        account_id: String,
+    },
     build_and_submit_transaction {
         account_id: String,
         addresses_and_amounts: Option<Vec<(String, Amount)>>,
@@ -58,6 +147,7 @@ pub enum JsonCommandRequest {
         sender_memo_credential_subaddress_index: Option<String>,
         payment_request_id: Option<String>,
         spend_subaddress: Option<String>,
+        device_id: Option<String>,
     },
     build_burn_transaction {
         account_id: String,
@@ -70,6 +160,7 @@ pub enum JsonCommandRequest {
         max_spendable_value: Option<String>,
         block_version: Option<String>,
         spend_subaddress: Option<String>,
+        device_id: Option<String>,
     },
     build_transaction {
         account_id: String,
@@ -77,6 +168,7 @@ pub enum JsonCommandRequest {
         recipient_public_address: Option<String>,
         amount: Option<Amount>,
         input_txo_ids: Option<Vec<String>>,
+        reservation_id: Option<String>,
         fee_value: Option<String>,
         fee_token_id: Option<String>,
         tombstone_block: Option<String>,
@@ -85,6 +177,9 @@ pub enum JsonCommandRequest {
         sender_memo_credential_subaddress_index: Option<String>,
         payment_request_id: Option<String>,
         spend_subaddress: Option<String>,
+        #[serde(default = "bool::default")] // default is false
+        send_all: bool,
+        device_id: Option<String>,
     },
     build_unsigned_burn_transaction {
         account_id: String,
@@ -111,6 +206,9 @@ pub enum JsonCommandRequest {
         block_version: Option<String>,
         spend_subaddress: Option<String>,
     },
+    cancel_hardware_wallet_signing {
+        account_id: String,
+    },
     check_b58_type {
         b58_code: String,
     },
@@ -118,6 +216,10 @@ pub enum JsonCommandRequest {
         address: String,
         receiver_receipt: ReceiverReceipt,
     },
+    check_receiver_receipts_status {
+        address: String,
+        receiver_receipts: Vec<ReceiverReceipt>,
+    },
     create_account {
         name: Option<String>,
         fog_info: Option<FogInfo>,
@@ -139,22 +241,39 @@ pub enum JsonCommandRequest {
     create_view_only_account_sync_request {
         account_id: String,
     },
+    derive_address_for_account {
+        account_id: String,
+        subaddress_index: String,
+    },
     export_account_secrets {
         account_id: String,
     },
+    export_encrypted_account_secrets {
+        account_id: String,
+        #[serde(serialize_with = "expose_secret")]
+        passphrase: Secret<String>,
+    },
+    get_account_lifetime_stats {
+        account_id: String,
+    },
     get_account_status {
         account_id: String,
     },
     get_accounts {
         offset: Option<u64>,
         limit: Option<u64>,
+        include_status: Option<bool>,
+        sort_by: Option<String>,
     },
     get_address_details {
         address: String,
     },
     get_address_for_account {
         account_id: String,
-        index: i64,
+        index: Option<i64>,
+    },
+    get_address_for_txo {
+        txo_id: String,
     },
     get_address_status {
         address: String,
@@ -166,7 +285,9 @@ pub enum JsonCommandRequest {
         account_id: Option<String>,
         offset: Option<u64>,
         limit: Option<u64>,
+        include_balances: Option<bool>,
     },
+    get_api_schema,
     get_balance {
         account_id: String,
     },
@@ -181,9 +302,19 @@ pub enum JsonCommandRequest {
     get_recent_blocks {
         limit: Option<usize>,
     },
+    get_change_txos {
+        account_id: String,
+        token_id: Option<String>,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    },
     get_confirmations {
         transaction_log_id: String,
     },
+    get_hardware_wallet_devices,
+    get_hardware_wallet_sync_progress {
+        account_id: String,
+    },
     get_mc_protocol_transaction {
         transaction_log_id: String,
     },
@@ -192,6 +323,12 @@ pub enum JsonCommandRequest {
     },
     get_network_status,
     get_token_metadata,
+    get_spent_key_images {
+        account_id: String,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    },
+    get_supported_token_ids,
     get_transaction_log {
         transaction_log_id: String,
     },
@@ -202,15 +339,27 @@ pub enum JsonCommandRequest {
         offset: Option<u64>,
         limit: Option<u64>,
     },
+    get_transaction_logs_for_txo {
+        txo_id: String,
+    },
+    get_transaction_proof_bundle {
+        transaction_log_id: String,
+    },
     get_txo_block_index {
         public_key: String,
     },
+    get_txo_confirmation_number {
+        txo_id: String,
+    },
     get_txo_membership_proofs {
         outputs: Vec<JsonTxOut>,
     },
     get_txo {
         txo_id: String,
     },
+    get_txo_history {
+        txo_id: String,
+    },
     get_txos {
         account_id: Option<String>,
         address: Option<String>,
@@ -218,10 +367,25 @@ pub enum JsonCommandRequest {
         token_id: Option<String>,
         min_received_block_index: Option<String>,
         max_received_block_index: Option<String>,
+        unspent_for_blocks: Option<String>,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    },
+    get_txos_missing_required_memo {
+        account_id: String,
         offset: Option<u64>,
         limit: Option<u64>,
     },
     get_wallet_status,
+    get_watcher_status,
+    import_account_from_encrypted_secrets {
+        encrypted_account_secrets: EncryptedSecrets,
+        #[serde(serialize_with = "expose_secret")]
+        passphrase: Secret<String>,
+        first_block_index: Option<String>,
+        next_subaddress_index: Option<String>,
+        fog_info: Option<FogInfo>,
+    },
     import_account_from_legacy_root_entropy {
         entropy: String,
         name: Option<String>,
@@ -233,6 +397,7 @@ pub enum JsonCommandRequest {
     },
     import_account {
         mnemonic: String,
+        account_index: Option<String>,
         name: Option<String>,
         first_block_index: Option<String>,
         next_subaddress_index: Option<String>,
@@ -240,25 +405,46 @@ pub enum JsonCommandRequest {
         #[serde(default = "bool::default")] // default is false
         require_spend_subaddress: bool,
     },
+    import_accounts {
+        accounts: Vec<ImportAccountSpec>,
+    },
     import_view_only_account {
         view_private_key: String,
         spend_public_key: String,
+        account_index: Option<String>,
         name: Option<String>,
         first_block_index: Option<String>,
         next_subaddress_index: Option<String>,
         #[serde(default = "bool::default")] // default is false
         require_spend_subaddress: bool,
+        subaddresses: Option<Vec<SubaddressMetadata>>,
     },
     import_view_only_account_from_hardware_wallet {
+        account_index: Option<String>,
         name: Option<String>,
         first_block_index: Option<String>,
         fog_info: Option<FogInfo>,
         #[serde(default = "bool::default")] // default is false
         require_spend_subaddress: bool,
+        device_id: Option<String>,
+    },
+    preview_spendable_selection {
+        account_id: String,
+        amount: Amount,
+        fee_value: Option<String>,
+        max_spendable_value: Option<String>,
+        spend_subaddress: Option<String>,
     },
     remove_account {
         account_id: String,
     },
+    reserve_inputs {
+        account_id: String,
+        amount: Amount,
+        fee_value: Option<String>,
+        max_spendable_value: Option<String>,
+        spend_subaddress: Option<String>,
+    },
     resync_account {
         account_id: String,
     },
@@ -266,13 +452,41 @@ pub enum JsonCommandRequest {
         num_mixins: u64,
         excluded_outputs: Vec<JsonTxOut>,
     },
+    scan_address_for_txos {
+        address: String,
+        first_block_index: String,
+        last_block_index: Option<String>,
+    },
     search_ledger {
         query: String,
     },
+    set_default_receive_subaddress {
+        account_id: String,
+        subaddress_index: i64,
+    },
+    set_log_level {
+        module: Option<String>,
+        level: String,
+    },
+    set_poll_interval {
+        poll_interval_ms: String,
+    },
+    set_require_sender_memo {
+        account_id: String,
+        require_sender_memo: bool,
+    },
     set_require_spend_subaddress {
         account_id: String,
         require_spend_subaddress: bool,
     },
+    set_tx_source_urls {
+        tx_source_urls: Vec<String>,
+    },
+    submit_raw_tx {
+        tx_bytes_hex: String,
+        comment: Option<String>,
+        account_id: Option<String>,
+    },
     submit_transaction {
         tx_proposal: TxProposal,
         comment: Option<String>,
@@ -281,6 +495,16 @@ pub enum JsonCommandRequest {
     sync_view_only_account {
         account_id: String,
         synced_txos: Option<Vec<TxoSynced>>,
+        device_id: Option<String>,
+    },
+    /// Lowering `first_block_index` rescans the account from the new first
+    /// block through the current tip, so that TXOs found in the earlier
+    /// blocks are marked spent if they were spent in later blocks. Raising it
+    /// requires `force`.
+    update_account_first_block_index {
+        account_id: String,
+        first_block_index: String,
+        force: Option<bool>,
     },
     update_account_name {
         account_id: String,
@@ -295,6 +519,13 @@ pub enum JsonCommandRequest {
         txo_id: String,
         sender_address: String,
     },
+    verify_account_backup {
+        account_id: String,
+        mnemonic: Option<String>,
+        entropy: Option<String>,
+        account_index: Option<String>,
+        fog_info: Option<FogInfo>,
+    },
     verify_address {
         address: String,
     },
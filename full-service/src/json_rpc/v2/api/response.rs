@@ -9,31 +9,36 @@ use crate::{
         json_rpc_request::JsonRPCRequest,
         json_rpc_response::JsonCommandResponse as JsonCommandResponseTrait,
         v2::models::{
-            account::{Account, AccountMap},
+            account::{Account, AccountMap, ImportAccountResult},
             account_secrets::AccountSecrets,
-            address::{Address, AddressMap},
-            balance::BalanceMap,
+            address::{Address, AddressMap, ScannedTxo},
+            api_schema::ApiMethod,
+            balance::{
+                AccountStatusMap, AddressBalanceMap, BalanceMap, LifetimeStatsMap,
+                PendingOutgoingMap,
+            },
             block::{Block, BlockContents},
             confirmation_number::Confirmation,
+            hardware_wallet::{HardwareWalletDevice, HardwareWalletSyncProgress},
             ledger::LedgerSearchResult,
             network_status::NetworkStatus,
             public_address::PublicAddress,
-            receiver_receipt::ReceiverReceipt,
+            receiver_receipt::{ReceiverReceipt, ReceiverReceiptStatus},
             transaction_log::TransactionLog,
             tx_proposal::{TxProposal, UnsignedTxProposal},
-            txo::Txo,
+            txo::{SpendableSelection, SpentKeyImage, Txo, TxoEvent},
             wallet_status::WalletStatus,
-            watcher::WatcherBlockInfo,
+            watcher::{WatcherBlockInfo, WatcherStatus},
         },
     },
     service::receipt::ReceiptTransactionStatus,
-    util::b58::PrintableWrapperType,
+    util::{b58::PrintableWrapperType, encrypted_secrets::EncryptedSecrets},
 };
 use mc_mobilecoind_json::data_types::{JsonTx, JsonTxOut, JsonTxOutMembershipProof};
 use mc_transaction_signer::types::TxoSyncReq;
 use serde::{Deserialize, Serialize};
 use serde_json::Map;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Responses from the Full Service Wallet.
 #[derive(Deserialize, Serialize, Debug)]
@@ -41,9 +46,13 @@ use std::collections::HashMap;
 #[allow(non_camel_case_types)]
 #[allow(clippy::large_enum_variant)]
 pub enum JsonCommandResponse {
+    abandon_transaction,
     assign_address_for_account {
         address: Address,
     },
+    backfill_memos {
+        num_txos: String,
+    },
     build_and_submit_transaction {
         transaction_log: TransactionLog,
         tx_proposal: TxProposal,
@@ -55,6 +64,8 @@ pub enum JsonCommandResponse {
     build_transaction {
         tx_proposal: TxProposal,
         transaction_log_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        send_all_value: Option<String>,
     },
     build_unsigned_burn_transaction {
         account_id: String,
@@ -64,6 +75,9 @@ pub enum JsonCommandResponse {
         account_id: String,
         unsigned_tx_proposal: UnsignedTxProposal,
     },
+    cancel_hardware_wallet_signing {
+        cancelled: bool,
+    },
     check_b58_type {
         b58_type: PrintableWrapperType,
         data: HashMap<String, String>,
@@ -72,6 +86,9 @@ pub enum JsonCommandResponse {
         receipt_transaction_status: ReceiptTransactionStatus,
         txo: Option<Txo>,
     },
+    check_receiver_receipts_status {
+        receipt_statuses: Vec<ReceiverReceiptStatus>,
+    },
     create_account {
         account: Account,
     },
@@ -87,18 +104,39 @@ pub enum JsonCommandResponse {
     create_view_only_account_sync_request {
         txo_sync_request: TxoSyncReq,
     },
+    derive_address_for_account {
+        public_address_b58: String,
+    },
     export_account_secrets {
         account_secrets: AccountSecrets,
     },
+    export_encrypted_account_secrets {
+        encrypted_account_secrets: EncryptedSecrets,
+    },
+    get_account_lifetime_stats {
+        lifetime_stats_per_token: LifetimeStatsMap,
+    },
     get_account_status {
         account: Account,
         network_block_height: String,
         local_block_height: String,
+        sync_blocks_per_second: Option<String>,
+        sync_seconds_remaining: Option<String>,
+        last_activity_block_index: Option<String>,
+        last_activity_timestamp: Option<String>,
         balance_per_token: BalanceMap,
+        pending_outgoing: PendingOutgoingMap,
     },
     get_accounts {
         account_ids: Vec<String>,
         account_map: AccountMap,
+        total_count: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        network_block_height: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        local_block_height: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        status_map: Option<AccountStatusMap>,
     },
     get_address_details {
         details: PublicAddress,
@@ -110,9 +148,14 @@ pub enum JsonCommandResponse {
     get_address_for_account {
         address: Address,
     },
+    get_address_for_txo {
+        address: Address,
+    },
     get_addresses {
         public_addresses: Vec<String>,
         address_map: AddressMap,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        address_balance_map: Option<AddressBalanceMap>,
     },
     get_address_status {
         address: Address,
@@ -121,6 +164,9 @@ pub enum JsonCommandResponse {
         local_block_height: String,
         balance_per_token: BalanceMap,
     },
+    get_api_schema {
+        methods: Vec<ApiMethod>,
+    },
     get_block {
         block: Block,
         block_contents: BlockContents,
@@ -137,9 +183,19 @@ pub enum JsonCommandResponse {
         watcher_infos: Vec<Option<WatcherBlockInfo>>,
         network_status: NetworkStatus,
     },
+    get_change_txos {
+        txo_ids: Vec<String>,
+        txo_map: Map<String, serde_json::Value>,
+    },
     get_confirmations {
         confirmations: Vec<Confirmation>,
     },
+    get_hardware_wallet_devices {
+        devices: Vec<HardwareWalletDevice>,
+    },
+    get_hardware_wallet_sync_progress {
+        sync_progress: Option<HardwareWalletSyncProgress>,
+    },
     get_mc_protocol_transaction {
         transaction: JsonTx,
     },
@@ -153,6 +209,12 @@ pub enum JsonCommandResponse {
         verified: bool,
         metadata: String,
     },
+    get_spent_key_images {
+        spent_key_images: Vec<SpentKeyImage>,
+    },
+    get_supported_token_ids {
+        token_ids: Vec<String>,
+    },
     get_transaction_log {
         transaction_log: TransactionLog,
     },
@@ -160,12 +222,27 @@ pub enum JsonCommandResponse {
         transaction_log_ids: Vec<String>,
         transaction_log_map: Map<String, serde_json::Value>,
     },
+    get_transaction_logs_for_txo {
+        transaction_log_ids: Vec<String>,
+        transaction_log_map: Map<String, serde_json::Value>,
+    },
+    get_transaction_proof_bundle {
+        transaction_log: TransactionLog,
+        receiver_receipts: Vec<ReceiverReceipt>,
+        confirmations: Vec<Confirmation>,
+    },
     get_txo {
         txo: Txo,
     },
     get_txo_block_index {
         block_index: String,
     },
+    get_txo_confirmation_number {
+        confirmation: Option<String>,
+    },
+    get_txo_history {
+        events: Vec<TxoEvent>,
+    },
     get_txos {
         txo_ids: Vec<String>,
         txo_map: Map<String, serde_json::Value>,
@@ -174,12 +251,25 @@ pub enum JsonCommandResponse {
         outputs: Vec<JsonTxOut>,
         membership_proofs: Vec<JsonTxOutMembershipProof>,
     },
+    get_txos_missing_required_memo {
+        txo_ids: Vec<String>,
+        txo_map: Map<String, serde_json::Value>,
+    },
     get_wallet_status {
         wallet_status: WalletStatus,
     },
+    get_watcher_status {
+        watcher_status: Option<WatcherStatus>,
+    },
     import_account {
         account: Account,
     },
+    import_accounts {
+        results: Vec<ImportAccountResult>,
+    },
+    import_account_from_encrypted_secrets {
+        account: Account,
+    },
     import_account_from_legacy_root_entropy {
         account: Account,
     },
@@ -189,24 +279,56 @@ pub enum JsonCommandResponse {
     import_view_only_account_from_hardware_wallet {
         account: Account,
     },
+    preview_spendable_selection {
+        spendable_selection: SpendableSelection,
+    },
     remove_account {
         removed: bool,
     },
+    reserve_inputs {
+        reservation_id: String,
+        spendable_selection: SpendableSelection,
+    },
     resync_account,
     sample_mixins {
         mixins: Vec<JsonTxOut>,
         membership_proofs: Vec<JsonTxOutMembershipProof>,
     },
+    scan_address_for_txos {
+        txos: Vec<ScannedTxo>,
+    },
     search_ledger {
         results: Vec<LedgerSearchResult>,
     },
+    set_default_receive_subaddress {
+        account: Account,
+    },
+    set_log_level {
+        default_level: String,
+        module_levels: BTreeMap<String, String>,
+    },
+    set_poll_interval {
+        poll_interval_ms: String,
+    },
+    set_require_sender_memo {
+        account: Account,
+    },
     set_require_spend_subaddress {
         account: Account,
     },
+    set_tx_source_urls {
+        tx_source_urls: Vec<String>,
+    },
+    submit_raw_tx {
+        transaction_log: Option<TransactionLog>,
+    },
     submit_transaction {
         transaction_log: Option<TransactionLog>,
     },
     sync_view_only_account,
+    update_account_first_block_index {
+        account: Account,
+    },
     update_account_name {
         account: Account,
     },
@@ -216,6 +338,9 @@ pub enum JsonCommandResponse {
     validate_sender_memo {
         validated: bool,
     },
+    verify_account_backup {
+        verified: bool,
+    },
     verify_address {
         verified: bool,
         address_hash: Option<String>,
@@ -1,15 +1,17 @@
 // Copyright (c) 2020-2021 MobileCoin Inc.
 
 use crate::{
-    config::NetworkConfig,
+    config::{NetworkConfig, PollInterval, TokenMetadataMap},
+    db::authenticated_sender_memo::SenderMemoFilter,
     json_rpc::{
+        gzip::{Gzip, GzipJson},
         json_rpc_request::JsonRPCRequest,
         json_rpc_response::JsonRPCResponse,
         v2::api::{
             request::JsonCommandRequest, response::JsonCommandResponse, wallet::wallet_api_inner,
         },
     },
-    service::{t3_sync::T3Config, WalletService},
+    service::{block_info_cache::BlockInfoCache, t3_sync::T3Config, WalletService},
     test_utils::{
         get_resolver_factory, get_test_ledger, setup_peer_manager_and_network_state,
         WalletDbTestContext,
@@ -47,7 +49,7 @@ use std::{
         atomic::{AtomicUsize, Ordering::SeqCst},
         Arc, RwLock,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 pub fn get_free_port() -> u16 {
@@ -65,8 +67,9 @@ pub struct TestWalletState {
 async fn test_wallet_api(
     _guard: ApiKeyGuard,
     state: &rocket::State<TestWalletState>,
-    command: Json<JsonRPCRequest>,
+    command: GzipJson<JsonRPCRequest>,
 ) -> Result<Json<JsonRPCResponse<JsonCommandResponse>>, String> {
+    let command = command.0;
     let req: JsonRPCRequest = command.0.clone();
 
     let mut response = JsonRPCResponse {
@@ -75,8 +78,10 @@ async fn test_wallet_api(
         error: None,
         jsonrpc: "2.0".to_string(),
         id: command.0.id,
+        elapsed_ms: None,
     };
 
+    let start = Instant::now();
     match wallet_api_inner(&state.service, JsonCommandRequest::try_from(&req)?).await {
         Ok(command_response) => {
             response.result = Some(command_response);
@@ -85,12 +90,16 @@ async fn test_wallet_api(
             response.error = Some(rpc_error);
         }
     };
+    if req.include_elapsed_ms == Some(true) {
+        response.elapsed_ms = Some(start.elapsed().as_millis() as u64);
+    }
 
     Ok(Json(response))
 }
 
 pub fn test_rocket(rocket_config: rocket::Config, state: TestWalletState) -> rocket::Rocket<Build> {
     rocket::custom(rocket_config)
+        .attach(Gzip)
         .mount("/", routes![test_wallet_api])
         .manage(state)
 }
@@ -177,8 +186,16 @@ pub fn create_test_setup(
         peer_manager,
         network_setup_config,
         network_state.clone(),
+        PollInterval::default(),
+        BlockInfoCache::default(),
+        None,
         get_resolver_factory(rng).unwrap(),
         false,
+        false,
+        0,
+        SenderMemoFilter::default(),
+        None,
+        TokenMetadataMap::default(),
         T3Config::default(),
         webhook_config,
         logger,
@@ -206,7 +223,7 @@ pub fn setup(
     let (rocket_instance, ledger_db, db_test_context, network_state) =
         create_test_setup(rng, true, false, None, logger);
 
-    let rocket = rocket_instance.manage(APIKeyState("".to_string()));
+    let rocket = rocket_instance.manage(APIKeyState::single("".to_string()));
     (
         Client::untracked(rocket).expect("valid rocket instance"),
         ledger_db,
@@ -228,7 +245,7 @@ pub fn setup_with_webhook(
     let (rocket_instance, ledger_db, db_test_context, network_state) =
         create_test_setup(rng, true, false, Some(webhook_config), logger);
 
-    let rocket = rocket_instance.manage(APIKeyState("".to_string()));
+    let rocket = rocket_instance.manage(APIKeyState::single("".to_string()));
     (
         Client::untracked(rocket).expect("valid rocket instance"),
         ledger_db,
@@ -249,7 +266,7 @@ pub fn setup_with_watcher(
     let (rocket_instance, ledger_db, db_test_context, network_state) =
         create_test_setup(rng, true, true, None, logger);
 
-    let rocket = rocket_instance.manage(APIKeyState("".to_string()));
+    let rocket = rocket_instance.manage(APIKeyState::single("".to_string()));
     (
         Client::untracked(rocket).expect("valid rocket instance"),
         ledger_db,
@@ -270,7 +287,7 @@ pub fn setup_no_wallet_db(
     let (rocket_instance, ledger_db, db_test_context, network_state) =
         create_test_setup(rng, false, false, None, logger);
 
-    let rocket = rocket_instance.manage(APIKeyState("".to_string()));
+    let rocket = rocket_instance.manage(APIKeyState::single("".to_string()));
     (
         Client::untracked(rocket).expect("valid rocket instance"),
         ledger_db,
@@ -292,7 +309,7 @@ pub fn setup_with_api_key(
     let (rocket_instance, ledger_db, db_test_context, network_state) =
         create_test_setup(rng, true, false, None, logger);
 
-    let rocket = rocket_instance.manage(APIKeyState(api_key));
+    let rocket = rocket_instance.manage(APIKeyState::single(api_key));
 
     (
         Client::untracked(rocket).expect("valid rocket instance"),
@@ -0,0 +1,291 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! Translation of v1 requests to their v2 equivalents.
+//!
+//! Requests to the v2 API which do not parse as v2 requests are parsed as v1
+//! requests and translated here, so that v1 clients can move to the v2
+//! endpoint one method at a time. Only methods with a one-to-one v2 equivalent
+//! are translated. Methods whose v2 replacement takes different inputs or has
+//! different semantics, such as building and submitting transactions, gift
+//! codes, and receiver receipts, are unsupported and must be rewritten
+//! against the v2 API.
+
+use crate::json_rpc::{
+    json_rpc_request::JsonRPCRequest,
+    v1::api::request::JsonCommandRequest as JsonCommandRequestV1,
+    v2::{
+        api::request::JsonCommandRequest,
+        models::{account_key::FogInfo, amount::Amount},
+    },
+};
+use mc_transaction_core::{tokens::Mob, Token};
+use std::convert::TryFrom;
+
+/// Parse a request in the v1 format and translate it to v2.
+///
+/// Returns None if the request is not a v1 request. A request with params
+/// which the v1 method does not take is not treated as a v1 request, so that a
+/// malformed v2 request is not reinterpreted as a v1 request which ignores
+/// some of its params.
+pub fn translate_v1_request(src: &JsonRPCRequest) -> Option<Result<JsonCommandRequest, String>> {
+    let request = JsonCommandRequestV1::try_from(src).ok()?;
+
+    if let Some(params) = src.params.as_ref().and_then(|params| params.as_object()) {
+        let v1_params = serde_json::to_value(&request).ok()?["params"].clone();
+        if params.keys().any(|name| v1_params.get(name).is_none()) {
+            return None;
+        }
+    }
+
+    Some(JsonCommandRequest::try_from(request))
+}
+
+impl TryFrom<JsonCommandRequestV1> for JsonCommandRequest {
+    type Error = String;
+
+    fn try_from(src: JsonCommandRequestV1) -> Result<JsonCommandRequest, String> {
+        let request = match src {
+            JsonCommandRequestV1::assign_address_for_account {
+                account_id,
+                metadata,
+            } => JsonCommandRequest::assign_address_for_account {
+                account_id,
+                metadata,
+            },
+            JsonCommandRequestV1::check_b58_type { b58_code } => {
+                JsonCommandRequest::check_b58_type { b58_code }
+            }
+            JsonCommandRequestV1::create_account {
+                name,
+                fog_report_url,
+                fog_report_id,
+                fog_authority_spki,
+            } => JsonCommandRequest::create_account {
+                name,
+                fog_info: fog_info(fog_report_url, fog_report_id, fog_authority_spki),
+                require_spend_subaddress: false,
+            },
+            JsonCommandRequestV1::create_payment_request {
+                account_id,
+                subaddress_index,
+                amount_pmob,
+                memo,
+            } => JsonCommandRequest::create_payment_request {
+                account_id,
+                subaddress_index,
+                amount: Amount {
+                    value: amount_pmob.into(),
+                    token_id: Mob::ID.to_string().into(),
+                },
+                memo,
+            },
+            JsonCommandRequestV1::export_account_secrets { account_id } => {
+                JsonCommandRequest::export_account_secrets { account_id }
+            }
+            // The v2 account status includes the account.
+            JsonCommandRequestV1::get_account { account_id }
+            | JsonCommandRequestV1::get_account_status { account_id } => {
+                JsonCommandRequest::get_account_status { account_id }
+            }
+            JsonCommandRequestV1::get_address_for_account { account_id, index } => {
+                JsonCommandRequest::get_address_for_account {
+                    account_id,
+                    index: Some(index),
+                }
+            }
+            JsonCommandRequestV1::get_addresses_for_account {
+                account_id,
+                offset,
+                limit,
+            } => JsonCommandRequest::get_addresses {
+                account_id: Some(account_id),
+                offset: parse_page_param(offset)?,
+                limit: parse_page_param(limit)?,
+                include_balances: None,
+            },
+            JsonCommandRequestV1::get_all_accounts => JsonCommandRequest::get_accounts {
+                offset: None,
+                limit: None,
+                include_status: None,
+                sort_by: None,
+            },
+            JsonCommandRequestV1::get_all_txos_for_address { address } => {
+                JsonCommandRequest::get_txos {
+                    account_id: None,
+                    address: Some(address),
+                    status: None,
+                    token_id: Some(Mob::ID.to_string()),
+                    min_received_block_index: None,
+                    max_received_block_index: None,
+                    unspent_for_blocks: None,
+                    offset: None,
+                    limit: None,
+                }
+            }
+            JsonCommandRequestV1::get_balance_for_account { account_id } => {
+                JsonCommandRequest::get_balance { account_id }
+            }
+            JsonCommandRequestV1::get_balance_for_address { address } => {
+                JsonCommandRequest::get_address_status { address }
+            }
+            JsonCommandRequestV1::get_block { block_index } => JsonCommandRequest::get_block {
+                block_index: Some(block_index),
+                txo_public_key: None,
+            },
+            JsonCommandRequestV1::get_confirmations { transaction_log_id } => {
+                JsonCommandRequest::get_confirmations { transaction_log_id }
+            }
+            JsonCommandRequestV1::get_mc_protocol_transaction { transaction_log_id } => {
+                JsonCommandRequest::get_mc_protocol_transaction { transaction_log_id }
+            }
+            JsonCommandRequestV1::get_mc_protocol_txo { txo_id } => {
+                JsonCommandRequest::get_mc_protocol_txo { txo_id }
+            }
+            JsonCommandRequestV1::get_network_status => JsonCommandRequest::get_network_status,
+            JsonCommandRequestV1::get_transaction_log { transaction_log_id } => {
+                JsonCommandRequest::get_transaction_log { transaction_log_id }
+            }
+            JsonCommandRequestV1::get_transaction_logs_for_account {
+                account_id,
+                offset,
+                limit,
+                min_block_index,
+                max_block_index,
+            } => JsonCommandRequest::get_transaction_logs {
+                account_id: Some(account_id),
+                min_block_index,
+                max_block_index,
+                offset: parse_page_param(offset)?,
+                limit: parse_page_param(limit)?,
+            },
+            JsonCommandRequestV1::get_txo { txo_id } => JsonCommandRequest::get_txo { txo_id },
+            JsonCommandRequestV1::get_txos_for_account {
+                account_id,
+                status,
+                offset,
+                limit,
+            } => JsonCommandRequest::get_txos {
+                account_id: Some(account_id),
+                address: None,
+                status,
+                token_id: Some(Mob::ID.to_string()),
+                min_received_block_index: None,
+                max_received_block_index: None,
+                unspent_for_blocks: None,
+                offset: parse_page_param(offset)?,
+                limit: parse_page_param(limit)?,
+            },
+            JsonCommandRequestV1::get_wallet_status => JsonCommandRequest::get_wallet_status,
+            JsonCommandRequestV1::import_account {
+                mnemonic,
+                name,
+                first_block_index,
+                next_subaddress_index,
+                fog_report_url,
+                fog_report_id,
+                fog_authority_spki,
+            } => JsonCommandRequest::import_account {
+                mnemonic,
+                account_index: None,
+                name,
+                first_block_index,
+                next_subaddress_index,
+                fog_info: fog_info(fog_report_url, fog_report_id, fog_authority_spki),
+                require_spend_subaddress: false,
+            },
+            JsonCommandRequestV1::import_account_from_legacy_root_entropy {
+                entropy,
+                name,
+                first_block_index,
+                next_subaddress_index,
+                fog_report_url,
+                fog_report_id,
+                fog_authority_spki,
+            } => JsonCommandRequest::import_account_from_legacy_root_entropy {
+                entropy,
+                name,
+                first_block_index,
+                next_subaddress_index,
+                fog_info: fog_info(fog_report_url, fog_report_id, fog_authority_spki),
+                require_spend_subaddress: false,
+            },
+            JsonCommandRequestV1::remove_account { account_id } => {
+                JsonCommandRequest::remove_account { account_id }
+            }
+            JsonCommandRequestV1::update_account_name { account_id, name } => {
+                JsonCommandRequest::update_account_name { account_id, name }
+            }
+            JsonCommandRequestV1::validate_confirmation {
+                account_id,
+                txo_id,
+                confirmation,
+            } => JsonCommandRequest::validate_confirmation {
+                account_id,
+                txo_id,
+                confirmation,
+            },
+            JsonCommandRequestV1::verify_address { address } => {
+                JsonCommandRequest::verify_address { address }
+            }
+            JsonCommandRequestV1::version => JsonCommandRequest::version,
+
+            // Unsupported: v2 transactions are built from amounts with token ids, and
+            // their proposals are not compatible with v1 proposals.
+            JsonCommandRequestV1::build_and_submit_transaction { .. }
+            | JsonCommandRequestV1::build_split_txo_transaction { .. }
+            | JsonCommandRequestV1::build_transaction { .. }
+            | JsonCommandRequestV1::create_receiver_receipts { .. }
+            | JsonCommandRequestV1::check_receiver_receipt_status { .. }
+            | JsonCommandRequestV1::submit_transaction { .. }
+            // Unsupported: v2 has no gift codes.
+            | JsonCommandRequestV1::build_gift_code { .. }
+            | JsonCommandRequestV1::check_gift_code_status { .. }
+            | JsonCommandRequestV1::claim_gift_code { .. }
+            | JsonCommandRequestV1::get_all_gift_codes
+            | JsonCommandRequestV1::get_gift_code { .. }
+            | JsonCommandRequestV1::remove_gift_code { .. }
+            | JsonCommandRequestV1::submit_gift_code { .. }
+            // Unsupported: v1 mixes received txos into these transaction logs.
+            | JsonCommandRequestV1::get_all_transaction_logs_for_block { .. }
+            | JsonCommandRequestV1::get_all_transaction_logs_ordered_by_block => {
+                return Err(format!(
+                    "v1 method {} has no v2 equivalent, please use the v2 API",
+                    method_name(&src)
+                ))
+            }
+        };
+
+        Ok(request)
+    }
+}
+
+/// The method name of a v1 request, as sent by clients.
+fn method_name(request: &JsonCommandRequestV1) -> String {
+    serde_json::to_value(request)
+        .ok()
+        .and_then(|value| value["method"].as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn fog_info(
+    report_url: Option<String>,
+    report_id: Option<String>,
+    authority_spki: Option<String>,
+) -> Option<FogInfo> {
+    if report_url.is_none() && authority_spki.is_none() {
+        return None;
+    }
+    Some(FogInfo {
+        report_url: report_url.unwrap_or_default(),
+        report_id,
+        authority_spki: authority_spki.unwrap_or_default(),
+    })
+}
+
+/// v1 pagination params are strings, while v2 params are numbers.
+fn parse_page_param(param: Option<String>) -> Result<Option<u64>, String> {
+    param
+        .map(|p| p.parse::<u64>())
+        .transpose()
+        .map_err(|e| format!("Could not parse pagination param: {e}"))
+}
@@ -1,42 +1,53 @@
 use crate::{
     db::{
-        account::{AccountID, AccountModel},
+        account::{AccountID, AccountModel, AccountSortOrder},
         transaction_log::TransactionId,
         txo::{TxoID, TxoStatus},
     },
     json_rpc::{
+        audit_log::AuditLogState,
         json_rpc_request::JsonRPCRequest,
         json_rpc_response::{
             format_error, format_invalid_params_error, format_invalid_request_error, JsonRPCError,
             JsonRPCResponse,
         },
         v2::{
-            api::{request::JsonCommandRequest, response::JsonCommandResponse},
+            api::{
+                request::{api_schema, JsonCommandRequest},
+                response::JsonCommandResponse,
+            },
             models::{
-                account::{Account, AccountMap},
+                account::{Account, AccountMap, ImportAccountResult},
+                account_key::FogInfo,
                 account_secrets::AccountSecrets,
                 address::{Address, AddressMap},
-                balance::{Balance, BalanceMap},
+                balance::{
+                    AccountStatus, AccountStatusMap, AddressBalance, AddressBalanceMap, Balance,
+                    BalanceMap, LifetimeStats, LifetimeStatsMap, PendingOutgoing,
+                    PendingOutgoingMap,
+                },
                 block::{Block, BlockContents},
                 confirmation_number::Confirmation,
+                hardware_wallet::{HardwareWalletDevice, HardwareWalletSyncProgress},
                 network_status::NetworkStatus,
                 public_address::PublicAddress,
-                receiver_receipt::ReceiverReceipt,
+                receiver_receipt::{ReceiverReceipt, ReceiverReceiptStatus},
                 transaction_log::TransactionLog,
                 tx_proposal::{TxProposal as TxProposalJSON, UnsignedTxProposal},
-                txo::Txo,
+                txo::{SpendableSelection, SpentKeyImage, Txo, TxoEvent},
                 wallet_status::WalletStatus,
             },
         },
-        wallet::{ApiKeyGuard, WalletState},
+        wallet::{ApiKeyGuard, MethodFilterState, WalletState},
     },
+    log_filter::LOG_FILTER,
     service::{
         self,
-        account::AccountService,
+        account::{AccountBackup, AccountImport, AccountService},
         address::AddressService,
         balance::BalanceService,
         confirmation_number::ConfirmationService,
-        hardware_wallet::sync_txos,
+        hardware_wallet::{cancel_signing, get_sync_progress, list_devices, sync_txos},
         ledger::LedgerService,
         memo::MemoService,
         models::tx_proposal::TxProposal,
@@ -49,9 +60,12 @@ use crate::{
         watcher::WatcherService,
         WalletService,
     },
-    util::b58::{
-        b58_decode_payment_request, b58_encode_public_address, b58_printable_wrapper_type,
-        PrintableWrapperType,
+    util::{
+        b58::{
+            b58_decode_payment_request, b58_encode_public_address, b58_printable_wrapper_type,
+            PrintableWrapperType,
+        },
+        encrypted_secrets::EncryptedSecrets,
     },
 };
 use mc_account_keys::{burn_address, ShortAddressHash, DEFAULT_SUBADDRESS_INDEX};
@@ -61,16 +75,17 @@ use mc_connection::{BlockchainConnection, UserTxConnection};
 use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPrivate, RistrettoPublic};
 use mc_fog_report_validation::FogPubkeyResolver;
 use mc_mobilecoind_json::data_types::{JsonTx, JsonTxOut, JsonTxOutMembershipProof};
-use mc_transaction_core::Amount;
+use mc_transaction_core::{tx::Tx, Amount, TokenId};
 use mc_transaction_extra::BurnRedemptionMemo;
 use mc_transaction_signer::types::{AccountId, TxoSyncReq, TxoUnsynced};
-use rocket::{self, serde::json::Json};
+use rocket::{self, serde::json::Json, tokio};
 use serde_json::Map;
 use std::{
     collections::HashMap,
     convert::{TryFrom, TryInto},
     iter::FromIterator,
     str::FromStr,
+    time::{Duration, Instant},
 };
 
 /// Default amount of recent blocks to return
@@ -80,7 +95,9 @@ pub const RECENT_BLOCKS_DEFAULT_LIMIT: usize = 10;
 pub const MAX_BLOCKS_PER_REQUEST: usize = 100;
 
 pub async fn generic_wallet_api<T, FPR>(
-    _api_key_guard: ApiKeyGuard,
+    api_key_guard: ApiKeyGuard,
+    method_filter: &rocket::State<MethodFilterState>,
+    audit_log: &rocket::State<AuditLogState>,
     state: &rocket::State<WalletState<T, FPR>>,
     command: Json<JsonRPCRequest>,
 ) -> Result<Json<JsonRPCResponse<JsonCommandResponse>>, String>
@@ -96,6 +113,7 @@ where
         error: None,
         jsonrpc: "2.0".to_string(),
         id: command.0.id,
+        elapsed_ms: None,
     };
 
     let request = match JsonCommandRequest::try_from(&req) {
@@ -106,6 +124,24 @@ where
         }
     };
 
+    // v1 requests are translated to their v2 equivalent above, so filter and
+    // audit on the translated request rather than the method name the client
+    // sent.
+    let canonical_req = canonical_request(&req, &request);
+    if let Err(error) = method_filter
+        .check(&canonical_req.method)
+        .and_then(|()| api_key_guard.check(&canonical_req.method))
+    {
+        response.error = Some(format_invalid_request_error(error));
+        return Ok(Json(response));
+    }
+
+    if let Err(error) = audit_log.record_request(&canonical_req) {
+        response.error = Some(format_error(error));
+        return Ok(Json(response));
+    }
+
+    let start = Instant::now();
     match wallet_api_inner(&state.service, request).await {
         Ok(command_response) => {
             global_log::info!(
@@ -119,10 +155,29 @@ where
             response.error = Some(rpc_error);
         }
     };
+    audit_log.record_outcome(
+        &canonical_req,
+        response.result.as_ref(),
+        response.error.as_ref(),
+    );
+    if req.include_elapsed_ms == Some(true) {
+        response.elapsed_ms = Some(start.elapsed().as_millis() as u64);
+    }
 
     Ok(Json(response))
 }
 
+/// The request as it will be executed: the v2 method and params of `request`,
+/// with the remaining fields taken from the original `req`.
+fn canonical_request(req: &JsonRPCRequest, request: &JsonCommandRequest) -> JsonRPCRequest {
+    let value = serde_json::to_value(request).unwrap_or_default();
+    JsonRPCRequest {
+        method: value["method"].as_str().unwrap_or(&req.method).to_string(),
+        params: value.get("params").cloned(),
+        ..req.clone()
+    }
+}
+
 /// The Wallet API inner method, which handles switching on the method enum.
 ///
 /// Note that this is structured this way so that the routes can be defined to
@@ -148,6 +203,30 @@ where
     }
 
     let response = match command {
+        JsonCommandRequest::abandon_transaction {
+            tx_proposal,
+            reservation_id,
+        } => {
+            match (tx_proposal, reservation_id) {
+                (Some(tx_proposal), None) => {
+                    let tx_proposal = TxProposal::try_from(&tx_proposal).map_err(format_error)?;
+                    service
+                        .abandon_transaction(&tx_proposal)
+                        .map_err(format_error)?;
+                }
+                (None, Some(reservation_id)) => {
+                    service
+                        .release_input_reservation(&reservation_id)
+                        .map_err(format_error)?;
+                }
+                _ => {
+                    return Err(format_invalid_params_error(
+                        "Exactly one of tx_proposal or reservation_id must be provided",
+                    ))
+                }
+            }
+            JsonCommandResponse::abandon_transaction
+        }
         JsonCommandRequest::assign_address_for_account {
             account_id,
             metadata,
@@ -158,6 +237,14 @@ where
                     .map_err(format_error)?,
             ),
         },
+        JsonCommandRequest::backfill_memos { account_id } => {
+            let num_txos = service
+                .backfill_memos(&AccountID(account_id))
+                .map_err(format_error)?;
+            JsonCommandResponse::backfill_memos {
+                num_txos: num_txos.to_string(),
+            }
+        }
         JsonCommandRequest::build_and_submit_transaction {
             account_id,
             addresses_and_amounts,
@@ -173,6 +260,7 @@ where
             sender_memo_credential_subaddress_index,
             payment_request_id,
             spend_subaddress,
+            device_id,
         } => {
             // The user can specify a list of addresses and values,
             // or a single address and a single value.
@@ -220,6 +308,7 @@ where
                     transaction_memo,
                     block_version,
                     spend_subaddress,
+                    device_id.as_deref(),
                 )
                 .await
                 .map_err(format_error)?;
@@ -244,6 +333,7 @@ where
             max_spendable_value,
             block_version,
             spend_subaddress,
+            device_id,
         } => {
             let mut memo_data = [0; BurnRedemptionMemo::MEMO_DATA_LEN];
             if let Some(redemption_memo_hex) = redemption_memo_hex {
@@ -280,6 +370,7 @@ where
                     TransactionMemo::BurnRedemption(memo_data),
                     block_version,
                     spend_subaddress,
+                    device_id.as_deref(),
                 )
                 .await
                 .map_err(format_error)?;
@@ -297,6 +388,7 @@ where
             recipient_public_address,
             amount,
             input_txo_ids,
+            reservation_id,
             fee_value,
             fee_token_id,
             tombstone_block,
@@ -305,6 +397,8 @@ where
             sender_memo_credential_subaddress_index,
             payment_request_id,
             spend_subaddress,
+            send_all,
+            device_id,
         } => {
             // The user can specify a list of addresses and values,
             // or a single address and a single value.
@@ -313,6 +407,14 @@ where
                 addresses_and_amounts.push((address, amount));
             }
 
+            // A transaction built against a reservation spends exactly the
+            // reserved inputs.
+            if reservation_id.is_some() && (input_txo_ids.is_some() || send_all) {
+                return Err(format_invalid_params_error(
+                    "reservation_id can not be used with input_txo_ids or send_all",
+                ));
+            }
+
             let block_version = match block_version {
                 Some(block_version) => Some(
                     BlockVersion::try_from(block_version.parse::<u32>().map_err(format_error)?)
@@ -339,27 +441,99 @@ where
                 },
             };
 
-            let tx_proposal = service
-                .build_and_sign_transaction(
-                    &account_id,
-                    &addresses_and_amounts,
-                    input_txo_ids.as_ref(),
-                    fee_value,
-                    fee_token_id,
-                    tombstone_block,
-                    max_spendable_value,
-                    transaction_memo,
-                    block_version,
-                    spend_subaddress,
-                )
-                .await
-                .map_err(format_error)?;
+            let (tx_proposal, send_all_value) = if send_all {
+                // Only the recipient and token of the amount are used, as the
+                // value sent is everything that can be spent, less the fee.
+                let (recipient_public_address, amount) = match addresses_and_amounts.as_slice() {
+                    [(address, amount)] => (address, amount),
+                    _ => {
+                        return Err(format_invalid_params_error(
+                            "send_all requires exactly one recipient",
+                        ))
+                    }
+                };
+                if input_txo_ids.is_some() {
+                    return Err(format_invalid_params_error(
+                        "send_all can not be used with input_txo_ids",
+                    ));
+                }
+                if let Some(fee_token_id) = &fee_token_id {
+                    if fee_token_id != amount.token_id.expose_secret() {
+                        return Err(format_invalid_params_error(
+                            "send_all requires the fee be paid in the token being sent",
+                        ));
+                    }
+                }
+                let token_id = TokenId::from(
+                    amount
+                        .token_id
+                        .expose_secret()
+                        .parse::<u64>()
+                        .map_err(format_error)?,
+                );
+
+                let tx_proposal = service
+                    .build_and_sign_send_all_transaction(
+                        &account_id,
+                        recipient_public_address,
+                        token_id,
+                        fee_value,
+                        tombstone_block,
+                        max_spendable_value,
+                        transaction_memo,
+                        block_version,
+                        spend_subaddress,
+                        device_id.as_deref(),
+                    )
+                    .await
+                    .map_err(format_error)?;
+                let send_all_value = tx_proposal
+                    .payload_txos
+                    .first()
+                    .map(|txo| txo.amount.value.to_string());
+                (tx_proposal, send_all_value)
+            } else if let Some(reservation_id) = reservation_id {
+                let tx_proposal = service
+                    .build_and_sign_reserved_transaction(
+                        &account_id,
+                        &reservation_id,
+                        &addresses_and_amounts,
+                        fee_value,
+                        fee_token_id,
+                        tombstone_block,
+                        transaction_memo,
+                        block_version,
+                        device_id.as_deref(),
+                    )
+                    .await
+                    .map_err(format_error)?;
+                (tx_proposal, None)
+            } else {
+                let tx_proposal = service
+                    .build_and_sign_transaction(
+                        &account_id,
+                        &addresses_and_amounts,
+                        input_txo_ids.as_ref(),
+                        fee_value,
+                        fee_token_id,
+                        tombstone_block,
+                        max_spendable_value,
+                        transaction_memo,
+                        block_version,
+                        spend_subaddress,
+                        device_id.as_deref(),
+                    )
+                    .await
+                    .map_err(format_error)?;
+                (tx_proposal, None)
+            };
 
             JsonCommandResponse::build_transaction {
                 tx_proposal: TxProposalJSON::try_from(&tx_proposal).map_err(format_error)?,
                 transaction_log_id: TransactionId::try_from(&tx_proposal)
                     .map_err(format_error)?
                     .to_string(),
+                send_all_value,
             }
         }
         JsonCommandRequest::build_unsigned_burn_transaction {
@@ -467,6 +641,11 @@ where
                 unsigned_tx_proposal,
             }
         }
+        JsonCommandRequest::cancel_hardware_wallet_signing { account_id } => {
+            JsonCommandResponse::cancel_hardware_wallet_signing {
+                cancelled: cancel_signing(&AccountID(account_id)),
+            }
+        }
         JsonCommandRequest::check_b58_type { b58_code } => {
             let b58_type = b58_printable_wrapper_type(b58_code.clone()).map_err(format_error)?;
             let mut b58_data = HashMap::new();
@@ -482,10 +661,12 @@ where
                         b58_encode_public_address(&payment_request.public_address)
                             .map_err(format_error)?;
                     b58_data.insert("public_address_b58".to_string(), public_address_b58);
-                    b58_data.insert("value".to_string(), payment_request.value.to_string());
+                    // Open amount requests have no value, the payer chooses it.
+                    if let Some(value) = payment_request.value {
+                        b58_data.insert("value".to_string(), value.to_string());
+                    }
                     b58_data.insert("token_id".to_string(), payment_request.token_id.to_string());
                     b58_data.insert("memo".to_string(), payment_request.memo);
-                    b58_data.insert("token_id".to_string(), payment_request.token_id.to_string());
                 }
             }
             JsonCommandResponse::check_b58_type {
@@ -505,7 +686,31 @@ where
 
             JsonCommandResponse::check_receiver_receipt_status {
                 receipt_transaction_status: status,
-                txo: txo_status_and_memo.map(|txo_info| (&txo_info).into()),
+                txo: txo_status_and_memo
+                    .map(|txo_info| Txo::new(&txo_info, &service.token_metadata)),
+            }
+        }
+        JsonCommandRequest::check_receiver_receipts_status {
+            address,
+            receiver_receipts,
+        } => {
+            let receipts = receiver_receipts
+                .iter()
+                .map(service::receipt::ReceiverReceipt::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(format_error)?;
+            let statuses = service
+                .check_receipts_status(&address, &receipts)
+                .map_err(format_error)?;
+
+            JsonCommandResponse::check_receiver_receipts_status {
+                receipt_statuses: statuses
+                    .into_iter()
+                    .map(|(status, txo_info)| ReceiverReceiptStatus {
+                        receipt_transaction_status: status,
+                        txo: txo_info.map(|txo_info| Txo::new(&txo_info, &service.token_metadata)),
+                    })
+                    .collect(),
             }
         }
         JsonCommandRequest::create_account {
@@ -618,6 +823,17 @@ where
 
             JsonCommandResponse::create_view_only_account_sync_request { txo_sync_request }
         }
+        JsonCommandRequest::derive_address_for_account {
+            account_id,
+            subaddress_index,
+        } => {
+            let subaddress_index = subaddress_index.parse::<u64>().map_err(format_error)?;
+            JsonCommandResponse::derive_address_for_account {
+                public_address_b58: service
+                    .derive_address_for_account(&AccountID(account_id), subaddress_index)
+                    .map_err(format_error)?,
+            }
+        }
         JsonCommandRequest::export_account_secrets { account_id } => {
             let account = service
                 .get_account(&AccountID(account_id))
@@ -626,6 +842,40 @@ where
                 account_secrets: AccountSecrets::try_from(&account).map_err(format_error)?,
             }
         }
+        JsonCommandRequest::export_encrypted_account_secrets {
+            account_id,
+            passphrase,
+        } => {
+            let account = service
+                .get_account(&AccountID(account_id))
+                .map_err(format_error)?;
+            let account_secrets = AccountSecrets::try_from(&account).map_err(format_error)?;
+            let plaintext = serde_json::to_vec(&account_secrets).map_err(format_error)?;
+            // Deriving the key takes a while, so keep it off the async workers.
+            let encrypted_account_secrets = tokio::task::spawn_blocking(move || {
+                EncryptedSecrets::encrypt(&plaintext, passphrase.expose_secret())
+            })
+            .await
+            .map_err(format_error)?
+            .map_err(format_invalid_params_error)?;
+            JsonCommandResponse::export_encrypted_account_secrets {
+                encrypted_account_secrets,
+            }
+        }
+        JsonCommandRequest::get_account_lifetime_stats { account_id } => {
+            let lifetime_stats = service
+                .get_account_lifetime_stats(&AccountID(account_id))
+                .map_err(format_error)?;
+
+            JsonCommandResponse::get_account_lifetime_stats {
+                lifetime_stats_per_token: LifetimeStatsMap(
+                    lifetime_stats
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), LifetimeStats::from(v)))
+                        .collect(),
+                ),
+            }
+        }
         JsonCommandRequest::get_account_status { account_id }
         | JsonCommandRequest::get_balance { account_id } => {
             let account = service
@@ -651,13 +901,37 @@ where
             let network_status = service.get_network_status().map_err(format_error)?;
 
             let balance = service
-                .get_balance_for_account(&AccountID(account_id))
+                .get_balance_for_account(&AccountID(account_id.clone()))
                 .map_err(format_error)?;
 
             let balance_formatted = BalanceMap(
                 balance
                     .iter()
-                    .map(|(k, v)| (k.to_string(), Balance::from(v)))
+                    .map(|(k, v)| {
+                        (
+                            k.to_string(),
+                            Balance::new(v, service.token_metadata.get(**k)),
+                        )
+                    })
+                    .collect(),
+            );
+
+            let sync_estimate = service
+                .get_account_sync_estimate(&AccountID(account_id.clone()))
+                .map_err(format_error)?;
+
+            let (last_activity_block_index, last_activity_timestamp) = service
+                .get_account_last_activity(&AccountID(account_id.clone()))
+                .map_err(format_error)?;
+
+            let pending_outgoing = service
+                .get_pending_outgoing_for_account(&AccountID(account_id))
+                .map_err(format_error)?;
+
+            let pending_outgoing_formatted = PendingOutgoingMap(
+                pending_outgoing
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), PendingOutgoing::from(v)))
                     .collect(),
             );
 
@@ -665,11 +939,33 @@ where
                 account,
                 network_block_height: network_status.network_block_height.to_string(),
                 local_block_height: network_status.local_block_height.to_string(),
+                sync_blocks_per_second: sync_estimate
+                    .blocks_per_second
+                    .map(|rate| format!("{rate:.2}")),
+                sync_seconds_remaining: sync_estimate
+                    .time_remaining
+                    .map(|time_remaining| time_remaining.as_secs().to_string()),
+                last_activity_block_index: last_activity_block_index.map(|i| i.to_string()),
+                last_activity_timestamp: last_activity_timestamp.map(|t| t.to_string()),
                 balance_per_token: balance_formatted,
+                pending_outgoing: pending_outgoing_formatted,
             }
         }
-        JsonCommandRequest::get_accounts { offset, limit } => {
-            let accounts = service.list_accounts(offset, limit).map_err(format_error)?;
+        JsonCommandRequest::get_accounts {
+            offset,
+            limit,
+            include_status,
+            sort_by,
+        } => {
+            let sort_order = sort_by
+                .map(|sort_by| AccountSortOrder::from_str(&sort_by))
+                .transpose()
+                .map_err(format_invalid_params_error)?
+                .unwrap_or_default();
+            let accounts = service
+                .list_accounts(sort_order, offset, limit)
+                .map_err(format_error)?;
+            let total_count = service.count_accounts().map_err(format_error)?;
             let account_map = AccountMap(
                 accounts
                     .iter()
@@ -694,9 +990,79 @@ where
                     .collect::<Result<_, _>>()?,
             );
 
+            let (network_block_height, local_block_height, status_map) = if include_status
+                .unwrap_or(false)
+            {
+                let network_status = service.get_network_status().map_err(format_error)?;
+                let balances = service
+                    .get_balances_for_accounts(&accounts)
+                    .map_err(format_error)?;
+                let pending_outgoing = service
+                    .get_pending_outgoing_for_all_accounts()
+                    .map_err(format_error)?;
+
+                let status_map = AccountStatusMap(
+                    accounts
+                        .iter()
+                        .map(|a| {
+                            let account_id = AccountID(a.id.clone());
+                            let balance_per_token = BalanceMap(
+                                balances
+                                    .get(&account_id)
+                                    .map(|balance| {
+                                        balance
+                                            .iter()
+                                            .map(|(k, v)| {
+                                                (
+                                                    k.to_string(),
+                                                    Balance::new(
+                                                        v,
+                                                        service.token_metadata.get(**k),
+                                                    ),
+                                                )
+                                            })
+                                            .collect()
+                                    })
+                                    .unwrap_or_default(),
+                            );
+                            let pending_outgoing = PendingOutgoingMap(
+                                pending_outgoing
+                                    .get(&account_id)
+                                    .map(|pending| {
+                                        pending
+                                            .iter()
+                                            .map(|(k, v)| (k.to_string(), PendingOutgoing::from(v)))
+                                            .collect()
+                                    })
+                                    .unwrap_or_default(),
+                            );
+                            (
+                                a.id.clone(),
+                                AccountStatus {
+                                    balance_per_token,
+                                    pending_outgoing,
+                                },
+                            )
+                        })
+                        .collect(),
+                );
+
+                (
+                    Some(network_status.network_block_height.to_string()),
+                    Some(network_status.local_block_height.to_string()),
+                    Some(status_map),
+                )
+            } else {
+                (None, None, None)
+            };
+
             JsonCommandResponse::get_accounts {
                 account_ids: accounts.iter().map(|a| a.id.clone()).collect(),
                 account_map,
+                total_count: total_count.to_string(),
+                network_block_height,
+                local_block_height,
+                status_map,
             }
         }
         JsonCommandRequest::get_address { public_address_b58 } => {
@@ -716,17 +1082,36 @@ where
             }
         }
         JsonCommandRequest::get_address_for_account { account_id, index } => {
+            let account_id = AccountID(account_id);
+            let index = match index {
+                Some(index) => index,
+                None => {
+                    service
+                        .get_account(&account_id)
+                        .map_err(format_error)?
+                        .default_receive_subaddress_index
+                }
+            };
             let assigned_subaddress = service
-                .get_address_for_account(&AccountID(account_id), index)
+                .get_address_for_account(&account_id, index)
                 .map_err(format_error)?;
             JsonCommandResponse::get_address_for_account {
                 address: Address::from(&assigned_subaddress),
             }
         }
+        JsonCommandRequest::get_address_for_txo { txo_id } => {
+            let assigned_subaddress = service
+                .get_address_for_txo(&TxoID(txo_id))
+                .map_err(format_error)?;
+            JsonCommandResponse::get_address_for_txo {
+                address: Address::from(&assigned_subaddress),
+            }
+        }
         JsonCommandRequest::get_addresses {
             account_id,
             offset,
             limit,
+            include_balances,
         } => {
             let addresses = service
                 .get_addresses(account_id, offset, limit)
@@ -739,12 +1124,29 @@ where
                     .collect(),
             );
 
+            let address_balance_map = if include_balances.unwrap_or(false) {
+                let address_balances = service
+                    .get_address_balances(&addresses)
+                    .map_err(format_error)?;
+                Some(AddressBalanceMap(
+                    address_balances
+                        .iter()
+                        .map(|(address_b58, balance)| {
+                            (address_b58.clone(), AddressBalance::from(balance))
+                        })
+                        .collect(),
+                ))
+            } else {
+                None
+            };
+
             JsonCommandResponse::get_addresses {
                 public_addresses: addresses
                     .iter()
                     .map(|a| a.public_address_b58.clone())
                     .collect(),
                 address_map,
+                address_balance_map,
             }
         }
         JsonCommandRequest::get_address_status { address } => {
@@ -760,7 +1162,12 @@ where
             let balance_per_token = BalanceMap(
                 balance
                     .iter()
-                    .map(|(a, b)| (a.to_string(), Balance::from(b)))
+                    .map(|(a, b)| {
+                        (
+                            a.to_string(),
+                            Balance::new(b, service.token_metadata.get(**a)),
+                        )
+                    })
                     .collect(),
             );
 
@@ -772,6 +1179,9 @@ where
                 balance_per_token,
             }
         }
+        JsonCommandRequest::get_api_schema => JsonCommandResponse::get_api_schema {
+            methods: api_schema(),
+        },
         JsonCommandRequest::get_block {
             block_index,
             txo_public_key,
@@ -898,6 +1308,42 @@ where
                 network_status,
             }
         }
+        JsonCommandRequest::get_change_txos {
+            account_id,
+            token_id,
+            offset,
+            limit,
+        } => {
+            let token_id = match token_id {
+                Some(t) => Some(t.parse::<u64>().map_err(format_error)?),
+                None => None,
+            };
+
+            let txo_infos = service
+                .list_change_txos(&AccountID(account_id), token_id, offset, limit)
+                .map_err(format_error)?;
+
+            let txo_map = Map::from_iter(
+                txo_infos
+                    .iter()
+                    .map(|txo_info| {
+                        (
+                            txo_info.txo.id.clone(),
+                            serde_json::to_value(Txo::new(txo_info, &service.token_metadata))
+                                .expect("Could not get json value"),
+                        )
+                    })
+                    .collect::<Vec<(String, serde_json::Value)>>(),
+            );
+
+            JsonCommandResponse::get_change_txos {
+                txo_ids: txo_infos
+                    .into_iter()
+                    .map(|txo_info| txo_info.txo.id)
+                    .collect(),
+                txo_map,
+            }
+        }
         JsonCommandRequest::get_confirmations { transaction_log_id } => {
             JsonCommandResponse::get_confirmations {
                 confirmations: service
@@ -908,6 +1354,23 @@ where
                     .collect(),
             }
         }
+        JsonCommandRequest::get_hardware_wallet_devices => {
+            JsonCommandResponse::get_hardware_wallet_devices {
+                devices: list_devices()
+                    .await
+                    .map_err(format_error)?
+                    .iter()
+                    .map(HardwareWalletDevice::from)
+                    .collect(),
+            }
+        }
+        JsonCommandRequest::get_hardware_wallet_sync_progress { account_id } => {
+            JsonCommandResponse::get_hardware_wallet_sync_progress {
+                sync_progress: get_sync_progress(&AccountID(account_id))
+                    .as_ref()
+                    .map(HardwareWalletSyncProgress::from),
+            }
+        }
         JsonCommandRequest::get_mc_protocol_transaction { transaction_log_id } => {
             let tx = service
                 .get_transaction_object(&transaction_log_id)
@@ -937,6 +1400,28 @@ where
                 metadata: metadata_info.metadata,
             }
         }
+        JsonCommandRequest::get_spent_key_images {
+            account_id,
+            offset,
+            limit,
+        } => {
+            let spent_key_images = service
+                .list_spent_key_images(&AccountID(account_id), offset, limit)
+                .map_err(format_error)?;
+            JsonCommandResponse::get_spent_key_images {
+                spent_key_images: spent_key_images.iter().map(SpentKeyImage::from).collect(),
+            }
+        }
+        JsonCommandRequest::get_supported_token_ids => {
+            JsonCommandResponse::get_supported_token_ids {
+                token_ids: service
+                    .get_supported_token_ids()
+                    .map_err(format_error)?
+                    .iter()
+                    .map(|token_id| token_id.to_string())
+                    .collect(),
+            }
+        }
         JsonCommandRequest::get_transaction_log { transaction_log_id } => {
             let (transaction_log, associated_txos, value_map) = service
                 .get_transaction_log(&transaction_log_id)
@@ -991,10 +1476,65 @@ where
                 transaction_log_map,
             }
         }
+        JsonCommandRequest::get_transaction_logs_for_txo { txo_id } => {
+            let transaction_logs_and_txos = service
+                .get_transaction_logs_for_txo(&txo_id)
+                .map_err(format_error)?;
+
+            let transaction_log_map = Map::from_iter(
+                transaction_logs_and_txos
+                    .iter()
+                    .map(|(t, a, v)| {
+                        (
+                            t.id.clone(),
+                            serde_json::to_value(TransactionLog::new(t, a, v))
+                                .expect("Could not get json value"),
+                        )
+                    })
+                    .collect::<Vec<(String, serde_json::Value)>>(),
+            );
+
+            JsonCommandResponse::get_transaction_logs_for_txo {
+                transaction_log_ids: transaction_logs_and_txos
+                    .iter()
+                    .map(|(t, _, _)| t.id.clone())
+                    .collect(),
+                transaction_log_map,
+            }
+        }
+        JsonCommandRequest::get_transaction_proof_bundle { transaction_log_id } => {
+            let (transaction_log, associated_txos, value_map) = service
+                .get_transaction_log(&transaction_log_id)
+                .map_err(format_error)?;
+            let receiver_receipts = service
+                .create_receiver_receipts_for_transaction_log(&TransactionId(
+                    transaction_log_id.clone(),
+                ))
+                .map_err(format_error)?
+                .iter()
+                .map(ReceiverReceipt::try_from)
+                .collect::<Result<Vec<ReceiverReceipt>, String>>()
+                .map_err(format_error)?;
+            let confirmations = service
+                .get_confirmations(&transaction_log_id)
+                .map_err(format_error)?
+                .iter()
+                .map(Confirmation::from)
+                .collect();
+            JsonCommandResponse::get_transaction_proof_bundle {
+                transaction_log: TransactionLog::new(
+                    &transaction_log,
+                    &associated_txos,
+                    &value_map,
+                ),
+                receiver_receipts,
+                confirmations,
+            }
+        }
         JsonCommandRequest::get_txo { txo_id } => {
             let txo_info = service.get_txo(&TxoID(txo_id)).map_err(format_error)?;
             JsonCommandResponse::get_txo {
-                txo: (&txo_info).into(),
+                txo: Txo::new(&txo_info, &service.token_metadata),
             }
         }
         JsonCommandRequest::get_txo_block_index { public_key } => {
@@ -1010,6 +1550,23 @@ where
                 block_index: block_index.to_string(),
             }
         }
+        JsonCommandRequest::get_txo_confirmation_number { txo_id } => {
+            let confirmation = service
+                .get_txo_confirmation_number(&TxoID(txo_id))
+                .map_err(format_error)?;
+            JsonCommandResponse::get_txo_confirmation_number {
+                confirmation: confirmation
+                    .map(|confirmation| hex::encode(mc_util_serial::encode(&confirmation))),
+            }
+        }
+        JsonCommandRequest::get_txo_history { txo_id } => {
+            let events = service
+                .get_txo_history(&TxoID(txo_id))
+                .map_err(format_error)?;
+            JsonCommandResponse::get_txo_history {
+                events: events.iter().map(TxoEvent::from).collect(),
+            }
+        }
         JsonCommandRequest::get_txos {
             account_id,
             address,
@@ -1017,6 +1574,7 @@ where
             token_id,
             min_received_block_index,
             max_received_block_index,
+            unspent_for_blocks,
             offset,
             limit,
         } => {
@@ -1040,8 +1598,28 @@ where
                 None => None,
             };
 
-            let txos_and_statuses = service
-                .list_txos(
+            let txos_and_statuses = match unspent_for_blocks {
+                Some(unspent_for_blocks) => {
+                    let unspent_for_blocks = unspent_for_blocks
+                        .parse::<u64>()
+                        .map_err(format_invalid_params_error)?;
+                    if status.as_ref().map_or(false, |s| *s != TxoStatus::Unspent) {
+                        return Err(format_invalid_params_error(
+                            "unspent_for_blocks can only be used with the unspent status",
+                        ));
+                    }
+                    service.list_stale_txos(
+                        account_id,
+                        address,
+                        token_id,
+                        unspent_for_blocks,
+                        min_received_block_index,
+                        max_received_block_index,
+                        offset,
+                        limit,
+                    )
+                }
+                None => service.list_txos(
                     account_id,
                     address,
                     status,
@@ -1050,8 +1628,9 @@ where
                     max_received_block_index,
                     offset,
                     limit,
-                )
-                .map_err(format_error)?;
+                ),
+            }
+            .map_err(format_error)?;
 
             let txo_map = Map::from_iter(
                 txos_and_statuses
@@ -1059,7 +1638,7 @@ where
                     .map(|txo_info| {
                         (
                             txo_info.txo.id.clone(),
-                            serde_json::to_value(Txo::from(txo_info))
+                            serde_json::to_value(Txo::new(txo_info, &service.token_metadata))
                                 .expect("Could not get json value"),
                         )
                     })
@@ -1108,20 +1687,62 @@ where
                 membership_proofs,
             }
         }
+        JsonCommandRequest::get_txos_missing_required_memo {
+            account_id,
+            offset,
+            limit,
+        } => {
+            let txo_infos = service
+                .list_txos_missing_required_memo(&AccountID(account_id), offset, limit)
+                .map_err(format_error)?;
+
+            let txo_map = Map::from_iter(
+                txo_infos
+                    .iter()
+                    .map(|txo_info| {
+                        (
+                            txo_info.txo.id.clone(),
+                            serde_json::to_value(Txo::new(txo_info, &service.token_metadata))
+                                .expect("Could not get json value"),
+                        )
+                    })
+                    .collect::<Vec<(String, serde_json::Value)>>(),
+            );
+
+            JsonCommandResponse::get_txos_missing_required_memo {
+                txo_ids: txo_infos
+                    .into_iter()
+                    .map(|txo_info| txo_info.txo.id)
+                    .collect(),
+                txo_map,
+            }
+        }
         JsonCommandRequest::get_wallet_status => JsonCommandResponse::get_wallet_status {
-            wallet_status: WalletStatus::try_from(
+            wallet_status: WalletStatus::new(
                 &service.get_wallet_status().map_err(format_error)?,
-            )
-            .map_err(format_error)?,
+                &service.token_metadata,
+            ),
+        },
+        JsonCommandRequest::get_watcher_status => JsonCommandResponse::get_watcher_status {
+            watcher_status: service
+                .get_watcher_status()
+                .map_err(format_error)?
+                .as_ref()
+                .map(Into::into),
         },
         JsonCommandRequest::import_account {
             mnemonic,
+            account_index,
             name,
             first_block_index,
             next_subaddress_index,
             fog_info,
             require_spend_subaddress,
         } => {
+            let ai = account_index
+                .map(|ai| ai.parse::<u32>())
+                .transpose()
+                .map_err(format_error)?;
             let fb = first_block_index
                 .map(|fb| fb.parse::<u64>())
                 .transpose()
@@ -1136,6 +1757,7 @@ where
             let account = service
                 .import_account(
                     mnemonic,
+                    ai,
                     name,
                     fb,
                     ns,
@@ -1163,6 +1785,147 @@ where
 
             JsonCommandResponse::import_account { account }
         }
+        JsonCommandRequest::import_accounts { accounts } => {
+            // Accounts with invalid parameters are reported in their place in the
+            // results, rather than failing the whole request.
+            let imports: Vec<Result<AccountImport, String>> =
+                accounts.iter().map(AccountImport::try_from).collect();
+
+            let mut imported = service
+                .import_accounts(imports.iter().filter_map(|i| i.clone().ok()).collect())
+                .map_err(format_error)?
+                .into_iter();
+
+            let results = imports
+                .into_iter()
+                .map(|import| {
+                    let account = import.and_then(|_| {
+                        let account = imported
+                            .next()
+                            .expect("one result for each import")
+                            .map_err(|e| e.to_string())?;
+                        let next_subaddress_index = service
+                            .get_next_subaddress_index_for_account(&AccountID(account.id.clone()))
+                            .map_err(|e| e.to_string())?;
+                        let main_public_address: mc_account_keys::PublicAddress = (&service
+                            .get_address_for_account(
+                                &account.id.clone().into(),
+                                DEFAULT_SUBADDRESS_INDEX as i64,
+                            )
+                            .map_err(|e| e.to_string())?)
+                            .try_into()
+                            .map_err(|e| format!("{e}"))?;
+                        Account::new(&account, &main_public_address, next_subaddress_index)
+                    });
+                    match account {
+                        Ok(account) => ImportAccountResult {
+                            account: Some(account),
+                            error: None,
+                        },
+                        Err(error) => ImportAccountResult {
+                            account: None,
+                            error: Some(error),
+                        },
+                    }
+                })
+                .collect();
+
+            JsonCommandResponse::import_accounts { results }
+        }
+        JsonCommandRequest::import_account_from_encrypted_secrets {
+            encrypted_account_secrets,
+            passphrase,
+            first_block_index,
+            next_subaddress_index,
+            fog_info,
+        } => {
+            let fb = first_block_index
+                .map(|fb| fb.parse::<u64>())
+                .transpose()
+                .map_err(format_error)?;
+            let ns = next_subaddress_index
+                .map(|ns| ns.parse::<u64>())
+                .transpose()
+                .map_err(format_error)?;
+
+            // Deriving the key takes a while, so keep it off the async workers.
+            let plaintext = tokio::task::spawn_blocking(move || {
+                encrypted_account_secrets.decrypt(passphrase.expose_secret())
+            })
+            .await
+            .map_err(format_error)?
+            .map_err(format_invalid_params_error)?;
+            let account_secrets: AccountSecrets =
+                serde_json::from_slice(&plaintext).map_err(format_error)?;
+
+            // Unless overridden, keep the fog info the account was exported with.
+            let fog_info =
+                fog_info.unwrap_or_else(|| match account_secrets.account_key.expose_secret() {
+                    Some(account_key) => FogInfo {
+                        report_url: account_key.fog_report_url.clone(),
+                        report_id: None,
+                        authority_spki: account_key.fog_authority_spki.clone(),
+                    },
+                    None => FogInfo::default(),
+                });
+            let name = Some(account_secrets.name.clone());
+            let require_spend_subaddress = account_secrets.require_spend_subaddress;
+
+            let account = match (
+                account_secrets.mnemonic.expose_secret(),
+                account_secrets.entropy.expose_secret(),
+            ) {
+                (Some(mnemonic), _) => {
+                    let ai = account_secrets
+                        .account_index
+                        .parse::<u32>()
+                        .map_err(format_error)?;
+                    service.import_account(
+                        mnemonic.clone(),
+                        Some(ai),
+                        name,
+                        fb,
+                        ns,
+                        fog_info.report_url,
+                        fog_info.authority_spki,
+                        require_spend_subaddress,
+                    )
+                }
+                (None, Some(entropy)) => service.import_account_from_legacy_root_entropy(
+                    entropy.clone(),
+                    name,
+                    fb,
+                    ns,
+                    fog_info.report_url,
+                    fog_info.authority_spki,
+                    require_spend_subaddress,
+                ),
+                (None, None) => {
+                    return Err(format_invalid_params_error(
+                        "The secrets are for a view only account, which must be imported with import_view_only_account",
+                    ))
+                }
+            }
+            .map_err(format_error)?;
+
+            let next_subaddress_index = service
+                .get_next_subaddress_index_for_account(&AccountID(account.id.clone()))
+                .map_err(format_error)?;
+
+            let main_public_address: mc_account_keys::PublicAddress = (&service
+                .get_address_for_account(
+                    &account.id.clone().into(),
+                    DEFAULT_SUBADDRESS_INDEX as i64,
+                )
+                .map_err(format_error)?)
+                .try_into()
+                .map_err(format_error)?;
+
+            let account = Account::new(&account, &main_public_address, next_subaddress_index)
+                .map_err(format_error)?;
+
+            JsonCommandResponse::import_account_from_encrypted_secrets { account }
+        }
         JsonCommandRequest::import_account_from_legacy_root_entropy {
             entropy,
             name,
@@ -1215,11 +1978,17 @@ where
         JsonCommandRequest::import_view_only_account {
             view_private_key,
             spend_public_key,
+            account_index,
             name,
             first_block_index,
             next_subaddress_index,
             require_spend_subaddress,
+            subaddresses,
         } => {
+            let ai = account_index
+                .map(|ai| ai.parse::<u32>())
+                .transpose()
+                .map_err(format_error)?;
             let fb = first_block_index
                 .map(|fb| fb.parse::<u64>())
                 .transpose()
@@ -1228,6 +1997,12 @@ where
                 .map(|ns| ns.parse::<u64>())
                 .transpose()
                 .map_err(format_error)?;
+            let subaddresses = subaddresses
+                .unwrap_or_default()
+                .into_iter()
+                .map(|s| Ok((s.subaddress_index.parse::<u64>()?, s.metadata)))
+                .collect::<Result<Vec<_>, std::num::ParseIntError>>()
+                .map_err(format_error)?;
 
             let mut view_private_key_bytes = [0u8; 32];
             hex::decode_to_slice(view_private_key, &mut view_private_key_bytes)
@@ -1245,10 +2020,12 @@ where
                 .import_view_only_account(
                     &view_private_key.into(),
                     &spend_public_key.into(),
+                    ai,
                     name,
                     fb,
                     ns,
                     require_spend_subaddress,
+                    &subaddresses,
                 )
                 .map_err(format_error)?;
             let next_subaddress_index = service
@@ -1268,11 +2045,17 @@ where
             JsonCommandResponse::import_view_only_account { account }
         }
         JsonCommandRequest::import_view_only_account_from_hardware_wallet {
+            account_index,
             name,
             first_block_index,
             fog_info,
             require_spend_subaddress,
+            device_id,
         } => {
+            let ai = account_index
+                .map(|ai| ai.parse::<u32>())
+                .transpose()
+                .map_err(format_error)?;
             let fb = first_block_index
                 .map(|fb| fb.parse::<u64>())
                 .transpose()
@@ -1280,10 +2063,12 @@ where
 
             let account = service
                 .import_view_only_account_from_hardware_wallet(
+                    ai,
                     name,
                     fb,
                     fog_info,
                     require_spend_subaddress,
+                    device_id.as_deref(),
                 )
                 .await
                 .map_err(format_error)?;
@@ -1304,11 +2089,72 @@ where
 
             JsonCommandResponse::import_view_only_account_from_hardware_wallet { account }
         }
+        JsonCommandRequest::preview_spendable_selection {
+            account_id,
+            amount,
+            fee_value,
+            max_spendable_value,
+            spend_subaddress,
+        } => {
+            let amount = Amount::try_from(&amount).map_err(format_error)?;
+            let fee_value = fee_value
+                .map(|f| f.parse::<u64>())
+                .transpose()
+                .map_err(format_error)?;
+            let max_spendable_value = max_spendable_value
+                .map(|v| v.parse::<u64>())
+                .transpose()
+                .map_err(format_error)?;
+            let spendable_selection = service
+                .preview_spendable_selection(
+                    &AccountID(account_id),
+                    amount.value,
+                    amount.token_id,
+                    fee_value,
+                    max_spendable_value,
+                    spend_subaddress,
+                )
+                .map_err(format_error)?;
+            JsonCommandResponse::preview_spendable_selection {
+                spendable_selection: SpendableSelection::from(&spendable_selection),
+            }
+        }
         JsonCommandRequest::remove_account { account_id } => JsonCommandResponse::remove_account {
             removed: service
                 .remove_account(&AccountID(account_id))
                 .map_err(format_error)?,
         },
+        JsonCommandRequest::reserve_inputs {
+            account_id,
+            amount,
+            fee_value,
+            max_spendable_value,
+            spend_subaddress,
+        } => {
+            let amount = Amount::try_from(&amount).map_err(format_error)?;
+            let fee_value = fee_value
+                .map(|f| f.parse::<u64>())
+                .transpose()
+                .map_err(format_error)?;
+            let max_spendable_value = max_spendable_value
+                .map(|v| v.parse::<u64>())
+                .transpose()
+                .map_err(format_error)?;
+            let (reservation_id, spendable_selection) = service
+                .reserve_inputs(
+                    &AccountID(account_id),
+                    amount.value,
+                    amount.token_id,
+                    fee_value,
+                    max_spendable_value,
+                    spend_subaddress,
+                )
+                .map_err(format_error)?;
+            JsonCommandResponse::reserve_inputs {
+                reservation_id,
+                spendable_selection: SpendableSelection::from(&spendable_selection),
+            }
+        }
         JsonCommandRequest::resync_account { account_id } => {
             service
                 .resync_account(&AccountID(account_id))
@@ -1363,12 +2209,101 @@ where
                 membership_proofs,
             }
         }
+        JsonCommandRequest::scan_address_for_txos {
+            address,
+            first_block_index,
+            last_block_index,
+        } => {
+            let first_block_index = first_block_index
+                .parse::<u64>()
+                .map_err(format_invalid_params_error)?;
+            let last_block_index = last_block_index
+                .map(|i| i.parse::<u64>())
+                .transpose()
+                .map_err(format_invalid_params_error)?;
+            let txos = service
+                .scan_address_for_txos(&address, first_block_index, last_block_index)
+                .map_err(format_error)?;
+            JsonCommandResponse::scan_address_for_txos {
+                txos: txos.iter().map(Into::into).collect(),
+            }
+        }
         JsonCommandRequest::search_ledger { query } => {
             let results = service.search_ledger(&query).map_err(format_error)?;
             JsonCommandResponse::search_ledger {
                 results: results.iter().map(Into::into).collect(),
             }
         }
+        JsonCommandRequest::set_log_level { module, level } => {
+            let level = slog::Level::from_str(&level)
+                .map_err(|_| format_invalid_params_error(format!("Invalid log level: {level}")))?;
+            LOG_FILTER.set_level(module.as_deref(), level);
+
+            let (default_level, module_levels) = LOG_FILTER.levels();
+            JsonCommandResponse::set_log_level {
+                default_level: default_level.as_str().to_lowercase(),
+                module_levels: module_levels
+                    .into_iter()
+                    .map(|(module, level)| (module, level.as_str().to_lowercase()))
+                    .collect(),
+            }
+        }
+        JsonCommandRequest::set_poll_interval { poll_interval_ms } => {
+            let poll_interval =
+                Duration::from_millis(poll_interval_ms.parse::<u64>().map_err(format_error)?);
+            service
+                .set_poll_interval(poll_interval)
+                .map_err(format_invalid_params_error)?;
+            JsonCommandResponse::set_poll_interval {
+                poll_interval_ms: service.poll_interval.get().as_millis().to_string(),
+            }
+        }
+        JsonCommandRequest::set_default_receive_subaddress {
+            account_id,
+            subaddress_index,
+        } => {
+            let account_id = AccountID(account_id);
+            let account = service
+                .update_default_receive_subaddress_index(&account_id, subaddress_index)
+                .map_err(format_error)?;
+            let next_subaddress_index = service
+                .get_next_subaddress_index_for_account(&AccountID(account.id.clone()))
+                .map_err(format_error)?;
+            let main_public_address: mc_account_keys::PublicAddress = (&service
+                .get_address_for_account(
+                    &account.id.clone().into(),
+                    DEFAULT_SUBADDRESS_INDEX as i64,
+                )
+                .map_err(format_error)?)
+                .try_into()
+                .map_err(format_error)?;
+            let account = Account::new(&account, &main_public_address, next_subaddress_index)
+                .map_err(format_error)?;
+            JsonCommandResponse::set_default_receive_subaddress { account }
+        }
+        JsonCommandRequest::set_require_sender_memo {
+            account_id,
+            require_sender_memo,
+        } => {
+            let account_id = AccountID(account_id);
+            let account = service
+                .update_require_sender_memo(&account_id, require_sender_memo)
+                .map_err(format_error)?;
+            let next_subaddress_index = service
+                .get_next_subaddress_index_for_account(&AccountID(account.id.clone()))
+                .map_err(format_error)?;
+            let main_public_address: mc_account_keys::PublicAddress = (&service
+                .get_address_for_account(
+                    &account.id.clone().into(),
+                    DEFAULT_SUBADDRESS_INDEX as i64,
+                )
+                .map_err(format_error)?)
+                .try_into()
+                .map_err(format_error)?;
+            let account = Account::new(&account, &main_public_address, next_subaddress_index)
+                .map_err(format_error)?;
+            JsonCommandResponse::set_require_sender_memo { account }
+        }
         JsonCommandRequest::set_require_spend_subaddress {
             account_id,
             require_spend_subaddress,
@@ -1392,6 +2327,30 @@ where
                 .map_err(format_error)?;
             JsonCommandResponse::set_require_spend_subaddress { account }
         }
+        JsonCommandRequest::set_tx_source_urls { tx_source_urls } => {
+            JsonCommandResponse::set_tx_source_urls {
+                tx_source_urls: service
+                    .set_tx_source_urls(tx_source_urls)
+                    .map_err(format_invalid_params_error)?,
+            }
+        }
+        JsonCommandRequest::submit_raw_tx {
+            tx_bytes_hex,
+            comment,
+            account_id,
+        } => {
+            let tx_bytes = hex::decode(tx_bytes_hex).map_err(format_error)?;
+            let tx: Tx = mc_util_serial::decode(&tx_bytes).map_err(format_error)?;
+            let result: Option<TransactionLog> = service
+                .submit_raw_tx(&tx, comment, account_id)
+                .map_err(format_error)?
+                .map(|(transaction_log, associated_txos, value_map)| {
+                    TransactionLog::new(&transaction_log, &associated_txos, &value_map)
+                });
+            JsonCommandResponse::submit_raw_tx {
+                transaction_log: result,
+            }
+        }
         JsonCommandRequest::submit_transaction {
             tx_proposal,
             comment,
@@ -1411,6 +2370,7 @@ where
         JsonCommandRequest::sync_view_only_account {
             account_id,
             synced_txos,
+            device_id,
         } => {
             let synced_txos = match synced_txos {
                 Some(synced_txos) => synced_txos,
@@ -1449,9 +2409,14 @@ where
                         })
                         .collect::<Result<Vec<_>, JsonRPCError>>()?;
 
-                    sync_txos(unsynced_txos, &view_account_keys)
-                        .await
-                        .map_err(format_error)?
+                    sync_txos(
+                        unsynced_txos,
+                        &view_account_keys,
+                        account.account_index as u32,
+                        device_id.as_deref(),
+                    )
+                    .await
+                    .map_err(format_error)?
                 }
             };
 
@@ -1461,6 +2426,35 @@ where
 
             JsonCommandResponse::sync_view_only_account
         }
+        JsonCommandRequest::update_account_first_block_index {
+            account_id,
+            first_block_index,
+            force,
+        } => {
+            let account_id = AccountID(account_id);
+            let first_block_index = first_block_index.parse::<u64>().map_err(format_error)?;
+            let account = service
+                .update_account_first_block_index(
+                    &account_id,
+                    first_block_index,
+                    force.unwrap_or(false),
+                )
+                .map_err(format_error)?;
+            let next_subaddress_index = service
+                .get_next_subaddress_index_for_account(&account_id)
+                .map_err(format_error)?;
+            let main_public_address: mc_account_keys::PublicAddress = (&service
+                .get_address_for_account(
+                    &account.id.clone().into(),
+                    DEFAULT_SUBADDRESS_INDEX as i64,
+                )
+                .map_err(format_error)?)
+                .try_into()
+                .map_err(format_error)?;
+            let account = Account::new(&account, &main_public_address, next_subaddress_index)
+                .map_err(format_error)?;
+            JsonCommandResponse::update_account_first_block_index { account }
+        }
         JsonCommandRequest::update_account_name { account_id, name } => {
             let account_id = AccountID(account_id);
             let account = service
@@ -1500,6 +2494,53 @@ where
                 .map_err(format_error)?;
             JsonCommandResponse::validate_sender_memo { validated: result }
         }
+        JsonCommandRequest::verify_account_backup {
+            account_id,
+            mnemonic,
+            entropy,
+            account_index,
+            fog_info,
+        } => {
+            let backup = match (mnemonic, entropy) {
+                (Some(mnemonic_phrase), None) => AccountBackup::Mnemonic {
+                    mnemonic_phrase,
+                    account_index: account_index
+                        .map(|ai| ai.parse::<u32>())
+                        .transpose()
+                        .map_err(format_error)?,
+                },
+                (None, Some(entropy)) => {
+                    if account_index.is_some() {
+                        return Err(format_invalid_params_error(
+                            "account_index only applies to a mnemonic",
+                        ));
+                    }
+                    AccountBackup::LegacyRootEntropy { entropy }
+                }
+                (None, None) => {
+                    return Err(format_invalid_params_error(
+                        "Must specify either mnemonic or entropy",
+                    ));
+                }
+                (Some(_), Some(_)) => {
+                    return Err(format_invalid_params_error(
+                        "Must specify either mnemonic or entropy, not both",
+                    ))
+                }
+            };
+            let fog_info = fog_info.unwrap_or_default();
+
+            let verified = service
+                .verify_account_backup(
+                    backup,
+                    fog_info.report_url,
+                    fog_info.authority_spki,
+                    &AccountID(account_id),
+                )
+                .map_err(format_error)?;
+
+            JsonCommandResponse::verify_account_backup { verified }
+        }
         JsonCommandRequest::verify_address { address } => match service.verify_address(&address) {
             Ok(public_address) => JsonCommandResponse::verify_address {
                 verified: true,
@@ -11,7 +11,9 @@ mod e2e_account {
             models::public_address::PublicAddress,
         },
         test_utils::{add_block_to_ledger_db, manually_sync_account},
-        util::b58::b58_decode_public_address,
+        util::b58::{
+            b58_decode_payment_request, b58_decode_public_address, b58_encode_public_address,
+        },
     };
 
     use mc_common::logger::{test_with_logger, Logger};
@@ -475,6 +477,111 @@ mod e2e_account {
         assert_eq!(subaddress_index, "2");
     }
 
+    #[test_with_logger]
+    fn test_default_receive_subaddress(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Alice Main Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let account = &res["result"]["account"];
+        let account_id = account["id"].as_str().unwrap();
+        assert_eq!(account["default_receive_subaddress_index"], "0");
+
+        // Without an index, the main address is returned.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_address_for_account",
+            "params": {
+                "account_id": account_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert_eq!(res["result"]["address"]["subaddress_index"], "0");
+
+        // An unassigned subaddress cannot be the default.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "set_default_receive_subaddress",
+            "params": {
+                "account_id": account_id,
+                "subaddress_index": 2,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert_eq!(res["error"]["code"], -32603);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "assign_address_for_account",
+            "params": {
+                "account_id": account_id,
+                "comment": "test",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let assigned_address = res["result"]["address"]["public_address_b58"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "set_default_receive_subaddress",
+            "params": {
+                "account_id": account_id,
+                "subaddress_index": 2,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert_eq!(
+            res["result"]["account"]["default_receive_subaddress_index"],
+            "2"
+        );
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_address_for_account",
+            "params": {
+                "account_id": account_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert_eq!(res["result"]["address"]["subaddress_index"], "2");
+
+        // Payment requests without a subaddress are made to the default.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_payment_request",
+            "params": {
+                "account_id": account_id,
+                "amount": { "value": "1000", "token_id": "0" },
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let payment_request_b58 = res["result"]["payment_request_b58"].as_str().unwrap();
+        let public_address = b58_decode_payment_request(payment_request_b58.to_string())
+            .unwrap()
+            .public_address;
+        assert_eq!(
+            b58_encode_public_address(&public_address).unwrap(),
+            assigned_address
+        );
+    }
+
     #[test_with_logger]
     fn test_verify_address(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -123,6 +123,64 @@ mod e2e_account {
         );
     }
 
+    #[test_with_logger]
+    fn test_export_and_import_encrypted_account_secrets(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let mnemonic = "sheriff odor square mistake huge skate mouse shoot purity weapon proof stuff correct concert blanket neck own shift clay mistake air viable stick group";
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "import_account",
+            "params": {
+                "mnemonic": mnemonic,
+                "name": "Alice Main Account",
+                "first_block_index": "200",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let account_id = res["result"]["account"]["id"].clone();
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "export_encrypted_account_secrets",
+            "params": {
+                "account_id": account_id,
+                "passphrase": "correct horse battery staple",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let encrypted_account_secrets = res["result"]["encrypted_account_secrets"].clone();
+        assert!(!encrypted_account_secrets.to_string().contains("sheriff"));
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "remove_account",
+            "params": {
+                "account_id": account_id,
+            }
+        });
+        dispatch(&client, body, &logger);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "import_account_from_encrypted_secrets",
+            "params": {
+                "encrypted_account_secrets": encrypted_account_secrets,
+                "passphrase": "correct horse battery staple",
+                "first_block_index": "200",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let account_obj = &res["result"]["account"];
+        assert_eq!(account_obj["id"], account_id);
+        assert_eq!(account_obj["name"], "Alice Main Account");
+    }
+
     #[test_with_logger]
     fn test_account_status(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -175,4 +233,53 @@ mod e2e_account {
         assert_eq!(unspent, (42 * MOB).to_string());
         let _account = result.get("account").unwrap();
     }
+
+    #[test_with_logger]
+    fn test_backfill_memos(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, mut ledger_db, db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Alice Main Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let account_obj = result.get("account").unwrap();
+        let account_id = account_obj.get("id").unwrap().as_str().unwrap();
+        let b58_public_address = account_obj.get("main_address").unwrap().as_str().unwrap();
+        let public_address = b58_decode_public_address(b58_public_address).unwrap();
+
+        // Add a block with a txo for this address
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![public_address],
+            42 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+
+        manually_sync_account(
+            &ledger_db,
+            &db_ctx.get_db_instance(logger.clone()),
+            &AccountID(account_id.to_string()),
+            &logger,
+        );
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "backfill_memos",
+            "params": {
+                "account_id": account_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert_eq!(result["num_txos"].as_str().unwrap(), "1");
+    }
 }
@@ -36,6 +36,14 @@ mod e2e_account {
         assert_eq!(account_obj.get("next_subaddress_index").unwrap(), "2");
         assert_eq!(account_obj.get("recovery_mode").unwrap(), false);
         assert_eq!(account_obj.get("fog_enabled").unwrap(), false);
+        assert_eq!(
+            account_obj.get("fog_report_url").unwrap(),
+            &serde_json::Value::Null
+        );
+        assert_eq!(
+            account_obj.get("fog_report_id").unwrap(),
+            &serde_json::Value::Null
+        );
 
         let account_id = account_obj.get("id").unwrap();
 
@@ -50,6 +58,7 @@ mod e2e_account {
         let result = res.get("result").unwrap();
         let accounts = result.get("account_ids").unwrap().as_array().unwrap();
         assert_eq!(accounts.len(), 1);
+        assert_eq!(result["total_count"], "1");
         let account_map = result.get("account_map").unwrap().as_object().unwrap();
         assert_eq!(
             account_map
@@ -60,6 +69,10 @@ mod e2e_account {
             &account_id.clone()
         );
 
+        // Status is only included when requested
+        assert!(result.get("status_map").is_none());
+        assert!(result.get("network_block_height").is_none());
+
         let body = json!({
             "jsonrpc": "2.0",
             "id": 2,
@@ -128,6 +141,7 @@ mod e2e_account {
         let result = res.get("result").unwrap();
         let accounts = result.get("account_ids").unwrap().as_array().unwrap();
         assert_eq!(accounts.len(), 0);
+        assert_eq!(result["total_count"], "0");
     }
 
     #[test_with_logger]
@@ -160,5 +174,10 @@ mod e2e_account {
         assert!(account_obj.get("main_address").is_some());
         assert_eq!(account_obj.get("next_subaddress_index").unwrap(), "1");
         assert_eq!(account_obj.get("fog_enabled").unwrap(), true);
+        assert_eq!(
+            account_obj.get("fog_report_url").unwrap(),
+            "fog://fog-report.example.com"
+        );
+        assert_eq!(account_obj.get("fog_report_id").unwrap(), "");
     }
 }
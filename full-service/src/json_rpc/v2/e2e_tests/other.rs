@@ -10,6 +10,7 @@ mod e2e_misc {
                 test_utils::{
                     dispatch, dispatch_with_header, dispatch_with_header_expect_error, setup,
                     setup_no_wallet_db, setup_with_api_key, setup_with_watcher, wait_for_sync,
+                    BASE_TEST_BLOCK_HEIGHT,
                 },
                 wallet::RECENT_BLOCKS_DEFAULT_LIMIT,
             },
@@ -29,9 +30,11 @@ mod e2e_misc {
     use mc_rand::RngCore;
     use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, Amount, BlockVersion, Token};
 
+    use flate2::{read::GzDecoder, write::GzEncoder, Compression};
     use rand::{rngs::StdRng, SeedableRng};
-    use rocket::http::{Header, Status};
+    use rocket::http::{ContentType, Header, Status};
     use serde_json::json;
+    use std::io::{Read, Write};
 
     #[test_with_logger]
     fn test_wallet_status(logger: Logger) {
@@ -138,6 +141,43 @@ mod e2e_misc {
         );
     }
 
+    #[test_with_logger]
+    fn test_get_api_schema(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_api_schema"
+        });
+        let res = dispatch(&client, body, &logger);
+        let methods = res["result"]["methods"].as_array().unwrap();
+
+        let method = |name: &str| {
+            methods
+                .iter()
+                .find(|method| method["method"] == name)
+                .unwrap()
+                .clone()
+        };
+
+        assert_eq!(method("get_network_status")["params"], json!([]));
+        assert_eq!(
+            method("get_addresses")["params"],
+            json!([
+                {"name": "account_id", "type": "string", "required": false},
+                {"name": "offset", "type": "number", "required": false},
+                {"name": "limit", "type": "number", "required": false},
+                {"name": "include_balances", "type": "boolean", "required": false},
+            ])
+        );
+        assert_eq!(
+            method("create_payment_request")["params"][2],
+            json!({"name": "amount", "type": "object", "required": true})
+        );
+    }
+
     #[test_with_logger]
     fn test_get_txo_block_index(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -200,6 +240,10 @@ mod e2e_misc {
             error.get("data").unwrap().get("server_error").unwrap(),
             "LedgerDB(Record not found)"
         );
+        assert_eq!(
+            error.get("data").unwrap().get("error_code").unwrap(),
+            "ledger_db"
+        );
     }
 
     #[test_with_logger]
@@ -450,6 +494,43 @@ mod e2e_misc {
         }
     }
 
+    #[test_with_logger]
+    fn test_get_watcher_status(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _, _, _) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_watcher_status",
+        });
+        let res = dispatch(&client, body.clone(), &logger);
+        let result = res.get("result").unwrap();
+        assert!(result.get("watcher_status").unwrap().is_null());
+
+        let (client, _, _, _) = setup_with_watcher(&mut rng, logger.clone());
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let watcher_status = result.get("watcher_status").unwrap();
+
+        // create_test_watcher_db signs every block in the ledger, from the first
+        // of its two sources only.
+        assert_eq!(
+            watcher_status
+                .get("highest_timestamped_block_index")
+                .unwrap(),
+            &(BASE_TEST_BLOCK_HEIGHT - 1).to_string()
+        );
+        assert_eq!(watcher_status.get("num_blocks_behind").unwrap(), "0");
+        let sources = watcher_status.get("sources").unwrap().as_array().unwrap();
+        assert_eq!(sources.len(), 2);
+        assert_eq!(
+            sources[0].get("last_synced_block_index").unwrap(),
+            &(BASE_TEST_BLOCK_HEIGHT - 1).to_string()
+        );
+        assert!(sources[1].get("last_synced_block_index").unwrap().is_null());
+    }
+
     #[test_with_logger]
     fn test_get_blocks(logger: Logger) {
         let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
@@ -543,6 +624,7 @@ mod e2e_misc {
             data.get("server_error").unwrap(),
             "Database(WalletFunctionsDisabled)"
         );
+        assert_eq!(data.get("error_code").unwrap(), "wallet_functions_disabled");
 
         // This should work just fine since it doesn't interact with the wallet_db
         let body = json!({
@@ -754,5 +836,190 @@ mod e2e_misc {
             account_obj.get("main_address").unwrap().as_str().unwrap()
         );
         assert_eq!(memo, "");
+
+        // A payment request with a value of 0 is for an open amount, which
+        // has no value.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_payment_request",
+            "params": {
+                "account_id": account_id,
+                "amount": { "value": "0", "token_id": "0" },
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let payment_request_b58 = res["result"]["payment_request_b58"].as_str().unwrap();
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "check_b58_type",
+            "params": {
+                "b58_code": payment_request_b58,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let data = &res["result"]["data"];
+        assert!(data.get("value").is_none());
+        assert_eq!(data["token_id"], "0");
+    }
+
+    #[test_with_logger]
+    fn test_set_poll_interval(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "set_poll_interval",
+            "params": {
+                "poll_interval_ms": "500",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert_eq!(result.get("poll_interval_ms").unwrap(), "500");
+
+        // Intervals below the minimum are rejected.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "set_poll_interval",
+            "params": {
+                "poll_interval_ms": "1",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("result").is_none());
+        assert!(res.get("error").is_some());
+    }
+
+    #[test_with_logger]
+    fn test_include_elapsed_ms(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "get_network_status",
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("elapsed_ms").is_none());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "get_network_status",
+            "include_elapsed_ms": true,
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("elapsed_ms").unwrap().is_u64());
+        assert_eq!(res.get("id").unwrap(), 7);
+        assert_eq!(res.get("method").unwrap(), "get_network_status");
+    }
+
+    #[test_with_logger]
+    fn test_v1_requests_are_translated(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Alice Main Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let account_id = res["result"]["account"]["id"].as_str().unwrap();
+
+        // v1 methods with a v2 equivalent get v2 responses.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_all_accounts",
+        });
+        let res = dispatch(&client, body, &logger);
+        assert_eq!(res["result"]["account_ids"], json!([account_id]));
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_all_addresses_for_account",
+            "params": {
+                "account_id": account_id,
+                "offset": "0",
+                "limit": "10",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert_eq!(
+            res["result"]["public_addresses"].as_array().unwrap().len(),
+            3
+        );
+
+        // v1 methods without one are reported as unsupported.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_all_gift_codes",
+        });
+        let res = dispatch(&client, body, &logger);
+        let error = res["error"]["data"]["details"].as_str().unwrap();
+        assert!(error.contains("no v2 equivalent"), "{error}");
+
+        // A malformed v2 request is not reinterpreted as a v1 request which
+        // would ignore its fog_info.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Bob Main Account",
+                "fog_info": {"report_url": "fog://fog.example.com"},
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("result").is_none());
+    }
+
+    #[test_with_logger]
+    fn test_gzip_request_and_response(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, _ledger_db, _db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_network_status",
+        });
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body.to_string().as_bytes()).unwrap();
+        let compressed_body = encoder.finish().unwrap();
+
+        let res = client
+            .post("/wallet/v2")
+            .header(ContentType::JSON)
+            .header(Header::new("Content-Encoding", "gzip"))
+            .header(Header::new("Accept-Encoding", "gzip, deflate"))
+            .body(compressed_body)
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        assert_eq!(res.headers().get_one("Content-Encoding"), Some("gzip"));
+
+        let mut response_body = String::new();
+        GzDecoder::new(&res.into_bytes().unwrap()[..])
+            .read_to_string(&mut response_body)
+            .unwrap();
+        let res: serde_json::Value = serde_json::from_str(&response_body).unwrap();
+        assert!(res.get("result").unwrap().get("network_status").is_some());
+
+        // Clients which do not accept gzip get a plain response.
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("result").unwrap().get("network_status").is_some());
     }
 }
@@ -273,8 +273,8 @@ mod e2e_transaction {
             }
         });
         let res = dispatch(&client, body, &logger);
-        let err = res.get("error");
-        assert!(err.is_some());
+        let err = res.get("error").unwrap();
+        assert_eq!(err["data"]["error_code"], "fee_token_mismatch");
 
         let body = json!({
             "jsonrpc": "2.0",
@@ -7,7 +7,7 @@ mod e2e_transaction {
     use crate::{
         db::{account::AccountID, transaction_log::TxStatus},
         json_rpc::v2::{
-            api::test_utils::{dispatch, dispatch_expect_error, setup},
+            api::test_utils::{dispatch, dispatch_expect_error, setup, TestWalletState},
             models::{
                 account::Account, amount::Amount as AmountJSON, transaction_log::TransactionLog,
                 tx_proposal::TxProposal as TxProposalJSON,
@@ -774,6 +774,17 @@ mod e2e_transaction {
                 });
                 let res = dispatch(&client, body, &logger);
                 let result = res.get("result").unwrap();
+
+                // Release the reservation of the proposal's inputs, as though it
+                // had expired, so that both proposals spend the same txo.
+                let txo_reservations = &client
+                    .rocket()
+                    .state::<TestWalletState>()
+                    .unwrap()
+                    .service
+                    .txo_reservations;
+                txo_reservations.release(&txo_reservations.reserved_txo_ids());
+
                 let tx_log_id =
                     serde_json::from_value(result.get("transaction_log_id").unwrap().clone())
                         .unwrap();
@@ -827,9 +838,9 @@ mod e2e_transaction {
 
         // The first transaction log will have succeeded, because it's outputs
         // show up on the ledger.
-        // The second transaction log will still be failed, because it's inputs
-        // were consumed but its outputs don't exist
-        let expected_statuses = [TxStatus::Succeeded, TxStatus::Failed];
+        // The second transaction log will be conflicting, because it's inputs
+        // were consumed by the first transaction but its outputs don't exist
+        let expected_statuses = [TxStatus::Succeeded, TxStatus::Conflicting];
 
         tx_log_id_and_proposals
             .iter()
@@ -852,4 +863,118 @@ mod e2e_transaction {
                 assert_eq!(&tx_log.id, tx_log_id);
             });
     }
+
+    #[test_with_logger]
+    fn test_abandon_transaction(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, mut ledger_db, db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Alice Main Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let account: Account =
+            serde_json::from_value(result.get("account").unwrap().clone()).unwrap();
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![b58_decode_public_address(&account.main_address).unwrap()],
+            100000000000000, // 100.0 MOB
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            &db_ctx.get_db_instance(logger.clone()),
+            &AccountID(account.id.clone()),
+            &logger,
+        );
+
+        let build_body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "build_transaction",
+            "params": {
+                "account_id": account.id,
+                "recipient_public_address": account.main_address,
+                "amount": { "value": "42000000000000", "token_id": "0"}, // 42.0 MOB
+            }
+        });
+        let res = dispatch(&client, build_body.clone(), &logger);
+        let result = res.get("result").unwrap();
+        let tx_proposal = result.get("tx_proposal").unwrap().clone();
+        let tx_log_id = result.get("transaction_log_id").unwrap().clone();
+
+        // The only txo is reserved by the built transaction.
+        let res = dispatch(&client, build_body.clone(), &logger);
+        assert!(res.get("error").is_some());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "abandon_transaction",
+            "params": {
+                "tx_proposal": tx_proposal,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("error").is_none());
+
+        // The transaction log and its output txos are removed.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_transaction_log",
+            "params": {
+                "transaction_log_id": tx_log_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("error").is_some());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_txos",
+            "params": {}
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert_eq!(result.get("txo_ids").unwrap().as_array().unwrap().len(), 1);
+
+        // The input can be selected again, and once submitted the transaction can no
+        // longer be abandoned.
+        let res = dispatch(&client, build_body, &logger);
+        let result = res.get("result").unwrap();
+        let tx_proposal = result.get("tx_proposal").unwrap().clone();
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "submit_transaction",
+            "params": {
+                "tx_proposal": tx_proposal,
+                "account_id": account.id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("result").is_some());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "abandon_transaction",
+            "params": {
+                "tx_proposal": tx_proposal,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("error").is_some());
+    }
 }
@@ -50,7 +50,7 @@ mod e2e_transaction {
         // add some funds to that account
         add_block_to_ledger_db(
             &mut ledger_db,
-            &vec![main_account_address],
+            &vec![main_account_address.clone()],
             100 * MOB,
             &[KeyImage::from(rng.next_u64())],
             &mut rng,
@@ -138,6 +138,22 @@ mod e2e_transaction {
         assert_eq!(unverified, "100000000000000");
         assert_eq!(unspent, "0");
 
+        // Add a second txo, since the first is reserved by the first unsigned
+        // transaction until it is submitted.
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![main_account_address],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            &wallet_db,
+            &AccountID(vo_account_id.as_str().unwrap().to_string()),
+            &logger,
+        );
+
         let account = result.get("account").unwrap();
         let vo_account_id = account.get("id").unwrap();
         assert_eq!(vo_account_id, account_id);
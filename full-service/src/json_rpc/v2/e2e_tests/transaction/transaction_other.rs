@@ -136,6 +136,34 @@ mod e2e_transaction {
         assert_eq!(unspent, "1");
         assert_eq!(pending, "100000000000100");
 
+        let pending_outgoing = result.get("pending_outgoing").unwrap();
+        let pending_outgoing_mob = pending_outgoing.get(Mob::ID.to_string()).unwrap();
+        assert_eq!(pending_outgoing_mob["num_transactions"], "1");
+        assert_eq!(pending_outgoing_mob["value"], "42000000000000");
+
+        // The same status is available for all accounts at once
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_accounts",
+            "params": {
+                "include_status": true,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert!(result.get("network_block_height").is_some());
+        assert!(result.get("local_block_height").is_some());
+        let account_status = &result["status_map"][account_id];
+        assert_eq!(
+            account_status["balance_per_token"][Mob::ID.to_string()]["pending"],
+            "100000000000100"
+        );
+        assert_eq!(
+            account_status["pending_outgoing"][Mob::ID.to_string()]["num_transactions"],
+            "1"
+        );
+
         // Add a block with 1 MOB to increment height 2 times,
         // which should cause the previous transaction to
         // become invalid and free up the TXO as well as mark
@@ -198,6 +226,7 @@ mod e2e_transaction {
         assert_eq!(unspent, "100000000000103".to_string());
         assert_eq!(pending, "0");
         assert_eq!(spent, "0");
+        assert_eq!(result["pending_outgoing"], json!({}));
     }
 
     #[test_with_logger]
@@ -355,5 +384,29 @@ mod e2e_transaction {
         let result = res.get("result").unwrap();
         let status = result["receipt_transaction_status"].as_str().unwrap();
         assert_eq!(status, "TransactionSuccess");
+
+        // Bob checks the same receipt in a batch
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "check_receiver_receipts_status",
+            "params": {
+                "address": bob_b58_public_address,
+                "receiver_receipts": [receipt, receipt],
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let receipt_statuses = result["receipt_statuses"].as_array().unwrap();
+        assert_eq!(receipt_statuses.len(), 2);
+        for receipt_status in receipt_statuses {
+            assert_eq!(
+                receipt_status["receipt_transaction_status"]
+                    .as_str()
+                    .unwrap(),
+                "TransactionSuccess"
+            );
+            assert!(receipt_status["txo"].is_object());
+        }
     }
 }
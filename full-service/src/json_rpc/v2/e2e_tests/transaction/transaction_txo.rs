@@ -20,7 +20,7 @@ mod e2e_transaction {
         util::b58::b58_decode_public_address,
     };
 
-    use mc_account_keys::ShortAddressHash;
+    use mc_account_keys::{AccountKey, ShortAddressHash};
     use mc_common::logger::{test_with_logger, Logger};
     use mc_rand::rand_core::RngCore;
     use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, Token};
@@ -584,6 +584,50 @@ mod e2e_transaction {
         let value = txo.get("value").unwrap().as_str().unwrap();
         assert_eq!(value, "100");
 
+        // The txo was received in the last block, so it has not been unspent for a
+        // block until another block is added.
+        let get_stale_txos = |unspent_for_blocks: &str| {
+            let body = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "get_txos",
+                "params": {
+                    "account_id": account_id,
+                    "unspent_for_blocks": unspent_for_blocks,
+                }
+            });
+            let res = dispatch(&client, body, &logger);
+            res.get("result").unwrap()["txo_ids"]
+                .as_array()
+                .unwrap()
+                .len()
+        };
+        assert_eq!(get_stale_txos("0"), 1);
+        assert_eq!(get_stale_txos("1"), 0);
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &[AccountKey::random(&mut rng).default_subaddress()],
+            100,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        assert_eq!(get_stale_txos("1"), 1);
+        assert_eq!(get_stale_txos("1000"), 0);
+        assert_eq!(get_stale_txos(&u64::MAX.to_string()), 0);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_txos",
+            "params": {
+                "account_id": account_id,
+                "status": "spent",
+                "unspent_for_blocks": "1",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("error").is_some());
+
         // Check the overall balance for the account
         let body = json!({
             "jsonrpc": "2.0",
@@ -698,4 +742,130 @@ mod e2e_transaction {
 
         assert_eq!(txo.memo, expected_memo);
     }
+
+    #[test_with_logger]
+    fn test_get_txo_confirmation_number(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let (client, mut ledger_db, db_ctx, _network_state) = setup(&mut rng, logger.clone());
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "create_account",
+            "params": {
+                "name": "Alice Main Account",
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let account_obj = result.get("account").unwrap();
+        let account_id = account_obj.get("id").unwrap().as_str().unwrap();
+        let b58_public_address = account_obj.get("main_address").unwrap().as_str().unwrap();
+        let public_address = b58_decode_public_address(b58_public_address).unwrap();
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![public_address],
+            100_000_000_000_000, // 100.0 MOB
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            &db_ctx.get_db_instance(logger.clone()),
+            &AccountID(account_id.to_string()),
+            &logger,
+        );
+
+        // Txos received from outside the wallet have no confirmation number.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_txos",
+            "params": {
+                "account_id": account_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let txo_ids = result.get("txo_ids").unwrap().as_array().unwrap();
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_txo_confirmation_number",
+            "params": {
+                "txo_id": txo_ids[0],
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert!(result.get("confirmation").unwrap().is_null());
+
+        // Send to ourselves, so that the wallet knows the confirmation number of the
+        // received txo.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "build_and_submit_transaction",
+            "params": {
+                "account_id": account_id,
+                "recipient_public_address": b58_public_address,
+                "amount": { "value": "42000000000000", "token_id": "0" }, // 42.0 MOB
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let tx_proposal: TxProposalJSON =
+            serde_json::from_value(result.get("tx_proposal").unwrap().clone()).unwrap();
+        let transaction_log: TransactionLogJSON =
+            serde_json::from_value(result.get("transaction_log").unwrap().clone()).unwrap();
+        let payload_txo_id = transaction_log.output_txos[0].txo_id.clone();
+
+        // The payload txo is not owned by the account until it is received.
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_txo_confirmation_number",
+            "params": {
+                "txo_id": payload_txo_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        assert!(res.get("error").is_some());
+
+        let tx_proposal = TxProposal::try_from(&tx_proposal).unwrap();
+        add_block_with_tx(&mut ledger_db, tx_proposal.tx, &mut rng);
+        manually_sync_account(
+            &ledger_db,
+            &db_ctx.get_db_instance(logger.clone()),
+            &AccountID(account_id.to_string()),
+            &logger,
+        );
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "get_txo_confirmation_number",
+            "params": {
+                "txo_id": payload_txo_id,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        let confirmation = result.get("confirmation").unwrap().as_str().unwrap();
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "validate_confirmation",
+            "params": {
+                "account_id": account_id,
+                "txo_id": payload_txo_id,
+                "confirmation": confirmation,
+            }
+        });
+        let res = dispatch(&client, body, &logger);
+        let result = res.get("result").unwrap();
+        assert!(result.get("validated").unwrap().as_bool().unwrap());
+    }
 }
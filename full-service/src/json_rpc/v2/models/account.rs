@@ -2,10 +2,19 @@
 
 //! API definition for the Account object.
 
-use crate::{db, util::b58::b58_encode_public_address};
+use crate::{
+    db,
+    json_rpc::v2::models::{account_key::FogInfo, address::SubaddressMetadata},
+    service::account::AccountImport,
+    util::b58::b58_encode_public_address,
+};
 use mc_account_keys::PublicAddress;
+use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
 use serde_derive::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    convert::{TryFrom, TryInto},
+};
 
 #[derive(Deserialize, Serialize, Default, Debug, Clone)]
 pub struct AccountMap(pub BTreeMap<String, Account>);
@@ -53,6 +62,12 @@ pub struct Account {
     /// PublicAddressB58's with fog credentials.
     pub fog_enabled: bool,
 
+    /// The fog report server url of the account's addresses, if fog enabled.
+    pub fog_report_url: Option<String>,
+
+    /// The fog report id of the account's addresses, if fog enabled.
+    pub fog_report_id: Option<String>,
+
     /// A flag that indicates if this account is a watch only account.
     pub view_only: bool,
 
@@ -64,6 +79,24 @@ pub struct Account {
     /// specified when building a transaction in order to keep subaddress
     /// balances correct.
     pub require_spend_subaddress: bool,
+
+    /// A flag that indicates that Txos received by the account without a
+    /// sender memo are flagged, and listed by
+    /// `get_txos_missing_required_memo`.
+    pub require_sender_memo: bool,
+
+    /// The subaddress index used by `create_payment_request` and
+    /// `get_address_for_account` when no subaddress is given.
+    pub default_receive_subaddress_index: String,
+
+    /// A flag that indicates the account was imported and has not yet synced
+    /// up to the tip of the ledger, so its balance may still change as past
+    /// blocks are scanned.
+    pub import_in_progress: bool,
+
+    /// The SLIP-0010 account index the account key was derived at from its
+    /// mnemonic.
+    pub account_index: String,
 }
 
 impl Account {
@@ -85,9 +118,134 @@ impl Account {
             next_block_index: (src.next_block_index as u64).to_string(),
             recovery_mode: false,
             fog_enabled: src.fog_enabled,
+            fog_report_url: main_public_address.fog_report_url().map(String::from),
+            fog_report_id: main_public_address.fog_report_id().map(String::from),
             view_only: src.view_only,
             managed_by_hardware_wallet: src.managed_by_hardware_wallet,
             require_spend_subaddress: src.require_spend_subaddress,
+            require_sender_memo: src.require_sender_memo,
+            default_receive_subaddress_index: src.default_receive_subaddress_index.to_string(),
+            import_in_progress: src.import_in_progress,
+            account_index: src.account_index.to_string(),
         })
     }
 }
+
+/// An account to import with `import_accounts`, given either by its mnemonic,
+/// or by its view private key and spend public key for a view only account.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct ImportAccountSpec {
+    /// The mnemonic of an account with spend keys.
+    pub mnemonic: Option<String>,
+
+    /// The SLIP-0010 account index to derive the account key at from the
+    /// mnemonic, or that the keys of a view only account were derived at.
+    pub account_index: Option<String>,
+
+    /// The hex-encoded view private key of a view only account.
+    pub view_private_key: Option<String>,
+
+    /// The hex-encoded spend public key of a view only account.
+    pub spend_public_key: Option<String>,
+
+    pub name: Option<String>,
+
+    pub first_block_index: Option<String>,
+
+    pub next_subaddress_index: Option<String>,
+
+    /// Fog info, for an account imported from its mnemonic.
+    pub fog_info: Option<FogInfo>,
+
+    #[serde(default = "bool::default")] // default is false
+    pub require_spend_subaddress: bool,
+
+    /// Subaddresses to assign to a view only account, with their metadata.
+    pub subaddresses: Option<Vec<SubaddressMetadata>>,
+}
+
+/// The outcome of importing one of the accounts given to `import_accounts`.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct ImportAccountResult {
+    /// The imported account, if it was imported.
+    pub account: Option<Account>,
+
+    /// The reason the account was not imported, if it was not.
+    pub error: Option<String>,
+}
+
+impl TryFrom<&ImportAccountSpec> for AccountImport {
+    type Error = String;
+
+    fn try_from(src: &ImportAccountSpec) -> Result<AccountImport, String> {
+        let parse_u64 = |value: &Option<String>| {
+            value
+                .as_ref()
+                .map(|value| value.parse::<u64>())
+                .transpose()
+                .map_err(|err| format!("Could not parse u64: {err:?}"))
+        };
+        let account_index = src
+            .account_index
+            .as_ref()
+            .map(|account_index| account_index.parse::<u32>())
+            .transpose()
+            .map_err(|err| format!("Could not parse u32: {err:?}"))?;
+        let first_block_index = parse_u64(&src.first_block_index)?;
+        let next_subaddress_index = parse_u64(&src.next_subaddress_index)?;
+
+        match (&src.mnemonic, &src.view_private_key, &src.spend_public_key) {
+            (Some(mnemonic), None, None) => {
+                let fog_info = src.fog_info.clone().unwrap_or_default();
+                Ok(AccountImport::Mnemonic {
+                    mnemonic_phrase: mnemonic.clone(),
+                    account_index,
+                    name: src.name.clone(),
+                    first_block_index,
+                    next_subaddress_index,
+                    fog_report_url: fog_info.report_url,
+                    fog_authority_spki: fog_info.authority_spki,
+                    require_spend_subaddress: src.require_spend_subaddress,
+                })
+            }
+            (None, Some(view_private_key), Some(spend_public_key)) => {
+                let mut view_private_key_bytes = [0u8; 32];
+                hex::decode_to_slice(view_private_key, &mut view_private_key_bytes)
+                    .map_err(|err| format!("Could not decode hex for view_private_key: {err:?}"))?;
+                let view_private_key: RistrettoPrivate = (&view_private_key_bytes)
+                    .try_into()
+                    .map_err(|err| format!("Could not decode view private key: {err:?}"))?;
+
+                let mut spend_public_key_bytes = [0u8; 32];
+                hex::decode_to_slice(spend_public_key, &mut spend_public_key_bytes)
+                    .map_err(|err| format!("Could not decode hex for spend_public_key: {err:?}"))?;
+                let spend_public_key: RistrettoPublic = (&spend_public_key_bytes)
+                    .try_into()
+                    .map_err(|err| format!("Could not decode spend public key: {err:?}"))?;
+
+                let subaddresses = src
+                    .subaddresses
+                    .iter()
+                    .flatten()
+                    .map(|s| Ok((s.subaddress_index.parse::<u64>()?, s.metadata.clone())))
+                    .collect::<Result<Vec<_>, std::num::ParseIntError>>()
+                    .map_err(|err| format!("Could not parse u64: {err:?}"))?;
+
+                Ok(AccountImport::ViewOnly {
+                    view_private_key: view_private_key.into(),
+                    spend_public_key: spend_public_key.into(),
+                    account_index,
+                    name: src.name.clone(),
+                    first_block_index,
+                    next_subaddress_index,
+                    require_spend_subaddress: src.require_spend_subaddress,
+                    subaddresses,
+                })
+            }
+            _ => Err(
+                "Each account must have either a mnemonic, or a view_private_key and spend_public_key"
+                    .to_string(),
+            ),
+        }
+    }
+}
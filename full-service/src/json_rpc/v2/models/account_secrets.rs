@@ -35,6 +35,10 @@ pub struct AccountSecrets {
     /// The key derivation version that this mnemonic goes with
     pub key_derivation_version: String,
 
+    /// The SLIP-0010 account index the account key was derived at from the
+    /// mnemonic (version 2)
+    pub account_index: String,
+
     ///  Private keys for receiving and spending MobileCoin.
     #[serde(serialize_with = "expose_secret")]
     pub account_key: Secret<Option<AccountKey>>,
@@ -65,6 +69,7 @@ impl TryFrom<&Account> for AccountSecrets {
                 entropy: Secret::new(None),
                 mnemonic: Secret::new(None),
                 key_derivation_version: src.key_derivation_version.to_string(),
+                account_index: src.account_index.to_string(),
                 account_key: Secret::new(None),
                 view_account_key: Secret::new(Some(ViewAccountKey::from(&view_account_key))),
                 require_spend_subaddress: src.require_spend_subaddress,
@@ -99,6 +104,7 @@ impl TryFrom<&Account> for AccountSecrets {
                 entropy,
                 mnemonic,
                 key_derivation_version: src.key_derivation_version.to_string(),
+                account_index: src.account_index.to_string(),
                 account_key: Secret::new(Some(AccountKey::try_from(&account_key).map_err(
                     |err| {
                         format!("Could not convert account_key to json_rpc representation: {err:?}")
@@ -4,7 +4,10 @@
 
 use std::collections::BTreeMap;
 
-use crate::db::models::AssignedSubaddress;
+use crate::{
+    db::{models::AssignedSubaddress, txo::TxoID},
+    service::address::ScannedTxo as ServiceScannedTxo,
+};
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Default, Debug, Clone)]
@@ -34,6 +37,47 @@ pub struct Address {
     pub subaddress_index: String,
 }
 
+/// A subaddress to assign when importing an account, with the metadata to
+/// associate with it.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct SubaddressMetadata {
+    /// The index of the subaddress in the subaddress space for the account.
+    pub subaddress_index: String,
+
+    /// Additional data associated with this subaddress.
+    pub metadata: String,
+}
+
+/// A Txo received at an address, found by scanning a range of blocks with
+/// `scan_address_for_txos`.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct ScannedTxo {
+    /// The id the Txo will have in the wallet once its account has synced.
+    pub id: String,
+
+    /// The hex-encoded public key of the Txo.
+    pub public_key: String,
+
+    /// The index of the block the Txo was found in.
+    pub block_index: String,
+
+    pub value: String,
+
+    pub token_id: String,
+}
+
+impl From<&ServiceScannedTxo> for ScannedTxo {
+    fn from(src: &ServiceScannedTxo) -> ScannedTxo {
+        ScannedTxo {
+            id: TxoID::from(&src.tx_out).to_string(),
+            public_key: hex::encode(mc_util_serial::encode(&src.tx_out.public_key)),
+            block_index: src.block_index.to_string(),
+            value: src.amount.value.to_string(),
+            token_id: src.amount.token_id.to_string(),
+        }
+    }
+}
+
 impl From<&AssignedSubaddress> for Address {
     fn from(src: &AssignedSubaddress) -> Address {
         Address {
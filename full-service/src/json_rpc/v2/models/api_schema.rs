@@ -0,0 +1,30 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! API definition for the description of the API's own methods.
+
+use serde_derive::{Deserialize, Serialize};
+
+/// A method of the Wallet API, and the parameters it accepts.
+#[derive(Deserialize, Serialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct ApiMethod {
+    /// The name of the method, as given in a request's `method` field.
+    pub method: String,
+
+    /// The parameters of the method, in the order they are declared.
+    pub params: Vec<ApiParam>,
+}
+
+/// A single parameter of a Wallet API method.
+#[derive(Deserialize, Serialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct ApiParam {
+    /// The name of the parameter.
+    pub name: String,
+
+    /// The JSON type of the parameter: one of `string`, `number`, `boolean`,
+    /// `array`, or `object`. None if the type could not be determined.
+    #[serde(rename = "type")]
+    pub param_type: Option<String>,
+
+    /// Whether the parameter must be provided.
+    pub required: bool,
+}
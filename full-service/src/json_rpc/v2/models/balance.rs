@@ -4,7 +4,7 @@
 
 use std::collections::BTreeMap;
 
-use crate::service;
+use crate::{config::TokenMetadata, json_rpc::v2::models::is_secret_none, service};
 
 use redact::{expose_secret, Secret};
 use serde_derive::{Deserialize, Serialize};
@@ -51,10 +51,23 @@ pub struct Balance {
     /// index is recovered.
     #[serde(serialize_with = "expose_secret")]
     pub orphaned: Secret<String>,
+
+    /// The symbol of the token, if metadata is configured for it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_symbol: Option<String>,
+
+    /// The unspent value formatted as a decimal number of whole tokens, if
+    /// metadata is configured for the token.
+    #[serde(
+        default,
+        serialize_with = "expose_secret",
+        skip_serializing_if = "is_secret_none"
+    )]
+    pub unspent_decimal: Secret<Option<String>>,
 }
 
-impl From<&service::balance::Balance> for Balance {
-    fn from(src: &service::balance::Balance) -> Balance {
+impl Balance {
+    pub fn new(src: &service::balance::Balance, token_metadata: Option<&TokenMetadata>) -> Balance {
         Balance {
             max_spendable: src.max_spendable.to_string().into(),
             unverified: src.unverified.to_string().into(),
@@ -63,6 +76,114 @@ impl From<&service::balance::Balance> for Balance {
             spent: src.spent.to_string().into(),
             secreted: src.secreted.to_string().into(),
             orphaned: src.orphaned.to_string().into(),
+            token_symbol: token_metadata.map(|m| m.symbol.clone()),
+            unspent_decimal: token_metadata.map(|m| m.format_value(src.unspent)).into(),
+        }
+    }
+}
+
+impl From<&service::balance::Balance> for Balance {
+    fn from(src: &service::balance::Balance) -> Balance {
+        Balance::new(src, None)
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct PendingOutgoingMap(pub BTreeMap<String, PendingOutgoing>);
+
+/// A summary of the outgoing transactions for a token which have been
+/// submitted but are not yet finalized or failed.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct PendingOutgoing {
+    /// The number of pending outgoing transactions.
+    pub num_transactions: String,
+
+    /// The total value of the pending outgoing transactions, excluding change.
+    #[serde(serialize_with = "expose_secret")]
+    pub value: Secret<String>,
+}
+
+impl From<&service::transaction_log::PendingOutgoing> for PendingOutgoing {
+    fn from(src: &service::transaction_log::PendingOutgoing) -> PendingOutgoing {
+        PendingOutgoing {
+            num_transactions: src.num_transactions.to_string(),
+            value: src.value.to_string().into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct AccountStatusMap(pub BTreeMap<String, AccountStatus>);
+
+/// The balances and pending outgoing transactions for a single account, as
+/// returned by `get_accounts` when `include_status` is set.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct AccountStatus {
+    /// The balance of the account for each token it has received.
+    pub balance_per_token: BalanceMap,
+
+    /// A summary of the account's submitted but not yet finalized
+    /// transactions, per token.
+    pub pending_outgoing: PendingOutgoingMap,
+}
+
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct AddressBalanceMap(pub BTreeMap<String, AddressBalance>);
+
+/// The unspent value and number of received txos for a single address, as
+/// returned by `get_addresses` when `include_balances` is set.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct AddressBalance {
+    /// The unspent value at the address for each token it has received.
+    #[serde(serialize_with = "expose_secret")]
+    pub unspent_per_token: Secret<BTreeMap<String, String>>,
+
+    /// The number of txos received at the address.
+    pub num_received_txos: String,
+}
+
+impl From<&service::balance::AddressBalance> for AddressBalance {
+    fn from(src: &service::balance::AddressBalance) -> AddressBalance {
+        AddressBalance {
+            unspent_per_token: src
+                .unspent_per_token
+                .iter()
+                .map(|(token_id, value)| (token_id.to_string(), value.to_string()))
+                .collect::<BTreeMap<_, _>>()
+                .into(),
+            num_received_txos: src.num_received_txos.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct LifetimeStatsMap(pub BTreeMap<String, LifetimeStats>);
+
+/// The total value an account has received and sent over its lifetime for a
+/// single token.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct LifetimeStats {
+    /// The total value of all txos received by the account, whether or not
+    /// they have since been spent. Change is not included.
+    #[serde(serialize_with = "expose_secret")]
+    pub received: Secret<String>,
+
+    /// The total value of the outputs of the account's submitted transactions.
+    /// Change, fees, and failed transactions are not included.
+    #[serde(serialize_with = "expose_secret")]
+    pub sent: Secret<String>,
+
+    /// The received value less the sent value. May be negative.
+    #[serde(serialize_with = "expose_secret")]
+    pub net: Secret<String>,
+}
+
+impl From<&service::balance::LifetimeStats> for LifetimeStats {
+    fn from(src: &service::balance::LifetimeStats) -> LifetimeStats {
+        LifetimeStats {
+            received: src.received.to_string().into(),
+            sent: src.sent.to_string().into(),
+            net: src.net().to_string().into(),
         }
     }
 }
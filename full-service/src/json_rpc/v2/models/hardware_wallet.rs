@@ -0,0 +1,46 @@
+// Copyright (c) 2020-2023 MobileCoin Inc.
+
+//! API definition for the Hardware Wallet Device object.
+
+use crate::service;
+use serde_derive::{Deserialize, Serialize};
+
+/// A hardware wallet connected to the machine running full-service.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct HardwareWalletDevice {
+    /// Identifies the device. Pass this as `device_id` to target the device
+    /// when more than one is connected.
+    pub id: String,
+
+    /// A human readable description of the device.
+    pub label: String,
+}
+
+impl From<&service::hardware_wallet::HardwareWalletDevice> for HardwareWalletDevice {
+    fn from(src: &service::hardware_wallet::HardwareWalletDevice) -> Self {
+        Self {
+            id: src.id.clone(),
+            label: src.label.clone(),
+        }
+    }
+}
+
+/// Progress of generating key images for an account's TXOs on a hardware
+/// wallet, as part of `sync_view_only_account`.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct HardwareWalletSyncProgress {
+    /// The number of TXOs whose key images have been generated.
+    pub completed: String,
+
+    /// The number of TXOs in the sync.
+    pub total: String,
+}
+
+impl From<&service::hardware_wallet::SyncProgress> for HardwareWalletSyncProgress {
+    fn from(src: &service::hardware_wallet::SyncProgress) -> Self {
+        Self {
+            completed: src.completed.to_string(),
+            total: src.total.to_string(),
+        }
+    }
+}
@@ -3,9 +3,11 @@ pub mod account_key;
 pub mod account_secrets;
 pub mod address;
 pub mod amount;
+pub mod api_schema;
 pub mod balance;
 pub mod block;
 pub mod confirmation_number;
+pub mod hardware_wallet;
 pub mod ledger;
 pub mod masked_amount;
 pub mod memo;
@@ -17,3 +19,11 @@ pub mod tx_proposal;
 pub mod txo;
 pub mod wallet_status;
 pub mod watcher;
+
+use redact::Secret;
+
+/// Whether an optional secret is empty, so that it can be left out of a
+/// response.
+pub fn is_secret_none<T>(value: &Secret<Option<T>>) -> bool {
+    value.expose_secret().is_none()
+}
@@ -31,6 +31,10 @@ pub struct NetworkStatus {
 
     /// How we're connecting to the network
     pub network_info: NetworkConfig,
+
+    /// How many milliseconds ago the network block height, fees and block
+    /// version were fetched from the network. Null in offline mode.
+    pub block_info_age_ms: Option<String>,
 }
 
 impl TryFrom<&service::balance::NetworkStatus> for NetworkStatus {
@@ -49,6 +53,7 @@ impl TryFrom<&service::balance::NetworkStatus> for NetworkStatus {
             block_version: src.block_version.to_string(),
             max_tombstone_blocks: constants::MAX_TOMBSTONE_BLOCKS.to_string(),
             network_info: src.network_info.clone(),
+            block_info_age_ms: src.block_info_age.map(|age| age.as_millis().to_string()),
         })
     }
 }
@@ -2,7 +2,10 @@
 
 //! API definition for the ReceiverReceipt object.
 
-use crate::{json_rpc::v2::models::masked_amount::MaskedAmount, service};
+use crate::{
+    json_rpc::v2::models::{masked_amount::MaskedAmount, txo::Txo},
+    service::{self, receipt::ReceiptTransactionStatus},
+};
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_transaction_extra::TxOutConfirmationNumber;
 use serde_derive::{Deserialize, Serialize};
@@ -126,3 +129,13 @@ mod tests {
         assert_eq!(service_receipt, service_receipt_from_json);
     }
 }
+
+/// The status of a receipt, as checked by the receiver.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ReceiverReceiptStatus {
+    /// The status of the transaction the receipt is for.
+    pub receipt_transaction_status: ReceiptTransactionStatus,
+
+    /// The received Txo, if it is in the wallet.
+    pub txo: Option<Txo>,
+}
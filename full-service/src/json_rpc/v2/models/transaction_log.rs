@@ -48,8 +48,8 @@ pub struct TransactionLog {
     pub finalized_block_index: Option<String>,
 
     /// String representing the transaction log status. On "sent", valid
-    /// statuses are "built", "pending", "succeeded", "failed".  On "received",
-    /// the status is "succeeded".
+    /// statuses are "built", "pending", "succeeded", "failed", "conflicting".
+    /// On "received", the status is "succeeded".
     pub status: String,
 
     /// Time at which sent transaction log was created. Only available if
@@ -2,7 +2,12 @@
 
 //! API definition for the Txo object.
 
-use crate::{db::txo::TxoInfo, json_rpc::v2::models::memo::Memo};
+use crate::{
+    config::TokenMetadataMap,
+    db::{self, txo::TxoInfo},
+    json_rpc::v2::models::{amount::Amount, is_secret_none, memo::Memo},
+    service,
+};
 use redact::{expose_secret, Secret};
 use serde_derive::{Deserialize, Serialize};
 
@@ -68,10 +73,24 @@ pub struct Txo {
     #[serde(serialize_with = "expose_secret")]
     pub shared_secret: Secret<Option<String>>,
     pub memo: Memo,
+
+    /// The symbol of the txo's token, if metadata is configured for it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_symbol: Option<String>,
+
+    /// The txo's value formatted as a decimal number of whole tokens, if
+    /// metadata is configured for its token.
+    #[serde(
+        default,
+        serialize_with = "expose_secret",
+        skip_serializing_if = "is_secret_none"
+    )]
+    pub value_decimal: Secret<Option<String>>,
 }
 
-impl From<&TxoInfo> for Txo {
-    fn from(txo_info: &TxoInfo) -> Self {
+impl Txo {
+    pub fn new(txo_info: &TxoInfo, token_metadata: &TokenMetadataMap) -> Self {
+        let token_metadata = token_metadata.get(txo_info.txo.token_id as u64);
         Txo {
             id: txo_info.txo.id.clone(),
             value: (txo_info.txo.value as u64).to_string().into(),
@@ -98,6 +117,106 @@ impl From<&TxoInfo> for Txo {
             confirmation: txo_info.txo.confirmation.as_ref().map(hex::encode).into(),
             shared_secret: txo_info.txo.shared_secret.as_ref().map(hex::encode).into(),
             memo: (&txo_info.memo).into(),
+            token_symbol: token_metadata.map(|m| m.symbol.clone()),
+            value_decimal: token_metadata
+                .map(|m| m.format_value(u128::from(txo_info.txo.value as u64)))
+                .into(),
+        }
+    }
+}
+
+impl From<&TxoInfo> for Txo {
+    fn from(txo_info: &TxoInfo) -> Self {
+        Txo::new(txo_info, &TokenMetadataMap::default())
+    }
+}
+
+/// The key image of a spent Txo, and the block in which it was spent.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct SpentKeyImage {
+    /// Unique identifier for the Txo.
+    pub txo_id: String,
+
+    /// The hex-encoded key image of the Txo, as it appears in the ledger.
+    #[serde(serialize_with = "expose_secret")]
+    pub key_image: Secret<String>,
+
+    /// Block index in which the Txo was spent.
+    pub spent_block_index: String,
+}
+
+impl From<&service::txo::SpentKeyImage> for SpentKeyImage {
+    fn from(src: &service::txo::SpentKeyImage) -> Self {
+        SpentKeyImage {
+            txo_id: src.txo_id.clone(),
+            key_image: hex::encode(src.key_image.as_bytes()).into(),
+            spent_block_index: src.spent_block_index.to_string(),
+        }
+    }
+}
+
+/// An event in the lifecycle of a Txo.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct TxoEvent {
+    /// The kind of event: `created`, `received`, `used_as_input` or `spent`.
+    pub event_type: String,
+
+    /// The block index at which the event happened. For events from a
+    /// transaction, the block index at which it was submitted, if it was.
+    pub block_index: Option<String>,
+
+    /// The transaction log which referenced the Txo, for events from a
+    /// transaction.
+    pub transaction_log_id: Option<String>,
+}
+
+impl From<&db::txo::TxoEvent> for TxoEvent {
+    fn from(src: &db::txo::TxoEvent) -> Self {
+        TxoEvent {
+            event_type: src.event_type.to_string(),
+            block_index: src.block_index.map(|i| i.to_string()),
+            transaction_log_id: src.transaction_log_id.clone(),
+        }
+    }
+}
+
+/// A Txo which would be selected as an input, and its value.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct SelectedTxo {
+    /// Unique identifier for the Txo.
+    pub txo_id: String,
+
+    /// The value of the Txo.
+    pub value: String,
+}
+
+/// The Txos which would be selected as inputs to spend a value, along with the
+/// fee the transaction would pay.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct SpendableSelection {
+    /// The Txos which would be selected as inputs.
+    pub txos: Vec<SelectedTxo>,
+
+    /// The total value of the selected Txos.
+    pub total_value: String,
+
+    /// The fee the transaction would pay.
+    pub fee: Amount,
+}
+
+impl From<&service::txo::SpendableSelection> for SpendableSelection {
+    fn from(src: &service::txo::SpendableSelection) -> Self {
+        SpendableSelection {
+            txos: src
+                .txos
+                .iter()
+                .map(|txo| SelectedTxo {
+                    txo_id: txo.id.clone(),
+                    value: (txo.value as u64).to_string(),
+                })
+                .collect(),
+            total_value: src.total_value.to_string(),
+            fee: Amount::new(src.fee_value, src.token_id),
         }
     }
 }
@@ -106,6 +225,7 @@ impl From<&TxoInfo> for Txo {
 mod tests {
     use super::*;
     use crate::{
+        config::TokenMetadata,
         db,
         db::{account::AccountModel, models::Account, txo::TxoModel},
         test_utils::{create_test_received_txo, WalletDbTestContext, MOB},
@@ -115,6 +235,7 @@ mod tests {
     use mc_transaction_core::{tokens::Mob, Amount, Token};
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
+    use std::collections::BTreeMap;
 
     #[test_with_logger]
     fn test_display_txo_in_origin(logger: Logger) {
@@ -166,5 +287,26 @@ mod tests {
         assert_eq!(json_txo.value.expose_secret(), "15625000000000000000");
         assert_eq!(json_txo.token_id.expose_secret(), "0");
         assert_eq!(json_txo.memo, (&memo).into());
+        assert_eq!(json_txo.token_symbol, None);
+        assert_eq!(json_txo.value_decimal.expose_secret(), &None);
+
+        // Fields without metadata are left out of the response.
+        let json = serde_json::to_value(&json_txo).unwrap();
+        assert!(json.get("token_symbol").is_none());
+        assert!(json.get("value_decimal").is_none());
+
+        let token_metadata = TokenMetadataMap(BTreeMap::from([(
+            0,
+            TokenMetadata {
+                symbol: "MOB".to_string(),
+                decimals: 12,
+            },
+        )]));
+        let json_txo = Txo::new(&txo_info, &token_metadata);
+        assert_eq!(json_txo.token_symbol, Some("MOB".to_string()));
+        assert_eq!(
+            json_txo.value_decimal.expose_secret(),
+            &Some("15625000.000000000000".to_string())
+        );
     }
 }
@@ -2,7 +2,7 @@
 
 //! API definition for the Wallet Status object.
 
-use crate::{json_rpc::v2::models::balance::Balance, service};
+use crate::{config::TokenMetadataMap, json_rpc::v2::models::balance::Balance, service};
 
 use serde_derive::{Deserialize, Serialize};
 use std::{collections::BTreeMap, convert::TryFrom};
@@ -26,23 +26,48 @@ pub struct WalletStatus {
     /// The minimum synced block across all accounts
     pub min_synced_block_index: String,
 
+    /// The rolling average of blocks scanned per second across all accounts,
+    /// or null if no account has scanned blocks recently.
+    pub sync_blocks_per_second: Option<String>,
+
+    /// The estimated number of seconds until all accounts are synced to the
+    /// network_block_height, or null if it can not yet be estimated.
+    pub sync_seconds_remaining: Option<String>,
+
     pub balance_per_token: BTreeMap<String, Balance>,
 }
 
-impl TryFrom<&service::balance::WalletStatus> for WalletStatus {
-    type Error = String;
-
-    fn try_from(src: &service::balance::WalletStatus) -> Result<WalletStatus, String> {
-        Ok(WalletStatus {
+impl WalletStatus {
+    pub fn new(
+        src: &service::balance::WalletStatus,
+        token_metadata: &TokenMetadataMap,
+    ) -> WalletStatus {
+        WalletStatus {
             network_block_height: src.network_block_height.to_string(),
             local_block_height: src.local_block_height.to_string(),
             is_synced_all: src.min_synced_block_index + 1 >= src.network_block_height,
             min_synced_block_index: src.min_synced_block_index.to_string(),
+            sync_blocks_per_second: src
+                .sync_estimate
+                .blocks_per_second
+                .map(|rate| format!("{rate:.2}")),
+            sync_seconds_remaining: src
+                .sync_estimate
+                .time_remaining
+                .map(|time_remaining| time_remaining.as_secs().to_string()),
             balance_per_token: src
                 .balance_per_token
                 .iter()
-                .map(|(k, v)| (k.to_string(), Balance::from(v)))
+                .map(|(k, v)| (k.to_string(), Balance::new(v, token_metadata.get(**k))))
                 .collect(),
-        })
+        }
+    }
+}
+
+impl TryFrom<&service::balance::WalletStatus> for WalletStatus {
+    type Error = String;
+
+    fn try_from(src: &service::balance::WalletStatus) -> Result<WalletStatus, String> {
+        Ok(WalletStatus::new(src, &TokenMetadataMap::default()))
     }
 }
@@ -47,3 +47,43 @@ impl From<&crate::service::models::watcher::WatcherBlockInfo> for WatcherBlockIn
         }
     }
 }
+
+/// The last block the Watcher synced from one of its sources.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct WatcherSourceStatus {
+    pub url: String,
+    pub last_synced_block_index: Option<String>,
+}
+
+/// How far the Watcher has synced, compared to the local ledger.
+#[derive(Deserialize, Serialize, Default, Debug, Clone)]
+pub struct WatcherStatus {
+    /// The highest block the Watcher has a timestamp for.
+    pub highest_timestamped_block_index: Option<String>,
+
+    /// The number of blocks in the local ledger the Watcher has not yet
+    /// timestamped.
+    pub num_blocks_behind: String,
+
+    /// The last block synced from each of the Watcher's source URLs.
+    pub sources: Vec<WatcherSourceStatus>,
+}
+
+impl From<&crate::service::models::watcher::WatcherStatus> for WatcherStatus {
+    fn from(src: &crate::service::models::watcher::WatcherStatus) -> Self {
+        Self {
+            highest_timestamped_block_index: src
+                .highest_timestamped_block_index
+                .map(|block_index| block_index.to_string()),
+            num_blocks_behind: src.num_blocks_behind.to_string(),
+            sources: src
+                .sources
+                .iter()
+                .map(|(url, last_synced)| WatcherSourceStatus {
+                    url: url.to_string(),
+                    last_synced_block_index: last_synced.map(|block_index| block_index.to_string()),
+                })
+                .collect(),
+        }
+    }
+}
@@ -3,9 +3,14 @@
 //! Entrypoint for Wallet API.
 
 use crate::{
+    config::{ApiKeyConfig, ApiKeysConfig},
+    error::ErrorCode,
     json_rpc::{
+        audit_log::AuditLogState,
+        gzip::{Gzip, GzipJson},
         json_rpc_request::JsonRPCRequest,
         json_rpc_response::JsonRPCResponse,
+        rate_limit::RateLimitGuard,
         v1::api::{
             request::help_str as help_str_v1,
             response::JsonCommandResponse as JsonCommandResponse_v1,
@@ -17,13 +22,20 @@ use crate::{
             wallet::generic_wallet_api as generic_wallet_api_v2,
         },
     },
-    service::WalletService,
+    metrics,
+    service::{
+        ledger::LedgerService, submission_tracker::SubmissionTracker, sync::SyncThread,
+        WalletService,
+    },
 };
+use displaydoc::Display;
+use mc_common::logger::global_log;
 use mc_connection::{
     BlockchainConnection, HardcodedCredentialsProvider, ThickClient, UserTxConnection,
 };
 use mc_fog_report_resolver::FogResolver;
 use mc_fog_report_validation::FogPubkeyResolver;
+use mc_ledger_db::Ledger;
 use mc_validator_connection::ValidatorConnection;
 use rocket::{
     self,
@@ -35,7 +47,14 @@ use rocket::{
     request::FromRequest,
     routes,
     serde::json::Json,
-    Request, Response, State,
+    tokio, Request, Response, State,
+};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    iter::FromIterator,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 /// State managed by rocket.
@@ -49,11 +68,63 @@ pub struct WalletState<
 
 pub const API_KEY_HEADER: &str = "X-API-KEY";
 
-pub struct APIKeyState(pub String);
+/// The API keys accepted by the wallet API, each with the methods it may
+/// invoke, or None if it may invoke every method.
+pub struct APIKeyState(pub HashMap<String, Option<HashSet<String>>>);
+
+impl APIKeyState {
+    /// Accept a single key, which may invoke every method. If the key is
+    /// empty, requests made without a key are accepted.
+    pub fn single(api_key: String) -> Self {
+        Self(HashMap::from([(api_key, None)]))
+    }
+
+    /// Accept the key from `MC_API_KEY`, which may invoke every method, along
+    /// with the keys from an API keys file, which may be scoped to some
+    /// methods. When there is an API keys file, requests made without a key
+    /// are only accepted if the file includes an empty key.
+    pub fn new(api_key: String, api_keys: Option<&ApiKeysConfig>) -> Self {
+        let api_keys = match api_keys {
+            Some(api_keys) => api_keys,
+            None => return Self::single(api_key),
+        };
+
+        let mut keys = HashMap::new();
+        if !api_key.is_empty() {
+            keys.insert(api_key, None);
+        }
+        for ApiKeyConfig { key, methods } in &api_keys.0 {
+            keys.insert(
+                key.clone(),
+                methods
+                    .as_ref()
+                    .map(|methods| methods.iter().cloned().collect()),
+            );
+        }
+        Self(keys)
+    }
+}
 
 /// Ensures check for a pre-shared symmetric API key for the JsonRPC loop on the
-/// Mobilecoin wallet.
-pub struct ApiKeyGuard {}
+/// Mobilecoin wallet. Holds the methods the key may invoke, or None if it may
+/// invoke every method.
+pub struct ApiKeyGuard {
+    allowed_methods: Option<HashSet<String>>,
+}
+
+impl ApiKeyGuard {
+    /// Check whether the key the request was made with may invoke a method.
+    pub fn check(&self, method: &str) -> Result<(), MethodFilterError> {
+        match &self.allowed_methods {
+            Some(allowed_methods)
+                if !allowed_methods.contains(method) || ADMIN_METHODS.contains(&method) =>
+            {
+                Err(MethodFilterError::NotAllowedForApiKey(method.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum ApiKeyError {
@@ -70,8 +141,8 @@ impl<'r> FromRequest<'r> for ApiKeyGuard {
     ) -> Outcome<Self, (rocket::http::Status, Self::Error), rocket::http::Status> {
         let client_key = req.headers().get_one(API_KEY_HEADER).unwrap_or_default();
         // let outcome = req.guard::<State<APIKeyState>>().await;
-        let local_key = match req.guard::<&State<APIKeyState>>().await {
-            Outcome::Success(api_key_state) => api_key_state.0.clone(),
+        let api_key_state = match req.guard::<&State<APIKeyState>>().await {
+            Outcome::Success(api_key_state) => api_key_state,
             Outcome::Error(_) => {
                 return Outcome::Error((
                     Status::Unauthorized,
@@ -86,18 +157,85 @@ impl<'r> FromRequest<'r> for ApiKeyGuard {
             }
         };
 
-        if local_key == client_key {
-            Outcome::Success(ApiKeyGuard {})
-        } else {
-            Outcome::Error((Status::Unauthorized, ApiKeyError::Invalid))
+        match api_key_state.0.get(client_key) {
+            Some(allowed_methods) => Outcome::Success(ApiKeyGuard {
+                allowed_methods: allowed_methods.clone(),
+            }),
+            None => Outcome::Error((Status::Unauthorized, ApiKeyError::Invalid)),
         }
     }
 }
 
-/// Add CORS headers for a specific origin. Required for full-service to be used
-/// by a browser.
+/// The origins allowed to make requests from a browser. Each is either an
+/// exact origin such as `https://app.example.com`, a wildcard matching the
+/// subdomains of a domain such as `https://*.example.com` or `*.example.com`,
+/// or `*` to allow every origin.
+#[derive(Clone, Debug, Default)]
+pub struct AllowedOrigins(pub Vec<String>);
+
+impl AllowedOrigins {
+    /// Whether an Origin header value matches one of the allowed origins.
+    pub fn allows(&self, origin: &str) -> bool {
+        self.0.iter().any(|pattern| origin_matches(pattern, origin))
+    }
+}
+
+fn origin_matches(pattern: &str, origin: &str) -> bool {
+    if pattern == "*" || pattern == origin {
+        return true;
+    }
+    let (scheme, domain) = match pattern.split_once("*.") {
+        Some(parts) => parts,
+        None => return false,
+    };
+    // A wildcard without a scheme matches any scheme.
+    let host = if scheme.is_empty() {
+        origin.split_once("://").map_or(origin, |(_, host)| host)
+    } else {
+        match origin.strip_prefix(scheme) {
+            Some(host) => host,
+            None => return false,
+        }
+    };
+    host.strip_suffix(domain)
+        .and_then(|subdomain| subdomain.strip_suffix('.'))
+        .map_or(false, |subdomain| !subdomain.is_empty())
+}
+
+/// Rejects requests from origins which are not allowed, when CORS is
+/// configured. Requests without an Origin header, which are not made by a
+/// browser, are always allowed.
+pub struct OriginGuard {}
+
+#[derive(Debug)]
+pub enum OriginError {
+    NotAllowed(String),
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for OriginGuard {
+    type Error = OriginError;
+
+    async fn from_request(
+        req: &'r Request<'_>,
+    ) -> Outcome<Self, (rocket::http::Status, Self::Error), rocket::http::Status> {
+        let allowed_origins = req.rocket().state::<AllowedOrigins>();
+        match (allowed_origins, req.headers().get_one("Origin")) {
+            (Some(allowed_origins), Some(origin)) if !allowed_origins.allows(origin) => {
+                Outcome::Error((
+                    Status::Forbidden,
+                    OriginError::NotAllowed(origin.to_string()),
+                ))
+            }
+            _ => Outcome::Success(OriginGuard {}),
+        }
+    }
+}
+
+/// Add CORS headers for the allowed origins. Required for full-service to be
+/// used by a browser.
 pub struct CORS {
-    allowed_origin: String,
+    allowed_origins: AllowedOrigins,
 }
 
 #[rocket::async_trait]
@@ -109,21 +247,205 @@ impl Fairing for CORS {
         }
     }
 
-    async fn on_response<'r>(&self, _request: &'r Request<'_>, response: &mut Response<'r>) {
-        response.set_header(Header::new(
-            "Access-Control-Allow-Origin",
-            self.allowed_origin.clone(),
-        ));
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        // Without CORS headers, the browser does not let a disallowed origin
+        // read the response.
+        let origin = match request.headers().get_one("Origin") {
+            Some(origin) if self.allowed_origins.allows(origin) => origin.to_string(),
+            _ => return,
+        };
+        response.set_header(Header::new("Access-Control-Allow-Origin", origin));
         response.set_header(Header::new("Access-Control-Allow-Methods", "POST, OPTIONS"));
         response.set_header(Header::new("Access-Control-Allow-Headers", "*"));
+        response.adjoin_header(Header::new("Vary", "Origin"));
     }
 }
 
+/// The v1 and v2 JSON-RPC methods which build, sign or submit transactions,
+/// or export or import account secrets, including the view private key of a
+/// view-only account import request. These are disabled in read-only mode.
+pub(crate) const FUND_MOVING_METHODS: &[&str] = &[
+    "build_and_submit_transaction",
+    "build_burn_transaction",
+    "build_gift_code",
+    "build_split_txo_transaction",
+    "build_transaction",
+    "build_unsigned_burn_transaction",
+    "build_unsigned_transaction",
+    "claim_gift_code",
+    "create_view_only_account_import_request",
+    "export_account_secrets",
+    "export_encrypted_account_secrets",
+    "import_account_from_encrypted_secrets",
+    "submit_gift_code",
+    "submit_raw_tx",
+    "submit_transaction",
+];
+
+/// The JSON-RPC methods which change how the wallet itself runs. These are
+/// disabled in read-only mode, and can not be invoked with a scoped API key.
+pub(crate) const ADMIN_METHODS: &[&str] =
+    &["set_log_level", "set_poll_interval", "set_tx_source_urls"];
+
+/// The JSON-RPC methods which may be invoked, as configured at startup.
+pub struct MethodFilterState {
+    /// If set, only these methods may be invoked.
+    enabled_methods: Option<HashSet<String>>,
+
+    /// Methods which may not be invoked.
+    disabled_methods: HashSet<String>,
+
+    /// Whether methods which could move funds, expose secrets or change how the
+    /// wallet runs are disabled.
+    read_only: bool,
+}
+
+#[derive(Display, Debug)]
+pub enum MethodFilterError {
+    /// Method disabled: {0}
+    MethodDisabled(String),
+
+    /// Method disabled in read-only mode: {0}
+    ReadOnly(String),
+
+    /// Method not allowed for this API key: {0}
+    NotAllowedForApiKey(String),
+}
+
+impl ErrorCode for MethodFilterError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::MethodDisabled(..) => "method_disabled",
+            Self::ReadOnly(..) => "read_only",
+            Self::NotAllowedForApiKey(..) => "not_allowed_for_api_key",
+        }
+    }
+}
+
+impl MethodFilterState {
+    pub fn new(
+        enabled_methods: Option<Vec<String>>,
+        disabled_methods: Option<Vec<String>>,
+        read_only: bool,
+    ) -> Self {
+        Self {
+            enabled_methods: enabled_methods.map(HashSet::from_iter),
+            disabled_methods: disabled_methods.map(HashSet::from_iter).unwrap_or_default(),
+            read_only,
+        }
+    }
+
+    /// Check whether the given method may be invoked.
+    pub fn check(&self, method: &str) -> Result<(), MethodFilterError> {
+        if self.read_only
+            && (FUND_MOVING_METHODS.contains(&method) || ADMIN_METHODS.contains(&method))
+        {
+            return Err(MethodFilterError::ReadOnly(method.to_string()));
+        }
+
+        let enabled = self
+            .enabled_methods
+            .as_ref()
+            .map_or(true, |enabled_methods| enabled_methods.contains(method));
+
+        if enabled && !self.disabled_methods.contains(method) {
+            Ok(())
+        } else {
+            Err(MethodFilterError::MethodDisabled(method.to_string()))
+        }
+    }
+}
+
+/// The number of blocks the local ledger may trail the network by while still
+/// being reported as ready.
+pub struct HealthCheckState(pub u64);
+
+/// Body of the readiness check response.
+#[derive(Serialize)]
+pub struct HealthStatus {
+    pub synced: bool,
+    pub network_block_height: String,
+    pub local_block_height: String,
+}
+
+/// Report whether the local ledger is within the configured number of blocks of
+/// the network tip. Returns 200 when synced and 503 while catching up. A wallet
+/// running offline has no network tip to chase and is always ready.
+fn health_status<T, FPR>(
+    state: &rocket::State<WalletState<T, FPR>>,
+    health_check_state: &HealthCheckState,
+) -> (Status, Json<HealthStatus>)
+where
+    T: BlockchainConnection + UserTxConnection + 'static,
+    FPR: FogPubkeyResolver + Send + Sync + 'static,
+{
+    let local_block_height = state.service.ledger_db.num_blocks().ok();
+    let network_block_height = if state.service.offline {
+        local_block_height
+    } else {
+        state.service.get_network_block_height().ok()
+    };
+
+    let synced = match (network_block_height, local_block_height) {
+        (Some(network_block_height), Some(local_block_height)) => {
+            network_block_height.saturating_sub(local_block_height) <= health_check_state.0
+        }
+        _ => false,
+    };
+
+    let status = if synced {
+        Status::Ok
+    } else {
+        Status::ServiceUnavailable
+    };
+
+    (
+        status,
+        Json(HealthStatus {
+            synced,
+            network_block_height: network_block_height.unwrap_or_default().to_string(),
+            local_block_height: local_block_height.unwrap_or_default().to_string(),
+        }),
+    )
+}
+
+/// Liveness check. Returns 200 whenever the server is up, regardless of sync
+/// state.
 #[get("/health")]
 fn health() -> Result<(), ()> {
     Ok(())
 }
 
+#[get("/ready")]
+fn consensus_backed_ready(
+    state: &rocket::State<WalletState<ThickClient<HardcodedCredentialsProvider>, FogResolver>>,
+    health_check_state: &rocket::State<HealthCheckState>,
+) -> (Status, Json<HealthStatus>) {
+    health_status(state, health_check_state)
+}
+
+#[get("/ready")]
+fn validator_backed_ready(
+    state: &rocket::State<WalletState<ValidatorConnection, FogResolver>>,
+    health_check_state: &rocket::State<HealthCheckState>,
+) -> (Status, Json<HealthStatus>) {
+    health_status(state, health_check_state)
+}
+
+#[get("/metrics")]
+fn consensus_backed_metrics(
+    state: &rocket::State<WalletState<ThickClient<HardcodedCredentialsProvider>, FogResolver>>,
+) -> String {
+    metrics::gather(&state.service)
+}
+
+#[get("/metrics")]
+fn validator_backed_metrics(
+    state: &rocket::State<WalletState<ValidatorConnection, FogResolver>>,
+) -> String {
+    metrics::gather(&state.service)
+}
+
 #[get("/wallet")]
 fn wallet_help_v1() -> Result<String, String> {
     Ok(help_str_v1())
@@ -132,20 +454,28 @@ fn wallet_help_v1() -> Result<String, String> {
 /// The route for the Full Service Wallet API.
 #[post("/wallet", format = "json", data = "<command>")]
 async fn consensus_backed_wallet_api_v1(
-    _api_key_guard: ApiKeyGuard,
+    api_key_guard: ApiKeyGuard,
+    _origin_guard: OriginGuard,
+    _rate_limit_guard: RateLimitGuard,
+    method_filter: &rocket::State<MethodFilterState>,
+    audit_log: &rocket::State<AuditLogState>,
     state: &rocket::State<WalletState<ThickClient<HardcodedCredentialsProvider>, FogResolver>>,
-    command: Json<JsonRPCRequest>,
+    command: GzipJson<JsonRPCRequest>,
 ) -> Result<Json<JsonRPCResponse<JsonCommandResponse_v1>>, String> {
-    generic_wallet_api_v1(_api_key_guard, state, command).await
+    generic_wallet_api_v1(api_key_guard, method_filter, audit_log, state, command.0).await
 }
 
 #[post("/wallet", format = "json", data = "<command>")]
 async fn validator_backed_wallet_api_v1(
-    _api_key_guard: ApiKeyGuard,
+    api_key_guard: ApiKeyGuard,
+    _origin_guard: OriginGuard,
+    _rate_limit_guard: RateLimitGuard,
+    method_filter: &rocket::State<MethodFilterState>,
+    audit_log: &rocket::State<AuditLogState>,
     state: &rocket::State<WalletState<ValidatorConnection, FogResolver>>,
-    command: Json<JsonRPCRequest>,
+    command: GzipJson<JsonRPCRequest>,
 ) -> Result<Json<JsonRPCResponse<JsonCommandResponse_v1>>, String> {
-    generic_wallet_api_v1(_api_key_guard, state, command).await
+    generic_wallet_api_v1(api_key_guard, method_filter, audit_log, state, command.0).await
 }
 
 #[get("/wallet/v2")]
@@ -156,21 +486,97 @@ fn wallet_help_v2() -> Result<String, String> {
 /// The route for the Full Service Wallet API.
 #[post("/wallet/v2", format = "json", data = "<command>")]
 async fn consensus_backed_wallet_api_v2(
-    _api_key_guard: ApiKeyGuard,
+    api_key_guard: ApiKeyGuard,
+    _origin_guard: OriginGuard,
+    _rate_limit_guard: RateLimitGuard,
+    method_filter: &rocket::State<MethodFilterState>,
+    audit_log: &rocket::State<AuditLogState>,
     state: &rocket::State<WalletState<ThickClient<HardcodedCredentialsProvider>, FogResolver>>,
-    command: Json<JsonRPCRequest>,
+    command: GzipJson<JsonRPCRequest>,
 ) -> Result<Json<JsonRPCResponse<JsonCommandResponse_v2>>, String> {
-    generic_wallet_api_v2(_api_key_guard, state, command).await
+    generic_wallet_api_v2(api_key_guard, method_filter, audit_log, state, command.0).await
 }
 
 #[post("/wallet/v2", format = "json", data = "<command>")]
 async fn validator_backed_wallet_api_v2(
-    _api_key_guard: ApiKeyGuard,
+    api_key_guard: ApiKeyGuard,
+    _origin_guard: OriginGuard,
+    _rate_limit_guard: RateLimitGuard,
+    method_filter: &rocket::State<MethodFilterState>,
+    audit_log: &rocket::State<AuditLogState>,
     state: &rocket::State<WalletState<ValidatorConnection, FogResolver>>,
-    command: Json<JsonRPCRequest>,
+    command: GzipJson<JsonRPCRequest>,
 ) -> Result<Json<JsonRPCResponse<JsonCommandResponse_v2>>, String> {
-    generic_wallet_api_v2(_api_key_guard, state, command).await
+    generic_wallet_api_v2(api_key_guard, method_filter, audit_log, state, command.0).await
+}
+
+/// How long before the end of Rocket's shutdown grace period to stop waiting,
+/// since requests still in progress are cancelled once it ends.
+const SHUTDOWN_MARGIN: Duration = Duration::from_secs(1);
+
+/// Waits on shutdown for transaction submissions in progress to be logged,
+/// while refusing new ones, and then stops the account sync thread once its
+/// current pass is written.
+pub struct GracefulShutdown {
+    submission_tracker: SubmissionTracker,
+    sync_thread: Option<Arc<SyncThread>>,
+}
+
+impl GracefulShutdown {
+    pub fn new<T, FPR>(service: &WalletService<T, FPR>) -> Self
+    where
+        T: BlockchainConnection + UserTxConnection + 'static,
+        FPR: FogPubkeyResolver + Send + Sync + 'static,
+    {
+        Self {
+            submission_tracker: service.submission_tracker.clone(),
+            sync_thread: service.sync_thread.clone(),
+        }
+    }
 }
+
+#[rocket::async_trait]
+impl Fairing for GracefulShutdown {
+    fn info(&self) -> Info {
+        Info {
+            name: "Graceful Shutdown",
+            kind: Kind::Shutdown,
+        }
+    }
+
+    async fn on_shutdown(&self, rocket: &rocket::Rocket<rocket::Orbit>) {
+        let grace = Duration::from_secs(rocket.config().shutdown.grace as u64);
+        let deadline = Instant::now() + grace.saturating_sub(SHUTDOWN_MARGIN);
+
+        let in_flight = self.submission_tracker.in_flight();
+        if in_flight > 0 {
+            global_log::info!(
+                "Waiting for {} transaction submissions to finish",
+                in_flight
+            );
+        }
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        if !self.submission_tracker.shut_down(timeout).await {
+            global_log::warn!(
+                "Shutting down with {} transaction submissions in progress",
+                self.submission_tracker.in_flight()
+            );
+        }
+
+        if let Some(sync_thread) = self.sync_thread.clone() {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let stopped = tokio::time::timeout(
+                timeout,
+                tokio::task::spawn_blocking(move || sync_thread.stop()),
+            )
+            .await;
+            if stopped.is_err() {
+                global_log::warn!("Shutting down before the sync thread stopped");
+            }
+        }
+    }
+}
+
 /// Needed to preflight OPTIONS queries for CORS.
 /// Catches all OPTION requests in order to get the CORS related Fairing
 /// triggered.
@@ -182,14 +588,17 @@ fn all_options() {
 /// Returns an instance of a Rocket server.
 pub fn consensus_backed_rocket(
     rocket_config: rocket::Config,
-    allowed_origin: Option<String>,
+    allowed_origins: Vec<String>,
 ) -> rocket::Rocket<rocket::Build> {
-    let mut consensus_rocket = rocket::custom(rocket_config);
+    let mut consensus_rocket = rocket::custom(rocket_config).attach(Gzip);
 
-    if let Some(origin) = allowed_origin {
-        consensus_rocket = consensus_rocket.attach(CORS {
-            allowed_origin: origin,
-        });
+    if !allowed_origins.is_empty() {
+        let allowed_origins = AllowedOrigins(allowed_origins);
+        consensus_rocket = consensus_rocket
+            .attach(CORS {
+                allowed_origins: allowed_origins.clone(),
+            })
+            .manage(allowed_origins);
     }
 
     consensus_rocket.mount(
@@ -200,6 +609,8 @@ pub fn consensus_backed_rocket(
             wallet_help_v1,
             wallet_help_v2,
             health,
+            consensus_backed_ready,
+            consensus_backed_metrics,
             all_options
         ],
     )
@@ -207,14 +618,17 @@ pub fn consensus_backed_rocket(
 
 pub fn validator_backed_rocket(
     rocket_config: rocket::Config,
-    allowed_origin: Option<String>,
+    allowed_origins: Vec<String>,
 ) -> rocket::Rocket<rocket::Build> {
-    let mut validator_rocket = rocket::custom(rocket_config);
+    let mut validator_rocket = rocket::custom(rocket_config).attach(Gzip);
 
-    if let Some(origin) = allowed_origin {
-        validator_rocket = validator_rocket.attach(CORS {
-            allowed_origin: origin,
-        });
+    if !allowed_origins.is_empty() {
+        let allowed_origins = AllowedOrigins(allowed_origins);
+        validator_rocket = validator_rocket
+            .attach(CORS {
+                allowed_origins: allowed_origins.clone(),
+            })
+            .manage(allowed_origins);
     }
 
     validator_rocket.mount(
@@ -225,7 +639,190 @@ pub fn validator_backed_rocket(
             wallet_help_v1,
             wallet_help_v2,
             health,
+            validator_backed_ready,
+            validator_backed_metrics,
             all_options
         ],
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_origins() {
+        let allowed_origins = AllowedOrigins(vec![
+            "https://wallet.example.org".to_string(),
+            "https://*.example.com".to_string(),
+            "*.example.net".to_string(),
+        ]);
+        assert!(allowed_origins.allows("https://wallet.example.org"));
+        assert!(!allowed_origins.allows("http://wallet.example.org"));
+        assert!(!allowed_origins.allows("https://other.example.org"));
+
+        assert!(allowed_origins.allows("https://app.example.com"));
+        assert!(allowed_origins.allows("https://a.b.example.com"));
+        assert!(!allowed_origins.allows("http://app.example.com"));
+        assert!(!allowed_origins.allows("https://example.com"));
+        assert!(!allowed_origins.allows("https://evilexample.com"));
+
+        assert!(allowed_origins.allows("http://app.example.net"));
+        assert!(allowed_origins.allows("https://app.example.net"));
+        assert!(!allowed_origins.allows("https://app.example.net.evil.com"));
+
+        assert!(AllowedOrigins(vec!["*".to_string()]).allows("https://anything.com"));
+        assert!(!AllowedOrigins::default().allows("https://anything.com"));
+    }
+
+    #[get("/hello")]
+    fn hello() -> &'static str {
+        "hello"
+    }
+
+    #[test]
+    fn test_cors_and_gzip_both_vary() {
+        let rocket = rocket::build()
+            .attach(Gzip)
+            .attach(CORS {
+                allowed_origins: AllowedOrigins(vec!["*".to_string()]),
+            })
+            .mount("/", routes![hello]);
+        let client = rocket::local::blocking::Client::untracked(rocket).unwrap();
+
+        let res = client
+            .get("/hello")
+            .header(Header::new("Origin", "https://app.example.com"))
+            .header(Header::new("Accept-Encoding", "gzip"))
+            .dispatch();
+        assert_eq!(res.headers().get_one("Content-Encoding"), Some("gzip"));
+        assert_eq!(
+            res.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://app.example.com")
+        );
+        let vary: Vec<&str> = res.headers().get("Vary").collect();
+        assert!(vary.contains(&"Accept-Encoding"));
+        assert!(vary.contains(&"Origin"));
+    }
+
+    #[test]
+    fn test_api_key_scopes() {
+        // Without an API keys file, the single key may invoke every method.
+        let state = APIKeyState::new(String::new(), None);
+        assert_eq!(state.0, HashMap::from([(String::new(), None)]));
+
+        let api_keys = ApiKeysConfig(vec![
+            ApiKeyConfig {
+                key: "reader".to_string(),
+                methods: Some(vec!["get_account_status".to_string()]),
+            },
+            ApiKeyConfig {
+                key: "operator".to_string(),
+                methods: None,
+            },
+        ]);
+
+        // With one, requests without a key are no longer accepted.
+        let state = APIKeyState::new(String::new(), Some(&api_keys));
+        assert!(!state.0.contains_key(""));
+        let state = APIKeyState::new("admin".to_string(), Some(&api_keys));
+        assert_eq!(state.0.get("admin"), Some(&None));
+        assert_eq!(state.0.get("operator"), Some(&None));
+
+        let reader = ApiKeyGuard {
+            allowed_methods: state.0.get("reader").cloned().unwrap(),
+        };
+        assert!(reader.check("get_account_status").is_ok());
+        assert_eq!(
+            reader
+                .check("build_and_submit_transaction")
+                .unwrap_err()
+                .to_string(),
+            "Method not allowed for this API key: build_and_submit_transaction"
+        );
+        let operator = ApiKeyGuard {
+            allowed_methods: None,
+        };
+        assert!(operator.check("build_and_submit_transaction").is_ok());
+
+        // Scoped keys can not change how the wallet runs, even if the methods
+        // are listed.
+        let scoped_admin = ApiKeyGuard {
+            allowed_methods: Some(
+                ADMIN_METHODS
+                    .iter()
+                    .map(|method| method.to_string())
+                    .collect(),
+            ),
+        };
+        for method in ["set_log_level", "set_poll_interval", "set_tx_source_urls"] {
+            assert!(operator.check(method).is_ok());
+            assert!(scoped_admin.check(method).is_err());
+        }
+    }
+
+    #[test]
+    fn test_method_filter() {
+        let allow_all = MethodFilterState::new(None, None, false);
+        assert!(allow_all.check("create_account").is_ok());
+
+        let enabled = MethodFilterState::new(
+            Some(vec![
+                "get_accounts".to_string(),
+                "submit_transaction".to_string(),
+            ]),
+            None,
+            false,
+        );
+        assert!(enabled.check("get_accounts").is_ok());
+        assert!(enabled.check("submit_transaction").is_ok());
+        assert!(enabled.check("create_account").is_err());
+
+        let disabled = MethodFilterState::new(
+            None,
+            Some(vec![
+                "create_account".to_string(),
+                "export_account_secrets".to_string(),
+            ]),
+            false,
+        );
+        assert!(disabled.check("get_accounts").is_ok());
+        assert!(disabled.check("create_account").is_err());
+        assert_eq!(
+            disabled
+                .check("export_account_secrets")
+                .unwrap_err()
+                .to_string(),
+            "Method disabled: export_account_secrets"
+        );
+
+        // Read-only mode wins over explicitly enabled methods.
+        let read_only = MethodFilterState::new(
+            Some(vec![
+                "get_accounts".to_string(),
+                "submit_transaction".to_string(),
+            ]),
+            None,
+            true,
+        );
+        assert!(read_only.check("get_accounts").is_ok());
+        assert_eq!(
+            read_only
+                .check("submit_transaction")
+                .unwrap_err()
+                .to_string(),
+            "Method disabled in read-only mode: submit_transaction"
+        );
+        assert!(MethodFilterState::new(None, None, true)
+            .check("export_account_secrets")
+            .is_err());
+        assert!(MethodFilterState::new(None, None, true)
+            .check("create_view_only_account_import_request")
+            .is_err());
+        for method in ["set_log_level", "set_poll_interval", "set_tx_source_urls"] {
+            assert!(MethodFilterState::new(None, None, true)
+                .check(method)
+                .is_err());
+        }
+    }
+}
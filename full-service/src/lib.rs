@@ -5,15 +5,20 @@
 #![feature(proc_macro_hygiene, decl_macro)]
 #![feature(assert_matches)]
 
+pub mod check_chain_id;
 pub mod check_host;
 pub mod config;
+mod consensus_ledger_sync;
 pub mod db;
 mod error;
 pub mod json_rpc;
+pub mod log_filter;
+pub mod metrics;
 pub mod service;
 pub mod util;
 mod validator_ledger_sync;
 
+pub use consensus_ledger_sync::{ConsensusLedgerSyncThread, ReloadableTransactionsFetcher};
 pub use db::WalletDb;
 pub use json_rpc::wallet;
 pub use service::WalletService;
@@ -0,0 +1,211 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! Filtering of log records by module, adjustable at runtime.
+//!
+//! The app logger from `mc_common` filters records once at startup using
+//! `MC_LOG` or `RUST_LOG`, which can only be changed by restarting. Instead,
+//! full-service creates its own app logger, which lets every record through,
+//! and filters records with [LOG_FILTER] before they reach it. The filter is
+//! configured from the same environment variables, and the `set_log_level`
+//! API method changes it while the wallet is running.
+
+use lazy_static::lazy_static;
+use mc_common::logger::{o, Logger};
+use slog::{Drain, Level, Never, OwnedKVList, Record};
+use slog_async::Async;
+use slog_term::{FullFormat, TermDecorator};
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+lazy_static! {
+    /// The filter applied to every record logged by full-service.
+    pub static ref LOG_FILTER: LogFilter = LogFilter::default();
+}
+
+/// The log level used when neither `MC_LOG` nor `RUST_LOG` are set.
+const DEFAULT_LEVEL: Level = Level::Info;
+
+/// Create the app logger, filtered by [LOG_FILTER], and install it as the
+/// global logger for the rest of the process, including for records from the
+/// `log` crate.
+pub fn create_filtered_app_logger() -> Logger {
+    let decorator = TermDecorator::new().stdout().build();
+    let drain = FullFormat::new(decorator).build().fuse();
+    let drain = Async::new(drain).build().fuse();
+    let logger = LOG_FILTER.wrap(Logger::root(drain, o!()));
+
+    // The guard is not kept, so that rocket can still use the global logger
+    // from its own async context.
+    slog_scope::set_global_logger(logger.clone()).cancel_reset();
+    slog_stdlog::init_with_level(log::Level::Trace).expect("Could not set the log crate logger");
+
+    logger
+}
+
+#[derive(Clone)]
+pub struct LogFilter {
+    directives: Arc<RwLock<Directives>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Directives {
+    /// The level for records from modules without a level of their own.
+    default_level: Level,
+
+    /// The level for records from each module, and the modules within it.
+    module_levels: BTreeMap<String, Level>,
+}
+
+impl Default for LogFilter {
+    /// A filter which lets every record through.
+    fn default() -> Self {
+        Self {
+            directives: Arc::new(RwLock::new(Directives {
+                default_level: Level::Trace,
+                module_levels: BTreeMap::new(),
+            })),
+        }
+    }
+}
+
+impl LogFilter {
+    /// Replace the filter's levels with those in a `RUST_LOG` style spec, such
+    /// as `info,mc_full_service::service::sync=debug`. Directives that can not
+    /// be parsed are ignored, as are message filters following a `/`.
+    pub fn set_spec(&self, spec: &str) {
+        let spec = spec.split('/').next().unwrap_or_default();
+        let mut directives = Directives {
+            default_level: DEFAULT_LEVEL,
+            module_levels: BTreeMap::new(),
+        };
+        for directive in spec.split(',').map(str::trim) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    if let Ok(level) = Level::from_str(level.trim()) {
+                        directives
+                            .module_levels
+                            .insert(module.trim().to_string(), level);
+                    }
+                }
+                None => {
+                    if let Ok(level) = Level::from_str(directive) {
+                        directives.default_level = level;
+                    }
+                }
+            }
+        }
+        *self.directives.write().expect("lock poisoned") = directives;
+    }
+
+    /// Set the filter's levels from `MC_LOG`, or `RUST_LOG` if it is not set.
+    pub fn set_spec_from_env(&self) {
+        let spec = std::env::var("MC_LOG")
+            .or_else(|_| std::env::var("RUST_LOG"))
+            .unwrap_or_default();
+        self.set_spec(&spec);
+    }
+
+    /// Set the level for a module and the modules within it, or the default
+    /// level if no module is given.
+    pub fn set_level(&self, module: Option<&str>, level: Level) {
+        let mut directives = self.directives.write().expect("lock poisoned");
+        match module {
+            Some(module) => {
+                directives.module_levels.insert(module.to_string(), level);
+            }
+            None => directives.default_level = level,
+        }
+    }
+
+    /// The default level, and the level set for each module.
+    pub fn levels(&self) -> (Level, BTreeMap<String, Level>) {
+        let directives = self.directives.read().expect("lock poisoned");
+        (directives.default_level, directives.module_levels.clone())
+    }
+
+    /// Whether a record at `level` from `module` should be logged. The level
+    /// set for the most specific module containing `module` applies.
+    pub fn is_enabled(&self, module: &str, level: Level) -> bool {
+        let directives = self.directives.read().expect("lock poisoned");
+        let max_level = directives
+            .module_levels
+            .iter()
+            .filter(|(prefix, _)| {
+                module == prefix.as_str()
+                    || module
+                        .strip_prefix(prefix.as_str())
+                        .map_or(false, |rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(directives.default_level, |(_, level)| *level);
+        level.is_at_least(max_level)
+    }
+
+    /// Wrap a logger so that records are filtered before reaching it.
+    pub fn wrap(&self, logger: Logger) -> Logger {
+        Logger::root(
+            FilteredDrain {
+                filter: self.clone(),
+                inner: logger,
+            },
+            o!(),
+        )
+    }
+}
+
+struct FilteredDrain {
+    filter: LogFilter,
+    inner: Logger,
+}
+
+impl Drain for FilteredDrain {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<(), Never> {
+        if self.filter.is_enabled(record.module(), record.level()) {
+            Drain::log(&self.inner, record, values)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_common::logger::test_with_logger;
+
+    #[test_with_logger]
+    fn test_log_filter_levels(_logger: Logger) {
+        let filter = LogFilter::default();
+        assert!(filter.is_enabled("mc_full_service::service::sync", Level::Trace));
+
+        filter.set_spec("warn, mc_full_service::service=info,hyper=bogus/some regex");
+        assert_eq!(
+            filter.levels(),
+            (
+                Level::Warning,
+                BTreeMap::from([("mc_full_service::service".to_string(), Level::Info)])
+            )
+        );
+        assert!(!filter.is_enabled("mc_full_service::db", Level::Info));
+        assert!(filter.is_enabled("mc_full_service::db", Level::Warning));
+        assert!(filter.is_enabled("mc_full_service::service::sync", Level::Info));
+        assert!(!filter.is_enabled("mc_full_service::service::sync", Level::Debug));
+        // A module name that merely starts with the same text is not within it.
+        assert!(!filter.is_enabled("mc_full_service::service_extra", Level::Info));
+
+        // The most specific module wins, whichever is more verbose.
+        filter.set_level(Some("mc_full_service::service::sync"), Level::Debug);
+        assert!(filter.is_enabled("mc_full_service::service::sync", Level::Debug));
+        assert!(!filter.is_enabled("mc_full_service::service::txo", Level::Debug));
+        filter.set_level(Some("mc_full_service::service::sync"), Level::Error);
+        assert!(!filter.is_enabled("mc_full_service::service::sync", Level::Warning));
+
+        filter.set_level(None, Level::Error);
+        assert!(!filter.is_enabled("mc_full_service::db", Level::Warning));
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (c) 2020-2023 MobileCoin Inc.
+
+//! Prometheus metrics for wallet operations.
+//!
+//! Counters are incremented inline on the submit and sync paths. Gauges that
+//! describe current state are refreshed on each scrape of `/metrics`.
+
+use crate::{
+    db::{
+        account::AccountModel,
+        models::{Account, TransactionLog},
+        transaction_log::TransactionLogModel,
+    },
+    service::{ledger::LedgerService, WalletService},
+};
+use lazy_static::lazy_static;
+use mc_connection::{BlockchainConnection, UserTxConnection};
+use mc_fog_report_validation::FogPubkeyResolver;
+use mc_ledger_db::Ledger;
+use prometheus::{
+    register_int_counter, register_int_gauge, Encoder, IntCounter, IntGauge, TextEncoder,
+};
+use std::ops::DerefMut;
+
+lazy_static! {
+    /// Number of accounts tracked by the wallet.
+    pub static ref ACCOUNTS_TRACKED: IntGauge = register_int_gauge!(
+        "full_service_accounts_tracked",
+        "Number of accounts tracked by the wallet"
+    )
+    .unwrap();
+
+    /// Number of blocks in the local ledger.
+    pub static ref LEDGER_BLOCK_HEIGHT: IntGauge = register_int_gauge!(
+        "full_service_ledger_block_height",
+        "Number of blocks in the local ledger"
+    )
+    .unwrap();
+
+    /// Number of blocks on the network, as last polled.
+    pub static ref NETWORK_BLOCK_HEIGHT: IntGauge = register_int_gauge!(
+        "full_service_network_block_height",
+        "Number of blocks on the network"
+    )
+    .unwrap();
+
+    /// Number of blocks the local ledger trails the network by.
+    pub static ref LEDGER_SYNC_LAG_BLOCKS: IntGauge = register_int_gauge!(
+        "full_service_ledger_sync_lag_blocks",
+        "Number of blocks the local ledger trails the network by"
+    )
+    .unwrap();
+
+    /// Number of blocks the least-synced account trails the local ledger by.
+    pub static ref ACCOUNT_SYNC_LAG_BLOCKS: IntGauge = register_int_gauge!(
+        "full_service_account_sync_lag_blocks",
+        "Number of blocks the least-synced account trails the local ledger by"
+    )
+    .unwrap();
+
+    /// Number of blocks scanned for accounts by the sync thread.
+    pub static ref BLOCKS_SYNCED: IntCounter = register_int_counter!(
+        "full_service_account_blocks_synced_total",
+        "Number of blocks scanned for accounts by the sync thread"
+    )
+    .unwrap();
+
+    /// Number of submitted transactions that are not yet finalized or failed.
+    pub static ref PENDING_TRANSACTIONS: IntGauge = register_int_gauge!(
+        "full_service_pending_transactions",
+        "Number of submitted transactions that are not yet finalized or failed"
+    )
+    .unwrap();
+
+    /// Number of transactions successfully proposed to consensus.
+    pub static ref SUBMIT_SUCCESSES: IntCounter = register_int_counter!(
+        "full_service_submit_transaction_successes_total",
+        "Number of transactions successfully proposed to consensus"
+    )
+    .unwrap();
+
+    /// Number of transactions that failed to be proposed to consensus.
+    pub static ref SUBMIT_FAILURES: IntCounter = register_int_counter!(
+        "full_service_submit_transaction_failures_total",
+        "Number of transactions that failed to be proposed to consensus"
+    )
+    .unwrap();
+
+    /// Number of connections currently held by the wallet DB pool.
+    pub static ref DB_POOL_CONNECTIONS: IntGauge = register_int_gauge!(
+        "full_service_db_pool_connections",
+        "Number of connections currently held by the wallet DB pool"
+    )
+    .unwrap();
+
+    /// Number of idle connections in the wallet DB pool.
+    pub static ref DB_POOL_IDLE_CONNECTIONS: IntGauge = register_int_gauge!(
+        "full_service_db_pool_idle_connections",
+        "Number of idle connections in the wallet DB pool"
+    )
+    .unwrap();
+}
+
+/// Refresh the state gauges from the wallet service and render all registered
+/// metrics in the Prometheus text exposition format.
+pub fn gather<T, FPR>(service: &WalletService<T, FPR>) -> String
+where
+    T: BlockchainConnection + UserTxConnection + 'static,
+    FPR: FogPubkeyResolver + Send + Sync + 'static,
+{
+    let local_block_height = service.ledger_db.num_blocks().unwrap_or_default();
+    let network_block_height = service.get_network_block_height().unwrap_or_default();
+
+    LEDGER_BLOCK_HEIGHT.set(local_block_height as i64);
+    NETWORK_BLOCK_HEIGHT.set(network_block_height as i64);
+    LEDGER_SYNC_LAG_BLOCKS.set(network_block_height.saturating_sub(local_block_height) as i64);
+
+    if let Some(wallet_db) = service.wallet_db.as_ref() {
+        let pool_state = wallet_db.pool.state();
+        DB_POOL_CONNECTIONS.set(pool_state.connections as i64);
+        DB_POOL_IDLE_CONNECTIONS.set(pool_state.idle_connections as i64);
+
+        if let Ok(mut pooled_conn) = wallet_db.get_pooled_conn() {
+            let conn = pooled_conn.deref_mut();
+            if let Ok(accounts) = Account::list_all(conn, None, None) {
+                ACCOUNTS_TRACKED.set(accounts.len() as i64);
+            }
+            if let Ok(pending) = TransactionLog::count_pending(conn) {
+                PENDING_TRANSACTIONS.set(pending as i64);
+            }
+        }
+    }
+
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("Failed encoding metrics");
+    String::from_utf8(buffer).expect("Metrics are not valid utf-8")
+}
@@ -6,12 +6,13 @@ use std::ops::DerefMut;
 
 use crate::{
     db::{
-        account::{AccountID, AccountModel},
+        account::{AccountID, AccountModel, AccountSortOrder},
         exclusive_transaction,
         models::{Account, Txo},
         txo::TxoModel,
-        WalletDbError,
+        Conn, WalletDbError,
     },
+    error::ErrorCode,
     json_rpc::{
         json_rpc_request::JsonRPCRequest,
         v2::{api::request::JsonCommandRequest, models::account_key::FogInfo},
@@ -21,28 +22,32 @@ use crate::{
             get_view_only_account_keys, get_view_only_subaddress_keys, HardwareWalletServiceError,
         },
         ledger::{LedgerService, LedgerServiceError},
+        watcher::{WatcherService, WatcherServiceError},
         WalletService,
     },
 };
 
 use base64::{engine::general_purpose, Engine};
 use bip39::{Language, Mnemonic, MnemonicType};
+use diesel::Connection;
 use displaydoc::Display;
 
 use mc_account_keys::{
-    AccountKey, PublicAddress, RootEntropy, ViewAccountKey, DEFAULT_SUBADDRESS_INDEX,
+    AccountKey, PublicAddress, RootEntropy, RootIdentity, ViewAccountKey, DEFAULT_SUBADDRESS_INDEX,
 };
-use mc_common::logger::log;
+use mc_common::{logger::log, HashMap};
 use mc_connection::{BlockchainConnection, UserTxConnection};
 use mc_core::{
     account::{RingCtAddress, ViewSubaddress},
     keys::{RootSpendPublic, RootViewPrivate},
+    slip10::Slip10KeyGenerator,
 };
-use mc_crypto_keys::RistrettoPublic;
+use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
 use mc_fog_report_validation::FogPubkeyResolver;
 use mc_fog_sig_authority::Signer;
 use mc_ledger_db::Ledger;
 use mc_transaction_signer::types::TxoSynced;
+use mc_watcher_api::TimestampResultCode;
 
 #[derive(Display, Debug)]
 pub enum AccountServiceError {
@@ -87,6 +92,39 @@ pub enum AccountServiceError {
 
     /// Error with the HardwareWalletService: {0}
     HardwareWalletService(HardwareWalletServiceError),
+
+    /// Raising first_block_index from {0} to {1} requires force
+    FirstBlockIndexIncrease(u64, u64),
+
+    /// Error with the WatcherService: {0}
+    WatcherService(WatcherServiceError),
+
+    /// Account limit reached: the wallet holds at most {0} accounts
+    AccountLimitReached(u64),
+}
+
+impl ErrorCode for AccountServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Database(err) => err.error_code(),
+            Self::LedgerDB(..) => "ledger_db",
+            Self::HexDecode(..) => "hex_decode",
+            Self::Diesel(..) => "diesel",
+            Self::LedgerService(err) => err.error_code(),
+            Self::UnknownKeyDerivation(..) => "unknown_key_derivation",
+            Self::InvalidMnemonic(..) => "invalid_mnemonic",
+            Self::Base64DecodeError(..) => "base64_decode_error",
+            Self::DecodePrivateKeyError(..) => "decode_private_key_error",
+            Self::AccountIsViewOnly(..) => "account_is_view_only",
+            Self::AccountIsNotViewOnly(..) => "account_is_not_view_only",
+            Self::InvalidJsonRPCRequest => "invalid_json_rpc_request",
+            Self::Key(..) => "key",
+            Self::HardwareWalletService(err) => err.error_code(),
+            Self::FirstBlockIndexIncrease(..) => "first_block_index_increase",
+            Self::WatcherService(err) => err.error_code(),
+            Self::AccountLimitReached(..) => "account_limit_reached",
+        }
+    }
 }
 
 impl From<WalletDbError> for AccountServiceError {
@@ -149,6 +187,53 @@ impl From<HardwareWalletServiceError> for AccountServiceError {
     }
 }
 
+impl From<WatcherServiceError> for AccountServiceError {
+    fn from(src: WatcherServiceError) -> Self {
+        Self::WatcherService(src)
+    }
+}
+
+/// An account to import with `import_accounts`.
+#[derive(Clone)]
+pub enum AccountImport {
+    /// An account with spend keys, derived from a mnemonic.
+    Mnemonic {
+        mnemonic_phrase: String,
+        account_index: Option<u32>,
+        name: Option<String>,
+        first_block_index: Option<u64>,
+        next_subaddress_index: Option<u64>,
+        fog_report_url: String,
+        fog_authority_spki: String,
+        require_spend_subaddress: bool,
+    },
+
+    /// A view only account.
+    ViewOnly {
+        view_private_key: RootViewPrivate,
+        spend_public_key: RootSpendPublic,
+        account_index: Option<u32>,
+        name: Option<String>,
+        first_block_index: Option<u64>,
+        next_subaddress_index: Option<u64>,
+        require_spend_subaddress: bool,
+        subaddresses: Vec<(u64, String)>,
+    },
+}
+
+/// The exported secrets of an account, to check with `verify_account_backup`.
+#[derive(Clone)]
+pub enum AccountBackup {
+    /// A mnemonic, and the account index the account was derived at.
+    Mnemonic {
+        mnemonic_phrase: String,
+        account_index: Option<u32>,
+    },
+
+    /// The hex-encoded root entropy of a legacy account.
+    LegacyRootEntropy { entropy: String },
+}
+
 /// AccountService trait defining the ways in which the wallet can interact with and manage
 #[rustfmt::skip]
 #[async_trait]
@@ -179,6 +264,7 @@ pub trait AccountService {
     ///| Name                     | Purpose                                                                                    | Notes                                                            |
     ///|--------------------------|--------------------------------------------------------------------------------------------|------------------------------------------------------------------|
     ///| `mnemonic_phrase`        | The secret mnemonic to recover the account.                                                | A label can have duplicates, but it is not recommended.          |
+    ///| `account_index`          | The SLIP-0010 account index to derive the account key at.                                  | Defaults to 0 if not provided.                                   |
     ///| `name`                   | A Optional label for this account.                                                         |                                                                  |
     ///| `first_block_index`      | The block from which to start scanning the ledger.                                         | All subaddresses below this index will be created.               |
     ///| `next_subaddress_index`  | The next known unused subaddress index for the account.                                    |                                                                  |
@@ -190,6 +276,7 @@ pub trait AccountService {
     fn import_account(
         &self,
         mnemonic_phrase: String,
+        account_index: Option<u32>,
         name: Option<String>,
         first_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
@@ -232,26 +319,85 @@ pub trait AccountService {
     ///|-------------------------|---------------------------------------------------------|---------------------------------------------------------|
     ///| `view_private_key`      | The view private key of this account                    |                                                         |
     ///| `spend_public_key`      | The spend public key of this account                    |                                                         |
+    ///| `account_index`         | The index the account's keys were derived at.           | Optional, defaults to 0.                                |
     ///| `name`                  | A label for this account.                               | A label can have duplicates, but it is not recommended. |
     ///| `first_block_index`     | The block from which to start scanning the ledger.      | All subaddresses below this index will be created.      |
     ///| `next_subaddress_index` | The next known unused subaddress index for the account. |                                                         |
+    ///| `subaddresses`          | Subaddress indices to assign, with their metadata.      | Assigned before the first sync of the account.          |
     ///
+    #[allow(clippy::too_many_arguments)]
     fn import_view_only_account(
         &self,
         view_private_key: &RootViewPrivate,
         spend_public_key: &RootSpendPublic,
+        account_index: Option<u32>,
         name: Option<String>,
         first_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
         require_spend_subaddress: bool,
+        subaddresses: &[(u64, String)],
     ) -> Result<Account, AccountServiceError>;
 
+    /// Import several accounts in a single database transaction. Each account is imported
+    /// independently, so an account which fails to import does not prevent the others from
+    /// being imported.
+    ///
+    /// # Arguments
+    ///
+    ///| Name      | Purpose                  | Notes |
+    ///|-----------|--------------------------|-------|
+    ///| `imports` | The accounts to import.  |       |
+    ///
+    /// # Returns:
+    /// * The imported account, or the error importing it, for each of the imports in order.
+    fn import_accounts(
+        &self,
+        imports: Vec<AccountImport>,
+    ) -> Result<Vec<Result<Account, AccountServiceError>>, AccountServiceError>;
+
+    /// Check that the exported secrets of an account derive the expected account, without
+    /// importing it.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                  | Purpose                                               | Notes                                                            |
+    ///|-----------------------|-------------------------------------------------------|------------------------------------------------------------------|
+    ///| `backup`              | The exported mnemonic or root entropy of the account. |                                                                  |
+    ///| `fog_report_url`      | Fog Report server url.                                | Applicable only if user has Fog service, empty string otherwise. |
+    ///| `fog_authority_spki`  | Fog Authority Subject Public Key Info.                | Applicable only if user has Fog service, empty string otherwise. |
+    ///| `expected_account_id` | The account id the secrets should derive.             |                                                                  |
+    ///
+    /// # Returns:
+    /// * Whether the secrets derive the expected account id.
+    fn verify_account_backup(
+        &self,
+        backup: AccountBackup,
+        fog_report_url: String,
+        fog_authority_spki: String,
+        expected_account_id: &AccountID,
+    ) -> Result<bool, AccountServiceError>;
+
+    /// Import a view only account using the keys of a connected hardware wallet.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                       | Purpose                                                 | Notes                                                   |
+    ///|----------------------------|---------------------------------------------------------|---------------------------------------------------------|
+    ///| `account_index`            | The index of the account on the hardware wallet.        | Optional, defaults to 0.                                |
+    ///| `name`                     | A label for this account.                               | A label can have duplicates, but it is not recommended. |
+    ///| `first_block_index`        | The block from which to start scanning the ledger.      |                                                         |
+    ///| `fog_info`                 | Fog info for the account's main address.                | Optional.                                               |
+    ///| `require_spend_subaddress` | Whether to enable require_spend_subaddress mode.        |                                                         |
+    ///| `device_id`                | The hardware wallet to import from.                     | Optional, defaults to the first device found.           |
+    ///
     async fn import_view_only_account_from_hardware_wallet(
         &self,
+        account_index: Option<u32>,
         name: Option<String>,
         first_block_index: Option<u64>,
         fog_info: Option<FogInfo>,
         require_spend_subaddress: bool,
+        device_id: Option<&str>,
     ) -> Result<Account, AccountServiceError>;
 
     /// Re-create sync request for a view only account
@@ -284,17 +430,22 @@ pub trait AccountService {
     ///
     /// # Arguments
     ///
-    ///| Name     | Purpose                                                    | Notes                      |
-    ///|----------|------------------------------------------------------------|----------------------------|
-    ///| `offset` | The pagination offset. Results start at the offset index.  | Optional, defaults to 0.   |
-    ///| `limit`  | Limit for the number of results.                           | Optional                   |
+    ///| Name         | Purpose                                                    | Notes                      |
+    ///|--------------|------------------------------------------------------------|----------------------------|
+    ///| `sort_order` | The order to list the accounts in.                         | Ties are broken by id.     |
+    ///| `offset`     | The pagination offset. Results start at the offset index.  | Optional, defaults to 0.   |
+    ///| `limit`      | Limit for the number of results.                           | Optional                   |
     ///
     fn list_accounts(
         &self,
+        sort_order: AccountSortOrder,
         offset: Option<u64>,
         limit: Option<u64>,
     ) -> Result<Vec<Account>, AccountServiceError>;
 
+    /// Count the accounts in the wallet, e.g. to page through them with list_accounts.
+    fn count_accounts(&self) -> Result<u64, AccountServiceError>;
+
     /// Get the current status of a given account. The account status includes both the account object and the balance object.
     ///
     /// # Arguments
@@ -308,6 +459,24 @@ pub trait AccountService {
         account_id: &AccountID
     ) -> Result<Account, AccountServiceError>;
 
+    /// Get when an account last received a Txo, or had one of its transactions submitted or
+    /// finalized.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                      | Notes                             |
+    ///|--------------|----------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account on which to perform this action. | Account must exist in the wallet. |
+    ///
+    /// # Returns:
+    /// * (block_index, timestamp), where the timestamp of the block is only known if a watcher
+    ///   is configured and has synced that block. Both are None if the account has had no
+    ///   activity.
+    fn get_account_last_activity(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<(Option<u64>, Option<u64>), AccountServiceError>;
+
     /// Get the next subaddress index for an account
     ///
     /// # Arguments
@@ -336,6 +505,37 @@ pub trait AccountService {
         name: String,
     ) -> Result<Account, AccountServiceError>;
 
+    /// Update the first block index (birthday) for an account.
+    ///
+    /// Lowering the first block index rewinds the account's next block index
+    /// to the new value, so the sync thread rescans every block from there,
+    /// not just the newly included range. This is required: sync only marks
+    /// a TXO spent when it sees the TXO's key image after the TXO itself, so
+    /// a TXO found in the earlier blocks but spent in a block that was
+    /// already scanned would otherwise never be marked spent. Rescanning
+    /// blocks already seen is harmless, but may take a while for accounts
+    /// with a long history.
+    ///
+    /// Raising the first block index is refused unless `force` is set, since
+    /// TXOs already received in the excluded blocks are left in the wallet
+    /// and would not be found again by a resync. When forced, the next block
+    /// index is advanced to the new first block index if it is behind it.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                | Purpose                                      | Notes                                      |
+    ///|---------------------|----------------------------------------------|--------------------------------------------|
+    ///| `account_id`        | The account on which to perform this action. | Account must exist in the wallet.          |
+    ///| `first_block_index` | The new first block index for this account.  |                                            |
+    ///| `force`             | Allow raising the first block index.         | Already-scanned TXOs are kept in the DB.   |
+    ///
+    fn update_account_first_block_index(
+        &self,
+        account_id: &AccountID,
+        first_block_index: u64,
+        force: bool,
+    ) -> Result<Account, AccountServiceError>;
+
     /// Update the require_spend_subaddress field for an account.
     ///
     /// # Arguments
@@ -351,6 +551,37 @@ pub trait AccountService {
         require_spend_subaddress: bool,
     ) -> Result<Account, AccountServiceError>;
 
+    /// Update the subaddress an account receives at when no subaddress is given, such as for new
+    /// payment requests.
+    ///
+    /// # Arguments
+    ///
+    ///| Name               | Purpose                                         | Notes                                                       |
+    ///|--------------------|-------------------------------------------------|-------------------------------------------------------------|
+    ///| `account_id`       | The account on which to perform this action.    | Account must exist in the wallet.                           |
+    ///| `subaddress_index` | The index of the new default receive subaddress | Must be assigned to the account, and not the change address. |
+    ///
+    fn update_default_receive_subaddress_index(
+        &self,
+        account_id: &AccountID,
+        subaddress_index: i64,
+    ) -> Result<Account, AccountServiceError>;
+
+    /// Update the require_sender_memo field for an account.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                  | Purpose                                                    | Notes                                       |
+    ///|-----------------------|------------------------------------------------------------|---------------------------------------------|
+    ///| `account_id`          | The account on which to perform this action.               | Account must exist in the wallet.           |
+    ///| `require_sender_memo` | Whether to flag Txos received without a sender memo        | Only applies to Txos synced after updating. |
+    ///
+    fn update_require_sender_memo(
+        &self,
+        account_id: &AccountID,
+        require_sender_memo: bool,
+    ) -> Result<Account, AccountServiceError>;
+
     /// complete a sync request for a view only account
     ///
     /// # Arguments
@@ -424,8 +655,10 @@ where
         let conn = pooled_conn.deref_mut();
 
         exclusive_transaction(conn, |conn| {
+            check_account_limit(self.max_accounts, conn)?;
             let (account_id, _public_address_b58) = Account::create_from_mnemonic(
                 &mnemonic,
+                0,
                 Some(first_block_index),
                 Some(import_block_index),
                 None,
@@ -443,6 +676,7 @@ where
     fn import_account(
         &self,
         mnemonic_phrase: String,
+        account_index: Option<u32>,
         name: Option<String>,
         first_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
@@ -474,8 +708,10 @@ where
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
         exclusive_transaction(conn, |conn| {
+            check_account_limit(self.max_accounts, conn)?;
             Ok(Account::import(
                 &mnemonic,
+                account_index.unwrap_or_default(),
                 name,
                 import_block,
                 first_block_index,
@@ -515,6 +751,7 @@ where
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
         exclusive_transaction(conn, |conn| {
+            check_account_limit(self.max_accounts, conn)?;
             Ok(Account::import_legacy(
                 &RootEntropy::from(&entropy_bytes),
                 name,
@@ -533,10 +770,12 @@ where
         &self,
         view_private_key: &RootViewPrivate,
         spend_public_key: &RootSpendPublic,
+        account_index: Option<u32>,
         name: Option<String>,
         first_block_index: Option<u64>,
         next_subaddress_index: Option<u64>,
         require_spend_subaddress: bool,
+        subaddresses: &[(u64, String)],
     ) -> Result<Account, AccountServiceError> {
         log::info!(
             self.logger,
@@ -553,27 +792,97 @@ where
             ViewAccountKey::new(*view_private_key.as_ref(), *spend_public_key.as_ref());
 
         exclusive_transaction(conn, |conn| {
+            check_account_limit(self.max_accounts, conn)?;
             Ok(Account::import_view_only(
                 &view_account_key,
+                account_index.unwrap_or_default(),
                 name,
                 import_block_index,
                 first_block_index,
                 next_subaddress_index,
                 false,
                 require_spend_subaddress,
+                subaddresses,
                 conn,
             )?)
         })
     }
 
+    fn import_accounts(
+        &self,
+        imports: Vec<AccountImport>,
+    ) -> Result<Vec<Result<Account, AccountServiceError>>, AccountServiceError> {
+        log::info!(self.logger, "Importing {} accounts", imports.len());
+
+        let import_block_index = self.ledger_db.num_blocks()? - 1;
+
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        exclusive_transaction(conn, |conn| {
+            // Each import runs in its own savepoint, so that a failed import is rolled back
+            // without affecting the others.
+            Ok(imports
+                .into_iter()
+                .map(|import| {
+                    conn.transaction(|conn| {
+                        import_account_in_transaction(
+                            import,
+                            import_block_index,
+                            self.max_accounts,
+                            conn,
+                        )
+                    })
+                })
+                .collect())
+        })
+    }
+
+    fn verify_account_backup(
+        &self,
+        backup: AccountBackup,
+        fog_report_url: String,
+        fog_authority_spki: String,
+        expected_account_id: &AccountID,
+    ) -> Result<bool, AccountServiceError> {
+        let fog_authority_spki = general_purpose::STANDARD.decode(fog_authority_spki)?;
+        let account_key = match backup {
+            AccountBackup::Mnemonic {
+                mnemonic_phrase,
+                account_index,
+            } => {
+                let mnemonic = Mnemonic::from_phrase(&mnemonic_phrase, Language::English)
+                    .map_err(|_| AccountServiceError::InvalidMnemonic(mnemonic_phrase.clone()))?;
+                let account_key: AccountKey = mnemonic
+                    .derive_slip10_key(account_index.unwrap_or_default())
+                    .into();
+                account_key.with_fog(&fog_report_url, "".to_string(), fog_authority_spki)
+            }
+            AccountBackup::LegacyRootEntropy { entropy } => {
+                let mut entropy_bytes = [0u8; 32];
+                hex::decode_to_slice(entropy, &mut entropy_bytes)?;
+                AccountKey::from(&RootIdentity {
+                    root_entropy: RootEntropy::from(&entropy_bytes),
+                    fog_report_url,
+                    fog_report_id: "".to_string(),
+                    fog_authority_spki,
+                })
+            }
+        };
+
+        Ok(&AccountID::from(&account_key) == expected_account_id)
+    }
+
     async fn import_view_only_account_from_hardware_wallet(
         &self,
+        account_index: Option<u32>,
         name: Option<String>,
         first_block_index: Option<u64>,
         fog_info: Option<FogInfo>,
         require_spend_subaddress: bool,
+        device_id: Option<&str>,
     ) -> Result<Account, AccountServiceError> {
-        let view_account = get_view_only_account_keys().await?;
+        let account_index = account_index.unwrap_or_default();
+        let view_account = get_view_only_account_keys(account_index, device_id).await?;
 
         let view_account_keys = ViewAccountKey::new(
             *view_account.view_private_key().as_ref(),
@@ -588,8 +897,12 @@ where
             Some(fog_info) => {
                 let fog_authority_spki =
                     general_purpose::STANDARD.decode(fog_info.authority_spki)?;
-                let default_subaddress_keys =
-                    get_view_only_subaddress_keys(DEFAULT_SUBADDRESS_INDEX).await?;
+                let default_subaddress_keys = get_view_only_subaddress_keys(
+                    account_index,
+                    DEFAULT_SUBADDRESS_INDEX,
+                    device_id,
+                )
+                .await?;
 
                 let default_public_address = get_public_fog_address(
                     &default_subaddress_keys,
@@ -597,8 +910,10 @@ where
                     &fog_authority_spki,
                 );
                 exclusive_transaction(conn, |conn| {
+                    check_account_limit(self.max_accounts, conn)?;
                     Ok(Account::import_view_only_from_hardware_wallet_with_fog(
                         &view_account_keys,
+                        account_index,
                         name,
                         import_block_index,
                         first_block_index,
@@ -609,14 +924,17 @@ where
                 })
             }
             None => exclusive_transaction(conn, |conn| {
+                check_account_limit(self.max_accounts, conn)?;
                 Ok(Account::import_view_only(
                     &view_account_keys,
+                    account_index,
                     name,
                     import_block_index,
                     first_block_index,
                     None,
                     true,
                     false,
+                    &[],
                     conn,
                 )?)
             }),
@@ -650,10 +968,12 @@ where
         let json_command_request = JsonCommandRequest::import_view_only_account {
             view_private_key: hex::encode(view_private_key.to_bytes()),
             spend_public_key: hex::encode(spend_public_key.to_bytes()),
+            account_index: Some(account.account_index.to_string()),
             name: Some(account.name.clone()),
             first_block_index: Some(account.first_block_index.to_string()),
             next_subaddress_index: Some(account.clone().next_subaddress_index(conn)?.to_string()),
             require_spend_subaddress: account.require_spend_subaddress,
+            subaddresses: None,
         };
 
         let src_json: serde_json::Value = serde_json::json!(json_command_request);
@@ -671,17 +991,25 @@ where
             params: Some(params.clone()),
             jsonrpc: "2.0".to_string(),
             id: serde_json::Value::Number(serde_json::Number::from(1)),
+            include_elapsed_ms: None,
         })
     }
 
     fn list_accounts(
         &self,
+        sort_order: AccountSortOrder,
         offset: Option<u64>,
         limit: Option<u64>,
     ) -> Result<Vec<Account>, AccountServiceError> {
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
-        Ok(Account::list_all(conn, offset, limit)?)
+        Ok(Account::list_all_sorted(conn, sort_order, offset, limit)?)
+    }
+
+    fn count_accounts(&self) -> Result<u64, AccountServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Ok(Account::count(conn)?)
     }
 
     fn get_account(&self, account_id: &AccountID) -> Result<Account, AccountServiceError> {
@@ -690,6 +1018,27 @@ where
         Ok(Account::get(account_id, conn)?)
     }
 
+    fn get_account_last_activity(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<(Option<u64>, Option<u64>), AccountServiceError> {
+        let block_index = {
+            let mut pooled_conn = self.get_pooled_conn()?;
+            let conn = pooled_conn.deref_mut();
+            Account::get(account_id, conn)?.last_activity_block_index(conn)?
+        };
+
+        let timestamp = match block_index {
+            Some(block_index) => self
+                .get_watcher_block_info(block_index)?
+                .filter(|info| info.timestamp_result_code == TimestampResultCode::TimestampFound)
+                .map(|info| info.timestamp),
+            None => None,
+        };
+
+        Ok((block_index, timestamp))
+    }
+
     fn get_next_subaddress_index_for_account(
         &self,
         account_id: &AccountID,
@@ -711,6 +1060,39 @@ where
         Ok(Account::get(account_id, conn)?)
     }
 
+    fn update_account_first_block_index(
+        &self,
+        account_id: &AccountID,
+        first_block_index: u64,
+        force: bool,
+    ) -> Result<Account, AccountServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        exclusive_transaction(conn, |conn| {
+            let account = Account::get(account_id, conn)?;
+            let current_first_block_index = account.first_block_index as u64;
+            let next_block_index = account.next_block_index as u64;
+
+            if first_block_index > current_first_block_index {
+                if !force {
+                    return Err(AccountServiceError::FirstBlockIndexIncrease(
+                        current_first_block_index,
+                        first_block_index,
+                    ));
+                }
+                account.update_first_block_index(first_block_index, conn)?;
+                if next_block_index < first_block_index {
+                    account.update_next_block_index(first_block_index, conn)?;
+                }
+            } else if first_block_index < current_first_block_index {
+                account.update_first_block_index(first_block_index, conn)?;
+                account.update_next_block_index(first_block_index, conn)?;
+            }
+
+            Ok(Account::get(account_id, conn)?)
+        })
+    }
+
     fn update_require_spend_subaddress(
         &self,
         account_id: &AccountID,
@@ -723,6 +1105,29 @@ where
         Ok(Account::get(account_id, conn)?)
     }
 
+    fn update_default_receive_subaddress_index(
+        &self,
+        account_id: &AccountID,
+        subaddress_index: i64,
+    ) -> Result<Account, AccountServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Account::get(account_id, conn)?
+            .update_default_receive_subaddress_index(subaddress_index, conn)?;
+        Ok(Account::get(account_id, conn)?)
+    }
+
+    fn update_require_sender_memo(
+        &self,
+        account_id: &AccountID,
+        require_sender_memo: bool,
+    ) -> Result<Account, AccountServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        Account::get(account_id, conn)?.update_require_sender_memo(require_sender_memo, conn)?;
+        Ok(Account::get(account_id, conn)?)
+    }
+
     fn sync_account(
         &self,
         account_id: &AccountID,
@@ -738,18 +1143,28 @@ where
             ));
         }
 
+        let mut key_images_by_public_key = HashMap::default();
         for synced_txo in synced_txos {
             let spent_block_index = self.ledger_db.check_key_image(&synced_txo.key_image)?;
             let ristretto_public: &RistrettoPublic = synced_txo.tx_out_public_key.as_ref();
-            Txo::update_key_image_by_pubkey(
-                &ristretto_public.into(),
-                &synced_txo.key_image,
-                spent_block_index,
-                conn,
-            )?;
+            key_images_by_public_key.insert(
+                CompressedRistrettoPublic::from(ristretto_public),
+                (synced_txo.key_image, spent_block_index),
+            );
         }
 
-        Ok(())
+        exclusive_transaction(conn, |conn| {
+            let public_keys: Vec<&CompressedRistrettoPublic> =
+                key_images_by_public_key.keys().collect();
+            let mut updates = Vec::with_capacity(public_keys.len());
+            for txo in Txo::select_by_public_key(&public_keys, conn)? {
+                let (key_image, spent_block_index) =
+                    key_images_by_public_key[&txo.public_key().map_err(WalletDbError::from)?];
+                updates.push((txo.id, key_image, spent_block_index));
+            }
+            Txo::batch_update_key_images(&updates, conn)?;
+            Ok::<(), AccountServiceError>(())
+        })
     }
 
     fn remove_account(&self, account_id: &AccountID) -> Result<bool, AccountServiceError> {
@@ -776,6 +1191,78 @@ where
     }
 }
 
+/// Import an account within a transaction opened by the caller.
+fn import_account_in_transaction(
+    import: AccountImport,
+    import_block_index: u64,
+    max_accounts: Option<u64>,
+    conn: Conn,
+) -> Result<Account, AccountServiceError> {
+    check_account_limit(max_accounts, conn)?;
+    match import {
+        AccountImport::Mnemonic {
+            mnemonic_phrase,
+            account_index,
+            name,
+            first_block_index,
+            next_subaddress_index,
+            fog_report_url,
+            fog_authority_spki,
+            require_spend_subaddress,
+        } => {
+            let mnemonic = Mnemonic::from_phrase(&mnemonic_phrase, Language::English)
+                .map_err(|_| AccountServiceError::InvalidMnemonic(mnemonic_phrase.clone()))?;
+            Ok(Account::import(
+                &mnemonic,
+                account_index.unwrap_or_default(),
+                name,
+                import_block_index,
+                first_block_index,
+                next_subaddress_index,
+                fog_report_url,
+                fog_authority_spki,
+                require_spend_subaddress,
+                conn,
+            )?)
+        }
+        AccountImport::ViewOnly {
+            view_private_key,
+            spend_public_key,
+            account_index,
+            name,
+            first_block_index,
+            next_subaddress_index,
+            require_spend_subaddress,
+            subaddresses,
+        } => {
+            let view_account_key =
+                ViewAccountKey::new(*view_private_key.as_ref(), *spend_public_key.as_ref());
+            Ok(Account::import_view_only(
+                &view_account_key,
+                account_index.unwrap_or_default(),
+                name,
+                import_block_index,
+                first_block_index,
+                next_subaddress_index,
+                false,
+                require_spend_subaddress,
+                &subaddresses,
+                conn,
+            )?)
+        }
+    }
+}
+
+/// Check that the wallet can hold another account.
+fn check_account_limit(max_accounts: Option<u64>, conn: Conn) -> Result<(), AccountServiceError> {
+    if let Some(max_accounts) = max_accounts {
+        if Account::count(conn)? >= max_accounts {
+            return Err(AccountServiceError::AccountLimitReached(max_accounts));
+        }
+    }
+    Ok(())
+}
+
 fn get_public_fog_address(
     subaddress_keys: &ViewSubaddress,
     fog_report_url: String,
@@ -1226,6 +1713,44 @@ mod tests {
         assert_eq!(account.import_block_index, Some(12));
     }
 
+    #[test_with_logger]
+    fn test_max_accounts(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+        let mut service = setup_wallet_service_offline(ledger_db, logger);
+        service.max_accounts = Some(1);
+
+        service
+            .create_account(Some("A".to_string()), "".to_string(), "".to_string(), false)
+            .unwrap();
+
+        // Neither creating nor importing another account is allowed.
+        match service.create_account(Some("B".to_string()), "".to_string(), "".to_string(), false) {
+            Err(AccountServiceError::AccountLimitReached(1)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        let mut entropy = [0u8; 32];
+        rng.fill_bytes(&mut entropy);
+        let mnemonic = Mnemonic::from_entropy(&entropy, Language::English).unwrap();
+        let results = service
+            .import_accounts(vec![AccountImport::Mnemonic {
+                mnemonic_phrase: mnemonic.phrase().to_string(),
+                account_index: None,
+                name: Some("C".to_string()),
+                first_block_index: None,
+                next_subaddress_index: None,
+                fog_report_url: "".to_string(),
+                fog_authority_spki: "".to_string(),
+                require_spend_subaddress: false,
+            }])
+            .unwrap();
+        match &results[0] {
+            Err(AccountServiceError::AccountLimitReached(1)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        assert_eq!(service.count_accounts().unwrap(), 1);
+    }
+
     #[test_with_logger]
     fn test_create_account_offline_no_ledger(logger: Logger) {
         let ledger_db = get_empty_test_ledger();
@@ -1283,7 +1808,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 false,
+                &[],
             )
             .unwrap();
 
@@ -1449,4 +1976,202 @@ mod tests {
         .unwrap();
         assert_eq!(unspent_txos.len(), 2);
     }
+
+    #[async_test_with_logger]
+    async fn test_update_account_first_block_index(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+        let service = setup_wallet_service(ledger_db, None, logger);
+
+        let entropy = RootEntropy::from_random(&mut rng);
+        let account = service
+            .import_account_from_legacy_root_entropy(
+                hex::encode(entropy.bytes),
+                None,
+                Some(5),
+                None,
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let account_id = AccountID(account.id.clone());
+        assert_eq!(account.first_block_index, 5);
+
+        // Lowering the first block index rewinds the sync position.
+        let account = service
+            .update_account_first_block_index(&account_id, 2, false)
+            .unwrap();
+        assert_eq!(account.first_block_index, 2);
+        assert_eq!(account.next_block_index, 2);
+
+        // Raising it is refused without force.
+        match service.update_account_first_block_index(&account_id, 8, false) {
+            Err(AccountServiceError::FirstBlockIndexIncrease(2, 8)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        let account = service.get_account(&account_id).unwrap();
+        assert_eq!(account.first_block_index, 2);
+
+        // With force, the sync position is advanced along with it.
+        let account = service
+            .update_account_first_block_index(&account_id, 8, true)
+            .unwrap();
+        assert_eq!(account.first_block_index, 8);
+        assert_eq!(account.next_block_index, 8);
+    }
+
+    #[test_with_logger]
+    fn test_import_accounts(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+        let service = setup_wallet_service(ledger_db, None, logger);
+
+        let mut entropy = [0u8; 32];
+        rng.fill_bytes(&mut entropy);
+        let mnemonic = Mnemonic::from_entropy(&entropy, Language::English).unwrap();
+        let mnemonic_import = AccountImport::Mnemonic {
+            mnemonic_phrase: mnemonic.phrase().to_string(),
+            account_index: None,
+            name: Some("A".to_string()),
+            first_block_index: Some(3),
+            next_subaddress_index: None,
+            fog_report_url: "".to_string(),
+            fog_authority_spki: "".to_string(),
+            require_spend_subaddress: false,
+        };
+        let mut invalid_import = mnemonic_import.clone();
+        if let AccountImport::Mnemonic {
+            mnemonic_phrase, ..
+        } = &mut invalid_import
+        {
+            *mnemonic_phrase = "not a mnemonic".to_string();
+        }
+
+        let view_account_key = ViewAccountKey::from(&AccountKey::new(
+            &RistrettoPrivate::from_random(&mut rng),
+            &RistrettoPrivate::from_random(&mut rng),
+        ));
+        let view_only_import = AccountImport::ViewOnly {
+            view_private_key: (*view_account_key.view_private_key()).into(),
+            spend_public_key: (*view_account_key.spend_public_key()).into(),
+            account_index: None,
+            name: Some("B".to_string()),
+            first_block_index: None,
+            next_subaddress_index: None,
+            require_spend_subaddress: false,
+            subaddresses: vec![(5, "five".to_string())],
+        };
+
+        // The invalid mnemonic, and the second import of the same account, fail
+        // without preventing the other accounts from being imported.
+        let results = service
+            .import_accounts(vec![
+                mnemonic_import.clone(),
+                invalid_import,
+                mnemonic_import,
+                view_only_import,
+            ])
+            .unwrap();
+        assert_eq!(results.len(), 4);
+
+        let account = results[0].as_ref().unwrap();
+        assert_eq!(account.name, "A");
+        assert_eq!(account.first_block_index, 3);
+        assert_eq!(account.import_block_index, Some(11));
+        match &results[1] {
+            Err(AccountServiceError::InvalidMnemonic(_)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        assert!(results[2].is_err());
+        let view_only_account = results[3].as_ref().unwrap();
+        assert!(view_only_account.view_only);
+
+        assert_eq!(service.count_accounts().unwrap(), 2);
+        let address = service
+            .get_address_for_account(&AccountID(view_only_account.id.clone()), 5)
+            .unwrap();
+        assert_eq!(address.comment, "five");
+    }
+
+    #[test_with_logger]
+    fn test_verify_account_backup(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+        let service = setup_wallet_service(ledger_db, None, logger);
+
+        let mut entropy = [0u8; 32];
+        rng.fill_bytes(&mut entropy);
+        let mnemonic = Mnemonic::from_entropy(&entropy, Language::English).unwrap();
+        let mnemonic_account = service
+            .import_account(
+                mnemonic.phrase().to_string(),
+                Some(1),
+                None,
+                None,
+                None,
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let legacy_account = service
+            .import_account_from_legacy_root_entropy(
+                hex::encode(entropy),
+                None,
+                None,
+                None,
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        let verify = |backup: AccountBackup, account_id: &str| {
+            service.verify_account_backup(
+                backup,
+                "".to_string(),
+                "".to_string(),
+                &AccountID(account_id.to_string()),
+            )
+        };
+        let mnemonic_backup = |account_index| AccountBackup::Mnemonic {
+            mnemonic_phrase: mnemonic.phrase().to_string(),
+            account_index,
+        };
+        let legacy_backup = AccountBackup::LegacyRootEntropy {
+            entropy: hex::encode(entropy),
+        };
+
+        assert!(verify(mnemonic_backup(Some(1)), &mnemonic_account.id).unwrap());
+        assert!(verify(legacy_backup.clone(), &legacy_account.id).unwrap());
+
+        // The wrong account index, or kind of secret, derives another account.
+        assert!(!verify(mnemonic_backup(None), &mnemonic_account.id).unwrap());
+        assert!(!verify(legacy_backup, &mnemonic_account.id).unwrap());
+
+        // So does the wrong fog info.
+        assert!(!service
+            .verify_account_backup(
+                mnemonic_backup(Some(1)),
+                "fog://fog.test.mobilecoin.com".to_string(),
+                "".to_string(),
+                &AccountID(mnemonic_account.id.clone()),
+            )
+            .unwrap());
+
+        match verify(
+            AccountBackup::Mnemonic {
+                mnemonic_phrase: "not a mnemonic".to_string(),
+                account_index: None,
+            },
+            &mnemonic_account.id,
+        ) {
+            Err(AccountServiceError::InvalidMnemonic(_)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // Verifying does not import anything.
+        assert_eq!(service.count_accounts().unwrap(), 2);
+    }
 }
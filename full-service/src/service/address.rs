@@ -6,15 +6,24 @@ use std::ops::DerefMut;
 
 use crate::{
     db::{
-        account::AccountID, assigned_subaddress::AssignedSubaddressModel, exclusive_transaction,
-        models::AssignedSubaddress, WalletDbError,
+        account::{AccountID, AccountModel},
+        assigned_subaddress::AssignedSubaddressModel,
+        exclusive_transaction,
+        models::{Account, AssignedSubaddress, Txo},
+        txo::{TxoID, TxoModel},
+        WalletDbError,
     },
-    service::WalletService,
-    util::b58::{b58_decode_public_address, B58Error},
+    error::ErrorCode,
+    service::{sync::decode_amount, WalletService},
+    util::b58::{b58_decode_public_address, b58_encode_public_address, B58Error},
 };
 use mc_account_keys::PublicAddress;
 use mc_connection::{BlockchainConnection, UserTxConnection};
+use mc_crypto_keys::RistrettoPublic;
 use mc_fog_report_validation::FogPubkeyResolver;
+use mc_ledger_db::Ledger;
+use mc_transaction_core::{onetime_keys::recover_public_subaddress_spend_key, tx::TxOut, Amount};
+use std::convert::TryFrom;
 
 use displaydoc::Display;
 
@@ -30,6 +39,38 @@ pub enum AddressServiceError {
 
     /// B58 Error
     B58(B58Error),
+
+    /// Txo {0} was not received at an assigned subaddress of an account
+    TxoNotAtAssignedSubaddress(String),
+
+    /// Error with LedgerDB: {0}
+    LedgerDB(mc_ledger_db::Error),
+
+    /// Invalid block range: {0} to {1}
+    InvalidBlockRange(u64, u64),
+
+    /// Block range too large: {0} blocks, at most {1} can be scanned at once
+    BlockRangeTooLarge(u64, u64),
+}
+
+impl ErrorCode for AddressServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Database(err) => err.error_code(),
+            Self::Diesel(..) => "diesel",
+            Self::B58(err) => err.error_code(),
+            Self::TxoNotAtAssignedSubaddress(..) => "txo_not_at_assigned_subaddress",
+            Self::LedgerDB(..) => "ledger_db",
+            Self::InvalidBlockRange(..) => "invalid_block_range",
+            Self::BlockRangeTooLarge(..) => "block_range_too_large",
+        }
+    }
+}
+
+impl From<mc_ledger_db::Error> for AddressServiceError {
+    fn from(src: mc_ledger_db::Error) -> Self {
+        Self::LedgerDB(src)
+    }
 }
 
 impl From<WalletDbError> for AddressServiceError {
@@ -50,6 +91,17 @@ impl From<B58Error> for AddressServiceError {
     }
 }
 
+/// The most blocks `scan_address_for_txos` will scan in one call.
+pub const MAX_SCAN_ADDRESS_BLOCKS: u64 = 1000;
+
+/// A Txo found in the ledger by `scan_address_for_txos`.
+#[derive(Clone, Debug)]
+pub struct ScannedTxo {
+    pub tx_out: TxOut,
+    pub block_index: u64,
+    pub amount: Amount,
+}
+
 /// Trait defining the ways in which the wallet can interact with and manage
 /// addresses.
 #[rustfmt::skip]
@@ -98,6 +150,35 @@ pub trait AddressService {
         index: i64,
     ) -> Result<AssignedSubaddress, AddressServiceError>;
 
+    /// Derive the b58 address at a subaddress index of an account, without assigning it. The
+    /// wallet does not track Txos received at the address until it is assigned.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                      | Notes                                                           |
+    ///|--------------|----------------------------------------------|-----------------------------------------------------------------|
+    ///| `account_id` | The account on which to perform this action. | The account must exist in the wallet, and not be fog enabled.   |
+    ///| `index`      | The subaddress index to derive.              | The address does not need to have been assigned.                |
+    ///
+    fn derive_address_for_account(
+        &self,
+        account_id: &AccountID,
+        index: u64,
+    ) -> Result<String, AddressServiceError>;
+
+    /// Get the assigned address at which a Txo was received.
+    ///
+    /// # Arguments
+    ///
+    ///| Name     | Purpose                               | Notes                                                              |
+    ///|----------|---------------------------------------|--------------------------------------------------------------------|
+    ///| `txo_id` | The Txo for which to get the address. | Orphaned Txos and Txos not received by the wallet have no address. |
+    ///
+    fn get_address_for_txo(
+        &self,
+        txo_id: &TxoID,
+    ) -> Result<AssignedSubaddress, AddressServiceError>;
+
     /// Gets all the addresses for an optionally given account.
     ///
     /// # Arguments
@@ -127,6 +208,26 @@ pub trait AddressService {
         &self, 
         public_address: &str
     ) -> Result<PublicAddress, AddressServiceError>;
+
+    /// Scan a range of blocks for Txos received at an assigned address, without syncing its
+    /// account. This is quicker than a full sync for checking whether a deposit has arrived.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                | Purpose                                   | Notes                                                                               |
+    ///|---------------------|-------------------------------------------|-------------------------------------------------------------------------------------|
+    ///| `address_b58`       | The address to look for Txos received at. | The address must be assigned to an account in the wallet.                           |
+    ///| `first_block_index` | The first block to scan.                  |                                                                                     |
+    ///| `last_block_index`  | The last block to scan.                   | Defaults to the last block in the ledger. At most `MAX_SCAN_ADDRESS_BLOCKS` blocks. |
+    ///
+    /// # Returns:
+    /// * The Txos received at the address, with the blocks they were found in.
+    fn scan_address_for_txos(
+        &self,
+        address_b58: &str,
+        first_block_index: u64,
+        last_block_index: Option<u64>,
+    ) -> Result<Vec<ScannedTxo>, AddressServiceError>;
 }
 
 impl<T, FPR> AddressService for WalletService<T, FPR>
@@ -159,6 +260,32 @@ where
         Ok(AssignedSubaddress::get(address_b58, conn)?)
     }
 
+    fn get_address_for_txo(
+        &self,
+        txo_id: &TxoID,
+    ) -> Result<AssignedSubaddress, AddressServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let txo = Txo::get(&txo_id.to_string(), conn)?;
+
+        let (account_id, subaddress_index) = match (txo.account_id, txo.subaddress_index) {
+            (Some(account_id), Some(subaddress_index)) => (account_id, subaddress_index),
+            _ => {
+                return Err(AddressServiceError::TxoNotAtAssignedSubaddress(
+                    txo_id.to_string(),
+                ))
+            }
+        };
+
+        match AssignedSubaddress::get_for_account_by_index(&account_id, subaddress_index, conn) {
+            Ok(assigned_subaddress) => Ok(assigned_subaddress),
+            Err(WalletDbError::Diesel(diesel::result::Error::NotFound)) => Err(
+                AddressServiceError::TxoNotAtAssignedSubaddress(txo_id.to_string()),
+            ),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     fn get_address_for_account(
         &self,
         account_id: &AccountID,
@@ -173,6 +300,28 @@ where
         )?)
     }
 
+    fn derive_address_for_account(
+        &self,
+        account_id: &AccountID,
+        index: u64,
+    ) -> Result<String, AddressServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let account = Account::get(account_id, conn)?;
+
+        // Fog enabled accounts only receive at their assigned addresses.
+        if account.fog_enabled {
+            return Err(WalletDbError::SubaddressesNotSupportedForFOGEnabledAccounts.into());
+        }
+
+        let public_address = if account.view_only {
+            account.view_account_key()?.subaddress(index)
+        } else {
+            account.public_address(index)?
+        };
+        Ok(b58_encode_public_address(&public_address)?)
+    }
+
     fn get_addresses(
         &self,
         account_id: Option<String>,
@@ -189,6 +338,78 @@ where
     fn verify_address(&self, public_address: &str) -> Result<PublicAddress, AddressServiceError> {
         Ok(b58_decode_public_address(public_address)?)
     }
+
+    fn scan_address_for_txos(
+        &self,
+        address_b58: &str,
+        first_block_index: u64,
+        last_block_index: Option<u64>,
+    ) -> Result<Vec<ScannedTxo>, AddressServiceError> {
+        let (view_private_key, subaddress_spend_public_key) = {
+            let mut pooled_conn = self.get_pooled_conn()?;
+            let conn = pooled_conn.deref_mut();
+            let assigned_subaddress = AssignedSubaddress::get(address_b58, conn)?;
+            let account = Account::get(&AccountID(assigned_subaddress.account_id), conn)?;
+            let view_account_key = account.view_account_key()?;
+            let subaddress =
+                view_account_key.subaddress(assigned_subaddress.subaddress_index as u64);
+            (
+                *view_account_key.view_private_key(),
+                *subaddress.spend_public_key(),
+            )
+        };
+
+        let last_block_index = match last_block_index {
+            Some(last_block_index) => last_block_index,
+            None => self.ledger_db.num_blocks()?.saturating_sub(1),
+        };
+        if last_block_index < first_block_index {
+            return Err(AddressServiceError::InvalidBlockRange(
+                first_block_index,
+                last_block_index,
+            ));
+        }
+        let num_blocks = last_block_index - first_block_index + 1;
+        if num_blocks > MAX_SCAN_ADDRESS_BLOCKS {
+            return Err(AddressServiceError::BlockRangeTooLarge(
+                num_blocks,
+                MAX_SCAN_ADDRESS_BLOCKS,
+            ));
+        }
+
+        let mut scanned_txos = Vec::new();
+        for block_index in first_block_index..=last_block_index {
+            let block_contents = self.ledger_db.get_block_contents(block_index)?;
+            for tx_out in block_contents.outputs {
+                let (tx_public_key, tx_out_target_key) = match (
+                    RistrettoPublic::try_from(&tx_out.public_key),
+                    RistrettoPublic::try_from(&tx_out.target_key),
+                ) {
+                    (Ok(tx_public_key), Ok(tx_out_target_key)) => {
+                        (tx_public_key, tx_out_target_key)
+                    }
+                    _ => continue,
+                };
+                if recover_public_subaddress_spend_key(
+                    &view_private_key,
+                    &tx_out_target_key,
+                    &tx_public_key,
+                ) != subaddress_spend_public_key
+                {
+                    continue;
+                }
+                if let Some(amount) = decode_amount(&tx_out, &view_private_key) {
+                    scanned_txos.push(ScannedTxo {
+                        tx_out,
+                        block_index,
+                        amount,
+                    });
+                }
+            }
+        }
+
+        Ok(scanned_txos)
+    }
 }
 
 #[cfg(test)]
@@ -197,13 +418,16 @@ mod tests {
     use crate::{
         db::account::AccountModel,
         service::account::AccountService,
-        test_utils::{get_test_ledger, setup_wallet_service},
-        util::b58::b58_encode_public_address,
+        test_utils::{
+            add_block_to_ledger_db, create_test_received_txo, get_test_ledger,
+            setup_wallet_service, MOB,
+        },
     };
     use mc_account_keys::{AccountKey, PublicAddress};
     use mc_common::logger::{test_with_logger, Logger};
     use mc_crypto_keys::{RistrettoPrivate, RistrettoPublic};
     use mc_rand::rand_core::RngCore;
+    use mc_transaction_core::{tokens::Mob, Amount, Token};
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
 
@@ -256,7 +480,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
                 false,
+                &[],
             )
             .unwrap();
         assert_eq!(account.clone().next_subaddress_index(conn).unwrap(), 2);
@@ -271,6 +497,99 @@ mod tests {
         assert_eq!(account.next_subaddress_index(conn).unwrap(), 3);
     }
 
+    #[test_with_logger]
+    fn test_get_address_for_txo(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+        let service = setup_wallet_service(ledger_db, None, logger);
+        let wallet_db = service.wallet_db.as_ref().unwrap();
+
+        let account = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let address = service
+            .assign_address_for_account(&AccountID(account.id.clone()), Some("Customer"))
+            .unwrap();
+
+        // A Txo received at an assigned subaddress resolves to that address.
+        let (txo_id, _, _) = create_test_received_txo(
+            &account_key,
+            address.subaddress_index as u64,
+            Amount::new(100 * MOB, Mob::ID),
+            13,
+            &mut rng,
+            wallet_db,
+        );
+        let found = service.get_address_for_txo(&TxoID(txo_id)).unwrap();
+        assert_eq!(found.public_address_b58, address.public_address_b58);
+        assert_eq!(found.comment, "Customer");
+
+        // A Txo received at a subaddress which has not been assigned does not.
+        let (txo_id, _, _) = create_test_received_txo(
+            &account_key,
+            address.subaddress_index as u64 + 10,
+            Amount::new(100 * MOB, Mob::ID),
+            13,
+            &mut rng,
+            wallet_db,
+        );
+        assert!(matches!(
+            service.get_address_for_txo(&TxoID(txo_id)),
+            Err(AddressServiceError::TxoNotAtAssignedSubaddress(_))
+        ));
+    }
+
+    #[test_with_logger]
+    fn test_derive_address_for_account(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+        let service = setup_wallet_service(ledger_db, None, logger);
+
+        let account = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_id = AccountID(account.id.clone());
+        let view_only_account = service
+            .import_view_only_account(
+                &RistrettoPrivate::from_random(&mut rng).into(),
+                &RistrettoPublic::from_random(&mut rng).into(),
+                None,
+                None,
+                None,
+                None,
+                false,
+                &[],
+            )
+            .unwrap();
+        let view_only_account_id = AccountID(view_only_account.id.clone());
+
+        // A high index is derived from the account key, without being assigned.
+        let address_b58 = service
+            .derive_address_for_account(&account_id, 1000)
+            .unwrap();
+        assert_eq!(
+            address_b58,
+            b58_encode_public_address(&account.public_address(1000).unwrap()).unwrap()
+        );
+        assert!(service.get_address(&address_b58).is_err());
+
+        // The next index derives the address that is then assigned, for view only
+        // accounts too.
+        for account_id in [account_id, view_only_account_id] {
+            let address_b58 = service.derive_address_for_account(&account_id, 2).unwrap();
+            let assigned_subaddress = service
+                .assign_address_for_account(&account_id, None)
+                .unwrap();
+            assert_eq!(assigned_subaddress.subaddress_index, 2);
+            assert_eq!(address_b58, assigned_subaddress.public_address_b58);
+        }
+        assert_eq!(service.get_addresses(None, None, None).unwrap().len(), 6);
+    }
+
     // A properly encoded address should verify.
     #[test_with_logger]
     fn test_verify_address_succeeds(logger: Logger) {
@@ -310,4 +629,63 @@ mod tests {
             bs58::encode(mc_util_serial::encode(&public_address)).into_string();
         assert!(service.verify_address(&public_address_b58).is_err());
     }
+
+    #[test_with_logger]
+    fn test_scan_address_for_txos(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let mut ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+        let service = setup_wallet_service(ledger_db.clone(), None, logger);
+
+        let account = service
+            .create_account(None, "".to_string(), "".to_string(), false)
+            .unwrap();
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key).unwrap();
+        let address = service
+            .assign_address_for_account(&AccountID(account.id.clone()), Some("Deposit"))
+            .unwrap();
+
+        // Two Txos to the deposit address, and one to the account's main address.
+        let deposit_address = account_key.subaddress(address.subaddress_index as u64);
+        let num_blocks = add_block_to_ledger_db(
+            &mut ledger_db,
+            &[
+                deposit_address.clone(),
+                account_key.default_subaddress(),
+                deposit_address,
+            ],
+            42 * MOB,
+            &[],
+            &mut rng,
+        );
+        let block_index = num_blocks - 1;
+
+        let scanned_txos = service
+            .scan_address_for_txos(&address.public_address_b58, block_index, None)
+            .unwrap();
+        assert_eq!(scanned_txos.len(), 2);
+        for scanned_txo in scanned_txos {
+            assert_eq!(scanned_txo.block_index, block_index);
+            assert_eq!(scanned_txo.amount, Amount::new(42 * MOB, Mob::ID));
+        }
+
+        // Blocks outside the range are not scanned.
+        let scanned_txos = service
+            .scan_address_for_txos(&address.public_address_b58, 0, Some(block_index - 1))
+            .unwrap();
+        assert!(scanned_txos.is_empty());
+
+        match service.scan_address_for_txos(&address.public_address_b58, 5, Some(4)) {
+            Err(AddressServiceError::InvalidBlockRange(5, 4)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        match service.scan_address_for_txos(
+            &address.public_address_b58,
+            0,
+            Some(MAX_SCAN_ADDRESS_BLOCKS),
+        ) {
+            Err(AddressServiceError::BlockRangeTooLarge(_, MAX_SCAN_ADDRESS_BLOCKS)) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
 }
@@ -1,7 +1,12 @@
 // Copyright (c) 2020-2021 MobileCoin Inc.
 
 //! Service for managing balances.
-use std::{collections::BTreeMap, convert::TryFrom, ops::DerefMut};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    ops::DerefMut,
+    time::Duration,
+};
 
 use crate::{
     config::NetworkConfig,
@@ -9,12 +14,14 @@ use crate::{
         account::{AccountID, AccountModel},
         assigned_subaddress::AssignedSubaddressModel,
         models::{Account, AssignedSubaddress, Txo},
-        txo::TxoModel,
+        txo::{TxoModel, TxoStatus},
         Conn, WalletDbError,
     },
+    error::ErrorCode,
     service::{
         account::{AccountService, AccountServiceError},
         ledger::{LedgerService, LedgerServiceError},
+        sync_progress::SyncEstimate,
         WalletService,
     },
 };
@@ -52,6 +59,20 @@ pub enum BalanceServiceError {
     FeeMap(FeeMapError),
 }
 
+impl ErrorCode for BalanceServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Database(err) => err.error_code(),
+            Self::Diesel(..) => "diesel",
+            Self::LedgerDB(..) => "ledger_db",
+            Self::NetworkBlockHeight(err) => err.error_code(),
+            Self::UnexpectedAccountTxoStatus(..) => "unexpected_account_txo_status",
+            Self::AccountServiceError(err) => err.error_code(),
+            Self::FeeMap(..) => "fee_map",
+        }
+    }
+}
+
 impl From<WalletDbError> for BalanceServiceError {
     fn from(src: WalletDbError) -> Self {
         Self::Database(src)
@@ -117,6 +138,28 @@ impl Default for &Balance {
     }
 }
 
+/// The unspent value and number of received txos for an address.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AddressBalance {
+    pub unspent_per_token: BTreeMap<TokenId, u128>,
+    pub num_received_txos: u64,
+}
+
+/// The total value an account has received and sent over its lifetime, for a
+/// single token.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LifetimeStats {
+    pub received: u128,
+    pub sent: u128,
+}
+
+impl LifetimeStats {
+    /// The value received less the value sent.
+    pub fn net(&self) -> i128 {
+        self.received as i128 - self.sent as i128
+    }
+}
+
 /// The Network Status object.
 /// This holds the number of blocks in the ledger, on the network and locally.
 pub struct NetworkStatus {
@@ -126,6 +169,9 @@ pub struct NetworkStatus {
     pub fees: FeeMap,
     pub block_version: u32,
     pub network_info: NetworkConfig,
+    /// How long ago the block info used for the network fields was fetched,
+    /// or None in offline mode.
+    pub block_info_age: Option<Duration>,
 }
 
 /// The Wallet Status object returned by balance services.
@@ -142,6 +188,7 @@ pub struct WalletStatus {
     pub min_synced_block_index: u64,
     pub account_ids: Vec<AccountID>,
     pub account_map: HashMap<AccountID, Account>,
+    pub sync_estimate: SyncEstimate,
 }
 
 impl WalletStatus {
@@ -167,6 +214,20 @@ pub trait BalanceService {
         account_id: &AccountID,
     ) -> Result<BTreeMap<TokenId, Balance>, BalanceServiceError>;
 
+    /// Gets the balances for several accounts at once, sharing a single database connection and
+    /// network status lookup between them.
+    ///
+    /// # Arguments
+    ///
+    ///| Name       | Purpose                                  | Notes                                 |
+    ///|------------|------------------------------------------|---------------------------------------|
+    ///| `accounts` | The accounts for which to get balances.  | Accounts must exist in the wallet.    |
+    ///
+    fn get_balances_for_accounts(
+        &self,
+        accounts: &[Account],
+    ) -> Result<HashMap<AccountID, BTreeMap<TokenId, Balance>>, BalanceServiceError>;
+
     /// Get the current balance for a given address.
     ///
     /// # Arguments
@@ -180,11 +241,59 @@ pub trait BalanceService {
         address: &str,
     ) -> Result<BTreeMap<TokenId, Balance>, BalanceServiceError>;
 
+    /// Get the unspent value per token and the number of received txos for several addresses at
+    /// once, sharing a single database connection between them.
+    ///
+    /// # Arguments
+    ///
+    ///| Name        | Purpose                                    | Notes                                                    |
+    ///|-------------|--------------------------------------------|----------------------------------------------------------|
+    ///| `addresses` | The addresses for which to get balances.   | Addresses must be assigned for an account in the wallet. |
+    ///
+    fn get_address_balances(
+        &self,
+        addresses: &[AssignedSubaddress],
+    ) -> Result<HashMap<String, AddressBalance>, BalanceServiceError>;
+
+    /// Get the total value ever received and sent by an account, per token. Received value counts
+    /// every txo received by the account whether or not it has since been spent, excluding
+    /// change. Sent value counts the outputs of the account's submitted transactions, excluding
+    /// change, fees, and failed transactions.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                      | Notes                             |
+    ///|--------------|----------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account on which to perform this action. | Account must exist in the wallet. |
+    ///
+    fn get_account_lifetime_stats(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<BTreeMap<TokenId, LifetimeStats>, BalanceServiceError>;
+
     /// Get the current status of the network.
     fn get_network_status(&self) -> Result<NetworkStatus, BalanceServiceError>;
 
+    /// Get the ids of the tokens the network currently has fees for, and so can be transacted.
+    /// MOB is always included.
+    fn get_supported_token_ids(&self) -> Result<Vec<TokenId>, BalanceServiceError>;
+
     /// Get the current status of a wallet. **Note that pmob calculations do not include view-only-accounts**
     fn get_wallet_status(&self) -> Result<WalletStatus, BalanceServiceError>;
+
+    /// Get the rate at which an account is syncing, and an estimate of how long it will take to
+    /// sync up to the network block height.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                      | Notes                             |
+    ///|--------------|----------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account on which to perform this action. | Account must exist in the wallet. |
+    ///
+    fn get_account_sync_estimate(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<SyncEstimate, BalanceServiceError>;
 }
 
 impl<T, FPR> BalanceService for WalletService<T, FPR>
@@ -202,6 +311,7 @@ where
         let distinct_token_ids = account.get_token_ids(conn)?;
 
         let network_status = self.get_network_status()?;
+        let max_received_block_index = self.max_spendable_received_block_index()?;
 
         let balances = distinct_token_ids
             .into_iter()
@@ -215,6 +325,7 @@ where
                     None,
                     token_id,
                     &default_token_fee,
+                    max_received_block_index,
                     conn,
                 )?;
                 Ok((token_id, balance))
@@ -224,6 +335,43 @@ where
         Ok(balances)
     }
 
+    fn get_balances_for_accounts(
+        &self,
+        accounts: &[Account],
+    ) -> Result<HashMap<AccountID, BTreeMap<TokenId, Balance>>, BalanceServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let network_status = self.get_network_status()?;
+        let max_received_block_index = self.max_spendable_received_block_index()?;
+
+        accounts
+            .iter()
+            .map(|account| {
+                let balances = account
+                    .clone()
+                    .get_token_ids(conn)?
+                    .into_iter()
+                    .map(|token_id| {
+                        let default_token_fee = network_status
+                            .fees
+                            .get_fee_for_token(&token_id)
+                            .unwrap_or(0);
+                        let balance = Self::get_balance_inner(
+                            Some(&account.id),
+                            None,
+                            token_id,
+                            &default_token_fee,
+                            max_received_block_index,
+                            conn,
+                        )?;
+                        Ok((token_id, balance))
+                    })
+                    .collect::<Result<BTreeMap<TokenId, Balance>, BalanceServiceError>>()?;
+                Ok((AccountID(account.id.clone()), balances))
+            })
+            .collect()
+    }
+
     fn get_balance_for_address(
         &self,
         address: &str,
@@ -235,6 +383,7 @@ where
         let account = self.get_account(&account_id)?;
         let distinct_token_ids = account.get_token_ids(conn)?;
         let network_status = self.get_network_status()?;
+        let max_received_block_index = self.max_spendable_received_block_index()?;
 
         let balances = distinct_token_ids
             .into_iter()
@@ -248,6 +397,7 @@ where
                     Some(address),
                     token_id,
                     &default_token_fee,
+                    max_received_block_index,
                     conn,
                 )?;
                 Ok((token_id, balance))
@@ -257,38 +407,125 @@ where
         Ok(balances)
     }
 
+    fn get_address_balances(
+        &self,
+        addresses: &[AssignedSubaddress],
+    ) -> Result<HashMap<String, AddressBalance>, BalanceServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        addresses
+            .iter()
+            .map(|address| {
+                let address_b58 = &address.public_address_b58;
+                let num_received_txos =
+                    Txo::list_for_address(address_b58, None, None, None, None, None, None, conn)?
+                        .len() as u64;
+
+                let mut unspent_per_token = BTreeMap::new();
+                for txo in Txo::list_for_address(
+                    address_b58,
+                    Some(TxoStatus::Unspent),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    conn,
+                )? {
+                    *unspent_per_token
+                        .entry(TokenId::from(txo.token_id as u64))
+                        .or_insert(0) += txo.value as u64 as u128;
+                }
+
+                Ok((
+                    address_b58.clone(),
+                    AddressBalance {
+                        unspent_per_token,
+                        num_received_txos,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    fn get_account_lifetime_stats(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<BTreeMap<TokenId, LifetimeStats>, BalanceServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let account = Account::get(account_id, conn)?;
+
+        let mut stats: BTreeMap<TokenId, LifetimeStats> = BTreeMap::new();
+        for (token_id, received) in account.lifetime_received(conn)? {
+            stats.entry(token_id).or_default().received = received;
+        }
+        for (token_id, sent) in account.lifetime_sent(conn)? {
+            stats.entry(token_id).or_default().sent = sent;
+        }
+
+        Ok(stats)
+    }
+
     fn get_network_status(&self) -> Result<NetworkStatus, BalanceServiceError> {
-        let (network_block_height, fee_map, block_version) = match self.offline {
+        let (network_block_height, fee_map, block_version, block_info_age) = match self.offline {
             true => {
                 let mut fees = BTreeMap::new();
                 fees.insert(Mob::ID, Mob::MINIMUM_FEE);
                 fees.insert(TokenId::from(1), 2560);
                 let fee_map = FeeMap::try_from(fees)?;
-                (0, fee_map, *BlockVersion::MAX)
+                (0, fee_map, *BlockVersion::MAX, None)
             }
             false => {
-                let network_block_info = self.get_latest_block_info()?;
+                let network_block_info = self.get_cached_block_info()?;
                 (
                     network_block_info.block_index + 1,
                     FeeMap::try_from(network_block_info.minimum_fees)?,
                     network_block_info.network_block_version,
+                    self.block_info_cache.age(),
                 )
             }
         };
 
+        // The transaction sources may have been replaced since startup.
+        let mut network_info = self.network_setup_config.clone();
+        if let Some(transactions_fetcher) = &self.transactions_fetcher {
+            network_info.tx_sources = Some(
+                transactions_fetcher
+                    .source_urls()
+                    .iter()
+                    .map(|url| url.to_string())
+                    .collect(),
+            );
+        }
+
         Ok(NetworkStatus {
             network_block_height,
             local_block_height: self.ledger_db.num_blocks()?,
             local_num_txos: self.ledger_db.num_txos()?,
             fees: fee_map,
             block_version,
-            network_info: self.network_setup_config.clone(),
+            network_info,
+            block_info_age,
         })
     }
 
+    fn get_supported_token_ids(&self) -> Result<Vec<TokenId>, BalanceServiceError> {
+        let network_status = self.get_network_status()?;
+        let mut token_ids: BTreeSet<TokenId> = network_status
+            .fees
+            .iter()
+            .map(|(token_id, _fee)| *token_id)
+            .collect();
+        token_ids.insert(Mob::ID);
+        Ok(token_ids.into_iter().collect())
+    }
+
     // Wallet Status is an overview of the wallet's status
     fn get_wallet_status(&self) -> Result<WalletStatus, BalanceServiceError> {
         let network_status = self.get_network_status()?;
+        let max_received_block_index = self.max_spendable_received_block_index()?;
 
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
@@ -299,6 +536,7 @@ where
 
         let mut min_synced_block_index = network_status.local_block_height.saturating_sub(1);
         let mut account_ids = Vec::new();
+        let mut remaining_blocks = Vec::new();
 
         for account in accounts {
             let account_id = AccountID(account.id.clone());
@@ -314,6 +552,7 @@ where
                     None,
                     token_id,
                     &default_token_fee,
+                    max_received_block_index,
                     conn,
                 )?;
                 balance_per_token
@@ -335,6 +574,12 @@ where
                 min_synced_block_index,
                 (account.next_block_index as u64).saturating_sub(1),
             );
+            remaining_blocks.push((
+                account_id.clone(),
+                network_status
+                    .network_block_height
+                    .saturating_sub(account.next_block_index as u64),
+            ));
             account_ids.push(account_id);
         }
 
@@ -345,8 +590,21 @@ where
             min_synced_block_index,
             account_ids,
             account_map,
+            sync_estimate: self.sync_progress.estimate_all(&remaining_blocks),
         })
     }
+
+    fn get_account_sync_estimate(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<SyncEstimate, BalanceServiceError> {
+        let network_status = self.get_network_status()?;
+        let account = self.get_account(account_id)?;
+        let remaining_blocks = network_status
+            .network_block_height
+            .saturating_sub(account.next_block_index as u64);
+        Ok(self.sync_progress.estimate(account_id, remaining_blocks))
+    }
 }
 
 fn sum_query_result(txos: Vec<Txo>) -> u128 {
@@ -364,6 +622,7 @@ where
         public_address_b58: Option<&str>,
         token_id: TokenId,
         default_token_fee: &u64,
+        max_received_block_index: Option<u64>,
         conn: Conn,
     ) -> Result<Balance, BalanceServiceError> {
         let unspent = sum_query_result(Txo::list_unspent(
@@ -432,6 +691,7 @@ where
             public_address_b58,
             *token_id,
             *default_token_fee,
+            max_received_block_index,
             conn,
         )?;
 
@@ -461,6 +721,19 @@ mod tests {
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
 
+    // The supported token ids are those the network has fees for.
+    #[test_with_logger]
+    fn test_get_supported_token_ids(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+        let service = setup_wallet_service(ledger_db, None, logger);
+
+        assert_eq!(
+            service.get_supported_token_ids().unwrap(),
+            vec![Mob::ID, TokenId::from(1)]
+        );
+    }
+
     // The balance for an address should be accurate.
     #[test_with_logger]
     fn test_address_balance(logger: Logger) {
@@ -559,6 +832,32 @@ mod tests {
         assert_eq!(address_balance2_pmob.secreted, 0);
         assert_eq!(address_balance2_pmob.orphaned, 0);
 
+        let main_address = service
+            .get_address(&b58_pub_address)
+            .expect("Could not get address");
+        let address_balances = service
+            .get_address_balances(&[main_address, address.clone()])
+            .expect("Could not get address balances");
+        assert_eq!(address_balances.len(), 2);
+        for address_b58 in [&b58_pub_address, &address.public_address_b58] {
+            let address_balance = address_balances.get(address_b58).unwrap();
+            assert_eq!(address_balance.num_received_txos, 12);
+            assert_eq!(
+                address_balance.unspent_per_token,
+                BTreeMap::from([(Mob::ID, 60_000 * MOB as u128)])
+            );
+        }
+
+        // Every received txo counts towards the lifetime total, including the
+        // orphaned txos at subaddress 3, and nothing has been sent.
+        let lifetime_stats = service
+            .get_account_lifetime_stats(&AccountID(account.id.clone()))
+            .expect("Could not get lifetime stats");
+        let lifetime_stats_pmob = lifetime_stats.get(&Mob::ID).unwrap();
+        assert_eq!(lifetime_stats_pmob.received, 240_000 * MOB as u128);
+        assert_eq!(lifetime_stats_pmob.sent, 0);
+        assert_eq!(lifetime_stats_pmob.net(), 240_000 * MOB as i128);
+
         // Even though subaddress 3 has funds, we are not watching it, so we should get
         // an error.
         let b58_pub_address3 =
@@ -0,0 +1,123 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! A cache of the latest block info reported by the network.
+//!
+//! Fetching block info queries every peer, which is too slow to do for every
+//! transaction built. The ledger sync thread refreshes this cache each time it
+//! polls the network, and the wallet service reads the network fees and block
+//! version from it, only querying the peers itself if the cache has gone
+//! stale.
+
+use crate::service::ledger::LedgerServiceError;
+use mc_connection::{
+    _retry::delay::Fibonacci, BlockInfo, BlockchainConnection, ConnectionManager,
+    RetryableBlockchainConnection,
+};
+use rayon::prelude::*; // For par_iter
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Default)]
+pub struct BlockInfoCache {
+    /// The latest block info, along with the time it was fetched.
+    latest: Arc<RwLock<Option<(Instant, BlockInfo)>>>,
+}
+
+impl BlockInfoCache {
+    /// Get the cached block info, or None if there is none or it is older
+    /// than `max_age`.
+    pub fn get(&self, max_age: Duration) -> Option<BlockInfo> {
+        let latest = self.latest.read().expect("lock poisoned");
+        match latest.as_ref() {
+            Some((fetched_at, block_info)) if fetched_at.elapsed() < max_age => {
+                Some(block_info.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// How long ago the cached block info was fetched, or None if it has never
+    /// been fetched.
+    pub fn age(&self) -> Option<Duration> {
+        let latest = self.latest.read().expect("lock poisoned");
+        latest.as_ref().map(|(fetched_at, _)| fetched_at.elapsed())
+    }
+
+    pub fn set(&self, block_info: BlockInfo) {
+        let mut latest = self.latest.write().expect("lock poisoned");
+        *latest = Some((Instant::now(), block_info));
+    }
+
+    /// Fetch the latest block info from the peers and cache it, unless the
+    /// cached block info is younger than `max_age`.
+    pub fn refresh_if_older_than<BC: BlockchainConnection + 'static>(
+        &self,
+        peer_manager: &ConnectionManager<BC>,
+        max_age: Duration,
+    ) -> Result<(), LedgerServiceError> {
+        if self.get(max_age).is_none() {
+            self.refresh(peer_manager)?;
+        }
+        Ok(())
+    }
+
+    /// Fetch the latest block info from the peers and cache it.
+    pub fn refresh<BC: BlockchainConnection + 'static>(
+        &self,
+        peer_manager: &ConnectionManager<BC>,
+    ) -> Result<BlockInfo, LedgerServiceError> {
+        // Get the last block information from all nodes we are aware of, in parallel.
+        let last_block_infos = peer_manager
+            .conns()
+            .par_iter()
+            .filter_map(|conn| {
+                conn.fetch_block_info(Fibonacci::from_millis(10).take(5))
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+
+        // Ensure that all nodes agree on the latest block version and network fees.
+        if last_block_infos.windows(2).any(|window| {
+            window[0].network_block_version != window[1].network_block_version
+                || window[0].minimum_fees != window[1].minimum_fees
+        }) {
+            return Err(LedgerServiceError::InconsistentLastBlockInfo);
+        }
+
+        let block_info = last_block_infos
+            .first()
+            .cloned()
+            .ok_or(LedgerServiceError::NoLastBlockInfo)?;
+        self.set(block_info.clone());
+
+        Ok(block_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_transaction_core::{tokens::Mob, Token};
+
+    #[test]
+    fn test_get_respects_max_age() {
+        let cache = BlockInfoCache::default();
+        assert!(cache.get(Duration::from_secs(60)).is_none());
+        assert!(cache.age().is_none());
+
+        let mut block_info = BlockInfo {
+            block_index: 10,
+            ..Default::default()
+        };
+        block_info.minimum_fees.insert(Mob::ID, Mob::MINIMUM_FEE);
+        cache.set(block_info.clone());
+
+        assert_eq!(cache.get(Duration::from_secs(60)), Some(block_info));
+        assert!(cache.age().unwrap() < Duration::from_secs(60));
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cache.get(Duration::from_millis(10)).is_none());
+    }
+}
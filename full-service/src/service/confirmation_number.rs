@@ -11,6 +11,7 @@ use crate::{
         txo::{TxoID, TxoModel},
         WalletDbError,
     },
+    error::ErrorCode,
     service::{
         transaction_log::{TransactionLogService, TransactionLogServiceError},
         txo::{TxoService, TxoServiceError},
@@ -51,6 +52,25 @@ pub enum ConfirmationServiceError {
 
     /// Error with the TxoService: {0}
     TransactionLogService(TransactionLogServiceError),
+
+    /// Txo is not owned by an account in this wallet: {0}
+    TxoNotOwned(String),
+}
+
+impl ErrorCode for ConfirmationServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Database(err) => err.error_code(),
+            Self::Diesel(..) => "diesel",
+            Self::LedgerDB(..) => "ledger_db",
+            Self::ProstDecode(..) => "prost_decode",
+            Self::HexDecode(..) => "hex_decode",
+            Self::MissingConfirmation(..) => "missing_confirmation",
+            Self::TxoService(err) => err.error_code(),
+            Self::TransactionLogService(err) => err.error_code(),
+            Self::TxoNotOwned(..) => "txo_not_owned",
+        }
+    }
 }
 
 impl From<WalletDbError> for ConfirmationServiceError {
@@ -120,6 +140,22 @@ pub trait ConfirmationService {
         transaction_log_id: &str,
     ) -> Result<Vec<Confirmation>, ConfirmationServiceError>;
 
+    /// Get the stored confirmation number for a Txo received by an account in the wallet.
+    ///
+    /// # Arguments
+    ///
+    ///| Name     | Purpose                                                     | Notes                                          |
+    ///|----------|-------------------------------------------------------------|------------------------------------------------|
+    ///| `txo_id` | The ID of the TXO for which to get the confirmation number. | TXO must be owned by an account in the wallet. |
+    ///
+    /// # Returns
+    /// * The confirmation number, or None if the wallet has no confirmation number stored for
+    ///   the TXO.
+    fn get_txo_confirmation_number(
+        &self,
+        txo_id: &TxoID,
+    ) -> Result<Option<TxOutConfirmationNumber>, ConfirmationServiceError>;
+
     /// Validate the confirmation number with a given Txo.
     ///
     /// # Arguments
@@ -172,6 +208,23 @@ where
         Ok(results)
     }
 
+    fn get_txo_confirmation_number(
+        &self,
+        txo_id: &TxoID,
+    ) -> Result<Option<TxOutConfirmationNumber>, ConfirmationServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let txo = Txo::get(&txo_id.to_string(), conn)?;
+        if txo.account_id.is_none() {
+            return Err(ConfirmationServiceError::TxoNotOwned(txo.id));
+        }
+
+        Ok(txo
+            .confirmation
+            .map(|confirmation| mc_util_serial::decode(&confirmation))
+            .transpose()?)
+    }
+
     fn validate_confirmation(
         &self,
         account_id: &AccountID,
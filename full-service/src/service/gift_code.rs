@@ -11,11 +11,11 @@ use crate::{
     db::{
         account::{AccountID, AccountModel},
         exclusive_transaction,
-        gift_code::GiftCodeModel,
+        gift_code::{GiftCodeDbError, GiftCodeModel},
         models::{Account, GiftCode},
         WalletDbError,
     },
-    error::WalletTransactionBuilderError,
+    error::{ErrorCode, WalletTransactionBuilderError},
     service::{
         account::AccountServiceError,
         address::{AddressService, AddressServiceError},
@@ -43,7 +43,7 @@ use mc_transaction_builder::{InputCredentials, RTHMemoBuilder, TransactionBuilde
 use mc_transaction_core::{
     constants::RING_SIZE,
     get_tx_out_shared_secret,
-    onetime_keys::recover_onetime_private_key,
+    onetime_keys::{recover_onetime_private_key, recover_public_subaddress_spend_key},
     ring_signature::KeyImage,
     tokens::Mob,
     tx::{Tx, TxOut},
@@ -133,6 +133,13 @@ pub enum GiftCodeServiceError {
     /// Gift Code was removed from the DB prior to claiming
     GiftCodeRemoved,
 
+    /// Gift Code Txo value does not match the value it was built with: expected
+    /// {0}, found {1}
+    GiftCodeValueMismatch(u64, u64),
+
+    /// Gift Code Txo is not owned by the gift account's main subaddress
+    GiftCodeTxoNotOwned,
+
     /// Node Not Found
     NodeNotFound,
 
@@ -176,6 +183,53 @@ pub enum GiftCodeServiceError {
     Retry(mc_connection::RetryError<mc_connection::Error>),
 }
 
+impl ErrorCode for GiftCodeServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Database(err) => err.error_code(),
+            Self::LedgerDB(..) => "ledger_db",
+            Self::HexDecode(..) => "hex_decode",
+            Self::ProstDecode(..) => "prost_decode",
+            Self::BuildGiftCodeFailed => "build_gift_code_failed",
+            Self::UnexpectedTxStatus(..) => "unexpected_tx_status",
+            Self::UnexpectedNumOutputs(..) => "unexpected_num_outputs",
+            Self::InsufficientValueForFee(..) => "insufficient_value_for_fee",
+            Self::UnexpectedNumTxosInGiftCodeAccount(..) => {
+                "unexpected_num_txos_in_gift_code_account"
+            }
+            Self::UnexpectedValueInGiftCodeTxo(..) => "unexpected_value_in_gift_code_txo",
+            Self::TxoNotConsumable => "txo_not_consumable",
+            Self::AccountNotFound => "account_not_found",
+            Self::UnexpectedTxProposalFormat => "unexpected_tx_proposal_format",
+            Self::Diesel(..) => "diesel",
+            Self::TransactionService(err) => err.error_code(),
+            Self::AccountService(err) => err.error_code(),
+            Self::PrintableWrapper(..) => "printable_wrapper",
+            Self::CryptoKey(..) => "crypto_key",
+            Self::GiftCodeTxoNotInLedger(..) => "gift_code_txo_not_in_ledger",
+            Self::GiftCodeClaimed => "gift_code_claimed",
+            Self::GiftCodeNotYetAvailable => "gift_code_not_yet_available",
+            Self::GiftCodeRemoved => "gift_code_removed",
+            Self::GiftCodeValueMismatch(..) => "gift_code_value_mismatch",
+            Self::GiftCodeTxoNotOwned => "gift_code_txo_not_owned",
+            Self::NodeNotFound => "node_not_found",
+            Self::Connection(..) => "connection",
+            Self::ProtoConversion(..) => "proto_conversion",
+            Self::TxBuilder(..) => "tx_builder",
+            Self::UriParse(..) => "uri_parse",
+            Self::AddressService(err) => err.error_code(),
+            Self::B58(err) => err.error_code(),
+            Self::FogPubkeyResolver(..) => "fog_pubkey_resolver",
+            Self::InvalidFogUri(..) => "invalid_fog_uri",
+            Self::Amount(..) => "amount",
+            Self::WalletTransactionBuilder(err) => err.error_code(),
+            Self::TxOutConversion(..) => "tx_out_conversion",
+            Self::LedgerService(err) => err.error_code(),
+            Self::Retry(..) => "retry",
+        }
+    }
+}
+
 impl From<WalletDbError> for GiftCodeServiceError {
     fn from(src: WalletDbError) -> Self {
         Self::Database(src)
@@ -548,7 +602,7 @@ where
                    * in gift_code construction */
         )?;
 
-        let tx_proposal = unsigned_tx_proposal.sign(&from_account).await?;
+        let tx_proposal = unsigned_tx_proposal.sign(&from_account, None).await?;
 
         if tx_proposal.payload_txos.len() != 1 {
             return Err(GiftCodeServiceError::UnexpectedTxProposalFormat);
@@ -711,6 +765,21 @@ where
 
         let gift_value = gift_value.ok_or(GiftCodeServiceError::GiftCodeNotYetAvailable)?;
 
+        // The value was decoded from the gift Txo with the gift account's view
+        // key, which also verifies it against the Txo's amount commitment. Gift
+        // codes built by this wallet additionally record the value they were
+        // funded with, which the decoded value must match.
+        match GiftCode::get(gift_code_b58, self.get_pooled_conn()?.deref_mut()) {
+            Ok(gift_code) if gift_code.value != gift_value => {
+                return Err(GiftCodeServiceError::GiftCodeValueMismatch(
+                    gift_code.value as u64,
+                    gift_value as u64,
+                ))
+            }
+            Ok(_) | Err(WalletDbError::GiftCode(GiftCodeDbError::GiftCodeNotFound(_))) => {}
+            Err(e) => return Err(e.into()),
+        }
+
         let transfer_payload = decode_transfer_payload(gift_code_b58)?;
         let gift_account_key = transfer_payload.account_key;
 
@@ -779,6 +848,10 @@ where
 
         let real_output = ring[0].clone();
 
+        // Every claim, including of gift codes this wallet did not build, is
+        // checked against the funding Txo itself before it is spent.
+        verify_gift_txo(&real_output, &gift_account_key, gift_value as u64)?;
+
         let onetime_private_key = recover_onetime_private_key(
             &RistrettoPublic::try_from(&real_output.public_key)?,
             gift_account_key.view_private_key(),
@@ -859,21 +932,58 @@ pub fn decode_transfer_payload(
     Ok(b58_decode_transfer_payload(gift_code_b58.to_string())?)
 }
 
+/// Verify that a gift code's funding Txo is owned by the main subaddress of
+/// the gift account, and that it holds the expected value of MOB. Decoding the
+/// value with the gift account's view key checks it against the Txo's amount
+/// commitment.
+#[allow(clippy::result_large_err)]
+fn verify_gift_txo(
+    gift_txo: &TxOut,
+    gift_account_key: &AccountKey,
+    expected_value: u64,
+) -> Result<(), GiftCodeServiceError> {
+    let public_key = RistrettoPublic::try_from(&gift_txo.public_key)?;
+    let subaddress_spend_public_key = recover_public_subaddress_spend_key(
+        gift_account_key.view_private_key(),
+        &RistrettoPublic::try_from(&gift_txo.target_key)?,
+        &public_key,
+    );
+    if &subaddress_spend_public_key
+        != gift_account_key
+            .subaddress(DEFAULT_SUBADDRESS_INDEX)
+            .spend_public_key()
+    {
+        return Err(GiftCodeServiceError::GiftCodeTxoNotOwned);
+    }
+
+    let shared_secret = get_tx_out_shared_secret(gift_account_key.view_private_key(), &public_key);
+    let (amount, _blinding) = gift_txo.get_masked_amount()?.get_value(&shared_secret)?;
+    if amount.token_id != Mob::ID || amount.value != expected_value {
+        return Err(GiftCodeServiceError::GiftCodeValueMismatch(
+            expected_value,
+            amount.value,
+        ));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         service::{account::AccountService, balance::BalanceService},
         test_utils::{
-            add_block_to_ledger_db, add_block_with_tx, get_test_ledger, manually_sync_account,
-            setup_wallet_service, MOB,
+            add_block_to_ledger_db, add_block_with_tx, create_test_txo_for_recipient,
+            get_test_ledger, manually_sync_account, setup_wallet_service, MOB,
         },
     };
     use mc_account_keys::PublicAddress;
     use mc_common::logger::{async_test_with_logger, Logger};
     use mc_rand::rand_core::RngCore;
-    use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, Token};
+    use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, Token, TokenId};
     use rand::{rngs::StdRng, SeedableRng};
+    use std::assert_matches::assert_matches;
 
     #[async_test_with_logger]
     async fn test_gift_code_lifecycle(logger: Logger) {
@@ -1025,6 +1135,24 @@ mod tests {
         );
         assert!(result.is_err());
 
+        // A gift code whose Txo value does not match the value it was built with
+        // cannot be claimed.
+        let set_gift_code_value = |value: u64| {
+            use crate::db::schema::gift_codes;
+            use diesel::prelude::*;
+            diesel::update(gift_codes::table)
+                .set(gift_codes::value.eq(value as i64))
+                .execute(service.get_pooled_conn().unwrap().deref_mut())
+                .unwrap();
+        };
+        set_gift_code_value(3 * MOB);
+        assert_matches!(
+            service.claim_gift_code(&gift_code_b58, &AccountID(bob.id.clone()), None),
+            Err(GiftCodeServiceError::GiftCodeValueMismatch(expected, found))
+                if expected == 3 * MOB && found == 2 * MOB
+        );
+        set_gift_code_value(2 * MOB);
+
         let tx = service
             .claim_gift_code(&gift_code_b58, &AccountID(bob.id.clone()), None)
             .unwrap();
@@ -1160,4 +1288,54 @@ mod tests {
             .expect("Could not list gift codes");
         assert_eq!(gift_codes.len(), 0);
     }
+
+    #[test]
+    fn test_verify_gift_txo() {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+        let gift_account_key = AccountKey::random(&mut rng);
+
+        let (gift_txo, _) = create_test_txo_for_recipient(
+            &gift_account_key,
+            DEFAULT_SUBADDRESS_INDEX,
+            Amount::new(2 * MOB, Mob::ID),
+            &mut rng,
+        );
+        verify_gift_txo(&gift_txo, &gift_account_key, 2 * MOB).unwrap();
+        assert_matches!(
+            verify_gift_txo(&gift_txo, &gift_account_key, 3 * MOB),
+            Err(GiftCodeServiceError::GiftCodeValueMismatch(expected, found))
+                if expected == 3 * MOB && found == 2 * MOB
+        );
+
+        // A Txo of another token is not a gift of MOB.
+        let (gift_txo, _) = create_test_txo_for_recipient(
+            &gift_account_key,
+            DEFAULT_SUBADDRESS_INDEX,
+            Amount::new(2 * MOB, TokenId::from(1)),
+            &mut rng,
+        );
+        assert_matches!(
+            verify_gift_txo(&gift_txo, &gift_account_key, 2 * MOB),
+            Err(GiftCodeServiceError::GiftCodeValueMismatch(..))
+        );
+
+        // Nor is a Txo sent to another subaddress, or to another account.
+        let (gift_txo, _) = create_test_txo_for_recipient(
+            &gift_account_key,
+            1,
+            Amount::new(2 * MOB, Mob::ID),
+            &mut rng,
+        );
+        assert_matches!(
+            verify_gift_txo(&gift_txo, &gift_account_key, 2 * MOB),
+            Err(GiftCodeServiceError::GiftCodeTxoNotOwned)
+        );
+        let (gift_txo, _) = create_test_txo_for_recipient(
+            &AccountKey::random(&mut rng),
+            DEFAULT_SUBADDRESS_INDEX,
+            Amount::new(2 * MOB, Mob::ID),
+            &mut rng,
+        );
+        assert!(verify_gift_txo(&gift_txo, &gift_account_key, 2 * MOB).is_err());
+    }
 }
@@ -2,31 +2,58 @@
 
 //! Service for managing ledger materials and MobileCoin protocol objects.
 
-use std::convert::{TryFrom, TryInto};
+use std::{
+    convert::{TryFrom, TryInto},
+    sync::Mutex,
+};
 
-use ledger_mob::{DeviceHandle, Filters, LedgerHandle, LedgerProvider, Transport};
+use lazy_static::lazy_static;
+use ledger_mob::{DeviceHandle, Filters, LedgerHandle, LedgerInfo, LedgerProvider, Transport};
 
 use mc_account_keys::ViewAccountKey;
-use mc_common::logger::global_log;
+use mc_common::{logger::global_log, HashMap};
 use mc_core::account::{ViewAccount, ViewSubaddress};
 use mc_crypto_keys::RistrettoPublic;
 use mc_transaction_core::tx::TxOut;
 use mc_transaction_signer::types::TxoSynced;
+use rocket::tokio::{self, sync::oneshot};
 use strum::Display;
 
-use crate::service::models::tx_proposal::{InputTxo, TxProposal, UnsignedTxProposal};
+use crate::{
+    db::account::AccountID,
+    error::ErrorCode,
+    service::models::tx_proposal::{InputTxo, TxProposal, UnsignedTxProposal},
+};
 
 /// Errors for the Address Service.
 #[derive(Display, Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum HardwareWalletServiceError {
     NoHardwareWalletsFound,
+    DeviceNotFound(String),
     LedgerMob(ledger_mob::Error),
     PresignedRingsNotSupported,
     KeyImageNotFoundForSignedInput,
     RingCT(mc_transaction_core::ring_ct::Error),
     CryptoKeys(mc_crypto_keys::KeyError),
     CredentialMismatch,
+    SigningCancelled,
+}
+
+impl ErrorCode for HardwareWalletServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::NoHardwareWalletsFound => "no_hardware_wallets_found",
+            Self::DeviceNotFound(..) => "device_not_found",
+            Self::LedgerMob(..) => "ledger_mob",
+            Self::PresignedRingsNotSupported => "presigned_rings_not_supported",
+            Self::KeyImageNotFoundForSignedInput => "key_image_not_found_for_signed_input",
+            Self::RingCT(..) => "ring_ct",
+            Self::CryptoKeys(..) => "crypto_keys",
+            Self::CredentialMismatch => "credential_mismatch",
+            Self::SigningCancelled => "signing_cancelled",
+        }
+    }
 }
 
 impl From<mc_transaction_core::ring_ct::Error> for HardwareWalletServiceError {
@@ -47,20 +74,100 @@ impl From<ledger_mob::Error> for HardwareWalletServiceError {
     }
 }
 
-async fn get_device_handle() -> Result<DeviceHandle<LedgerHandle>, HardwareWalletServiceError> {
+/// Progress of generating key images for an account's TXOs on a hardware
+/// wallet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SyncProgress {
+    /// The number of TXOs whose key images have been generated.
+    pub completed: u64,
+
+    /// The number of TXOs in the sync.
+    pub total: u64,
+}
+
+lazy_static! {
+    /// The progress of the most recent hardware wallet sync for each account.
+    static ref SYNC_PROGRESS: Mutex<HashMap<AccountID, SyncProgress>> =
+        Mutex::new(HashMap::default());
+
+    /// Senders used to cancel the in-progress hardware wallet signing for
+    /// each account.
+    static ref SIGNING_CANCELLATIONS: Mutex<HashMap<AccountID, oneshot::Sender<()>>> =
+        Mutex::new(HashMap::default());
+}
+
+/// Get the progress of the most recent hardware wallet sync for an account,
+/// if one has been started since the wallet was launched.
+pub fn get_sync_progress(account_id: &AccountID) -> Option<SyncProgress> {
+    SYNC_PROGRESS
+        .lock()
+        .expect("mutex poisoned")
+        .get(account_id)
+        .copied()
+}
+
+fn set_sync_progress(account_id: &AccountID, completed: u64, total: u64) {
+    SYNC_PROGRESS
+        .lock()
+        .expect("mutex poisoned")
+        .insert(account_id.clone(), SyncProgress { completed, total });
+}
+
+/// A hardware wallet connected to this machine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HardwareWalletDevice {
+    /// Identifies the device by its connection, for selecting it in later
+    /// requests.
+    pub id: String,
+
+    /// A human readable description of the device.
+    pub label: String,
+}
+
+impl From<&LedgerInfo> for HardwareWalletDevice {
+    fn from(src: &LedgerInfo) -> Self {
+        Self {
+            id: src.conn.to_string(),
+            label: src.to_string(),
+        }
+    }
+}
+
+/// List the hardware wallets currently connected.
+pub async fn list_devices() -> Result<Vec<HardwareWalletDevice>, HardwareWalletServiceError> {
     let mut ledger_provider = LedgerProvider::init().await;
     let devices = ledger_provider
         .list(Filters::Hid)
         .await
         .map_err(ledger_mob::Error::from)?;
 
-    // Get the first device found, or error if none are found.
-    let device = devices
-        .first()
-        .ok_or(HardwareWalletServiceError::NoHardwareWalletsFound)?;
+    Ok(devices.iter().map(HardwareWalletDevice::from).collect())
+}
+
+/// Connect to the hardware wallet with the given id, or to the first one
+/// found if no id is given.
+async fn get_device_handle(
+    device_id: Option<&str>,
+) -> Result<DeviceHandle<LedgerHandle>, HardwareWalletServiceError> {
+    let mut ledger_provider = LedgerProvider::init().await;
+    let devices = ledger_provider
+        .list(Filters::Hid)
+        .await
+        .map_err(ledger_mob::Error::from)?;
 
     global_log::info!("Found devices: {:04x?}", devices);
 
+    let device = match device_id {
+        Some(device_id) => devices
+            .iter()
+            .find(|device| HardwareWalletDevice::from(*device).id == device_id)
+            .ok_or_else(|| HardwareWalletServiceError::DeviceNotFound(device_id.to_string()))?,
+        // Get the first device found, or error if none are found.
+        None => devices
+            .first()
+            .ok_or(HardwareWalletServiceError::NoHardwareWalletsFound)?,
+    };
+
     let handle = ledger_provider
         .connect(device.clone())
         .await
@@ -73,53 +180,116 @@ async fn get_device_handle() -> Result<DeviceHandle<LedgerHandle>, HardwareWalle
 pub async fn sync_txos(
     unsynced_txos: Vec<(TxOut, u64)>,
     view_account: &ViewAccountKey,
+    account_index: u32,
+    device_id: Option<&str>,
 ) -> Result<Vec<TxoSynced>, HardwareWalletServiceError> {
-    let mut device_handle = get_device_handle().await?;
+    let mut device_handle = get_device_handle(device_id).await?;
 
     // Check device and requested accounts match
-    let device_keys = device_handle.account_keys(0).await?;
+    let device_keys = device_handle.account_keys(account_index).await?;
     if device_keys.view_private_key() != view_account.view_private_key()
         || device_keys.spend_public_key() != view_account.spend_public_key()
     {
         return Err(HardwareWalletServiceError::CredentialMismatch);
     }
 
+    let account_id = AccountID::from(view_account);
+    let total = unsynced_txos.len() as u64;
+    set_sync_progress(&account_id, 0, total);
+
     let mut synced_txos = vec![];
     for unsynced_txo in unsynced_txos {
         let tx_public_key = (&unsynced_txo.0.public_key).try_into()?;
         let key_image = device_handle
-            .key_image(0, unsynced_txo.1, tx_public_key)
+            .key_image(account_index, unsynced_txo.1, tx_public_key)
             .await?;
 
         synced_txos.push(TxoSynced {
             tx_out_public_key: tx_public_key.into(),
             key_image,
         });
+        set_sync_progress(&account_id, synced_txos.len() as u64, total);
     }
 
     Ok(synced_txos)
 }
 
-pub async fn get_view_only_account_keys() -> Result<ViewAccount, HardwareWalletServiceError> {
-    let mut device_handle = get_device_handle().await?;
-    Ok(device_handle.account_keys(0).await?)
+pub async fn get_view_only_account_keys(
+    account_index: u32,
+    device_id: Option<&str>,
+) -> Result<ViewAccount, HardwareWalletServiceError> {
+    let mut device_handle = get_device_handle(device_id).await?;
+    Ok(device_handle.account_keys(account_index).await?)
 }
 
 pub async fn get_view_only_subaddress_keys(
+    account_index: u32,
     subaddress_index: u64,
+    device_id: Option<&str>,
 ) -> Result<ViewSubaddress, HardwareWalletServiceError> {
-    let mut device_handle = get_device_handle().await?;
-    Ok(device_handle.subaddress_keys(0, subaddress_index).await?)
+    let mut device_handle = get_device_handle(device_id).await?;
+    Ok(device_handle
+        .subaddress_keys(account_index, subaddress_index)
+        .await?)
 }
 
+/// Sign a transaction proposal on a hardware wallet.
+///
+/// The signing can be aborted with [cancel_signing], in which case the device
+/// handle is released and [HardwareWalletServiceError::SigningCancelled] is
+/// returned.
 pub async fn sign_tx_proposal(
     unsigned_tx_proposal: UnsignedTxProposal,
     view_account: &ViewAccountKey,
+    account_index: u32,
+    device_id: Option<&str>,
+) -> Result<TxProposal, HardwareWalletServiceError> {
+    let account_id = AccountID::from(view_account);
+    let (cancel_sender, cancel_receiver) = oneshot::channel();
+    SIGNING_CANCELLATIONS
+        .lock()
+        .expect("mutex poisoned")
+        .insert(account_id.clone(), cancel_sender);
+
+    // Dropping the signing future on cancellation drops the device handle
+    // along with it.
+    let result = tokio::select! {
+        result = sign_tx_proposal_on_device(unsigned_tx_proposal, view_account, account_index, device_id) => result,
+        Ok(()) = cancel_receiver => Err(HardwareWalletServiceError::SigningCancelled),
+    };
+
+    SIGNING_CANCELLATIONS
+        .lock()
+        .expect("mutex poisoned")
+        .remove(&account_id);
+
+    result
+}
+
+/// Cancel an in-progress hardware wallet signing for an account.
+///
+/// Returns whether there was a signing in progress to cancel.
+pub fn cancel_signing(account_id: &AccountID) -> bool {
+    match SIGNING_CANCELLATIONS
+        .lock()
+        .expect("mutex poisoned")
+        .remove(account_id)
+    {
+        Some(cancel_sender) => cancel_sender.send(()).is_ok(),
+        None => false,
+    }
+}
+
+async fn sign_tx_proposal_on_device(
+    unsigned_tx_proposal: UnsignedTxProposal,
+    view_account: &ViewAccountKey,
+    account_index: u32,
+    device_id: Option<&str>,
 ) -> Result<TxProposal, HardwareWalletServiceError> {
-    let mut device_handle = get_device_handle().await?;
+    let mut device_handle = get_device_handle(device_id).await?;
 
     // Check device and requested accounts match
-    let device_keys = device_handle.account_keys(0).await?;
+    let device_keys = device_handle.account_keys(account_index).await?;
     if device_keys.view_private_key() != view_account.view_private_key()
         || device_keys.spend_public_key() != view_account.spend_public_key()
     {
@@ -129,7 +299,7 @@ pub async fn sign_tx_proposal(
     // Sign transaction proposal
     global_log::debug!("Signing tx proposal with hardware device");
     let (tx, txos_synced) = device_handle
-        .transaction(0, 60, unsigned_tx_proposal.unsigned_tx)
+        .transaction(account_index, 60, unsigned_tx_proposal.unsigned_tx)
         .await?;
 
     let mut input_txos = vec![];
@@ -8,6 +8,7 @@ use crate::{
         transaction_log::{TransactionId, TransactionLogModel},
         txo::TxoModel,
     },
+    error::ErrorCode,
     service::{
         models::ledger::LedgerSearchResult,
         watcher::{WatcherService, WatcherServiceError},
@@ -16,10 +17,7 @@ use crate::{
 };
 use mc_blockchain_types::{Block, BlockContents, BlockVersion, BlockVersionError};
 use mc_common::HashSet;
-use mc_connection::{
-    BlockInfo, BlockchainConnection, RetryableBlockchainConnection, UserTxConnection,
-    _retry::delay::Fibonacci,
-};
+use mc_connection::{BlockInfo, BlockchainConnection, UserTxConnection};
 use mc_crypto_keys::CompressedRistrettoPublic;
 use mc_fog_report_validation::FogPubkeyResolver;
 use mc_ledger_db::{Error as LedgerError, Ledger, LedgerDB};
@@ -34,8 +32,7 @@ use rand::Rng;
 
 use crate::db::WalletDbError;
 use displaydoc::Display;
-use rayon::prelude::*; // For par_iter
-use std::{convert::TryFrom, ops::DerefMut};
+use std::{convert::TryFrom, ops::DerefMut, time::Duration};
 
 /// Errors for the Address Service.
 #[derive(Display, Debug)]
@@ -84,6 +81,32 @@ pub enum LedgerServiceError {
 
     /// Error interacting with watcher database: {0}
     WatcherDb(WatcherDBError),
+
+    /// Transaction sources can only be set when syncing the ledger from
+    /// consensus
+    NoTransactionsFetcher,
+}
+
+impl ErrorCode for LedgerServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Database(err) => err.error_code(),
+            Self::LedgerDB(..) => "ledger_db",
+            Self::ProstDecode(..) => "prost_decode",
+            Self::NoTxInTransaction => "no_tx_in_transaction",
+            Self::FromHex(..) => "from_hex",
+            Self::Key(..) => "key",
+            Self::InvalidArgument(..) => "invalid_argument",
+            Self::InsufficientTxOuts => "insufficient_tx_outs",
+            Self::NoLastBlockInfo => "no_last_block_info",
+            Self::InconsistentLastBlockInfo => "inconsistent_last_block_info",
+            Self::BlockVersion(..) => "block_version",
+            Self::LedgerInconsistent => "ledger_inconsistent",
+            Self::FeeMap(..) => "fee_map",
+            Self::WatcherDb(..) => "watcher_db",
+            Self::NoTransactionsFetcher => "no_transactions_fetcher",
+        }
+    }
 }
 
 impl From<mc_ledger_db::Error> for LedgerServiceError {
@@ -132,6 +155,7 @@ impl From<WatcherServiceError> for LedgerServiceError {
     fn from(src: WatcherServiceError) -> Self {
         match src {
             WatcherServiceError::WatcherDb(err) => Self::WatcherDb(err),
+            WatcherServiceError::LedgerDB(err) => Self::LedgerDB(err),
         }
     }
 }
@@ -226,6 +250,10 @@ pub trait LedgerService {
     /// Get the last block information cross all nodes
     fn get_latest_block_info(&self) -> Result<BlockInfo, LedgerServiceError>;
 
+    /// Get the last block information, from the cache refreshed by the ledger sync thread
+    /// unless it has gone stale
+    fn get_cached_block_info(&self) -> Result<BlockInfo, LedgerServiceError>;
+
     /// Get an object for fees in each of the configured token types
     fn get_network_fees(&self) -> Result<FeeMap, LedgerServiceError>;
 
@@ -298,6 +326,35 @@ pub trait LedgerService {
         &self, 
         query: &str
     ) -> Result<Vec<LedgerSearchResult>, LedgerServiceError>;
+
+    /// Set the interval at which the ledger sync thread polls the network. Takes effect on the next sync iteration.
+    ///
+    /// # Arguments
+    ///
+    ///| Name            | Purpose                   | Notes                                 |
+    ///|-----------------|---------------------------|---------------------------------------|
+    ///| `poll_interval` | The new interval to use.  | Must be at least `MIN_POLL_INTERVAL`. |
+    ///
+    fn set_poll_interval(
+        &self,
+        poll_interval: Duration,
+    ) -> Result<(), LedgerServiceError>;
+
+    /// Replace the URLs the ledger sync thread fetches transaction data from. Takes effect on
+    /// the next block fetched.
+    ///
+    /// # Arguments
+    ///
+    ///| Name             | Purpose                      | Notes                                                                 |
+    ///|------------------|------------------------------|-----------------------------------------------------------------------|
+    ///| `tx_source_urls` | The new transaction sources. | Must not be empty. Every URL must be a valid http, https or file URL. |
+    ///
+    /// # Returns
+    /// * The transaction source URLs now in use
+    fn set_tx_source_urls(
+        &self,
+        tx_source_urls: Vec<String>,
+    ) -> Result<Vec<String>, LedgerServiceError>;
 }
 
 impl<T, FPR> LedgerService for WalletService<T, FPR>
@@ -398,34 +455,21 @@ where
     }
 
     fn get_latest_block_info(&self) -> Result<BlockInfo, LedgerServiceError> {
-        // Get the last block information from all nodes we are aware of, in parallel.
-        let last_block_infos = self
-            .peer_manager
-            .conns()
-            .par_iter()
-            .filter_map(|conn| {
-                conn.fetch_block_info(Fibonacci::from_millis(10).take(5))
-                    .ok()
-            })
-            .collect::<Vec<_>>();
-
-        // Ensure that all nodes agree on the latest block version and network fees.
-        if last_block_infos.windows(2).any(|window| {
-            window[0].network_block_version != window[1].network_block_version
-                || window[0].minimum_fees != window[1].minimum_fees
-        }) {
-            return Err(LedgerServiceError::InconsistentLastBlockInfo);
-        }
+        self.block_info_cache.refresh(&self.peer_manager)
+    }
 
-        last_block_infos
-            .first()
-            .cloned()
-            .ok_or(LedgerServiceError::NoLastBlockInfo)
+    fn get_cached_block_info(&self) -> Result<BlockInfo, LedgerServiceError> {
+        // The ledger sync thread refreshes the cache every poll interval, so allow it
+        // to miss one refresh before querying the peers ourselves.
+        match self.block_info_cache.get(self.poll_interval.get() * 2) {
+            Some(block_info) => Ok(block_info),
+            None => self.get_latest_block_info(),
+        }
     }
 
     fn get_network_fees(&self) -> Result<FeeMap, LedgerServiceError> {
         Ok(FeeMap::try_from(
-            self.get_latest_block_info()?.minimum_fees,
+            self.get_cached_block_info()?.minimum_fees,
         )?)
     }
 
@@ -444,7 +488,7 @@ where
         }
 
         Ok(BlockVersion::try_from(
-            self.get_latest_block_info()?.network_block_version,
+            self.get_cached_block_info()?.network_block_version,
         )?)
     }
 
@@ -544,6 +588,31 @@ where
 
         Ok(results)
     }
+
+    fn set_poll_interval(&self, poll_interval: Duration) -> Result<(), LedgerServiceError> {
+        self.poll_interval
+            .set(poll_interval)
+            .map_err(LedgerServiceError::InvalidArgument)
+    }
+
+    fn set_tx_source_urls(
+        &self,
+        tx_source_urls: Vec<String>,
+    ) -> Result<Vec<String>, LedgerServiceError> {
+        let transactions_fetcher = self
+            .transactions_fetcher
+            .as_ref()
+            .ok_or(LedgerServiceError::NoTransactionsFetcher)?;
+        transactions_fetcher
+            .set_source_urls(tx_source_urls)
+            .map_err(LedgerServiceError::InvalidArgument)?;
+
+        Ok(transactions_fetcher
+            .source_urls()
+            .iter()
+            .map(|url| url.to_string())
+            .collect())
+    }
 }
 
 impl<T, FPR> WalletService<T, FPR>
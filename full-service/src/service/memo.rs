@@ -1,5 +1,12 @@
 use crate::{
-    db::{account::AccountModel, models::Txo, txo::TxoModel, WalletDbError},
+    db::{
+        account::{AccountID, AccountModel},
+        exclusive_transaction,
+        models::{Account, Txo},
+        txo::TxoModel,
+        WalletDbError,
+    },
+    error::ErrorCode,
     service::ledger::{LedgerService, LedgerServiceError},
     util::b58::{b58_decode_public_address, B58Error},
     WalletService,
@@ -8,6 +15,7 @@ use displaydoc::Display;
 use mc_connection::{BlockchainConnection, UserTxConnection};
 use mc_crypto_keys::RistrettoPublic;
 use mc_fog_report_validation::FogPubkeyResolver;
+use mc_ledger_db::Ledger;
 use mc_transaction_extra::{MemoDecodingError, MemoType, UnusedMemo};
 use std::{convert::TryFrom, ops::DerefMut};
 
@@ -37,6 +45,29 @@ pub enum MemoServiceError {
 
     /// Unknown subaddress index for txo_id {0}. Can't validate.
     TxoOrphaned(String),
+
+    /// Diesel Error: {0}
+    Diesel(diesel::result::Error),
+
+    /// Error with LedgerDB: {0}
+    LedgerDB(mc_ledger_db::Error),
+}
+
+impl ErrorCode for MemoServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::WalletDb(err) => err.error_code(),
+            Self::B58(err) => err.error_code(),
+            Self::Decode(..) => "decode",
+            Self::LedgerService(err) => err.error_code(),
+            Self::Key(..) => "key",
+            Self::MemoDecoding(..) => "memo_decoding",
+            Self::InvalidMemoTypeForValidation => "invalid_memo_type_for_validation",
+            Self::TxoOrphaned(..) => "txo_orphaned",
+            Self::Diesel(..) => "diesel",
+            Self::LedgerDB(..) => "ledger_db",
+        }
+    }
 }
 
 impl From<WalletDbError> for MemoServiceError {
@@ -75,12 +106,38 @@ impl From<MemoDecodingError> for MemoServiceError {
     }
 }
 
+impl From<diesel::result::Error> for MemoServiceError {
+    fn from(src: diesel::result::Error) -> Self {
+        Self::Diesel(src)
+    }
+}
+
+impl From<mc_ledger_db::Error> for MemoServiceError {
+    fn from(src: mc_ledger_db::Error) -> Self {
+        Self::LedgerDB(src)
+    }
+}
+
 pub trait MemoService {
     fn validate_sender_memo(
         &self,
         txo_id_hex: &str,
         sender_address: &str,
     ) -> Result<bool, MemoServiceError>;
+
+    /// Decode the memos of the txos received by an account again, replacing the
+    /// memos stored for them. This fills in memos which were not stored
+    /// when the txos were synced, without syncing the account again.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                             | Notes                             |
+    ///|--------------|-------------------------------------|-----------------------------------|
+    ///| `account_id` | The account whose memos to backfill | Account must exist in the wallet. |
+    ///
+    /// # Returns
+    /// * The number of txos whose memos were decoded
+    fn backfill_memos(&self, account_id: &AccountID) -> Result<usize, MemoServiceError>;
 }
 
 impl<T, FPR> MemoService for WalletService<T, FPR>
@@ -153,4 +210,107 @@ where
             Err(e) => Err(e.into()),
         }
     }
+
+    fn backfill_memos(&self, account_id: &AccountID) -> Result<usize, MemoServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        Account::get(account_id, conn)?;
+        // Txos received before shared secrets were stored need them to decrypt memos.
+        exclusive_transaction(conn, Txo::backfill_shared_secrets)?;
+        let txos = Txo::list_for_account(&account_id.0, None, None, None, None, None, None, conn)?;
+
+        // Decrypt the memos before starting the exclusive transaction.
+        let mut memo_payloads = Vec::new();
+        for txo in txos {
+            let Some(shared_secret) = &txo.shared_secret else {
+                continue;
+            };
+            let shared_secret: RistrettoPublic = mc_util_serial::decode(shared_secret)?;
+            // Read the TxOut from the ledger directly, rather than through
+            // get_txo_object, which would check out a second connection.
+            let tx_out = self.ledger_db.get_tx_out_by_index(
+                self.ledger_db
+                    .get_tx_out_index_by_public_key(&txo.public_key()?)?,
+            )?;
+            let memo_payload = match tx_out.e_memo {
+                Some(e_memo) => e_memo.decrypt(&shared_secret),
+                None => UnusedMemo.into(),
+            };
+            memo_payloads.push((txo, memo_payload));
+        }
+
+        exclusive_transaction(conn, |conn| {
+            for (txo, memo_payload) in &memo_payloads {
+                txo.update_memo(memo_payload, conn)?;
+            }
+            Ok(memo_payloads.len())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        service::account::AccountService,
+        test_utils::{
+            add_block_to_ledger_db, get_test_ledger, manually_sync_account, setup_wallet_service,
+            MOB,
+        },
+    };
+    use mc_account_keys::{AccountKey, PublicAddress};
+    use mc_common::logger::{test_with_logger, Logger};
+    use mc_rand::RngCore;
+    use mc_transaction_core::ring_signature::KeyImage;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test_with_logger]
+    fn test_backfill_memos(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        for _ in 0..2 {
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![alice_account_key.default_subaddress()],
+                100 * MOB,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        // Both received txos have their memos decoded again.
+        assert_eq!(service.backfill_memos(&alice_account_id).unwrap(), 2);
+
+        // An account which is not in the wallet can not be backfilled.
+        let bob_account_key = AccountKey::random(&mut rng);
+        assert!(matches!(
+            service.backfill_memos(&AccountID::from(&bob_account_key)),
+            Err(MemoServiceError::WalletDb(WalletDbError::AccountNotFound(
+                _
+            )))
+        ));
+    }
 }
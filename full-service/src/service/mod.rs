@@ -5,6 +5,7 @@
 pub mod account;
 pub mod address;
 pub mod balance;
+pub mod block_info_cache;
 pub mod confirmation_number;
 pub mod gift_code;
 pub mod hardware_wallet;
@@ -14,12 +15,15 @@ pub mod models;
 pub mod network;
 pub mod payment_request;
 pub mod receipt;
+pub mod submission_tracker;
 pub mod sync;
+pub mod sync_progress;
 pub mod t3_sync;
 pub mod transaction;
 pub mod transaction_builder;
 pub mod transaction_log;
 pub mod txo;
+pub mod txo_reservation;
 pub mod watcher;
 
 mod wallet_service;
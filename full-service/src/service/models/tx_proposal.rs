@@ -63,11 +63,23 @@ pub struct UnsignedTxProposal {
 }
 
 impl UnsignedTxProposal {
-    pub async fn sign(self, account: &Account) -> Result<TxProposal, TransactionServiceError> {
+    /// Sign with the account's keys, or with the hardware wallet identified by
+    /// `device_id` if the account is view only.
+    pub async fn sign(
+        self,
+        account: &Account,
+        device_id: Option<&str>,
+    ) -> Result<TxProposal, TransactionServiceError> {
         match account.view_only {
             true => {
                 global_log::debug!("signing tx proposal with hardware wallet");
-                Ok(hardware_wallet::sign_tx_proposal(self, &account.view_account_key()?).await?)
+                Ok(hardware_wallet::sign_tx_proposal(
+                    self,
+                    &account.view_account_key()?,
+                    account.account_index as u32,
+                    device_id,
+                )
+                .await?)
             }
             false => {
                 global_log::debug!("signing tx proposal with local signer");
@@ -6,6 +6,7 @@ use mc_watcher::watcher_db::BlockSignatureData;
 use mc_watcher_api::TimestampResultCode;
 
 use serde_derive::{Deserialize, Serialize};
+use url::Url;
 
 /// Information about a block provided by the Watcher.
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -14,3 +15,18 @@ pub struct WatcherBlockInfo {
     pub timestamp_result_code: TimestampResultCode,
     pub signatures: Vec<BlockSignatureData>,
 }
+
+/// How far the Watcher has synced block signatures from each of its sources.
+#[derive(Debug, Clone)]
+pub struct WatcherStatus {
+    /// The highest block for which the Watcher has a signature, and so a
+    /// timestamp, from any source.
+    pub highest_timestamped_block_index: Option<u64>,
+
+    /// The number of blocks in the local ledger that the Watcher has not
+    /// yet timestamped.
+    pub num_blocks_behind: u64,
+
+    /// The last block synced from each source URL.
+    pub sources: Vec<(Url, Option<u64>)>,
+}
@@ -5,7 +5,13 @@
 use std::ops::DerefMut;
 
 use crate::{
-    db::{assigned_subaddress::AssignedSubaddressModel, models::AssignedSubaddress, WalletDbError},
+    db::{
+        account::{AccountID, AccountModel},
+        assigned_subaddress::AssignedSubaddressModel,
+        models::{Account, AssignedSubaddress},
+        WalletDbError,
+    },
+    error::ErrorCode,
     service::WalletService,
     util::b58::{b58_decode_public_address, b58_encode_payment_request, B58Error},
 };
@@ -43,6 +49,21 @@ pub enum PaymentRequestServiceError {
     InvalidMnemonic(String),
 }
 
+impl ErrorCode for PaymentRequestServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::B58(err) => err.error_code(),
+            Self::Database(err) => err.error_code(),
+            Self::LedgerDB(..) => "ledger_db",
+            Self::HexDecode(..) => "hex_decode",
+            Self::Diesel(..) => "diesel",
+            Self::LedgerService(err) => err.error_code(),
+            Self::UnknownKeyDerivation(..) => "unknown_key_derivation",
+            Self::InvalidMnemonic(..) => "invalid_mnemonic",
+        }
+    }
+}
+
 impl From<WalletDbError> for PaymentRequestServiceError {
     fn from(src: WalletDbError) -> Self {
         Self::Database(src)
@@ -88,8 +109,8 @@ pub trait PaymentRequestService {
     ///| Name               | Purpose                                                          | Notes                             |
     ///|--------------------|------------------------------------------------------------------|-----------------------------------|
     ///| `account_id`       | The account on which to perform this action.                     | Account must exist in the wallet. |
-    ///| `subaddress_index` | The subaddress index on the account to generate the request with |                                   |
-    ///| `amount`           | The Amount to send in this transaction                           | 64-bit signed integer             |
+    ///| `subaddress_index` | The subaddress index on the account to generate the request with | Defaults to the account's default receive subaddress. |
+    ///| `amount`           | The Amount to send in this transaction                           | A value of 0 is an open amount.   |
     ///| `memo`             | Memo for the payment request                                     |                                   |
     ///
     fn create_payment_request(
@@ -116,11 +137,14 @@ where
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
 
-        let assigned_subaddress = AssignedSubaddress::get_for_account_by_index(
-            &account_id,
-            subaddress_index.unwrap_or_default(),
-            conn,
-        )?;
+        let subaddress_index = match subaddress_index {
+            Some(subaddress_index) => subaddress_index,
+            None => {
+                Account::get(&AccountID(account_id.clone()), conn)?.default_receive_subaddress_index
+            }
+        };
+        let assigned_subaddress =
+            AssignedSubaddress::get_for_account_by_index(&account_id, subaddress_index, conn)?;
 
         let public_address = b58_decode_public_address(&assigned_subaddress.public_address_b58)?;
 
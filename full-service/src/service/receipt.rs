@@ -12,10 +12,12 @@ use crate::{
     db::{
         account::{AccountID, AccountModel},
         assigned_subaddress::AssignedSubaddressModel,
-        models::{Account, AssignedSubaddress, Txo},
+        models::{Account, AssignedSubaddress, TransactionLog, Txo},
+        transaction_log::{TransactionId, TransactionLogModel},
         txo::{TxoInfo, TxoModel, TxoStatus},
-        WalletDbError,
+        Conn, WalletDbError,
     },
+    error::ErrorCode,
     service::models::tx_proposal::TxProposal,
     WalletService,
 };
@@ -24,7 +26,7 @@ use mc_account_keys::AccountKey;
 use mc_connection::{BlockchainConnection, UserTxConnection};
 use mc_crypto_keys::{CompressedRistrettoPublic, RistrettoPublic};
 use mc_fog_report_validation::FogPubkeyResolver;
-use mc_transaction_core::{get_tx_out_shared_secret, MaskedAmount};
+use mc_transaction_core::{get_tx_out_shared_secret, tx::Tx, MaskedAmount};
 use mc_transaction_extra::TxOutConfirmationNumber;
 use serde::{Deserialize, Serialize};
 use std::{convert::TryFrom, ops::DerefMut};
@@ -59,6 +61,30 @@ pub enum ReceiptServiceError {
 
     /// Tx Out Conversion Error: {0}
     TxOutConversion(mc_transaction_core::TxOutConversionError),
+
+    /// Minted Txo should contain confirmation: {0}
+    MissingConfirmation(String),
+
+    /// Txo {0} is not an output of the logged transaction
+    OutputNotInTransaction(String),
+}
+
+impl ErrorCode for ReceiptServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Database(err) => err.error_code(),
+            Self::Diesel(..) => "diesel",
+            Self::LedgerDB(..) => "ledger_db",
+            Self::ProtoConversion(..) => "proto_conversion",
+            Self::ProtoConversionInfallible => "proto_conversion_infallible",
+            Self::ProstDecode(..) => "prost_decode",
+            Self::CryptoKey(..) => "crypto_key",
+            Self::HexDecode(..) => "hex_decode",
+            Self::TxOutConversion(..) => "tx_out_conversion",
+            Self::MissingConfirmation(..) => "missing_confirmation",
+            Self::OutputNotInTransaction(..) => "output_not_in_transaction",
+        }
+    }
 }
 
 impl From<WalletDbError> for ReceiptServiceError {
@@ -190,6 +216,22 @@ pub trait ReceiptService {
         receiver_receipt: &ReceiverReceipt,
     ) -> Result<(ReceiptTransactionStatus, Option<TxoInfo>), ReceiptServiceError>;
 
+    /// Check the status of several receipts sent to the same address, against one view of the wallet.
+    ///
+    /// # Arguments
+    /// 
+    ///| Name                | Purpose                                        | Notes                            |
+    ///|---------------------|------------------------------------------------|----------------------------------|
+    ///| `address`           | The account's public address.                  | Must be a valid account address. |
+    ///| `receiver_receipts` | The receipts whose statuses are being checked. |                                  |
+    ///
+    #[allow(clippy::type_complexity)]
+    fn check_receipts_status(
+        &self,
+        address: &str,
+        receiver_receipts: &[ReceiverReceipt],
+    ) -> Result<Vec<(ReceiptTransactionStatus, Option<TxoInfo>)>, ReceiptServiceError>;
+
     /// Create a receipt from a given TxProposal
     ///
     /// # Arguments
@@ -202,6 +244,20 @@ pub trait ReceiptService {
         &self,
         tx_proposal: &TxProposal,
     ) -> Result<Vec<ReceiverReceipt>, ReceiptServiceError>;
+
+    /// Create the receipts for the outputs of a transaction the wallet has already built, from
+    /// its transaction log.
+    ///
+    /// # Arguments
+    /// 
+    ///| Name                 | Purpose                                                        | Notes                                         |
+    ///|----------------------|----------------------------------------------------------------|-----------------------------------------------|
+    ///| `transaction_log_id` | The transaction log of the transaction to create receipts for. | The transaction log must exist in the wallet. |
+    ///
+    fn create_receiver_receipts_for_transaction_log(
+        &self,
+        transaction_log_id: &TransactionId,
+    ) -> Result<Vec<ReceiverReceipt>, ReceiptServiceError>;
 }
 
 impl<T, FPR> ReceiptService for WalletService<T, FPR>
@@ -216,63 +272,20 @@ where
     ) -> Result<(ReceiptTransactionStatus, Option<TxoInfo>), ReceiptServiceError> {
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
-        let assigned_address = AssignedSubaddress::get(address, conn)?;
-        let account_id = AccountID(assigned_address.account_id);
-        let account = Account::get(&account_id, conn)?;
-        // Get the transaction from the database, with status.
-        let txos = Txo::select_by_public_key(&[&receiver_receipt.public_key], conn)?;
-
-        // Return if the Txo from the receipt is not in this wallet yet.
-        if txos.is_empty() {
-            return Ok((ReceiptTransactionStatus::TransactionPending, None));
-        }
-        let txo = txos[0].clone();
-        let status = txo.status(conn)?;
-        let memo = txo.memo(conn)?;
-
-        let txo_info = TxoInfo { txo, memo, status };
-
-        if (txo_info.status == TxoStatus::Pending) || (txo_info.status == TxoStatus::Created) {
-            return Ok((ReceiptTransactionStatus::TransactionPending, Some(txo_info)));
-        }
-
-        // Decrypt the amount to get the expected value
-        let account_key: AccountKey = mc_util_serial::decode(&account.account_key)?;
-        let public_key: RistrettoPublic = RistrettoPublic::try_from(&receiver_receipt.public_key)?;
-        let shared_secret = get_tx_out_shared_secret(account_key.view_private_key(), &public_key);
-        let expected_value = match receiver_receipt.amount.get_value(&shared_secret) {
-            Ok((v, _blinding)) => v,
-            Err(_) => {
-                return Ok((
-                    ReceiptTransactionStatus::FailedAmountDecryption,
-                    Some(txo_info),
-                ))
-            }
-        };
-        // Check that the value of the received Txo matches the expected value.
-        if (txo_info.txo.value as u64) != expected_value.value {
-            return Ok((
-                ReceiptTransactionStatus::AmountMismatch(format!(
-                    "Expected: {}, Got: {}",
-                    expected_value.value,
-                    (txo_info.txo.value as u64)
-                )),
-                Some(txo_info),
-            ));
-        }
-
-        // Validate the confirmation number.
-        let confirmation_hex = hex::encode(mc_util_serial::encode(&receiver_receipt.confirmation));
-        let confirmation: TxOutConfirmationNumber =
-            mc_util_serial::decode(&hex::decode(confirmation_hex)?)?;
-        if !Txo::validate_confirmation(&account_id, &txo_info.txo.id, &confirmation, conn)? {
-            return Ok((
-                ReceiptTransactionStatus::InvalidConfirmation,
-                Some(txo_info),
-            ));
-        }
+        check_receipt_status_with_conn(address, receiver_receipt, conn)
+    }
 
-        Ok((ReceiptTransactionStatus::TransactionSuccess, Some(txo_info)))
+    fn check_receipts_status(
+        &self,
+        address: &str,
+        receiver_receipts: &[ReceiverReceipt],
+    ) -> Result<Vec<(ReceiptTransactionStatus, Option<TxoInfo>)>, ReceiptServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        receiver_receipts
+            .iter()
+            .map(|receiver_receipt| check_receipt_status_with_conn(address, receiver_receipt, conn))
+            .collect()
     }
 
     fn create_receiver_receipts(
@@ -293,6 +306,107 @@ where
             .collect::<Result<Vec<ReceiverReceipt>, ReceiptServiceError>>()?;
         Ok(receiver_tx_receipts)
     }
+
+    fn create_receiver_receipts_for_transaction_log(
+        &self,
+        transaction_log_id: &TransactionId,
+    ) -> Result<Vec<ReceiverReceipt>, ReceiptServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let transaction_log = TransactionLog::get(transaction_log_id, conn)?;
+        let associated_txos = transaction_log.get_associated_txos(conn)?;
+        let tx: Tx = mc_util_serial::decode(&transaction_log.tx)?;
+
+        associated_txos
+            .outputs
+            .iter()
+            .map(|(txo, _)| {
+                let public_key = txo.public_key()?;
+                let tx_out = tx
+                    .prefix
+                    .outputs
+                    .iter()
+                    .find(|tx_out| tx_out.public_key == public_key)
+                    .ok_or_else(|| ReceiptServiceError::OutputNotInTransaction(txo.id.clone()))?;
+                let confirmation = txo
+                    .confirmation
+                    .as_ref()
+                    .ok_or_else(|| ReceiptServiceError::MissingConfirmation(txo.id.clone()))?;
+                Ok(ReceiverReceipt {
+                    public_key,
+                    tombstone_block: tx.prefix.tombstone_block,
+                    confirmation: mc_util_serial::decode(confirmation)?,
+                    amount: tx_out.get_masked_amount()?.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Check the status of a receipt against the wallet database.
+#[allow(clippy::type_complexity)]
+fn check_receipt_status_with_conn(
+    address: &str,
+    receiver_receipt: &ReceiverReceipt,
+    conn: Conn,
+) -> Result<(ReceiptTransactionStatus, Option<TxoInfo>), ReceiptServiceError> {
+    let assigned_address = AssignedSubaddress::get(address, conn)?;
+    let account_id = AccountID(assigned_address.account_id);
+    let account = Account::get(&account_id, conn)?;
+    // Get the transaction from the database, with status.
+    let txos = Txo::select_by_public_key(&[&receiver_receipt.public_key], conn)?;
+
+    // Return if the Txo from the receipt is not in this wallet yet.
+    if txos.is_empty() {
+        return Ok((ReceiptTransactionStatus::TransactionPending, None));
+    }
+    let txo = txos[0].clone();
+    let status = txo.status(conn)?;
+    let memo = txo.memo(conn)?;
+
+    let txo_info = TxoInfo { txo, memo, status };
+
+    if (txo_info.status == TxoStatus::Pending) || (txo_info.status == TxoStatus::Created) {
+        return Ok((ReceiptTransactionStatus::TransactionPending, Some(txo_info)));
+    }
+
+    // Decrypt the amount to get the expected value
+    let account_key: AccountKey = mc_util_serial::decode(&account.account_key)?;
+    let public_key: RistrettoPublic = RistrettoPublic::try_from(&receiver_receipt.public_key)?;
+    let shared_secret = get_tx_out_shared_secret(account_key.view_private_key(), &public_key);
+    let expected_value = match receiver_receipt.amount.get_value(&shared_secret) {
+        Ok((v, _blinding)) => v,
+        Err(_) => {
+            return Ok((
+                ReceiptTransactionStatus::FailedAmountDecryption,
+                Some(txo_info),
+            ))
+        }
+    };
+    // Check that the value of the received Txo matches the expected value.
+    if (txo_info.txo.value as u64) != expected_value.value {
+        return Ok((
+            ReceiptTransactionStatus::AmountMismatch(format!(
+                "Expected: {}, Got: {}",
+                expected_value.value,
+                (txo_info.txo.value as u64)
+            )),
+            Some(txo_info),
+        ));
+    }
+
+    // Validate the confirmation number.
+    let confirmation_hex = hex::encode(mc_util_serial::encode(&receiver_receipt.confirmation));
+    let confirmation: TxOutConfirmationNumber =
+        mc_util_serial::decode(&hex::decode(confirmation_hex)?)?;
+    if !Txo::validate_confirmation(&account_id, &txo_info.txo.id, &confirmation, conn)? {
+        return Ok((
+            ReceiptTransactionStatus::InvalidConfirmation,
+            Some(txo_info),
+        ));
+    }
+
+    Ok((ReceiptTransactionStatus::TransactionSuccess, Some(txo_info)))
 }
 
 #[cfg(test)]
@@ -436,6 +550,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .expect("Could not build transaction");
@@ -493,6 +608,16 @@ mod tests {
             .expect("Could not get confirmations");
         assert_eq!(confirmations.len(), 1);
 
+        // The receipts can be created again from the transaction log.
+        assert_eq!(
+            service
+                .create_receiver_receipts_for_transaction_log(&TransactionId(
+                    sent_transaction_log.id.clone()
+                ))
+                .expect("Could not create receiver receipts from the transaction log"),
+            receipts
+        );
+
         let txo_pubkey = mc_util_serial::decode(&txos_and_statuses[0].txo.public_key)
             .expect("Could not decode pubkey");
         assert_eq!(receipt.public_key, txo_pubkey);
@@ -571,6 +696,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .expect("Could not build transaction");
@@ -699,6 +825,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .expect("Could not build transaction");
@@ -847,6 +974,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .expect("Could not build transaction");
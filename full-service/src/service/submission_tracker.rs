@@ -0,0 +1,103 @@
+// Copyright (c) 2020-2023 MobileCoin Inc.
+
+//! Tracking of transaction submissions in progress.
+//!
+//! Submitting a transaction proposes it to consensus and then records it in a
+//! transaction log. Exiting between those steps leaves a transaction on the
+//! network that the wallet has no record of, so on shutdown the wallet stops
+//! starting new submissions and waits for those in progress to finish.
+
+use rocket::tokio;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// How often to check whether in-flight submissions have finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Default)]
+pub struct SubmissionTracker {
+    /// The number of submissions in progress.
+    in_flight: Arc<AtomicUsize>,
+
+    /// Whether new submissions are refused because the wallet is shutting
+    /// down.
+    shutting_down: Arc<AtomicBool>,
+}
+
+impl SubmissionTracker {
+    /// Begin a submission, or None if the wallet is shutting down. The
+    /// submission is in flight until the returned guard is dropped.
+    pub fn start(&self) -> Option<SubmissionGuard> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = SubmissionGuard {
+            in_flight: self.in_flight.clone(),
+        };
+
+        // Checked after counting the submission, so that a shutdown either
+        // sees this submission in flight or this submission sees the shutdown.
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// The number of submissions in progress.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Refuse new submissions, then wait for those in progress to finish.
+    ///
+    /// Returns false if submissions were still in progress after the timeout.
+    pub async fn shut_down(&self, timeout: Duration) -> bool {
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        let start = Instant::now();
+        while self.in_flight() > 0 {
+            if start.elapsed() >= timeout {
+                return false;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        true
+    }
+}
+
+/// Marks a submission as in flight until dropped.
+pub struct SubmissionGuard {
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for SubmissionGuard {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_common::logger::{async_test_with_logger, Logger};
+
+    #[async_test_with_logger]
+    async fn test_shut_down_waits_for_in_flight(_logger: Logger) {
+        let tracker = SubmissionTracker::default();
+
+        let guard = tracker.start().unwrap();
+        assert_eq!(tracker.in_flight(), 1);
+
+        // Times out while the submission is in flight, and refuses new ones.
+        assert!(!tracker.shut_down(Duration::from_millis(100)).await);
+        assert!(tracker.start().is_none());
+        assert_eq!(tracker.in_flight(), 1);
+
+        drop(guard);
+        assert_eq!(tracker.in_flight(), 0);
+        assert!(tracker.shut_down(Duration::from_millis(100)).await);
+    }
+}
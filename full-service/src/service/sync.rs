@@ -6,13 +6,16 @@ use crate::{
     db::{
         account::{AccountID, AccountModel},
         assigned_subaddress::AssignedSubaddressModel,
+        authenticated_sender_memo::SenderMemoFilter,
         exclusive_transaction,
         models::{Account, AssignedSubaddress, TransactionLog, Txo},
         transaction_log::TransactionLogModel,
         txo::TxoModel,
-        Conn, WalletDb,
+        Conn, WalletDb, WalletDbError,
     },
     error::SyncError,
+    metrics,
+    service::sync_progress::SyncProgress,
 };
 use mc_account_keys::{AccountKey, ViewAccountKey};
 use mc_common::{
@@ -28,7 +31,7 @@ use mc_transaction_core::{
     tx::TxOut,
     Amount,
 };
-use rayon::prelude::*;
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
 
 use std::{
     collections::HashMap,
@@ -43,10 +46,13 @@ use std::{
 
 const BLOCKS_CHUNK_SIZE: u64 = 1_000;
 
+/// The maximum number of accounts whose blocks are scanned concurrently.
+const MAX_CONCURRENT_ACCOUNT_SCANS: usize = 4;
+
 /// Sync thread - holds objects needed to cleanly terminate the sync thread.
 pub struct SyncThread {
     /// The main sync thread handle.
-    join_handle: Option<thread::JoinHandle<()>>,
+    join_handle: Mutex<Option<thread::JoinHandle<()>>>,
 
     /// Stop trigger, used to signal the thread to terminate.
     stop_requested: Arc<AtomicBool>,
@@ -57,6 +63,8 @@ impl SyncThread {
         ledger_db: LedgerDB,
         wallet_db: WalletDb,
         accounts_with_deposits: Arc<Mutex<HashMap<AccountID, bool>>>,
+        sync_progress: SyncProgress,
+        sender_memo_filter: SenderMemoFilter,
         logger: Logger,
     ) -> Self {
         // Start the sync thread.
@@ -65,7 +73,7 @@ impl SyncThread {
         let thread_stop_requested = stop_requested.clone();
         let thread_accounts_with_deposits = accounts_with_deposits.clone();
 
-        let join_handle = Some(
+        let join_handle = Mutex::new(Some(
             thread::Builder::new()
                 .name("sync".to_string())
                 .spawn(move || {
@@ -75,6 +83,12 @@ impl SyncThread {
                         .get_pooled_conn()
                         .expect("failed getting wallet db connection");
 
+                    let scan_pool = ThreadPoolBuilder::new()
+                        .num_threads(MAX_CONCURRENT_ACCOUNT_SCANS)
+                        .thread_name(|i| format!("sync-scan-{i}"))
+                        .build()
+                        .expect("failed starting sync scan thread pool");
+
                     loop {
                         if thread_stop_requested.load(Ordering::SeqCst) {
                             log::debug!(logger, "SyncThread stop requested.");
@@ -84,7 +98,10 @@ impl SyncThread {
                         match sync_all_accounts(
                             &ledger_db,
                             conn,
+                            &scan_pool,
                             thread_accounts_with_deposits.clone(),
+                            &sync_progress,
+                            &sender_memo_filter,
                             &logger,
                         ) {
                             Ok(()) => (),
@@ -98,7 +115,7 @@ impl SyncThread {
                     log::debug!(logger, "SyncThread stopped.");
                 })
                 .expect("failed starting main sync thread"),
-        );
+        ));
 
         Self {
             join_handle,
@@ -106,9 +123,10 @@ impl SyncThread {
         }
     }
 
-    pub fn stop(&mut self) {
+    /// Stop the thread, waiting for the sync pass in progress to be written.
+    pub fn stop(&self) {
         self.stop_requested.store(true, Ordering::SeqCst);
-        if let Some(join_handle) = self.join_handle.take() {
+        if let Some(join_handle) = self.join_handle.lock().expect("mutex poisoned").take() {
             join_handle.join().expect("SyncThread join failed");
         }
     }
@@ -123,7 +141,10 @@ impl Drop for SyncThread {
 pub fn sync_all_accounts(
     ledger_db: &LedgerDB,
     conn: Conn,
+    scan_pool: &ThreadPool,
     accounts_with_deposits: Arc<Mutex<HashMap<AccountID, bool>>>,
+    sync_progress: &SyncProgress,
+    sender_memo_filter: &SenderMemoFilter,
     logger: &Logger,
 ) -> Result<(), SyncError> {
     // Get the current number of blocks in ledger.
@@ -139,6 +160,15 @@ pub fn sync_all_accounts(
     let accounts: Vec<Account> =
         { Account::list_all(conn, None, None).expect("Failed getting accounts from database") };
 
+    metrics::ACCOUNTS_TRACKED.set(accounts.len() as i64);
+    let min_next_block_index = accounts
+        .iter()
+        .map(|account| account.next_block_index as u64)
+        .min()
+        .unwrap_or(num_blocks);
+    metrics::ACCOUNT_SYNC_LAG_BLOCKS.set(num_blocks.saturating_sub(min_next_block_index) as i64);
+
+    let mut accounts_to_sync = Vec::new();
     for account in accounts {
         // If there are no new blocks for this account, don't do anything.
         //
@@ -157,9 +187,40 @@ pub fn sync_all_accounts(
                 account.update_resyncing(false, conn)?;
             }
 
+            if account.import_in_progress {
+                account.update_import_in_progress(false, conn)?;
+            }
+
+            sync_progress.clear(&AccountID(account.id.clone()));
             continue;
         }
-        let found_txos = sync_account_next_chunk(ledger_db, conn, &account.id, logger)?;
+        accounts_to_sync.push(account);
+    }
+
+    let pass_start_time = Instant::now();
+
+    // Scanning the ledger only reads from the ledger db, so the accounts are
+    // scanned concurrently. The results are then written to the wallet db one
+    // account at a time, each in its own transaction.
+    let scanned_chunks: Vec<Result<Option<ScannedChunk>, SyncError>> = scan_pool.install(|| {
+        accounts_to_sync
+            .par_iter()
+            .map(|account| scan_account_next_chunk(ledger_db, account))
+            .collect()
+    });
+
+    let mut num_blocks_scanned = Vec::new();
+    for (account, scanned_chunk) in accounts_to_sync.into_iter().zip(scanned_chunks) {
+        let scanned_chunk = match scanned_chunk? {
+            Some(scanned_chunk) => scanned_chunk,
+            None => continue,
+        };
+        num_blocks_scanned.push((
+            AccountID(account.id.clone()),
+            scanned_chunk.end_block_index - scanned_chunk.start_block_index + 1,
+        ));
+        let found_txos =
+            write_scanned_chunk(conn, &account.id, scanned_chunk, sender_memo_filter, logger)?;
         if found_txos > 0 && !account.resyncing {
             // Start tracking the accounts with deposits, but do not fire the webhook
             // until they are fully synced.
@@ -170,6 +231,13 @@ pub fn sync_all_accounts(
         }
     }
 
+    // Each account advanced by its chunk over the whole pass, since the
+    // accounts are scanned side by side.
+    let pass_elapsed = pass_start_time.elapsed();
+    for (account_id, num_blocks) in num_blocks_scanned {
+        sync_progress.record(&account_id, num_blocks, pass_elapsed);
+    }
+
     Ok(())
 }
 
@@ -177,50 +245,134 @@ pub fn sync_account_next_chunk(
     ledger_db: &LedgerDB,
     conn: Conn,
     account_id_hex: &str,
+    sender_memo_filter: &SenderMemoFilter,
     logger: &Logger,
 ) -> Result<usize, SyncError> {
-    exclusive_transaction(conn, |conn| {
-        // Get the account data. If it is no longer available, the account has been
-        // removed and we can simply return.
-        let account_id = AccountID(account_id_hex.to_string());
-        let account = Account::get(&account_id, conn)?;
-
-        let start_time = Instant::now();
-        let start_block_index = account.next_block_index as u64;
-        let mut end_block_index: Option<u64> = None;
-
-        // Load transaction outputs and key images for this chunk.
-        let mut tx_outs: Vec<(u64, TxOut)> = Vec::new();
-        let mut key_images: Vec<(u64, KeyImage)> = Vec::new();
-
-        let start = account.next_block_index as u64;
-        let end = start + BLOCKS_CHUNK_SIZE;
-        for block_index in start..end {
-            let block_contents = match ledger_db.get_block_contents(block_index) {
-                Ok(block_contents) => block_contents,
-                Err(mc_ledger_db::Error::NotFound) => {
-                    break;
-                }
-                Err(err) => {
-                    return Err(err.into());
-                }
-            };
-            end_block_index = Some(block_index);
-
-            for tx_out in block_contents.outputs {
-                tx_outs.push((block_index, tx_out));
-            }
+    // Get the account data. If it is no longer available, the account has been
+    // removed and we can simply return.
+    let account = Account::get(&AccountID(account_id_hex.to_string()), conn)?;
+
+    match scan_account_next_chunk(ledger_db, &account)? {
+        Some(scanned_chunk) => write_scanned_chunk(
+            conn,
+            account_id_hex,
+            scanned_chunk,
+            sender_memo_filter,
+            logger,
+        ),
+        None => Ok(0),
+    }
+}
+
+/// The blocks following an account's next block index, along with the
+/// transaction outputs which the account received in them.
+struct ScannedChunk {
+    start_time: Instant,
+    start_block_index: u64,
+    end_block_index: u64,
+    tx_outs: Vec<(u64, TxOut)>,
+    key_images: Vec<(u64, KeyImage)>,
+    received_txos: Vec<(u64, TxOut, Amount)>,
+}
 
-            for key_image in block_contents.key_images {
-                key_images.push((block_index, key_image));
+/// Read the next chunk of blocks for an account from the ledger, and decode the
+/// transaction outputs it received in them. This does not access the wallet
+/// db, so it may be done for several accounts at once.
+///
+/// Returns None if there are no new blocks.
+fn scan_account_next_chunk(
+    ledger_db: &LedgerDB,
+    account: &Account,
+) -> Result<Option<ScannedChunk>, SyncError> {
+    let start_time = Instant::now();
+    let start_block_index = account.next_block_index as u64;
+    let mut end_block_index: Option<u64> = None;
+
+    // Load transaction outputs and key images for this chunk.
+    let mut tx_outs: Vec<(u64, TxOut)> = Vec::new();
+    let mut key_images: Vec<(u64, KeyImage)> = Vec::new();
+
+    let start = account.next_block_index as u64;
+    let end = start + BLOCKS_CHUNK_SIZE;
+    for block_index in start..end {
+        let block_contents = match ledger_db.get_block_contents(block_index) {
+            Ok(block_contents) => block_contents,
+            Err(mc_ledger_db::Error::NotFound) => {
+                break;
             }
+            Err(err) => {
+                return Err(err.into());
+            }
+        };
+        end_block_index = Some(block_index);
+
+        for tx_out in block_contents.outputs {
+            tx_outs.push((block_index, tx_out));
+        }
+
+        for key_image in block_contents.key_images {
+            key_images.push((block_index, key_image));
         }
+    }
 
-        // If no blocks were found, exit.
-        if end_block_index.is_none() {
+    // If no blocks were found, exit.
+    let end_block_index = match end_block_index {
+        Some(end_block_index) => end_block_index,
+        None => return Ok(None),
+    };
+
+    let view_private_key = account_view_private_key(account)?;
+
+    // Attempt to decode each transaction as received by this account.
+    let received_txos: Vec<_> = tx_outs
+        .par_iter()
+        .filter_map(|(block_index, tx_out)| {
+            let amount = decode_amount(tx_out, &view_private_key)?;
+            Some((*block_index, tx_out.clone(), amount))
+        })
+        .collect();
+
+    Ok(Some(ScannedChunk {
+        start_time,
+        start_block_index,
+        end_block_index,
+        tx_outs,
+        key_images,
+        received_txos,
+    }))
+}
+
+/// Write a scanned chunk of blocks to the wallet db for an account, in a
+/// single transaction.
+///
+/// The chunk is skipped if the account was removed, or its next block index
+/// changed, since it was scanned.
+fn write_scanned_chunk(
+    conn: Conn,
+    account_id_hex: &str,
+    scanned_chunk: ScannedChunk,
+    sender_memo_filter: &SenderMemoFilter,
+    logger: &Logger,
+) -> Result<usize, SyncError> {
+    let ScannedChunk {
+        start_time,
+        start_block_index,
+        end_block_index,
+        tx_outs,
+        key_images,
+        received_txos,
+    } = scanned_chunk;
+
+    exclusive_transaction(conn, |conn| {
+        let account_id = AccountID(account_id_hex.to_string());
+        let account = match Account::get(&account_id, conn) {
+            Ok(account) => account,
+            Err(WalletDbError::AccountNotFound(_)) => return Ok(0),
+            Err(err) => return Err(err.into()),
+        };
+        if account.next_block_index as u64 != start_block_index {
             return Ok(0);
         }
-        let end_block_index = end_block_index.unwrap();
 
         let (view_private_key, account_key) = if account.view_only {
             let view_account_key: ViewAccountKey = mc_util_serial::decode(&account.account_key)?;
@@ -241,15 +393,6 @@ pub fn sync_account_next_chunk(
             })
         })?;
 
-        // Attempt to decode each transaction as received by this account.
-        let received_txos: Vec<_> = tx_outs
-            .into_par_iter()
-            .filter_map(|(block_index, tx_out)| {
-                let amount = decode_amount(&tx_out, &view_private_key)?;
-                Some((block_index, tx_out, amount))
-            })
-            .collect();
-
         let mut received_txos_with_subaddresses_and_key_images = Vec::new();
         for (block_index, tx_out, amount) in received_txos {
             let (subaddress_index, key_image) = decode_subaddress_and_key_image(
@@ -281,10 +424,23 @@ pub fn sync_account_next_chunk(
                 amount,
                 block_index,
                 account_id_hex,
+                sender_memo_filter,
                 conn,
             )?;
         }
 
+        // Finalize the transaction logs whose key images all landed in one block,
+        // including those with no outputs to match above.
+        let key_image_block_indices: MCHashMap<KeyImage, u64> = key_images
+            .iter()
+            .map(|(block_index, key_image)| (*key_image, *block_index))
+            .collect();
+        TransactionLog::update_pending_with_key_images_to_succeeded(
+            account_id_hex,
+            &key_image_block_indices,
+            conn,
+        )?;
+
         // Match key images to mark existing unspent transactions as spent.
         let unspent_key_images: MCHashMap<KeyImage, String> =
             Txo::list_unspent_or_pending_key_images(account_id_hex, None, conn)?;
@@ -315,6 +471,7 @@ pub fn sync_account_next_chunk(
         account.update_next_block_index(end_block_index + 1, conn)?;
 
         let num_blocks_synced = end_block_index - start_block_index + 1;
+        metrics::BLOCKS_SYNCED.inc_by(num_blocks_synced);
 
         let duration = start_time.elapsed();
 
@@ -335,6 +492,16 @@ pub fn sync_account_next_chunk(
     })
 }
 
+fn account_view_private_key(account: &Account) -> Result<RistrettoPrivate, SyncError> {
+    Ok(if account.view_only {
+        let view_account_key: ViewAccountKey = mc_util_serial::decode(&account.account_key)?;
+        *view_account_key.view_private_key()
+    } else {
+        let account_key: AccountKey = mc_util_serial::decode(&account.account_key)?;
+        *account_key.view_private_key()
+    })
+}
+
 /// Attempt to decode the transaction amount. If we can't, then this transaction
 /// does not belong to this account.
 pub fn decode_amount(tx_out: &TxOut, view_private_key: &RistrettoPrivate) -> Option<Amount> {
@@ -407,17 +574,23 @@ pub fn decode_subaddress_and_key_image(
 mod tests {
     use super::*;
     use crate::{
-        service::{account::AccountService, balance::BalanceService, txo::TxoService},
+        db::transaction_log::{TransactionId, TxStatus},
+        service::{
+            account::AccountService, balance::BalanceService, transaction::TransactionMemo,
+            txo::TxoService,
+        },
         test_utils::{
-            add_block_to_ledger_db, get_test_ledger, manually_sync_account, setup_wallet_service,
-            MOB,
+            add_block_to_ledger_db, add_block_with_tx, builder_for_random_recipient,
+            get_test_ledger, manually_sync_account, random_account_with_seed_values,
+            setup_wallet_service, WalletDbTestContext, MOB,
         },
     };
     use mc_account_keys::{AccountKey, RootEntropy, RootIdentity};
-    use mc_common::logger::{test_with_logger, Logger};
+    use mc_common::logger::{async_test_with_logger, test_with_logger, Logger};
     use mc_transaction_core::{tokens::Mob, Token};
     use mc_util_from_random::FromRandom;
     use rand::{rngs::StdRng, SeedableRng};
+    use std::ops::DerefMut;
 
     #[test_with_logger]
     fn test_process_txo_bigint_in_origin(logger: Logger) {
@@ -506,4 +679,205 @@ mod tests {
         let balance_pmob = balance.get(&Mob::ID).unwrap();
         assert_eq!(balance_pmob.unspent, 250_000_000 * MOB as u128);
     }
+
+    #[test_with_logger]
+    fn test_import_in_progress_cleared_once_synced(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+        let conn = &mut wallet_db.get_pooled_conn().unwrap();
+
+        let root_id = RootIdentity::from_random(&mut rng);
+        let imported_id = AccountID(
+            Account::import_legacy(
+                &root_id.root_entropy,
+                Some("Imported".to_string()),
+                ledger_db.num_blocks().unwrap(),
+                Some(0),
+                None,
+                "".to_string(),
+                "".to_string(),
+                false,
+                conn,
+            )
+            .unwrap()
+            .id,
+        );
+        // Created accounts also have an import block index, but are not
+        // imports.
+        let root_id = RootIdentity::from_random(&mut rng);
+        let (created_id, _) = Account::create_from_root_entropy(
+            &root_id.root_entropy,
+            Some(0),
+            Some(ledger_db.num_blocks().unwrap()),
+            None,
+            "Created",
+            "".to_string(),
+            "".to_string(),
+            false,
+            conn,
+        )
+        .unwrap();
+
+        assert!(Account::get(&imported_id, conn).unwrap().import_in_progress);
+        assert!(!Account::get(&created_id, conn).unwrap().import_in_progress);
+
+        // The first pass scans the ledger, and the second sees the account synced.
+        let scan_pool = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        let sync_progress = SyncProgress::default();
+        for _ in 0..2 {
+            sync_all_accounts(
+                &ledger_db,
+                conn,
+                &scan_pool,
+                Arc::new(Mutex::new(HashMap::new())),
+                &sync_progress,
+                &SenderMemoFilter::default(),
+                &logger,
+            )
+            .unwrap();
+        }
+
+        assert!(!Account::get(&imported_id, conn).unwrap().import_in_progress);
+    }
+
+    #[test_with_logger]
+    fn test_sync_all_accounts_concurrently(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let mut ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+
+        let root_ids: Vec<RootIdentity> = (0..3)
+            .map(|_| RootIdentity::from_random(&mut rng))
+            .collect();
+        let mut account_ids = Vec::new();
+        for (i, root_id) in root_ids.iter().enumerate() {
+            let (account_id, _public_address_b58) = Account::create_from_root_entropy(
+                &root_id.root_entropy,
+                Some(0),
+                None,
+                None,
+                &format!("Account {i}"),
+                "".to_string(),
+                "".to_string(),
+                false,
+                &mut wallet_db.get_pooled_conn().unwrap(),
+            )
+            .unwrap();
+            account_ids.push(account_id);
+        }
+
+        let recipients: Vec<_> = root_ids
+            .iter()
+            .map(|root_id| AccountKey::from(root_id).default_subaddress())
+            .collect();
+        add_block_to_ledger_db(&mut ledger_db, &recipients, 10 * MOB, &[], &mut rng);
+
+        let scan_pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+        let accounts_with_deposits = Arc::new(Mutex::new(HashMap::new()));
+        let sync_progress = SyncProgress::default();
+        let conn = &mut wallet_db.get_pooled_conn().unwrap();
+
+        // The first pass syncs every account, and the second sees them fully synced.
+        for _ in 0..2 {
+            sync_all_accounts(
+                &ledger_db,
+                conn,
+                &scan_pool,
+                accounts_with_deposits.clone(),
+                &sync_progress,
+                &SenderMemoFilter::default(),
+                &logger,
+            )
+            .unwrap();
+        }
+
+        let num_blocks = ledger_db.num_blocks().unwrap();
+        for account_id in &account_ids {
+            let account = Account::get(account_id, conn).unwrap();
+            assert_eq!(account.next_block_index as u64, num_blocks);
+
+            let unspent = Txo::list_unspent(
+                Some(&account_id.0),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                conn,
+            )
+            .unwrap();
+            assert_eq!(unspent.len(), 1);
+            assert_eq!(unspent[0].value as u64, 10 * MOB);
+
+            assert_eq!(
+                accounts_with_deposits.lock().unwrap().get(account_id),
+                Some(&true)
+            );
+        }
+    }
+
+    #[async_test_with_logger]
+    async fn test_raw_tx_without_change_succeeds(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let mut ledger_db = get_test_ledger(5, &[], 12, &mut rng);
+
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[70 * MOB],
+            &mut rng,
+            &logger,
+        );
+        let account_id = AccountID::from(&account_key);
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+        let account = Account::get(&account_id, conn).unwrap();
+
+        // Send everything, so that nothing comes back to the account.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 0, Mob::ID).unwrap();
+        builder.set_tombstone(0).unwrap();
+        builder.select_txos_for_send_all(conn, None).unwrap();
+        let unsigned_tx_proposal = builder
+            .build(
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                conn,
+            )
+            .unwrap();
+        let tx_proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
+        assert!(tx_proposal.change_txos.is_empty());
+
+        // Log only the Tx, as if it had been built elsewhere.
+        let tx_log = TransactionLog::log_submitted_raw(
+            &tx_proposal.tx,
+            ledger_db.num_blocks().unwrap(),
+            "".to_string(),
+            &account_id.to_string(),
+            conn,
+        )
+        .unwrap();
+        assert!(tx_log.get_associated_txos(conn).unwrap().change.is_empty());
+
+        let num_blocks = add_block_with_tx(&mut ledger_db, tx_proposal.tx, &mut rng);
+        manually_sync_account(&ledger_db, &wallet_db, &account_id, &logger);
+
+        // The log is finalized when its inputs land, rather than failed as a
+        // conflicting spend of them.
+        let tx_log = TransactionLog::get(&TransactionId(tx_log.id), conn).unwrap();
+        assert_eq!(tx_log.status(), TxStatus::Succeeded);
+        assert_eq!(tx_log.finalized_block_index, Some(num_blocks as i64 - 1));
+        assert!(!tx_log.conflicting);
+    }
 }
@@ -0,0 +1,165 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! Tracking of how quickly accounts are being synced.
+//!
+//! The sync thread records how many blocks it scanned for each account on each
+//! pass, and how long the pass took. The wallet service uses a rolling average
+//! of those samples to estimate how long an account, or the whole wallet, has
+//! left to sync.
+
+use crate::db::account::AccountID;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+/// The number of recent sync passes the rolling average is taken over.
+const NUM_RATE_SAMPLES: usize = 20;
+
+#[derive(Clone, Default)]
+pub struct SyncProgress {
+    /// The number of blocks scanned and the time taken, for each of the most
+    /// recent sync passes of each account.
+    samples: Arc<RwLock<HashMap<AccountID, VecDeque<(u64, Duration)>>>>,
+}
+
+/// The current sync rate, and the estimated time until syncing is done.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SyncEstimate {
+    /// The rolling average of blocks scanned per second, or None if no blocks
+    /// have been scanned recently.
+    pub blocks_per_second: Option<f64>,
+
+    /// The estimated time until syncing is done, or None if it can not be
+    /// estimated because there are blocks left but no recent rate.
+    pub time_remaining: Option<Duration>,
+}
+
+impl SyncProgress {
+    /// Record that a sync pass scanned `num_blocks` blocks for an account in
+    /// `elapsed` time.
+    pub fn record(&self, account_id: &AccountID, num_blocks: u64, elapsed: Duration) {
+        let mut samples = self.samples.write().expect("lock poisoned");
+        let account_samples = samples.entry(account_id.clone()).or_default();
+        if account_samples.len() == NUM_RATE_SAMPLES {
+            account_samples.pop_front();
+        }
+        account_samples.push_back((num_blocks, elapsed));
+    }
+
+    /// Forget the samples for an account, once it is fully synced.
+    pub fn clear(&self, account_id: &AccountID) {
+        self.samples
+            .write()
+            .expect("lock poisoned")
+            .remove(account_id);
+    }
+
+    /// The rolling average of blocks scanned per second for an account.
+    pub fn blocks_per_second(&self, account_id: &AccountID) -> Option<f64> {
+        let samples = self.samples.read().expect("lock poisoned");
+        let (num_blocks, elapsed) = samples.get(account_id)?.iter().fold(
+            (0u64, Duration::ZERO),
+            |(num_blocks, elapsed), (sample_blocks, sample_elapsed)| {
+                (num_blocks + sample_blocks, elapsed + *sample_elapsed)
+            },
+        );
+        if num_blocks == 0 || elapsed.is_zero() {
+            return None;
+        }
+        Some(num_blocks as f64 / elapsed.as_secs_f64())
+    }
+
+    /// Estimate how long an account with `remaining_blocks` left to scan will
+    /// take to sync.
+    pub fn estimate(&self, account_id: &AccountID, remaining_blocks: u64) -> SyncEstimate {
+        let blocks_per_second = self.blocks_per_second(account_id);
+        let time_remaining = if remaining_blocks == 0 {
+            Some(Duration::ZERO)
+        } else {
+            blocks_per_second.map(|rate| Duration::from_secs_f64(remaining_blocks as f64 / rate))
+        };
+        SyncEstimate {
+            blocks_per_second,
+            time_remaining,
+        }
+    }
+
+    /// Estimate how long the wallet will take to sync, given the number of
+    /// blocks each account has left to scan. Accounts are synced side by side,
+    /// so the wallet is done when its slowest account is, and its rate is the
+    /// total over all accounts.
+    pub fn estimate_all(&self, remaining_blocks: &[(AccountID, u64)]) -> SyncEstimate {
+        let estimates: Vec<SyncEstimate> = remaining_blocks
+            .iter()
+            .map(|(account_id, remaining)| self.estimate(account_id, *remaining))
+            .collect();
+
+        let blocks_per_second = estimates
+            .iter()
+            .filter_map(|estimate| estimate.blocks_per_second)
+            .reduce(|total, rate| total + rate);
+        let time_remaining = estimates
+            .iter()
+            .map(|estimate| estimate.time_remaining)
+            .try_fold(Duration::ZERO, |max, time_remaining| {
+                Some(max.max(time_remaining?))
+            });
+        SyncEstimate {
+            blocks_per_second,
+            time_remaining,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_common::logger::{test_with_logger, Logger};
+
+    #[test_with_logger]
+    fn test_sync_estimate(_logger: Logger) {
+        let progress = SyncProgress::default();
+        let fast = AccountID("fast".to_string());
+        let slow = AccountID("slow".to_string());
+        let done = AccountID("done".to_string());
+
+        // No samples yet, so nothing can be estimated for blocks left to scan.
+        assert_eq!(progress.estimate(&fast, 100).time_remaining, None);
+        assert_eq!(
+            progress.estimate(&done, 0).time_remaining,
+            Some(Duration::ZERO)
+        );
+
+        progress.record(&fast, 1000, Duration::from_secs(1));
+        progress.record(&fast, 3000, Duration::from_secs(1));
+        progress.record(&slow, 100, Duration::from_secs(1));
+
+        let estimate = progress.estimate(&fast, 4000);
+        assert_eq!(estimate.blocks_per_second, Some(2000.0));
+        assert_eq!(estimate.time_remaining, Some(Duration::from_secs(2)));
+
+        // The wallet's rate is the total, and it is done when the slowest
+        // account is.
+        let estimate =
+            progress.estimate_all(&[(fast.clone(), 4000), (slow.clone(), 500), (done.clone(), 0)]);
+        assert_eq!(estimate.blocks_per_second, Some(2100.0));
+        assert_eq!(estimate.time_remaining, Some(Duration::from_secs(5)));
+
+        // Only the most recent samples are averaged.
+        for _ in 0..NUM_RATE_SAMPLES {
+            progress.record(&slow, 10, Duration::from_secs(1));
+        }
+        assert_eq!(progress.blocks_per_second(&slow), Some(10.0));
+
+        progress.clear(&slow);
+        assert_eq!(progress.blocks_per_second(&slow), None);
+        assert_eq!(
+            progress
+                .estimate_all(&[(fast, 4000), (slow, 500)])
+                .time_remaining,
+            None
+        );
+    }
+}
@@ -6,12 +6,14 @@ use crate::{
     db::{
         account::{AccountID, AccountModel},
         exclusive_transaction,
-        models::{Account, TransactionLog},
-        transaction_log::{AssociatedTxos, TransactionLogModel, ValueMap},
+        models::{Account, TransactionLog, Txo},
+        transaction_log::{AssociatedTxos, TransactionId, TransactionLogModel, ValueMap},
+        txo::{TxoID, TxoModel},
         WalletDbError,
     },
-    error::WalletTransactionBuilderError,
+    error::{ErrorCode, WalletTransactionBuilderError},
     json_rpc::v2::models::amount::Amount as AmountJSON,
+    metrics,
     service::{
         address::{AddressService, AddressServiceError},
         ledger::{LedgerService, LedgerServiceError},
@@ -35,6 +37,7 @@ use mc_transaction_builder::{
 use mc_transaction_core::{
     constants::{MAX_INPUTS, MAX_OUTPUTS},
     tokens::Mob,
+    tx::Tx,
     Amount, Token, TokenId,
 };
 use mc_transaction_extra::{BurnRedemptionMemo, SenderMemoCredential};
@@ -81,6 +84,9 @@ pub enum TransactionServiceError {
     /// Cannot complete this action in offline mode.
     Offline,
 
+    /// The wallet is shutting down and is not accepting new submissions.
+    ShuttingDown,
+
     /// Connection Error
     Connection(retry::Error<mc_connection::Error>),
 
@@ -128,6 +134,60 @@ pub enum TransactionServiceError {
 
     /// Hardware Wallet Service Error: {0}
     HardwareWalletService(crate::service::hardware_wallet::HardwareWalletServiceError),
+
+    /// Transaction has already been submitted and cannot be abandoned: {0}
+    TransactionAlreadySubmitted(String),
+
+    /// Invalid transaction proposal: {0}
+    InvalidTxProposal(String),
+
+    /// Txo {0} is reserved as an input of another transaction
+    TxoReserved(String),
+
+    /// Input reservation not found: {0}
+    InputReservationNotFound(String),
+
+    /// Input reservation {0} does not belong to account {1}
+    InputReservationAccountMismatch(String, String),
+}
+
+impl ErrorCode for TransactionServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::B58(err) => err.error_code(),
+            Self::Database(err) => err.error_code(),
+            Self::TransactionBuilder(err) => err.error_code(),
+            Self::U64Parse => "u64_parse",
+            Self::MissingAccountOnSubmit => "missing_account_on_submit",
+            Self::NodeNotFound => "node_not_found",
+            Self::NoPeersConfigured => "no_peers_configured",
+            Self::ProtoConversion(..) => "proto_conversion",
+            Self::ProtoConversionInfallible => "proto_conversion_infallible",
+            Self::Offline => "offline",
+            Self::ShuttingDown => "shutting_down",
+            Self::Connection(..) => "connection",
+            Self::InvalidPublicAddress(..) => "invalid_public_address",
+            Self::AddressService(err) => err.error_code(),
+            Self::Diesel(..) => "diesel",
+            Self::LedgerDB(..) => "ledger_db",
+            Self::InvalidAmount(..) => "invalid_amount",
+            Self::DefaultFeeNotFoundForToken(..) => "default_fee_not_found_for_token",
+            Self::FromHex(..) => "from_hex",
+            Self::InvalidBurnRedemptionMemo(..) => "invalid_burn_redemption_memo",
+            Self::Decode(..) => "decode",
+            Self::TxBuilder(..) => "tx_builder",
+            Self::LedgerService(err) => err.error_code(),
+            Self::Key(..) => "key",
+            Self::Retry(..) => "retry",
+            Self::RingCT(..) => "ring_ct",
+            Self::HardwareWalletService(err) => err.error_code(),
+            Self::TransactionAlreadySubmitted(..) => "transaction_already_submitted",
+            Self::InvalidTxProposal(..) => "invalid_tx_proposal",
+            Self::TxoReserved(..) => "txo_reserved",
+            Self::InputReservationNotFound(..) => "input_reservation_not_found",
+            Self::InputReservationAccountMismatch(..) => "input_reservation_account_mismatch",
+        }
+    }
 }
 
 impl From<WalletDbError> for TransactionServiceError {
@@ -301,6 +361,45 @@ impl TransactionMemo {
             }
         }
     }
+
+    /// A memo builder for accounts whose spend key is not available, such as
+    /// view only accounts.
+    ///
+    /// Outputs to recipients get no sender memo, since it cannot be
+    /// authenticated without the spend key, but change outputs still get a
+    /// destination memo so that the account's send history can be recovered
+    /// from the ledger.
+    pub fn memo_builder_without_sender_credential(&self) -> Box<dyn MemoBuilder + Send + Sync> {
+        match self {
+            Self::Empty => Box::<EmptyMemoBuilder>::default(),
+            Self::RTH { .. } => {
+                let mut memo_builder = RTHMemoBuilder::default();
+                memo_builder.enable_destination_memo();
+                Box::new(memo_builder)
+            }
+            Self::RTHWithPaymentIntentId {
+                payment_intent_id, ..
+            } => {
+                let mut memo_builder = RTHMemoBuilder::default();
+                memo_builder.enable_destination_memo();
+                memo_builder.set_payment_intent_id(*payment_intent_id);
+                Box::new(memo_builder)
+            }
+            Self::RTHWithPaymentRequestId {
+                payment_request_id, ..
+            } => {
+                let mut memo_builder = RTHMemoBuilder::default();
+                memo_builder.enable_destination_memo();
+                memo_builder.set_payment_request_id(*payment_request_id);
+                Box::new(memo_builder)
+            }
+            Self::BurnRedemption(memo_data) => {
+                let mut memo_builder = BurnRedemptionMemoBuilder::new(*memo_data);
+                memo_builder.enable_destination_memo();
+                Box::new(memo_builder)
+            }
+        }
+    }
 }
 
 fn generate_rth_memo_builder(
@@ -375,6 +474,7 @@ pub trait TransactionService {
     ///| `memo`                  | Memo for the transaction                                          |                                                                                                   |
     ///| `block_version`         | The block version to build this transaction for.                  | Defaults to the network block version                                                             |
     ///| `spend_subaddress` | The subaddress index to spend from.                               |                                                                                                   |
+    ///| `device_id`             | The hardware wallet to sign with                                  | Optional, only used for view only accounts. Defaults to the first device found.                   |
     ///
     #[allow(clippy::too_many_arguments)]
     async fn build_and_sign_transaction(
@@ -389,6 +489,75 @@ pub trait TransactionService {
         memo: TransactionMemo,
         block_version: Option<BlockVersion>,
         spend_subaddress: Option<String>,
+        device_id: Option<&str>,
+    ) -> Result<TxProposal, TransactionServiceError>;
+
+    /// Build a transaction sending as much of an account's balance of a token as can be spent in
+    /// one transaction, less the fee, to a single recipient, and sign it. The transaction has no
+    /// change output.
+    ///
+    /// At most MAX_INPUTS Txos can be spent in one transaction, so if the balance is fragmented
+    /// across more Txos than that, only the most valuable are spent and some of the balance is not
+    /// sent. The value sent is the value of the transaction's only payload output.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                       | Purpose                                                           | Notes                                                      |
+    ///|----------------------------|-------------------------------------------------------------------|------------------------------------------------------------|
+    ///| `account_id_hex`           | The account on which to perform this action                       | Account must exist in the wallet                           |
+    ///| `recipient_public_address` | The address to send to                                            | b58-encoded public address                                 |
+    ///| `token_id`                 | The token to send                                                 | The fee is paid in the same token                          |
+    ///| `fee_value`                | The fee value to submit with this transaction                     | If not provided, uses MINIMUM_FEE of the token             |
+    ///| `tombstone_block`          | The block after which this transaction expires                    | If not provided, uses current height + 10                  |
+    ///| `max_spendable_value`      | The maximum amount for an input TXO selected for this transaction |                                                            |
+    ///| `memo`                     | Memo for the transaction                                          |                                                            |
+    ///| `block_version`            | The block version to build this transaction for.                  | Defaults to the network block version                      |
+    ///| `spend_subaddress`         | The subaddress index to spend from.                               | Only the balance of this subaddress is sent, if provided   |
+    ///| `device_id`                | The hardware wallet to sign with                                  | Optional, only used for view only accounts                 |
+    ///
+    #[allow(clippy::too_many_arguments)]
+    async fn build_and_sign_send_all_transaction(
+        &self,
+        account_id_hex: &str,
+        recipient_public_address: &str,
+        token_id: TokenId,
+        fee_value: Option<String>,
+        tombstone_block: Option<String>,
+        max_spendable_value: Option<String>,
+        memo: TransactionMemo,
+        block_version: Option<BlockVersion>,
+        spend_subaddress: Option<String>,
+        device_id: Option<&str>,
+    ) -> Result<TxProposal, TransactionServiceError>;
+
+    /// Build a transaction spending exactly the Txos reserved with `reserve_inputs`, and sign it.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                    | Purpose                                             | Notes                                                                                             |
+    ///|-------------------------|-----------------------------------------------------|---------------------------------------------------------------------------------------------------|
+    ///| `account_id_hex`        | The account on which to perform this action         | Must be the account the inputs were reserved for                                                  |
+    ///| `reservation_id`        | The id returned by `reserve_inputs`                 | Must not have expired or had its Txos released                                                    |
+    ///| `addresses_and_amounts` | An array of public addresses and Amounts as a tuple | addresses are b58-encoded public addresses                                                        |
+    ///| `fee_value`             | The fee value to submit with this transaction       | If not provided, uses MINIMUM_FEE of the first outputs token_id, if available, or defaults to MOB |
+    ///| `fee_token_id`          | The fee token_id to submit with this transaction    | If not provided, uses token_id of first output, if available, or defaults to MOB                  |
+    ///| `tombstone_block`       | The block after which this transaction expires      | If not provided, uses current height + 10                                                         |
+    ///| `memo`                  | Memo for the transaction                            |                                                                                                   |
+    ///| `block_version`         | The block version to build this transaction for.    | Defaults to the network block version                                                             |
+    ///| `device_id`             | The hardware wallet to sign with                    | Optional, only used for view only accounts. Defaults to the first device found.                   |
+    ///
+    #[allow(clippy::too_many_arguments)]
+    async fn build_and_sign_reserved_transaction(
+        &self,
+        account_id_hex: &str,
+        reservation_id: &str,
+        addresses_and_amounts: &[(String, AmountJSON)],
+        fee_value: Option<String>,
+        fee_token_id: Option<String>,
+        tombstone_block: Option<String>,
+        memo: TransactionMemo,
+        block_version: Option<BlockVersion>,
+        device_id: Option<&str>,
     ) -> Result<TxProposal, TransactionServiceError>;
 
     /// Submits a pre-built TxProposal to the MobileCoin Consensus Network.
@@ -408,6 +577,35 @@ pub trait TransactionService {
         account_id_hex: Option<String>,
     ) -> Result<Option<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionServiceError>;
 
+    /// Submits a signed Tx which was not built by this wallet to the MobileCoin Consensus Network.
+    ///
+    /// # Arguments
+    ///
+    ///| Name             | Purpose                                                     | Notes                                                                                                                         |
+    ///|------------------|-------------------------------------------------------------|-------------------------------------------------------------------------------------------------------------------------------|
+    ///| `tx`             | Transaction to submit                                       |                                                                                                                               |
+    ///| `comment`        | Comment to annotate this transaction in the transaction log |                                                                                                                               |
+    ///| `account_id_hex` | Account ID for which to log the transaction.                | If omitted, the transaction is not logged. It is checked before submitting. The log only includes the account's own txos.     |
+    ///
+    fn submit_raw_tx(
+        &self,
+        tx: &Tx,
+        comment: Option<String>,
+        account_id_hex: Option<String>,
+    ) -> Result<Option<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionServiceError>;
+
+    /// Abandon a built transaction which will not be submitted, so that its inputs may be
+    /// selected for other transactions. This releases the reservation of its inputs and removes
+    /// its transaction log and unreferenced output txos, if they were logged.
+    ///
+    /// # Arguments
+    ///
+    ///| Name          | Purpose                         | Notes                                          |
+    ///|---------------|---------------------------------|------------------------------------------------|
+    ///| `tx_proposal` | Transaction proposal to abandon | Created with build_transaction. Not submitted. |
+    ///
+    fn abandon_transaction(&self, tx_proposal: &TxProposal) -> Result<(), TransactionServiceError>;
+
     /// Build and sign a transaction and submit it to the network.
     ///
     /// # Arguments
@@ -424,6 +622,7 @@ pub trait TransactionService {
     ///| `memo`                  | Memo for the transaction                                          |                                                                                                   |
     ///| `block_version`         | The block version to build this transaction for.                  | Defaults to the network block version                                                             |
     ///| `spend_subaddress` | The subaddress index to spend from.                               |                                                                                                   |
+    ///| `device_id`             | The hardware wallet to sign with                                  | Optional, only used for view only accounts. Defaults to the first device found.                   |
     ///
     #[allow(clippy::too_many_arguments)]
     async fn build_sign_and_submit_transaction(
@@ -439,6 +638,7 @@ pub trait TransactionService {
         memo: TransactionMemo,
         block_version: Option<BlockVersion>,
         spend_subaddress: Option<String>,
+        device_id: Option<&str>,
     ) -> Result<(TransactionLog, AssociatedTxos, ValueMap, TxProposal), TransactionServiceError>;
 }
 
@@ -460,6 +660,346 @@ where
         memo: TransactionMemo,
         block_version: Option<BlockVersion>,
         spend_subaddress: Option<String>,
+    ) -> Result<UnsignedTxProposal, TransactionServiceError> {
+        self.build_unsigned_tx_proposal(
+            account_id_hex,
+            addresses_and_amounts,
+            input_txo_ids,
+            fee_value,
+            fee_token_id,
+            tombstone_block,
+            max_spendable_value,
+            memo,
+            block_version,
+            spend_subaddress,
+            false,
+            None,
+        )
+    }
+
+    async fn build_and_sign_transaction(
+        &self,
+        account_id_hex: &str,
+        addresses_and_amounts: &[(String, AmountJSON)],
+        input_txo_ids: Option<&Vec<String>>,
+        fee_value: Option<String>,
+        fee_token_id: Option<String>,
+        tombstone_block: Option<String>,
+        max_spendable_value: Option<String>,
+        memo: TransactionMemo,
+        block_version: Option<BlockVersion>,
+        spend_subaddress: Option<String>,
+        device_id: Option<&str>,
+    ) -> Result<TxProposal, TransactionServiceError> {
+        let unsigned_tx_proposal = self.build_transaction(
+            account_id_hex,
+            addresses_and_amounts,
+            input_txo_ids,
+            fee_value,
+            fee_token_id,
+            tombstone_block,
+            max_spendable_value,
+            memo,
+            block_version,
+            spend_subaddress,
+        )?;
+
+        self.sign_and_log_tx_proposal(account_id_hex, unsigned_tx_proposal, device_id)
+            .await
+    }
+
+    async fn build_and_sign_send_all_transaction(
+        &self,
+        account_id_hex: &str,
+        recipient_public_address: &str,
+        token_id: TokenId,
+        fee_value: Option<String>,
+        tombstone_block: Option<String>,
+        max_spendable_value: Option<String>,
+        memo: TransactionMemo,
+        block_version: Option<BlockVersion>,
+        spend_subaddress: Option<String>,
+        device_id: Option<&str>,
+    ) -> Result<TxProposal, TransactionServiceError> {
+        // The value of the output is set once the inputs are selected.
+        let unsigned_tx_proposal = self.build_unsigned_tx_proposal(
+            account_id_hex,
+            &[(
+                recipient_public_address.to_string(),
+                AmountJSON::new(0, token_id),
+            )],
+            None,
+            fee_value,
+            Some(token_id.to_string()),
+            tombstone_block,
+            max_spendable_value,
+            memo,
+            block_version,
+            spend_subaddress,
+            true,
+            None,
+        )?;
+
+        self.sign_and_log_tx_proposal(account_id_hex, unsigned_tx_proposal, device_id)
+            .await
+    }
+
+    async fn build_and_sign_reserved_transaction(
+        &self,
+        account_id_hex: &str,
+        reservation_id: &str,
+        addresses_and_amounts: &[(String, AmountJSON)],
+        fee_value: Option<String>,
+        fee_token_id: Option<String>,
+        tombstone_block: Option<String>,
+        memo: TransactionMemo,
+        block_version: Option<BlockVersion>,
+        device_id: Option<&str>,
+    ) -> Result<TxProposal, TransactionServiceError> {
+        let reservation = self
+            .txo_reservations
+            .get_input_reservation(reservation_id)
+            .ok_or_else(|| {
+                TransactionServiceError::InputReservationNotFound(reservation_id.to_string())
+            })?;
+        if reservation.account_id != account_id_hex {
+            return Err(TransactionServiceError::InputReservationAccountMismatch(
+                reservation_id.to_string(),
+                account_id_hex.to_string(),
+            ));
+        }
+
+        let unsigned_tx_proposal = self.build_unsigned_tx_proposal(
+            account_id_hex,
+            addresses_and_amounts,
+            Some(&reservation.txo_ids),
+            fee_value,
+            fee_token_id,
+            tombstone_block,
+            None,
+            memo,
+            block_version,
+            None,
+            false,
+            Some(reservation_id),
+        )?;
+
+        self.sign_and_log_tx_proposal(account_id_hex, unsigned_tx_proposal, device_id)
+            .await
+    }
+
+    fn submit_transaction(
+        &self,
+        tx_proposal: &TxProposal,
+        comment: Option<String>,
+        account_id_hex: Option<String>,
+    ) -> Result<Option<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionServiceError> {
+        if self.offline {
+            return Err(TransactionServiceError::Offline);
+        }
+
+        // Held until the transaction is logged, so that shutdown waits for it.
+        let _submission = self
+            .submission_tracker
+            .start()
+            .ok_or(TransactionServiceError::ShuttingDown)?;
+
+        // Once submitted, the inputs are excluded from selection as pending, so
+        // they no longer need to be reserved. If submission fails the proposal
+        // is not going to be used, so its inputs are freed for other
+        // transactions.
+        let input_txo_ids: Vec<String> = tx_proposal
+            .input_txos
+            .iter()
+            .map(|input| TxoID::from(&input.tx_out).to_string())
+            .collect();
+        let block_index = self.propose_tx(&tx_proposal.tx);
+        self.txo_reservations.release(&input_txo_ids);
+        let block_index = block_index?;
+
+        if let Some(account_id_hex) = account_id_hex {
+            let mut pooled_conn = self.get_pooled_conn()?;
+            let conn = pooled_conn.deref_mut();
+            let account_id = AccountID(account_id_hex.to_string());
+
+            if Account::get(&account_id, conn).is_ok() {
+                let transaction_log = TransactionLog::log_submitted(
+                    tx_proposal,
+                    block_index,
+                    comment.unwrap_or_default(),
+                    &account_id_hex,
+                    conn,
+                )?;
+
+                let associated_txos = transaction_log.get_associated_txos(conn)?;
+                let value_map = transaction_log.value_map(conn)?;
+
+                Ok(Some((transaction_log, associated_txos, value_map)))
+            } else {
+                Err(TransactionServiceError::Database(
+                    WalletDbError::AccountNotFound(account_id_hex),
+                ))
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn submit_raw_tx(
+        &self,
+        tx: &Tx,
+        comment: Option<String>,
+        account_id_hex: Option<String>,
+    ) -> Result<Option<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionServiceError> {
+        if self.offline {
+            return Err(TransactionServiceError::Offline);
+        }
+
+        // Held until the transaction is logged, so that shutdown waits for it.
+        let _submission = self
+            .submission_tracker
+            .start()
+            .ok_or(TransactionServiceError::ShuttingDown)?;
+
+        // Check the account before submitting, so that a transaction which was
+        // submitted is not reported as failed and retried.
+        if let Some(account_id_hex) = &account_id_hex {
+            let mut pooled_conn = self.get_pooled_conn()?;
+            Account::get(&AccountID(account_id_hex.clone()), pooled_conn.deref_mut())?;
+        }
+
+        let block_index = self.propose_tx(tx)?;
+
+        let account_id_hex = match account_id_hex {
+            Some(account_id_hex) => account_id_hex,
+            None => return Ok(None),
+        };
+
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let transaction_log = TransactionLog::log_submitted_raw(
+            tx,
+            block_index,
+            comment.unwrap_or_default(),
+            &account_id_hex,
+            conn,
+        )?;
+        let associated_txos = transaction_log.get_associated_txos(conn)?;
+        let value_map = transaction_log.value_map(conn)?;
+
+        Ok(Some((transaction_log, associated_txos, value_map)))
+    }
+
+    fn abandon_transaction(&self, tx_proposal: &TxProposal) -> Result<(), TransactionServiceError> {
+        let transaction_log_id = TransactionId::try_from(tx_proposal)
+            .map_err(|e| TransactionServiceError::InvalidTxProposal(e.to_string()))?;
+
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        exclusive_transaction(conn, |conn| {
+            match TransactionLog::get(&transaction_log_id, conn) {
+                Ok(transaction_log) => {
+                    if transaction_log.submitted_block_index.is_some() {
+                        return Err(TransactionServiceError::TransactionAlreadySubmitted(
+                            transaction_log_id.to_string(),
+                        ));
+                    }
+                    transaction_log.delete(conn)?;
+                    // Remove the output txos created for this transaction, which are
+                    // no longer referenced by any transaction log.
+                    Txo::delete_unreferenced(conn)?;
+                }
+                Err(WalletDbError::TransactionLogNotFound(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
+            Ok(())
+        })?;
+
+        let input_txo_ids: Vec<String> = tx_proposal
+            .input_txos
+            .iter()
+            .map(|input| TxoID::from(&input.tx_out).to_string())
+            .collect();
+        self.txo_reservations.release(&input_txo_ids);
+
+        Ok(())
+    }
+
+    async fn build_sign_and_submit_transaction(
+        &self,
+        account_id_hex: &str,
+        addresses_and_amounts: &[(String, AmountJSON)],
+        input_txo_ids: Option<&Vec<String>>,
+        fee_value: Option<String>,
+        fee_token_id: Option<String>,
+        tombstone_block: Option<String>,
+        max_spendable_value: Option<String>,
+        comment: Option<String>,
+        memo: TransactionMemo,
+        block_version: Option<BlockVersion>,
+        spend_subaddress: Option<String>,
+        device_id: Option<&str>,
+    ) -> Result<(TransactionLog, AssociatedTxos, ValueMap, TxProposal), TransactionServiceError>
+    {
+        let tx_proposal = self
+            .build_and_sign_transaction(
+                account_id_hex,
+                addresses_and_amounts,
+                input_txo_ids,
+                fee_value,
+                fee_token_id,
+                tombstone_block,
+                max_spendable_value,
+                memo,
+                block_version,
+                spend_subaddress,
+                device_id,
+            )
+            .await?;
+
+        if let Some(transaction_log_and_associated_txos) =
+            self.submit_transaction(&tx_proposal, comment, Some(account_id_hex.to_string()))?
+        {
+            Ok((
+                transaction_log_and_associated_txos.0,
+                transaction_log_and_associated_txos.1,
+                transaction_log_and_associated_txos.2,
+                tx_proposal,
+            ))
+        } else {
+            Err(TransactionServiceError::MissingAccountOnSubmit)
+        }
+    }
+}
+
+impl<T, FPR> WalletService<T, FPR>
+where
+    T: BlockchainConnection + UserTxConnection + 'static,
+    FPR: FogPubkeyResolver + Send + Sync + 'static,
+{
+    /// Build a transaction, selecting inputs for the outputs unless they are
+    /// given. If `send_all` is set, the most valuable inputs that can be spent
+    /// together are selected instead, and the only output is set to their
+    /// total less the fee.
+    ///
+    /// Given inputs must not be reserved by another transaction, unless they
+    /// are the inputs reserved under `reservation_id`.
+    #[allow(clippy::too_many_arguments)]
+    fn build_unsigned_tx_proposal(
+        &self,
+        account_id_hex: &str,
+        addresses_and_amounts: &[(String, AmountJSON)],
+        input_txo_ids: Option<&Vec<String>>,
+        fee_value: Option<String>,
+        fee_token_id: Option<String>,
+        tombstone_block: Option<String>,
+        max_spendable_value: Option<String>,
+        memo: TransactionMemo,
+        block_version: Option<BlockVersion>,
+        spend_subaddress: Option<String>,
+        send_all: bool,
+        reservation_id: Option<&str>,
     ) -> Result<UnsignedTxProposal, TransactionServiceError> {
         validate_number_inputs(input_txo_ids.unwrap_or(&Vec::new()).len() as u64)?;
         validate_number_outputs(addresses_and_amounts.len() as u64)?;
@@ -482,6 +1022,8 @@ where
                 self.ledger_db.clone(),
                 self.fog_resolver_factory.clone(),
             );
+            builder.set_deterministic_output_order(self.deterministic_output_order);
+            builder.set_max_received_block_index(self.max_spendable_received_block_index()?);
 
             let mut default_fee_token_id = Mob::ID;
 
@@ -527,6 +1069,9 @@ where
             }
 
             if let Some(inputs) = input_txo_ids {
+                self.txo_reservations
+                    .check_unreserved(inputs, reservation_id)
+                    .map_err(TransactionServiceError::TxoReserved)?;
                 builder.set_txos(conn, inputs)?;
             } else {
                 if let Some(subaddress) = spend_subaddress {
@@ -540,47 +1085,57 @@ where
                 } else {
                     None
                 };
-                builder.select_txos(conn, max_spendable)?;
+                builder.set_excluded_txos(self.txo_reservations.reserved_txo_ids());
+                if send_all {
+                    builder.select_txos_for_send_all(conn, max_spendable)?;
+                } else {
+                    builder.select_txos(conn, max_spendable)?;
+                }
             }
 
             let unsigned_tx_proposal = builder.build(memo, conn)?;
 
+            // Reserve the inputs so that concurrent builds do not select them
+            // before this proposal is submitted.
+            self.txo_reservations.reserve(
+                unsigned_tx_proposal
+                    .unsigned_input_txos
+                    .iter()
+                    .map(|input| TxoID::from(&input.tx_out).to_string()),
+            );
+
             Ok(unsigned_tx_proposal)
         })
     }
 
-    async fn build_and_sign_transaction(
+    /// Sign an unsigned tx proposal with an account, and log it as signed. The
+    /// `device_id` selects the hardware wallet which signs for a view only
+    /// account.
+    async fn sign_and_log_tx_proposal(
         &self,
         account_id_hex: &str,
-        addresses_and_amounts: &[(String, AmountJSON)],
-        input_txo_ids: Option<&Vec<String>>,
-        fee_value: Option<String>,
-        fee_token_id: Option<String>,
-        tombstone_block: Option<String>,
-        max_spendable_value: Option<String>,
-        memo: TransactionMemo,
-        block_version: Option<BlockVersion>,
-        spend_subaddress: Option<String>,
+        unsigned_tx_proposal: UnsignedTxProposal,
+        device_id: Option<&str>,
     ) -> Result<TxProposal, TransactionServiceError> {
-        let unsigned_tx_proposal = self.build_transaction(
-            account_id_hex,
-            addresses_and_amounts,
-            input_txo_ids,
-            fee_value,
-            fee_token_id,
-            tombstone_block,
-            max_spendable_value,
-            memo,
-            block_version,
-            spend_subaddress,
-        )?;
-
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
 
         let account = Account::get(&AccountID(account_id_hex.to_string()), conn)?;
 
-        let tx_proposal = unsigned_tx_proposal.sign(&account).await?;
+        // A proposal which could not be signed will not be submitted, so its
+        // inputs are freed for other transactions.
+        let input_txo_ids: Vec<String> = unsigned_tx_proposal
+            .unsigned_input_txos
+            .iter()
+            .map(|input| TxoID::from(&input.tx_out).to_string())
+            .collect();
+        let tx_proposal = match unsigned_tx_proposal.sign(&account, device_id).await {
+            Ok(tx_proposal) => tx_proposal,
+            Err(err) => {
+                self.txo_reservations.release(&input_txo_ids);
+                return Err(err);
+            }
+        };
 
         exclusive_transaction(conn, |conn| {
             TransactionLog::log_signed(tx_proposal.clone(), "".to_string(), account_id_hex, conn)?;
@@ -588,16 +1143,9 @@ where
         })
     }
 
-    fn submit_transaction(
-        &self,
-        tx_proposal: &TxProposal,
-        comment: Option<String>,
-        account_id_hex: Option<String>,
-    ) -> Result<Option<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionServiceError> {
-        if self.offline {
-            return Err(TransactionServiceError::Offline);
-        }
-
+    /// Propose a transaction to the next peer, returning the block index at
+    /// which it was submitted.
+    fn propose_tx(&self, tx: &Tx) -> Result<u64, TransactionServiceError> {
         // Pick a peer to submit to.
         let responder_ids = self.peer_manager.responder_ids();
         if responder_ids.is_empty() {
@@ -611,86 +1159,21 @@ where
             .peer_manager
             .conn(responder_id)
             .ok_or(TransactionServiceError::NodeNotFound)?
-            .propose_tx(&tx_proposal.tx, Fibonacci::from_millis(10).take(5))
-            .map_err(TransactionServiceError::from)?;
+            .propose_tx(tx, Fibonacci::from_millis(10).take(5))
+            .map_err(|err| {
+                metrics::SUBMIT_FAILURES.inc();
+                TransactionServiceError::from(err)
+            })?;
+        metrics::SUBMIT_SUCCESSES.inc();
 
         log::trace!(
             self.logger,
             "Tx {:?} submitted at block height {}",
-            tx_proposal.tx,
+            tx,
             block_index
         );
 
-        if let Some(account_id_hex) = account_id_hex {
-            let mut pooled_conn = self.get_pooled_conn()?;
-            let conn = pooled_conn.deref_mut();
-            let account_id = AccountID(account_id_hex.to_string());
-
-            if Account::get(&account_id, conn).is_ok() {
-                let transaction_log = TransactionLog::log_submitted(
-                    tx_proposal,
-                    block_index,
-                    comment.unwrap_or_default(),
-                    &account_id_hex,
-                    conn,
-                )?;
-
-                let associated_txos = transaction_log.get_associated_txos(conn)?;
-                let value_map = transaction_log.value_map(conn)?;
-
-                Ok(Some((transaction_log, associated_txos, value_map)))
-            } else {
-                Err(TransactionServiceError::Database(
-                    WalletDbError::AccountNotFound(account_id_hex),
-                ))
-            }
-        } else {
-            Ok(None)
-        }
-    }
-
-    async fn build_sign_and_submit_transaction(
-        &self,
-        account_id_hex: &str,
-        addresses_and_amounts: &[(String, AmountJSON)],
-        input_txo_ids: Option<&Vec<String>>,
-        fee_value: Option<String>,
-        fee_token_id: Option<String>,
-        tombstone_block: Option<String>,
-        max_spendable_value: Option<String>,
-        comment: Option<String>,
-        memo: TransactionMemo,
-        block_version: Option<BlockVersion>,
-        spend_subaddress: Option<String>,
-    ) -> Result<(TransactionLog, AssociatedTxos, ValueMap, TxProposal), TransactionServiceError>
-    {
-        let tx_proposal = self
-            .build_and_sign_transaction(
-                account_id_hex,
-                addresses_and_amounts,
-                input_txo_ids,
-                fee_value,
-                fee_token_id,
-                tombstone_block,
-                max_spendable_value,
-                memo,
-                block_version,
-                spend_subaddress,
-            )
-            .await?;
-
-        if let Some(transaction_log_and_associated_txos) =
-            self.submit_transaction(&tx_proposal, comment, Some(account_id_hex.to_string()))?
-        {
-            Ok((
-                transaction_log_and_associated_txos.0,
-                transaction_log_and_associated_txos.1,
-                transaction_log_and_associated_txos.2,
-                tx_proposal,
-            ))
-        } else {
-            Err(TransactionServiceError::MissingAccountOnSubmit)
-        }
+        Ok(block_index)
     }
 }
 
@@ -703,48 +1186,141 @@ fn validate_number_inputs(num_inputs: u64) -> Result<(), TransactionServiceError
     Ok(())
 }
 
-fn validate_number_outputs(num_outputs: u64) -> Result<(), TransactionServiceError> {
-    // maximum number of outputs is 16 but we reserve 1 for change
-    let max_outputs = MAX_OUTPUTS - 1;
-    if num_outputs > max_outputs {
-        return Err(TransactionServiceError::TransactionBuilder(WalletTransactionBuilderError::InvalidArgument(
-            format!("Invalid number of recipiants. {num_outputs:?} recipiants provided but maximum allowed number of outputs is {max_outputs:?}")
-        )));
+fn validate_number_outputs(num_outputs: u64) -> Result<(), TransactionServiceError> {
+    // maximum number of outputs is 16 but we reserve 1 for change
+    let max_outputs = MAX_OUTPUTS - 1;
+    if num_outputs > max_outputs {
+        return Err(TransactionServiceError::TransactionBuilder(WalletTransactionBuilderError::InvalidArgument(
+            format!("Invalid number of recipiants. {num_outputs:?} recipiants provided but maximum allowed number of outputs is {max_outputs:?}")
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{
+            account::AccountID, assigned_subaddress::AssignedSubaddressModel, models::Txo,
+            transaction_log::TxStatus, txo::TxoModel,
+        },
+        service::{
+            account::AccountService, address::AddressService, balance::BalanceService,
+            transaction_log::TransactionLogService,
+        },
+        test_utils::{
+            add_block_to_ledger_db, add_block_with_tx_outs, get_test_ledger, manually_sync_account,
+            setup_wallet_service, MOB,
+        },
+        util::b58::b58_encode_public_address,
+    };
+    use mc_account_keys::{AccountKey, PublicAddress, ViewAccountKey};
+    use mc_common::logger::{async_test_with_logger, Logger};
+    use mc_core::account::ShortAddressHash;
+    use mc_crypto_keys::RistrettoPublic;
+    use mc_rand::rand_core::RngCore;
+    use mc_transaction_core::{
+        get_tx_out_shared_secret, ring_signature::KeyImage, tokens::Mob, Token,
+    };
+    use mc_transaction_extra::{
+        AuthenticatedSenderMemo, AuthenticatedSenderWithPaymentRequestIdMemo, DestinationMemo,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::convert::TryFrom;
+
+    #[async_test_with_logger]
+    async fn test_build_send_all_transaction(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_account_key.default_subaddress(); 3],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let bob = service
+            .create_account(
+                Some("Bob's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let bob_address = service
+            .assign_address_for_account(&AccountID(bob.id.clone()), None)
+            .unwrap();
+
+        // Everything is sent, less the fee, with no change.
+        let tx_proposal = service
+            .build_and_sign_send_all_transaction(
+                &alice.id,
+                &bob_address.public_address_b58,
+                Mob::ID,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(tx_proposal.input_txos.len(), 3);
+        assert_eq!(tx_proposal.payload_txos.len(), 1);
+        assert_eq!(
+            tx_proposal.payload_txos[0].amount,
+            Amount::new(300 * MOB - Mob::MINIMUM_FEE, Mob::ID)
+        );
+        assert!(tx_proposal.change_txos.is_empty());
+        assert_eq!(tx_proposal.tx.prefix.outputs.len(), 1);
+
+        // The inputs are reserved, so nothing is left to send.
+        assert!(service
+            .build_and_sign_send_all_transaction(
+                &alice.id,
+                &bob_address.public_address_b58,
+                Mob::ID,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+                None,
+            )
+            .await
+            .is_err());
     }
-    Ok(())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{
-        db::{
-            account::AccountID, assigned_subaddress::AssignedSubaddressModel, models::Txo,
-            txo::TxoModel,
-        },
-        service::{
-            account::AccountService, address::AddressService, balance::BalanceService,
-            transaction_log::TransactionLogService,
-        },
-        test_utils::{
-            add_block_to_ledger_db, add_block_with_tx_outs, get_test_ledger, manually_sync_account,
-            setup_wallet_service, MOB,
-        },
-        util::b58::b58_encode_public_address,
-    };
-    use mc_account_keys::{AccountKey, PublicAddress};
-    use mc_common::logger::{async_test_with_logger, Logger};
-    use mc_core::account::ShortAddressHash;
-    use mc_crypto_keys::RistrettoPublic;
-    use mc_rand::rand_core::RngCore;
-    use mc_transaction_core::{
-        get_tx_out_shared_secret, ring_signature::KeyImage, tokens::Mob, Token,
-    };
-    use mc_transaction_extra::{
-        AuthenticatedSenderMemo, AuthenticatedSenderWithPaymentRequestIdMemo, DestinationMemo,
-    };
-    use rand::{rngs::StdRng, SeedableRng};
-    use std::convert::TryFrom;
 
     #[async_test_with_logger]
     async fn test_build_transaction_and_log(logger: Logger) {
@@ -778,7 +1354,7 @@ mod tests {
 
         add_block_to_ledger_db(
             &mut ledger_db,
-            &vec![alice_public_address],
+            &vec![alice_public_address.clone()],
             100 * MOB,
             &[KeyImage::from(rng.next_u64())],
             &mut rng,
@@ -804,6 +1380,22 @@ mod tests {
         let balance_pmob = balance.get(&Mob::ID).unwrap();
         assert_eq!(balance_pmob.unspent, 100 * MOB as u128);
 
+        // Each built transaction reserves its input until it is submitted, so
+        // give Alice a txo for each of the other transactions below.
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_public_address; 2],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
         // Add an account for Bob
         let bob = service
             .create_account(
@@ -839,6 +1431,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -872,6 +1465,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -905,6 +1499,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -917,6 +1512,200 @@ mod tests {
         assert_eq!(3, tx_logs.len());
     }
 
+    #[async_test_with_logger]
+    async fn test_build_transaction_reserves_inputs(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_public_address.clone(); 2],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let recipient = b58_encode_public_address(&alice_public_address).unwrap();
+        let build = || {
+            service.build_transaction(
+                &alice.id,
+                &[(recipient.clone(), AmountJSON::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+            )
+        };
+
+        // Two proposals built before either is submitted do not share inputs.
+        let first = build().unwrap();
+        let second = build().unwrap();
+        assert_eq!(first.unsigned_input_txos.len(), 1);
+        assert_eq!(second.unsigned_input_txos.len(), 1);
+        assert_ne!(
+            first.unsigned_input_txos[0].tx_out,
+            second.unsigned_input_txos[0].tx_out
+        );
+
+        // Neither can be spent explicitly by another transaction.
+        let first_input_txo_ids =
+            vec![TxoID::from(&first.unsigned_input_txos[0].tx_out).to_string()];
+        match service.build_transaction(
+            &alice.id,
+            &[(recipient.clone(), AmountJSON::new(42 * MOB, Mob::ID))],
+            Some(&first_input_txo_ids),
+            None,
+            None,
+            None,
+            None,
+            TransactionMemo::RTH {
+                subaddress_index: None,
+            },
+            None,
+            None,
+        ) {
+            Err(TransactionServiceError::TxoReserved(txo_id)) => {
+                assert_eq!(txo_id, first_input_txo_ids[0])
+            }
+            Ok(_) => panic!("Should error with TxoReserved"),
+            Err(e) => panic!("Should error with TxoReserved but got {:?}", e),
+        }
+
+        // With both txos reserved, there is nothing left to select.
+        match build() {
+            Err(TransactionServiceError::TransactionBuilder(
+                WalletTransactionBuilderError::WalletDb(WalletDbError::NoSpendableTxos(_)),
+            )) => {}
+            Ok(_) => panic!("Should error with NoSpendableTxos"),
+            Err(e) => panic!("Should error with NoSpendableTxos but got {:?}", e),
+        }
+
+        // Submitting a proposal releases the reservation of its inputs.
+        let account =
+            Account::get(&alice_account_id, &mut service.get_pooled_conn().unwrap()).unwrap();
+        let tx_proposal = first.sign(&account, None).await.unwrap();
+        service
+            .submit_transaction(&tx_proposal, None, Some(alice.id.clone()))
+            .unwrap();
+        assert_eq!(service.txo_reservations.reserved_txo_ids().len(), 1);
+    }
+
+    #[async_test_with_logger]
+    async fn test_submit_raw_tx(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![alice_public_address.clone()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        // Build a transaction without logging it, as an external tool would.
+        let recipient = b58_encode_public_address(&alice_public_address).unwrap();
+        let unsigned_tx_proposal = service
+            .build_transaction(
+                &alice.id,
+                &[(recipient, AmountJSON::new(42 * MOB, Mob::ID))],
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+            )
+            .unwrap();
+        let account =
+            Account::get(&alice_account_id, &mut service.get_pooled_conn().unwrap()).unwrap();
+        let tx = unsigned_tx_proposal.sign(&account, None).await.unwrap().tx;
+
+        // Without an account, the transaction is submitted but not logged.
+        assert!(service.submit_raw_tx(&tx, None, None).unwrap().is_none());
+
+        let (transaction_log, associated_txos, _) = service
+            .submit_raw_tx(&tx, Some("relayed".to_string()), Some(alice.id.clone()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction_log.id, TransactionId::try_from(&tx).unwrap().0);
+        assert_eq!(transaction_log.account_id, alice.id);
+        assert_eq!(transaction_log.comment, "relayed");
+        assert_eq!(transaction_log.fee_value as u64, tx.prefix.fee);
+        assert_eq!(transaction_log.tx, mc_util_serial::encode(&tx));
+        assert_eq!(
+            transaction_log.tombstone_block_index,
+            Some(tx.prefix.tombstone_block as i64)
+        );
+        assert_eq!(transaction_log.status(), TxStatus::Pending);
+        // The payload was sent back to Alice, so it is decoded along with the change.
+        assert_eq!(associated_txos.inputs.len(), 1);
+        assert_eq!(associated_txos.outputs.len(), 1);
+        assert_eq!(associated_txos.change.len(), 1);
+
+        // An account which is not in the wallet is rejected before submitting.
+        assert!(matches!(
+            service.submit_raw_tx(&tx, None, Some("nonexistent".to_string())),
+            Err(TransactionServiceError::Database(
+                WalletDbError::AccountNotFound(_)
+            ))
+        ));
+    }
+
     // Test sending a transaction from Alice -> Bob, and then from Bob -> Alice
     #[async_test_with_logger]
     async fn test_send_transaction(logger: Logger) {
@@ -1000,6 +1789,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -1102,6 +1892,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -1161,6 +1952,86 @@ mod tests {
         );
     }
 
+    #[async_test_with_logger]
+    async fn test_view_only_change_has_destination_memo(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+
+        let account_key = AccountKey::random(&mut rng);
+        let view_account_key = ViewAccountKey::from(&account_key);
+        let view_only_account = service
+            .import_view_only_account(
+                &(*view_account_key.view_private_key()).into(),
+                &(*view_account_key.spend_public_key()).into(),
+                None,
+                None,
+                None,
+                None,
+                false,
+                &[],
+            )
+            .unwrap();
+        let account_id = AccountID(view_only_account.id.clone());
+
+        add_block_to_ledger_db(
+            &mut ledger_db,
+            &vec![account_key.default_subaddress()],
+            100 * MOB,
+            &[KeyImage::from(rng.next_u64())],
+            &mut rng,
+        );
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &account_id,
+            &logger,
+        );
+
+        let recipient = AccountKey::random(&mut rng).default_subaddress();
+        let unsigned_tx_proposal = service
+            .build_transaction(
+                &account_id.to_string(),
+                &[(
+                    b58_encode_public_address(&recipient).unwrap(),
+                    AmountJSON::new(42 * MOB, Mob::ID),
+                )],
+                None,
+                None,
+                None,
+                None,
+                None,
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                None,
+                None,
+            )
+            .unwrap();
+
+        // The change output records who was paid, even though the sender
+        // memo could not be signed.
+        let change_tx_out = &unsigned_tx_proposal.change_txos[0].tx_out;
+        let shared_secret = get_tx_out_shared_secret(
+            account_key.view_private_key(),
+            &RistrettoPublic::try_from(&change_tx_out.public_key).unwrap(),
+        );
+        let change_memo = change_tx_out.decrypt_memo(&shared_secret);
+        let destination_memo = DestinationMemo::from(change_memo.get_memo_data());
+        assert_eq!(destination_memo.get_num_recipients(), 1);
+        assert_eq!(
+            destination_memo.get_total_outlay(),
+            42 * MOB + Mob::MINIMUM_FEE
+        );
+        assert_eq!(
+            destination_memo.get_address_hash(),
+            &ShortAddressHash::from(&recipient)
+        );
+    }
+
     // Building a transaction for an invalid public address should fail.
     #[async_test_with_logger]
     async fn test_invalid_public_address_fails(logger: Logger) {
@@ -1214,6 +2085,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
         {
@@ -1285,6 +2157,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
         {
@@ -1323,6 +2196,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
         {
@@ -1425,6 +2299,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -1618,6 +2493,7 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -1829,6 +2705,7 @@ mod tests {
                 },
                 None,
                 Some(alice_subaddress.public_address_b58.clone()),
+                None,
             )
             .await
             .unwrap();
@@ -1992,6 +2869,7 @@ mod tests {
                     },
                     None,
                     Some(alice_subaddress.public_address_b58.clone()),
+                    None,
                 )
                 .await;
             match res {
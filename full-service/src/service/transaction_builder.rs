@@ -15,7 +15,7 @@ use crate::{
         assigned_subaddress::AssignedSubaddressModel,
         models::{Account, Txo},
         txo::TxoModel,
-        Conn,
+        Conn, WalletDbError,
     },
     error::WalletTransactionBuilderError,
     service::transaction::TransactionMemo,
@@ -27,8 +27,7 @@ use mc_crypto_ring_signature_signer::OneTimeKeyDeriveData;
 use mc_fog_report_validation::FogPubkeyResolver;
 use mc_ledger_db::{Ledger, LedgerDB};
 use mc_transaction_builder::{
-    DefaultTxOutputsOrdering, EmptyMemoBuilder, InputCredentials, ReservedSubaddresses,
-    TransactionBuilder,
+    DefaultTxOutputsOrdering, InputCredentials, ReservedSubaddresses, TransactionBuilder,
 };
 use mc_transaction_core::{
     constants::RING_SIZE,
@@ -38,7 +37,7 @@ use mc_transaction_core::{
 };
 use mc_util_uri::FogUri;
 use rand::Rng;
-use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+use std::{collections::BTreeMap, convert::TryFrom, str::FromStr, sync::Arc};
 
 /// Default number of blocks used for calculating transaction tombstone block
 /// number.
@@ -78,6 +77,22 @@ pub struct WalletTransactionBuilder<FPR: FogPubkeyResolver + 'static> {
     /// Subaddress (index) from which to restrict TXOs for spending
     /// (optional).
     subaddress_index_to_spend_from: Option<u64>,
+
+    /// Txo ids to exclude when selecting inputs, because they are reserved
+    /// by other transactions which have been built but not yet submitted.
+    excluded_txo_ids: Vec<String>,
+
+    /// Whether to add outputs sorted by recipient address, token id and value,
+    /// rather than in the order the recipients were added.
+    deterministic_output_order: bool,
+
+    /// The highest block index from which txos may be selected as inputs, so
+    /// that inputs have the minimum number of confirmations.
+    max_received_block_index: Option<u64>,
+
+    /// Whether the transaction sends everything selected, less the fee, to a
+    /// single recipient, without a change output.
+    send_all: bool,
 }
 
 impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
@@ -97,6 +112,10 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
             block_version: None,
             fog_resolver_factory,
             subaddress_index_to_spend_from: None,
+            excluded_txo_ids: vec![],
+            deterministic_output_order: false,
+            max_received_block_index: None,
+            send_all: false,
         }
     }
 
@@ -109,6 +128,21 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
         Ok(())
     }
 
+    /// Sets txos to exclude when selecting inputs.
+    pub fn set_excluded_txos(&mut self, txo_ids: Vec<String>) {
+        self.excluded_txo_ids = txo_ids;
+    }
+
+    /// Sets whether outputs are added in a deterministic order.
+    pub fn set_deterministic_output_order(&mut self, deterministic_output_order: bool) {
+        self.deterministic_output_order = deterministic_output_order;
+    }
+
+    /// Sets the highest block index from which txos may be selected as inputs.
+    pub fn set_max_received_block_index(&mut self, max_received_block_index: Option<u64>) {
+        self.max_received_block_index = max_received_block_index;
+    }
+
     /// Sets inputs to the txos associated with the given txo_ids. Only unspent
     /// txos are included.
     pub fn set_txos(
@@ -135,51 +169,141 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
         conn: Conn,
         max_spendable_value: Option<u64>,
     ) -> Result<(), WalletTransactionBuilderError> {
-        let mut outlay_value_sum_map: BTreeMap<TokenId, u128> =
-            self.outlays
-                .iter()
-                .fold(BTreeMap::new(), |mut acc, (_, value, token_id)| {
-                    acc.entry(*token_id)
-                        .and_modify(|v| *v += *value as u128)
-                        .or_insert(*value as u128);
-                    acc
-                });
+        let (fee_value, fee_token_id) = self.validate_fee_token()?;
+        let outlay_value: u128 = self
+            .outlays
+            .iter()
+            .map(|(_, value, _)| *value as u128)
+            .sum();
+
+        let spend_subaddress = self.spend_subaddress_b58(conn)?;
+
+        self.inputs = match Txo::select_spendable_txos_for_value(
+            &self.account_id_hex,
+            outlay_value + fee_value as u128,
+            max_spendable_value,
+            spend_subaddress.as_deref(),
+            *fee_token_id,
+            fee_value,
+            &self.excluded_txo_ids,
+            self.max_received_block_index,
+            conn,
+        ) {
+            Ok(inputs) => inputs,
+            Err(
+                e @ (WalletDbError::NoSpendableTxos(_)
+                | WalletDbError::InsufficientFunds(_)
+                | WalletDbError::InsufficientFundsUnderMaxSpendable(_)),
+            ) => {
+                // If the outputs could be paid for on their own, it is the fee
+                // which can not be.
+                let outlays_spendable = outlay_value > 0
+                    && Txo::select_spendable_txos_for_value(
+                        &self.account_id_hex,
+                        outlay_value,
+                        max_spendable_value,
+                        spend_subaddress.as_deref(),
+                        *fee_token_id,
+                        0,
+                        &self.excluded_txo_ids,
+                        self.max_received_block_index,
+                        conn,
+                    )
+                    .is_ok();
+                if outlays_spendable {
+                    return Err(WalletTransactionBuilderError::InsufficientFeeTokenBalance(
+                        *fee_token_id,
+                        fee_value,
+                    ));
+                }
+                return Err(e.into());
+            }
+            Err(e) => return Err(e.into()),
+        };
 
-        let (fee_value, fee_token_id) = self.fee.unwrap_or((Mob::MINIMUM_FEE, Mob::ID));
-        outlay_value_sum_map
-            .entry(fee_token_id)
-            .and_modify(|v| *v += fee_value as u128)
-            .or_insert(fee_value as u128);
-
-        for (token_id, target_value) in outlay_value_sum_map {
-            let fee_value = if token_id == fee_token_id {
-                fee_value
-            } else {
-                0
-            };
+        Ok(())
+    }
 
-            let spend_subaddress =
-                if let Some(subaddress_index_to_spend_from) = self.subaddress_index_to_spend_from {
-                    let account = Account::get(&AccountID(self.account_id_hex.clone()), conn)?;
-                    let subaddress = account.public_address(subaddress_index_to_spend_from)?;
-                    let b58_subaddress = b58_encode_public_address(&subaddress)?;
-                    Some(b58_subaddress)
-                } else {
-                    None
-                };
+    /// Selects the most valuable Txos that can be spent together, and sets the
+    /// value sent to the only recipient to their total less the fee, so that
+    /// no change is left. The fee must be paid in the token being sent.
+    ///
+    /// If the account holds more Txos than can be spent in one transaction,
+    /// some of its balance is not sent.
+    ///
+    /// # Returns
+    /// * The value sent to the recipient
+    pub fn select_txos_for_send_all(
+        &mut self,
+        conn: Conn,
+        max_spendable_value: Option<u64>,
+    ) -> Result<u64, WalletTransactionBuilderError> {
+        if self.outlays.len() != 1 {
+            return Err(WalletTransactionBuilderError::InvalidArgument(
+                "Sending the entire balance requires exactly one recipient".to_string(),
+            ));
+        }
+        let (fee_value, token_id) = self.validate_fee_token()?;
+
+        let spend_subaddress = self.spend_subaddress_b58(conn)?;
+        self.inputs = Txo::select_max_spendable_txos(
+            &self.account_id_hex,
+            max_spendable_value,
+            spend_subaddress.as_deref(),
+            *token_id,
+            &self.excluded_txo_ids,
+            self.max_received_block_index,
+            conn,
+        )?;
 
-            self.inputs = Txo::select_spendable_txos_for_value(
-                &self.account_id_hex,
-                target_value,
-                max_spendable_value,
-                spend_subaddress.as_deref(),
-                *token_id,
-                fee_value,
-                conn,
-            )?;
+        let input_value: u128 = self
+            .inputs
+            .iter()
+            .map(|txo| (txo.value as u64) as u128)
+            .sum();
+        if input_value <= fee_value as u128 {
+            return Err(WalletTransactionBuilderError::InsufficientInputFunds(format!(
+                "The spendable value {input_value} does not cover the fee {fee_value} for token_id {token_id}"
+            )));
         }
+        let value = u64::try_from(input_value - fee_value as u128)
+            .map_err(|_| WalletTransactionBuilderError::OutboundValueTooLarge)?;
 
-        Ok(())
+        self.outlays[0].1 = value;
+        self.send_all = true;
+        Ok(value)
+    }
+
+    /// The fee, after checking that it is paid in the same token as every
+    /// output. Transactions mixing tokens are not supported.
+    fn validate_fee_token(&self) -> Result<(u64, TokenId), WalletTransactionBuilderError> {
+        let (fee_value, fee_token_id) = self.fee.unwrap_or((Mob::MINIMUM_FEE, Mob::ID));
+        if let Some((_, _, token_id)) = self
+            .outlays
+            .iter()
+            .find(|(_, _, token_id)| *token_id != fee_token_id)
+        {
+            return Err(WalletTransactionBuilderError::FeeTokenMismatch(
+                *fee_token_id,
+                **token_id,
+            ));
+        }
+        Ok((fee_value, fee_token_id))
+    }
+
+    /// The b58 address of the subaddress to spend from, if one is set.
+    fn spend_subaddress_b58(
+        &self,
+        conn: Conn,
+    ) -> Result<Option<String>, WalletTransactionBuilderError> {
+        match self.subaddress_index_to_spend_from {
+            Some(subaddress_index_to_spend_from) => {
+                let account = Account::get(&AccountID(self.account_id_hex.clone()), conn)?;
+                let subaddress = account.public_address(subaddress_index_to_spend_from)?;
+                Ok(Some(b58_encode_public_address(&subaddress)?))
+            }
+            None => Ok(None),
+        }
     }
 
     pub fn add_recipient(
@@ -221,6 +345,31 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
         Ok(())
     }
 
+    /// The outlays in the order their outputs should be added.
+    fn ordered_outlays(
+        &self,
+    ) -> Result<Vec<(PublicAddress, u64, TokenId)>, WalletTransactionBuilderError> {
+        if !self.deterministic_output_order {
+            return Ok(self.outlays.clone());
+        }
+
+        let mut keyed_outlays = self
+            .outlays
+            .iter()
+            .map(|outlay| Ok((b58_encode_public_address(&outlay.0)?, outlay.clone())))
+            .collect::<Result<Vec<_>, WalletTransactionBuilderError>>()?;
+        keyed_outlays.sort_by(
+            |(a_b58, (_, a_value, a_token_id)), (b_b58, (_, b_value, b_token_id))| {
+                (a_b58, a_token_id, a_value).cmp(&(b_b58, b_token_id, b_value))
+            },
+        );
+
+        Ok(keyed_outlays
+            .into_iter()
+            .map(|(_, outlay)| outlay)
+            .collect())
+    }
+
     pub fn get_fog_resolver(&self, conn: Conn) -> Result<FPR, WalletTransactionBuilderError> {
         let account = Account::get(&AccountID(self.account_id_hex.clone()), conn)?;
         let change_subaddress = account.change_subaddress(conn)?;
@@ -252,13 +401,13 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
         let reserved_subaddresses = ReservedSubaddresses::from(&view_account_key);
 
         let block_version = self.block_version.unwrap_or(BlockVersion::MAX);
-        let (fee, fee_token_id) = self.fee.unwrap_or((Mob::MINIMUM_FEE, Mob::ID));
+        let (fee, fee_token_id) = self.validate_fee_token()?;
         let fee_amount = Amount::new(fee, fee_token_id);
         let fog_resolver = self.get_fog_resolver(conn)?;
 
         let memo_builder = match account.account_key() {
             Ok(account_key) => memo.memo_builder(&account_key),
-            Err(_) => Box::<EmptyMemoBuilder>::default(),
+            Err(_) => memo.memo_builder_without_sender_credential(),
         };
 
         let mut transaction_builder = TransactionBuilder::new_with_box(
@@ -396,7 +545,7 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
         total_value_per_token.insert(fee_token_id, fee as u128);
 
         let mut payload_txos = Vec::new();
-        for (receiver, amount, token_id) in self.outlays.clone().into_iter() {
+        for (receiver, amount, token_id) in self.ordered_outlays()? {
             total_value_per_token
                 .entry(token_id)
                 .and_modify(|value| *value += amount as u128)
@@ -455,6 +604,11 @@ impl<FPR: FogPubkeyResolver + 'static> WalletTransactionBuilder<FPR> {
                 ));
             }
 
+            // Everything selected is sent, so there is no change to return.
+            if self.send_all && change_value == 0 {
+                continue;
+            }
+
             let change_amount = Amount::new(change_value as u64, token_id);
             if let Some(subaddress_index_to_spend_from) = self.subaddress_index_to_spend_from {
                 // Send the change back to the subaddress that is spending the inputs.
@@ -587,8 +741,8 @@ mod tests {
 
     use super::*;
     use crate::{
-        db::WalletDbError,
-        service::sync::SyncThread,
+        db::{authenticated_sender_memo::SenderMemoFilter, WalletDbError},
+        service::{sync::SyncThread, sync_progress::SyncProgress},
         test_utils::{
             builder_for_random_recipient, get_test_ledger, random_account_with_seed_values,
             WalletDbTestContext, MOB,
@@ -612,6 +766,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -651,7 +807,7 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
         assert_eq!(proposal.payload_txos.len(), 1);
         assert_eq!(proposal.payload_txos[0].recipient_public_address, recipient);
         assert_eq!(proposal.payload_txos[0].amount.value, value);
@@ -675,6 +831,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -788,6 +946,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -861,7 +1021,7 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
         assert_eq!(proposal.payload_txos.len(), 1);
         assert_eq!(proposal.payload_txos[0].recipient_public_address, recipient);
         assert_eq!(
@@ -890,6 +1050,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -995,6 +1157,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1071,6 +1235,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1128,7 +1294,7 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
         assert_eq!(proposal.payload_txos.len(), 1);
         assert_eq!(proposal.payload_txos[0].recipient_public_address, recipient);
         assert_eq!(proposal.payload_txos[0].amount.value, 80 * MOB);
@@ -1154,6 +1320,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1206,7 +1374,7 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
         assert_eq!(
             proposal.tx.prefix.tombstone_block,
             13 + DEFAULT_NEW_TX_BLOCK_ATTEMPTS
@@ -1232,7 +1400,7 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
         assert_eq!(proposal.tx.prefix.tombstone_block, 20);
     }
 
@@ -1251,6 +1419,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1283,7 +1453,7 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
         assert_eq!(proposal.tx.prefix.fee, Mob::MINIMUM_FEE);
 
         // You cannot set fee to 0
@@ -1308,7 +1478,7 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
         assert_eq!(proposal.tx.prefix.fee, Mob::MINIMUM_FEE);
 
         // Setting fee less than minimum fee should fail
@@ -1340,10 +1510,63 @@ mod tests {
                 conn,
             )
             .unwrap();
-        let proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
         assert_eq!(proposal.tx.prefix.fee, Mob::MINIMUM_FEE * 10);
     }
 
+    #[test_with_logger]
+    fn test_fee_token_validation(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[70 * MOB],
+            &mut rng,
+            &logger,
+        );
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        // The fee can not be paid in a different token than the outputs.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder
+            .add_recipient(recipient, 10 * MOB, TokenId::from(1))
+            .unwrap();
+        builder.set_fee(Mob::MINIMUM_FEE, Mob::ID).unwrap();
+        match builder.select_txos(conn, None) {
+            Err(WalletTransactionBuilderError::FeeTokenMismatch(0, 1)) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        // The balance covers the output, but not the fee as well.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 70 * MOB, Mob::ID).unwrap();
+        match builder.select_txos(conn, None) {
+            Err(WalletTransactionBuilderError::InsufficientFeeTokenBalance(0, fee)) => {
+                assert_eq!(fee, Mob::MINIMUM_FEE)
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+
+        // The balance does not cover the output at all.
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.add_recipient(recipient, 80 * MOB, Mob::ID).unwrap();
+        match builder.select_txos(conn, None) {
+            Err(WalletTransactionBuilderError::WalletDb(_)) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
     // Even if change is zero, we should still have a change output
     #[async_test_with_logger]
     async fn test_change_zero_mob(logger: Logger) {
@@ -1359,6 +1582,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1393,7 +1618,7 @@ mod tests {
             )
             .unwrap();
         let account = Account::get(&AccountID::from(&account_key), conn).unwrap();
-        let proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
 
         assert_eq!(proposal.tx.prefix.fee, Mob::MINIMUM_FEE);
         assert_eq!(proposal.payload_txos.len(), 1);
@@ -1420,6 +1645,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1461,7 +1688,7 @@ mod tests {
             )
             .unwrap();
         let account = Account::get(&AccountID::from(&account_key), conn).unwrap();
-        let proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+        let proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
 
         assert_eq!(proposal.tx.prefix.fee, Mob::MINIMUM_FEE);
         assert_eq!(proposal.payload_txos.len(), 4);
@@ -1492,6 +1719,8 @@ mod tests {
             ledger_db.clone(),
             wallet_db.clone(),
             Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
             logger.clone(),
         );
 
@@ -1514,4 +1743,93 @@ mod tests {
             .add_recipient(second_recipient, 40 * MOB, Mob::ID)
             .unwrap();
     }
+
+    // Outputs should be sorted by recipient when deterministic ordering is set.
+    #[test_with_logger]
+    fn test_deterministic_output_order(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let db_test_context = WalletDbTestContext::default();
+        let wallet_db = db_test_context.get_db_instance(logger.clone());
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        // Start sync thread
+        let _sync_thread = SyncThread::start(
+            ledger_db.clone(),
+            wallet_db.clone(),
+            Arc::new(Mutex::new(HashMap::<AccountID, bool>::new())),
+            SyncProgress::default(),
+            SenderMemoFilter::default(),
+            logger.clone(),
+        );
+
+        let account_key = random_account_with_seed_values(
+            &wallet_db,
+            &mut ledger_db,
+            &[70 * MOB, 80 * MOB, 90 * MOB],
+            &mut rng,
+            &logger,
+        );
+
+        let mut pooled_conn = wallet_db.get_pooled_conn().unwrap();
+        let conn = pooled_conn.deref_mut();
+
+        let (recipient, mut builder) =
+            builder_for_random_recipient(&account_key, &ledger_db, &mut rng);
+        builder.set_deterministic_output_order(true);
+
+        let second_recipient = AccountKey::random(&mut rng).subaddress(0);
+        let third_recipient = AccountKey::random(&mut rng).subaddress(0);
+        builder
+            .add_recipient(recipient.clone(), 30 * MOB, Mob::ID)
+            .unwrap();
+        builder
+            .add_recipient(second_recipient.clone(), 20 * MOB, Mob::ID)
+            .unwrap();
+        builder
+            .add_recipient(third_recipient.clone(), 10 * MOB, Mob::ID)
+            .unwrap();
+        builder
+            .add_recipient(recipient.clone(), 5 * MOB, Mob::ID)
+            .unwrap();
+
+        builder.select_txos(conn, None).unwrap();
+        builder.set_tombstone(0).unwrap();
+
+        let unsigned_tx_proposal = builder
+            .build(
+                TransactionMemo::RTH {
+                    subaddress_index: None,
+                },
+                conn,
+            )
+            .unwrap();
+
+        let mut expected = vec![
+            (b58_encode_public_address(&recipient).unwrap(), 5 * MOB),
+            (b58_encode_public_address(&recipient).unwrap(), 30 * MOB),
+            (
+                b58_encode_public_address(&second_recipient).unwrap(),
+                20 * MOB,
+            ),
+            (
+                b58_encode_public_address(&third_recipient).unwrap(),
+                10 * MOB,
+            ),
+        ];
+        expected.sort();
+
+        let actual: Vec<(String, u64)> = unsigned_tx_proposal
+            .payload_txos
+            .iter()
+            .map(|txo| {
+                (
+                    b58_encode_public_address(&txo.recipient_public_address).unwrap(),
+                    txo.amount.value,
+                )
+            })
+            .collect();
+        assert_eq!(actual, expected);
+    }
 }
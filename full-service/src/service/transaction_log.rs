@@ -6,16 +6,19 @@ use std::ops::DerefMut;
 
 use crate::{
     db::{
+        account::AccountID,
         models::TransactionLog,
         transaction_log::{AssociatedTxos, TransactionId, TransactionLogModel, ValueMap},
-        WalletDbError,
+        Conn, WalletDbError,
     },
-    error::WalletServiceError,
+    error::{ErrorCode, WalletServiceError},
     WalletService,
 };
 use displaydoc::Display;
+use mc_common::HashMap;
 use mc_connection::{BlockchainConnection, UserTxConnection};
 use mc_fog_report_validation::FogPubkeyResolver;
+use mc_transaction_core::TokenId;
 
 /// Errors for the Transaction Log Service.
 #[derive(Display, Debug)]
@@ -28,6 +31,15 @@ pub enum TransactionLogServiceError {
     Diesel(diesel::result::Error),
 }
 
+impl ErrorCode for TransactionLogServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Database(err) => err.error_code(),
+            Self::Diesel(..) => "diesel",
+        }
+    }
+}
+
 impl From<WalletDbError> for TransactionLogServiceError {
     fn from(src: WalletDbError) -> Self {
         Self::Database(src)
@@ -40,6 +52,17 @@ impl From<diesel::result::Error> for TransactionLogServiceError {
     }
 }
 
+/// A summary of the outgoing transactions for a single token which have been
+/// submitted but are not yet finalized or failed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PendingOutgoing {
+    /// The number of pending transactions with outputs in this token.
+    pub num_transactions: u64,
+
+    /// The total value of those outputs, excluding change.
+    pub value: u64,
+}
+
 /// Trait defining the ways in which the wallet can interact with and manage
 /// transaction logs.
 #[rustfmt::skip]
@@ -78,6 +101,40 @@ pub trait TransactionLogService {
         &self,
         transaction_id_hex: &str,
     ) -> Result<(TransactionLog, AssociatedTxos, ValueMap), TransactionLogServiceError>;
+
+    /// Get the transaction logs which spent or created a txo.
+    ///
+    /// # Arguments
+    ///
+    ///| Name     | Purpose                               | Notes                             |
+    ///|----------|---------------------------------------|-----------------------------------|
+    ///| `txo_id` | The txo to find transaction logs for. | Txo need not exist in the wallet. |
+    ///
+    fn get_transaction_logs_for_txo(
+        &self,
+        txo_id: &str,
+    ) -> Result<Vec<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionLogServiceError>;
+
+    /// Summarize the pending outgoing transactions for an account, grouped by token.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                      | Notes                              |
+    ///|--------------|----------------------------------------------|------------------------------------|
+    ///| `account_id` | The account id to scan for transaction logs. | Account must exist in the database |
+    ///
+    fn get_pending_outgoing_for_account(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<HashMap<TokenId, PendingOutgoing>, TransactionLogServiceError>;
+
+    /// Summarize the pending outgoing transactions for every account in the wallet, loading all
+    /// pending transaction logs in a single query and grouping them by account.
+    ///
+    /// Accounts without pending transactions are omitted.
+    fn get_pending_outgoing_for_all_accounts(
+        &self,
+    ) -> Result<HashMap<AccountID, HashMap<TokenId, PendingOutgoing>>, TransactionLogServiceError>;
 }
 
 impl<T, FPR> TransactionLogService for WalletService<T, FPR>
@@ -118,6 +175,70 @@ where
 
         Ok((transaction_log, associated, value_map))
     }
+
+    fn get_transaction_logs_for_txo(
+        &self,
+        txo_id: &str,
+    ) -> Result<Vec<(TransactionLog, AssociatedTxos, ValueMap)>, TransactionLogServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        TransactionLog::list_for_txo(txo_id, conn)?
+            .into_iter()
+            .map(|transaction_log| {
+                let associated = transaction_log.get_associated_txos(conn)?;
+                let value_map = transaction_log.value_map(conn)?;
+                Ok((transaction_log, associated, value_map))
+            })
+            .collect()
+    }
+
+    fn get_pending_outgoing_for_account(
+        &self,
+        account_id: &AccountID,
+    ) -> Result<HashMap<TokenId, PendingOutgoing>, TransactionLogServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        let mut pending_outgoing: HashMap<TokenId, PendingOutgoing> = HashMap::default();
+        for transaction_log in TransactionLog::list_pending_for_account(account_id, conn)? {
+            add_pending_outgoing(&mut pending_outgoing, &transaction_log, conn)?;
+        }
+
+        Ok(pending_outgoing)
+    }
+
+    fn get_pending_outgoing_for_all_accounts(
+        &self,
+    ) -> Result<HashMap<AccountID, HashMap<TokenId, PendingOutgoing>>, TransactionLogServiceError>
+    {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        let mut pending_outgoing: HashMap<AccountID, HashMap<TokenId, PendingOutgoing>> =
+            HashMap::default();
+        for transaction_log in TransactionLog::list_pending(conn)? {
+            let entry = pending_outgoing
+                .entry(AccountID(transaction_log.account_id.clone()))
+                .or_default();
+            add_pending_outgoing(entry, &transaction_log, conn)?;
+        }
+
+        Ok(pending_outgoing)
+    }
+}
+
+fn add_pending_outgoing(
+    pending_outgoing: &mut HashMap<TokenId, PendingOutgoing>,
+    transaction_log: &TransactionLog,
+    conn: Conn,
+) -> Result<(), TransactionLogServiceError> {
+    for (token_id, value) in transaction_log.value_map(conn)?.0 {
+        let entry = pending_outgoing.entry(token_id).or_default();
+        entry.num_transactions += 1;
+        entry.value += value;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -212,6 +333,7 @@ mod tests {
                     },
                     None,
                     None,
+                    None,
                 )
                 .await
                 .unwrap();
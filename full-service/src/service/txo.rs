@@ -8,23 +8,26 @@ use crate::{
     db::{
         account::{AccountID, AccountModel},
         assigned_subaddress::AssignedSubaddressModel,
+        exclusive_transaction,
         models::{Account, AssignedSubaddress, Txo},
-        txo::{TxoID, TxoInfo, TxoModel, TxoStatus},
-        WalletDbError,
+        txo::{TxoEvent, TxoID, TxoInfo, TxoModel, TxoStatus},
+        Conn, WalletDbError,
     },
-    error::WalletTransactionBuilderError,
+    error::{ErrorCode, WalletTransactionBuilderError},
     json_rpc::v2::models::amount::Amount,
     service::{
-        ledger::LedgerServiceError,
+        ledger::{LedgerService, LedgerServiceError},
         models::tx_proposal::TxProposal,
         transaction::{TransactionMemo, TransactionService, TransactionServiceError},
+        txo_reservation::InputReservation,
     },
     WalletService,
 };
 use displaydoc::Display;
 use mc_connection::{BlockchainConnection, UserTxConnection};
 use mc_fog_report_validation::FogPubkeyResolver;
-use mc_transaction_core::FeeMapError;
+use mc_ledger_db::Ledger;
+use mc_transaction_core::{ring_signature::KeyImage, FeeMapError, TokenId};
 
 /// Errors for the Txo Service.
 #[derive(Display, Debug)]
@@ -74,6 +77,36 @@ pub enum TxoServiceError {
 
     /// Ledger Service Error: {0}
     LedgerService(LedgerServiceError),
+
+    /// No default fee found for token id: {0}
+    DefaultFeeNotFoundForToken(TokenId),
+
+    /// Input reservation not found, or it has expired: {0}
+    InputReservationNotFound(String),
+}
+
+impl ErrorCode for TxoServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Database(err) => err.error_code(),
+            Self::LedgerDB(..) => "ledger_db",
+            Self::Diesel(..) => "diesel",
+            Self::MissingConfirmation(..) => "missing_confirmation",
+            Self::TransactionService(err) => err.error_code(),
+            Self::TxoNotSpendableByAnyAccount(..) => "txo_not_spendable_by_any_account",
+            Self::TxoNotSpendable(..) => "txo_not_spendable",
+            Self::InvalidQuery(..) => "invalid_query",
+            Self::Decode(..) => "decode",
+            Self::WalletTransactionBuilder(err) => err.error_code(),
+            Self::Key(..) => "key",
+            Self::From(..) => "from",
+            Self::TxBuilder(..) => "tx_builder",
+            Self::FeeMap(..) => "fee_map",
+            Self::LedgerService(err) => err.error_code(),
+            Self::DefaultFeeNotFoundForToken(..) => "default_fee_not_found_for_token",
+            Self::InputReservationNotFound(..) => "input_reservation_not_found",
+        }
+    }
 }
 
 impl From<WalletDbError> for TxoServiceError {
@@ -142,6 +175,24 @@ impl From<LedgerServiceError> for TxoServiceError {
     }
 }
 
+/// The key image of a spent Txo, and the block in which it was spent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpentKeyImage {
+    pub txo_id: String,
+    pub key_image: KeyImage,
+    pub spent_block_index: u64,
+}
+
+/// The Txos which would be selected as inputs to spend a value, along with the
+/// fee the transaction would pay.
+#[derive(Debug, Clone)]
+pub struct SpendableSelection {
+    pub txos: Vec<Txo>,
+    pub total_value: u128,
+    pub fee_value: u64,
+    pub token_id: TokenId,
+}
+
 /// Trait defining the ways in which the wallet can interact with and manage
 /// Txos.
 #[rustfmt::skip]
@@ -176,6 +227,89 @@ pub trait TxoService {
         limit: Option<u64>,
     ) -> Result<Vec<TxoInfo>, TxoServiceError>;
 
+    /// List the unspent Txos which have sat untouched for at least a number of blocks, i.e. which
+    /// were received that many blocks or more before the last block in the ledger.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                       | Purpose                                                              | Notes                             |
+    ///|----------------------------|----------------------------------------------------------------------|-----------------------------------|
+    ///| `account_id`               | The account on which to perform this action.                         | Account must exist in the wallet. |
+    ///| `address`                  | The address b58 on which to perform this action.                     | Address must exist in the wallet. |
+    ///| `token_id`                 | The tokenId of this a txo                                            |                                   |
+    ///| `unspent_for_blocks`       | The number of blocks the Txos must have been unspent for.            |                                   |
+    ///| `min_received_block_index` | The minimum block index to query for received txos, inclusive        |                                   |
+    ///| `max_received_block_index` | The maximum block index to query for received txos, inclusive        |                                   |
+    ///| `offset`                   | The pagination offset. Results start at the offset index.            | Optional, defaults to 0           |
+    ///| `limit`                    | Limit for the number of results.                                     | Optional                          |
+    ///
+    #[allow(clippy::too_many_arguments)]
+    fn list_stale_txos(
+        &self,
+        account_id: Option<String>,
+        address: Option<String>,
+        token_id: Option<u64>,
+        unspent_for_blocks: u64,
+        min_received_block_index: Option<u64>,
+        max_received_block_index: Option<u64>,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<TxoInfo>, TxoServiceError>;
+
+    /// List the Txos received at the change subaddress of an account.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                                   | Notes                             |
+    ///|--------------|-----------------------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account on which to perform this action.              | Account must exist in the wallet. |
+    ///| `token_id`   | The tokenId of this a txo                                 |                                   |
+    ///| `offset`     | The pagination offset. Results start at the offset index. | Optional, defaults to 0           |
+    ///| `limit`      | Limit for the number of results.                          | Optional                          |
+    ///
+    fn list_change_txos(
+        &self,
+        account_id: &AccountID,
+        token_id: Option<u64>,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<TxoInfo>, TxoServiceError>;
+
+    /// List the Txos received by an account without a sender memo while the account required one.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                                   | Notes                             |
+    ///|--------------|-----------------------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account on which to perform this action.              | Account must exist in the wallet. |
+    ///| `offset`     | The pagination offset. Results start at the offset index. | Optional, defaults to 0           |
+    ///| `limit`      | Limit for the number of results.                          | Optional                          |
+    ///
+    fn list_txos_missing_required_memo(
+        &self,
+        account_id: &AccountID,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<TxoInfo>, TxoServiceError>;
+
+    /// List the key images of an account's spent Txos along with the blocks they were spent in,
+    /// ordered by spent block index.
+    ///
+    /// # Arguments
+    ///
+    ///| Name         | Purpose                                                   | Notes                             |
+    ///|--------------|-----------------------------------------------------------|-----------------------------------|
+    ///| `account_id` | The account on which to perform this action.              | Account must exist in the wallet. |
+    ///| `offset`     | The pagination offset. Results start at the offset index. | Optional, defaults to 0           |
+    ///| `limit`      | Limit for the number of results.                          | Optional                          |
+    ///
+    fn list_spent_key_images(
+        &self,
+        account_id: &AccountID,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<SpentKeyImage>, TxoServiceError>;
+
     /// Get a Txo from the wallet.
     ///
     /// # Arguments
@@ -189,6 +323,95 @@ pub trait TxoService {
         txo_id: &TxoID
     ) -> Result<TxoInfo, TxoServiceError>;
 
+    /// Get the lifecycle events of a Txo in the wallet, in order.
+    ///
+    /// # Arguments
+    ///
+    ///| Name     | Purpose                                  | Notes |
+    ///|----------|------------------------------------------|-------|
+    ///| `txo_id` | The TXO ID for which to get the history. |       |
+    ///
+    fn get_txo_history(
+        &self,
+        txo_id: &TxoID,
+    ) -> Result<Vec<TxoEvent>, TxoServiceError>;
+
+    /// Preview the Txos which would be selected as inputs to spend a value, without building a
+    /// transaction. The fee is paid in the same token as the value.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                  | Purpose                                                      | Notes                                                |
+    ///|-----------------------|--------------------------------------------------------------|------------------------------------------------------|
+    ///| `account_id`          | The account on which to perform this action.                 | Account must exist in the wallet.                    |
+    ///| `value`               | The value to spend, not including the fee.                   |                                                      |
+    ///| `token_id`            | The token id of the value to spend.                          |                                                      |
+    ///| `fee_value`           | The fee value the transaction would pay.                     | If not provided, uses the network fee for the token. |
+    ///| `max_spendable_value` | The maximum value of a Txo which may be selected as an input. |                                                      |
+    ///| `spend_subaddress`    | The b58 subaddress to select Txos from.                      | Optional. Defaults to all subaddresses.              |
+    ///
+    #[allow(clippy::too_many_arguments)]
+    fn preview_spendable_selection(
+        &self,
+        account_id: &AccountID,
+        value: u64,
+        token_id: TokenId,
+        fee_value: Option<u64>,
+        max_spendable_value: Option<u64>,
+        spend_subaddress: Option<String>,
+    ) -> Result<SpendableSelection, TxoServiceError>;
+
+    /// Select the Txos to spend a value the same way as `preview_spendable_selection`, and reserve
+    /// them so that they are not selected by other transactions. Returns the id of the
+    /// reservation, which a transaction can be built against. The reservation expires if the
+    /// transaction is not submitted within the reservation timeout.
+    ///
+    /// # Arguments
+    ///
+    ///| Name                  | Purpose                                                       | Notes                                                |
+    ///|-----------------------|---------------------------------------------------------------|------------------------------------------------------|
+    ///| `account_id`          | The account on which to perform this action.                  | Account must exist in the wallet.                    |
+    ///| `value`               | The value to spend, not including the fee.                    |                                                      |
+    ///| `token_id`            | The token id of the value to spend.                           |                                                      |
+    ///| `fee_value`           | The fee value the transaction would pay.                      | If not provided, uses the network fee for the token. |
+    ///| `max_spendable_value` | The maximum value of a Txo which may be selected as an input. |                                                      |
+    ///| `spend_subaddress`    | The b58 subaddress to select Txos from.                       | Optional. Defaults to all subaddresses.              |
+    ///
+    #[allow(clippy::too_many_arguments)]
+    fn reserve_inputs(
+        &self,
+        account_id: &AccountID,
+        value: u64,
+        token_id: TokenId,
+        fee_value: Option<u64>,
+        max_spendable_value: Option<u64>,
+        spend_subaddress: Option<String>,
+    ) -> Result<(String, SpendableSelection), TxoServiceError>;
+
+    /// Get the Txos reserved by `reserve_inputs`.
+    ///
+    /// # Arguments
+    ///
+    ///| Name             | Purpose                              | Notes                                           |
+    ///|------------------|--------------------------------------|-------------------------------------------------|
+    ///| `reservation_id` | The id returned by `reserve_inputs`. | Must not have expired or had its Txos released. |
+    ///
+    fn get_input_reservation(
+        &self,
+        reservation_id: &str,
+    ) -> Result<InputReservation, TxoServiceError>;
+
+    /// Release the Txos reserved by `reserve_inputs`, so that they may be selected for other
+    /// transactions.
+    ///
+    /// # Arguments
+    ///
+    ///| Name             | Purpose                              | Notes                                           |
+    ///|------------------|--------------------------------------|-------------------------------------------------|
+    ///| `reservation_id` | The id returned by `reserve_inputs`. | Must not have expired or had its Txos released. |
+    ///
+    fn release_input_reservation(&self, reservation_id: &str) -> Result<(), TxoServiceError>;
+
     /// Build a transaction that will split a txo into multiple output txos to the origin account.
     ///
     /// # Arguments
@@ -281,6 +504,103 @@ where
         Ok(txo_infos)
     }
 
+    fn list_stale_txos(
+        &self,
+        account_id: Option<String>,
+        address: Option<String>,
+        token_id: Option<u64>,
+        unspent_for_blocks: u64,
+        min_received_block_index: Option<u64>,
+        max_received_block_index: Option<u64>,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<TxoInfo>, TxoServiceError> {
+        // The last block a Txo can have been received in and still have sat for
+        // `unspent_for_blocks` blocks. If the ledger is not that long, no Txo has.
+        let num_blocks = self.ledger_db.num_blocks()?;
+        let stale_block_index = match unspent_for_blocks
+            .checked_add(1)
+            .and_then(|blocks| num_blocks.checked_sub(blocks))
+        {
+            Some(stale_block_index) => stale_block_index,
+            None => return Ok(Vec::new()),
+        };
+        let max_received_block_index = max_received_block_index
+            .map_or(stale_block_index, |index| index.min(stale_block_index));
+
+        self.list_txos(
+            account_id,
+            address,
+            Some(TxoStatus::Unspent),
+            token_id,
+            min_received_block_index,
+            Some(max_received_block_index),
+            offset,
+            limit,
+        )
+    }
+
+    fn list_change_txos(
+        &self,
+        account_id: &AccountID,
+        token_id: Option<u64>,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<TxoInfo>, TxoServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        Txo::list_change_for_account(&account_id.to_string(), token_id, offset, limit, conn)?
+            .into_iter()
+            .map(|txo| {
+                let status = txo.status(conn)?;
+                let memo = txo.memo(conn)?;
+                Ok(TxoInfo { txo, memo, status })
+            })
+            .collect()
+    }
+
+    fn list_txos_missing_required_memo(
+        &self,
+        account_id: &AccountID,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<TxoInfo>, TxoServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        Txo::list_missing_required_memo_for_account(&account_id.to_string(), offset, limit, conn)?
+            .into_iter()
+            .map(|txo| {
+                let status = txo.status(conn)?;
+                let memo = txo.memo(conn)?;
+                Ok(TxoInfo { txo, memo, status })
+            })
+            .collect()
+    }
+
+    fn list_spent_key_images(
+        &self,
+        account_id: &AccountID,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Vec<SpentKeyImage>, TxoServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        let account = Account::get(account_id, conn)?;
+        Ok(
+            Txo::list_spent_key_images(&account.id, offset, limit, conn)?
+                .into_iter()
+                .map(|(txo_id, key_image, spent_block_index)| SpentKeyImage {
+                    txo_id,
+                    key_image,
+                    spent_block_index,
+                })
+                .collect(),
+        )
+    }
+
     fn get_txo(&self, txo_id: &TxoID) -> Result<TxoInfo, TxoServiceError> {
         let mut pooled_conn = self.get_pooled_conn()?;
         let conn = pooled_conn.deref_mut();
@@ -290,6 +610,83 @@ where
         Ok(TxoInfo { txo, memo, status })
     }
 
+    fn get_txo_history(&self, txo_id: &TxoID) -> Result<Vec<TxoEvent>, TxoServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+        let txo = Txo::get(&txo_id.to_string(), conn)?;
+        Ok(txo.history(conn)?)
+    }
+
+    fn preview_spendable_selection(
+        &self,
+        account_id: &AccountID,
+        value: u64,
+        token_id: TokenId,
+        fee_value: Option<u64>,
+        max_spendable_value: Option<u64>,
+        spend_subaddress: Option<String>,
+    ) -> Result<SpendableSelection, TxoServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        self.select_spendable(
+            account_id,
+            value,
+            token_id,
+            fee_value,
+            max_spendable_value,
+            spend_subaddress.as_deref(),
+            conn,
+        )
+    }
+
+    fn reserve_inputs(
+        &self,
+        account_id: &AccountID,
+        value: u64,
+        token_id: TokenId,
+        fee_value: Option<u64>,
+        max_spendable_value: Option<u64>,
+        spend_subaddress: Option<String>,
+    ) -> Result<(String, SpendableSelection), TxoServiceError> {
+        let mut pooled_conn = self.get_pooled_conn()?;
+        let conn = pooled_conn.deref_mut();
+
+        // Select and reserve within one transaction, so that concurrent
+        // selections can not pick the same txos.
+        exclusive_transaction(conn, |conn| {
+            let selection = self.select_spendable(
+                account_id,
+                value,
+                token_id,
+                fee_value,
+                max_spendable_value,
+                spend_subaddress.as_deref(),
+                conn,
+            )?;
+            let reservation_id = self.txo_reservations.reserve_inputs(
+                &account_id.to_string(),
+                selection.txos.iter().map(|txo| txo.id.clone()).collect(),
+            );
+            Ok((reservation_id, selection))
+        })
+    }
+
+    fn get_input_reservation(
+        &self,
+        reservation_id: &str,
+    ) -> Result<InputReservation, TxoServiceError> {
+        self.txo_reservations
+            .get_input_reservation(reservation_id)
+            .ok_or_else(|| TxoServiceError::InputReservationNotFound(reservation_id.to_string()))
+    }
+
+    fn release_input_reservation(&self, reservation_id: &str) -> Result<(), TxoServiceError> {
+        let reservation = self.get_input_reservation(reservation_id)?;
+        self.txo_reservations.release(&reservation.txo_ids);
+        Ok(())
+    }
+
     async fn split_txo(
         &self,
         txo_id: &TxoID,
@@ -344,7 +741,67 @@ where
 
         let account = Account::get(&AccountID(account_id_hex), conn)?;
 
-        Ok(unsigned_transaction.sign(&account).await?)
+        Ok(unsigned_transaction.sign(&account, None).await?)
+    }
+}
+
+impl<T, FPR> WalletService<T, FPR>
+where
+    T: BlockchainConnection + UserTxConnection + 'static,
+    FPR: FogPubkeyResolver + Send + Sync + 'static,
+{
+    /// Select the inputs to spend a value and pay the fee in the same token.
+    #[allow(clippy::too_many_arguments)]
+    fn select_spendable(
+        &self,
+        account_id: &AccountID,
+        value: u64,
+        token_id: TokenId,
+        fee_value: Option<u64>,
+        max_spendable_value: Option<u64>,
+        spend_subaddress: Option<&str>,
+        conn: Conn,
+    ) -> Result<SpendableSelection, TxoServiceError> {
+        let fee_value = match fee_value {
+            Some(fee_value) => fee_value,
+            None => self
+                .get_network_fees()?
+                .get_fee_for_token(&token_id)
+                .ok_or(TxoServiceError::DefaultFeeNotFoundForToken(token_id))?,
+        };
+        let max_received_block_index = self.max_spendable_received_block_index()?;
+
+        let account = Account::get(account_id, conn)?;
+        if let Some(spend_subaddress) = spend_subaddress {
+            let assigned_subaddress = AssignedSubaddress::get(spend_subaddress, conn)?;
+            if assigned_subaddress.account_id != account.id {
+                return Err(TxoServiceError::InvalidQuery(format!(
+                    "Subaddress {spend_subaddress} does not belong to account {account_id}"
+                )));
+            }
+        }
+
+        // Select the inputs the same way building a transaction would, skipping
+        // the inputs reserved by proposals which have not been submitted.
+        let txos = Txo::select_spendable_txos_for_value(
+            &account.id,
+            value as u128 + fee_value as u128,
+            max_spendable_value,
+            spend_subaddress,
+            *token_id,
+            fee_value,
+            &self.txo_reservations.reserved_txo_ids(),
+            max_received_block_index,
+            conn,
+        )?;
+        let total_value = txos.iter().map(|txo| (txo.value as u64) as u128).sum();
+
+        Ok(SpendableSelection {
+            txos,
+            total_value,
+            fee_value,
+            token_id,
+        })
     }
 }
 
@@ -352,7 +809,7 @@ where
 mod tests {
     use super::*;
     use crate::{
-        db::account::AccountID,
+        db::{account::AccountID, txo::TxoEventType},
         service::{
             account::AccountService, balance::BalanceService, transaction::TransactionService,
         },
@@ -363,7 +820,7 @@ mod tests {
         util::b58::b58_encode_public_address,
     };
     use mc_account_keys::{AccountKey, PublicAddress};
-    use mc_common::logger::{async_test_with_logger, Logger};
+    use mc_common::logger::{async_test_with_logger, test_with_logger, Logger};
     use mc_rand::RngCore;
     use mc_transaction_core::{ring_signature::KeyImage, tokens::Mob, Token};
     use rand::{rngs::StdRng, SeedableRng};
@@ -455,11 +912,13 @@ mod tests {
                 },
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
-        let _submitted = service
+        let (transaction_log, _, _) = service
             .submit_transaction(&tx_proposal, None, Some(alice.id.clone()))
+            .unwrap()
             .unwrap();
 
         let pending: Vec<TxoInfo> = service
@@ -477,6 +936,33 @@ mod tests {
         assert_eq!(pending.len(), 1);
         assert_eq!(pending[0].txo.value, 100000000000000);
 
+        // The spent txo was received, then used as an input of the transaction.
+        let history = service
+            .get_txo_history(&TxoID(pending[0].txo.id.clone()))
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[0],
+            TxoEvent {
+                event_type: TxoEventType::Received,
+                block_index: Some(12),
+                transaction_log_id: None,
+            }
+        );
+        assert_eq!(history[1].event_type, TxoEventType::UsedAsInput);
+        assert_eq!(
+            history[1].transaction_log_id,
+            Some(transaction_log.id.clone())
+        );
+
+        // The change was created by the transaction, but not yet received.
+        let history = service
+            .get_txo_history(&TxoID::from(&tx_proposal.change_txos[0].tx_out))
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].event_type, TxoEventType::Created);
+        assert_eq!(history[0].transaction_log_id, Some(transaction_log.id));
+
         // Our balance should reflect the various statuses of our txos
         let balance = service
             .get_balance_for_account(&AccountID(alice.id))
@@ -489,4 +975,132 @@ mod tests {
         assert_eq!(balance_pmob.spent, 0);
         assert_eq!(balance_pmob.orphaned, 0);
     }
+
+    #[test_with_logger]
+    fn test_preview_spendable_selection(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+        for value in [100 * MOB, 50 * MOB] {
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![alice_public_address.clone()],
+                value,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        // The smallest txo covering the value and fee is selected.
+        let selection = service
+            .preview_spendable_selection(&alice_account_id, 30 * MOB, Mob::ID, None, None, None)
+            .unwrap();
+        assert_eq!(selection.txos.len(), 1);
+        assert_eq!(selection.total_value, 50 * MOB as u128);
+        assert_eq!(selection.fee_value, Mob::MINIMUM_FEE);
+        assert_eq!(selection.token_id, Mob::ID);
+
+        // Both txos are needed to cover a larger value.
+        let selection = service
+            .preview_spendable_selection(&alice_account_id, 120 * MOB, Mob::ID, Some(1), None, None)
+            .unwrap();
+        assert_eq!(selection.txos.len(), 2);
+        assert_eq!(selection.total_value, 150 * MOB as u128);
+        assert_eq!(selection.fee_value, 1);
+
+        // Nothing is reserved or marked pending by a preview.
+        let balance = service.get_balance_for_account(&alice_account_id).unwrap();
+        assert_eq!(balance.get(&Mob::ID).unwrap().unspent, 150 * MOB as u128);
+
+        assert!(service
+            .preview_spendable_selection(&alice_account_id, 200 * MOB, Mob::ID, None, None, None)
+            .is_err());
+    }
+
+    #[test_with_logger]
+    fn test_reserve_inputs(logger: Logger) {
+        let mut rng: StdRng = SeedableRng::from_seed([20u8; 32]);
+
+        let known_recipients: Vec<PublicAddress> = Vec::new();
+        let mut ledger_db = get_test_ledger(5, &known_recipients, 12, &mut rng);
+
+        let service = setup_wallet_service(ledger_db.clone(), None, logger.clone());
+        let alice = service
+            .create_account(
+                Some("Alice's Main Account".to_string()),
+                "".to_string(),
+                "".to_string(),
+                false,
+            )
+            .unwrap();
+        let alice_account_key: AccountKey = mc_util_serial::decode(&alice.account_key).unwrap();
+        let alice_account_id = AccountID::from(&alice_account_key);
+        let alice_public_address = alice_account_key.default_subaddress();
+        for value in [100 * MOB, 50 * MOB] {
+            add_block_to_ledger_db(
+                &mut ledger_db,
+                &vec![alice_public_address.clone()],
+                value,
+                &[KeyImage::from(rng.next_u64())],
+                &mut rng,
+            );
+        }
+        manually_sync_account(
+            &ledger_db,
+            service.wallet_db.as_ref().unwrap(),
+            &alice_account_id,
+            &logger,
+        );
+
+        let (reservation_id, selection) = service
+            .reserve_inputs(&alice_account_id, 30 * MOB, Mob::ID, None, None, None)
+            .unwrap();
+        assert_eq!(selection.total_value, 50 * MOB as u128);
+
+        let reservation = service.get_input_reservation(&reservation_id).unwrap();
+        assert_eq!(reservation.account_id, alice_account_id.to_string());
+        assert_eq!(reservation.txo_ids, vec![selection.txos[0].id.clone()]);
+
+        // The reserved txo is not selected again.
+        let selection = service
+            .preview_spendable_selection(&alice_account_id, 30 * MOB, Mob::ID, None, None, None)
+            .unwrap();
+        assert_eq!(selection.total_value, 100 * MOB as u128);
+        assert!(service
+            .reserve_inputs(&alice_account_id, 120 * MOB, Mob::ID, None, None, None)
+            .is_err());
+
+        assert!(matches!(
+            service.get_input_reservation("unknown"),
+            Err(TxoServiceError::InputReservationNotFound(_))
+        ));
+
+        // Releasing the reservation makes its txo selectable again.
+        service.release_input_reservation(&reservation_id).unwrap();
+        assert!(service.get_input_reservation(&reservation_id).is_err());
+        let selection = service
+            .preview_spendable_selection(&alice_account_id, 120 * MOB, Mob::ID, None, None, None)
+            .unwrap();
+        assert_eq!(selection.total_value, 150 * MOB as u128);
+    }
 }
@@ -0,0 +1,229 @@
+// Copyright (c) 2020-2023 MobileCoin Inc.
+
+//! In-memory reservations of txos selected as inputs to built transactions.
+//!
+//! Between building a transaction and submitting it, its inputs are not yet
+//! recorded as pending, so a concurrent build for the same account could
+//! select the same txos. Reserving them excludes them from automatic input
+//! selection, and rejects them as explicitly given inputs, until the
+//! transaction is submitted, or the reservation expires because the proposal
+//! was abandoned.
+//!
+//! Txos can also be reserved ahead of building with `reserve_inputs`, which
+//! returns a reservation id that a transaction can later be built against.
+
+use rand::Rng;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// How long txos remain reserved for a built transaction which has not been
+/// submitted.
+pub const TXO_RESERVATION_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+pub struct TxoReservations {
+    /// How long a reservation lasts before it is released.
+    timeout: Duration,
+
+    /// Reserved txo ids, along with the time at which each was reserved.
+    reserved: Mutex<HashMap<String, Instant>>,
+
+    /// Inputs reserved ahead of building a transaction, by reservation id.
+    input_reservations: Mutex<HashMap<String, InputReservation>>,
+}
+
+/// Txos reserved together as the inputs of a transaction yet to be built.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputReservation {
+    /// The account the txos belong to.
+    pub account_id: String,
+
+    /// The reserved txo ids.
+    pub txo_ids: Vec<String>,
+
+    /// When the txos were reserved.
+    pub reserved_at: Instant,
+}
+
+impl Default for TxoReservations {
+    fn default() -> Self {
+        Self::new(TXO_RESERVATION_TIMEOUT)
+    }
+}
+
+impl TxoReservations {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            reserved: Mutex::new(HashMap::new()),
+            input_reservations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The ids of all currently reserved txos. Expired reservations are
+    /// released.
+    pub fn reserved_txo_ids(&self) -> Vec<String> {
+        let mut reserved = self.reserved.lock().expect("mutex poisoned");
+        let timeout = self.timeout;
+        reserved.retain(|_, reserved_at| reserved_at.elapsed() < timeout);
+        reserved.keys().cloned().collect()
+    }
+
+    /// Reserve txos, refreshing the reservation of any already reserved.
+    pub fn reserve(&self, txo_ids: impl IntoIterator<Item = String>) {
+        let mut reserved = self.reserved.lock().expect("mutex poisoned");
+        let now = Instant::now();
+        for txo_id in txo_ids {
+            reserved.insert(txo_id, now);
+        }
+    }
+
+    /// Check that none of the txos are reserved, other than as the inputs
+    /// reserved under `reservation_id`. Returns the id of the first txo which
+    /// is reserved by another transaction.
+    pub fn check_unreserved(
+        &self,
+        txo_ids: &[String],
+        reservation_id: Option<&str>,
+    ) -> Result<(), String> {
+        let held_txo_ids = reservation_id
+            .and_then(|reservation_id| self.get_input_reservation(reservation_id))
+            .map(|reservation| reservation.txo_ids)
+            .unwrap_or_default();
+        let reserved = self.reserved_txo_ids();
+
+        match txo_ids
+            .iter()
+            .find(|txo_id| reserved.contains(txo_id) && !held_txo_ids.contains(txo_id))
+        {
+            Some(txo_id) => Err(txo_id.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Release the reservations of txos, along with any input reservation
+    /// which includes one of them.
+    pub fn release<'a>(&self, txo_ids: impl IntoIterator<Item = &'a String>) {
+        let mut reserved = self.reserved.lock().expect("mutex poisoned");
+        let mut input_reservations = self.input_reservations.lock().expect("mutex poisoned");
+        for txo_id in txo_ids {
+            reserved.remove(txo_id);
+            input_reservations.retain(|_, reservation| !reservation.txo_ids.contains(txo_id));
+        }
+    }
+
+    /// Reserve txos as the inputs of a transaction to be built later,
+    /// returning the id of the reservation.
+    pub fn reserve_inputs(&self, account_id: &str, txo_ids: Vec<String>) -> String {
+        self.reserve(txo_ids.clone());
+
+        let reservation_id = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+        self.input_reservations
+            .lock()
+            .expect("mutex poisoned")
+            .insert(
+                reservation_id.clone(),
+                InputReservation {
+                    account_id: account_id.to_string(),
+                    txo_ids,
+                    reserved_at: Instant::now(),
+                },
+            );
+        reservation_id
+    }
+
+    /// The input reservation with an id, if it exists and has not expired.
+    pub fn get_input_reservation(&self, reservation_id: &str) -> Option<InputReservation> {
+        let mut input_reservations = self.input_reservations.lock().expect("mutex poisoned");
+        let timeout = self.timeout;
+        input_reservations.retain(|_, reservation| reservation.reserved_at.elapsed() < timeout);
+        input_reservations.get(reservation_id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_and_release() {
+        let reservations = TxoReservations::default();
+        reservations.reserve(vec!["a".to_string(), "b".to_string()]);
+
+        let mut reserved = reservations.reserved_txo_ids();
+        reserved.sort();
+        assert_eq!(reserved, vec!["a".to_string(), "b".to_string()]);
+
+        reservations.release(&["a".to_string()]);
+        assert_eq!(reservations.reserved_txo_ids(), vec!["b".to_string()]);
+    }
+
+    #[test]
+    fn test_check_unreserved() {
+        let reservations = TxoReservations::default();
+        reservations.reserve(vec!["a".to_string()]);
+        let reservation_id = reservations.reserve_inputs("account", vec!["b".to_string()]);
+
+        assert_eq!(
+            reservations.check_unreserved(&["c".to_string()], None),
+            Ok(())
+        );
+        assert_eq!(
+            reservations.check_unreserved(&["c".to_string(), "a".to_string()], None),
+            Err("a".to_string())
+        );
+        assert_eq!(
+            reservations.check_unreserved(&["b".to_string()], None),
+            Err("b".to_string())
+        );
+
+        // The inputs of a reservation may be spent by building against it.
+        assert_eq!(
+            reservations.check_unreserved(&["b".to_string()], Some(&reservation_id)),
+            Ok(())
+        );
+        assert_eq!(
+            reservations.check_unreserved(&["a".to_string()], Some(&reservation_id)),
+            Err("a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_reservations_expire() {
+        let reservations = TxoReservations::new(Duration::from_millis(50));
+        reservations.reserve(vec!["a".to_string()]);
+        assert_eq!(reservations.reserved_txo_ids(), vec!["a".to_string()]);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(reservations.reserved_txo_ids().is_empty());
+    }
+
+    #[test]
+    fn test_input_reservations() {
+        let reservations = TxoReservations::new(Duration::from_millis(50));
+        let reservation_id =
+            reservations.reserve_inputs("account", vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(reservations.reserved_txo_ids().len(), 2);
+
+        let reservation = reservations.get_input_reservation(&reservation_id).unwrap();
+        assert_eq!(reservation.account_id, "account");
+        assert_eq!(reservation.txo_ids, vec!["a".to_string(), "b".to_string()]);
+        assert!(reservations.get_input_reservation("unknown").is_none());
+
+        // Releasing any of the reserved txos releases the input reservation.
+        reservations.release(&["a".to_string()]);
+        assert!(reservations
+            .get_input_reservation(&reservation_id)
+            .is_none());
+
+        // Input reservations expire along with the txos they reserve.
+        let reservation_id = reservations.reserve_inputs("account", vec!["c".to_string()]);
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(reservations
+            .get_input_reservation(&reservation_id)
+            .is_none());
+        assert!(reservations.reserved_txo_ids().is_empty());
+    }
+}
@@ -3,11 +3,18 @@
 //! The Wallet Service for interacting with the wallet.
 
 use crate::{
-    config::{NetworkConfig, WebhookConfig},
-    db::{account::AccountID, WalletDb, WalletDbError},
+    config::{NetworkConfig, PollInterval, TokenMetadataMap, WebhookConfig},
+    consensus_ledger_sync::ReloadableTransactionsFetcher,
+    db::{
+        account::AccountID, authenticated_sender_memo::SenderMemoFilter, WalletDb, WalletDbError,
+    },
     service::{
+        block_info_cache::BlockInfoCache,
+        submission_tracker::SubmissionTracker,
         sync::SyncThread,
+        sync_progress::SyncProgress,
         t3_sync::{T3Config, T3SyncThread},
+        txo_reservation::TxoReservations,
         webhook::WebhookThread,
     },
 };
@@ -20,7 +27,7 @@ use mc_connection::{
     BlockchainConnection, ConnectionManager as McConnectionManager, UserTxConnection,
 };
 use mc_fog_report_validation::FogPubkeyResolver;
-use mc_ledger_db::LedgerDB;
+use mc_ledger_db::{Ledger, LedgerDB};
 use mc_ledger_sync::PollingNetworkState;
 use mc_rand::rand_core::RngCore;
 use mc_util_uri::FogUri;
@@ -57,13 +64,28 @@ pub struct WalletService<
     /// Representation of the current network state.
     pub network_state: Arc<RwLock<PollingNetworkState<T>>>,
 
+    /// Poll interval shared with the ledger sync thread.
+    pub poll_interval: PollInterval,
+
+    /// The latest block info from the network, refreshed by the ledger sync
+    /// thread.
+    pub block_info_cache: BlockInfoCache,
+
+    /// The transactions fetcher used by the consensus ledger sync thread, or
+    /// None if the ledger is not synced from consensus.
+    pub transactions_fetcher: Option<ReloadableTransactionsFetcher>,
+
     /// Fog resolver factory to obtain the public key of the ingest enclave from
     /// a fog address.
     #[allow(clippy::type_complexity)]
     pub fog_resolver_factory: Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync>,
 
-    /// Background ledger sync thread.
-    _sync_thread: Option<SyncThread>,
+    /// How quickly the sync thread is scanning blocks for each account.
+    pub sync_progress: SyncProgress,
+
+    /// Background ledger sync thread, shared with the shutdown fairing which
+    /// stops it.
+    pub(crate) sync_thread: Option<Arc<SyncThread>>,
 
     /// Background T3 sync thread.
     _t3_sync_thread: Option<T3SyncThread>,
@@ -75,9 +97,29 @@ pub struct WalletService<
     /// selection.
     pub submit_node_offset: Arc<AtomicUsize>,
 
+    /// Txos selected as inputs to transactions which have been built but not
+    /// yet submitted.
+    pub txo_reservations: TxoReservations,
+
+    /// Transaction submissions in progress, which shutdown waits for.
+    pub submission_tracker: SubmissionTracker,
+
     /// Whether the service should run in offline mode.
     pub offline: bool,
 
+    /// Whether built transactions should add their outputs in a deterministic
+    /// order, rather than in the order the recipients were given.
+    pub deterministic_output_order: bool,
+
+    /// The number of confirmations a Txo needs before it is spendable.
+    pub min_confirmations: u64,
+
+    /// The most accounts the wallet may hold, if limited.
+    pub max_accounts: Option<u64>,
+
+    /// Display metadata for tokens, included in balance and txo responses.
+    pub token_metadata: TokenMetadataMap,
+
     /// Logger.
     pub logger: Logger,
 }
@@ -95,24 +137,35 @@ impl<
         peer_manager: McConnectionManager<T>,
         network_setup_config: NetworkConfig,
         network_state: Arc<RwLock<PollingNetworkState<T>>>,
+        poll_interval: PollInterval,
+        block_info_cache: BlockInfoCache,
+        transactions_fetcher: Option<ReloadableTransactionsFetcher>,
         fog_resolver_factory: Arc<dyn Fn(&[FogUri]) -> Result<FPR, String> + Send + Sync>,
         offline: bool,
+        deterministic_output_order: bool,
+        min_confirmations: u64,
+        sender_memo_filter: SenderMemoFilter,
+        max_accounts: Option<u64>,
+        token_metadata: TokenMetadataMap,
         t3_sync_config: T3Config,
         webhook_config: Option<WebhookConfig>,
         logger: Logger,
     ) -> Self {
+        let sync_progress = SyncProgress::default();
         let (sync_thread, webhook_thread) = if let Some(wallet_db) = wallet_db.clone() {
             log::info!(logger, "Starting Wallet TXO Sync Task Thread");
 
             let accounts_with_deposits = Arc::new(Mutex::new(HashMap::<AccountID, bool>::new()));
 
             (
-                Some(SyncThread::start(
+                Some(Arc::new(SyncThread::start(
                     ledger_db.clone(),
                     wallet_db,
                     accounts_with_deposits.clone(),
+                    sync_progress.clone(),
+                    sender_memo_filter,
                     logger.clone(),
-                )),
+                ))),
                 // As a companion to the account syncing, start the webhook syncing
                 // if configured
                 if let Some(wh_config) = webhook_config {
@@ -153,16 +206,40 @@ impl<
             peer_manager,
             network_setup_config,
             network_state,
+            poll_interval,
+            block_info_cache,
+            transactions_fetcher,
             fog_resolver_factory,
-            _sync_thread: sync_thread,
+            sync_progress,
+            sync_thread,
             _t3_sync_thread: t3_sync_thread,
             _webhook_thread: webhook_thread,
             submit_node_offset: Arc::new(AtomicUsize::new(rng.next_u64() as usize)),
+            txo_reservations: TxoReservations::default(),
+            submission_tracker: SubmissionTracker::default(),
             offline,
+            deterministic_output_order,
+            min_confirmations,
+            max_accounts,
+            token_metadata,
             logger,
         }
     }
 
+    /// The highest block index at which a Txo may have been received and still
+    /// have the minimum number of confirmations to be spendable, or None if
+    /// every received Txo is spendable.
+    pub fn max_spendable_received_block_index(&self) -> Result<Option<u64>, mc_ledger_db::Error> {
+        if self.min_confirmations == 0 {
+            return Ok(None);
+        }
+        Ok(Some(
+            self.ledger_db
+                .num_blocks()?
+                .saturating_sub(self.min_confirmations),
+        ))
+    }
+
     pub fn get_pooled_conn(
         &self,
     ) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, WalletDbError> {
@@ -1,10 +1,15 @@
 // Copyright (c) 2020-2021 MobileCoin Inc.
 
 //! Service for accessing the watcher database.
-use crate::{service::models::watcher::WatcherBlockInfo, WalletService};
+use crate::{
+    error::ErrorCode,
+    service::models::watcher::{WatcherBlockInfo, WatcherStatus},
+    WalletService,
+};
 use displaydoc::Display;
 use mc_connection::{BlockchainConnection, UserTxConnection};
 use mc_fog_report_validation::FogPubkeyResolver;
+use mc_ledger_db::Ledger;
 use mc_watcher::error::WatcherDBError;
 
 /// Errors for the Watcher Service.
@@ -12,6 +17,18 @@ use mc_watcher::error::WatcherDBError;
 pub enum WatcherServiceError {
     /// Error interacting with watcher database: {0}
     WatcherDb(WatcherDBError),
+
+    /// Error with LedgerDB: {0}
+    LedgerDB(mc_ledger_db::Error),
+}
+
+impl ErrorCode for WatcherServiceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::WatcherDb(..) => "watcher_db",
+            Self::LedgerDB(..) => "ledger_db",
+        }
+    }
 }
 
 impl From<WatcherDBError> for WatcherServiceError {
@@ -20,6 +37,12 @@ impl From<WatcherDBError> for WatcherServiceError {
     }
 }
 
+impl From<mc_ledger_db::Error> for WatcherServiceError {
+    fn from(src: mc_ledger_db::Error) -> Self {
+        Self::LedgerDB(src)
+    }
+}
+
 /// Trait defining the ways in which the service can interact with the watcher.
 #[rustfmt::skip]
 pub trait WatcherService {
@@ -36,6 +59,12 @@ pub trait WatcherService {
         &self,
         block_index: u64,
     ) -> Result<Option<WatcherBlockInfo>, WatcherServiceError>;
+
+    /// Get how far the watcher has synced, compared to the local ledger
+    ///
+    /// # Returns
+    /// * The watcher status, or None if no watcher is configured
+    fn get_watcher_status(&self) -> Result<Option<WatcherStatus>, WatcherServiceError>;
 }
 
 impl<T, FPR> WatcherService for WalletService<T, FPR>
@@ -62,4 +91,32 @@ where
             None => Ok(None),
         }
     }
+
+    fn get_watcher_status(&self) -> Result<Option<WatcherStatus>, WatcherServiceError> {
+        let watcher_db = match &self.watcher_db {
+            Some(watcher_db) => watcher_db,
+            None => return Ok(None),
+        };
+
+        let mut sources: Vec<_> = watcher_db.last_synced_blocks()?.into_iter().collect();
+        sources.sort();
+
+        // The watcher has a timestamp for a block once any source has synced its
+        // signature.
+        let highest_timestamped_block_index = sources
+            .iter()
+            .filter_map(|(_, last_synced)| *last_synced)
+            .max();
+        let num_blocks = self.ledger_db.num_blocks()?;
+        let num_blocks_behind = match highest_timestamped_block_index {
+            Some(block_index) => num_blocks.saturating_sub(block_index + 1),
+            None => num_blocks,
+        };
+
+        Ok(Some(WatcherStatus {
+            highest_timestamped_block_index,
+            num_blocks_behind,
+            sources,
+        }))
+    }
 }
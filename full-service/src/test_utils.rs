@@ -3,9 +3,10 @@ use crate::service::t3_sync::T3Config;
 use crate::config::WebhookConfig;
 #[cfg(test)]
 use crate::{
-    config::NetworkConfig,
+    config::{NetworkConfig, PollInterval, TokenMetadataMap},
     db::{
         account::{AccountID, AccountModel},
+        authenticated_sender_memo::SenderMemoFilter,
         models::{Account, TransactionLog, Txo},
         transaction_log::TransactionLogModel,
         txo::TxoModel,
@@ -13,6 +14,7 @@ use crate::{
     },
     error::SyncError,
     service::{
+        block_info_cache::BlockInfoCache,
         models::tx_proposal::{TxProposal, UnsignedTxProposal},
         sync::sync_account_next_chunk,
         transaction::TransactionMemo,
@@ -352,6 +354,7 @@ pub fn manually_sync_account(
             ledger_db,
             wallet_db.get_pooled_conn().unwrap().deref_mut(),
             &account_id.to_string(),
+            &SenderMemoFilter::default(),
             logger,
         ) {
             Ok(_) => {}
@@ -444,6 +447,7 @@ pub fn create_test_received_txo(
         amount,
         received_block_index,
         &AccountID::from(account_key).to_string(),
+        &SenderMemoFilter::default(),
         wallet_db.get_pooled_conn().unwrap().deref_mut(),
     )
     .unwrap();
@@ -485,7 +489,7 @@ pub async fn create_test_minted_and_change_txos(
             conn,
         )
         .unwrap();
-    let tx_proposal = unsigned_tx_proposal.sign(&account).await.unwrap();
+    let tx_proposal = unsigned_tx_proposal.sign(&account, None).await.unwrap();
 
     // There should be 2 outputs, one to dest and one change
     assert_eq!(tx_proposal.tx.prefix.outputs.len(), 2);
@@ -695,8 +699,16 @@ fn setup_wallet_service_impl(
         peer_manager,
         network_setup_config,
         network_state,
+        PollInterval::default(),
+        BlockInfoCache::default(),
+        None,
         get_resolver_factory(&mut rng).unwrap(),
         offline,
+        false,
+        0,
+        SenderMemoFilter::default(),
+        None,
+        TokenMetadataMap::default(),
         T3Config::default(),
         webhook_config,
         logger,
@@ -1,3 +1,4 @@
+use crate::error::ErrorCode;
 use displaydoc::Display;
 
 #[derive(Display, Debug, PartialEq)]
@@ -27,6 +28,23 @@ pub enum B58Error {
     PrintableWrapper(mc_api::display::Error),
 }
 
+impl ErrorCode for B58Error {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::NotPrintableWrapper => "not_printable_wrapper",
+            Self::NotPublicAddress => "not_public_address",
+            Self::NotPaymentRequest => "not_payment_request",
+            Self::NotTransferPayload => "not_transfer_payload",
+            Self::TransferPayloadRequiresSingleEntropy => {
+                "transfer_payload_requires_single_entropy"
+            }
+            Self::InvalidEntropy => "invalid_entropy",
+            Self::ProtoConversion(..) => "proto_conversion",
+            Self::PrintableWrapper(..) => "printable_wrapper",
+        }
+    }
+}
+
 impl From<mc_api::ConversionError> for B58Error {
     fn from(src: mc_api::ConversionError) -> Self {
         Self::ProtoConversion(src)
@@ -15,7 +15,8 @@ use std::convert::TryFrom;
 
 pub struct DecodedPaymentRequest {
     pub public_address: PublicAddress,
-    pub value: u64,
+    /// The value requested, or None if the payer chooses the amount.
+    pub value: Option<u64>,
     pub token_id: u64,
     pub memo: String,
 }
@@ -67,6 +68,8 @@ pub fn b58_decode_public_address(public_address_b58_code: &str) -> Result<Public
     Ok(PublicAddress::try_from(public_address_proto)?)
 }
 
+/// Encode a payment request. A value of 0 requests an open amount, for the
+/// payer to choose.
 pub fn b58_encode_payment_request(
     public_address: &PublicAddress,
     amount: &Amount,
@@ -95,7 +98,10 @@ pub fn b58_decode_payment_request(
     };
 
     let public_address = PublicAddress::try_from(payment_request_message.get_public_address())?;
-    let value = payment_request_message.get_value();
+    let value = match payment_request_message.get_value() {
+        0 => None,
+        value => Some(value),
+    };
     let token_id = payment_request_message.get_token_id();
     let memo = payment_request_message.get_memo().to_string();
 
@@ -97,11 +97,28 @@ mod tests {
         let decoded = b58_decode_payment_request(encoded).unwrap();
 
         assert_eq!(decoded.public_address, public_address);
-        assert_eq!(decoded.value, 1_000_000_000_000);
+        assert_eq!(decoded.value, Some(1_000_000_000_000));
         assert_eq!(decoded.token_id, Mob::ID);
         assert_eq!(decoded.memo, "This is a memo".to_string());
     }
 
+    #[test]
+    fn decoding_open_amount_payment_request_succeeds() {
+        let mut rng: StdRng = SeedableRng::from_seed([91u8; 32]);
+        let public_address = get_public_address(&mut rng);
+        let encoded = b58_encode_payment_request(
+            &public_address,
+            &Amount::new(0, Mob::ID),
+            "Pay what you like".to_string(),
+        )
+        .unwrap();
+        let decoded = b58_decode_payment_request(encoded).unwrap();
+
+        assert_eq!(decoded.public_address, public_address);
+        assert_eq!(decoded.value, None);
+        assert_eq!(decoded.token_id, Mob::ID);
+    }
+
     #[test]
     fn decoding_transfer_payload_succeeds() {
         let mut rng: StdRng = SeedableRng::from_seed([91u8; 32]);
@@ -0,0 +1,220 @@
+// Copyright (c) 2020-2024 MobileCoin Inc.
+
+//! Passphrase encryption of exported account secrets.
+//!
+//! The secrets are encrypted with AES-256-GCM, under a key derived from the
+//! passphrase and a random salt with PBKDF2-HMAC-SHA256. The salt, nonce and
+//! number of rounds are stored alongside the ciphertext, so that the secrets
+//! can be decrypted with only the passphrase.
+
+use crate::error::ErrorCode;
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use displaydoc::Display;
+use hmac::Hmac;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::ops::RangeInclusive;
+use zeroize::Zeroize;
+
+/// The current version of the encrypted secrets format.
+pub const ENCRYPTED_SECRETS_VERSION: u32 = 1;
+
+/// The number of PBKDF2 rounds used when encrypting.
+pub const PBKDF2_ROUNDS: u32 = 600_000;
+
+/// The numbers of PBKDF2 rounds accepted when decrypting. The rounds are read
+/// from the encrypted secrets, so they are bounded to keep a single request
+/// from spending hours deriving the key.
+pub const ACCEPTED_PBKDF2_ROUNDS: RangeInclusive<u32> = PBKDF2_ROUNDS..=10 * PBKDF2_ROUNDS;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+#[derive(Display, Debug)]
+pub enum EncryptedSecretsError {
+    /// The passphrase must not be empty
+    EmptyPassphrase,
+
+    /// Unsupported encrypted secrets version: {0}
+    UnsupportedVersion(u32),
+
+    /// Error decoding hex: {0}
+    Hex(hex::FromHexError),
+
+    /// Invalid salt or nonce length
+    InvalidLength,
+
+    /// Unsupported number of key derivation rounds: {0}
+    InvalidKdfRounds(u32),
+
+    /// Failed encrypting the secrets
+    Encryption,
+
+    /// Failed decrypting the secrets, the passphrase may be wrong
+    Decryption,
+}
+
+impl ErrorCode for EncryptedSecretsError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::EmptyPassphrase => "empty_passphrase",
+            Self::UnsupportedVersion(..) => "unsupported_version",
+            Self::Hex(..) => "hex",
+            Self::InvalidLength => "invalid_length",
+            Self::InvalidKdfRounds(..) => "invalid_kdf_rounds",
+            Self::Encryption => "encryption",
+            Self::Decryption => "decryption",
+        }
+    }
+}
+
+impl From<hex::FromHexError> for EncryptedSecretsError {
+    fn from(src: hex::FromHexError) -> Self {
+        Self::Hex(src)
+    }
+}
+
+/// Secrets encrypted with a passphrase, with the hex encoded parameters needed
+/// to decrypt them.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct EncryptedSecrets {
+    pub version: u32,
+    pub kdf_rounds: u32,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl EncryptedSecrets {
+    /// Encrypt the secrets with a passphrase.
+    pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Self, EncryptedSecretsError> {
+        Self::encrypt_with_rounds(plaintext, passphrase, PBKDF2_ROUNDS)
+    }
+
+    fn encrypt_with_rounds(
+        plaintext: &[u8],
+        passphrase: &str,
+        kdf_rounds: u32,
+    ) -> Result<Self, EncryptedSecretsError> {
+        if passphrase.is_empty() {
+            return Err(EncryptedSecretsError::EmptyPassphrase);
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut salt = [0u8; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill_bytes(&mut nonce);
+
+        let cipher = cipher(passphrase, &salt, kdf_rounds);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| EncryptedSecretsError::Encryption)?;
+
+        Ok(Self {
+            version: ENCRYPTED_SECRETS_VERSION,
+            kdf_rounds,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Decrypt the secrets with the passphrase they were encrypted with.
+    pub fn decrypt(&self, passphrase: &str) -> Result<Vec<u8>, EncryptedSecretsError> {
+        self.decrypt_with_accepted_rounds(passphrase, ACCEPTED_PBKDF2_ROUNDS)
+    }
+
+    fn decrypt_with_accepted_rounds(
+        &self,
+        passphrase: &str,
+        accepted_rounds: RangeInclusive<u32>,
+    ) -> Result<Vec<u8>, EncryptedSecretsError> {
+        if self.version != ENCRYPTED_SECRETS_VERSION {
+            return Err(EncryptedSecretsError::UnsupportedVersion(self.version));
+        }
+        if !accepted_rounds.contains(&self.kdf_rounds) {
+            return Err(EncryptedSecretsError::InvalidKdfRounds(self.kdf_rounds));
+        }
+        let salt = hex::decode(&self.salt)?;
+        let nonce = hex::decode(&self.nonce)?;
+        if salt.len() != SALT_LEN || nonce.len() != NONCE_LEN {
+            return Err(EncryptedSecretsError::InvalidLength);
+        }
+        let ciphertext = hex::decode(&self.ciphertext)?;
+
+        let cipher = cipher(passphrase, &salt, self.kdf_rounds);
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| EncryptedSecretsError::Decryption)
+    }
+}
+
+/// Derive the cipher for a passphrase and salt.
+fn cipher(passphrase: &str, salt: &[u8], rounds: u32) -> Aes256Gcm {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase.as_bytes(), salt, rounds, &mut key);
+    let cipher = Aes256Gcm::new(&key.into());
+    key.zeroize();
+    cipher
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches::assert_matches;
+
+    #[test]
+    fn test_encrypted_secrets_round_trip() {
+        // Few rounds, to keep the test fast.
+        let secrets = br#"{"mnemonic":"some secret words"}"#;
+        let encrypted = EncryptedSecrets::encrypt_with_rounds(secrets, "hunter2", 10).unwrap();
+        assert!(!encrypted.ciphertext.contains(&hex::encode("secret")));
+
+        // Survives serialization, as when written to and read from a file.
+        let encrypted: EncryptedSecrets =
+            serde_json::from_str(&serde_json::to_string(&encrypted).unwrap()).unwrap();
+        assert_eq!(
+            encrypted
+                .decrypt_with_accepted_rounds("hunter2", 1..=10)
+                .unwrap(),
+            secrets.to_vec()
+        );
+
+        assert_matches!(
+            encrypted.decrypt_with_accepted_rounds("hunter3", 1..=10),
+            Err(EncryptedSecretsError::Decryption)
+        );
+        assert_matches!(
+            EncryptedSecrets::encrypt(secrets, ""),
+            Err(EncryptedSecretsError::EmptyPassphrase)
+        );
+
+        // Each encryption uses a fresh salt and nonce.
+        let reencrypted = EncryptedSecrets::encrypt_with_rounds(secrets, "hunter2", 10).unwrap();
+        assert_ne!(encrypted.salt, reencrypted.salt);
+        assert_ne!(encrypted.ciphertext, reencrypted.ciphertext);
+    }
+
+    #[test]
+    fn test_decrypt_rejects_unbounded_kdf_rounds() {
+        let encrypted = EncryptedSecrets::encrypt_with_rounds(b"secrets", "hunter2", 10).unwrap();
+        assert_matches!(
+            encrypted.decrypt("hunter2"),
+            Err(EncryptedSecretsError::InvalidKdfRounds(10))
+        );
+
+        let encrypted = EncryptedSecrets {
+            kdf_rounds: u32::MAX,
+            ..encrypted
+        };
+        assert_matches!(
+            encrypted.decrypt("hunter2"),
+            Err(EncryptedSecretsError::InvalidKdfRounds(u32::MAX))
+        );
+    }
+}
@@ -1,25 +1,147 @@
 // Copyright (c) 2018-2023 MobileCoin, Inc.
 
 //! Ledger syncing via the Validator Service.
+//!
+//! When a watcher db is configured, the block signatures the validator returns
+//! alongside each block are also recorded in it, with the validator as the
+//! watcher's only source, so that block timestamps are available.
 
+use crate::{config::PollInterval, service::block_info_cache::BlockInfoCache};
 use mc_blockchain_types::BlockData;
 use mc_common::logger::{log, Logger};
+use mc_connection::ConnectionManager;
 use mc_ledger_db::{Ledger, LedgerDB};
 use mc_ledger_sync::{NetworkState, PollingNetworkState};
+use mc_util_uri::ConnectionUri;
 use mc_validator_api::ValidatorUri;
 use mc_validator_connection::ValidatorConnection;
+use mc_watcher::{error::WatcherDBError, watcher_db::WatcherDB};
 use std::{
+    cmp::min,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, RwLock,
     },
     thread,
-    time::Duration,
 };
+use url::Url;
 
 /// The maximum number of blocks to try and retrieve in each iteration
 pub const MAX_BLOCKS_PER_SYNC_ITERATION: u32 = 1000;
 
+/// Fetch `num_blocks` blocks starting at `starting_block_index`, making one
+/// `fetch` call per `chunk_size` blocks so that wide ranges do not exceed the
+/// number of blocks the validator returns for a single request.
+///
+/// `fetch` is called with the index of the first block of each chunk and the
+/// number of blocks in it. Fetching stops early if a chunk comes back short,
+/// which means the end of the validator's ledger was reached.
+pub fn fetch_blocks_in_chunks<E>(
+    starting_block_index: u64,
+    num_blocks: u64,
+    chunk_size: u32,
+    mut fetch: impl FnMut(u64, u32) -> Result<Vec<BlockData>, E>,
+) -> Result<Vec<BlockData>, E> {
+    assert!(chunk_size > 0, "chunk_size must be positive");
+
+    let end_block_index = starting_block_index.saturating_add(num_blocks);
+    let mut blocks_data = Vec::new();
+    let mut block_index = starting_block_index;
+    while block_index < end_block_index {
+        let limit = min(end_block_index - block_index, chunk_size as u64) as u32;
+        let chunk = fetch(block_index, limit)?;
+        let chunk_len = chunk.len() as u64;
+        blocks_data.extend(chunk);
+        if chunk_len < limit as u64 {
+            break;
+        }
+        block_index += chunk_len;
+    }
+
+    Ok(blocks_data)
+}
+
+/// Records the block signatures fetched from the validator in a watcher db.
+struct ValidatorWatcherSync {
+    watcher_db: WatcherDB,
+
+    /// The watcher source the signatures are recorded under, which is the
+    /// validator's URI.
+    src_url: Url,
+}
+
+impl ValidatorWatcherSync {
+    /// The index of the next block the watcher needs.
+    fn next_block_index(&self) -> Result<u64, WatcherDBError> {
+        let last_synced = self.watcher_db.last_synced_blocks()?;
+        Ok(last_synced
+            .get(&self.src_url)
+            .copied()
+            .flatten()
+            .map_or(0, |block_index| block_index + 1))
+    }
+
+    /// Record the signatures of consecutive blocks, starting at the next block
+    /// the watcher needs.
+    fn add_blocks(&self, blocks_data: &[BlockData]) -> Result<(), WatcherDBError> {
+        for block_data in blocks_data {
+            let block_index = block_data.block().index;
+            match block_data.signature() {
+                // Blocks fetched from the validator do not come from an archive file.
+                Some(signature) => self.watcher_db.add_block_signature(
+                    &self.src_url,
+                    block_index,
+                    signature.clone(),
+                    String::new(),
+                )?,
+                // The origin block is not signed.
+                None => self
+                    .watcher_db
+                    .update_last_synced(&self.src_url, block_index)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetch and record signatures for blocks already in the ledger which the
+    /// watcher does not have yet, such as when the watcher db is added to an
+    /// existing ledger. Returns whether the watcher has caught up with the
+    /// ledger.
+    fn backfill(
+        &self,
+        ledger_db: &LedgerDB,
+        validator_conn: &ValidatorConnection,
+        chunk_size: u32,
+        logger: &Logger,
+    ) -> Result<bool, String> {
+        let next_block_index = self.next_block_index().map_err(|err| err.to_string())?;
+        let num_blocks = ledger_db.num_blocks().map_err(|err| err.to_string())?;
+        if next_block_index >= num_blocks {
+            return Ok(true);
+        }
+
+        let limit = min(
+            num_blocks - next_block_index,
+            MAX_BLOCKS_PER_SYNC_ITERATION as u64,
+        );
+        log::debug!(
+            logger,
+            "Watcher is behind the ledger, fetching signatures for {} blocks starting at {}",
+            limit,
+            next_block_index
+        );
+        let blocks_data =
+            fetch_blocks_in_chunks(next_block_index, limit, chunk_size, |start, limit| {
+                validator_conn.get_blocks_data(start, limit)
+            })
+            .map_err(|err| format!("{err:?}"))?;
+        self.add_blocks(&blocks_data)
+            .map_err(|err| err.to_string())?;
+
+        Ok(next_block_index + blocks_data.len() as u64 >= num_blocks)
+    }
+}
+
 pub struct ValidatorLedgerSyncThread {
     join_handle: Option<thread::JoinHandle<()>>,
     stop_requested: Arc<AtomicBool>,
@@ -29,13 +151,21 @@ impl ValidatorLedgerSyncThread {
     pub fn new(
         validator_uri: &ValidatorUri,
         chain_id: String,
-        poll_interval: Duration,
+        poll_interval: PollInterval,
+        blocks_chunk_size: u32,
+        block_info_cache: BlockInfoCache,
         ledger_db: LedgerDB,
+        watcher_db: Option<WatcherDB>,
         network_state: Arc<RwLock<PollingNetworkState<ValidatorConnection>>>,
         logger: Logger,
     ) -> Self {
         let stop_requested = Arc::new(AtomicBool::new(false));
 
+        let watcher_sync = watcher_db.map(|watcher_db| ValidatorWatcherSync {
+            watcher_db,
+            src_url: validator_uri.url().clone(),
+        });
+
         let validator_conn = ValidatorConnection::new(validator_uri, chain_id, logger.clone());
 
         let thread_stop_requested = stop_requested.clone();
@@ -46,7 +176,10 @@ impl ValidatorLedgerSyncThread {
                     Self::thread_entrypoint(
                         validator_conn,
                         poll_interval,
+                        blocks_chunk_size,
+                        block_info_cache,
                         ledger_db,
+                        watcher_sync,
                         network_state,
                         logger,
                         thread_stop_requested,
@@ -70,30 +203,64 @@ impl ValidatorLedgerSyncThread {
 
     fn thread_entrypoint(
         validator_conn: ValidatorConnection,
-        poll_interval: Duration,
+        poll_interval: PollInterval,
+        blocks_chunk_size: u32,
+        block_info_cache: BlockInfoCache,
         mut ledger_db: LedgerDB,
+        watcher_sync: Option<ValidatorWatcherSync>,
         mut network_state: Arc<RwLock<PollingNetworkState<ValidatorConnection>>>,
         logger: Logger,
         stop_requested: Arc<AtomicBool>,
     ) {
         log::info!(logger, "ValidatorLedgerSync thread started");
 
+        let conn_manager = ConnectionManager::new(vec![validator_conn.clone()], logger.clone());
+
         loop {
             if stop_requested.load(Ordering::SeqCst) {
                 log::debug!(logger, "ValidatorLedgerSyncThread stop requested.");
                 break;
             }
 
-            let block_data =
-                Self::get_next_blocks(&ledger_db, &validator_conn, &mut network_state, &logger);
+            // Only record new blocks in the watcher once it has caught up with the ledger,
+            // so that it never skips any blocks.
+            let watcher_caught_up = match &watcher_sync {
+                Some(watcher_sync) => watcher_sync
+                    .backfill(&ledger_db, &validator_conn, blocks_chunk_size, &logger)
+                    .unwrap_or_else(|err| {
+                        log::error!(logger, "Failed syncing watcher from validator: {}", err);
+                        false
+                    }),
+                None => false,
+            };
+
+            let block_data = Self::get_next_blocks(
+                &ledger_db,
+                &validator_conn,
+                blocks_chunk_size,
+                &mut network_state,
+                &logger,
+            );
+
+            if let Err(err) =
+                block_info_cache.refresh_if_older_than(&conn_manager, poll_interval.get())
+            {
+                log::warn!(logger, "Could not refresh block info: {:?}", err);
+            }
             if !block_data.is_empty() {
                 Self::append_safe_blocks(&mut ledger_db, &block_data, &logger);
+
+                if let (Some(watcher_sync), true) = (&watcher_sync, watcher_caught_up) {
+                    if let Err(err) = watcher_sync.add_blocks(&block_data) {
+                        log::error!(logger, "Failed adding blocks to watcher: {}", err);
+                    }
+                }
             }
 
             // If we got no blocks, or less than the amount we asked for, sleep for a bit.
             // Getting less the amount we asked for indicates we are fully synced.
             if block_data.is_empty() || block_data.len() < MAX_BLOCKS_PER_SYNC_ITERATION as usize {
-                thread::sleep(poll_interval);
+                thread::sleep(poll_interval.get());
             }
         }
     }
@@ -101,6 +268,7 @@ impl ValidatorLedgerSyncThread {
     fn get_next_blocks(
         ledger_db: &LedgerDB,
         validator_conn: &ValidatorConnection,
+        blocks_chunk_size: u32,
         network_state: &Arc<RwLock<PollingNetworkState<ValidatorConnection>>>,
         logger: &Logger,
     ) -> Vec<BlockData> {
@@ -131,18 +299,22 @@ impl ValidatorLedgerSyncThread {
         }
 
         log::debug!(logger, "network state is behind, local ledger has {} blocks, network highest block index is {}", num_blocks, highest_block_index_on_network);
-        let blocks_data =
-            match validator_conn.get_blocks_data(num_blocks, MAX_BLOCKS_PER_SYNC_ITERATION) {
-                Ok(blocks_data) => blocks_data,
-                Err(err) => {
-                    log::error!(
-                        logger,
-                        "Failed getting blocks data from validator: {:?}",
-                        err
-                    );
-                    return Vec::new();
-                }
-            };
+        let blocks_data = match fetch_blocks_in_chunks(
+            num_blocks,
+            MAX_BLOCKS_PER_SYNC_ITERATION as u64,
+            blocks_chunk_size,
+            |start, limit| validator_conn.get_blocks_data(start, limit),
+        ) {
+            Ok(blocks_data) => blocks_data,
+            Err(err) => {
+                log::error!(
+                    logger,
+                    "Failed getting blocks data from validator: {:?}",
+                    err
+                );
+                return Vec::new();
+            }
+        };
 
         mc_ledger_sync::identify_safe_blocks(ledger_db, &blocks_data, logger)
     }
@@ -181,3 +353,57 @@ impl Drop for ValidatorLedgerSyncThread {
         self.stop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_blockchain_types::{Block, BlockContents};
+
+    fn fake_blocks_data(start: u64, limit: u32, num_blocks: u64) -> Vec<BlockData> {
+        (start..min(start + limit as u64, num_blocks))
+            .map(|index| {
+                let block = Block {
+                    index,
+                    ..Default::default()
+                };
+                BlockData::new(block, BlockContents::default(), None, None)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fetch_blocks_in_chunks() {
+        // A range wider than the chunk size is fetched with multiple requests.
+        let mut requests = Vec::new();
+        let blocks_data: Result<_, ()> = fetch_blocks_in_chunks(5, 25, 10, |start, limit| {
+            requests.push((start, limit));
+            Ok(fake_blocks_data(start, limit, 100))
+        });
+        let indexes: Vec<u64> = blocks_data
+            .unwrap()
+            .iter()
+            .map(|block_data| block_data.block().index)
+            .collect();
+        assert_eq!(indexes, (5..30).collect::<Vec<_>>());
+        assert_eq!(requests, vec![(5, 10), (15, 10), (25, 5)]);
+
+        // Fetching stops at the end of the ledger.
+        let mut requests = Vec::new();
+        let blocks_data: Result<_, ()> = fetch_blocks_in_chunks(0, 100, 10, |start, limit| {
+            requests.push((start, limit));
+            Ok(fake_blocks_data(start, limit, 15))
+        });
+        assert_eq!(blocks_data.unwrap().len(), 15);
+        assert_eq!(requests, vec![(0, 10), (10, 10)]);
+
+        // Errors are returned as is.
+        let result = fetch_blocks_in_chunks(0, 100, 10, |start, _| {
+            if start == 0 {
+                Ok(fake_blocks_data(0, 10, 100))
+            } else {
+                Err("too large")
+            }
+        });
+        assert_eq!(result.unwrap_err(), "too large");
+    }
+}
@@ -17,3 +17,54 @@ mod autogenerated_code {
 pub use autogenerated_code::{wallet_service_mirror_api::*, *};
 
 pub mod uri;
+
+/// Read-only wallet service methods. These are the methods the private side
+/// forwards when `--supported-endpoints` is not provided.
+pub const READ_ONLY_ENDPOINTS: &[&str] = &[
+    "check_receiver_receipt_status",
+    "create_payment_request",
+    "get_account",
+    "get_account_status",
+    "get_address_for_account",
+    "get_addresses_for_account",
+    "get_address_status",
+    "get_accounts",
+    "get_transaction_logs",
+    "get_block",
+    "get_confirmations",
+    "get_network_status",
+    "get_transaction_log",
+    "get_wallet_status",
+    "validate_confirmation",
+    "validate_sender_memo",
+    "verify_address",
+    "get_txos",
+    "get_all_accounts",
+    "get_all_transaction_logs_for_block",
+    "get_balance_for_account",
+    "get_balance_for_address",
+    "get_transaction_logs_for_account",
+];
+
+/// Read-only wallet service methods which are not forwarded by default, so
+/// that upgrading the private side does not widen what an existing mirror
+/// exposes. The private side forwards them when started with
+/// `--opt-in-read-only-endpoints`, or when they are listed in
+/// `--supported-endpoints`.
+pub const OPT_IN_READ_ONLY_ENDPOINTS: &[&str] = &[
+    "check_receiver_receipts_status",
+    "derive_address_for_account",
+    "get_account_lifetime_stats",
+    "get_address_for_txo",
+    "get_api_schema",
+    "get_txo_confirmation_number",
+    "get_txo_history",
+    "get_spent_key_images",
+    "get_supported_token_ids",
+    "get_transaction_proof_bundle",
+    "get_watcher_status",
+    "preview_spendable_selection",
+    "get_txos_missing_required_memo",
+    "get_change_txos",
+    "get_transaction_logs_for_txo",
+];
@@ -4,13 +4,167 @@
 
 use boring::{
     pkey::Private,
+    rand::rand_bytes,
     rsa::{Padding, Rsa},
+    symm::{decrypt_aead, encrypt_aead, Cipher},
 };
 
 const PKCS1_PADDING_LEN: usize = 11;
 
-/// Encrypt a payload of arbitrary length using a private key.
-pub fn encrypt(key: &Rsa<Private>, payload: &[u8]) -> Result<Vec<u8>, String> {
+/// Prefix identifying a version 1 hybrid payload. Payloads without it are
+/// treated as the legacy format of chunked RSA PKCS1 blocks.
+///
+/// A version 1 request is laid out as:
+///   header || RSA-OAEP(aes key) || nonce || AES-256-GCM(payload) || tag
+/// and the response to it, encrypted under the same AES key, as:
+///   header || nonce || AES-256-GCM(payload) || tag
+/// The header is authenticated as additional data.
+const HYBRID_V1_HEADER: &[u8] = b"MCH\x01";
+
+const AES_KEY_LEN: usize = 32;
+const AES_GCM_NONCE_LEN: usize = 12;
+const AES_GCM_TAG_LEN: usize = 16;
+
+/// The key a response should be encrypted under, which depends on the format
+/// of the request it answers.
+pub enum ResponseKey<'a> {
+    /// The request used the legacy format, so the response is encrypted in
+    /// chunks with the mirror's private key.
+    Rsa(&'a Rsa<Private>),
+
+    /// The request used the hybrid format, so the response is encrypted with
+    /// the AES key the client generated for it.
+    Aes([u8; AES_KEY_LEN]),
+}
+
+/// Encrypt a response payload of arbitrary length, in the same format as the
+/// request it answers.
+pub fn encrypt(key: &ResponseKey, payload: &[u8]) -> Result<Vec<u8>, String> {
+    match key {
+        ResponseKey::Rsa(key) => encrypt_rsa(key, payload),
+        ResponseKey::Aes(aes_key) => {
+            let mut output = HYBRID_V1_HEADER.to_vec();
+            output.extend(encrypt_aes(aes_key, payload)?);
+            Ok(output)
+        }
+    }
+}
+
+/// Decrypt a request payload of arbitrary length using a private key,
+/// returning the plaintext and the key the response should be encrypted
+/// under.
+pub fn decrypt<'a>(
+    key: &'a Rsa<Private>,
+    payload: &[u8],
+) -> Result<(Vec<u8>, ResponseKey<'a>), String> {
+    // A legacy payload could begin with the header by chance, so fall back to
+    // the legacy format if the hybrid one does not decrypt.
+    if let Some(body) = payload.strip_prefix(HYBRID_V1_HEADER) {
+        if let Ok((plaintext, aes_key)) = decrypt_hybrid(key, body) {
+            return Ok((plaintext, ResponseKey::Aes(aes_key)));
+        }
+    }
+
+    let plaintext = decrypt_rsa(key, payload)?;
+    Ok((plaintext, ResponseKey::Rsa(key)))
+}
+
+/// Decrypt a payload using the first of several private keys that succeeds,
+/// returning the plaintext along with the key the response should be
+/// encrypted under. This allows requests encrypted under either an old or a
+/// new key to be accepted while keys are being rotated.
+///
+/// The legacy format is not authenticated, and a wrong key occasionally passes
+/// its padding check, so a key only decrypts a legacy payload if the plaintext
+/// is a JSON request. The hybrid format is authenticated by AES-GCM.
+pub fn decrypt_with_any_key<'a>(
+    keys: &'a [Rsa<Private>],
+    payload: &[u8],
+) -> Result<(Vec<u8>, ResponseKey<'a>), String> {
+    let mut errors = Vec::new();
+    for key in keys {
+        match decrypt(key, payload) {
+            Ok((plaintext, ResponseKey::Rsa(_)))
+                if serde_json::from_slice::<serde_json::Value>(&plaintext).is_err() =>
+            {
+                errors.push("decrypted payload is not JSON".to_string())
+            }
+            Ok(result) => return Ok(result),
+            Err(err) => errors.push(err),
+        }
+    }
+    Err(format!(
+        "no key could decrypt the payload: [{}]",
+        errors.join(", ")
+    ))
+}
+
+/// Decrypt the body of a version 1 hybrid request (without its header).
+fn decrypt_hybrid(key: &Rsa<Private>, body: &[u8]) -> Result<(Vec<u8>, [u8; AES_KEY_LEN]), String> {
+    let key_size = key.size() as usize;
+    if body.len() < key_size {
+        return Err("hybrid payload too short".into());
+    }
+    let (encrypted_aes_key, encrypted_payload) = body.split_at(key_size);
+
+    let mut output = vec![0u8; key_size];
+    let num_bytes = key
+        .private_decrypt(encrypted_aes_key, &mut output, Padding::PKCS1_OAEP)
+        .map_err(|e| format!("decrypt failed: {e:?}"))?;
+    if num_bytes != AES_KEY_LEN {
+        return Err(format!("unexpected AES key length {num_bytes}"));
+    }
+    let mut aes_key = [0u8; AES_KEY_LEN];
+    aes_key.copy_from_slice(&output[..AES_KEY_LEN]);
+
+    let plaintext = decrypt_aes(&aes_key, encrypted_payload)?;
+    Ok((plaintext, aes_key))
+}
+
+/// Encrypt a payload with AES-256-GCM under a fresh random nonce, returning
+/// nonce || ciphertext || tag.
+fn encrypt_aes(aes_key: &[u8; AES_KEY_LEN], payload: &[u8]) -> Result<Vec<u8>, String> {
+    let mut nonce = [0u8; AES_GCM_NONCE_LEN];
+    rand_bytes(&mut nonce).map_err(|e| format!("generating nonce failed: {e:?}"))?;
+
+    let mut tag = [0u8; AES_GCM_TAG_LEN];
+    let ciphertext = encrypt_aead(
+        Cipher::aes_256_gcm(),
+        aes_key,
+        Some(&nonce),
+        HYBRID_V1_HEADER,
+        payload,
+        &mut tag,
+    )
+    .map_err(|e| format!("encrypt failed: {e:?}"))?;
+
+    let mut output = nonce.to_vec();
+    output.extend(ciphertext);
+    output.extend(tag);
+    Ok(output)
+}
+
+/// Decrypt nonce || ciphertext || tag with AES-256-GCM.
+fn decrypt_aes(aes_key: &[u8; AES_KEY_LEN], payload: &[u8]) -> Result<Vec<u8>, String> {
+    if payload.len() < AES_GCM_NONCE_LEN + AES_GCM_TAG_LEN {
+        return Err("AES payload too short".into());
+    }
+    let (nonce, rest) = payload.split_at(AES_GCM_NONCE_LEN);
+    let (ciphertext, tag) = rest.split_at(rest.len() - AES_GCM_TAG_LEN);
+
+    decrypt_aead(
+        Cipher::aes_256_gcm(),
+        aes_key,
+        Some(nonce),
+        HYBRID_V1_HEADER,
+        ciphertext,
+        tag,
+    )
+    .map_err(|e| format!("decrypt failed: {e:?}"))
+}
+
+/// Encrypt a payload of arbitrary length in chunks using a private key.
+fn encrypt_rsa(key: &Rsa<Private>, payload: &[u8]) -> Result<Vec<u8>, String> {
     // Each encrypted chunk must be no longer than the length of the public
     // modulus minus 11 (PKCS1 padding size). (Taken from `rsa::oaep::encrypt`).
     let key_size = key.size() as usize;
@@ -34,8 +188,8 @@ pub fn encrypt(key: &Rsa<Private>, payload: &[u8]) -> Result<Vec<u8>, String> {
         .collect())
 }
 
-/// Decrypt a payload of arbitrary length using a private key.
-pub fn decrypt(key: &Rsa<Private>, payload: &[u8]) -> Result<Vec<u8>, String> {
+/// Decrypt a payload of arbitrary length in chunks using a private key.
+fn decrypt_rsa(key: &Rsa<Private>, payload: &[u8]) -> Result<Vec<u8>, String> {
     let key_size = key.size() as usize;
 
     let chunks: Vec<Vec<u8>> = payload
@@ -128,7 +282,7 @@ mod tests {
 
         let message = b"this message is less than the key size";
 
-        let encrypted = encrypt(&priv_key, message).unwrap();
+        let encrypted = encrypt_rsa(&priv_key, message).unwrap();
         assert_eq!(encrypted.len(), priv_key.size() as usize);
 
         let decrypted = decrypt_public(&pub_key, &encrypted).unwrap();
@@ -146,7 +300,7 @@ mod tests {
         let mut rng = Hc128Rng::from_seed([0u8; 32]);
         rng.fill_bytes(&mut message);
 
-        let encrypted = encrypt(&priv_key, &message).unwrap();
+        let encrypted = encrypt_rsa(&priv_key, &message).unwrap();
         assert_eq!(encrypted.len(), priv_key.size() as usize * 6);
 
         let decrypted = decrypt_public(&pub_key, &encrypted).unwrap();
@@ -164,7 +318,7 @@ mod tests {
         let mut rng = Hc128Rng::from_seed([0u8; 32]);
         rng.fill_bytes(&mut message);
 
-        let encrypted = encrypt(&priv_key, &message).unwrap();
+        let encrypted = encrypt_rsa(&priv_key, &message).unwrap();
         assert_eq!(encrypted.len(), priv_key.size() as usize * 5); // longer than message because of padding
 
         let decrypted = decrypt_public(&pub_key, &encrypted).unwrap();
@@ -182,7 +336,7 @@ mod tests {
         let mut rng = Hc128Rng::from_seed([0u8; 32]);
         rng.fill_bytes(&mut message);
 
-        let encrypted = encrypt(&priv_key, &message).unwrap();
+        let encrypted = encrypt_rsa(&priv_key, &message).unwrap();
         assert_eq!(encrypted.len(), priv_key.size() as usize * 3);
 
         let decrypted = decrypt_public(&pub_key, &encrypted).unwrap();
@@ -201,7 +355,7 @@ mod tests {
         let encrypted = encrypt_public(&pub_key, message).unwrap();
         assert_eq!(encrypted.len(), priv_key.size() as usize);
 
-        let decrypted = decrypt(&priv_key, &encrypted).unwrap();
+        let decrypted = decrypt_rsa(&priv_key, &encrypted).unwrap();
         assert_eq!(message, &decrypted[..]);
     }
 
@@ -219,7 +373,7 @@ mod tests {
         let encrypted = encrypt_public(&pub_key, &message).unwrap();
         assert_eq!(encrypted.len(), priv_key.size() as usize * 6);
 
-        let decrypted = decrypt(&priv_key, &encrypted).unwrap();
+        let decrypted = decrypt_rsa(&priv_key, &encrypted).unwrap();
         assert_eq!(message, &decrypted[..]);
     }
 
@@ -237,7 +391,7 @@ mod tests {
         let encrypted = encrypt_public(&pub_key, &message).unwrap();
         assert_eq!(encrypted.len(), priv_key.size() as usize * 5); // longer than message because of padding
 
-        let decrypted = decrypt(&priv_key, &encrypted).unwrap();
+        let decrypted = decrypt_rsa(&priv_key, &encrypted).unwrap();
         assert_eq!(message, &decrypted[..]);
     }
 
@@ -255,7 +409,122 @@ mod tests {
         let encrypted = encrypt_public(&pub_key, &message).unwrap();
         assert_eq!(encrypted.len(), priv_key.size() as usize * 3);
 
-        let decrypted = decrypt(&priv_key, &encrypted).unwrap();
+        let decrypted = decrypt_rsa(&priv_key, &encrypted).unwrap();
         assert_eq!(message, &decrypted[..]);
     }
+
+    /// Encrypt a payload in the version 1 hybrid format, as a client would.
+    fn encrypt_hybrid_public(
+        key: &Rsa<Public>,
+        aes_key: &[u8; AES_KEY_LEN],
+        payload: &[u8],
+    ) -> Vec<u8> {
+        let mut encrypted_aes_key = vec![0u8; key.size() as usize];
+        key.public_encrypt(aes_key, &mut encrypted_aes_key, Padding::PKCS1_OAEP)
+            .unwrap();
+
+        let mut output = HYBRID_V1_HEADER.to_vec();
+        output.extend(encrypted_aes_key);
+        output.extend(encrypt_aes(aes_key, payload).unwrap());
+        output
+    }
+
+    #[test]
+    fn hybrid_request_and_response_round_trip_with_large_message() {
+        let priv_key = Rsa::generate(2048).unwrap();
+
+        let pub_key_pem = priv_key.public_key_to_pem().unwrap();
+        let pub_key = Rsa::public_key_from_pem(&pub_key_pem).unwrap();
+
+        let mut rng = Hc128Rng::from_seed([0u8; 32]);
+        let mut aes_key = [0u8; AES_KEY_LEN];
+        rng.fill_bytes(&mut aes_key);
+        let mut message = vec![0u8; 1024 * 1024];
+        rng.fill_bytes(&mut message);
+
+        let encrypted = encrypt_hybrid_public(&pub_key, &aes_key, &message);
+        let (decrypted, response_key) = decrypt(&priv_key, &encrypted).unwrap();
+        assert_eq!(message, decrypted);
+        assert!(matches!(response_key, ResponseKey::Aes(key) if key == aes_key));
+
+        let response = b"a response encrypted under the client's AES key";
+        let encrypted_response = encrypt(&response_key, response).unwrap();
+        let body = encrypted_response.strip_prefix(HYBRID_V1_HEADER).unwrap();
+        assert_eq!(response, &decrypt_aes(&aes_key, body).unwrap()[..]);
+    }
+
+    #[test]
+    fn hybrid_request_is_rejected_when_tampered_with() {
+        let priv_key = Rsa::generate(2048).unwrap();
+
+        let pub_key_pem = priv_key.public_key_to_pem().unwrap();
+        let pub_key = Rsa::public_key_from_pem(&pub_key_pem).unwrap();
+
+        let mut encrypted = encrypt_hybrid_public(&pub_key, &[7u8; AES_KEY_LEN], b"some request");
+        let last = encrypted.len() - 1;
+        encrypted[last] ^= 1;
+
+        assert!(decrypt(&priv_key, &encrypted).is_err());
+    }
+
+    #[test]
+    fn legacy_request_gets_legacy_response() {
+        let priv_key = Rsa::generate(2048).unwrap();
+
+        let pub_key_pem = priv_key.public_key_to_pem().unwrap();
+        let pub_key = Rsa::public_key_from_pem(&pub_key_pem).unwrap();
+
+        let message = b"a request in the legacy format";
+        let encrypted = encrypt_public(&pub_key, message).unwrap();
+        let (decrypted, response_key) = decrypt(&priv_key, &encrypted).unwrap();
+        assert_eq!(message, &decrypted[..]);
+        assert!(matches!(response_key, ResponseKey::Rsa(_)));
+
+        let encrypted_response = encrypt(&response_key, message).unwrap();
+        assert_eq!(
+            message,
+            &decrypt_public(&pub_key, &encrypted_response).unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn decrypt_with_any_key_uses_the_matching_key() {
+        let old_key = Rsa::generate(2048).unwrap();
+        let new_key = Rsa::generate(2048).unwrap();
+        let other_key = Rsa::generate(2048).unwrap();
+
+        let pub_key_pem = new_key.public_key_to_pem().unwrap();
+        let pub_key = Rsa::public_key_from_pem(&pub_key_pem).unwrap();
+
+        let message = br#"{"method":"get_accounts","jsonrpc":"2.0","id":1}"#;
+        let encrypted = encrypt_public(&pub_key, message).unwrap();
+
+        let keys = vec![old_key, new_key];
+        let (decrypted, response_key) = decrypt_with_any_key(&keys, &encrypted).unwrap();
+        assert_eq!(message, &decrypted[..]);
+        match response_key {
+            ResponseKey::Rsa(key) => assert_eq!(
+                key.public_key_to_pem().unwrap(),
+                keys[1].public_key_to_pem().unwrap()
+            ),
+            ResponseKey::Aes(_) => panic!("expected a legacy response key"),
+        }
+
+        assert!(decrypt_with_any_key(&[other_key], &encrypted).is_err());
+        assert!(decrypt_with_any_key(&[], &encrypted).is_err());
+    }
+
+    #[test]
+    fn decrypt_with_any_key_rejects_legacy_plaintext_that_is_not_json() {
+        let key = Rsa::generate(2048).unwrap();
+
+        let pub_key_pem = key.public_key_to_pem().unwrap();
+        let pub_key = Rsa::public_key_from_pem(&pub_key_pem).unwrap();
+
+        // This is what a wrong key which passes the padding check yields, so the
+        // next key is tried instead.
+        let encrypted = encrypt_public(&pub_key, b"not a json request").unwrap();
+        assert!(decrypt(&key, &encrypted).is_ok());
+        assert!(decrypt_with_any_key(&[key], &encrypted).is_err());
+    }
 }
@@ -9,7 +9,7 @@
 
 mod crypto;
 
-use crate::crypto::{decrypt, encrypt, load_private_key};
+use crate::crypto::{decrypt_with_any_key, encrypt, load_private_key};
 use boring::{pkey::Private, rsa::Rsa};
 use grpcio::ChannelBuilder;
 use mc_common::logger::{create_app_logger, log, o, Logger};
@@ -19,40 +19,12 @@ use mc_full_service_mirror::{
         EncryptedResponse, PollRequest, QueryRequest, QueryResponse, UnencryptedResponse,
     },
     wallet_service_mirror_api_grpc::WalletServiceMirrorClient,
+    OPT_IN_READ_ONLY_ENDPOINTS, READ_ONLY_ENDPOINTS,
 };
 use mc_util_grpc::ConnectionUriGrpcioChannel;
 use std::{collections::HashMap, str::FromStr, sync::Arc, thread::sleep, time::Duration};
 use structopt::StructOpt;
 
-const SUPPORTED_ENDPOINTS: &[&str] = &[
-    "check_receiver_receipt_status",
-    "create_payment_request",
-    "get_account",
-    "get_account_status",
-    "get_address_for_account",
-    "get_addresses_for_account",
-    "get_address_status",
-    "get_accounts",
-    "get_transaction_logs",
-    "get_block",
-    "get_confirmations",
-    "get_network_status",
-    "get_transaction_log",
-    "get_wallet_status",
-    "validate_confirmation",
-    "validate_sender_memo",
-    "verify_address",
-    "get_txos",
-    "get_all_accounts",
-    "get_all_transaction_logs_for_block",
-    "get_balance_for_account",
-    "get_balance_for_address",
-    "get_transaction_logs_for_account",
-];
-
-/// How long do we wait for full-service to reply?
-const FULL_SERVICE_TIMEOUT: Duration = Duration::from_secs(120);
-
 /// A wrapper to ease monitor id parsing from a hex string when using
 /// `StructOpt`.
 #[derive(Clone, Debug)]
@@ -69,6 +41,58 @@ impl FromStr for MonitorId {
     }
 }
 
+/// A per-method override of how long to wait for full-service to reply, parsed
+/// from `method=milliseconds`.
+#[derive(Clone, Debug)]
+pub struct MethodTimeout {
+    pub method: String,
+    pub timeout: Duration,
+}
+impl FromStr for MethodTimeout {
+    type Err = String;
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let (method, millis) = src
+            .split_once('=')
+            .ok_or_else(|| format!("Expected method=milliseconds, got {src}"))?;
+        let timeout = parse_duration_in_milliseconds(millis)
+            .map_err(|err| format!("Error parsing timeout for {method}: {err}"))?;
+        Ok(Self {
+            method: method.to_string(),
+            timeout,
+        })
+    }
+}
+
+/// How long to wait for full-service to reply to each request.
+pub struct ForwardTimeouts {
+    default_timeout: Duration,
+    method_timeouts: HashMap<String, Duration>,
+}
+impl ForwardTimeouts {
+    pub fn new(default_timeout: Duration, method_timeouts: &[MethodTimeout]) -> Self {
+        Self {
+            default_timeout,
+            method_timeouts: method_timeouts
+                .iter()
+                .map(|m| (m.method.clone(), m.timeout))
+                .collect(),
+        }
+    }
+
+    /// The timeout for a JSON-RPC request, falling back to the default if the
+    /// method has no override.
+    pub fn for_request(&self, json: &str) -> Duration {
+        serde_json::from_str::<serde_json::Value>(json)
+            .ok()
+            .and_then(|json| {
+                json["method"]
+                    .as_str()
+                    .and_then(|method| self.method_timeouts.get(method).copied())
+            })
+            .unwrap_or(self.default_timeout)
+    }
+}
+
 /// Command line config
 #[derive(Clone, Debug, StructOpt)]
 #[structopt(
@@ -88,11 +112,39 @@ pub struct Config {
     #[structopt(long, default_value = "100", parse(try_from_str=parse_duration_in_milliseconds))]
     pub poll_interval: Duration,
 
-    /// Optional encryption public key. If provided, only encrypted requests are
-    /// accepted. See `example-client.js` for an example on how to submit
+    /// Optional encryption private key. If provided, only encrypted requests
+    /// are accepted. See `example-client.js` for an example on how to submit
     /// encrypted requests through the mirror.
+    ///
+    /// May be specified multiple times to rotate keys: each key is tried in
+    /// order until one decrypts the request, and the response is encrypted
+    /// under that same key.
     #[structopt(long, parse(try_from_str=load_private_key))]
-    pub mirror_key: Option<Rsa<Private>>,
+    pub mirror_key: Vec<Rsa<Private>>,
+
+    /// Comma-separated list of JSON-RPC methods to forward to the wallet
+    /// service. Requests for any other method are rejected. Defaults to a
+    /// built-in set of read-only methods.
+    #[structopt(long, use_delimiter = true)]
+    pub supported_endpoints: Option<Vec<String>>,
+
+    /// Also forward the read-only methods which are not in the built-in set by
+    /// default. Ignored when `--supported-endpoints` is provided.
+    #[structopt(long)]
+    pub opt_in_read_only_endpoints: bool,
+
+    /// How many milliseconds to wait for full-service to reply to a request.
+    /// Note that the public side of the mirror gives up on a request after
+    /// 120 seconds, and that this must be shorter than the public side's
+    /// `--query-claim-timeout`.
+    #[structopt(long, default_value = "120000", parse(try_from_str=parse_duration_in_milliseconds))]
+    pub full_service_timeout: Duration,
+
+    /// Per-method overrides of `--full-service-timeout`, as a comma-separated
+    /// list of `method=milliseconds`, e.g.
+    /// `build_transaction=110000,get_network_status=5000`.
+    #[structopt(long, use_delimiter = true)]
+    pub method_timeout: Vec<MethodTimeout>,
 }
 
 fn main() {
@@ -109,6 +161,28 @@ fn main() {
         config.wallet_service_uri,
     );
 
+    let supported_endpoints: Vec<String> =
+        config.supported_endpoints.clone().unwrap_or_else(|| {
+            let opt_in_endpoints = if config.opt_in_read_only_endpoints {
+                OPT_IN_READ_ONLY_ENDPOINTS
+            } else {
+                &[]
+            };
+            READ_ONLY_ENDPOINTS
+                .iter()
+                .chain(opt_in_endpoints)
+                .map(|s| s.to_string())
+                .collect()
+        });
+    log::info!(
+        logger,
+        "Forwarding supported endpoints: {}",
+        supported_endpoints.join(", ")
+    );
+
+    let forward_timeouts =
+        ForwardTimeouts::new(config.full_service_timeout, &config.method_timeout);
+
     // Set up the gRPC connection to the public side of the mirror.
     let mirror_api_client = {
         let env = Arc::new(grpcio::EnvBuilder::new().build());
@@ -127,10 +201,16 @@ fn main() {
 
     let mut pending_responses: HashMap<String, QueryResponse> = HashMap::new();
 
+    // Identifies this instance to the public side, which may be polled by
+    // several private side instances.
+    let session_id = hex::encode(rand::random::<[u8; 16]>());
+    log::info!(logger, "Polling with session id {}", session_id);
+
     loop {
         // Communicate with the public side of the mirror.
         let mut request = PollRequest::new();
         request.set_query_responses(pending_responses.clone());
+        request.set_session_id(session_id.clone());
 
         log::debug!(
             logger,
@@ -154,11 +234,13 @@ fn main() {
                     let query_logger = logger.new(o!("query_id" => query_id.clone()));
 
                     let response = {
-                        if let Some(mirror_key) = config.mirror_key.as_ref() {
+                        if !config.mirror_key.is_empty() {
                             process_encrypted_request(
                                 &config.wallet_service_uri,
-                                mirror_key,
+                                &config.mirror_key,
                                 query_request,
+                                &supported_endpoints,
+                                &forward_timeouts,
                                 &query_logger,
                             )
                             .unwrap_or_else(|err| {
@@ -176,6 +258,8 @@ fn main() {
                             process_unencrypted_request(
                                 &config.wallet_service_uri,
                                 query_request,
+                                &supported_endpoints,
+                                &forward_timeouts,
                                 &query_logger,
                             )
                             .unwrap_or_else(|err| {
@@ -209,15 +293,17 @@ fn main() {
     }
 }
 
-fn validate_method(json: &str) -> serde_json::Result<bool> {
+fn validate_method(json: &str, supported_endpoints: &[String]) -> serde_json::Result<bool> {
     let json: serde_json::Value = serde_json::from_str(json)?;
     let method = json["method"].as_str().unwrap_or("");
-    Ok(SUPPORTED_ENDPOINTS.iter().any(|&s| s == method))
+    Ok(supported_endpoints.iter().any(|s| s == method))
 }
 
 fn process_unencrypted_request(
     wallet_service_uri: &str,
     query_request: &QueryRequest,
+    supported_endpoints: &[String],
+    forward_timeouts: &ForwardTimeouts,
     logger: &Logger,
 ) -> Result<QueryResponse, String> {
     if !query_request.has_unencrypted_request() {
@@ -233,7 +319,7 @@ fn process_unencrypted_request(
     );
 
     // Check that the request is of an allowed type.
-    match validate_method(&unencrypted_request.json_request) {
+    match validate_method(&unencrypted_request.json_request, supported_endpoints) {
         Ok(true) => (),
         Ok(false) => return Err("Unsupported request".into()),
         Err(err) => {
@@ -245,7 +331,7 @@ fn process_unencrypted_request(
 
     // Pass request along to full-service.
     let client = reqwest::blocking::Client::builder()
-        .timeout(FULL_SERVICE_TIMEOUT)
+        .timeout(forward_timeouts.for_request(&unencrypted_request.json_request))
         .build()
         .map_err(|e| e.to_string())?;
     let res = client
@@ -266,8 +352,10 @@ fn process_unencrypted_request(
 
 fn process_encrypted_request(
     wallet_service_uri: &str,
-    mirror_key: &Rsa<Private>,
+    mirror_keys: &[Rsa<Private>],
     query_request: &QueryRequest,
+    supported_endpoints: &[String],
+    forward_timeouts: &ForwardTimeouts,
     logger: &Logger,
 ) -> Result<QueryResponse, String> {
     if !query_request.has_encrypted_request() {
@@ -276,24 +364,28 @@ fn process_encrypted_request(
 
     let encrypted_request = query_request.get_encrypted_request();
 
-    // Decrypt the request.
-    let json_request = match decrypt(mirror_key, &encrypted_request.payload)
-        .map_err(|err| format!("Error decrypting request: {err}"))
-        .and_then(|decrypted| {
-            String::from_utf8(decrypted).map_err(|err| format!("Error parsing utf8: {err}"))
-        }) {
-        Ok(json_request) => json_request,
-        Err(err) => {
-            let mut err_query_response = QueryResponse::new();
-            err_query_response.set_error(err);
-            return Ok(err_query_response);
-        }
-    };
+    // Decrypt the request, remembering which key the response should be
+    // encrypted under.
+    let (json_request, response_key) =
+        match decrypt_with_any_key(mirror_keys, &encrypted_request.payload)
+            .map_err(|err| format!("Error decrypting request: {err}"))
+            .and_then(|(decrypted, response_key)| {
+                String::from_utf8(decrypted)
+                    .map(|json_request| (json_request, response_key))
+                    .map_err(|err| format!("Error parsing utf8: {err}"))
+            }) {
+            Ok(result) => result,
+            Err(err) => {
+                let mut err_query_response = QueryResponse::new();
+                err_query_response.set_error(err);
+                return Ok(err_query_response);
+            }
+        };
 
     log::debug!(logger, "Incoming encrypted request ({})", json_request,);
 
     // Check that the request is of an allowed type.
-    match validate_method(&json_request) {
+    match validate_method(&json_request, supported_endpoints) {
         Ok(true) => (),
         Ok(false) => return Err("Unsupported request".into()),
         Err(err) => {
@@ -305,7 +397,7 @@ fn process_encrypted_request(
 
     // Pass request along to full-service.
     let client = reqwest::blocking::Client::builder()
-        .timeout(FULL_SERVICE_TIMEOUT)
+        .timeout(forward_timeouts.for_request(&json_request))
         .build()
         .map_err(|e| e.to_string())?;
     let res = client
@@ -317,7 +409,7 @@ fn process_encrypted_request(
     let json_response = res.text().map_err(|e| e.to_string())?;
 
     let encrypted_payload =
-        encrypt(mirror_key, json_response.as_bytes()).map_err(|_e| "Encryption failed")?;
+        encrypt(&response_key, json_response.as_bytes()).map_err(|_e| "Encryption failed")?;
 
     let mut encrypted_response = EncryptedResponse::new();
     encrypted_response.set_payload(encrypted_payload);
@@ -0,0 +1,164 @@
+// Copyright (c) 2018-2023 MobileCoin Inc.
+
+//! A short-lived cache of responses to read-only unencrypted requests, so that
+//! clients polling identical requests do not each round-trip to the private
+//! side of the mirror.
+
+use mc_full_service_mirror::{OPT_IN_READ_ONLY_ENDPOINTS, READ_ONLY_ENDPOINTS};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+pub struct ResponseCache {
+    /// How long a cached response remains valid. Zero disables caching.
+    ttl: Duration,
+
+    /// Cached responses, keyed by the canonical request JSON with its `id`
+    /// removed, along with the time each response was received.
+    entries: Mutex<HashMap<String, (Instant, Value)>>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the cache key for a request, or None if the request should not be
+    /// cached.
+    ///
+    /// The JSON-RPC `id` is excluded from the key so that identical requests
+    /// from different clients share a cache entry, and object keys are sorted
+    /// so that field order does not matter.
+    pub fn key(&self, request: &str) -> Option<String> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+
+        let mut request: Value = serde_json::from_str(request).ok()?;
+        let method = request.get("method")?.as_str()?;
+        if !READ_ONLY_ENDPOINTS.contains(&method) && !OPT_IN_READ_ONLY_ENDPOINTS.contains(&method) {
+            return None;
+        }
+        request.as_object_mut()?.remove("id");
+
+        Some(canonicalize(request).to_string())
+    }
+
+    /// Look up a cached response, rewriting its `id` to match the request.
+    pub fn get(&self, key: &str, request_id: Value) -> Option<String> {
+        let entries = self.entries.lock().expect("mutex poisoned");
+        let (received_at, response) = entries.get(key)?;
+        if received_at.elapsed() >= self.ttl {
+            return None;
+        }
+
+        let mut response = response.clone();
+        response["id"] = request_id;
+        Some(response.to_string())
+    }
+
+    /// Cache a response. Error responses are not cached.
+    pub fn insert(&self, key: String, response: &str) {
+        let response: Value = match serde_json::from_str(response) {
+            Ok(response) => response,
+            Err(_) => return,
+        };
+        if response.get("error").is_some() {
+            return;
+        }
+
+        let mut entries = self.entries.lock().expect("mutex poisoned");
+        let ttl = self.ttl;
+        entries.retain(|_, (received_at, _)| received_at.elapsed() < ttl);
+        entries.insert(key, (Instant::now(), response));
+    }
+}
+
+/// Recursively sort object keys.
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let sorted: BTreeMap<String, Value> = map
+                .into_iter()
+                .map(|(key, value)| (key, canonicalize(value)))
+                .collect();
+            Value::Object(sorted.into_iter().collect())
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(canonicalize).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_disabled_by_zero_ttl() {
+        let cache = ResponseCache::new(Duration::ZERO);
+        let request = json!({"method": "get_network_status", "jsonrpc": "2.0", "id": 1});
+        assert_eq!(cache.key(&request.to_string()), None);
+    }
+
+    #[test]
+    fn test_only_read_only_methods_are_cached() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+
+        let request = json!({"method": "get_network_status", "jsonrpc": "2.0", "id": 1});
+        assert!(cache.key(&request.to_string()).is_some());
+
+        let request = json!({
+            "method": "build_and_submit_transaction",
+            "jsonrpc": "2.0",
+            "id": 1,
+        });
+        assert_eq!(cache.key(&request.to_string()), None);
+
+        assert_eq!(cache.key("not json"), None);
+    }
+
+    #[test]
+    fn test_hit_rewrites_id() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+
+        let request_1 = json!({"method": "get_network_status", "jsonrpc": "2.0", "id": 1});
+        let request_2 = json!({"id": "abc", "jsonrpc": "2.0", "method": "get_network_status"});
+        let key = cache.key(&request_1.to_string()).unwrap();
+        assert_eq!(cache.key(&request_2.to_string()).unwrap(), key);
+
+        assert_eq!(cache.get(&key, json!("abc")), None);
+
+        let response =
+            json!({"method": "get_network_status", "result": {}, "jsonrpc": "2.0", "id": 1});
+        cache.insert(key.clone(), &response.to_string());
+
+        let cached: Value = serde_json::from_str(&cache.get(&key, json!("abc")).unwrap()).unwrap();
+        assert_eq!(cached["id"], "abc");
+        assert_eq!(cached["result"], json!({}));
+    }
+
+    #[test]
+    fn test_errors_and_expired_entries_are_not_served() {
+        let cache = ResponseCache::new(Duration::from_millis(50));
+        let request = json!({"method": "get_account", "jsonrpc": "2.0", "id": 1});
+        let key = cache.key(&request.to_string()).unwrap();
+
+        let error = json!({"method": "get_account", "error": {}, "jsonrpc": "2.0", "id": 1});
+        cache.insert(key.clone(), &error.to_string());
+        assert_eq!(cache.get(&key, json!(1)), None);
+
+        let response = json!({"method": "get_account", "result": {}, "jsonrpc": "2.0", "id": 1});
+        cache.insert(key.clone(), &response.to_string());
+        assert!(cache.get(&key, json!(1)).is_some());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(&key, json!(1)), None);
+    }
+}
@@ -9,10 +9,12 @@
 
 #![feature(decl_macro)]
 
+mod cache;
 mod mirror_service;
 mod query;
 mod utils;
 
+use cache::ResponseCache;
 use mirror_service::MirrorService;
 
 use mc_common::logger::{create_app_logger, log, o, Logger};
@@ -24,20 +26,25 @@ use mc_util_grpc::{BuildInfoService, ConnectionUriGrpcioServer, HealthService};
 use mc_util_uri::{ConnectionUri, Uri, UriScheme};
 
 use grpcio::{ChannelBuilder, EnvBuilder, ServerBuilder};
-use query::QueryManager;
+use query::{PollSessionStatus, QueryManager, QueryManagerStatus};
 use rocket::{
     config::{Config as RocketConfig, TlsConfig},
     data::ToByteUnit,
+    get,
     http::Status,
+    outcome::Outcome,
     post,
+    request::FromRequest,
     response::Responder,
     routes,
-    tokio::io::AsyncReadExt,
+    serde::json::Json,
     Build, Data, Request, Response, Rocket,
 };
+use serde_derive::Serialize;
 use structopt::StructOpt;
+use subtle::ConstantTimeEq;
 
-use std::{net::IpAddr, str::FromStr, sync::Arc};
+use std::{net::IpAddr, str::FromStr, sync::Arc, time::Duration};
 
 pub type ClientUri = Uri<ClientUriScheme>;
 
@@ -77,14 +84,67 @@ pub struct Config {
     /// Allow using self-signed TLS certificate for GRPC connections.
     #[structopt(long)]
     pub allow_self_signed_tls: bool,
+
+    /// Maximum size, in bytes, of a client request body. Larger requests are
+    /// rejected with 413 Payload Too Large.
+    #[structopt(long, default_value = "2097152")]
+    pub max_request_bytes: u64,
+
+    /// How many milliseconds to cache responses to identical read-only
+    /// unencrypted requests for. Defaults to 0, which disables caching.
+    #[structopt(long, default_value = "0", parse(try_from_str=parse_duration_in_milliseconds))]
+    pub cache_ttl: Duration,
+
+    /// How many milliseconds a private side instance may hold a query before
+    /// it is assumed lost and handed to another private side instance. Must be
+    /// longer than the private side's `--full-service-timeout`, or a query
+    /// still being forwarded may be forwarded again by another instance.
+    #[structopt(long, default_value = "130000", parse(try_from_str=parse_duration_in_milliseconds))]
+    pub query_claim_timeout: Duration,
+
+    /// Key required in the X-ADMIN-KEY header of requests to the /admin
+    /// endpoints, which list and revoke private side poll sessions. If not
+    /// provided, the admin endpoints are disabled.
+    #[structopt(long, env = "MC_MIRROR_ADMIN_KEY", hide_env_values = true)]
+    pub admin_key: Option<String>,
 }
 
 /// State that is accessible by all rocket requests
 struct State {
     query_manager: QueryManager,
+    max_request_bytes: u64,
+    response_cache: ResponseCache,
+    admin_key: Option<String>,
     logger: Logger,
 }
 
+const ADMIN_KEY_HEADER: &str = "X-ADMIN-KEY";
+
+/// Ensures requests to the admin endpoints include the configured admin key.
+/// The endpoints are not found if no admin key is configured.
+struct AdminGuard {}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminGuard {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, (Status, ()), Status> {
+        let admin_key = match req.rocket().state::<State>() {
+            Some(State {
+                admin_key: Some(admin_key),
+                ..
+            }) => admin_key,
+            _ => return Outcome::Error((Status::NotFound, ())),
+        };
+        let client_key = req.headers().get_one(ADMIN_KEY_HEADER).unwrap_or_default();
+        if bool::from(client_key.as_bytes().ct_eq(admin_key.as_bytes())) {
+            Outcome::Success(AdminGuard {})
+        } else {
+            Outcome::Error((Status::Unauthorized, ()))
+        }
+    }
+}
+
 /// Sets the status of the response to 400 (Bad Request).
 #[derive(Debug, Clone, PartialEq)]
 pub struct BadRequest(pub String);
@@ -110,20 +170,123 @@ impl From<String> for BadRequest {
     }
 }
 
+/// Sets the status of the response to 413 (Payload Too Large).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadTooLarge(pub String);
+
+/// Sets the status code of the response to 413 Payload Too Large and include an
+/// error message in the response.
+impl<'r> Responder<'r, 'static> for PayloadTooLarge {
+    fn respond_to(self, req: &'r Request<'_>) -> Result<Response<'static>, Status> {
+        let mut build = Response::build();
+        build.merge(self.0.respond_to(req)?);
+
+        build.status(Status::PayloadTooLarge).ok()
+    }
+}
+
+/// Errors returned to clients of the mirror.
+#[derive(Debug, Clone, PartialEq, rocket::Responder)]
+pub enum RequestError {
+    BadRequest(BadRequest),
+    PayloadTooLarge(PayloadTooLarge),
+}
+impl From<&str> for RequestError {
+    fn from(src: &str) -> Self {
+        Self::BadRequest(src.into())
+    }
+}
+impl From<String> for RequestError {
+    fn from(src: String) -> Self {
+        Self::BadRequest(src.into())
+    }
+}
+
+/// Read a request body, rejecting it if it exceeds `max_request_bytes`.
+async fn read_request_data(
+    data: Data<'_>,
+    max_request_bytes: u64,
+    logger: &Logger,
+) -> Result<Vec<u8>, RequestError> {
+    match data.open(max_request_bytes.bytes()).into_bytes().await {
+        Ok(bytes) if bytes.is_complete() => Ok(bytes.into_inner()),
+        Ok(_) => {
+            let msg = format!("Request data exceeds the maximum of {max_request_bytes} bytes");
+            log::error!(logger, "{}", msg);
+            Err(RequestError::PayloadTooLarge(PayloadTooLarge(msg)))
+        }
+        Err(err) => {
+            let msg = format!("Could not read request data: {err}");
+            log::error!(logger, "{}", msg);
+            Err(msg.into())
+        }
+    }
+}
+
+/// Report the depth of the query queue, to surface back-pressure when the
+/// private side falls behind.
+#[get("/status")]
+fn status(state: &rocket::State<State>) -> Json<QueryManagerStatus> {
+    Json(state.query_manager.status())
+}
+
+/// List the private side poll sessions which have polled recently.
+#[get("/admin/sessions")]
+fn list_sessions(_admin: AdminGuard, state: &rocket::State<State>) -> Json<Vec<PollSessionStatus>> {
+    Json(state.query_manager.sessions())
+}
+
+#[derive(Debug, Serialize)]
+struct RevokeSessionResponse {
+    /// Queries the session had claimed, which were re-queued for other
+    /// sessions.
+    requeued_queries: usize,
+}
+
+/// Revoke a private side poll session, cutting it off from further queries.
+/// This is advisory, since a private side can reconnect with a new session id.
+#[post("/admin/sessions/<session_id>/revoke")]
+fn revoke_session(
+    _admin: AdminGuard,
+    state: &rocket::State<State>,
+    session_id: &str,
+) -> Result<Json<RevokeSessionResponse>, RequestError> {
+    let requeued_queries = state.query_manager.revoke_session(session_id)?;
+    log::warn!(
+        state.logger,
+        "Revoked session {}, re-queueing {} queries",
+        session_id,
+        requeued_queries
+    );
+    Ok(Json(RevokeSessionResponse { requeued_queries }))
+}
+
 #[post("/unencrypted-request", format = "json", data = "<request_data>")]
 async fn unencrypted_request(
     state: &rocket::State<State>,
     request_data: rocket::Data<'_>,
-) -> Result<String, BadRequest> {
-    let mut request = String::new();
-    let res = request_data
-        .open(2.mebibytes())
-        .read_to_string(&mut request)
-        .await;
-    if res.is_err() {
-        let msg = "Could not read request data for unencrypted request.";
-        log::error!(state.logger, "{}", msg,);
-        return Err(msg.into());
+) -> Result<String, RequestError> {
+    let request_bytes =
+        read_request_data(request_data, state.max_request_bytes, &state.logger).await?;
+    let request = String::from_utf8(request_bytes).map_err(|err| {
+        let msg = format!("Could not read request data for unencrypted request: {err}");
+        log::error!(state.logger, "{}", msg);
+        msg
+    })?;
+
+    let cache_key = state.response_cache.key(&request);
+    if let Some(cache_key) = cache_key.as_ref() {
+        let request_id = serde_json::from_str::<serde_json::Value>(&request)
+            .map(|json| json["id"].clone())
+            .unwrap_or_default();
+        if let Some(response) = state.response_cache.get(cache_key, request_id) {
+            log::debug!(
+                state.logger,
+                "UnencryptedRequest({}) served from cache",
+                request
+            );
+            return Ok(response);
+        }
     }
 
     log::debug!(state.logger, "Enqueueing UnencryptedRequest({})", &request);
@@ -135,7 +298,7 @@ async fn unencrypted_request(
     query_request.set_unencrypted_request(unencrypted_request);
 
     let query = state.query_manager.enqueue_query(query_request);
-    let query_response = query.wait()?;
+    let query_response = state.query_manager.wait(query)?;
 
     if query_response.has_error() {
         log::error!(
@@ -162,6 +325,11 @@ async fn unencrypted_request(
     );
 
     let response = query_response.get_unencrypted_response();
+    if let Some(cache_key) = cache_key {
+        state
+            .response_cache
+            .insert(cache_key, response.get_json_response());
+    }
     Ok(response.get_json_response().to_string())
 }
 
@@ -173,13 +341,8 @@ async fn unencrypted_request(
 async fn encrypted_request(
     state: &rocket::State<State>,
     data: Data<'_>,
-) -> Result<Vec<u8>, BadRequest> {
-    let mut payload = Vec::new();
-    if let Err(err) = data.open(2.mebibytes()).read_to_end(&mut payload).await {
-        let msg = format!("Could not read request data for unencrypted request: {err}");
-        log::error!(state.logger, "{}", msg);
-        return Err(msg.into());
-    }
+) -> Result<Vec<u8>, RequestError> {
+    let payload = read_request_data(data, state.max_request_bytes, &state.logger).await?;
     let payload_len = payload.len();
 
     let mut encrypted_request = EncryptedRequest::new();
@@ -194,7 +357,7 @@ async fn encrypted_request(
         payload_len,
     );
     let query = state.query_manager.enqueue_query(query_request);
-    let query_response = query.wait()?;
+    let query_response = state.query_manager.wait(query)?;
 
     if query_response.has_error() {
         log::error!(
@@ -243,7 +406,7 @@ async fn main() -> Result<(), rocket::Error> {
     // going to be an issue.
     global_logger_guard.cancel_reset();
 
-    let query_manager = QueryManager::default();
+    let query_manager = QueryManager::new(config.query_claim_timeout);
 
     log::info!(
         logger.clone(),
@@ -298,11 +461,8 @@ fn build_grpc_server(
 
 /// Builds the rocket instance given the configuration.
 fn build_rocket(config: Config, query_manager: QueryManager, logger: Logger) -> Rocket<Build> {
-    // Start the client-facing webserver.
-    if config.client_listen_uri.use_tls() {
-        panic!("Client-listening using TLS is currently not supported due to `ring` crate version compatibility issues.");
-    }
-
+    // Start the client-facing webserver. TLS is terminated by Rocket when the
+    // client listen URI is https.
     let tls_config = if config.client_listen_uri.use_tls() {
         Some(TlsConfig::from_paths(
             config
@@ -333,7 +493,107 @@ fn build_rocket(config: Config, query_manager: QueryManager, logger: Logger) ->
     rocket::custom(rocket_config)
         .manage(State {
             query_manager,
+            max_request_bytes: config.max_request_bytes,
+            response_cache: ResponseCache::new(config.cache_ttl),
+            admin_key: config.admin_key,
             logger,
         })
-        .mount("/", routes![unencrypted_request, encrypted_request])
+        .mount(
+            "/",
+            routes![
+                status,
+                list_sessions,
+                revoke_session,
+                unencrypted_request,
+                encrypted_request
+            ],
+        )
+}
+
+fn parse_duration_in_milliseconds(src: &str) -> Result<Duration, std::num::ParseIntError> {
+    Ok(Duration::from_millis(u64::from_str(src)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mc_common::logger::create_null_logger;
+    use std::net::TcpListener;
+
+    const TLS_CHAIN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test/tls/server.crt");
+    const TLS_KEY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test/tls/server.key");
+
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[rocket::async_test]
+    async fn test_client_server_negotiates_tls() {
+        let logger = create_null_logger();
+        let port = free_port();
+
+        let config = Config {
+            mirror_listen_uri: WalletServiceMirrorUri::from_str(
+                "insecure-wallet-service-mirror://127.0.0.1/",
+            )
+            .unwrap(),
+            client_listen_uri: ClientUri::from_str(&format!(
+                "https://127.0.0.1:{port}/?tls-chain={TLS_CHAIN}&tls-key={TLS_KEY}"
+            ))
+            .unwrap(),
+            num_workers: Some(1),
+            allow_self_signed_tls: true,
+            max_request_bytes: 1024,
+            cache_ttl: Duration::ZERO,
+            query_claim_timeout: Duration::from_secs(130),
+            admin_key: None,
+        };
+
+        let rocket = build_rocket(config, QueryManager::default(), logger)
+            .ignite()
+            .await
+            .expect("Failed igniting rocket");
+        let shutdown = rocket.shutdown();
+        rocket::tokio::spawn(rocket.launch());
+
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap();
+
+        // Wait for the server to start listening.
+        let mut response = None;
+        for _ in 0..50 {
+            match client
+                .get(format!("https://127.0.0.1:{port}/"))
+                .send()
+                .await
+            {
+                Ok(res) => {
+                    response = Some(res);
+                    break;
+                }
+                Err(_) => rocket::tokio::time::sleep(Duration::from_millis(100)).await,
+            }
+        }
+
+        // There is no route at "/", so a 404 means the TLS handshake succeeded
+        // and Rocket served the request.
+        let response = response.expect("Could not connect to the server over TLS");
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+        // Plaintext requests are not served on a TLS listener.
+        assert!(client
+            .get(format!("http://127.0.0.1:{port}/"))
+            .send()
+            .await
+            .map(|res| !res.status().is_success())
+            .unwrap_or(true));
+
+        shutdown.notify();
+    }
 }
@@ -1,5 +1,5 @@
 use crate::query::QueryManager;
-use grpcio::{RpcContext, RpcStatus, Service, UnarySink};
+use grpcio::{RpcContext, RpcStatus, RpcStatusCode, Service, UnarySink};
 use mc_common::logger::{log, Logger};
 use mc_full_service_mirror::{
     wallet_service_mirror_api::{PollRequest, PollResponse},
@@ -29,6 +29,29 @@ impl MirrorService {
     }
 
     fn poll_impl(&self, request: PollRequest, logger: &Logger) -> Result<PollResponse, RpcStatus> {
+        // Claims are tracked per session, so a poll without a session id could
+        // neither have its claims re-queued nor be revoked.
+        if request.get_session_id().is_empty() {
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::INVALID_ARGUMENT,
+                "Missing session id".to_string(),
+            ));
+        }
+
+        // Responses from a revoked session are not trusted, and its claims have
+        // already been re-queued.
+        if self.query_manager.is_revoked(request.get_session_id()) {
+            log::warn!(
+                logger,
+                "Refusing poll from revoked session {}",
+                request.get_session_id()
+            );
+            return Err(RpcStatus::with_message(
+                RpcStatusCode::PERMISSION_DENIED,
+                "Session revoked".to_string(),
+            ));
+        }
+
         // Go over any responses we may have received and attempt to resolve them.
         for (query_id, query_response) in request.get_query_responses().iter() {
             match self.query_manager.resolve_query(query_id, query_response) {
@@ -38,11 +61,14 @@ impl MirrorService {
         }
 
         // Return any queries we have received.
-        let pending_requests = self.query_manager.get_pending_requests();
+        let pending_requests = self
+            .query_manager
+            .get_pending_requests(request.get_session_id());
 
         log::debug!(
             logger,
-            "Polled with {} returned responses and {} new requests",
+            "Session {} polled with {} returned responses and {} new requests",
+            request.get_session_id(),
             request.get_query_responses().len(),
             pending_requests.len()
         );
@@ -5,10 +5,14 @@
 
 use mc_full_service_mirror::wallet_service_mirror_api::{QueryRequest, QueryResponse};
 use rand::RngCore;
+use serde_derive::Serialize;
 use std::{
     collections::HashMap,
-    sync::{Arc, Condvar, Mutex},
-    time::Duration,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
 /// The length of the randomly generated query id that is used to tie requests
@@ -18,10 +22,26 @@ const QUERY_ID_LEN: usize = 8;
 /// The maximum amount of time to wait for a query to complete.
 const QUERY_MAX_DURATION: Duration = Duration::from_secs(120);
 
+/// The default amount of time a private side poll session may hold a query
+/// before it is assumed lost and re-queued for another session. This is longer
+/// than the private side's default timeout for forwarding a query to
+/// full-service, so that a query is not sent to full-service a second time
+/// while the first attempt may still be in progress.
+pub const DEFAULT_CLAIM_TIMEOUT: Duration = Duration::from_secs(130);
+
+/// How long a revoked session's polls are refused for, so that the set of
+/// revoked sessions does not grow without bound.
+const REVOKED_SESSION_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
 /// The state held by each individual query.
 struct QueryInner {
     request: QueryRequest,
     response: Option<QueryResponse>,
+    created_at: Instant,
+
+    /// Set once the waiting client has given up, so that the query is not
+    /// handed out again.
+    abandoned: bool,
 }
 
 /// An individual query that can be asynchronously resolved and waited on.
@@ -37,6 +57,8 @@ impl Query {
             inner: Arc::new(Mutex::new(QueryInner {
                 request,
                 response: None,
+                created_at: Instant::now(),
+                abandoned: false,
             })),
             condvar: Arc::new(Condvar::new()),
         }
@@ -46,6 +68,18 @@ impl Query {
         self.inner.lock().expect("mutex poisoned").request.clone()
     }
 
+    pub fn age(&self) -> Duration {
+        self.inner
+            .lock()
+            .expect("mutex poisoned")
+            .created_at
+            .elapsed()
+    }
+
+    pub fn is_abandoned(&self) -> bool {
+        self.inner.lock().expect("mutex poisoned").abandoned
+    }
+
     pub fn resolve(&self, response: QueryResponse) {
         let mut inner = self.inner.lock().expect("mutex poisoned");
         inner.response = Some(response);
@@ -63,6 +97,7 @@ impl Query {
             .expect("waiting on condvar failed");
 
         if wait_result.timed_out() {
+            inner.abandoned = true;
             return Err("timeout".into());
         }
 
@@ -80,11 +115,66 @@ struct QueryManagerInner {
     /// side of the mirror.
     pending_requests: HashMap<String, Query>,
 
-    /// Map of query id -> query of queries that were resolved by the mirror.
-    pending_responses: HashMap<String, Query>,
+    /// Map of query id -> query of queries that were claimed by a private side
+    /// poll session and are awaiting its response.
+    pending_responses: HashMap<String, ClaimedQuery>,
+
+    /// Map of session id -> when the private side poll session last polled.
+    sessions: HashMap<String, Instant>,
+
+    /// Map of session id -> when an operator revoked the session. Polls from
+    /// these sessions are refused until REVOKED_SESSION_RETENTION has passed.
+    revoked_sessions: HashMap<String, Instant>,
+}
+
+/// A query handed out to a private side poll session.
+struct ClaimedQuery {
+    query: Query,
+    session_id: String,
+    claimed_at: Instant,
+}
+
+/// A snapshot of the query manager's queue, for monitoring back-pressure from
+/// the private side of the mirror.
+#[derive(Debug, Serialize)]
+pub struct QueryManagerStatus {
+    /// Queries waiting to be picked up by the private side.
+    pub queued_queries: usize,
+
+    /// Queries picked up by the private side and awaiting a response.
+    pub in_flight_queries: usize,
+
+    /// How long the oldest queued or in-flight query has been waiting.
+    pub oldest_query_age_ms: u64,
+
+    /// Queries which received a response from the private side.
+    pub processed_queries: u64,
+
+    /// Queries which received an error response or timed out.
+    pub errored_queries: u64,
+}
+
+/// A private side poll session which has polled recently.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PollSessionStatus {
+    pub session_id: String,
+
+    /// How long ago the session last polled.
+    pub last_seen_ms_ago: u64,
+
+    /// Queries claimed by the session and awaiting its response.
+    pub in_flight_queries: usize,
 }
 
 impl QueryManagerInner {
+    fn is_revoked(&self, session_id: &str) -> bool {
+        self.revoked_sessions
+            .get(session_id)
+            .map_or(false, |revoked_at| {
+                revoked_at.elapsed() < REVOKED_SESSION_RETENTION
+            })
+    }
+
     pub fn generate_query_id(&self) -> String {
         let mut rng = rand::thread_rng();
 
@@ -109,23 +199,48 @@ impl QueryManagerInner {
     }
 }
 
+/// Hands queries out to the private side of the mirror.
+///
+/// Multiple private side instances may poll the same public side. Each query
+/// is claimed by exactly one poll session, identified by the session id the
+/// private side sends with each poll. Claimed queries are re-queued if the
+/// claiming session polls again without answering them, or does not answer
+/// within the claim timeout (e.g. because it went away).
+///
+/// Session ids are chosen by the private side and are not authenticated, so
+/// revoking a session is advisory: it cuts off a stuck or stale private side
+/// instance, but a private side that reconnects with a new session id is
+/// served again. Access to the mirror's GRPC port must be restricted to
+/// trusted private side instances by other means.
 #[derive(Clone)]
 pub struct QueryManager {
     inner: Arc<Mutex<QueryManagerInner>>,
+    claim_timeout: Duration,
+    processed_queries: Arc<AtomicU64>,
+    errored_queries: Arc<AtomicU64>,
 }
 
 impl Default for QueryManager {
     fn default() -> Self {
+        Self::new(DEFAULT_CLAIM_TIMEOUT)
+    }
+}
+
+impl QueryManager {
+    pub fn new(claim_timeout: Duration) -> Self {
         Self {
             inner: Arc::new(Mutex::new(QueryManagerInner {
                 pending_requests: HashMap::new(),
                 pending_responses: HashMap::new(),
+                sessions: HashMap::new(),
+                revoked_sessions: HashMap::new(),
             })),
+            claim_timeout,
+            processed_queries: Arc::new(AtomicU64::new(0)),
+            errored_queries: Arc::new(AtomicU64::new(0)),
         }
     }
-}
 
-impl QueryManager {
     pub fn enqueue_query(&self, request: QueryRequest) -> Query {
         let mut inner = self.inner.lock().expect("mutex poisoned");
         let query_id = inner.generate_query_id();
@@ -134,30 +249,290 @@ impl QueryManager {
         query
     }
 
-    pub fn get_pending_requests(&self) -> HashMap<String, QueryRequest> {
+    /// Claim all queued queries for the given poll session.
+    ///
+    /// This should be called after resolving the responses included in the
+    /// session's poll, since any queries the session previously claimed but
+    /// did not answer are assumed lost and re-queued.
+    pub fn get_pending_requests(&self, session_id: &str) -> HashMap<String, QueryRequest> {
         let mut inner = self.inner.lock().expect("mutex poisoned");
+        if inner.is_revoked(session_id) {
+            return HashMap::new();
+        }
+
+        // Forget sessions which have not polled within the claim timeout.
+        let claim_timeout = self.claim_timeout;
+        inner
+            .sessions
+            .retain(|_, last_seen| last_seen.elapsed() < claim_timeout);
+        inner
+            .sessions
+            .insert(session_id.to_string(), Instant::now());
+
+        // Drop queries whose clients have given up waiting.
+        inner
+            .pending_requests
+            .retain(|_, query| !query.is_abandoned());
+        inner
+            .pending_responses
+            .retain(|_, claimed| !claimed.query.is_abandoned());
+
+        // Re-queue lapsed claims.
+        let lapsed_query_ids: Vec<String> = inner
+            .pending_responses
+            .iter()
+            .filter(|(_, claimed)| {
+                claimed.session_id == session_id
+                    || claimed.claimed_at.elapsed() >= self.claim_timeout
+            })
+            .map(|(query_id, _)| query_id.clone())
+            .collect();
+        for query_id in lapsed_query_ids {
+            if let Some(claimed) = inner.pending_responses.remove(&query_id) {
+                inner.pending_requests.insert(query_id, claimed.query);
+            }
+        }
+
         let mut pending_requests = HashMap::new();
-        let mut pending_responses = HashMap::new();
+        let mut claimed_queries = HashMap::new();
 
         for (query_id, query) in inner.pending_requests.drain() {
             pending_requests.insert(query_id.clone(), query.request());
-            pending_responses.insert(query_id, query);
+            claimed_queries.insert(
+                query_id,
+                ClaimedQuery {
+                    query,
+                    session_id: session_id.to_string(),
+                    claimed_at: Instant::now(),
+                },
+            );
         }
 
-        for (query_id, query) in pending_responses.drain() {
-            inner.pending_responses.insert(query_id, query);
-        }
+        inner.pending_responses.extend(claimed_queries);
 
         pending_requests
     }
 
     pub fn resolve_query(&self, query_id: &str, response: &QueryResponse) -> Result<(), String> {
         let mut inner = self.inner.lock().expect("mutex poisoned");
-        let query = inner
-            .pending_responses
-            .remove(query_id)
-            .ok_or_else(|| format!("Unknown query id {query_id}"))?;
+        // A query may have been re-queued after its claim lapsed, in which case
+        // the first response received wins.
+        let query = match inner.pending_responses.remove(query_id) {
+            Some(claimed) => claimed.query,
+            None => inner
+                .pending_requests
+                .remove(query_id)
+                .ok_or_else(|| format!("Unknown query id {query_id}"))?,
+        };
         query.resolve(response.clone());
+
+        self.processed_queries.fetch_add(1, Ordering::SeqCst);
+        if response.has_error() {
+            self.errored_queries.fetch_add(1, Ordering::SeqCst);
+        }
         Ok(())
     }
+
+    /// Wait for a query to be resolved, counting it as errored if it times out.
+    pub fn wait(&self, query: Query) -> Result<QueryResponse, String> {
+        query.wait().map_err(|err| {
+            self.errored_queries.fetch_add(1, Ordering::SeqCst);
+            err
+        })
+    }
+
+    /// Whether an operator has revoked the given poll session.
+    pub fn is_revoked(&self, session_id: &str) -> bool {
+        self.inner
+            .lock()
+            .expect("mutex poisoned")
+            .is_revoked(session_id)
+    }
+
+    /// List the poll sessions which have polled within the claim timeout,
+    /// most recently seen first.
+    pub fn sessions(&self) -> Vec<PollSessionStatus> {
+        let mut inner = self.inner.lock().expect("mutex poisoned");
+        let claim_timeout = self.claim_timeout;
+        inner
+            .sessions
+            .retain(|_, last_seen| last_seen.elapsed() < claim_timeout);
+
+        let mut sessions: Vec<PollSessionStatus> = inner
+            .sessions
+            .iter()
+            .map(|(session_id, last_seen)| PollSessionStatus {
+                session_id: session_id.clone(),
+                last_seen_ms_ago: last_seen.elapsed().as_millis() as u64,
+                in_flight_queries: inner
+                    .pending_responses
+                    .values()
+                    .filter(|claimed| &claimed.session_id == session_id)
+                    .count(),
+            })
+            .collect();
+        sessions.sort_by_key(|session| session.last_seen_ms_ago);
+        sessions
+    }
+
+    /// Revoke a poll session, so that its polls are refused for
+    /// REVOKED_SESSION_RETENTION and the queries it claimed are re-queued for
+    /// other sessions. Returns the number of queries re-queued.
+    ///
+    /// This is advisory, see [QueryManager].
+    pub fn revoke_session(&self, session_id: &str) -> Result<usize, String> {
+        let mut inner = self.inner.lock().expect("mutex poisoned");
+        if inner.sessions.remove(session_id).is_none() {
+            return Err(format!("Unknown session id {session_id}"));
+        }
+        inner
+            .revoked_sessions
+            .retain(|_, revoked_at| revoked_at.elapsed() < REVOKED_SESSION_RETENTION);
+        inner
+            .revoked_sessions
+            .insert(session_id.to_string(), Instant::now());
+
+        let claimed_query_ids: Vec<String> = inner
+            .pending_responses
+            .iter()
+            .filter(|(_, claimed)| claimed.session_id == session_id)
+            .map(|(query_id, _)| query_id.clone())
+            .collect();
+        for query_id in &claimed_query_ids {
+            if let Some(claimed) = inner.pending_responses.remove(query_id) {
+                inner
+                    .pending_requests
+                    .insert(query_id.clone(), claimed.query);
+            }
+        }
+        Ok(claimed_query_ids.len())
+    }
+
+    pub fn status(&self) -> QueryManagerStatus {
+        let inner = self.inner.lock().expect("mutex poisoned");
+        let oldest_query_age = inner
+            .pending_requests
+            .values()
+            .chain(
+                inner
+                    .pending_responses
+                    .values()
+                    .map(|claimed| &claimed.query),
+            )
+            .map(Query::age)
+            .max()
+            .unwrap_or_default();
+
+        QueryManagerStatus {
+            queued_queries: inner.pending_requests.len(),
+            in_flight_queries: inner.pending_responses.len(),
+            oldest_query_age_ms: oldest_query_age.as_millis() as u64,
+            processed_queries: self.processed_queries.load(Ordering::SeqCst),
+            errored_queries: self.errored_queries.load(Ordering::SeqCst),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_tracks_queue_depth_and_outcomes() {
+        let query_manager = QueryManager::default();
+        let status = query_manager.status();
+        assert_eq!(status.queued_queries, 0);
+        assert_eq!(status.in_flight_queries, 0);
+        assert_eq!(status.oldest_query_age_ms, 0);
+
+        query_manager.enqueue_query(QueryRequest::new());
+        query_manager.enqueue_query(QueryRequest::new());
+        assert_eq!(query_manager.status().queued_queries, 2);
+
+        let pending_requests = query_manager.get_pending_requests("session");
+        let status = query_manager.status();
+        assert_eq!(status.queued_queries, 0);
+        assert_eq!(status.in_flight_queries, 2);
+
+        let mut query_ids = pending_requests.keys();
+        query_manager
+            .resolve_query(query_ids.next().unwrap(), &QueryResponse::new())
+            .unwrap();
+        let mut error_response = QueryResponse::new();
+        error_response.set_error("failed".to_string());
+        query_manager
+            .resolve_query(query_ids.next().unwrap(), &error_response)
+            .unwrap();
+
+        let status = query_manager.status();
+        assert_eq!(status.in_flight_queries, 0);
+        assert_eq!(status.processed_queries, 2);
+        assert_eq!(status.errored_queries, 1);
+    }
+
+    #[test]
+    fn test_queries_are_claimed_by_one_session() {
+        let query_manager = QueryManager::default();
+        query_manager.enqueue_query(QueryRequest::new());
+
+        assert_eq!(query_manager.get_pending_requests("a").len(), 1);
+        assert_eq!(query_manager.get_pending_requests("b").len(), 0);
+        assert_eq!(query_manager.status().in_flight_queries, 1);
+    }
+
+    #[test]
+    fn test_unanswered_queries_are_requeued() {
+        let query_manager = QueryManager::new(Duration::from_millis(50));
+        query_manager.enqueue_query(QueryRequest::new());
+        query_manager.enqueue_query(QueryRequest::new());
+
+        let claimed_by_a = query_manager.get_pending_requests("a");
+        assert_eq!(claimed_by_a.len(), 2);
+
+        // Session a answers one query and then goes away.
+        query_manager
+            .resolve_query(claimed_by_a.keys().next().unwrap(), &QueryResponse::new())
+            .unwrap();
+
+        // Session b cannot claim a's query until the claim times out.
+        assert_eq!(query_manager.get_pending_requests("b").len(), 0);
+        std::thread::sleep(Duration::from_millis(60));
+        let claimed_by_b = query_manager.get_pending_requests("b");
+        assert_eq!(claimed_by_b.len(), 1);
+        assert!(claimed_by_a.contains_key(claimed_by_b.keys().next().unwrap()));
+
+        // A session which polls again without answering its claims gets them
+        // handed back.
+        assert_eq!(query_manager.get_pending_requests("b").len(), 1);
+    }
+
+    #[test]
+    fn test_revoked_sessions_are_cut_off() {
+        let query_manager = QueryManager::default();
+        query_manager.enqueue_query(QueryRequest::new());
+        query_manager.enqueue_query(QueryRequest::new());
+
+        assert_eq!(query_manager.get_pending_requests("a").len(), 2);
+        assert_eq!(query_manager.get_pending_requests("b").len(), 0);
+
+        let sessions = query_manager.sessions();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session_id, "b");
+        assert_eq!(sessions[0].in_flight_queries, 0);
+        assert_eq!(sessions[1].session_id, "a");
+        assert_eq!(sessions[1].in_flight_queries, 2);
+
+        // Revoking a session re-queues its claims for the others.
+        assert_eq!(query_manager.revoke_session("a"), Ok(2));
+        assert!(query_manager.is_revoked("a"));
+        assert!(query_manager.revoke_session("a").is_err());
+        assert_eq!(query_manager.status().queued_queries, 2);
+
+        // And it is not handed any more queries.
+        assert_eq!(query_manager.get_pending_requests("a").len(), 0);
+        assert_eq!(query_manager.get_pending_requests("b").len(), 2);
+        let sessions = query_manager.sessions();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].session_id, "b");
+    }
 }
@@ -17,6 +17,15 @@ const logger = pino({ level: logLevel }, pretty())
 
 const KEY_SIZE = 512
 
+// Requests are sent in the version 1 hybrid format: a random AES-256-GCM key,
+// encrypted with the mirror's RSA public key, followed by the payload
+// encrypted under that AES key. Responses come back under the same AES key.
+// The header is authenticated as additional data (see mirror/src/private/crypto.rs).
+const HYBRID_V1_HEADER = Buffer.from('MCH\x01', 'latin1')
+const AES_KEY_LEN = 32
+const AES_GCM_NONCE_LEN = 12
+const AES_GCM_TAG_LEN = 16
+
 class resError extends Error {
   constructor (message) {
     super(message)
@@ -84,13 +93,17 @@ function sendRequestEncrypted (url, path, keyFile, msg) {
   }
 
   // Ensure the key is 4096 bits (outputs 512-byte chunks).
-  const testData = encrypt(key, [1, 2, 3])
-  if (testData.length !== KEY_SIZE) {
-    return Promise.reject(new Error(`Key is not 4096-bit, encrypted output chunk size returned was ${testData.length}`))
+  const aesKey = crypto.randomBytes(AES_KEY_LEN)
+  const encryptedAesKey = crypto.publicEncrypt({
+    key,
+    padding: crypto.constants.RSA_PKCS1_OAEP_PADDING
+  }, aesKey)
+  if (encryptedAesKey.length !== KEY_SIZE) {
+    return Promise.reject(new Error(`Key is not 4096-bit, encrypted output chunk size returned was ${encryptedAesKey.length}`))
   }
 
   // Prepare request
-  const encryptedMsg = encrypt(key, msg)
+  const encryptedMsg = Buffer.concat([HYBRID_V1_HEADER, encryptedAesKey, encrypt(aesKey, Buffer.from(msg))])
 
   // Send request to server
   const params = {
@@ -103,7 +116,7 @@ function sendRequestEncrypted (url, path, keyFile, msg) {
     }
   }
   const processSuccessfulResponse = (buf) => {
-    const result = decrypt(key, Buffer.concat(buf)).toString()
+    const result = decrypt(aesKey, Buffer.concat(buf)).toString()
     const resultJSON = JSON.parse(result)
     const log = JSON.stringify({ response: resultJSON }, null, 4)
     logger.debug(log)
@@ -140,40 +153,27 @@ function sendRequestUnencrypted (url, path, msg) {
 }
 
 // Crypto utilities
-function encrypt (key, buf) {
-  const res = []
-
-  // Each encrypted chunk must be no longer than the length of the public modulus minus padding size.
-  // PKCS1 is 11 bytes of padding (which is also defined as PKCS1_PADDING_LEN in the rust code).
-  const MAX_CHUNK_SIZE = KEY_SIZE - 11
-
-  while (buf.length > 0) {
-    const data = buf.slice(0, MAX_CHUNK_SIZE)
-    buf = buf.slice(data.length)
-
-    res.push(crypto.publicEncrypt({
-      key,
-      padding: crypto.constants.RSA_PKCS1_PADDING
-    }, Buffer.from(data)))
-  }
-
-  return Buffer.concat(res)
+function encrypt (aesKey, buf) {
+  const nonce = crypto.randomBytes(AES_GCM_NONCE_LEN)
+  const cipher = crypto.createCipheriv('aes-256-gcm', aesKey, nonce)
+  cipher.setAAD(HYBRID_V1_HEADER)
+  const ciphertext = Buffer.concat([cipher.update(buf), cipher.final()])
+  return Buffer.concat([nonce, ciphertext, cipher.getAuthTag()])
 }
 
-function decrypt (key, buf) {
-  const res = []
-
-  while (buf.length > 0) {
-    const data = buf.slice(0, KEY_SIZE)
-    buf = buf.slice(data.length)
-
-    res.push(crypto.publicDecrypt({
-      key,
-      padding: crypto.constants.RSA_PKCS1_PADDING
-    }, Buffer.from(data)))
+function decrypt (aesKey, buf) {
+  if (!buf.subarray(0, HYBRID_V1_HEADER.length).equals(HYBRID_V1_HEADER)) {
+    throw new Error('Response is not in the hybrid format')
   }
-
-  return Buffer.concat(res)
+  const body = buf.subarray(HYBRID_V1_HEADER.length)
+  const nonce = body.subarray(0, AES_GCM_NONCE_LEN)
+  const ciphertext = body.subarray(AES_GCM_NONCE_LEN, body.length - AES_GCM_TAG_LEN)
+  const tag = body.subarray(body.length - AES_GCM_TAG_LEN)
+
+  const decipher = crypto.createDecipheriv('aes-256-gcm', aesKey, nonce)
+  decipher.setAAD(HYBRID_V1_HEADER)
+  decipher.setAuthTag(tag)
+  return Buffer.concat([decipher.update(ciphertext), decipher.final()])
 }
 
 module.exports = {
@@ -7,6 +7,7 @@ use clap::Parser;
 use mc_common::logger::{create_app_logger, log, o};
 use mc_core::{account::Account, slip10::Slip10KeyGenerator};
 use mc_crypto_ring_signature_signer::LocalRingSigner;
+use mc_full_service::util::encrypted_secrets::EncryptedSecrets;
 use mc_signer::service;
 use mc_transaction_core::AccountKey;
 use mc_transaction_signer::{read_input, types::AccountInfo, write_output, Operations};
@@ -18,6 +19,11 @@ struct Args {
     #[clap(long, short, default_value = "mc_account_secrets.json")]
     account_secrets_file: String,
 
+    /// Passphrase used to encrypt the account secrets file on creation, and
+    /// to decrypt it when it is encrypted
+    #[clap(long, env = "MC_SIGNER_PASSPHRASE", hide_env_values = true)]
+    passphrase: Option<String>,
+
     #[command(subcommand)]
     action: Actions,
 }
@@ -29,6 +35,10 @@ enum Actions {
         /// File name for account secrets to be written to
         #[clap(long)]
         output: String,
+
+        /// Encrypt the account secrets with the passphrase
+        #[clap(long, requires = "passphrase")]
+        encrypt: bool,
     },
 
     // Implement shared signer commands
@@ -42,6 +52,31 @@ struct AccountSecrets {
     account_info: AccountInfo,
 }
 
+/// An account secrets file, which may be encrypted with a passphrase
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum AccountSecretsFile {
+    Encrypted(EncryptedSecrets),
+    Plaintext(AccountSecrets),
+}
+
+impl AccountSecretsFile {
+    fn into_secrets(self, passphrase: Option<&str>) -> anyhow::Result<AccountSecrets> {
+        match self {
+            Self::Encrypted(encrypted) => {
+                let passphrase = passphrase.ok_or_else(|| {
+                    anyhow::anyhow!("account secrets file is encrypted, a passphrase is required")
+                })?;
+                let plaintext = encrypted
+                    .decrypt(passphrase)
+                    .map_err(|e| anyhow::anyhow!("{}", e))?;
+                Ok(serde_json::from_slice(&plaintext)?)
+            }
+            Self::Plaintext(secrets) => Ok(secrets),
+        }
+    }
+}
+
 fn main() -> anyhow::Result<()> {
     // Parse command line arguments
     let args = Args::parse();
@@ -50,7 +85,7 @@ fn main() -> anyhow::Result<()> {
 
     // Run commands
     match &args.action {
-        Actions::Create { output } => {
+        Actions::Create { output, encrypt } => {
             let (mnemonic, account_info) = service::create_account();
             let account_secrets = AccountSecrets {
                 mnemonic: mnemonic.to_string(),
@@ -66,13 +101,24 @@ fn main() -> anyhow::Result<()> {
             }
 
             // Otherwise write out new secrets
-            write_output(output, &account_secrets)?;
+            let account_secrets_file = match (encrypt, &args.passphrase) {
+                (true, Some(passphrase)) => {
+                    let plaintext = serde_json::to_vec(&account_secrets)?;
+                    AccountSecretsFile::Encrypted(
+                        EncryptedSecrets::encrypt(&plaintext, passphrase)
+                            .map_err(|e| anyhow::anyhow!("{}", e))?,
+                    )
+                }
+                _ => AccountSecretsFile::Plaintext(account_secrets),
+            };
+            write_output(output, &account_secrets_file)?;
 
             log::info!(logger, "Account secrets written to '{}'", output);
         }
         Actions::Signer(operation) => {
             // Load account secrets
-            let secrets: AccountSecrets = read_input(&args.account_secrets_file)?;
+            let secrets = read_input::<AccountSecretsFile>(&args.account_secrets_file)?
+                .into_secrets(args.passphrase.as_deref())?;
             let mnemonic = Mnemonic::from_phrase(&secrets.mnemonic, Language::English)?;
 
             // Perform SLIP-0010 derivation
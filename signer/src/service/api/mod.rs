@@ -39,6 +39,7 @@ pub fn signer_service_api(
         error: None,
         jsonrpc: "2.0".to_string(),
         id: command.0.id,
+        elapsed_ms: None,
     };
 
     let request = match JsonCommandRequest::try_from(&req) {
@@ -75,14 +76,37 @@ fn signer_service_api_inner(command: JsonCommandRequest) -> Result<JsonCommandRe
         JsonCommandRequest::get_account {
             mnemonic,
             bip39_entropy,
+            account_index,
+            fog_info,
         } => match (mnemonic, bip39_entropy) {
             (Some(mnemonic), None) => {
-                let account_info = service::get_account_by_mnemonic(&mnemonic)?;
-                JsonCommandResponse::get_account { account_info }
+                let account_index = parse_account_index(account_index)?;
+                let account_info = service::get_account_by_mnemonic(&mnemonic, account_index)?;
+                let address_hashes = service::get_address_hashes_by_mnemonic(
+                    &mnemonic,
+                    account_index,
+                    fog_info.as_ref(),
+                )?;
+                JsonCommandResponse::get_account {
+                    account_info,
+                    default_address_hash: hex::encode(address_hashes.default_address_hash.as_ref()),
+                    change_address_hash: hex::encode(address_hashes.change_address_hash.as_ref()),
+                }
             }
             (None, Some(bip39_entropy)) => {
-                let account_info = service::get_account_by_bip39_entropy(&bip39_entropy)?;
-                JsonCommandResponse::get_account { account_info }
+                let account_index = parse_account_index(account_index)?;
+                let account_info =
+                    service::get_account_by_bip39_entropy(&bip39_entropy, account_index)?;
+                let address_hashes = service::get_address_hashes_by_bip39_entropy(
+                    &bip39_entropy,
+                    account_index,
+                    fog_info.as_ref(),
+                )?;
+                JsonCommandResponse::get_account {
+                    account_info,
+                    default_address_hash: hex::encode(address_hashes.default_address_hash.as_ref()),
+                    change_address_hash: hex::encode(address_hashes.change_address_hash.as_ref()),
+                }
             }
             (None, None) => {
                 return Err(anyhow!("Either mnemonic or bip39_entropy must be provided"));
@@ -96,11 +120,13 @@ fn signer_service_api_inner(command: JsonCommandRequest) -> Result<JsonCommandRe
         JsonCommandRequest::sign_tx {
             mnemonic,
             bip39_entropy,
+            account_index,
             unsigned_tx_proposal,
         } => match (mnemonic, bip39_entropy) {
             (Some(mnemonic), None) => {
                 let signed_tx = service::sign_tx_with_mnemonic(
                     &mnemonic,
+                    parse_account_index(account_index)?,
                     (&unsigned_tx_proposal)
                         .try_into()
                         .map_err(|e: String| anyhow!(e))?,
@@ -112,6 +138,7 @@ fn signer_service_api_inner(command: JsonCommandRequest) -> Result<JsonCommandRe
             (None, Some(bip39_entropy)) => {
                 let signed_tx = service::sign_tx_with_bip39_entropy(
                     &bip39_entropy,
+                    parse_account_index(account_index)?,
                     (&unsigned_tx_proposal)
                         .try_into()
                         .map_err(|e: String| anyhow!(e))?,
@@ -132,15 +159,23 @@ fn signer_service_api_inner(command: JsonCommandRequest) -> Result<JsonCommandRe
         JsonCommandRequest::sync_txos {
             mnemonic,
             bip39_entropy,
+            account_index,
             txos_unsynced,
         } => match (mnemonic, bip39_entropy) {
             (Some(mnemonic), None) => {
-                let txos_synced = service::sync_txos_by_mnemonic(&mnemonic, txos_unsynced)?;
+                let txos_synced = service::sync_txos_by_mnemonic(
+                    &mnemonic,
+                    parse_account_index(account_index)?,
+                    txos_unsynced,
+                )?;
                 JsonCommandResponse::sync_txos { txos_synced }
             }
             (None, Some(bip39_entropy)) => {
-                let txos_synced =
-                    service::sync_txos_by_bip39_entropy(&bip39_entropy, txos_unsynced)?;
+                let txos_synced = service::sync_txos_by_bip39_entropy(
+                    &bip39_entropy,
+                    parse_account_index(account_index)?,
+                    txos_unsynced,
+                )?;
                 JsonCommandResponse::sync_txos { txos_synced }
             }
             (None, None) => {
@@ -156,3 +191,12 @@ fn signer_service_api_inner(command: JsonCommandRequest) -> Result<JsonCommandRe
 
     Ok(response)
 }
+
+/// Parse the SLIP-0010 account index of a request, defaulting to 0.
+fn parse_account_index(account_index: Option<String>) -> Result<u32> {
+    account_index
+        .map(|account_index| account_index.parse::<u32>())
+        .transpose()
+        .map_err(|e| anyhow!("Invalid account_index: {e}"))
+        .map(Option::unwrap_or_default)
+}
@@ -1,7 +1,8 @@
 // Copyright (c) 2020-2023 MobileCoin Inc.
 
 use mc_full_service::json_rpc::{
-    json_rpc_request::JsonRPCRequest, v2::models::tx_proposal::UnsignedTxProposal,
+    json_rpc_request::JsonRPCRequest,
+    v2::models::{account_key::FogInfo, tx_proposal::UnsignedTxProposal},
 };
 use mc_transaction_signer::types::TxoUnsynced;
 use serde::{Deserialize, Serialize};
@@ -35,15 +36,19 @@ pub enum JsonCommandRequest {
     get_account {
         mnemonic: Option<String>,
         bip39_entropy: Option<String>,
+        account_index: Option<String>,
+        fog_info: Option<FogInfo>,
     },
     sign_tx {
         mnemonic: Option<String>,
         bip39_entropy: Option<String>,
+        account_index: Option<String>,
         unsigned_tx_proposal: UnsignedTxProposal,
     },
     sync_txos {
         mnemonic: Option<String>,
         bip39_entropy: Option<String>,
+        account_index: Option<String>,
         txos_unsynced: Vec<TxoUnsynced>,
     },
 }
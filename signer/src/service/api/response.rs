@@ -19,6 +19,8 @@ pub enum JsonCommandResponse {
     },
     get_account {
         account_info: AccountInfo,
+        default_address_hash: String,
+        change_address_hash: String,
     },
     sign_tx {
         tx_proposal: TxProposal,
@@ -1,10 +1,15 @@
 // Copyright (c) 2020-2023 MobileCoin Inc.
 
 use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine};
 use bip39::{Language, Mnemonic, MnemonicType};
 use mc_account_keys::AccountKey;
 use mc_core::{account::Account, slip10::Slip10KeyGenerator};
-use mc_full_service::service::models::tx_proposal::{TxProposal, UnsignedTxProposal};
+use mc_full_service::{
+    json_rpc::v2::models::account_key::FogInfo,
+    service::models::tx_proposal::{TxProposal, UnsignedTxProposal},
+};
+use mc_transaction_extra::ShortAddressHash;
 use mc_transaction_signer::{
     traits::KeyImageComputer,
     types::{AccountInfo, TxoSynced, TxoUnsynced},
@@ -14,7 +19,7 @@ pub mod api;
 
 pub fn create_account() -> (Mnemonic, AccountInfo) {
     let mnemonic = Mnemonic::new(MnemonicType::Words24, Language::English);
-    let account = get_account_from_mnemonic(mnemonic.clone());
+    let account = get_account_from_mnemonic(mnemonic.clone(), 0);
     let account_info = AccountInfo {
         view_private: account.view_private_key().clone(),
         spend_public: account.spend_public_key(),
@@ -24,44 +29,110 @@ pub fn create_account() -> (Mnemonic, AccountInfo) {
     (mnemonic, account_info)
 }
 
-pub fn get_account_by_mnemonic(mnemonic: &str) -> Result<AccountInfo> {
+pub fn get_account_by_mnemonic(mnemonic: &str, account_index: u32) -> Result<AccountInfo> {
     let mnemonic = Mnemonic::from_phrase(mnemonic, Language::English)?;
-    get_account(mnemonic)
+    get_account(mnemonic, account_index)
 }
 
-pub fn get_account_by_bip39_entropy(bip39_entropy: &str) -> Result<AccountInfo> {
+pub fn get_account_by_bip39_entropy(
+    bip39_entropy: &str,
+    account_index: u32,
+) -> Result<AccountInfo> {
     let mut entropy = [0u8; 32];
     hex::decode_to_slice(bip39_entropy, &mut entropy)?;
     let mnemonic = Mnemonic::from_entropy(&entropy, Language::English)?;
-    get_account(mnemonic)
+    get_account(mnemonic, account_index)
 }
 
-fn get_account(mnemonic: Mnemonic) -> Result<AccountInfo> {
-    let account = get_account_from_mnemonic(mnemonic);
+fn get_account(mnemonic: Mnemonic, account_index: u32) -> Result<AccountInfo> {
+    let account = get_account_from_mnemonic(mnemonic, account_index);
 
     Ok(AccountInfo {
         view_private: account.view_private_key().clone(),
         spend_public: account.spend_public_key(),
-        account_index: 0,
+        account_index,
+    })
+}
+
+/// The short address hashes of an account's default and change subaddresses,
+/// used to match incoming authenticated sender memos.
+pub struct AddressHashes {
+    pub default_address_hash: ShortAddressHash,
+    pub change_address_hash: ShortAddressHash,
+}
+
+pub fn get_address_hashes_by_mnemonic(
+    mnemonic: &str,
+    account_index: u32,
+    fog_info: Option<&FogInfo>,
+) -> Result<AddressHashes> {
+    let mnemonic = Mnemonic::from_phrase(mnemonic, Language::English)?;
+    get_address_hashes(mnemonic, account_index, fog_info)
+}
+
+pub fn get_address_hashes_by_bip39_entropy(
+    bip39_entropy: &str,
+    account_index: u32,
+    fog_info: Option<&FogInfo>,
+) -> Result<AddressHashes> {
+    let mut entropy = [0u8; 32];
+    hex::decode_to_slice(bip39_entropy, &mut entropy)?;
+    let mnemonic = Mnemonic::from_entropy(&entropy, Language::English)?;
+    get_address_hashes(mnemonic, account_index, fog_info)
+}
+
+fn get_address_hashes(
+    mnemonic: Mnemonic,
+    account_index: u32,
+    fog_info: Option<&FogInfo>,
+) -> Result<AddressHashes> {
+    let account = get_account_from_mnemonic(mnemonic, account_index);
+    let account_key = match fog_info {
+        Some(fog_info) => AccountKey::new_with_fog(
+            account.spend_private_key().as_ref(),
+            account.view_private_key().as_ref(),
+            fog_info.report_url.clone(),
+            fog_info.report_id.clone().unwrap_or_default(),
+            general_purpose::STANDARD.decode(&fog_info.authority_spki)?,
+        ),
+        None => AccountKey::new(
+            account.spend_private_key().as_ref(),
+            account.view_private_key().as_ref(),
+        ),
+    };
+
+    Ok(AddressHashes {
+        default_address_hash: ShortAddressHash::from(&account_key.default_subaddress()),
+        change_address_hash: ShortAddressHash::from(&account_key.change_subaddress()),
     })
 }
-pub fn sync_txos_by_mnemonic(mnemonic: &str, txos: Vec<TxoUnsynced>) -> Result<Vec<TxoSynced>> {
+
+pub fn sync_txos_by_mnemonic(
+    mnemonic: &str,
+    account_index: u32,
+    txos: Vec<TxoUnsynced>,
+) -> Result<Vec<TxoSynced>> {
     let mnemonic = Mnemonic::from_phrase(mnemonic, Language::English)?;
-    sync_txos(mnemonic, txos)
+    sync_txos(mnemonic, account_index, txos)
 }
 
 pub fn sync_txos_by_bip39_entropy(
     bip39_entropy: &str,
+    account_index: u32,
     txos: Vec<TxoUnsynced>,
 ) -> Result<Vec<TxoSynced>> {
     let mut entropy = [0u8; 32];
     hex::decode_to_slice(bip39_entropy, &mut entropy)?;
     let mnemonic = Mnemonic::from_entropy(&entropy, Language::English)?;
-    sync_txos(mnemonic, txos)
+    sync_txos(mnemonic, account_index, txos)
 }
 
-pub fn sync_txos(mnemonic: Mnemonic, txos: Vec<TxoUnsynced>) -> Result<Vec<TxoSynced>> {
-    let account = get_account_from_mnemonic(mnemonic);
+pub fn sync_txos(
+    mnemonic: Mnemonic,
+    account_index: u32,
+    txos: Vec<TxoUnsynced>,
+) -> Result<Vec<TxoSynced>> {
+    let account = get_account_from_mnemonic(mnemonic, account_index);
 
     let mut synced: Vec<TxoSynced> = Vec::new();
     for TxoUnsynced {
@@ -82,24 +153,35 @@ pub fn sync_txos(mnemonic: Mnemonic, txos: Vec<TxoUnsynced>) -> Result<Vec<TxoSy
 
 pub fn sign_tx_with_mnemonic(
     mnemonic: &str,
+    account_index: u32,
     unsigned_tx_proposal: UnsignedTxProposal,
 ) -> Result<TxProposal> {
     let mnemonic = Mnemonic::from_phrase(mnemonic, Language::English)?;
-    sign_tx(mnemonic, unsigned_tx_proposal)
+    sign_tx(mnemonic, account_index, unsigned_tx_proposal)
 }
 
 pub fn sign_tx_with_bip39_entropy(
     bip39_entropy: &str,
+    account_index: u32,
     unsigned_tx_proposal: UnsignedTxProposal,
 ) -> Result<TxProposal> {
     let mut entropy = [0u8; 32];
     hex::decode_to_slice(bip39_entropy, &mut entropy)?;
     let mnemonic = Mnemonic::from_entropy(&entropy, Language::English)?;
-    sign_tx(mnemonic, unsigned_tx_proposal)
+    sign_tx(mnemonic, account_index, unsigned_tx_proposal)
 }
 
-pub fn sign_tx(mnemonic: Mnemonic, unsigned_tx_proposal: UnsignedTxProposal) -> Result<TxProposal> {
-    let account = get_account_from_mnemonic(mnemonic);
+/// Sign an unsigned tx proposal with the account derived from a mnemonic.
+///
+/// The outputs of the proposal, including the encrypted fog hints of outputs
+/// to fog recipients, were already built by full-service using the fog reports
+/// it fetched, so signing only needs the proposal itself and works offline.
+pub fn sign_tx(
+    mnemonic: Mnemonic,
+    account_index: u32,
+    unsigned_tx_proposal: UnsignedTxProposal,
+) -> Result<TxProposal> {
+    let account = get_account_from_mnemonic(mnemonic, account_index);
     let account_key = AccountKey::new(
         account.spend_private_key().as_ref(),
         account.view_private_key().as_ref(),
@@ -110,7 +192,9 @@ pub fn sign_tx(mnemonic: Mnemonic, unsigned_tx_proposal: UnsignedTxProposal) ->
         .map_err(|e| anyhow!(e))
 }
 
-fn get_account_from_mnemonic(mnemonic: Mnemonic) -> Account {
-    let slip_10_key = mnemonic.derive_slip10_key(0);
+/// Derive the account at the given SLIP-0010 account index from a mnemonic.
+/// Index 0 is the account used by default.
+fn get_account_from_mnemonic(mnemonic: Mnemonic, account_index: u32) -> Account {
+    let slip_10_key = mnemonic.derive_slip10_key(account_index);
     Account::from(&slip_10_key)
 }